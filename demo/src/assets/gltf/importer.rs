@@ -176,7 +176,7 @@ impl Importer for GltfImporter {
     where
         Self: Sized,
     {
-        27
+        28
     }
 
     fn version(&self) -> u32 {
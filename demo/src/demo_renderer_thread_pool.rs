@@ -13,6 +13,7 @@ use std::sync::Arc;
 pub struct DemoRendererThreadPool {
     task_pool: TaskPool,
     feature_parallelism: Arc<HashMap<RenderFeatureIndex, ParallelChunkSizes>>,
+    write_chunk_size: usize,
 }
 
 impl DemoRendererThreadPool {
@@ -38,6 +39,7 @@ impl DemoRendererThreadPool {
         Self {
             task_pool: TaskPoolBuilder::new().build(),
             feature_parallelism: Arc::new(feature_parallelism),
+            write_chunk_size: 1024,
         }
     }
 
@@ -344,11 +346,44 @@ impl RendererThreadPool for DemoRendererThreadPool {
             .collect()
     }
 
+    fn write_thread_pool(&self) -> Option<&dyn RenderJobWriteThreadPool> {
+        Some(self)
+    }
+
     fn clone_to_box(&mut self) -> Box<dyn RendererThreadPool> {
         Box::new(self.clone())
     }
 }
 
+impl RenderJobWriteThreadPool for DemoRendererThreadPool {
+    fn write_chunk_size(
+        &self,
+        _view_phase: &ViewPhase,
+        num_submit_nodes: usize,
+    ) -> Option<usize> {
+        // Only split view phases that are large enough to make up for the extra command buffers
+        if num_submit_nodes > self.write_chunk_size {
+            Some(self.write_chunk_size)
+        } else {
+            None
+        }
+    }
+
+    fn run_write_chunks(
+        &self,
+        num_chunks: usize,
+        write_chunk: &(dyn Fn(usize) + Sync),
+    ) {
+        self.task_pool.scope(|scope| {
+            for chunk_index in 0..num_chunks {
+                scope.spawn(async move {
+                    write_chunk(chunk_index);
+                });
+            }
+        });
+    }
+}
+
 #[derive(Default)]
 struct ParallelChunkSizes {
     extract_chunk_size: Option<usize>,
@@ -22,6 +22,12 @@ use rafx::renderer::{
 use raw_window_handle::HasRawWindowHandle;
 use std::sync::Arc;
 
+// Pipelines used in previous runs are saved here so that they don't need to be compiled again
+#[cfg(not(target_arch = "wasm32"))]
+fn pipeline_cache_path() -> std::path::PathBuf {
+    std::env::temp_dir().join("rafx_demo_pipeline_cache.bin")
+}
+
 pub fn rendering_init(
     resources: &mut Resources,
     asset_source: AssetSource,
@@ -100,6 +106,18 @@ pub fn rendering_init(
         )
     }?;
 
+    // A missing or stale pipeline cache just means pipelines are compiled when first used
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if let Err(e) = renderer_builder_result
+            .asset_manager
+            .resource_manager()
+            .load_pipeline_cache(&pipeline_cache_path())
+        {
+            log::warn!("Failed to load pipeline cache: {:?}", e);
+        }
+    }
+
     let swapchain_helper = SwapchainHandler::create_swapchain(
         &mut renderer_builder_result.asset_manager,
         &mut renderer_builder_result.renderer,
@@ -134,6 +152,16 @@ pub fn rendering_destroy(resources: &mut Resources) -> RafxResult<()> {
             let mut asset_manager = resources.get_mut::<AssetManager>().unwrap();
             let renderer = resources.get::<Renderer>().unwrap();
             SwapchainHandler::destroy_swapchain(swapchain_helper, &mut *asset_manager, &*renderer)?;
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if let Err(e) = asset_manager
+                    .resource_manager()
+                    .save_pipeline_cache(&pipeline_cache_path())
+                {
+                    log::warn!("Failed to save pipeline cache: {:?}", e);
+                }
+            }
         }
 
         resources.remove::<Renderer>();
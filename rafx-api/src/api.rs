@@ -7,7 +7,7 @@
         feature = "rafx-gles3"
     ))
 ))]
-use crate::empty::RafxApiEmpty;
+use crate::empty::{RafxApiDefEmpty, RafxApiEmpty};
 #[cfg(feature = "rafx-gles2")]
 use crate::gles2::{RafxApiDefGles2, RafxApiGles2};
 #[cfg(feature = "rafx-gles3")]
@@ -84,9 +84,61 @@ impl RafxApi {
             return RafxApi::new_gles2(_window, _api_def, &Default::default());
         }
 
+        #[cfg(any(
+            feature = "rafx-empty",
+            not(any(
+                feature = "rafx-metal",
+                feature = "rafx-vulkan",
+                feature = "rafx-gles2",
+                feature = "rafx-gles3"
+            ))
+        ))]
+        {
+            return RafxApi::new_empty(_api_def, &Default::default());
+        }
+
         return Err("Rafx was compiled with no backend feature flag. Use on of the following features: rafx-metal, rafx-vulkan, rafx-gles2")?;
     }
 
+    /// Create a device using the "default" backend for the platform without a window. Swapchains
+    /// are not supported, but `RafxTexture`s may be used as render targets. This is intended for
+    /// offline rendering, compute-only tools, and automated tests.
+    ///
+    /// GL backends require a window to initialize and are not supported.
+    ///
+    /// # Safety
+    ///
+    /// GPU programming is fundamentally unsafe, so all rafx APIs that interact with the GPU should
+    /// be considered unsafe. However, rafx APIs are only gated by unsafe if they can cause undefined
+    /// behavior on the CPU for reasons other than interacting with the GPU.
+    #[allow(unreachable_code)]
+    pub unsafe fn new_headless(_api_def: &RafxApiDef) -> RafxResult<Self> {
+        #[cfg(feature = "rafx-metal")]
+        {
+            return RafxApi::new_metal_headless(_api_def, &Default::default());
+        }
+
+        #[cfg(feature = "rafx-vulkan")]
+        {
+            return RafxApi::new_vulkan_headless(_api_def, &Default::default());
+        }
+
+        #[cfg(any(
+            feature = "rafx-empty",
+            not(any(
+                feature = "rafx-metal",
+                feature = "rafx-vulkan",
+                feature = "rafx-gles2",
+                feature = "rafx-gles3"
+            ))
+        ))]
+        {
+            return RafxApi::new_empty(_api_def, &Default::default());
+        }
+
+        return Err("Rafx was compiled with no backend feature flag that supports headless devices. Use one of the following features: rafx-metal, rafx-vulkan")?;
+    }
+
     /// Initialize a device using vulkan
     ///
     /// # Safety
@@ -105,6 +157,24 @@ impl RafxApi {
         )?))
     }
 
+    /// Initialize a headless device using vulkan. Surface and swapchain extensions are not
+    /// enabled.
+    ///
+    /// # Safety
+    ///
+    /// GPU programming is fundamentally unsafe, so all rafx APIs that interact with the GPU should
+    /// be considered unsafe. However, rafx APIs are only gated by unsafe if they can cause undefined
+    /// behavior on the CPU for reasons other than interacting with the GPU.
+    #[cfg(feature = "rafx-vulkan")]
+    pub unsafe fn new_vulkan_headless(
+        api_def: &RafxApiDef,
+        vk_api_def: &RafxApiDefVulkan,
+    ) -> RafxResult<Self> {
+        Ok(RafxApi::Vk(RafxApiVulkan::new_headless(
+            api_def, vk_api_def,
+        )?))
+    }
+
     /// Initialize a device using vulkan
     ///
     /// # Safety
@@ -123,6 +193,24 @@ impl RafxApi {
         )?))
     }
 
+    /// Initialize a headless device using metal
+    ///
+    /// # Safety
+    ///
+    /// GPU programming is fundamentally unsafe, so all rafx APIs that interact with the GPU should
+    /// be considered unsafe. However, rafx APIs are only gated by unsafe if they can cause undefined
+    /// behavior on the CPU for reasons other than interacting with the GPU.
+    #[cfg(feature = "rafx-metal")]
+    pub unsafe fn new_metal_headless(
+        api_def: &RafxApiDef,
+        metal_api_def: &RafxApiDefMetal,
+    ) -> RafxResult<Self> {
+        Ok(RafxApi::Metal(RafxApiMetal::new_headless(
+            api_def,
+            metal_api_def,
+        )?))
+    }
+
     /// Initialize a device using OpenGL ES 2.0
     #[cfg(feature = "rafx-gles2")]
     pub fn new_gles2(
@@ -147,6 +235,26 @@ impl RafxApi {
         )?))
     }
 
+    /// Initialize a device using the empty backend. This backend does not use a GPU. Buffers and
+    /// textures are stored in host memory, copies are executed when command buffers are submitted,
+    /// and all other commands are recorded so that they can be inspected. This is intended for
+    /// testing code built on rafx on machines without a GPU.
+    #[cfg(any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    ))]
+    pub fn new_empty(
+        api_def: &RafxApiDef,
+        empty_api_def: &RafxApiDefEmpty,
+    ) -> RafxResult<Self> {
+        Ok(RafxApi::Empty(RafxApiEmpty::new(api_def, empty_api_def)?))
+    }
+
     /// Create a cloneable handle to the device. Most of the interaction with the graphics backend
     /// is done through this handle.
     ///
@@ -0,0 +1,58 @@
+use crate::{RafxApiDef, RafxResult};
+use std::sync::Arc;
+
+use crate::empty::{RafxDeviceContextEmpty, RafxDeviceContextEmptyInner};
+
+/// Empty-specific configuration
+#[derive(Default)]
+pub struct RafxApiDefEmpty {}
+
+pub struct RafxApiEmpty {
+    device_context: Option<RafxDeviceContextEmpty>,
+}
+
+impl Drop for RafxApiEmpty {
+    fn drop(&mut self) {
+        self.destroy().unwrap();
+    }
+}
+
+impl RafxApiEmpty {
+    pub fn device_context(&self) -> &RafxDeviceContextEmpty {
+        self.device_context.as_ref().unwrap()
+    }
+
+    pub fn new(
+        _api_def: &RafxApiDef,
+        _empty_api_def: &RafxApiDefEmpty,
+    ) -> RafxResult<Self> {
+        let inner = Arc::new(RafxDeviceContextEmptyInner::new()?);
+        let device_context = RafxDeviceContextEmpty::new(inner)?;
+
+        Ok(RafxApiEmpty {
+            device_context: Some(device_context),
+        })
+    }
+
+    pub fn destroy(&mut self) -> RafxResult<()> {
+        if let Some(device_context) = self.device_context.take() {
+            let inner = device_context.inner.clone();
+
+            // This should be the final device context
+            std::mem::drop(device_context);
+
+            let _strong_count = Arc::strong_count(&inner);
+            match Arc::try_unwrap(inner) {
+                Ok(inner) => std::mem::drop(inner),
+                Err(_arc) => {
+                    Err(format!(
+                        "Could not destroy device, {} references to it exist",
+                        _strong_count
+                    ))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
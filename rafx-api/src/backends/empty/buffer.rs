@@ -0,0 +1,190 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxBufferDef, RafxResult};
+
+use rafx_base::trust_cell::TrustCell;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+// Host memory shared between a buffer and any command buffers that reference it. Copies recorded
+// into a command buffer hold a clone of this so they can execute on submit.
+#[derive(Debug)]
+pub(crate) struct EmptyBufferContentsInner {
+    data: TrustCell<Box<[u8]>>,
+    resource_id: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct EmptyBufferContents {
+    inner: Arc<EmptyBufferContentsInner>,
+}
+
+impl EmptyBufferContents {
+    pub fn new(
+        size: u64,
+        resource_id: u64,
+    ) -> Self {
+        let inner = EmptyBufferContentsInner {
+            data: TrustCell::new(vec![0_u8; size as usize].into_boxed_slice()),
+            resource_id,
+        };
+
+        EmptyBufferContents {
+            inner: Arc::new(inner),
+        }
+    }
+
+    pub fn resource_id(&self) -> u64 {
+        self.inner.resource_id
+    }
+
+    pub fn size(&self) -> u64 {
+        self.inner.data.borrow().len() as u64
+    }
+
+    pub unsafe fn as_mut_ptr(&self) -> *mut u8 {
+        self.inner.data.borrow_mut().as_mut_ptr()
+    }
+
+    pub fn read(
+        &self,
+        offset: u64,
+        dst: &mut [u8],
+    ) {
+        let data = self.inner.data.borrow();
+        dst.copy_from_slice(&data.value[offset as usize..offset as usize + dst.len()]);
+    }
+
+    pub fn write(
+        &self,
+        offset: u64,
+        src: &[u8],
+    ) {
+        let mut data = self.inner.data.borrow_mut();
+        data[offset as usize..offset as usize + src.len()].copy_from_slice(src);
+    }
+}
+
+/// A buffer backed by host memory. All memory usages can be mapped, including `GpuOnly`.
+#[derive(Debug)]
+pub struct RafxBufferEmpty {
+    device_context: RafxDeviceContextEmpty,
+    buffer_def: RafxBufferDef,
+    buffer_contents: EmptyBufferContents,
+    mapped_count: AtomicU32,
+    debug_name: Mutex<Option<String>>,
+}
+
+impl Drop for RafxBufferEmpty {
+    fn drop(&mut self) {
+        self.device_context
+            .remove_allocated_memory(self.buffer_contents.size());
+    }
+}
+
+impl RafxBufferEmpty {
+    pub fn buffer_def(&self) -> &RafxBufferDef {
+        &self.buffer_def
+    }
+
+    /// The size of the host memory backing this buffer
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.buffer_contents.size()
+    }
+
+    /// A unique ID used to identify this buffer in the command log
+    pub fn resource_id(&self) -> u64 {
+        self.buffer_contents.resource_id()
+    }
+
+    pub(crate) fn buffer_contents(&self) -> &EmptyBufferContents {
+        &self.buffer_contents
+    }
+
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        *self.debug_name.lock().unwrap() = Some(name.as_ref().to_string());
+        Ok(())
+    }
+
+    /// The name most recently assigned with `set_debug_name`
+    pub fn debug_name(&self) -> Option<String> {
+        self.debug_name.lock().unwrap().clone()
+    }
+
+    /// Copies the current contents of the buffer. Intended for inspecting results in tests.
+    pub fn read_contents(&self) -> Vec<u8> {
+        let mut data = vec![0_u8; self.buffer_contents.size() as usize];
+        self.buffer_contents.read(0, &mut data);
+        data
+    }
+
+    pub fn map_buffer(&self) -> RafxResult<*mut u8> {
+        self.mapped_count.fetch_add(1, Ordering::Acquire);
+        unsafe { Ok(self.buffer_contents.as_mut_ptr()) }
+    }
+
+    pub fn unmap_buffer(&self) -> RafxResult<()> {
+        self.mapped_count.fetch_sub(1, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn mapped_memory(&self) -> Option<*mut u8> {
+        if self.mapped_count.load(Ordering::Relaxed) > 0 {
+            unsafe { Some(self.buffer_contents.as_mut_ptr()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn copy_to_host_visible_buffer<T: Copy>(
+        &self,
+        data: &[T],
+    ) -> RafxResult<()> {
+        self.copy_to_host_visible_buffer_with_offset(data, 0)
+    }
+
+    pub fn copy_to_host_visible_buffer_with_offset<T: Copy>(
+        &self,
+        data: &[T],
+        buffer_byte_offset: u64,
+    ) -> RafxResult<()> {
+        let data_size_in_bytes = rafx_base::memory::slice_size_in_bytes(data) as u64;
+        assert!(buffer_byte_offset + data_size_in_bytes <= self.buffer_def.size);
+
+        let src = data.as_ptr() as *const u8;
+
+        let required_alignment = std::mem::align_of::<T>();
+
+        unsafe {
+            let dst = self.map_buffer()?.add(buffer_byte_offset as usize);
+            assert_eq!(((dst as usize) % required_alignment), 0);
+            std::ptr::copy_nonoverlapping(src, dst, data_size_in_bytes as usize);
+        }
+
+        self.unmap_buffer()?;
+
+        Ok(())
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        buffer_def: &RafxBufferDef,
+    ) -> RafxResult<Self> {
+        buffer_def.verify();
+
+        let buffer_contents =
+            EmptyBufferContents::new(buffer_def.size, device_context.allocate_resource_id());
+        device_context.add_allocated_memory(buffer_contents.size());
+
+        Ok(RafxBufferEmpty {
+            device_context: device_context.clone(),
+            buffer_def: buffer_def.clone(),
+            buffer_contents,
+            mapped_count: AtomicU32::new(0),
+            debug_name: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,1874 @@
+use crate::empty::{
+    EmptyBufferContents, EmptyTextureContents, RafxBufferEmpty, RafxCommandPoolEmpty,
+    RafxDescriptorSetArrayEmpty, RafxDescriptorSetHandleEmpty, RafxPipelineEmpty,
+    RafxQueryPoolEmpty, RafxRootSignatureEmpty, RafxSubresourceLayoutEmpty, RafxTextureEmpty,
+};
+use crate::{
+    RafxBufferBarrier, RafxCmdBlitParams, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorRenderTargetBinding, RafxCommandBufferDef,
+    RafxDepthStencilRenderTargetBinding, RafxDispatchIndirectCommand,
+    RafxDrawIndexedIndirectCommand, RafxDrawIndirectCommand, RafxIndexBufferBinding, RafxIndexType,
+    RafxPipelineType, RafxPushConstantIndex, RafxQueryType, RafxQueueType, RafxResourceType,
+    RafxResult, RafxShaderStageFlags, RafxSubpassContents, RafxSubpassDef,
+    RafxSubpassInputAttachment, RafxTextureBarrier, RafxVertexBufferBinding,
+    RAFX_PIPELINE_STATISTICS_VALUE_COUNT,
+};
+use std::sync::Mutex;
+
+/// A command recorded by `RafxCommandBufferEmpty`. Buffers and textures are identified by their
+/// resource ID. Submitted commands can be inspected via `RafxQueueEmpty::submitted_commands()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RafxCommandEmpty {
+    BeginRenderPass {
+        color_targets: Vec<u64>,
+        depth_target: Option<u64>,
+        subpasses: Vec<RafxSubpassDef>,
+        contents: RafxSubpassContents,
+    },
+    NextSubpass,
+    EndRenderPass,
+    /// The commands of each executed secondary command buffer, in order
+    ExecuteSecondaryCommandBuffers {
+        command_buffers: Vec<Vec<RafxCommandEmpty>>,
+    },
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        depth_min: f32,
+        depth_max: f32,
+    },
+    SetScissor {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    SetStencilReferenceValue {
+        value: u32,
+    },
+    BindPipeline {
+        pipeline_type: RafxPipelineType,
+    },
+    BindVertexBuffers {
+        first_binding: u32,
+        buffers: Vec<(u64, u64)>,
+    },
+    BindIndexBuffer {
+        buffer: u64,
+        byte_offset: u64,
+        index_type: RafxIndexType,
+    },
+    BindDescriptorSet {
+        set_index: u32,
+        array_index: u32,
+    },
+    PushConstants {
+        push_constant_index: RafxPushConstantIndex,
+        stages: RafxShaderStageFlags,
+        data: Vec<u8>,
+    },
+    Draw {
+        vertex_count: u32,
+        first_vertex: u32,
+        instance_count: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        first_index: u32,
+        instance_count: u32,
+        first_instance: u32,
+        vertex_offset: i32,
+    },
+    Dispatch {
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    },
+    DrawIndirect {
+        indirect_buffer: u64,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    },
+    DrawIndexedIndirect {
+        indirect_buffer: u64,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    },
+    DrawIndirectCount {
+        indirect_buffer: u64,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: u64,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    },
+    DrawIndexedIndirectCount {
+        indirect_buffer: u64,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: u64,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    },
+    DispatchIndirect {
+        indirect_buffer: u64,
+        indirect_buffer_offset_in_bytes: u64,
+    },
+    ResourceBarrier {
+        buffers: Vec<u64>,
+        textures: Vec<u64>,
+    },
+    CopyBufferToBuffer {
+        src_buffer: u64,
+        dst_buffer: u64,
+        src_offset: u64,
+        dst_offset: u64,
+        size: u64,
+    },
+    CopyBufferToTexture {
+        src_buffer: u64,
+        dst_texture: u64,
+        buffer_offset: u64,
+        array_layer: u16,
+        mip_level: u8,
+    },
+    CopyTextureToBuffer {
+        src_texture: u64,
+        dst_buffer: u64,
+        buffer_offset: u64,
+        array_layer: u16,
+        mip_level: u8,
+    },
+    BlitImage {
+        src_texture: u64,
+        dst_texture: u64,
+        src_mip_level: u8,
+        dst_mip_level: u8,
+    },
+    GenerateMipmaps {
+        texture: u64,
+    },
+    ResetQueryPool {
+        query_pool: u64,
+        first_query: u32,
+        query_count: u32,
+    },
+    WriteTimestamp {
+        query_pool: u64,
+        query_index: u32,
+    },
+    BeginQuery {
+        query_pool: u64,
+        query_index: u32,
+    },
+    EndQuery {
+        query_pool: u64,
+        query_index: u32,
+    },
+    ResolveQueries {
+        query_pool: u64,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: u64,
+        dst_offset: u64,
+    },
+    PushDebugGroup {
+        name: String,
+    },
+    PopDebugGroup,
+    InsertDebugMarker {
+        name: String,
+    },
+}
+
+// Work that modifies host memory. It is deferred until submit so that the ordering of copies
+// relative to writes made through mapped buffers matches the other backends.
+#[derive(Clone, Debug)]
+enum EmptyCommandOperation {
+    CopyBufferToBuffer {
+        src: EmptyBufferContents,
+        dst: EmptyBufferContents,
+        src_offset: u64,
+        dst_offset: u64,
+        size: u64,
+    },
+    CopyBufferToTexture {
+        src: EmptyBufferContents,
+        dst: EmptyTextureContents,
+        buffer_offset: u64,
+        layout: RafxSubresourceLayoutEmpty,
+    },
+    CopyTextureToBuffer {
+        src: EmptyTextureContents,
+        dst: EmptyBufferContents,
+        buffer_offset: u64,
+        layout: RafxSubresourceLayoutEmpty,
+        row_pitch: u64,
+    },
+    ResetQueries {
+        query_pool: RafxQueryPoolEmpty,
+        first_query: u32,
+        query_count: u32,
+    },
+    WriteTimestamp {
+        query_pool: RafxQueryPoolEmpty,
+        query_index: u32,
+    },
+    WriteQueryResult {
+        query_pool: RafxQueryPoolEmpty,
+        query_index: u32,
+        values: Vec<u64>,
+    },
+    ResolveQueries {
+        query_pool: RafxQueryPoolEmpty,
+        first_query: u32,
+        query_count: u32,
+        dst: EmptyBufferContents,
+        dst_offset: u64,
+    },
+    ExecuteSecondaryCommandBuffers {
+        operations: Vec<EmptyCommandOperation>,
+    },
+}
+
+impl EmptyCommandOperation {
+    fn execute(&self) {
+        match self {
+            EmptyCommandOperation::CopyBufferToBuffer {
+                src,
+                dst,
+                src_offset,
+                dst_offset,
+                size,
+            } => {
+                let mut data = vec![0_u8; *size as usize];
+                src.read(*src_offset, &mut data);
+                dst.write(*dst_offset, &data);
+            }
+            EmptyCommandOperation::CopyBufferToTexture {
+                src,
+                dst,
+                buffer_offset,
+                layout,
+            } => {
+                let mut data = vec![0_u8; layout.size as usize];
+                src.read(*buffer_offset, &mut data);
+                dst.write(layout.offset, &data);
+            }
+            EmptyCommandOperation::CopyTextureToBuffer {
+                src,
+                dst,
+                buffer_offset,
+                layout,
+                row_pitch,
+            } => {
+                // Texture rows are tightly packed but the buffer rows may be padded
+                let mut row = vec![0_u8; layout.row_pitch as usize];
+                let row_count = layout.row_count as u64 * layout.depth as u64;
+                for i in 0..row_count {
+                    src.read(layout.offset + i * layout.row_pitch, &mut row);
+                    dst.write(buffer_offset + i * row_pitch, &row);
+                }
+            }
+            EmptyCommandOperation::ResetQueries {
+                query_pool,
+                first_query,
+                query_count,
+            } => query_pool.reset(*first_query, *query_count),
+            EmptyCommandOperation::WriteTimestamp {
+                query_pool,
+                query_index,
+            } => {
+                let timestamp = query_pool.device_context().current_timestamp();
+                query_pool.write(*query_index, &[timestamp]);
+            }
+            EmptyCommandOperation::WriteQueryResult {
+                query_pool,
+                query_index,
+                values,
+            } => query_pool.write(*query_index, values),
+            EmptyCommandOperation::ResolveQueries {
+                query_pool,
+                first_query,
+                query_count,
+                dst,
+                dst_offset,
+            } => {
+                let data: Vec<u8> = query_pool
+                    .read(*first_query, *query_count)
+                    .iter()
+                    .flat_map(|x| x.to_ne_bytes().to_vec())
+                    .collect();
+                dst.write(*dst_offset, &data);
+            }
+            EmptyCommandOperation::ExecuteSecondaryCommandBuffers { operations } => {
+                for operation in operations {
+                    operation.execute();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EmptyRecordedCommand {
+    command: RafxCommandEmpty,
+    operation: Option<EmptyCommandOperation>,
+}
+
+#[derive(Debug)]
+pub struct RafxCommandBufferEmpty {
+    queue_type: RafxQueueType,
+    is_secondary: bool,
+    recorded_commands: Mutex<Vec<EmptyRecordedCommand>>,
+}
+
+impl RafxCommandBufferEmpty {
+    pub fn queue_type(&self) -> RafxQueueType {
+        self.queue_type
+    }
+
+    /// Returns the commands recorded since the last call to `begin()`
+    pub fn recorded_commands(&self) -> Vec<RafxCommandEmpty> {
+        self.recorded_commands
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| x.command.clone())
+            .collect()
+    }
+
+    pub fn new(
+        command_pool: &RafxCommandPoolEmpty,
+        command_buffer_def: &RafxCommandBufferDef,
+    ) -> RafxResult<RafxCommandBufferEmpty> {
+        Ok(RafxCommandBufferEmpty {
+            queue_type: command_pool.queue_type(),
+            is_secondary: command_buffer_def.is_secondary,
+            recorded_commands: Default::default(),
+        })
+    }
+
+    // Called by the queue on submit. Executes any copies and returns the recorded commands
+    pub(crate) fn execute(&self) -> Vec<RafxCommandEmpty> {
+        let recorded_commands = self.recorded_commands.lock().unwrap();
+        let mut commands = Vec::with_capacity(recorded_commands.len());
+        for recorded_command in &*recorded_commands {
+            if let Some(operation) = &recorded_command.operation {
+                operation.execute();
+            }
+
+            commands.push(recorded_command.command.clone());
+        }
+
+        commands
+    }
+
+    fn record(
+        &self,
+        command: RafxCommandEmpty,
+    ) -> RafxResult<()> {
+        self.record_with_operation(command, None)
+    }
+
+    fn record_with_operation(
+        &self,
+        command: RafxCommandEmpty,
+        operation: Option<EmptyCommandOperation>,
+    ) -> RafxResult<()> {
+        self.recorded_commands
+            .lock()
+            .unwrap()
+            .push(EmptyRecordedCommand { command, operation });
+        Ok(())
+    }
+
+    // Returns the contents of the subpass being recorded, or None if not within a renderpass
+    fn current_subpass_contents(&self) -> Option<RafxSubpassContents> {
+        for recorded in self.recorded_commands.lock().unwrap().iter().rev() {
+            match &recorded.command {
+                RafxCommandEmpty::BeginRenderPass { contents, .. } => return Some(*contents),
+                RafxCommandEmpty::EndRenderPass => return None,
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    pub fn begin(&self) -> RafxResult<()> {
+        if self.is_secondary {
+            Err("Secondary command buffers must be started with begin_secondary")?;
+        }
+
+        self.recorded_commands.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn begin_secondary(
+        &self,
+        primary_command_buffer: &RafxCommandBufferEmpty,
+    ) -> RafxResult<()> {
+        if !self.is_secondary || primary_command_buffer.is_secondary {
+            Err("begin_secondary must be called on a secondary command buffer with a primary command buffer")?;
+        }
+
+        self.recorded_commands.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn end(&self) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn return_to_pool(&self) -> RafxResult<()> {
+        // Returning to pool means the command buffer is no longer needed
+        self.recorded_commands.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn cmd_begin_render_pass(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+    ) -> RafxResult<()> {
+        self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, &[])
+    }
+
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        self.cmd_begin_render_pass_with_contents(
+            color_targets,
+            depth_target,
+            subpasses,
+            RafxSubpassContents::Inline,
+        )
+    }
+
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
+    ) -> RafxResult<()> {
+        if color_targets.is_empty() && depth_target.is_none() {
+            Err("No color or depth target supplied to cmd_begin_render_pass")?;
+        }
+
+        if !subpasses.is_empty() {
+            if color_targets.iter().any(|x| x.resolve_target.is_some()) {
+                Err("Resolve targets are not supported in renderpasses with multiple subpasses")?;
+            }
+
+            for subpass in subpasses {
+                let is_valid_color_attachment =
+                    |index: &u32| (*index as usize) < color_targets.len();
+                let is_valid_input_attachment = |input: &RafxSubpassInputAttachment| match input {
+                    RafxSubpassInputAttachment::Color(index) => is_valid_color_attachment(index),
+                    RafxSubpassInputAttachment::DepthStencil => depth_target.is_some(),
+                };
+
+                if !subpass
+                    .color_attachments
+                    .iter()
+                    .all(is_valid_color_attachment)
+                    || !subpass
+                        .input_attachments
+                        .iter()
+                        .all(is_valid_input_attachment)
+                    || (subpass.use_depth_stencil_attachment && depth_target.is_none())
+                {
+                    Err(format!(
+                        "Subpass {:?} references an attachment that was not supplied to cmd_begin_render_pass_with_subpasses",
+                        subpass
+                    ))?;
+                }
+            }
+        }
+
+        let color_targets = color_targets
+            .iter()
+            .map(|x| x.texture.empty_texture().unwrap().resource_id())
+            .collect();
+        let depth_target = depth_target.map(|x| x.texture.empty_texture().unwrap().resource_id());
+
+        self.record(RafxCommandEmpty::BeginRenderPass {
+            color_targets,
+            depth_target,
+            subpasses: subpasses.to_vec(),
+            contents,
+        })
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        // Find the renderpass being recorded and how many subpasses have already been started
+        let mut next_subpass_count = 0;
+        let mut subpass_count = None;
+        for recorded in self.recorded_commands.lock().unwrap().iter().rev() {
+            match &recorded.command {
+                RafxCommandEmpty::NextSubpass => next_subpass_count += 1,
+                RafxCommandEmpty::BeginRenderPass { subpasses, .. } => {
+                    subpass_count = Some(subpasses.len().max(1));
+                    break;
+                }
+                RafxCommandEmpty::EndRenderPass => break,
+                _ => {}
+            }
+        }
+
+        match subpass_count {
+            Some(subpass_count) if next_subpass_count + 1 < subpass_count => {}
+            Some(_) => Err("cmd_next_subpass was called on the last subpass of the renderpass")?,
+            None => Err("cmd_next_subpass must be called within a renderpass")?,
+        }
+
+        self.record(RafxCommandEmpty::NextSubpass)
+    }
+
+    pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::EndRenderPass)
+    }
+
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        command_buffers: &[&RafxCommandBufferEmpty],
+    ) -> RafxResult<()> {
+        if self.is_secondary || command_buffers.iter().any(|x| !x.is_secondary) {
+            Err("cmd_execute_secondary_command_buffers must be called on a primary command buffer with secondary command buffers")?;
+        }
+
+        if self.current_subpass_contents() == Some(RafxSubpassContents::Inline) {
+            Err("cmd_execute_secondary_command_buffers was called within a subpass that does not use RafxSubpassContents::SecondaryCommandBuffers")?;
+        }
+
+        // Copy the operations so that they run when this command buffer is submitted
+        let mut commands = Vec::with_capacity(command_buffers.len());
+        let mut operations = Vec::default();
+        for command_buffer in command_buffers {
+            let recorded_commands = command_buffer.recorded_commands.lock().unwrap();
+            commands.push(
+                recorded_commands
+                    .iter()
+                    .map(|x| x.command.clone())
+                    .collect(),
+            );
+            operations.extend(recorded_commands.iter().filter_map(|x| x.operation.clone()));
+        }
+
+        self.record_with_operation(
+            RafxCommandEmpty::ExecuteSecondaryCommandBuffers {
+                command_buffers: commands,
+            },
+            Some(EmptyCommandOperation::ExecuteSecondaryCommandBuffers { operations }),
+        )
+    }
+
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::PushDebugGroup {
+            name: name.to_string(),
+        })
+    }
+
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::PopDebugGroup)
+    }
+
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::InsertDebugMarker {
+            name: name.to_string(),
+        })
+    }
+
+    pub fn cmd_set_viewport(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        depth_min: f32,
+        depth_max: f32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::SetViewport {
+            x,
+            y,
+            width,
+            height,
+            depth_min,
+            depth_max,
+        })
+    }
+
+    pub fn cmd_set_scissor(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::SetScissor {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    pub fn cmd_set_stencil_reference_value(
+        &self,
+        value: u32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::SetStencilReferenceValue { value })
+    }
+
+    pub fn cmd_bind_pipeline(
+        &self,
+        pipeline: &RafxPipelineEmpty,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::BindPipeline {
+            pipeline_type: pipeline.pipeline_type(),
+        })
+    }
+
+    pub fn cmd_bind_vertex_buffers(
+        &self,
+        first_binding: u32,
+        bindings: &[RafxVertexBufferBinding],
+    ) -> RafxResult<()> {
+        let buffers = bindings
+            .iter()
+            .map(|x| {
+                (
+                    x.buffer.empty_buffer().unwrap().resource_id(),
+                    x.byte_offset,
+                )
+            })
+            .collect();
+
+        self.record(RafxCommandEmpty::BindVertexBuffers {
+            first_binding,
+            buffers,
+        })
+    }
+
+    pub fn cmd_bind_index_buffer(
+        &self,
+        binding: &RafxIndexBufferBinding,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::BindIndexBuffer {
+            buffer: binding.buffer.empty_buffer().unwrap().resource_id(),
+            byte_offset: binding.byte_offset,
+            index_type: binding.index_type,
+        })
+    }
+
+    pub fn cmd_bind_descriptor_set(
+        &self,
+        descriptor_set_array: &RafxDescriptorSetArrayEmpty,
+        index: u32,
+    ) -> RafxResult<()> {
+        if index >= descriptor_set_array.array_length() {
+            Err(format!(
+                "Descriptor set index {} is out of range (array length is {})",
+                index,
+                descriptor_set_array.array_length()
+            ))?;
+        }
+
+        self.record(RafxCommandEmpty::BindDescriptorSet {
+            set_index: descriptor_set_array.set_index(),
+            array_index: index,
+        })
+    }
+
+    pub fn cmd_bind_descriptor_set_handle(
+        &self,
+        _root_signature: &RafxRootSignatureEmpty,
+        set_index: u32,
+        descriptor_set_handle: &RafxDescriptorSetHandleEmpty,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::BindDescriptorSet {
+            set_index,
+            array_index: descriptor_set_handle.array_index(),
+        })
+    }
+
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignatureEmpty,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        let push_constant = root_signature
+            .push_constant(push_constant_index)
+            .ok_or("Push constant index not found in root signature")?;
+
+        crate::internal_shared::validate_push_constant_data(
+            push_constant.name.as_deref(),
+            push_constant.size_in_bytes,
+            data,
+        )?;
+
+        self.record(RafxCommandEmpty::PushConstants {
+            push_constant_index,
+            stages: push_constant.used_in_shader_stages,
+            data: data.to_vec(),
+        })
+    }
+
+    pub fn cmd_draw(
+        &self,
+        vertex_count: u32,
+        first_vertex: u32,
+    ) -> RafxResult<()> {
+        self.cmd_draw_instanced(vertex_count, first_vertex, 1, 0)
+    }
+
+    pub fn cmd_draw_instanced(
+        &self,
+        vertex_count: u32,
+        first_vertex: u32,
+        instance_count: u32,
+        first_instance: u32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::Draw {
+            vertex_count,
+            first_vertex,
+            instance_count,
+            first_instance,
+        })
+    }
+
+    pub fn cmd_draw_indexed(
+        &self,
+        index_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+    ) -> RafxResult<()> {
+        self.cmd_draw_indexed_instanced(index_count, first_index, 1, 0, vertex_offset)
+    }
+
+    pub fn cmd_draw_indexed_instanced(
+        &self,
+        index_count: u32,
+        first_index: u32,
+        instance_count: u32,
+        first_instance: u32,
+        vertex_offset: i32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::DrawIndexed {
+            index_count,
+            first_index,
+            instance_count,
+            first_instance,
+            vertex_offset,
+        })
+    }
+
+    pub fn cmd_dispatch(
+        &self,
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::Dispatch {
+            group_count_x,
+            group_count_y,
+            group_count_z,
+        })
+    }
+
+    // Checks the same requirements that the vulkan spec places on indirect buffers
+    fn verify_indirect_buffer(
+        buffer: &RafxBufferEmpty,
+        offset_in_bytes: u64,
+        size_in_bytes: u64,
+    ) -> RafxResult<()> {
+        if !buffer
+            .buffer_def()
+            .resource_type
+            .contains(RafxResourceType::INDIRECT_BUFFER)
+        {
+            Err("Buffers used for indirect commands must be created with RafxResourceType::INDIRECT_BUFFER")?;
+        }
+
+        if !offset_in_bytes.is_multiple_of(4) {
+            Err(format!(
+                "Indirect buffer offset {} is not a multiple of 4",
+                offset_in_bytes
+            ))?;
+        }
+
+        if offset_in_bytes + size_in_bytes > buffer.buffer_def().size {
+            Err(format!(
+                "Indirect command of {} bytes at offset {} exceeds the size of the buffer ({} bytes)",
+                size_in_bytes,
+                offset_in_bytes,
+                buffer.buffer_def().size
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indirect(
+        &self,
+        indirect_buffer: &RafxBufferEmpty,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        Self::verify_indirect_buffer(
+            indirect_buffer,
+            indirect_buffer_offset_in_bytes,
+            draw_count as u64 * std::mem::size_of::<RafxDrawIndirectCommand>() as u64,
+        )?;
+
+        self.record(RafxCommandEmpty::DrawIndirect {
+            indirect_buffer: indirect_buffer.resource_id(),
+            indirect_buffer_offset_in_bytes,
+            draw_count,
+        })
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        indirect_buffer: &RafxBufferEmpty,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        Self::verify_indirect_buffer(
+            indirect_buffer,
+            indirect_buffer_offset_in_bytes,
+            draw_count as u64 * std::mem::size_of::<RafxDrawIndexedIndirectCommand>() as u64,
+        )?;
+
+        self.record(RafxCommandEmpty::DrawIndexedIndirect {
+            indirect_buffer: indirect_buffer.resource_id(),
+            indirect_buffer_offset_in_bytes,
+            draw_count,
+        })
+    }
+
+    pub fn cmd_draw_indirect_count(
+        &self,
+        indirect_buffer: &RafxBufferEmpty,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBufferEmpty,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Self::verify_indirect_buffer(
+            indirect_buffer,
+            indirect_buffer_offset_in_bytes,
+            max_draw_count as u64 * std::mem::size_of::<RafxDrawIndirectCommand>() as u64,
+        )?;
+        Self::verify_indirect_buffer(
+            count_buffer,
+            count_buffer_offset_in_bytes,
+            std::mem::size_of::<u32>() as u64,
+        )?;
+
+        self.record(RafxCommandEmpty::DrawIndirectCount {
+            indirect_buffer: indirect_buffer.resource_id(),
+            indirect_buffer_offset_in_bytes,
+            count_buffer: count_buffer.resource_id(),
+            count_buffer_offset_in_bytes,
+            max_draw_count,
+        })
+    }
+
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        indirect_buffer: &RafxBufferEmpty,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBufferEmpty,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Self::verify_indirect_buffer(
+            indirect_buffer,
+            indirect_buffer_offset_in_bytes,
+            max_draw_count as u64 * std::mem::size_of::<RafxDrawIndexedIndirectCommand>() as u64,
+        )?;
+        Self::verify_indirect_buffer(
+            count_buffer,
+            count_buffer_offset_in_bytes,
+            std::mem::size_of::<u32>() as u64,
+        )?;
+
+        self.record(RafxCommandEmpty::DrawIndexedIndirectCount {
+            indirect_buffer: indirect_buffer.resource_id(),
+            indirect_buffer_offset_in_bytes,
+            count_buffer: count_buffer.resource_id(),
+            count_buffer_offset_in_bytes,
+            max_draw_count,
+        })
+    }
+
+    pub fn cmd_dispatch_indirect(
+        &self,
+        indirect_buffer: &RafxBufferEmpty,
+        indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        Self::verify_indirect_buffer(
+            indirect_buffer,
+            indirect_buffer_offset_in_bytes,
+            std::mem::size_of::<RafxDispatchIndirectCommand>() as u64,
+        )?;
+
+        self.record(RafxCommandEmpty::DispatchIndirect {
+            indirect_buffer: indirect_buffer.resource_id(),
+            indirect_buffer_offset_in_bytes,
+        })
+    }
+
+    pub fn cmd_resource_barrier(
+        &self,
+        buffer_barriers: &[RafxBufferBarrier],
+        texture_barriers: &[RafxTextureBarrier],
+    ) -> RafxResult<()> {
+        let buffers = buffer_barriers
+            .iter()
+            .map(|x| x.buffer.empty_buffer().unwrap().resource_id())
+            .collect();
+        let textures = texture_barriers
+            .iter()
+            .map(|x| x.texture.empty_texture().unwrap().resource_id())
+            .collect();
+
+        self.record(RafxCommandEmpty::ResourceBarrier { buffers, textures })
+    }
+
+    pub fn cmd_copy_buffer_to_buffer(
+        &self,
+        src_buffer: &RafxBufferEmpty,
+        dst_buffer: &RafxBufferEmpty,
+        src_offset: u64,
+        dst_offset: u64,
+        size: u64,
+    ) -> RafxResult<()> {
+        if src_offset + size > src_buffer.buffer_def().size {
+            Err(format!(
+                "Copy of {} bytes at offset {} exceeds the size of the source buffer ({} bytes)",
+                size,
+                src_offset,
+                src_buffer.buffer_def().size
+            ))?;
+        }
+
+        if dst_offset + size > dst_buffer.buffer_def().size {
+            Err(format!(
+                "Copy of {} bytes at offset {} exceeds the size of the destination buffer ({} bytes)",
+                size,
+                dst_offset,
+                dst_buffer.buffer_def().size
+            ))?;
+        }
+
+        let command = RafxCommandEmpty::CopyBufferToBuffer {
+            src_buffer: src_buffer.resource_id(),
+            dst_buffer: dst_buffer.resource_id(),
+            src_offset,
+            dst_offset,
+            size,
+        };
+
+        let operation = EmptyCommandOperation::CopyBufferToBuffer {
+            src: src_buffer.buffer_contents().clone(),
+            dst: dst_buffer.buffer_contents().clone(),
+            src_offset,
+            dst_offset,
+            size,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_copy_buffer_to_texture(
+        &self,
+        src_buffer: &RafxBufferEmpty,
+        dst_texture: &RafxTextureEmpty,
+        params: &RafxCmdCopyBufferToTextureParams,
+    ) -> RafxResult<()> {
+        let layout = dst_texture
+            .subresource_layout(params.array_layer as u32, params.mip_level as u32)
+            .ok_or_else(|| {
+                format!(
+                    "Array layer {} mip level {} does not exist in the destination texture",
+                    params.array_layer, params.mip_level
+                )
+            })?;
+
+        if params.buffer_offset + layout.size > src_buffer.buffer_def().size {
+            Err(format!(
+                "Copy of {} bytes at offset {} exceeds the size of the source buffer ({} bytes)",
+                layout.size,
+                params.buffer_offset,
+                src_buffer.buffer_def().size
+            ))?;
+        }
+
+        let command = RafxCommandEmpty::CopyBufferToTexture {
+            src_buffer: src_buffer.resource_id(),
+            dst_texture: dst_texture.resource_id(),
+            buffer_offset: params.buffer_offset,
+            array_layer: params.array_layer,
+            mip_level: params.mip_level,
+        };
+
+        let operation = EmptyCommandOperation::CopyBufferToTexture {
+            src: src_buffer.buffer_contents().clone(),
+            dst: dst_texture.texture_contents().clone(),
+            buffer_offset: params.buffer_offset,
+            layout,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureEmpty,
+        dst_buffer: &RafxBufferEmpty,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let layout = src_texture
+            .subresource_layout(params.array_layer as u32, params.mip_level as u32)
+            .ok_or_else(|| {
+                format!(
+                    "Array layer {} mip level {} does not exist in the source texture",
+                    params.array_layer, params.mip_level
+                )
+            })?;
+
+        let row_pitch = crate::internal_shared::texture_copy_row_pitch(
+            src_texture.device_context().device_info(),
+            src_texture.texture_def().format,
+            layout.extents.width,
+        ) as u64;
+
+        let row_count = layout.row_count as u64 * layout.depth as u64;
+        let size = row_pitch * (row_count - 1) + layout.row_pitch;
+        if params.buffer_offset + size > dst_buffer.buffer_def().size {
+            Err(format!(
+                "Copy of {} bytes at offset {} exceeds the size of the destination buffer ({} bytes)",
+                size,
+                params.buffer_offset,
+                dst_buffer.buffer_def().size
+            ))?;
+        }
+
+        let command = RafxCommandEmpty::CopyTextureToBuffer {
+            src_texture: src_texture.resource_id(),
+            dst_buffer: dst_buffer.resource_id(),
+            buffer_offset: params.buffer_offset,
+            array_layer: params.array_layer,
+            mip_level: params.mip_level,
+        };
+
+        let operation = EmptyCommandOperation::CopyTextureToBuffer {
+            src: src_texture.texture_contents().clone(),
+            dst: dst_buffer.buffer_contents().clone(),
+            buffer_offset: params.buffer_offset,
+            layout,
+            row_pitch,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_blit_image(
+        &self,
+        src_texture: &RafxTextureEmpty,
+        dst_texture: &RafxTextureEmpty,
+        params: &RafxCmdBlitParams,
+    ) -> RafxResult<()> {
+        let array_slices = params.array_slices.unwrap_or([0, 0]);
+        for (texture, mip_level, array_slice, name) in [
+            (src_texture, params.src_mip_level, array_slices[0], "source"),
+            (
+                dst_texture,
+                params.dst_mip_level,
+                array_slices[1],
+                "destination",
+            ),
+        ] {
+            let texture_def = texture.texture_def();
+            if mip_level as u32 >= texture_def.mip_count {
+                Err(format!(
+                    "Mip level {} does not exist in the {} texture",
+                    mip_level, name
+                ))?;
+            }
+
+            if array_slice as u32 >= texture_def.array_length {
+                Err(format!(
+                    "Array slice {} does not exist in the {} texture",
+                    array_slice, name
+                ))?;
+            }
+        }
+
+        self.record(RafxCommandEmpty::BlitImage {
+            src_texture: src_texture.resource_id(),
+            dst_texture: dst_texture.resource_id(),
+            src_mip_level: params.src_mip_level,
+            dst_mip_level: params.dst_mip_level,
+        })
+    }
+
+    pub fn cmd_reset_query_pool(
+        &self,
+        query_pool: &RafxQueryPoolEmpty,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<()> {
+        query_pool.validate_range(first_query, query_count)?;
+
+        let command = RafxCommandEmpty::ResetQueryPool {
+            query_pool: query_pool.resource_id(),
+            first_query,
+            query_count,
+        };
+
+        let operation = EmptyCommandOperation::ResetQueries {
+            query_pool: query_pool.clone(),
+            first_query,
+            query_count,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_write_timestamp(
+        &self,
+        query_pool: &RafxQueryPoolEmpty,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        if query_pool.query_pool_def().query_type != RafxQueryType::Timestamp {
+            Err("cmd_write_timestamp requires a timestamp query pool")?;
+        }
+        query_pool.validate_range(query_index, 1)?;
+
+        let command = RafxCommandEmpty::WriteTimestamp {
+            query_pool: query_pool.resource_id(),
+            query_index,
+        };
+
+        let operation = EmptyCommandOperation::WriteTimestamp {
+            query_pool: query_pool.clone(),
+            query_index,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_begin_query(
+        &self,
+        query_pool: &RafxQueryPoolEmpty,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        if query_pool.query_pool_def().query_type == RafxQueryType::Timestamp {
+            Err("cmd_begin_query cannot be used with a timestamp query pool")?;
+        }
+        query_pool.validate_range(query_index, 1)?;
+
+        self.record(RafxCommandEmpty::BeginQuery {
+            query_pool: query_pool.resource_id(),
+            query_index,
+        })
+    }
+
+    pub fn cmd_end_query(
+        &self,
+        query_pool: &RafxQueryPoolEmpty,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        let begin_command = RafxCommandEmpty::BeginQuery {
+            query_pool: query_pool.resource_id(),
+            query_index,
+        };
+
+        // Find the matching begin and gather statistics from the draws recorded after it
+        let mut vertex_count = 0;
+        let mut found_begin = false;
+        for recorded_command in self.recorded_commands.lock().unwrap().iter().rev() {
+            match &recorded_command.command {
+                RafxCommandEmpty::Draw {
+                    vertex_count: count,
+                    instance_count,
+                    ..
+                } => vertex_count += *count as u64 * *instance_count as u64,
+                RafxCommandEmpty::DrawIndexed {
+                    index_count: count,
+                    instance_count,
+                    ..
+                } => vertex_count += *count as u64 * *instance_count as u64,
+                command if *command == begin_command => {
+                    found_begin = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        if !found_begin {
+            Err(format!(
+                "cmd_end_query called for query {} without a matching cmd_begin_query",
+                query_index
+            ))?;
+        }
+
+        let values = match query_pool.query_pool_def().query_type {
+            RafxQueryType::PipelineStatistics => {
+                let mut values = vec![0; RAFX_PIPELINE_STATISTICS_VALUE_COUNT as usize];
+                // input_assembly_vertices and vertex_shader_invocations
+                values[0] = vertex_count;
+                values[2] = vertex_count;
+                values
+            }
+            // Nothing is rasterized so no samples pass
+            _ => vec![0],
+        };
+
+        let command = RafxCommandEmpty::EndQuery {
+            query_pool: query_pool.resource_id(),
+            query_index,
+        };
+
+        let operation = EmptyCommandOperation::WriteQueryResult {
+            query_pool: query_pool.clone(),
+            query_index,
+            values,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    pub fn cmd_resolve_queries(
+        &self,
+        query_pool: &RafxQueryPoolEmpty,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &RafxBufferEmpty,
+        dst_offset: u64,
+    ) -> RafxResult<()> {
+        query_pool.validate_range(first_query, query_count)?;
+
+        let size = query_count as u64
+            * query_pool.values_per_query() as u64
+            * std::mem::size_of::<u64>() as u64;
+        if dst_offset + size > dst_buffer.buffer_def().size {
+            Err(format!(
+                "Resolving {} bytes of query results at offset {} exceeds the size of the destination buffer ({} bytes)",
+                size,
+                dst_offset,
+                dst_buffer.buffer_def().size
+            ))?;
+        }
+
+        let command = RafxCommandEmpty::ResolveQueries {
+            query_pool: query_pool.resource_id(),
+            first_query,
+            query_count,
+            dst_buffer: dst_buffer.resource_id(),
+            dst_offset,
+        };
+
+        let operation = EmptyCommandOperation::ResolveQueries {
+            query_pool: query_pool.clone(),
+            first_query,
+            query_count,
+            dst: dst_buffer.buffer_contents().clone(),
+            dst_offset,
+        };
+
+        self.record_with_operation(command, Some(operation))
+    }
+
+    // Only recorded, the contents of lower mip levels are not generated
+    pub(crate) fn cmd_generate_mipmaps(
+        &self,
+        texture: &RafxTextureEmpty,
+    ) -> RafxResult<()> {
+        self.record(RafxCommandEmpty::GenerateMipmaps {
+            texture: texture.resource_id(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::empty::test_util::{create_command_buffer, test_device};
+    use crate::empty::RafxCommandEmpty;
+    use crate::*;
+
+    #[test]
+    fn test_copy_buffer_to_buffer() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Transfer)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let data = [1_u8, 2, 3, 4, 5, 6, 7, 8];
+            let src = device_context
+                .create_buffer(&RafxBufferDef::for_staging_buffer_data(
+                    &data,
+                    RafxResourceType::BUFFER,
+                ))
+                .unwrap();
+            src.copy_to_host_visible_buffer(&data).unwrap();
+
+            let dst = device_context
+                .create_buffer(&RafxBufferDef {
+                    size: 8,
+                    memory_usage: RafxMemoryUsage::GpuOnly,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_copy_buffer_to_buffer(&src, &dst, 2, 4, 4)
+                .unwrap();
+            command_buffer.end().unwrap();
+
+            // Copies do not execute until the command buffer is submitted
+            let dst = dst.empty_buffer().unwrap();
+            assert_eq!(dst.read_contents(), vec![0; 8]);
+
+            let fence = device_context.create_fence().unwrap();
+            assert_eq!(
+                fence.get_fence_status().unwrap(),
+                RafxFenceStatus::Unsubmitted
+            );
+            queue
+                .submit(&[&command_buffer], &[], &[], Some(&fence))
+                .unwrap();
+            assert_eq!(fence.get_fence_status().unwrap(), RafxFenceStatus::Complete);
+            assert_eq!(
+                fence.get_fence_status().unwrap(),
+                RafxFenceStatus::Unsubmitted
+            );
+
+            assert_eq!(dst.read_contents(), vec![0, 0, 0, 0, 3, 4, 5, 6]);
+
+            // Copies that are out of range are rejected when recorded
+            command_buffer.begin().unwrap();
+            assert!(command_buffer
+                .cmd_copy_buffer_to_buffer(&src, &src, 0, 6, 4)
+                .is_err());
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_copy_buffer_to_texture() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let texture = device_context
+                .create_texture(&RafxTextureDef {
+                    extents: RafxExtents3D {
+                        width: 4,
+                        height: 2,
+                        depth: 1,
+                    },
+                    mip_count: 2,
+                    format: RafxFormat::R8G8B8A8_UNORM,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            // Mip 1 is 2x1 pixels, 4 bytes each
+            let data: Vec<u8> = (0..8).collect();
+            let src = device_context
+                .create_buffer(&RafxBufferDef::for_staging_buffer_data(
+                    &data,
+                    RafxResourceType::BUFFER,
+                ))
+                .unwrap();
+            src.copy_to_host_visible_buffer(&data).unwrap();
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_copy_buffer_to_texture(
+                    &src,
+                    &texture,
+                    &RafxCmdCopyBufferToTextureParams {
+                        buffer_offset: 0,
+                        array_layer: 0,
+                        mip_level: 1,
+                    },
+                )
+                .unwrap();
+            command_buffer.end().unwrap();
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+
+            let texture = texture.empty_texture().unwrap();
+            assert_eq!(texture.read_subresource(0, 0).unwrap(), vec![0; 32]);
+            assert_eq!(texture.read_subresource(0, 1).unwrap(), data);
+            assert!(texture.read_subresource(0, 2).is_none());
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_blit_image() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let create_texture = |width, height| {
+                device_context
+                    .create_texture(&RafxTextureDef {
+                        extents: RafxExtents3D {
+                            width,
+                            height,
+                            depth: 1,
+                        },
+                        format: RafxFormat::R8G8B8A8_UNORM,
+                        ..Default::default()
+                    })
+                    .unwrap()
+            };
+            let src = create_texture(4, 4);
+            let dst = create_texture(2, 2);
+
+            let blit_params = |dst_mip_level| RafxCmdBlitParams {
+                src_state: RafxResourceState::COPY_SRC,
+                dst_state: RafxResourceState::COPY_DST,
+                src_extents: [RafxExtents3D::default(), src.texture_def().extents],
+                dst_extents: [RafxExtents3D::default(), dst.texture_def().extents],
+                src_mip_level: 0,
+                dst_mip_level,
+                array_slices: None,
+            };
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_blit_image(&src, &dst, &blit_params(0))
+                .unwrap();
+            assert!(command_buffer
+                .cmd_blit_image(&src, &dst, &blit_params(1))
+                .is_err());
+            command_buffer.end().unwrap();
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+
+            assert_eq!(
+                queue.empty_queue().unwrap().submitted_commands(),
+                vec![RafxCommandEmpty::BlitImage {
+                    src_texture: src.empty_texture().unwrap().resource_id(),
+                    dst_texture: dst.empty_texture().unwrap().resource_id(),
+                    src_mip_level: 0,
+                    dst_mip_level: 0,
+                }]
+            );
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_indirect_commands() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let draws = [RafxDrawIndirectCommand {
+                vertex_count: 3,
+                instance_count: 1,
+                first_vertex: 0,
+                first_instance: 0,
+            }; 2];
+            let indirect_buffer = device_context
+                .create_buffer(&RafxBufferDef::for_staging_buffer_data(
+                    &draws,
+                    RafxResourceType::INDIRECT_BUFFER,
+                ))
+                .unwrap();
+            let vertex_buffer = device_context
+                .create_buffer(&RafxBufferDef::for_staging_buffer_data(
+                    &draws,
+                    RafxResourceType::VERTEX_BUFFER,
+                ))
+                .unwrap();
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_draw_indirect(&indirect_buffer, 0, 2)
+                .unwrap();
+
+            // Reading past the end of the buffer, misaligned offsets and buffers without the
+            // INDIRECT_BUFFER resource type are all rejected
+            assert!(command_buffer
+                .cmd_draw_indirect(&indirect_buffer, 0, 3)
+                .is_err());
+            assert!(command_buffer
+                .cmd_draw_indirect(&indirect_buffer, 2, 1)
+                .is_err());
+            assert!(command_buffer
+                .cmd_draw_indirect(&vertex_buffer, 0, 1)
+                .is_err());
+            command_buffer.end().unwrap();
+
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+            let commands = queue.empty_queue().unwrap().submitted_commands();
+            assert_eq!(
+                commands,
+                vec![RafxCommandEmpty::DrawIndirect {
+                    indirect_buffer: indirect_buffer.empty_buffer().unwrap().resource_id(),
+                    indirect_buffer_offset_in_bytes: 0,
+                    draw_count: 2,
+                }]
+            );
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_push_constants() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context.create_queue(RafxQueueType::Compute).unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let shader_module = device_context
+                .create_shader_module(RafxShaderModuleDef {
+                    empty: Some(RafxShaderModuleDefEmpty::Empty(Default::default())),
+                })
+                .unwrap();
+            let shader = device_context
+                .create_shader(vec![RafxShaderStageDef {
+                    shader_module,
+                    reflection: RafxShaderStageReflection {
+                        shader_stage: RafxShaderStageFlags::COMPUTE,
+                        resources: vec![RafxShaderResource {
+                            resource_type: RafxResourceType::ROOT_CONSTANT,
+                            size_in_bytes: 16,
+                            used_in_shader_stages: RafxShaderStageFlags::COMPUTE,
+                            name: Some("PushConstants".to_string()),
+                            ..Default::default()
+                        }],
+                        compute_threads_per_group: Some([1, 1, 1]),
+                        entry_point_name: "main".to_string(),
+                    },
+                }])
+                .unwrap();
+            let root_signature = device_context
+                .create_root_signature(&RafxRootSignatureDef {
+                    shaders: &[shader],
+                    immutable_samplers: &[],
+                })
+                .unwrap();
+
+            assert!(root_signature
+                .find_push_constant_by_name("DoesNotExist")
+                .is_none());
+            let push_constant_index = root_signature
+                .find_push_constant_by_name("PushConstants")
+                .unwrap();
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_push_constants(&root_signature, push_constant_index, &[1; 8])
+                .unwrap();
+
+            // Data larger than the push constant or not a multiple of 4 bytes is rejected
+            assert!(command_buffer
+                .cmd_push_constants(&root_signature, push_constant_index, &[0; 20])
+                .is_err());
+            assert!(command_buffer
+                .cmd_push_constants(&root_signature, push_constant_index, &[0; 6])
+                .is_err());
+            command_buffer.end().unwrap();
+
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+            let commands = queue.empty_queue().unwrap().submitted_commands();
+            assert_eq!(
+                commands,
+                vec![RafxCommandEmpty::PushConstants {
+                    push_constant_index,
+                    stages: RafxShaderStageFlags::COMPUTE,
+                    data: vec![1; 8],
+                }]
+            );
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_subpasses() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let create_render_target = |format| {
+                device_context
+                    .create_texture(&RafxTextureDef {
+                        extents: RafxExtents3D {
+                            width: 4,
+                            height: 4,
+                            depth: 1,
+                        },
+                        format,
+                        resource_type: RafxResourceType::RENDER_TARGET_COLOR
+                            | RafxResourceType::INPUT_ATTACHMENT,
+                        ..Default::default()
+                    })
+                    .unwrap()
+            };
+
+            let gbuffer = create_render_target(RafxFormat::R8G8B8A8_UNORM);
+            let lighting = create_render_target(RafxFormat::R16G16B16A16_SFLOAT);
+            let color_target = |texture| RafxColorRenderTargetBinding {
+                texture,
+                load_op: RafxLoadOp::Clear,
+                store_op: RafxStoreOp::DontCare,
+                mip_slice: None,
+                array_slice: None,
+                clear_value: Default::default(),
+                resolve_target: None,
+                resolve_store_op: RafxStoreOp::DontCare,
+                resolve_mip_slice: None,
+                resolve_array_slice: None,
+            };
+
+            let subpasses = vec![
+                RafxSubpassDef {
+                    color_attachments: vec![0],
+                    ..Default::default()
+                },
+                RafxSubpassDef {
+                    color_attachments: vec![1],
+                    input_attachments: vec![RafxSubpassInputAttachment::Color(0)],
+                    ..Default::default()
+                },
+            ];
+
+            command_buffer.begin().unwrap();
+
+            // Subpasses may only reference attachments that were supplied
+            assert!(command_buffer
+                .cmd_begin_render_pass_with_subpasses(
+                    &[color_target(&gbuffer), color_target(&lighting)],
+                    None,
+                    &[RafxSubpassDef {
+                        use_depth_stencil_attachment: true,
+                        ..Default::default()
+                    }],
+                )
+                .is_err());
+
+            command_buffer
+                .cmd_begin_render_pass_with_subpasses(
+                    &[color_target(&gbuffer), color_target(&lighting)],
+                    None,
+                    &subpasses,
+                )
+                .unwrap();
+            command_buffer.cmd_next_subpass().unwrap();
+
+            // There is no third subpass
+            assert!(command_buffer.cmd_next_subpass().is_err());
+            command_buffer.cmd_end_render_pass().unwrap();
+            assert!(command_buffer.cmd_next_subpass().is_err());
+            command_buffer.end().unwrap();
+
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+            let commands = queue.empty_queue().unwrap().submitted_commands();
+            assert_eq!(
+                commands,
+                vec![
+                    RafxCommandEmpty::BeginRenderPass {
+                        color_targets: vec![
+                            gbuffer.empty_texture().unwrap().resource_id(),
+                            lighting.empty_texture().unwrap().resource_id()
+                        ],
+                        depth_target: None,
+                        subpasses,
+                        contents: RafxSubpassContents::Inline,
+                    },
+                    RafxCommandEmpty::NextSubpass,
+                    RafxCommandEmpty::EndRenderPass,
+                ]
+            );
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_secondary_command_buffers() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            assert!(
+                device_context
+                    .device_info()
+                    .supports_secondary_command_buffers
+            );
+
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (mut command_pool, command_buffer) = create_command_buffer(&queue);
+            let secondary_command_buffer = command_pool
+                .create_command_buffer(&RafxCommandBufferDef { is_secondary: true })
+                .unwrap();
+
+            let data = [1_u8, 2, 3, 4];
+            let src = device_context
+                .create_buffer(&RafxBufferDef::for_staging_buffer_data(
+                    &data,
+                    RafxResourceType::BUFFER,
+                ))
+                .unwrap();
+            src.copy_to_host_visible_buffer(&data).unwrap();
+
+            let dst = device_context
+                .create_buffer(&RafxBufferDef {
+                    size: 4,
+                    memory_usage: RafxMemoryUsage::GpuOnly,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let render_target = device_context
+                .create_texture(&RafxTextureDef {
+                    extents: RafxExtents3D {
+                        width: 4,
+                        height: 4,
+                        depth: 1,
+                    },
+                    format: RafxFormat::R8G8B8A8_UNORM,
+                    resource_type: RafxResourceType::RENDER_TARGET_COLOR,
+                    ..Default::default()
+                })
+                .unwrap();
+            let color_target = || RafxColorRenderTargetBinding {
+                texture: &render_target,
+                load_op: RafxLoadOp::Clear,
+                store_op: RafxStoreOp::Store,
+                mip_slice: None,
+                array_slice: None,
+                clear_value: Default::default(),
+                resolve_target: None,
+                resolve_store_op: RafxStoreOp::DontCare,
+                resolve_mip_slice: None,
+                resolve_array_slice: None,
+            };
+
+            // Secondary command buffers must be started with a primary command buffer
+            assert!(secondary_command_buffer.begin().is_err());
+            assert!(command_buffer
+                .begin_secondary(&secondary_command_buffer)
+                .is_err());
+
+            command_buffer.begin().unwrap();
+
+            secondary_command_buffer
+                .begin_secondary(&command_buffer)
+                .unwrap();
+            secondary_command_buffer
+                .cmd_copy_buffer_to_buffer(&src, &dst, 0, 0, 4)
+                .unwrap();
+            secondary_command_buffer.end().unwrap();
+            command_buffer
+                .cmd_execute_secondary_command_buffers(&[&secondary_command_buffer])
+                .unwrap();
+
+            // Inline subpasses can't execute secondary command buffers
+            command_buffer
+                .cmd_begin_render_pass(&[color_target()], None)
+                .unwrap();
+            assert!(command_buffer
+                .cmd_execute_secondary_command_buffers(&[&secondary_command_buffer])
+                .is_err());
+            command_buffer.cmd_end_render_pass().unwrap();
+
+            command_buffer
+                .cmd_begin_render_pass_with_contents(
+                    &[color_target()],
+                    None,
+                    &[],
+                    RafxSubpassContents::SecondaryCommandBuffers,
+                )
+                .unwrap();
+            assert!(command_buffer
+                .cmd_execute_secondary_command_buffers(&[&command_buffer])
+                .is_err());
+            secondary_command_buffer
+                .begin_secondary(&command_buffer)
+                .unwrap();
+            secondary_command_buffer.cmd_draw(3, 0).unwrap();
+            secondary_command_buffer.end().unwrap();
+            command_buffer
+                .cmd_execute_secondary_command_buffers(&[&secondary_command_buffer])
+                .unwrap();
+            command_buffer.cmd_end_render_pass().unwrap();
+            command_buffer.end().unwrap();
+
+            // Copies recorded in the secondary command buffer do not execute until the primary
+            // command buffer is submitted
+            let dst = dst.empty_buffer().unwrap();
+            assert_eq!(dst.read_contents(), vec![0; 4]);
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+            assert_eq!(dst.read_contents(), data.to_vec());
+
+            let commands = queue.empty_queue().unwrap().submitted_commands();
+            assert_eq!(commands.len(), 6);
+            assert_eq!(
+                commands[1],
+                RafxCommandEmpty::BeginRenderPass {
+                    color_targets: vec![render_target.empty_texture().unwrap().resource_id()],
+                    depth_target: None,
+                    subpasses: vec![],
+                    contents: RafxSubpassContents::Inline,
+                }
+            );
+            assert_eq!(
+                commands[4],
+                RafxCommandEmpty::ExecuteSecondaryCommandBuffers {
+                    command_buffers: vec![vec![RafxCommandEmpty::Draw {
+                        vertex_count: 3,
+                        first_vertex: 0,
+                        instance_count: 1,
+                        first_instance: 0,
+                    }]],
+                }
+            );
+        }
+        std::mem::drop(api);
+    }
+}
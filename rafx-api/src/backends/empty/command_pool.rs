@@ -0,0 +1,41 @@
+use crate::empty::{RafxCommandBufferEmpty, RafxDeviceContextEmpty, RafxQueueEmpty};
+use crate::{RafxCommandBufferDef, RafxCommandPoolDef, RafxQueueType, RafxResult};
+
+pub struct RafxCommandPoolEmpty {
+    queue: RafxQueueEmpty,
+}
+
+impl RafxCommandPoolEmpty {
+    pub fn device_context(&self) -> &RafxDeviceContextEmpty {
+        self.queue.device_context()
+    }
+
+    pub fn queue_type(&self) -> RafxQueueType {
+        self.queue.queue_type()
+    }
+
+    pub fn queue(&self) -> &RafxQueueEmpty {
+        &self.queue
+    }
+
+    pub fn create_command_buffer(
+        &self,
+        command_buffer_def: &RafxCommandBufferDef,
+    ) -> RafxResult<RafxCommandBufferEmpty> {
+        RafxCommandBufferEmpty::new(self, command_buffer_def)
+    }
+
+    pub fn reset_command_pool(&self) -> RafxResult<()> {
+        // do nothing
+        Ok(())
+    }
+
+    pub fn new(
+        queue: &RafxQueueEmpty,
+        _command_pool_def: &RafxCommandPoolDef,
+    ) -> RafxResult<RafxCommandPoolEmpty> {
+        Ok(RafxCommandPoolEmpty {
+            queue: queue.clone(),
+        })
+    }
+}
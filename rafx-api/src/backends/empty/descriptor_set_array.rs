@@ -0,0 +1,351 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{
+    RafxDescriptorSetArrayDef, RafxDescriptorUpdate, RafxResourceType, RafxResult,
+    RafxRootSignature,
+};
+use fnv::FnvHashMap;
+use rafx_base::trust_cell::TrustCell;
+use std::sync::Arc;
+
+// For every descriptor set in the array, the resource IDs of buffers/textures written to each
+// binding. Samplers are not tracked.
+pub(crate) type DescriptorSetArrayData = Vec<FnvHashMap<u32, Vec<Option<u64>>>>;
+
+#[derive(Clone)]
+pub struct RafxDescriptorSetHandleEmpty {
+    descriptor_set_array_data: Arc<TrustCell<DescriptorSetArrayData>>,
+    set_index: u32,
+    array_index: u32,
+}
+
+impl std::fmt::Debug for RafxDescriptorSetHandleEmpty {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("RafxDescriptorSetHandleEmpty")
+            .field("set_index", &self.set_index)
+            .field("array_index", &self.array_index)
+            .finish()
+    }
+}
+
+impl RafxDescriptorSetHandleEmpty {
+    pub fn set_index(&self) -> u32 {
+        self.set_index
+    }
+
+    pub fn array_index(&self) -> u32 {
+        self.array_index
+    }
+
+    /// Returns the resource IDs of the buffers or textures written to the given binding
+    pub fn bound_resource_ids(
+        &self,
+        binding: u32,
+    ) -> Vec<Option<u64>> {
+        self.descriptor_set_array_data.borrow()[self.array_index as usize]
+            .get(&binding)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+pub struct RafxDescriptorSetArrayEmpty {
+    root_signature: RafxRootSignature,
+    set_index: u32,
+    data: Arc<TrustCell<DescriptorSetArrayData>>,
+    array_length: u32,
+    pending_writes: Vec<(u32, u32, Vec<Option<u64>>, u32)>,
+}
+
+impl std::fmt::Debug for RafxDescriptorSetArrayEmpty {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("RafxDescriptorSetArrayEmpty")
+            .field("set_index", &self.set_index)
+            .field("array_length", &self.array_length)
+            .finish()
+    }
+}
+
+impl RafxDescriptorSetArrayEmpty {
+    pub fn root_signature(&self) -> &RafxRootSignature {
+        &self.root_signature
+    }
+
+    pub fn set_index(&self) -> u32 {
+        self.set_index
+    }
+
+    pub fn array_length(&self) -> u32 {
+        self.array_length
+    }
+
+    pub fn handle(
+        &self,
+        array_index: u32,
+    ) -> Option<RafxDescriptorSetHandleEmpty> {
+        if array_index >= self.array_length {
+            return None;
+        }
+
+        Some(RafxDescriptorSetHandleEmpty {
+            descriptor_set_array_data: self.data.clone(),
+            set_index: self.set_index,
+            array_index,
+        })
+    }
+
+    pub(crate) fn new(
+        _device_context: &RafxDeviceContextEmpty,
+        descriptor_set_array_def: &RafxDescriptorSetArrayDef,
+    ) -> RafxResult<Self> {
+        let root_signature = descriptor_set_array_def.root_signature.clone();
+        if root_signature.empty_root_signature().is_none() {
+            return Err(
+                "Descriptor set arrays require a root signature created by the empty backend",
+            )?;
+        }
+
+        let data = vec![FnvHashMap::default(); descriptor_set_array_def.array_length];
+
+        Ok(RafxDescriptorSetArrayEmpty {
+            root_signature,
+            set_index: descriptor_set_array_def.set_index,
+            data: Arc::new(TrustCell::new(data)),
+            array_length: descriptor_set_array_def.array_length as u32,
+            pending_writes: Default::default(),
+        })
+    }
+
+    pub fn update_descriptor_set(
+        &mut self,
+        descriptor_updates: &[RafxDescriptorUpdate],
+    ) -> RafxResult<()> {
+        for update in descriptor_updates {
+            self.queue_descriptor_set_update(update)?;
+        }
+        self.flush_descriptor_set_updates()
+    }
+
+    pub fn flush_descriptor_set_updates(&mut self) -> RafxResult<()> {
+        let mut data = self.data.borrow_mut();
+        for (array_index, binding, resource_ids, dst_element_offset) in
+            self.pending_writes.drain(..)
+        {
+            let elements = data[array_index as usize].entry(binding).or_default();
+
+            let required_len = dst_element_offset as usize + resource_ids.len();
+            if elements.len() < required_len {
+                elements.resize(required_len, None);
+            }
+
+            for (i, resource_id) in resource_ids.into_iter().enumerate() {
+                elements[dst_element_offset as usize + i] = resource_id;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn queue_descriptor_set_update(
+        &mut self,
+        update: &RafxDescriptorUpdate,
+    ) -> RafxResult<()> {
+        if update.array_index >= self.array_length {
+            return Err(format!(
+                "Descriptor set update array index {} is out of range (array length is {})",
+                update.array_index, self.array_length
+            ))?;
+        }
+
+        let root_signature = self.root_signature.empty_root_signature().unwrap();
+        let descriptor_index = root_signature
+            .find_descriptor(self.set_index, &update.descriptor_key)
+            .ok_or_else(|| {
+                format!(
+                    "Could not find descriptor {:?} in set {}",
+                    update.descriptor_key, self.set_index
+                )
+            })?;
+        let descriptor = root_signature.descriptor(descriptor_index).unwrap();
+        if descriptor.set_index != self.set_index {
+            return Err(format!(
+                "Descriptor {:?} is in set {} but this descriptor set array is for set {}",
+                descriptor.name, descriptor.set_index, self.set_index
+            ))?;
+        }
+
+        let resource_ids: Vec<_> = if descriptor
+            .resource_type
+            .intersects(RafxResourceType::TEXTURE | RafxResourceType::TEXTURE_READ_WRITE)
+        {
+            let textures = update.elements.textures.ok_or("No textures provided")?;
+            textures
+                .iter()
+                .map(|x| x.empty_texture().map(|x| x.resource_id()))
+                .collect()
+        } else if descriptor.resource_type.intersects(
+            RafxResourceType::UNIFORM_BUFFER
+                | RafxResourceType::BUFFER
+                | RafxResourceType::BUFFER_READ_WRITE
+                | RafxResourceType::TEXEL_BUFFER
+                | RafxResourceType::TEXEL_BUFFER_READ_WRITE,
+        ) {
+            let buffers = update.elements.buffers.ok_or("No buffers provided")?;
+            buffers
+                .iter()
+                .map(|x| x.empty_buffer().map(|x| x.resource_id()))
+                .collect()
+        } else if descriptor
+            .resource_type
+            .intersects(RafxResourceType::SAMPLER)
+        {
+            update.elements.samplers.ok_or("No samplers provided")?;
+            vec![]
+        } else {
+            vec![]
+        };
+
+        if update.dst_element_offset as usize + resource_ids.len()
+            > descriptor.element_count as usize
+        {
+            return Err(format!(
+                "Descriptor {:?} has {} elements but the update writes up to element {}",
+                descriptor.name,
+                descriptor.element_count,
+                update.dst_element_offset as usize + resource_ids.len()
+            ))?;
+        }
+
+        self.pending_writes.push((
+            update.array_index,
+            descriptor.binding,
+            resource_ids,
+            update.dst_element_offset,
+        ));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::empty::test_util::test_device;
+    use crate::*;
+
+    #[test]
+    fn test_bindless_descriptors() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            assert!(device_context.device_info().supports_bindless_descriptors);
+
+            let create_root_signature = |resource_type, element_count| {
+                let shader_module = device_context
+                    .create_shader_module(RafxShaderModuleDef {
+                        empty: Some(RafxShaderModuleDefEmpty::Empty(Default::default())),
+                    })
+                    .unwrap();
+                let shader = device_context
+                    .create_shader(vec![RafxShaderStageDef {
+                        shader_module,
+                        reflection: RafxShaderStageReflection {
+                            shader_stage: RafxShaderStageFlags::FRAGMENT,
+                            resources: vec![RafxShaderResource {
+                                resource_type,
+                                set_index: 3,
+                                binding: 0,
+                                element_count,
+                                used_in_shader_stages: RafxShaderStageFlags::FRAGMENT,
+                                name: Some("bindless_textures".to_string()),
+                                ..Default::default()
+                            }],
+                            compute_threads_per_group: None,
+                            entry_point_name: "main".to_string(),
+                        },
+                    }])
+                    .unwrap();
+                device_context.create_root_signature(&RafxRootSignatureDef {
+                    shaders: &[shader],
+                    immutable_samplers: &[],
+                })
+            };
+
+            // Bindless arrays must have a size and may only contain textures or storage buffers
+            assert!(create_root_signature(
+                RafxResourceType::TEXTURE | RafxResourceType::BINDLESS,
+                0
+            )
+            .is_err());
+            assert!(create_root_signature(
+                RafxResourceType::UNIFORM_BUFFER | RafxResourceType::BINDLESS,
+                1024
+            )
+            .is_err());
+
+            let root_signature =
+                create_root_signature(RafxResourceType::TEXTURE | RafxResourceType::BINDLESS, 1024)
+                    .unwrap();
+            let mut descriptor_set_array = device_context
+                .create_descriptor_set_array(&RafxDescriptorSetArrayDef {
+                    root_signature: &root_signature,
+                    set_index: 3,
+                    array_length: 1,
+                })
+                .unwrap();
+
+            let texture = device_context
+                .create_texture(&RafxTextureDef {
+                    extents: RafxExtents3D {
+                        width: 1,
+                        height: 1,
+                        depth: 1,
+                    },
+                    format: RafxFormat::R8G8B8A8_UNORM,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            // Elements can be written individually and anywhere in the array
+            descriptor_set_array
+                .update_descriptor_set(&[RafxDescriptorUpdate {
+                    descriptor_key: RafxDescriptorKey::Binding(0),
+                    elements: RafxDescriptorElements {
+                        textures: Some(&[&texture]),
+                        ..Default::default()
+                    },
+                    dst_element_offset: 500,
+                    ..Default::default()
+                }])
+                .unwrap();
+            assert!(descriptor_set_array
+                .update_descriptor_set(&[RafxDescriptorUpdate {
+                    descriptor_key: RafxDescriptorKey::Binding(0),
+                    elements: RafxDescriptorElements {
+                        textures: Some(&[&texture]),
+                        ..Default::default()
+                    },
+                    dst_element_offset: 1024,
+                    ..Default::default()
+                }])
+                .is_err());
+
+            let bound_resource_ids = descriptor_set_array
+                .empty_descriptor_set_array()
+                .unwrap()
+                .handle(0)
+                .unwrap()
+                .bound_resource_ids(0);
+            assert_eq!(
+                bound_resource_ids[500],
+                Some(texture.empty_texture().unwrap().resource_id())
+            );
+            assert!(bound_resource_ids[..500].iter().all(|x| x.is_none()));
+        }
+        std::mem::drop(api);
+    }
+}
@@ -0,0 +1,452 @@
+use crate::{
+    RafxBufferDef, RafxComputePipelineDef, RafxDescriptorSetArrayDef, RafxDeviceContext,
+    RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxMemoryBudget, RafxMemoryHeapBudget,
+    RafxQueryPoolDef, RafxQueueType, RafxResourceType, RafxResult, RafxRootSignatureDef,
+    RafxSampleCount, RafxSamplerDef, RafxShaderModuleDefEmpty, RafxShaderStageDef,
+    RafxSwapchainDef, RafxTextureDef,
+};
+use raw_window_handle::HasRawWindowHandle;
+use std::sync::{Arc, Mutex};
+
+use crate::empty::{
+    RafxBufferEmpty, RafxDescriptorSetArrayEmpty, RafxFenceEmpty, RafxPipelineEmpty,
+    RafxQueryPoolEmpty, RafxQueueEmpty, RafxRootSignatureEmpty, RafxSamplerEmpty,
+    RafxSemaphoreEmpty, RafxShaderEmpty, RafxShaderModuleEmpty, RafxSwapchainEmpty,
+    RafxTextureEmpty,
+};
+use crate::internal_shared::{
+    decode_pipeline_cache_data, encode_pipeline_cache_data, PipelineCacheDataIdentity,
+    PIPELINE_CACHE_DATA_BACKEND_EMPTY,
+};
+
+use fnv::FnvHashSet;
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+
+const PIPELINE_CACHE_DATA_IDENTITY_EMPTY: PipelineCacheDataIdentity = PipelineCacheDataIdentity {
+    backend: PIPELINE_CACHE_DATA_BACKEND_EMPTY,
+    vendor_id: 0,
+    device_id: 0,
+    driver_version: 0,
+    pipeline_cache_uuid: [0; 16],
+};
+
+const EMPTY_MEMORY_HEAP_SIZE_IN_BYTES: u64 = 16 * 1024 * 1024 * 1024;
+
+pub struct RafxDeviceContextEmptyInner {
+    pub(crate) device_info: RafxDeviceInfo,
+    destroyed: AtomicBool,
+
+    // Buffers and textures are assigned a unique ID so that they can be identified in the
+    // command log
+    next_resource_id: AtomicU64,
+
+    // Total size of all live buffers and textures, reported as usage by memory_budget()
+    allocated_memory_in_bytes: AtomicU64,
+
+    // Timestamp queries report the time elapsed since the device was created
+    created_at: Instant,
+
+    // No pipelines are compiled, but entries of merged pipeline cache data are kept so that they
+    // are exported again, like a real pipeline cache would. Each entry is a u64
+    pipeline_cache_entries: Mutex<FnvHashSet<u64>>,
+}
+
+impl Drop for RafxDeviceContextEmptyInner {
+    fn drop(&mut self) {
+        log::trace!("destroying device");
+        self.destroyed.swap(true, Ordering::AcqRel);
+    }
+}
+
+impl RafxDeviceContextEmptyInner {
+    pub fn new() -> RafxResult<Self> {
+        let device_info = RafxDeviceInfo {
+            supports_multithreaded_usage: true,
+            // Use the strictest values that are common on real hardware so that code tested
+            // against this backend does not rely on looser alignment
+            min_uniform_buffer_offset_alignment: 256,
+            min_storage_buffer_offset_alignment: 64,
+            upload_buffer_texture_alignment: 16,
+            upload_buffer_texture_row_alignment: 1,
+            supports_clamp_to_border_color: true,
+            max_vertex_attribute_count: 16,
+            // Timestamps are reported in nanoseconds
+            timestamp_period: 1.0,
+            supports_timestamp_queries: true,
+            supports_occlusion_queries: true,
+            supports_pipeline_statistics_queries: true,
+            supports_draw_indirect: true,
+            supports_multi_draw_indirect: true,
+            supports_draw_indirect_count: true,
+            supports_dispatch_indirect: true,
+            supports_transient_attachments: false,
+            supports_resource_aliasing: false,
+            supports_subpasses: true,
+            supports_secondary_command_buffers: true,
+            supports_blit_images: true,
+            supports_pipeline_cache_data: true,
+            supports_bindless_descriptors: true,
+            max_bindless_descriptor_count: 1 << 20,
+        };
+
+        Ok(RafxDeviceContextEmptyInner {
+            device_info,
+            destroyed: AtomicBool::new(false),
+            next_resource_id: AtomicU64::new(1),
+            allocated_memory_in_bytes: AtomicU64::new(0),
+            created_at: Instant::now(),
+            pipeline_cache_entries: Default::default(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RafxDeviceContextEmpty {
+    pub(crate) inner: Arc<RafxDeviceContextEmptyInner>,
+}
+
+impl std::fmt::Debug for RafxDeviceContextEmpty {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        f.debug_struct("RafxDeviceContextEmpty").finish()
+    }
+}
+
+impl From<RafxDeviceContextEmpty> for RafxDeviceContext {
+    fn from(device_context: RafxDeviceContextEmpty) -> Self {
+        RafxDeviceContext::Empty(device_context)
+    }
+}
+
+impl RafxDeviceContextEmpty {
+    pub fn device_info(&self) -> &RafxDeviceInfo {
+        &self.inner.device_info
+    }
+
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        let mut entries: Vec<u64> = self
+            .inner
+            .pipeline_cache_entries
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        entries.sort_unstable();
+
+        let mut payload = Vec::with_capacity(entries.len() * 8);
+        for entry in entries {
+            payload.extend_from_slice(&entry.to_le_bytes());
+        }
+
+        Ok(encode_pipeline_cache_data(
+            &PIPELINE_CACHE_DATA_IDENTITY_EMPTY,
+            &payload,
+        ))
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        data: &[u8],
+    ) -> RafxResult<bool> {
+        let payload = match decode_pipeline_cache_data(&PIPELINE_CACHE_DATA_IDENTITY_EMPTY, data) {
+            Some(payload) => payload,
+            None => return Ok(false),
+        };
+
+        if payload.len() % 8 != 0 {
+            Err("Pipeline cache data payload is not a list of u64 entries")?;
+        }
+
+        let mut entries = self.inner.pipeline_cache_entries.lock().unwrap();
+        for entry in payload.chunks_exact(8) {
+            entries.insert(u64::from_le_bytes(entry.try_into().unwrap()));
+        }
+
+        Ok(true)
+    }
+
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        // Resources live in host memory, so report a single heap with a budget that is large
+        // enough to never be a limiting factor
+        let heap_size_in_bytes = EMPTY_MEMORY_HEAP_SIZE_IN_BYTES;
+        Ok(RafxMemoryBudget {
+            heaps: vec![RafxMemoryHeapBudget {
+                is_device_local: true,
+                heap_size_in_bytes,
+                budget_in_bytes: heap_size_in_bytes,
+                usage_in_bytes: self.inner.allocated_memory_in_bytes.load(Ordering::Relaxed),
+            }],
+            is_budget_reported_by_driver: true,
+        })
+    }
+
+    pub(crate) fn add_allocated_memory(
+        &self,
+        size_in_bytes: u64,
+    ) {
+        self.inner
+            .allocated_memory_in_bytes
+            .fetch_add(size_in_bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn remove_allocated_memory(
+        &self,
+        size_in_bytes: u64,
+    ) {
+        self.inner
+            .allocated_memory_in_bytes
+            .fetch_sub(size_in_bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn allocate_resource_id(&self) -> u64 {
+        self.inner.next_resource_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub(crate) fn current_timestamp(&self) -> u64 {
+        self.inner.created_at.elapsed().as_nanos() as u64
+    }
+
+    pub fn new(inner: Arc<RafxDeviceContextEmptyInner>) -> RafxResult<Self> {
+        Ok(RafxDeviceContextEmpty { inner })
+    }
+
+    pub fn create_queue(
+        &self,
+        queue_type: RafxQueueType,
+    ) -> RafxResult<RafxQueueEmpty> {
+        RafxQueueEmpty::new(self, queue_type)
+    }
+
+    pub fn create_fence(&self) -> RafxResult<RafxFenceEmpty> {
+        RafxFenceEmpty::new(self)
+    }
+
+    pub fn create_semaphore(&self) -> RafxResult<RafxSemaphoreEmpty> {
+        RafxSemaphoreEmpty::new(self)
+    }
+
+    pub fn create_swapchain(
+        &self,
+        _raw_window_handle: &dyn HasRawWindowHandle,
+        swapchain_def: &RafxSwapchainDef,
+    ) -> RafxResult<RafxSwapchainEmpty> {
+        RafxSwapchainEmpty::new(self, swapchain_def)
+    }
+
+    pub fn wait_for_fences(
+        &self,
+        fences: &[&RafxFenceEmpty],
+    ) -> RafxResult<()> {
+        RafxFenceEmpty::wait_for_fences(self, fences)
+    }
+
+    pub fn create_sampler(
+        &self,
+        sampler_def: &RafxSamplerDef,
+    ) -> RafxResult<RafxSamplerEmpty> {
+        RafxSamplerEmpty::new(self, sampler_def)
+    }
+
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolEmpty> {
+        RafxQueryPoolEmpty::new(self, query_pool_def)
+    }
+
+    pub fn create_texture(
+        &self,
+        texture_def: &RafxTextureDef,
+    ) -> RafxResult<RafxTextureEmpty> {
+        RafxTextureEmpty::new(self, texture_def)
+    }
+
+    pub fn create_buffer(
+        &self,
+        buffer_def: &RafxBufferDef,
+    ) -> RafxResult<RafxBufferEmpty> {
+        RafxBufferEmpty::new(self, buffer_def)
+    }
+
+    pub fn create_shader(
+        &self,
+        stages: Vec<RafxShaderStageDef>,
+    ) -> RafxResult<RafxShaderEmpty> {
+        RafxShaderEmpty::new(self, stages)
+    }
+
+    pub fn create_root_signature(
+        &self,
+        root_signature_def: &RafxRootSignatureDef,
+    ) -> RafxResult<RafxRootSignatureEmpty> {
+        RafxRootSignatureEmpty::new(self, root_signature_def)
+    }
+
+    pub fn create_descriptor_set_array(
+        &self,
+        descriptor_set_array_def: &RafxDescriptorSetArrayDef,
+    ) -> RafxResult<RafxDescriptorSetArrayEmpty> {
+        RafxDescriptorSetArrayEmpty::new(self, descriptor_set_array_def)
+    }
+
+    pub fn create_graphics_pipeline(
+        &self,
+        graphics_pipeline_def: &RafxGraphicsPipelineDef,
+    ) -> RafxResult<RafxPipelineEmpty> {
+        RafxPipelineEmpty::new_graphics_pipeline(self, graphics_pipeline_def)
+    }
+
+    pub fn create_compute_pipeline(
+        &self,
+        compute_pipeline_def: &RafxComputePipelineDef,
+    ) -> RafxResult<RafxPipelineEmpty> {
+        RafxPipelineEmpty::new_compute_pipeline(self, compute_pipeline_def)
+    }
+
+    pub fn create_shader_module(
+        &self,
+        data: RafxShaderModuleDefEmpty,
+    ) -> RafxResult<RafxShaderModuleEmpty> {
+        RafxShaderModuleEmpty::new(self, data)
+    }
+
+    // All formats are "supported" because texture contents are only ever copied, never sampled
+    pub fn find_supported_format(
+        &self,
+        candidates: &[RafxFormat],
+        _resource_type: RafxResourceType,
+    ) -> Option<RafxFormat> {
+        candidates.iter().copied().find(|x| !x.is_undefined())
+    }
+
+    pub fn find_supported_sample_count(
+        &self,
+        candidates: &[RafxSampleCount],
+    ) -> Option<RafxSampleCount> {
+        candidates.first().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::empty::test_util::test_device;
+    use crate::*;
+
+    #[test]
+    fn test_pipeline_cache_data() {
+        use crate::internal_shared::{
+            encode_pipeline_cache_data, PipelineCacheDataIdentity,
+            PIPELINE_CACHE_DATA_BACKEND_EMPTY,
+        };
+
+        let identity = PipelineCacheDataIdentity {
+            backend: PIPELINE_CACHE_DATA_BACKEND_EMPTY,
+            vendor_id: 0,
+            device_id: 0,
+            driver_version: 0,
+            pipeline_cache_uuid: [0; 16],
+        };
+        let mut payload = Vec::default();
+        payload.extend_from_slice(&7u64.to_le_bytes());
+        payload.extend_from_slice(&3u64.to_le_bytes());
+        let data = encode_pipeline_cache_data(&identity, &payload);
+
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            assert!(device_context.device_info().supports_pipeline_cache_data);
+
+            let empty_data = device_context.pipeline_cache_data().unwrap();
+            assert!(device_context
+                .merge_pipeline_cache_data(&empty_data)
+                .unwrap());
+            assert_eq!(device_context.pipeline_cache_data().unwrap(), empty_data);
+
+            // Merged entries are exported again
+            assert!(device_context.merge_pipeline_cache_data(&data).unwrap());
+            assert!(device_context.merge_pipeline_cache_data(&data).unwrap());
+            let mut sorted_payload = Vec::default();
+            sorted_payload.extend_from_slice(&3u64.to_le_bytes());
+            sorted_payload.extend_from_slice(&7u64.to_le_bytes());
+            assert_eq!(
+                device_context.pipeline_cache_data().unwrap(),
+                encode_pipeline_cache_data(&identity, &sorted_payload)
+            );
+
+            // Data from another device or driver, or that is truncated, is ignored
+            let other_driver_identity = PipelineCacheDataIdentity {
+                driver_version: 1,
+                ..identity
+            };
+            let other_driver_data = encode_pipeline_cache_data(&other_driver_identity, &[]);
+            assert!(!device_context
+                .merge_pipeline_cache_data(&other_driver_data)
+                .unwrap());
+            assert!(!device_context
+                .merge_pipeline_cache_data(&data[..data.len() - 1])
+                .unwrap());
+            assert!(!device_context.merge_pipeline_cache_data(&[]).unwrap());
+            assert_eq!(
+                device_context.pipeline_cache_data().unwrap(),
+                encode_pipeline_cache_data(&identity, &sorted_payload)
+            );
+        }
+        std::mem::drop(api);
+    }
+
+    #[test]
+    fn test_memory_budget_and_allocation_sizes() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let usage = || {
+                let budget = device_context.memory_budget().unwrap();
+                assert!(budget.is_budget_reported_by_driver);
+                assert_eq!(budget.heaps.len(), 1);
+                budget.device_local_usage_in_bytes()
+            };
+            assert_eq!(usage(), 0);
+
+            let buffer = device_context
+                .create_buffer(&RafxBufferDef {
+                    size: 256,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(buffer.allocation_size_in_bytes(), 256);
+
+            // 4x4 + 2x2 + 1x1 pixels, 4 bytes each, for each of the 2 layers
+            let texture = device_context
+                .create_texture(&RafxTextureDef {
+                    extents: RafxExtents3D {
+                        width: 4,
+                        height: 4,
+                        depth: 1,
+                    },
+                    array_length: 2,
+                    mip_count: 3,
+                    format: RafxFormat::R8G8B8A8_UNORM,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(texture.allocation_size_in_bytes(), 168);
+            assert_eq!(usage(), 256 + 168);
+
+            // Clones share the texture's memory
+            let texture_clone = texture.clone();
+            std::mem::drop(texture);
+            assert_eq!(usage(), 256 + 168);
+            std::mem::drop(texture_clone);
+            assert_eq!(usage(), 256);
+
+            std::mem::drop(buffer);
+            assert_eq!(usage(), 0);
+        }
+        std::mem::drop(api);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxFenceStatus, RafxResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct RafxFenceEmpty {
+    _device_context: RafxDeviceContextEmpty,
+    // Set to true when an operation is scheduled to signal this fence
+    // Cleared when an operation is scheduled to consume this fence
+    submitted: AtomicBool,
+}
+
+impl RafxFenceEmpty {
+    pub fn new(device_context: &RafxDeviceContextEmpty) -> RafxResult<RafxFenceEmpty> {
+        Ok(RafxFenceEmpty {
+            _device_context: device_context.clone(),
+            submitted: AtomicBool::new(false),
+        })
+    }
+
+    pub(crate) fn submitted(&self) -> bool {
+        self.submitted.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_submitted(
+        &self,
+        submitted: bool,
+    ) {
+        self.submitted.store(submitted, Ordering::Relaxed);
+    }
+
+    pub fn wait(&self) -> RafxResult<()> {
+        // Work is executed synchronously during submit, so there is never anything to wait for
+        self.set_submitted(false);
+        Ok(())
+    }
+
+    pub fn wait_for_fences(
+        _device_context: &RafxDeviceContextEmpty,
+        fences: &[&RafxFenceEmpty],
+    ) -> RafxResult<()> {
+        for fence in fences {
+            fence.set_submitted(false);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_fence_status(&self) -> RafxResult<RafxFenceStatus> {
+        if !self.submitted() {
+            Ok(RafxFenceStatus::Unsubmitted)
+        } else {
+            self.set_submitted(false);
+            Ok(RafxFenceStatus::Complete)
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! A CPU reference backend. Buffers and textures are backed by host memory, copies are executed
+//! when command buffers are submitted, and all other commands are recorded into a log that can be
+//! inspected via `RafxQueueEmpty::submitted_commands()`. This allows code built on rafx-api to run
+//! (and be tested) on machines without a GPU. Shaders are never executed.
+
+mod api;
+pub use api::*;
+
+mod device_context;
+pub use device_context::*;
+
+mod swapchain;
+pub use swapchain::*;
+
+mod texture;
+pub use texture::*;
+
+mod semaphore;
+pub use semaphore::*;
+
+mod fence;
+pub use fence::*;
+
+mod queue;
+pub use queue::*;
+
+mod command_pool;
+pub use command_pool::*;
+
+mod command_buffer;
+pub use command_buffer::*;
+
+mod buffer;
+pub use buffer::*;
+
+mod shader_module;
+pub use shader_module::*;
+
+mod shader;
+pub use shader::*;
+
+mod root_signature;
+pub use root_signature::*;
+
+mod descriptor_set_array;
+pub use descriptor_set_array::*;
+
+mod sampler;
+pub use sampler::*;
+
+mod query_pool;
+pub use query_pool::*;
+
+mod pipeline;
+pub use pipeline::*;
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use crate::*;
+
+    pub(crate) fn test_device() -> RafxApi {
+        RafxApi::new_empty(&Default::default(), &Default::default()).unwrap()
+    }
+
+    pub(crate) fn create_command_buffer(queue: &RafxQueue) -> (RafxCommandPool, RafxCommandBuffer) {
+        let mut command_pool = queue
+            .create_command_pool(&RafxCommandPoolDef { transient: true })
+            .unwrap();
+        let command_buffer = command_pool
+            .create_command_buffer(&RafxCommandBufferDef {
+                is_secondary: false,
+            })
+            .unwrap();
+        (command_pool, command_buffer)
+    }
+}
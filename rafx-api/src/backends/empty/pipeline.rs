@@ -0,0 +1,78 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{
+    RafxComputePipelineDef, RafxGraphicsPipelineDef, RafxPipelineType, RafxPrimitiveTopology,
+    RafxResult, RafxRootSignature,
+};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub struct RafxPipelineEmpty {
+    pipeline_type: RafxPipelineType,
+    // It's a RafxRootSignatureEmpty, but stored as RafxRootSignature so we can return refs to it
+    root_signature: RafxRootSignature,
+    primitive_topology: Option<RafxPrimitiveTopology>,
+    _device_context: RafxDeviceContextEmpty,
+    debug_name: Mutex<Option<String>>,
+}
+
+impl RafxPipelineEmpty {
+    pub fn pipeline_type(&self) -> RafxPipelineType {
+        self.pipeline_type
+    }
+
+    pub fn root_signature(&self) -> &RafxRootSignature {
+        &self.root_signature
+    }
+
+    /// The primitive topology of a graphics pipeline, `None` for compute pipelines
+    pub fn primitive_topology(&self) -> Option<RafxPrimitiveTopology> {
+        self.primitive_topology
+    }
+
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        *self.debug_name.lock().unwrap() = Some(name.as_ref().to_string());
+        Ok(())
+    }
+
+    /// The name most recently assigned with `set_debug_name`
+    pub fn debug_name(&self) -> Option<String> {
+        self.debug_name.lock().unwrap().clone()
+    }
+
+    pub fn new_graphics_pipeline(
+        device_context: &RafxDeviceContextEmpty,
+        pipeline_def: &RafxGraphicsPipelineDef,
+    ) -> RafxResult<Self> {
+        if pipeline_def.root_signature.pipeline_type() != RafxPipelineType::Graphics {
+            return Err("Graphics pipelines require a graphics root signature")?;
+        }
+
+        Ok(RafxPipelineEmpty {
+            pipeline_type: RafxPipelineType::Graphics,
+            root_signature: pipeline_def.root_signature.clone(),
+            primitive_topology: Some(pipeline_def.primitive_topology),
+            _device_context: device_context.clone(),
+            debug_name: Default::default(),
+        })
+    }
+
+    pub fn new_compute_pipeline(
+        device_context: &RafxDeviceContextEmpty,
+        pipeline_def: &RafxComputePipelineDef,
+    ) -> RafxResult<Self> {
+        if pipeline_def.root_signature.pipeline_type() != RafxPipelineType::Compute {
+            return Err("Compute pipelines require a compute root signature")?;
+        }
+
+        Ok(RafxPipelineEmpty {
+            pipeline_type: RafxPipelineType::Compute,
+            root_signature: pipeline_def.root_signature.clone(),
+            primitive_topology: None,
+            _device_context: device_context.clone(),
+            debug_name: Default::default(),
+        })
+    }
+}
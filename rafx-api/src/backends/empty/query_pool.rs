@@ -0,0 +1,231 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxQueryPoolDef, RafxResult};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct EmptyQueryResults {
+    values: Vec<u64>,
+    available: Vec<bool>,
+}
+
+#[derive(Debug)]
+pub struct RafxQueryPoolEmptyInner {
+    device_context: RafxDeviceContextEmpty,
+    query_pool_def: RafxQueryPoolDef,
+    resource_id: u64,
+    results: Mutex<EmptyQueryResults>,
+}
+
+/// Results are written when the command buffer that contains the query is submitted. Timestamps
+/// are nanoseconds since the device was created, occlusion queries always report 0 samples, and
+/// pipeline statistics queries count the vertices of draws recorded between begin and end.
+#[derive(Debug, Clone)]
+pub struct RafxQueryPoolEmpty {
+    inner: Arc<RafxQueryPoolEmptyInner>,
+}
+
+impl RafxQueryPoolEmpty {
+    pub fn query_pool_def(&self) -> &RafxQueryPoolDef {
+        &self.inner.query_pool_def
+    }
+
+    pub fn resource_id(&self) -> u64 {
+        self.inner.resource_id
+    }
+
+    pub(crate) fn device_context(&self) -> &RafxDeviceContextEmpty {
+        &self.inner.device_context
+    }
+
+    pub(crate) fn values_per_query(&self) -> u32 {
+        self.inner.query_pool_def.query_type.values_per_query()
+    }
+
+    pub(crate) fn validate_range(
+        &self,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<()> {
+        if first_query + query_count > self.inner.query_pool_def.query_count {
+            Err(format!(
+                "Queries {}..{} are out of range (query pool has {} queries)",
+                first_query,
+                first_query + query_count,
+                self.inner.query_pool_def.query_count
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn reset(
+        &self,
+        first_query: u32,
+        query_count: u32,
+    ) {
+        let mut results = self.inner.results.lock().unwrap();
+        for available in
+            &mut results.available[first_query as usize..(first_query + query_count) as usize]
+        {
+            *available = false;
+        }
+    }
+
+    pub(crate) fn write(
+        &self,
+        query_index: u32,
+        values: &[u64],
+    ) {
+        let values_per_query = self.values_per_query() as usize;
+        assert_eq!(values.len(), values_per_query);
+
+        let mut results = self.inner.results.lock().unwrap();
+        let begin = query_index as usize * values_per_query;
+        results.values[begin..begin + values_per_query].copy_from_slice(values);
+        results.available[query_index as usize] = true;
+    }
+
+    // Unavailable queries read as zero
+    pub(crate) fn read(
+        &self,
+        first_query: u32,
+        query_count: u32,
+    ) -> Vec<u64> {
+        let values_per_query = self.values_per_query() as usize;
+        let results = self.inner.results.lock().unwrap();
+        let mut values = Vec::with_capacity(query_count as usize * values_per_query);
+        for query_index in first_query as usize..(first_query + query_count) as usize {
+            let begin = query_index * values_per_query;
+            if results.available[query_index] {
+                values.extend_from_slice(&results.values[begin..begin + values_per_query]);
+            } else {
+                values.resize(values.len() + values_per_query, 0);
+            }
+        }
+
+        values
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolEmpty> {
+        let query_count = query_pool_def.query_count as usize;
+        let values_per_query = query_pool_def.query_type.values_per_query() as usize;
+        let results = EmptyQueryResults {
+            values: vec![0; query_count * values_per_query],
+            available: vec![false; query_count],
+        };
+
+        let inner = RafxQueryPoolEmptyInner {
+            device_context: device_context.clone(),
+            query_pool_def: query_pool_def.clone(),
+            resource_id: device_context.allocate_resource_id(),
+            results: Mutex::new(results),
+        };
+
+        Ok(RafxQueryPoolEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn get_results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<Option<Vec<u64>>> {
+        self.validate_range(first_query, query_count)?;
+
+        let all_available = self.inner.results.lock().unwrap().available
+            [first_query as usize..(first_query + query_count) as usize]
+            .iter()
+            .all(|x| *x);
+        if !all_available {
+            return Ok(None);
+        }
+
+        Ok(Some(self.read(first_query, query_count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::empty::test_util::{create_command_buffer, test_device};
+    use crate::*;
+
+    #[test]
+    fn test_query_pool() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            let timestamps = device_context
+                .create_query_pool(&RafxQueryPoolDef {
+                    query_type: RafxQueryType::Timestamp,
+                    query_count: 2,
+                })
+                .unwrap();
+            let statistics = device_context
+                .create_query_pool(&RafxQueryPoolDef {
+                    query_type: RafxQueryType::PipelineStatistics,
+                    query_count: 1,
+                })
+                .unwrap();
+            let resolve_buffer = device_context
+                .create_buffer(&RafxBufferDef {
+                    size: 16,
+                    memory_usage: RafxMemoryUsage::GpuToCpu,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            command_buffer.begin().unwrap();
+            command_buffer
+                .cmd_reset_query_pool(&timestamps, 0, 2)
+                .unwrap();
+            command_buffer
+                .cmd_reset_query_pool(&statistics, 0, 1)
+                .unwrap();
+            command_buffer.cmd_write_timestamp(&timestamps, 0).unwrap();
+            command_buffer.cmd_begin_query(&statistics, 0).unwrap();
+            command_buffer.cmd_draw(3, 0).unwrap();
+            command_buffer.cmd_draw_instanced(6, 0, 2, 0).unwrap();
+            command_buffer.cmd_end_query(&statistics, 0).unwrap();
+            command_buffer.cmd_write_timestamp(&timestamps, 1).unwrap();
+            command_buffer
+                .cmd_resolve_queries(&timestamps, 0, 2, &resolve_buffer, 0)
+                .unwrap();
+            command_buffer.end().unwrap();
+
+            // Timestamps can only be written to a timestamp query pool
+            assert!(command_buffer.cmd_write_timestamp(&statistics, 0).is_err());
+
+            // Results are not available until the command buffer is submitted
+            assert!(timestamps.get_results(0, 2).unwrap().is_none());
+            queue.submit(&[&command_buffer], &[], &[], None).unwrap();
+
+            let results = timestamps.get_results(0, 2).unwrap().unwrap();
+            assert!(results[1] >= results[0]);
+
+            let resolved: Vec<u64> = resolve_buffer
+                .empty_buffer()
+                .unwrap()
+                .read_contents()
+                .chunks(8)
+                .map(|x| u64::from_ne_bytes([x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7]]))
+                .collect();
+            assert_eq!(resolved, results);
+
+            let results = statistics.get_results(0, 1).unwrap().unwrap();
+            let statistics = RafxPipelineStatistics::from_results(&results);
+            assert_eq!(statistics.input_assembly_vertices, 15);
+            assert_eq!(statistics.vertex_shader_invocations, 15);
+            assert!(timestamps.get_results(1, 2).is_err());
+        }
+        std::mem::drop(api);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::empty::{
+    RafxCommandBufferEmpty, RafxCommandEmpty, RafxCommandPoolEmpty, RafxDeviceContextEmpty,
+    RafxFenceEmpty, RafxSemaphoreEmpty, RafxSwapchainEmpty,
+};
+use crate::{RafxCommandPoolDef, RafxPresentSuccessResult, RafxQueueType, RafxResult};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+static NEXT_QUEUE_ID: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug)]
+pub struct RafxQueueEmptyInner {
+    device_context: RafxDeviceContextEmpty,
+    queue_type: RafxQueueType,
+    queue_id: u32,
+    // Every command submitted to this queue, in submission order
+    submitted_commands: Mutex<Vec<RafxCommandEmpty>>,
+}
+
+/// Command buffers submitted to this queue are executed immediately on the calling thread, so
+/// fences and semaphores are signaled as soon as `submit()` returns.
+#[derive(Clone, Debug)]
+pub struct RafxQueueEmpty {
+    inner: Arc<RafxQueueEmptyInner>,
+}
+
+impl RafxQueueEmpty {
+    pub fn queue_id(&self) -> u32 {
+        self.inner.queue_id
+    }
+
+    // There is a single queue family, so resources are never transferred between queues
+    pub fn queue_family_index(&self) -> u32 {
+        0
+    }
+
+    pub fn queue_type(&self) -> RafxQueueType {
+        self.inner.queue_type
+    }
+
+    pub fn device_context(&self) -> &RafxDeviceContextEmpty {
+        &self.inner.device_context
+    }
+
+    /// Returns all commands submitted to this queue since it was created or last cleared
+    pub fn submitted_commands(&self) -> Vec<RafxCommandEmpty> {
+        self.inner.submitted_commands.lock().unwrap().clone()
+    }
+
+    /// Returns all commands submitted to this queue and clears the log
+    pub fn take_submitted_commands(&self) -> Vec<RafxCommandEmpty> {
+        std::mem::take(&mut *self.inner.submitted_commands.lock().unwrap())
+    }
+
+    pub fn create_command_pool(
+        &self,
+        command_pool_def: &RafxCommandPoolDef,
+    ) -> RafxResult<RafxCommandPoolEmpty> {
+        RafxCommandPoolEmpty::new(self, command_pool_def)
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        queue_type: RafxQueueType,
+    ) -> RafxResult<RafxQueueEmpty> {
+        let queue_id = NEXT_QUEUE_ID.fetch_add(1, Ordering::Relaxed);
+        let inner = RafxQueueEmptyInner {
+            device_context: device_context.clone(),
+            queue_type,
+            queue_id,
+            submitted_commands: Default::default(),
+        };
+
+        Ok(RafxQueueEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn wait_for_queue_idle(&self) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn submit(
+        &self,
+        command_buffers: &[&RafxCommandBufferEmpty],
+        wait_semaphores: &[&RafxSemaphoreEmpty],
+        signal_semaphores: &[&RafxSemaphoreEmpty],
+        signal_fence: Option<&RafxFenceEmpty>,
+    ) -> RafxResult<()> {
+        assert!(!command_buffers.is_empty());
+
+        for wait_semaphore in wait_semaphores {
+            wait_semaphore.set_signal_available(false);
+        }
+
+        let mut submitted_commands = self.inner.submitted_commands.lock().unwrap();
+        for command_buffer in command_buffers {
+            submitted_commands.append(&mut command_buffer.execute());
+        }
+
+        for signal_semaphore in signal_semaphores {
+            signal_semaphore.set_signal_available(true);
+        }
+
+        if let Some(signal_fence) = signal_fence {
+            signal_fence.set_submitted(true);
+        }
+
+        Ok(())
+    }
+
+    pub fn present(
+        &self,
+        _swapchain: &RafxSwapchainEmpty,
+        wait_semaphores: &[&RafxSemaphoreEmpty],
+        _image_index: u32,
+    ) -> RafxResult<RafxPresentSuccessResult> {
+        for wait_semaphore in wait_semaphores {
+            wait_semaphore.set_signal_available(false);
+        }
+
+        Ok(RafxPresentSuccessResult::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::empty::test_util::{create_command_buffer, test_device};
+    use crate::empty::RafxCommandEmpty;
+    use crate::*;
+
+    #[test]
+    fn test_command_log() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+            let (_command_pool, command_buffer) = create_command_buffer(&queue);
+
+            command_buffer.begin().unwrap();
+            command_buffer.cmd_set_scissor(0, 0, 16, 16).unwrap();
+            command_buffer.cmd_draw(3, 0).unwrap();
+            command_buffer.cmd_dispatch(4, 2, 1).unwrap();
+            command_buffer.end().unwrap();
+
+            let semaphore = device_context.create_semaphore().unwrap();
+            queue
+                .submit(&[&command_buffer], &[], &[&semaphore], None)
+                .unwrap();
+            assert!(semaphore.empty_semaphore().unwrap().signal_available());
+
+            let queue = queue.empty_queue().unwrap();
+            let expected = vec![
+                RafxCommandEmpty::SetScissor {
+                    x: 0,
+                    y: 0,
+                    width: 16,
+                    height: 16,
+                },
+                RafxCommandEmpty::Draw {
+                    vertex_count: 3,
+                    first_vertex: 0,
+                    instance_count: 1,
+                    first_instance: 0,
+                },
+                RafxCommandEmpty::Dispatch {
+                    group_count_x: 4,
+                    group_count_y: 2,
+                    group_count_z: 1,
+                },
+            ];
+            assert_eq!(queue.submitted_commands(), expected);
+            assert_eq!(queue.take_submitted_commands(), expected);
+            assert!(queue.submitted_commands().is_empty());
+        }
+        std::mem::drop(api);
+    }
+}
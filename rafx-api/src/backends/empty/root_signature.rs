@@ -0,0 +1,206 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{
+    RafxDescriptorIndex, RafxDescriptorKey, RafxPipelineType, RafxPushConstantIndex,
+    RafxResourceType, RafxResult, RafxRootSignatureDef, RafxShaderStageFlags,
+    MAX_DESCRIPTOR_SET_LAYOUTS,
+};
+use fnv::FnvHashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+static NEXT_ROOT_SIGNATURE_ID: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Clone, Debug)]
+pub(crate) struct DescriptorInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) resource_type: RafxResourceType,
+    pub(crate) set_index: u32,
+    pub(crate) binding: u32,
+    pub(crate) element_count: u32,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct PushConstantInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) size_in_bytes: u32,
+    pub(crate) used_in_shader_stages: RafxShaderStageFlags,
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct DescriptorSetLayoutInfo {
+    // Indexes binding index to the descriptors list
+    pub(crate) binding_to_descriptor_index: FnvHashMap<u32, RafxDescriptorIndex>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RafxRootSignatureEmptyInner {
+    pub(crate) device_context: RafxDeviceContextEmpty,
+    pub(crate) pipeline_type: RafxPipelineType,
+    pub(crate) layouts: [DescriptorSetLayoutInfo; MAX_DESCRIPTOR_SET_LAYOUTS],
+    pub(crate) descriptors: Vec<DescriptorInfo>,
+    pub(crate) name_to_descriptor_index: FnvHashMap<String, RafxDescriptorIndex>,
+    pub(crate) push_constants: Vec<PushConstantInfo>,
+    pub(crate) name_to_push_constant_index: FnvHashMap<String, RafxPushConstantIndex>,
+    pub(crate) root_signature_id: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct RafxRootSignatureEmpty {
+    pub(crate) inner: Arc<RafxRootSignatureEmptyInner>,
+}
+
+impl PartialEq for RafxRootSignatureEmpty {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.inner.root_signature_id == other.inner.root_signature_id
+    }
+}
+
+impl RafxRootSignatureEmpty {
+    pub fn device_context(&self) -> &RafxDeviceContextEmpty {
+        &self.inner.device_context
+    }
+
+    pub fn pipeline_type(&self) -> RafxPipelineType {
+        self.inner.pipeline_type
+    }
+
+    pub fn find_descriptor_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxDescriptorIndex> {
+        self.inner.name_to_descriptor_index.get(name).copied()
+    }
+
+    pub fn find_descriptor_by_binding(
+        &self,
+        set_index: u32,
+        binding: u32,
+    ) -> Option<RafxDescriptorIndex> {
+        self.inner
+            .layouts
+            .get(set_index as usize)
+            .and_then(|x| x.binding_to_descriptor_index.get(&binding))
+            .copied()
+    }
+
+    pub(crate) fn find_descriptor(
+        &self,
+        set_index: u32,
+        key: &RafxDescriptorKey,
+    ) -> Option<RafxDescriptorIndex> {
+        match key {
+            RafxDescriptorKey::Undefined => None,
+            RafxDescriptorKey::Name(name) => self.find_descriptor_by_name(name),
+            RafxDescriptorKey::Binding(binding) => {
+                self.find_descriptor_by_binding(set_index, *binding)
+            }
+            RafxDescriptorKey::DescriptorIndex(descriptor_index) => Some(*descriptor_index),
+        }
+    }
+
+    pub(crate) fn descriptor(
+        &self,
+        descriptor_index: RafxDescriptorIndex,
+    ) -> Option<&DescriptorInfo> {
+        self.inner.descriptors.get(descriptor_index.0 as usize)
+    }
+
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        self.inner.name_to_push_constant_index.get(name).copied()
+    }
+
+    pub(crate) fn push_constant(
+        &self,
+        push_constant_index: RafxPushConstantIndex,
+    ) -> Option<&PushConstantInfo> {
+        self.inner
+            .push_constants
+            .get(push_constant_index.0 as usize)
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        root_signature_def: &RafxRootSignatureDef,
+    ) -> RafxResult<Self> {
+        log::trace!("Create RafxRootSignatureEmpty");
+
+        // Make sure all shaders are compatible/build lookup of shared data from them
+        let (pipeline_type, merged_resources, _merged_resources_name_index_map) =
+            crate::internal_shared::merge_resources(root_signature_def)?;
+        crate::internal_shared::validate_bindless_resources(
+            device_context.device_info(),
+            &merged_resources,
+        )?;
+
+        let mut layouts = [
+            DescriptorSetLayoutInfo::default(),
+            DescriptorSetLayoutInfo::default(),
+            DescriptorSetLayoutInfo::default(),
+            DescriptorSetLayoutInfo::default(),
+        ];
+
+        let mut descriptors = Vec::with_capacity(merged_resources.len());
+        let mut name_to_descriptor_index = FnvHashMap::default();
+        let mut push_constants = vec![];
+        let mut name_to_push_constant_index = FnvHashMap::default();
+
+        for resource in &merged_resources {
+            resource.validate()?;
+
+            // Push constants are not bound through descriptor sets
+            if resource.resource_type == RafxResourceType::ROOT_CONSTANT {
+                let push_constant_index = RafxPushConstantIndex(push_constants.len() as u32);
+                if let Some(name) = &resource.name {
+                    name_to_push_constant_index.insert(name.clone(), push_constant_index);
+                }
+
+                push_constants.push(PushConstantInfo {
+                    name: resource.name.clone(),
+                    size_in_bytes: resource.size_in_bytes,
+                    used_in_shader_stages: resource.used_in_shader_stages,
+                });
+                continue;
+            }
+
+            let descriptor_index = RafxDescriptorIndex(descriptors.len() as u32);
+            layouts[resource.set_index as usize]
+                .binding_to_descriptor_index
+                .insert(resource.binding, descriptor_index);
+
+            if let Some(name) = &resource.name {
+                name_to_descriptor_index.insert(name.clone(), descriptor_index);
+            }
+
+            descriptors.push(DescriptorInfo {
+                name: resource.name.clone(),
+                resource_type: resource.resource_type,
+                set_index: resource.set_index,
+                binding: resource.binding,
+                element_count: resource.element_count_normalized(),
+            });
+        }
+
+        let root_signature_id = NEXT_ROOT_SIGNATURE_ID.fetch_add(1, Ordering::Relaxed);
+
+        let inner = RafxRootSignatureEmptyInner {
+            device_context: device_context.clone(),
+            pipeline_type,
+            layouts,
+            descriptors,
+            name_to_descriptor_index,
+            push_constants,
+            name_to_push_constant_index,
+            root_signature_id,
+        };
+
+        Ok(RafxRootSignatureEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+}
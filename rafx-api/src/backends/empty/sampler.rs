@@ -0,0 +1,32 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxResult, RafxSamplerDef};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct RafxSamplerEmptyInner {
+    pub(crate) sampler_def: RafxSamplerDef,
+}
+
+#[derive(Debug, Clone)]
+pub struct RafxSamplerEmpty {
+    pub(crate) inner: Arc<RafxSamplerEmptyInner>,
+}
+
+impl RafxSamplerEmpty {
+    pub fn sampler_def(&self) -> &RafxSamplerDef {
+        &self.inner.sampler_def
+    }
+
+    pub fn new(
+        _device_context: &RafxDeviceContextEmpty,
+        sampler_def: &RafxSamplerDef,
+    ) -> RafxResult<RafxSamplerEmpty> {
+        let inner = RafxSamplerEmptyInner {
+            sampler_def: sampler_def.clone(),
+        };
+
+        Ok(RafxSamplerEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+}
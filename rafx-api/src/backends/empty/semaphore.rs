@@ -0,0 +1,32 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::RafxResult;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct RafxSemaphoreEmpty {
+    _device_context: RafxDeviceContextEmpty,
+
+    // Set to true when an operation is scheduled to signal this semaphore
+    // Cleared when an operation is scheduled to consume this semaphore
+    signal_available: AtomicBool,
+}
+
+impl RafxSemaphoreEmpty {
+    pub fn new(device_context: &RafxDeviceContextEmpty) -> RafxResult<RafxSemaphoreEmpty> {
+        // All work completes during submit, so semaphores only track whether they were signaled
+        Ok(RafxSemaphoreEmpty {
+            _device_context: device_context.clone(),
+            signal_available: AtomicBool::new(false),
+        })
+    }
+
+    pub fn signal_available(&self) -> bool {
+        self.signal_available.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_signal_available(
+        &self,
+        available: bool,
+    ) {
+        self.signal_available.store(available, Ordering::Relaxed);
+    }
+}
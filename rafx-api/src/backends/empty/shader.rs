@@ -0,0 +1,52 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxPipelineReflection, RafxResult, RafxShaderStageDef, RafxShaderStageFlags};
+use std::sync::Arc;
+
+#[derive(Debug)]
+struct RafxShaderEmptyInner {
+    _device_context: RafxDeviceContextEmpty,
+    stage_flags: RafxShaderStageFlags,
+    stages: Vec<RafxShaderStageDef>,
+    pipeline_reflection: RafxPipelineReflection,
+}
+
+#[derive(Clone, Debug)]
+pub struct RafxShaderEmpty {
+    inner: Arc<RafxShaderEmptyInner>,
+}
+
+impl RafxShaderEmpty {
+    pub fn stages(&self) -> &[RafxShaderStageDef] {
+        &self.inner.stages
+    }
+
+    pub fn pipeline_reflection(&self) -> &RafxPipelineReflection {
+        &self.inner.pipeline_reflection
+    }
+
+    pub fn stage_flags(&self) -> RafxShaderStageFlags {
+        self.inner.stage_flags
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        stages: Vec<RafxShaderStageDef>,
+    ) -> RafxResult<Self> {
+        let pipeline_reflection = RafxPipelineReflection::from_stages(&stages)?;
+        let mut stage_flags = RafxShaderStageFlags::empty();
+        for stage in &stages {
+            stage_flags |= stage.reflection.shader_stage;
+        }
+
+        let inner = RafxShaderEmptyInner {
+            _device_context: device_context.clone(),
+            stages,
+            pipeline_reflection,
+            stage_flags,
+        };
+
+        Ok(RafxShaderEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+}
@@ -0,0 +1,19 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxResult, RafxShaderModuleDefEmpty};
+
+/// Shader modules are accepted but never executed by the empty backend
+#[derive(Clone, Debug)]
+pub struct RafxShaderModuleEmpty {
+    _device_context: RafxDeviceContextEmpty,
+}
+
+impl RafxShaderModuleEmpty {
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        _data: RafxShaderModuleDefEmpty,
+    ) -> RafxResult<Self> {
+        Ok(RafxShaderModuleEmpty {
+            _device_context: device_context.clone(),
+        })
+    }
+}
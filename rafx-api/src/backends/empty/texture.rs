@@ -0,0 +1,265 @@
+use crate::empty::RafxDeviceContextEmpty;
+use crate::{RafxExtents3D, RafxFormat, RafxResult, RafxTextureDef, RafxTextureDimensions};
+use rafx_base::trust_cell::TrustCell;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Describes where a single mip level of a single array layer is stored within the host memory of
+/// a `RafxTextureEmpty`. Rows are tightly packed, matching the layout expected by
+/// `cmd_copy_buffer_to_texture`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RafxSubresourceLayoutEmpty {
+    pub offset: u64,
+    pub size: u64,
+    pub row_pitch: u64,
+    pub row_count: u32,
+    pub depth: u32,
+    pub extents: RafxExtents3D,
+}
+
+fn mip_extents(
+    extents: &RafxExtents3D,
+    mip_level: u32,
+) -> RafxExtents3D {
+    RafxExtents3D {
+        width: (extents.width >> mip_level).max(1),
+        height: (extents.height >> mip_level).max(1),
+        depth: (extents.depth >> mip_level).max(1),
+    }
+}
+
+fn calculate_subresource_layouts(
+    texture_def: &RafxTextureDef
+) -> (Vec<RafxSubresourceLayoutEmpty>, u64) {
+    let format = texture_def.format;
+    let block_size = format.block_or_pixel_size_in_bytes() as u64;
+    let block_width = format.block_width_in_pixels();
+    let block_height = format.block_height_in_pixels();
+
+    let mut layouts =
+        Vec::with_capacity((texture_def.array_length * texture_def.mip_count) as usize);
+    let mut offset = 0;
+    for _ in 0..texture_def.array_length {
+        for mip_level in 0..texture_def.mip_count {
+            let extents = mip_extents(&texture_def.extents, mip_level);
+            let row_pitch = extents.width.div_ceil(block_width) as u64 * block_size;
+            let row_count = extents.height.div_ceil(block_height);
+            let size = row_pitch * row_count as u64 * extents.depth as u64;
+
+            layouts.push(RafxSubresourceLayoutEmpty {
+                offset,
+                size,
+                row_pitch,
+                row_count,
+                depth: extents.depth,
+                extents,
+            });
+
+            offset += size;
+        }
+    }
+
+    (layouts, offset)
+}
+
+// Host memory shared between a texture and any command buffers that reference it
+#[derive(Debug)]
+pub(crate) struct EmptyTextureContentsInner {
+    data: TrustCell<Box<[u8]>>,
+    layouts: Vec<RafxSubresourceLayoutEmpty>,
+    mip_count: u32,
+    resource_id: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct EmptyTextureContents {
+    inner: Arc<EmptyTextureContentsInner>,
+}
+
+impl EmptyTextureContents {
+    pub fn resource_id(&self) -> u64 {
+        self.inner.resource_id
+    }
+
+    pub fn size(&self) -> u64 {
+        self.inner.data.borrow().len() as u64
+    }
+
+    pub fn subresource_layout(
+        &self,
+        array_layer: u32,
+        mip_level: u32,
+    ) -> Option<RafxSubresourceLayoutEmpty> {
+        if mip_level >= self.inner.mip_count {
+            return None;
+        }
+
+        self.inner
+            .layouts
+            .get((array_layer * self.inner.mip_count + mip_level) as usize)
+            .copied()
+    }
+
+    pub fn read(
+        &self,
+        offset: u64,
+        dst: &mut [u8],
+    ) {
+        let data = self.inner.data.borrow();
+        dst.copy_from_slice(&data.value[offset as usize..offset as usize + dst.len()]);
+    }
+
+    pub fn write(
+        &self,
+        offset: u64,
+        src: &[u8],
+    ) {
+        let mut data = self.inner.data.borrow_mut();
+        data[offset as usize..offset as usize + src.len()].copy_from_slice(src);
+    }
+}
+
+#[derive(Debug)]
+pub struct RafxTextureEmptyInner {
+    device_context: RafxDeviceContextEmpty,
+    texture_def: RafxTextureDef,
+    contents: EmptyTextureContents,
+    debug_name: Mutex<Option<String>>,
+}
+
+impl Drop for RafxTextureEmptyInner {
+    fn drop(&mut self) {
+        self.device_context
+            .remove_allocated_memory(self.contents.size());
+    }
+}
+
+/// A texture backed by host memory. Every array layer and mip level is stored with tightly packed
+/// rows.
+#[derive(Clone, Debug)]
+pub struct RafxTextureEmpty {
+    inner: Arc<RafxTextureEmptyInner>,
+}
+
+impl PartialEq for RafxTextureEmpty {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.resource_id() == other.resource_id()
+    }
+}
+
+impl Eq for RafxTextureEmpty {}
+
+impl Hash for RafxTextureEmpty {
+    fn hash<H: Hasher>(
+        &self,
+        state: &mut H,
+    ) {
+        self.resource_id().hash(state);
+    }
+}
+
+impl RafxTextureEmpty {
+    pub fn texture_def(&self) -> &RafxTextureDef {
+        &self.inner.texture_def
+    }
+
+    pub fn device_context(&self) -> &RafxDeviceContextEmpty {
+        &self.inner.device_context
+    }
+
+    /// The size of the host memory backing all array layers and mip levels of this texture
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.inner.contents.size()
+    }
+
+    /// A unique ID used to identify this texture in the command log
+    pub fn resource_id(&self) -> u64 {
+        self.inner.contents.resource_id()
+    }
+
+    pub(crate) fn texture_contents(&self) -> &EmptyTextureContents {
+        &self.inner.contents
+    }
+
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        *self.inner.debug_name.lock().unwrap() = Some(name.as_ref().to_string());
+        Ok(())
+    }
+
+    /// The name most recently assigned with `set_debug_name`
+    pub fn debug_name(&self) -> Option<String> {
+        self.inner.debug_name.lock().unwrap().clone()
+    }
+
+    /// Returns where the given array layer and mip level is stored in host memory
+    pub fn subresource_layout(
+        &self,
+        array_layer: u32,
+        mip_level: u32,
+    ) -> Option<RafxSubresourceLayoutEmpty> {
+        self.inner
+            .contents
+            .subresource_layout(array_layer, mip_level)
+    }
+
+    /// Copies the current contents of a single array layer and mip level. Intended for inspecting
+    /// results in tests.
+    pub fn read_subresource(
+        &self,
+        array_layer: u32,
+        mip_level: u32,
+    ) -> Option<Vec<u8>> {
+        let layout = self.subresource_layout(array_layer, mip_level)?;
+        let mut data = vec![0_u8; layout.size as usize];
+        self.inner.contents.read(layout.offset, &mut data);
+        Some(data)
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextEmpty,
+        texture_def: &RafxTextureDef,
+    ) -> RafxResult<RafxTextureEmpty> {
+        texture_def.verify();
+
+        if texture_def.format == RafxFormat::UNDEFINED {
+            return Err("Cannot create a texture with an undefined format")?;
+        }
+
+        let mut texture_def = texture_def.clone();
+        if texture_def.dimensions == RafxTextureDimensions::Auto {
+            texture_def.dimensions = texture_def
+                .dimensions
+                .determine_dimensions(texture_def.extents);
+        }
+
+        let (layouts, total_size) = calculate_subresource_layouts(&texture_def);
+        let inner = EmptyTextureContentsInner {
+            data: TrustCell::new(vec![0_u8; total_size as usize].into_boxed_slice()),
+            layouts,
+            mip_count: texture_def.mip_count,
+            resource_id: device_context.allocate_resource_id(),
+        };
+
+        let contents = EmptyTextureContents {
+            inner: Arc::new(inner),
+        };
+        device_context.add_allocated_memory(contents.size());
+
+        let inner = RafxTextureEmptyInner {
+            device_context: device_context.clone(),
+            texture_def,
+            contents,
+            debug_name: Default::default(),
+        };
+
+        Ok(RafxTextureEmpty {
+            inner: Arc::new(inner),
+        })
+    }
+}
@@ -2,14 +2,17 @@ use crate::gles2::{
     AttributeEnabledBits, BoundDescriptorSet, BoundVertexBuffer, CommandPoolGles2State,
     CommandPoolGles2StateInner, DescriptorSetArrayData, GlContext, Gles2PipelineInfo,
     RafxBufferGles2, RafxCommandPoolGles2, RafxDescriptorSetArrayGles2,
-    RafxDescriptorSetHandleGles2, RafxPipelineGles2, RafxQueueGles2, RafxRootSignatureGles2,
-    RafxTextureGles2, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM, NONE_TEXTURE,
+    RafxDescriptorSetHandleGles2, RafxPipelineGles2, RafxQueryPoolGles2, RafxQueueGles2,
+    RafxRootSignatureGles2, RafxTextureGles2, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM,
+    NONE_TEXTURE,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorFlags,
-    RafxColorRenderTargetBinding, RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding,
-    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxResourceType, RafxResult,
-    RafxTextureBarrier, RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxBufferBarrier, RafxCmdBlitParams, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorFlags, RafxColorRenderTargetBinding,
+    RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding, RafxExtents3D,
+    RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPushConstantIndex, RafxResourceType,
+    RafxResult, RafxSubpassContents, RafxSubpassDef, RafxTextureBarrier, RafxVertexBufferBinding,
+    MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 
 use rafx_base::trust_cell::TrustCell;
@@ -51,6 +54,13 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    pub fn begin_secondary(
+        &self,
+        _primary_command_buffer: &RafxCommandBufferGles2,
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported in GL ES 2.0")?
+    }
+
     pub fn end(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -66,6 +76,7 @@ impl RafxCommandBufferGles2 {
         state.current_gl_pipeline_info = None;
         state.stencil_reference_value = 0;
         state.clear_bindings();
+        state.clear_push_constants();
         Self::update_vertex_attributes_in_use(gl_context, &mut *state, 0)?;
         assert_eq!(state.vertex_attribute_enabled_bits, 0);
         for attribute in &mut state.vertex_attributes {
@@ -288,6 +299,37 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Subpasses are not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
+    ) -> RafxResult<()> {
+        if contents != RafxSubpassContents::Inline {
+            Err("Secondary command buffers are not supported in GL ES 2.0")?;
+        }
+
+        if subpasses.is_empty() {
+            self.cmd_begin_render_pass(color_targets, depth_target)
+        } else {
+            self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+        }
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Subpasses are not supported in GL ES 2.0")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -299,6 +341,40 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        _command_buffers: &[&RafxCommandBufferGles2],
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_push_debug_group(name)
+    }
+
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_pop_debug_group()
+    }
+
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_insert_debug_marker(name)
+    }
+
     pub fn cmd_set_viewport(
         &self,
         x: f32,
@@ -731,6 +807,53 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    // Like descriptor sets, this does not affect the program right away. The uniforms are updated
+    // when we try to draw
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignatureGles2,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        let mut state = self.command_pool_state.borrow_mut();
+        assert!(state.is_started);
+
+        let push_constant = root_signature
+            .push_constant(push_constant_index)
+            .ok_or("Push constant index not found in root signature")?;
+
+        crate::internal_shared::validate_push_constant_data(
+            push_constant.name.as_deref(),
+            push_constant.size_in_bytes,
+            data,
+        )?;
+
+        if state.bound_push_constants_root_signature.as_ref() != Some(root_signature) {
+            state.clear_push_constants();
+            state.bound_push_constants_root_signature = Some(root_signature.clone());
+        }
+
+        if state.bound_push_constants.len() <= push_constant_index.0 as usize {
+            state
+                .bound_push_constants
+                .resize(push_constant_index.0 as usize + 1, None);
+        }
+
+        // Data that is shorter than the push constant only overwrites the start of it, leaving the
+        // rest of the previously pushed values intact. The storage is padded to 16 bytes because
+        // gl_type_util::byte_size_of_type() treats vec3 as 16 bytes.
+        let words =
+            state.bound_push_constants[push_constant_index.0 as usize].get_or_insert_with(|| {
+                vec![0; (push_constant.size_in_bytes as usize).div_ceil(16) * 4]
+            });
+        for (word, bytes) in words.iter_mut().zip(data.chunks_exact(4)) {
+            *word = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        state.push_constants_update_index += 1;
+        Ok(())
+    }
+
     // This does not affect the program right away, we wait until we try to draw, then update the
     // program as necessary
     fn set_current_descriptor_set(
@@ -771,6 +894,7 @@ impl RafxCommandBufferGles2 {
         // If the program was previously bound by some other command pool, we can't assume it's in
         // the same state as before. Clear the last_descriptor_updates values to ensure that we push
         // all sets into the program state
+        let mut last_push_constants_update = pipeline.last_push_constants_update.borrow_mut();
         let mut last_bound_by_command_pool = pipeline.last_bound_by_command_pool.borrow_mut();
         if *last_bound_by_command_pool != state.id {
             *last_bound_by_command_pool = state.id;
@@ -778,6 +902,20 @@ impl RafxCommandBufferGles2 {
             for set_index in 0..MAX_DESCRIPTOR_SET_LAYOUTS {
                 last_descriptor_updates[set_index] = 0;
             }
+
+            *last_push_constants_update = 0;
+        }
+
+        if let Some(bound_push_constants_root_signature) =
+            &state.bound_push_constants_root_signature
+        {
+            // Only update the program if the pushed constants match the root signature
+            if *bound_push_constants_root_signature == pipeline.root_signature
+                && *last_push_constants_update < state.push_constants_update_index
+            {
+                Self::do_set_push_constants(gl_context, pipeline, &state.bound_push_constants)?;
+                *last_push_constants_update = state.push_constants_update_index;
+            }
         }
 
         if let Some(bound_descriptor_sets_root_signature) =
@@ -809,6 +947,48 @@ impl RafxCommandBufferGles2 {
         Ok(())
     }
 
+    fn do_set_push_constants(
+        gl_context: &GlContext,
+        pipeline_info: &Arc<Gles2PipelineInfo>,
+        bound_push_constants: &[Option<Vec<u32>>],
+    ) -> RafxResult<()> {
+        let root_signature = &pipeline_info.root_signature;
+        let uniform_reflection_data = root_signature.uniform_reflection_data();
+        for (push_constant, data) in root_signature
+            .inner
+            .push_constants
+            .iter()
+            .zip(bound_push_constants)
+        {
+            if let (Some(uniform_index), Some(data)) = (push_constant.uniform_index, data) {
+                let uniform_data_ptr = data.as_ptr() as *const u8;
+                for field in uniform_reflection_data.uniform_fields(uniform_index) {
+                    // Skip anything that lies outside the push constant's active range
+                    let field_end = field.offset
+                        + gl_type_util::byte_size_of_type(field.ty) * field.element_count;
+                    if field_end as usize > data.len() * 4 {
+                        continue;
+                    }
+
+                    if let Some(location) = pipeline_info.uniform_member_location(field.field_index)
+                    {
+                        let field_ref = unsafe { &*uniform_data_ptr.add(field.offset as usize) };
+
+                        gl_type_util::set_uniform(
+                            gl_context,
+                            location,
+                            field_ref,
+                            field.ty,
+                            field.element_count,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     // Does the actual descriptor set binding
     fn do_bind_descriptor_set(
         gl_context: &GlContext,
@@ -1059,6 +1239,54 @@ impl RafxCommandBufferGles2 {
         unimplemented!("Compute shaders not supported in GL ES 2.0");
     }
 
+    pub fn cmd_draw_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles2,
+        _indirect_buffer_offset_in_bytes: u64,
+        _draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indirect is not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles2,
+        _indirect_buffer_offset_in_bytes: u64,
+        _draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indexed_indirect is not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_draw_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferGles2,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferGles2,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indirect_count is not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferGles2,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferGles2,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indexed_indirect_count is not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_dispatch_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles2,
+        _indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        Err("cmd_dispatch_indirect is not supported in GL ES 2.0")?
+    }
+
     pub fn cmd_resource_barrier(
         &self,
         _buffer_barriers: &[RafxBufferBarrier],
@@ -1142,4 +1370,139 @@ impl RafxCommandBufferGles2 {
         )?;
         gl_context.gl_bind_texture(dst_texture.gl_target(), NONE_TEXTURE)
     }
+
+    pub fn cmd_blit_image(
+        &self,
+        _src_texture: &RafxTextureGles2,
+        _dst_texture: &RafxTextureGles2,
+        _params: &RafxCmdBlitParams,
+    ) -> RafxResult<()> {
+        Err("Blitting images is not supported in GL ES 2.0")?
+    }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureGles2,
+        dst_buffer: &RafxBufferGles2,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let mut state = self.command_pool_state.borrow_mut();
+        assert!(state.is_started);
+        assert!(state.surface_size.is_none());
+
+        let gl_context = self.queue.device_context().gl_context();
+
+        let width = 1.max(src_texture.texture_def().extents.width >> params.mip_level);
+        let height = 1.max(src_texture.texture_def().extents.height >> params.mip_level);
+
+        let format_info = src_texture.gl_format_info();
+
+        if src_texture.gl_raw_image().gl_texture_id().is_none() {
+            Err("Cannot use cmd_copy_texture_to_buffer with swapchain image in GL ES")?;
+        }
+
+        let mut buffer_contents = dst_buffer
+            .buffer_contents()
+            .try_borrow_mut()
+            .ok_or("dst buffer must be CPU-visible in cmd_copy_texture_to_buffer")?;
+        let buffer_slice = buffer_contents
+            .get_mut(params.buffer_offset as usize..)
+            .ok_or(
+                "buffer_offset is past the end of the dst buffer in cmd_copy_texture_to_buffer",
+            )?;
+
+        // glReadPixels writes the whole image without knowing the size of the destination
+        let format = src_texture.texture_def().format;
+        let row_pitch = crate::internal_shared::texture_copy_row_pitch(
+            self.queue.device_context().device_info(),
+            format,
+            width,
+        ) as usize;
+        let required_size = row_pitch * (height as usize - 1)
+            + (width * format.block_or_pixel_size_in_bytes()) as usize;
+        if buffer_slice.len() < required_size {
+            Err(format!(
+                "dst buffer is too small for cmd_copy_texture_to_buffer, {} bytes are required after the offset but only {} are available",
+                required_size,
+                buffer_slice.len()
+            ))?;
+        }
+
+        // Read back by attaching the texture to the command pool's framebuffer. glReadPixels
+        // writes rows using PACK_ALIGNMENT, which is reported as the row alignment
+        gl_context.gl_bind_framebuffer(gles2_bindings::FRAMEBUFFER, state.framebuffer_id)?;
+        Self::bind_framebuffer(
+            gl_context,
+            src_texture,
+            gles2_bindings::COLOR_ATTACHMENT0,
+            params.array_layer,
+            params.mip_level,
+        )?;
+        state.framebuffer_color_bound[0] = true;
+
+        let result = gl_context.gl_check_framebuffer_status(gles2_bindings::FRAMEBUFFER)?;
+        if result != gles2_bindings::FRAMEBUFFER_COMPLETE {
+            gl_context.gl_bind_framebuffer(gles2_bindings::FRAMEBUFFER, NONE_FRAMEBUFFER)?;
+            Err(format!(
+                "Framebuffer Status is not FRAMEBUFFER_COMPLETE, result: {:#x}",
+                result
+            ))?;
+        }
+
+        gl_context.gl_read_pixels(
+            0,
+            0,
+            width,
+            height,
+            format_info.gl_format,
+            format_info.gl_type,
+            buffer_slice,
+        )?;
+
+        gl_context.gl_bind_framebuffer(gles2_bindings::FRAMEBUFFER, NONE_FRAMEBUFFER)
+    }
+
+    pub fn cmd_reset_query_pool(
+        &self,
+        _query_pool: &RafxQueryPoolGles2,
+        _first_query: u32,
+        _query_count: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+
+    pub fn cmd_write_timestamp(
+        &self,
+        _query_pool: &RafxQueryPoolGles2,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+
+    pub fn cmd_begin_query(
+        &self,
+        _query_pool: &RafxQueryPoolGles2,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+
+    pub fn cmd_end_query(
+        &self,
+        _query_pool: &RafxQueryPoolGles2,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+
+    pub fn cmd_resolve_queries(
+        &self,
+        _query_pool: &RafxQueryPoolGles2,
+        _first_query: u32,
+        _query_count: u32,
+        _dst_buffer: &RafxBufferGles2,
+        _dst_offset: u64,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
 }
@@ -45,6 +45,12 @@ pub(crate) struct CommandPoolGles2StateInner {
     pub(crate) bound_descriptor_sets_root_signature: Option<RafxRootSignatureGles2>,
     pub(crate) descriptor_sets_update_index: [u64; MAX_DESCRIPTOR_SET_LAYOUTS],
 
+    // Indexed by push constant index. Stored as u32s so that the data is suitably aligned to be
+    // passed to glUniform*
+    pub(crate) bound_push_constants: Vec<Option<Vec<u32>>>,
+    pub(crate) bound_push_constants_root_signature: Option<RafxRootSignatureGles2>,
+    pub(crate) push_constants_update_index: u64,
+
     // One per possible bound vertex buffer (could be 1 per attribute!)
     pub(crate) vertex_attribute_enabled_bits: AttributeEnabledBits,
     // Holds the currently bound attribute metadata
@@ -98,6 +104,12 @@ impl CommandPoolGles2StateInner {
             self.bound_descriptor_sets_root_signature = None;
         }
     }
+
+    pub(crate) fn clear_push_constants(&mut self) {
+        self.bound_push_constants.clear();
+        self.bound_push_constants_root_signature = None;
+        self.push_constants_update_index += 1;
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -129,6 +141,9 @@ impl CommandPoolGles2State {
             bound_descriptor_sets: Default::default(),
             bound_descriptor_sets_root_signature: None,
             descriptor_sets_update_index: Default::default(),
+            bound_push_constants: Default::default(),
+            bound_push_constants_root_signature: None,
+            push_constants_update_index: 0,
             framebuffer_id,
         };
 
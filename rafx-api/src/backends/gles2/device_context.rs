@@ -1,17 +1,18 @@
 use crate::{
     RafxApiDefGles2, RafxBufferDef, RafxComputePipelineDef, RafxDescriptorSetArrayDef,
-    RafxDeviceContext, RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxQueueType,
-    RafxResourceType, RafxResult, RafxRootSignatureDef, RafxSampleCount, RafxSamplerDef,
-    RafxShaderModuleDefGles2, RafxShaderStageDef, RafxSwapchainDef, RafxTextureDef,
+    RafxDeviceContext, RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxMemoryBudget,
+    RafxQueryPoolDef, RafxQueueType, RafxResourceType, RafxResult, RafxRootSignatureDef,
+    RafxSampleCount, RafxSamplerDef, RafxShaderModuleDefGles2, RafxShaderStageDef,
+    RafxSwapchainDef, RafxTextureDef,
 };
 use raw_window_handle::HasRawWindowHandle;
 use std::sync::Arc;
 
 use crate::gles2::{
     GlContextManager, RafxBufferGles2, RafxDescriptorSetArrayGles2, RafxFenceGles2,
-    RafxPipelineGles2, RafxQueueGles2, RafxRootSignatureGles2, RafxSamplerGles2,
-    RafxSemaphoreGles2, RafxShaderGles2, RafxShaderModuleGles2, RafxSwapchainGles2,
-    RafxTextureGles2,
+    RafxPipelineGles2, RafxQueryPoolGles2, RafxQueueGles2, RafxRootSignatureGles2,
+    RafxSamplerGles2, RafxSemaphoreGles2, RafxShaderGles2, RafxShaderModuleGles2,
+    RafxSwapchainGles2, RafxTextureGles2,
 };
 
 use crate::gles2::gles2_bindings;
@@ -85,6 +86,25 @@ impl RafxDeviceContextGles2Inner {
             upload_buffer_texture_row_alignment: pack_alignment,
             supports_clamp_to_border_color: false, // requires GLES 3.2 or an extension
             max_vertex_attribute_count,
+            // Query pools are not supported by the GL backends
+            timestamp_period: 1.0,
+            supports_timestamp_queries: false,
+            supports_occlusion_queries: false,
+            supports_pipeline_statistics_queries: false,
+            // Indirect draws and compute require GL ES 3.1
+            supports_draw_indirect: false,
+            supports_multi_draw_indirect: false,
+            supports_draw_indirect_count: false,
+            supports_dispatch_indirect: false,
+            supports_transient_attachments: false,
+            supports_resource_aliasing: false,
+            supports_subpasses: false,
+            supports_secondary_command_buffers: false,
+            supports_blit_images: false,
+            supports_pipeline_cache_data: false,
+            // GL ES has no equivalent of descriptor indexing
+            supports_bindless_descriptors: false,
+            max_bindless_descriptor_count: 0,
         };
 
         let fullscreen_quad = FullscreenQuad::new(&gl_context)?;
@@ -192,6 +212,23 @@ impl RafxDeviceContextGles2 {
         &self.inner.device_info
     }
 
+    // Pipeline caches are not supported by GL ES 2.0, so there is never any data to persist
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        Ok(Vec::default())
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        _data: &[u8],
+    ) -> RafxResult<bool> {
+        Ok(false)
+    }
+
+    // GL does not expose memory heaps or a budget
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        Ok(RafxMemoryBudget::default())
+    }
+
     pub fn gl_context(&self) -> &GlContext {
         &self.inner.gl_context
     }
@@ -255,6 +292,13 @@ impl RafxDeviceContextGles2 {
         RafxSamplerGles2::new(self, sampler_def)
     }
 
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolGles2> {
+        RafxQueryPoolGles2::new(self, query_pool_def)
+    }
+
     pub fn create_texture(
         &self,
         texture_def: &RafxTextureDef,
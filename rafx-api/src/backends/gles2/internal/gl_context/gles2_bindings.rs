@@ -3,6 +3,7 @@
 // - Accepts GL_OES_vertex_array_object function names without OES on the end of them. This ensures
 //   these functions are available when running on desktop GL
 // - GetStringi: required to support finding extension information on desktop GL
+// - Adds debug functions which may not be present on all devices
 
 mod __gl_imports {
     pub use std::marker::Send;
@@ -200,6 +201,8 @@ pub const BOOL_VEC3: types::GLenum = 0x8B58;
 #[allow(dead_code, non_upper_case_globals)]
 pub const BOOL_VEC4: types::GLenum = 0x8B59;
 #[allow(dead_code, non_upper_case_globals)]
+pub const BUFFER: types::GLenum = 0x82E0;
+#[allow(dead_code, non_upper_case_globals)]
 pub const BUFFER_SIZE: types::GLenum = 0x8764;
 #[allow(dead_code, non_upper_case_globals)]
 pub const BUFFER_USAGE: types::GLenum = 0x8765;
@@ -535,6 +538,8 @@ pub const POLYGON_OFFSET_FILL: types::GLenum = 0x8037;
 #[allow(dead_code, non_upper_case_globals)]
 pub const POLYGON_OFFSET_UNITS: types::GLenum = 0x2A00;
 #[allow(dead_code, non_upper_case_globals)]
+pub const PROGRAM: types::GLenum = 0x82E2;
+#[allow(dead_code, non_upper_case_globals)]
 pub const RED_BITS: types::GLenum = 0x0D52;
 #[allow(dead_code, non_upper_case_globals)]
 pub const RENDERBUFFER: types::GLenum = 0x8D41;
@@ -1001,8 +1006,17 @@ pub struct Gles2 {
     pub LineWidth: FnPtr,
     /// Fallbacks: LinkProgramARB
     pub LinkProgram: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: ObjectLabelKHR
+    pub ObjectLabel: FnPtr,
     pub PixelStorei: FnPtr,
     pub PolygonOffset: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: PopDebugGroupKHR
+    pub PopDebugGroup: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: PushDebugGroupKHR
+    pub PushDebugGroup: FnPtr,
     pub ReadPixels: FnPtr,
     pub ReleaseShaderCompiler: FnPtr,
     /// Fallbacks: RenderbufferStorageEXT
@@ -1313,8 +1327,11 @@ impl Gles2 {
             )),
             LineWidth: FnPtr::new(metaloadfn("glLineWidth", &[])),
             LinkProgram: FnPtr::new(metaloadfn("glLinkProgram", &["glLinkProgramARB"])),
+            ObjectLabel: FnPtr::new(metaloadfn("glObjectLabel", &["glObjectLabelKHR"])),
             PixelStorei: FnPtr::new(metaloadfn("glPixelStorei", &[])),
             PolygonOffset: FnPtr::new(metaloadfn("glPolygonOffset", &[])),
+            PopDebugGroup: FnPtr::new(metaloadfn("glPopDebugGroup", &["glPopDebugGroupKHR"])),
+            PushDebugGroup: FnPtr::new(metaloadfn("glPushDebugGroup", &["glPushDebugGroupKHR"])),
             ReadPixels: FnPtr::new(metaloadfn("glReadPixels", &[])),
             ReleaseShaderCompiler: FnPtr::new(metaloadfn("glReleaseShaderCompiler", &[])),
             RenderbufferStorage: FnPtr::new(metaloadfn(
@@ -2729,6 +2746,30 @@ impl Gles2 {
         )(program)
     }
     #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn ObjectLabel(
+        &self,
+        identifier: types::GLenum,
+        name: types::GLuint,
+        length: types::GLsizei,
+        label: *const types::GLchar,
+    ) -> () {
+        __gl_imports::mem::transmute::<
+            _,
+            extern "system" fn(
+                types::GLenum,
+                types::GLuint,
+                types::GLsizei,
+                *const types::GLchar,
+            ) -> (),
+        >(self.ObjectLabel.f)(identifier, name, length, label)
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
     #[inline]
     pub unsafe fn PixelStorei(
         &self,
@@ -2751,6 +2792,40 @@ impl Gles2 {
         )(factor, units)
     }
     #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn PopDebugGroup(&self) -> () {
+        __gl_imports::mem::transmute::<_, extern "system" fn() -> ()>(self.PopDebugGroup.f)()
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn PushDebugGroup(
+        &self,
+        source: types::GLenum,
+        id: types::GLuint,
+        length: types::GLsizei,
+        message: *const types::GLchar,
+    ) -> () {
+        __gl_imports::mem::transmute::<
+            _,
+            extern "system" fn(
+                types::GLenum,
+                types::GLuint,
+                types::GLsizei,
+                *const types::GLchar,
+            ) -> (),
+        >(self.PushDebugGroup.f)(source, id, length, message)
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
     #[inline]
     pub unsafe fn ReadPixels(
         &self,
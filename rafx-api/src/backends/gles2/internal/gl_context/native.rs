@@ -1213,6 +1213,31 @@ impl GlContext {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn gl_read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        format: GLenum,
+        type_: u32,
+        pixels: &mut [u8],
+    ) -> RafxResult<()> {
+        unsafe {
+            self.gles2.ReadPixels(
+                x,
+                y,
+                width as _,
+                height as _,
+                format,
+                type_,
+                pixels.as_mut_ptr() as _,
+            );
+            self.check_for_error()
+        }
+    }
+
     pub fn gl_pixel_storei(
         &self,
         pname: GLenum,
@@ -1285,6 +1310,100 @@ impl GlContext {
             self.check_for_error()
         }
     }
+
+    // The debug functions are only available with KHR_debug (or ES 3.2), so they silently do
+    // nothing if the function was not loaded
+    pub fn gl_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        if !self.gles2.PushDebugGroup.is_loaded() {
+            return Ok(());
+        }
+
+        unsafe {
+            self.gles2.PushDebugGroup(
+                gles2_bindings::DEBUG_SOURCE_APPLICATION,
+                0,
+                name.len() as _,
+                name.as_ptr() as _,
+            );
+            self.check_for_error()
+        }
+    }
+
+    pub fn gl_pop_debug_group(&self) -> RafxResult<()> {
+        if !self.gles2.PopDebugGroup.is_loaded() {
+            return Ok(());
+        }
+
+        unsafe {
+            self.gles2.PopDebugGroup();
+            self.check_for_error()
+        }
+    }
+
+    pub fn gl_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        if !self.gles2.DebugMessageInsert.is_loaded() {
+            return Ok(());
+        }
+
+        unsafe {
+            self.gles2.DebugMessageInsert(
+                gles2_bindings::DEBUG_SOURCE_APPLICATION,
+                gles2_bindings::DEBUG_TYPE_MARKER,
+                0,
+                gles2_bindings::DEBUG_SEVERITY_NOTIFICATION,
+                name.len() as _,
+                name.as_ptr() as _,
+            );
+            self.check_for_error()
+        }
+    }
+
+    fn gl_object_label(
+        &self,
+        identifier: GLenum,
+        name: u32,
+        label: &str,
+    ) -> RafxResult<()> {
+        if !self.gles2.ObjectLabel.is_loaded() {
+            return Ok(());
+        }
+
+        unsafe {
+            self.gles2
+                .ObjectLabel(identifier, name, label.len() as _, label.as_ptr() as _);
+            self.check_for_error()
+        }
+    }
+
+    pub fn gl_label_buffer(
+        &self,
+        buffer_id: BufferId,
+        label: &str,
+    ) -> RafxResult<()> {
+        self.gl_object_label(gles2_bindings::BUFFER, buffer_id.0, label)
+    }
+
+    pub fn gl_label_texture(
+        &self,
+        texture_id: TextureId,
+        label: &str,
+    ) -> RafxResult<()> {
+        self.gl_object_label(gles2_bindings::TEXTURE, texture_id.0, label)
+    }
+
+    pub fn gl_label_program(
+        &self,
+        program_id: ProgramId,
+        label: &str,
+    ) -> RafxResult<()> {
+        self.gl_object_label(gles2_bindings::PROGRAM, program_id.0, label)
+    }
 }
 
 fn to_gl_bool(value: bool) -> GLboolean {
@@ -1094,6 +1094,31 @@ impl GlContext {
         self.check_for_error()
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn gl_read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        format: GLenum,
+        type_: u32,
+        pixels: &mut [u8],
+    ) -> RafxResult<()> {
+        self.context
+            .read_pixels_with_opt_u8_array(
+                x,
+                y,
+                width as _,
+                height as _,
+                format,
+                type_,
+                Some(pixels),
+            )
+            .map_err(|x| format!("{:?}", x))?;
+        self.check_for_error()
+    }
+
     pub fn gl_pixel_storei(
         &self,
         pname: GLenum,
@@ -1158,4 +1183,47 @@ impl GlContext {
         self.context.generate_mipmap(target);
         self.check_for_error()
     }
+
+    // WebGL does not support debug groups, markers or object labels
+    pub fn gl_push_debug_group(
+        &self,
+        _name: &str,
+    ) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn gl_pop_debug_group(&self) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn gl_insert_debug_marker(
+        &self,
+        _name: &str,
+    ) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn gl_label_buffer(
+        &self,
+        _buffer_id: BufferId,
+        _label: &str,
+    ) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn gl_label_texture(
+        &self,
+        _texture_id: TextureId,
+        _label: &str,
+    ) -> RafxResult<()> {
+        Ok(())
+    }
+
+    pub fn gl_label_program(
+        &self,
+        _program_id: ProgramId,
+        _label: &str,
+    ) -> RafxResult<()> {
+        Ok(())
+    }
 }
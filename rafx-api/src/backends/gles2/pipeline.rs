@@ -34,6 +34,7 @@ pub(crate) struct Gles2PipelineInfo {
     uniform_field_locations: Vec<Option<LocationId>>,
     pub(crate) root_signature: RafxRootSignatureGles2,
     pub(crate) last_descriptor_updates: TrustCell<[u64; MAX_DESCRIPTOR_SET_LAYOUTS]>,
+    pub(crate) last_push_constants_update: TrustCell<u64>,
     pub(crate) last_bound_by_command_pool: TrustCell<u32>,
 }
 
@@ -92,6 +93,18 @@ impl RafxPipelineGles2 {
         self.gl_pipeline_info.program_id
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.root_signature
+            .gles2_root_signature()
+            .unwrap()
+            .device_context()
+            .gl_context()
+            .gl_label_program(self.gl_pipeline_info.program_id, name.as_ref())
+    }
+
     pub(crate) fn gl_pipeline_info(&self) -> &Arc<Gles2PipelineInfo> {
         &self.gl_pipeline_info
     }
@@ -192,6 +205,7 @@ impl RafxPipelineGles2 {
             uniform_field_locations,
             root_signature: gl_root_signature.clone(),
             last_descriptor_updates: Default::default(),
+            last_push_constants_update: Default::default(),
         };
 
         // Front face needs to be reversed because we render GL with a flipped Y axis:
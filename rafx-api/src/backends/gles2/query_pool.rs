@@ -0,0 +1,29 @@
+use crate::gles2::RafxDeviceContextGles2;
+use crate::{RafxQueryPoolDef, RafxResult};
+
+/// Query pools are not yet supported by the GL ES 2.0 backend. Creating one will return an error.
+#[derive(Debug, Clone)]
+pub struct RafxQueryPoolGles2 {
+    query_pool_def: RafxQueryPoolDef,
+}
+
+impl RafxQueryPoolGles2 {
+    pub fn query_pool_def(&self) -> &RafxQueryPoolDef {
+        &self.query_pool_def
+    }
+
+    pub fn new(
+        _device_context: &RafxDeviceContextGles2,
+        _query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolGles2> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+
+    pub fn get_results(
+        &self,
+        _first_query: u32,
+        _query_count: u32,
+    ) -> RafxResult<Option<Vec<u64>>> {
+        Err("Query pools are not supported by the GL ES 2.0 backend")?
+    }
+}
@@ -25,6 +25,11 @@ impl RafxQueueGles2 {
         self.inner.queue_id
     }
 
+    // There is a single queue family, so resources are never transferred between queues
+    pub fn queue_family_index(&self) -> u32 {
+        0
+    }
+
     pub fn queue_type(&self) -> RafxQueueType {
         self.inner.queue_type
     }
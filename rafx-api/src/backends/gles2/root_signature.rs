@@ -1,8 +1,8 @@
 use crate::gles2::reflection::{UniformIndex, UniformReflectionData};
 use crate::gles2::{ProgramId, RafxDeviceContextGles2, RafxSamplerGles2};
 use crate::{
-    RafxDescriptorIndex, RafxPipelineType, RafxResourceType, RafxResult, RafxRootSignatureDef,
-    MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxDescriptorIndex, RafxPipelineType, RafxPushConstantIndex, RafxResourceType, RafxResult,
+    RafxRootSignatureDef, MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 use fnv::FnvHashMap;
 use std::ffi::CString;
@@ -63,6 +63,17 @@ pub(crate) struct DescriptorInfo {
     pub(crate) first_location_index: Option<u32>,
 }
 
+#[derive(Clone, Debug)]
+pub(crate) struct PushConstantInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) size_in_bytes: u32,
+
+    // --- gl-specific ---
+    // Push constants are emulated with plain uniforms. May be none if the push constant is not
+    // active in any shader
+    pub(crate) uniform_index: Option<UniformIndex>,
+}
+
 #[derive(Default, Debug)]
 pub(crate) struct DescriptorSetLayoutInfo {
     // Settable descriptors, immutable samplers are omitted
@@ -90,6 +101,8 @@ pub(crate) struct RafxRootSignatureGles2Inner {
     pub(crate) layouts: [DescriptorSetLayoutInfo; MAX_DESCRIPTOR_SET_LAYOUTS],
     pub(crate) descriptors: Vec<DescriptorInfo>,
     pub(crate) name_to_descriptor_index: FnvHashMap<String, RafxDescriptorIndex>,
+    pub(crate) push_constants: Vec<PushConstantInfo>,
+    pub(crate) name_to_push_constant_index: FnvHashMap<String, RafxPushConstantIndex>,
     //
     // --- gl-specific ---
     pub(crate) immutable_samplers: Vec<ImmutableSampler>,
@@ -148,6 +161,22 @@ impl RafxRootSignatureGles2 {
         self.inner.descriptors.get(descriptor_index.0 as usize)
     }
 
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        self.inner.name_to_push_constant_index.get(name).copied()
+    }
+
+    pub(crate) fn push_constant(
+        &self,
+        push_constant_index: RafxPushConstantIndex,
+    ) -> Option<&PushConstantInfo> {
+        self.inner
+            .push_constants
+            .get(push_constant_index.0 as usize)
+    }
+
     pub(crate) fn uniform_reflection_data(&self) -> &UniformReflectionData {
         &self.inner.uniform_reflection
     }
@@ -178,6 +207,10 @@ impl RafxRootSignatureGles2 {
         // Make sure all shaders are compatible/build lookup of shared data from them
         let (pipeline_type, merged_resources, _merged_resources_name_index_map) =
             crate::internal_shared::merge_resources(root_signature_def)?;
+        crate::internal_shared::validate_bindless_resources(
+            device_context.device_info(),
+            &merged_resources,
+        )?;
 
         let mut layouts = [
             DescriptorSetLayoutInfo::default(),
@@ -194,6 +227,8 @@ impl RafxRootSignatureGles2 {
 
         let mut descriptors = Vec::with_capacity(merged_resources.len());
         let mut name_to_descriptor_index = FnvHashMap::default();
+        let mut push_constants = vec![];
+        let mut name_to_push_constant_index = FnvHashMap::default();
 
         let program_ids: Vec<ProgramId> = root_signature_def
             .shaders
@@ -213,6 +248,27 @@ impl RafxRootSignatureGles2 {
         for resource in &merged_resources {
             resource.validate()?;
 
+            // Push constants are not bound through descriptor sets. The shader processor emits
+            // them as a plain uniform struct, so they are set the same way as uniform buffers
+            if resource.resource_type == RafxResourceType::ROOT_CONSTANT {
+                let push_constant_index = RafxPushConstantIndex(push_constants.len() as u32);
+                if let Some(name) = resource.name.as_ref() {
+                    name_to_push_constant_index.insert(name.clone(), push_constant_index);
+                }
+
+                let gl_name = resource
+                    .gles_name
+                    .as_ref()
+                    .ok_or("GL ES 2.0 push constants must have a gles_name")?;
+
+                push_constants.push(PushConstantInfo {
+                    name: resource.name.clone(),
+                    size_in_bytes: resource.size_in_bytes,
+                    uniform_index: uniform_reflection.uniform_index(gl_name),
+                });
+                continue;
+            }
+
             let element_count = resource.element_count_normalized();
 
             let descriptor_data_offset_in_set;
@@ -259,9 +315,6 @@ impl RafxRootSignatureGles2 {
                 }
             }
 
-            // Not currently supported
-            assert_ne!(resource.resource_type, RafxResourceType::ROOT_CONSTANT);
-
             // Verify set index is valid
             let immutable_sampler_def_index = crate::internal_shared::find_immutable_sampler_index(
                 root_signature_def.immutable_samplers,
@@ -413,6 +466,8 @@ impl RafxRootSignatureGles2 {
             layouts,
             descriptors,
             name_to_descriptor_index,
+            push_constants,
+            name_to_push_constant_index,
             immutable_samplers,
             uniform_reflection,
             root_signature_id,
@@ -86,6 +86,11 @@ impl RafxTextureGles2 {
         &self.inner.texture_def
     }
 
+    // GL does not report the size of a texture's storage, so this is an estimate
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        crate::internal_shared::estimate_texture_size_in_bytes(&self.inner.texture_def)
+    }
+
     pub fn gl_raw_image(&self) -> &RafxRawImageGles2 {
         &self.inner.image
     }
@@ -98,6 +103,20 @@ impl RafxTextureGles2 {
         &self.inner.format_info
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        if let Some(texture_id) = self.inner.image.gl_texture_id() {
+            self.inner
+                .device_context
+                .gl_context()
+                .gl_label_texture(texture_id, name.as_ref())?;
+        }
+
+        Ok(())
+    }
+
     pub fn new(
         device_context: &RafxDeviceContextGles2,
         texture_def: &RafxTextureDef,
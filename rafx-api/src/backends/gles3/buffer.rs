@@ -3,7 +3,7 @@ use crate::{RafxBufferDef, RafxMemoryUsage, RafxResourceType, RafxResult};
 
 use crate::gles3::gles3_bindings;
 use crate::gles3::gles3_bindings::types::GLenum;
-use rafx_base::trust_cell::TrustCell;
+use rafx_base::trust_cell::{RefMut, TrustCell};
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -63,6 +63,10 @@ impl Gles3BufferContents {
         Some(&self.inner.data.as_ref()?.borrow().value[offset as _..])
     }
 
+    pub fn try_borrow_mut(&self) -> Option<RefMut<'_, Box<[u8]>>> {
+        Some(self.inner.data.as_ref()?.borrow_mut())
+    }
+
     #[allow(dead_code)]
     pub fn allocation_size(&self) -> u64 {
         self.inner.allocation_size
@@ -95,6 +99,10 @@ impl RafxBufferGles3 {
         &self.buffer_def
     }
 
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.buffer_contents.allocation_size()
+    }
+
     // only some for vertex and index buffers
     pub fn gl_buffer_id(&self) -> Option<BufferId> {
         self.buffer_id
@@ -104,6 +112,20 @@ impl RafxBufferGles3 {
         self.target
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        // Buffers without a GL buffer object are emulated on the CPU and have nothing to label
+        if let Some(buffer_id) = self.buffer_id {
+            self.device_context
+                .gl_context()
+                .gl_label_buffer(buffer_id, name.as_ref())?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn buffer_contents(&self) -> &Gles3BufferContents {
         &self.buffer_contents
     }
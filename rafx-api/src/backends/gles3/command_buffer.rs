@@ -2,14 +2,17 @@ use crate::gles3::{
     AttributeEnabledBits, BoundDescriptorSet, BoundVertexBuffer, CommandPoolGles3State,
     CommandPoolGles3StateInner, DescriptorSetArrayData, GlContext, Gles3PipelineInfo,
     RafxBufferGles3, RafxCommandPoolGles3, RafxDescriptorSetArrayGles3,
-    RafxDescriptorSetHandleGles3, RafxPipelineGles3, RafxQueueGles3, RafxRootSignatureGles3,
-    RafxTextureGles3, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM, NONE_TEXTURE,
+    RafxDescriptorSetHandleGles3, RafxPipelineGles3, RafxQueryPoolGles3, RafxQueueGles3,
+    RafxRootSignatureGles3, RafxTextureGles3, NONE_BUFFER, NONE_FRAMEBUFFER, NONE_PROGRAM,
+    NONE_TEXTURE,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorFlags,
-    RafxColorRenderTargetBinding, RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding,
-    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxResourceType, RafxResult,
-    RafxTextureBarrier, RafxVertexBufferBinding, MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxBufferBarrier, RafxCmdBlitParams, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorFlags, RafxColorRenderTargetBinding,
+    RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding, RafxExtents3D,
+    RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPushConstantIndex, RafxResourceType,
+    RafxResult, RafxSubpassContents, RafxSubpassDef, RafxTextureBarrier, RafxVertexBufferBinding,
+    MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 
 use rafx_base::trust_cell::TrustCell;
@@ -51,6 +54,13 @@ impl RafxCommandBufferGles3 {
         Ok(())
     }
 
+    pub fn begin_secondary(
+        &self,
+        _primary_command_buffer: &RafxCommandBufferGles3,
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported in GL ES 3.0")?
+    }
+
     pub fn end(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -66,6 +76,7 @@ impl RafxCommandBufferGles3 {
         state.current_gl_pipeline_info = None;
         state.stencil_reference_value = 0;
         state.clear_bindings();
+        state.bound_push_constants_root_signature = None;
         Self::update_vertex_attributes_in_use(gl_context, &mut *state, 0)?;
         assert_eq!(state.vertex_attribute_enabled_bits, 0);
         for attribute in &mut state.vertex_attributes {
@@ -288,6 +299,37 @@ impl RafxCommandBufferGles3 {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Subpasses are not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
+    ) -> RafxResult<()> {
+        if contents != RafxSubpassContents::Inline {
+            Err("Secondary command buffers are not supported in GL ES 3.0")?;
+        }
+
+        if subpasses.is_empty() {
+            self.cmd_begin_render_pass(color_targets, depth_target)
+        } else {
+            self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+        }
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Subpasses are not supported in GL ES 3.0")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         let mut state = self.command_pool_state.borrow_mut();
         assert!(state.is_started);
@@ -299,6 +341,40 @@ impl RafxCommandBufferGles3 {
         Ok(())
     }
 
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        _command_buffers: &[&RafxCommandBufferGles3],
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_push_debug_group(name)
+    }
+
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_pop_debug_group()
+    }
+
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        self.queue
+            .device_context()
+            .gl_context()
+            .gl_insert_debug_marker(name)
+    }
+
     pub fn cmd_set_viewport(
         &self,
         x: f32,
@@ -734,6 +810,41 @@ impl RafxCommandBufferGles3 {
         Ok(())
     }
 
+    // The data is written into the root signature's hidden uniform buffer immediately. The buffer
+    // is bound to the program's uniform block when we try to draw
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignatureGles3,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        let mut state = self.command_pool_state.borrow_mut();
+        assert!(state.is_started);
+
+        let push_constant = root_signature
+            .push_constant(push_constant_index)
+            .ok_or("Push constant index not found in root signature")?;
+
+        crate::internal_shared::validate_push_constant_data(
+            push_constant.name.as_deref(),
+            push_constant.size_in_bytes,
+            data,
+        )?;
+
+        let gl_context = self.queue.device_context().gl_context();
+        gl_context.gl_bind_buffer(gles3_bindings::UNIFORM_BUFFER, push_constant.buffer_id)?;
+        gl_context.gl_buffer_sub_data(
+            gles3_bindings::UNIFORM_BUFFER,
+            0,
+            data.len() as u64,
+            data.as_ptr(),
+        )?;
+        gl_context.gl_bind_buffer(gles3_bindings::UNIFORM_BUFFER, NONE_BUFFER)?;
+
+        state.bound_push_constants_root_signature = Some(root_signature.clone());
+        Ok(())
+    }
+
     // This does not affect the program right away, we wait until we try to draw, then update the
     // program as necessary
     fn set_current_descriptor_set(
@@ -809,6 +920,22 @@ impl RafxCommandBufferGles3 {
             }
         }
 
+        // Uniform buffer binding points are shared by all programs, so the push constant buffers
+        // are rebound on every draw
+        if let Some(bound_push_constants_root_signature) =
+            &state.bound_push_constants_root_signature
+        {
+            if *bound_push_constants_root_signature == pipeline.root_signature {
+                for push_constant in &pipeline.root_signature.inner.push_constants {
+                    gl_context.gl_bind_buffer_base(
+                        gles3_bindings::UNIFORM_BUFFER,
+                        push_constant.uniform_block_binding,
+                        push_constant.buffer_id,
+                    )?;
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -1086,6 +1213,54 @@ impl RafxCommandBufferGles3 {
         unimplemented!("Compute shaders not supported in GL ES 2.0");
     }
 
+    pub fn cmd_draw_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles3,
+        _indirect_buffer_offset_in_bytes: u64,
+        _draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indirect is not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles3,
+        _indirect_buffer_offset_in_bytes: u64,
+        _draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indexed_indirect is not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_draw_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferGles3,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferGles3,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indirect_count is not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferGles3,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferGles3,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indexed_indirect_count is not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_dispatch_indirect(
+        &self,
+        _indirect_buffer: &RafxBufferGles3,
+        _indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        Err("cmd_dispatch_indirect is not supported in GL ES 3.0")?
+    }
+
     pub fn cmd_resource_barrier(
         &self,
         _buffer_barriers: &[RafxBufferBarrier],
@@ -1169,4 +1344,139 @@ impl RafxCommandBufferGles3 {
         )?;
         gl_context.gl_bind_texture(dst_texture.gl_target(), NONE_TEXTURE)
     }
+
+    pub fn cmd_blit_image(
+        &self,
+        _src_texture: &RafxTextureGles3,
+        _dst_texture: &RafxTextureGles3,
+        _params: &RafxCmdBlitParams,
+    ) -> RafxResult<()> {
+        Err("Blitting images is not supported in GL ES 3.0")?
+    }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureGles3,
+        dst_buffer: &RafxBufferGles3,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let mut state = self.command_pool_state.borrow_mut();
+        assert!(state.is_started);
+        assert!(state.surface_size.is_none());
+
+        let gl_context = self.queue.device_context().gl_context();
+
+        let width = 1.max(src_texture.texture_def().extents.width >> params.mip_level);
+        let height = 1.max(src_texture.texture_def().extents.height >> params.mip_level);
+
+        let format_info = src_texture.gl_format_info();
+
+        if src_texture.gl_raw_image().gl_texture_id().is_none() {
+            Err("Cannot use cmd_copy_texture_to_buffer with swapchain image in GL ES")?;
+        }
+
+        let mut buffer_contents = dst_buffer
+            .buffer_contents()
+            .try_borrow_mut()
+            .ok_or("dst buffer must be CPU-visible in cmd_copy_texture_to_buffer")?;
+        let buffer_slice = buffer_contents
+            .get_mut(params.buffer_offset as usize..)
+            .ok_or(
+                "buffer_offset is past the end of the dst buffer in cmd_copy_texture_to_buffer",
+            )?;
+
+        // glReadPixels writes the whole image without knowing the size of the destination
+        let format = src_texture.texture_def().format;
+        let row_pitch = crate::internal_shared::texture_copy_row_pitch(
+            self.queue.device_context().device_info(),
+            format,
+            width,
+        ) as usize;
+        let required_size = row_pitch * (height as usize - 1)
+            + (width * format.block_or_pixel_size_in_bytes()) as usize;
+        if buffer_slice.len() < required_size {
+            Err(format!(
+                "dst buffer is too small for cmd_copy_texture_to_buffer, {} bytes are required after the offset but only {} are available",
+                required_size,
+                buffer_slice.len()
+            ))?;
+        }
+
+        // Read back by attaching the texture to the command pool's framebuffer. glReadPixels
+        // writes rows using PACK_ALIGNMENT, which is reported as the row alignment
+        gl_context.gl_bind_framebuffer(gles3_bindings::FRAMEBUFFER, state.framebuffer_id)?;
+        Self::bind_framebuffer(
+            gl_context,
+            src_texture,
+            gles3_bindings::COLOR_ATTACHMENT0,
+            params.array_layer,
+            params.mip_level,
+        )?;
+        state.framebuffer_color_bound[0] = true;
+
+        let result = gl_context.gl_check_framebuffer_status(gles3_bindings::FRAMEBUFFER)?;
+        if result != gles3_bindings::FRAMEBUFFER_COMPLETE {
+            gl_context.gl_bind_framebuffer(gles3_bindings::FRAMEBUFFER, NONE_FRAMEBUFFER)?;
+            Err(format!(
+                "Framebuffer Status is not FRAMEBUFFER_COMPLETE, result: {:#x}",
+                result
+            ))?;
+        }
+
+        gl_context.gl_read_pixels(
+            0,
+            0,
+            width,
+            height,
+            format_info.gl_format,
+            format_info.gl_type,
+            buffer_slice,
+        )?;
+
+        gl_context.gl_bind_framebuffer(gles3_bindings::FRAMEBUFFER, NONE_FRAMEBUFFER)
+    }
+
+    pub fn cmd_reset_query_pool(
+        &self,
+        _query_pool: &RafxQueryPoolGles3,
+        _first_query: u32,
+        _query_count: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 3.0 backend")?
+    }
+
+    pub fn cmd_write_timestamp(
+        &self,
+        _query_pool: &RafxQueryPoolGles3,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 3.0 backend")?
+    }
+
+    pub fn cmd_begin_query(
+        &self,
+        _query_pool: &RafxQueryPoolGles3,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 3.0 backend")?
+    }
+
+    pub fn cmd_end_query(
+        &self,
+        _query_pool: &RafxQueryPoolGles3,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 3.0 backend")?
+    }
+
+    pub fn cmd_resolve_queries(
+        &self,
+        _query_pool: &RafxQueryPoolGles3,
+        _first_query: u32,
+        _query_count: u32,
+        _dst_buffer: &RafxBufferGles3,
+        _dst_offset: u64,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the GL ES 3.0 backend")?
+    }
 }
@@ -45,6 +45,9 @@ pub(crate) struct CommandPoolGles3StateInner {
     pub(crate) bound_descriptor_sets_root_signature: Option<RafxRootSignatureGles3>,
     pub(crate) descriptor_sets_update_index: [u64; MAX_DESCRIPTOR_SET_LAYOUTS],
 
+    // The root signature that owns the uniform buffers most recently written by cmd_push_constants
+    pub(crate) bound_push_constants_root_signature: Option<RafxRootSignatureGles3>,
+
     // One per possible bound vertex buffer (could be 1 per attribute!)
     pub(crate) vertex_attribute_enabled_bits: AttributeEnabledBits,
     // Holds the currently bound attribute metadata
@@ -129,6 +132,7 @@ impl CommandPoolGles3State {
             bound_descriptor_sets: Default::default(),
             bound_descriptor_sets_root_signature: None,
             descriptor_sets_update_index: Default::default(),
+            bound_push_constants_root_signature: None,
             framebuffer_id,
         };
 
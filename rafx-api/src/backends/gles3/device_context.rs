@@ -1,17 +1,18 @@
 use crate::{
     RafxApiDefGles3, RafxBufferDef, RafxComputePipelineDef, RafxDescriptorSetArrayDef,
-    RafxDeviceContext, RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxQueueType,
-    RafxResourceType, RafxResult, RafxRootSignatureDef, RafxSampleCount, RafxSamplerDef,
-    RafxShaderModuleDefGles3, RafxShaderStageDef, RafxSwapchainDef, RafxTextureDef,
+    RafxDeviceContext, RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxMemoryBudget,
+    RafxQueryPoolDef, RafxQueueType, RafxResourceType, RafxResult, RafxRootSignatureDef,
+    RafxSampleCount, RafxSamplerDef, RafxShaderModuleDefGles3, RafxShaderStageDef,
+    RafxSwapchainDef, RafxTextureDef,
 };
 use raw_window_handle::HasRawWindowHandle;
 use std::sync::Arc;
 
 use crate::gles3::{
     GlContextManager, RafxBufferGles3, RafxDescriptorSetArrayGles3, RafxFenceGles3,
-    RafxPipelineGles3, RafxQueueGles3, RafxRootSignatureGles3, RafxSamplerGles3,
-    RafxSemaphoreGles3, RafxShaderGles3, RafxShaderModuleGles3, RafxSwapchainGles3,
-    RafxTextureGles3,
+    RafxPipelineGles3, RafxQueryPoolGles3, RafxQueueGles3, RafxRootSignatureGles3,
+    RafxSamplerGles3, RafxSemaphoreGles3, RafxShaderGles3, RafxShaderModuleGles3,
+    RafxSwapchainGles3, RafxTextureGles3,
 };
 
 use crate::gles3::gles3_bindings;
@@ -89,6 +90,25 @@ impl RafxDeviceContextGles3Inner {
             upload_buffer_texture_row_alignment: pack_alignment,
             supports_clamp_to_border_color: false, // requires GLES 3.2 or an extension
             max_vertex_attribute_count,
+            // Query pools are not supported by the GL backends
+            timestamp_period: 1.0,
+            supports_timestamp_queries: false,
+            supports_occlusion_queries: false,
+            supports_pipeline_statistics_queries: false,
+            // Indirect draws and compute require GL ES 3.1
+            supports_draw_indirect: false,
+            supports_multi_draw_indirect: false,
+            supports_draw_indirect_count: false,
+            supports_dispatch_indirect: false,
+            supports_transient_attachments: false,
+            supports_resource_aliasing: false,
+            supports_subpasses: false,
+            supports_secondary_command_buffers: false,
+            supports_blit_images: false,
+            supports_pipeline_cache_data: false,
+            // GL ES has no equivalent of descriptor indexing
+            supports_bindless_descriptors: false,
+            max_bindless_descriptor_count: 0,
         };
 
         // Enable sRGB framebuffers on desktop GL. This is enabled by default on ES 3.0
@@ -204,6 +224,23 @@ impl RafxDeviceContextGles3 {
         &self.inner.device_info
     }
 
+    // Pipeline caches are not supported by GL ES 3.0, so there is never any data to persist
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        Ok(Vec::default())
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        _data: &[u8],
+    ) -> RafxResult<bool> {
+        Ok(false)
+    }
+
+    // GL does not expose memory heaps or a budget
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        Ok(RafxMemoryBudget::default())
+    }
+
     pub fn gl_context(&self) -> &GlContext {
         &self.inner.gl_context
     }
@@ -267,6 +304,13 @@ impl RafxDeviceContextGles3 {
         RafxSamplerGles3::new(self, sampler_def)
     }
 
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolGles3> {
+        RafxQueryPoolGles3::new(self, query_pool_def)
+    }
+
     pub fn create_texture(
         &self,
         texture_def: &RafxTextureDef,
@@ -209,6 +209,8 @@ pub const BOOL_VEC3: types::GLenum = 0x8B58;
 #[allow(dead_code, non_upper_case_globals)]
 pub const BOOL_VEC4: types::GLenum = 0x8B59;
 #[allow(dead_code, non_upper_case_globals)]
+pub const BUFFER: types::GLenum = 0x82E0;
+#[allow(dead_code, non_upper_case_globals)]
 pub const BUFFER_ACCESS_FLAGS: types::GLenum = 0x911F;
 #[allow(dead_code, non_upper_case_globals)]
 pub const BUFFER_MAPPED: types::GLenum = 0x88BC;
@@ -862,6 +864,8 @@ pub const POLYGON_OFFSET_UNITS: types::GLenum = 0x2A00;
 #[allow(dead_code, non_upper_case_globals)]
 pub const PRIMITIVE_RESTART_FIXED_INDEX: types::GLenum = 0x8D69;
 #[allow(dead_code, non_upper_case_globals)]
+pub const PROGRAM: types::GLenum = 0x82E2;
+#[allow(dead_code, non_upper_case_globals)]
 pub const PROGRAM_BINARY_FORMATS: types::GLenum = 0x87FF;
 #[allow(dead_code, non_upper_case_globals)]
 pub const PROGRAM_BINARY_LENGTH: types::GLenum = 0x8741;
@@ -1743,14 +1747,23 @@ pub struct Gles3 {
     pub LinkProgram: FnPtr,
     /// Fallbacks: MapBufferRangeEXT
     pub MapBufferRange: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: ObjectLabelKHR
+    pub ObjectLabel: FnPtr,
     /// Fallbacks: PauseTransformFeedbackNV
     pub PauseTransformFeedback: FnPtr,
     pub PixelStorei: FnPtr,
     pub PolygonOffset: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: PopDebugGroupKHR
+    pub PopDebugGroup: FnPtr,
     /// Fallbacks: ProgramBinaryOES
     pub ProgramBinary: FnPtr,
     /// Fallbacks: ProgramParameteriARB, ProgramParameteriEXT
     pub ProgramParameteri: FnPtr,
+    /// Might not be present, check it's loaded before using
+    /// Fallbacks: PushDebugGroupKHR
+    pub PushDebugGroup: FnPtr,
     pub ReadBuffer: FnPtr,
     pub ReadPixels: FnPtr,
     pub ReleaseShaderCompiler: FnPtr,
@@ -2277,17 +2290,20 @@ impl Gles3 {
             LineWidth: FnPtr::new(metaloadfn("glLineWidth", &[])),
             LinkProgram: FnPtr::new(metaloadfn("glLinkProgram", &["glLinkProgramARB"])),
             MapBufferRange: FnPtr::new(metaloadfn("glMapBufferRange", &["glMapBufferRangeEXT"])),
+            ObjectLabel: FnPtr::new(metaloadfn("glObjectLabel", &["glObjectLabelKHR"])),
             PauseTransformFeedback: FnPtr::new(metaloadfn(
                 "glPauseTransformFeedback",
                 &["glPauseTransformFeedbackNV"],
             )),
             PixelStorei: FnPtr::new(metaloadfn("glPixelStorei", &[])),
             PolygonOffset: FnPtr::new(metaloadfn("glPolygonOffset", &[])),
+            PopDebugGroup: FnPtr::new(metaloadfn("glPopDebugGroup", &["glPopDebugGroupKHR"])),
             ProgramBinary: FnPtr::new(metaloadfn("glProgramBinary", &["glProgramBinaryOES"])),
             ProgramParameteri: FnPtr::new(metaloadfn(
                 "glProgramParameteri",
                 &["glProgramParameteriARB", "glProgramParameteriEXT"],
             )),
+            PushDebugGroup: FnPtr::new(metaloadfn("glPushDebugGroup", &["glPushDebugGroupKHR"])),
             ReadBuffer: FnPtr::new(metaloadfn("glReadBuffer", &[])),
             ReadPixels: FnPtr::new(metaloadfn("glReadPixels", &[])),
             ReleaseShaderCompiler: FnPtr::new(metaloadfn("glReleaseShaderCompiler", &[])),
@@ -4761,6 +4777,30 @@ impl Gles3 {
         >(self.MapBufferRange.f)(target, offset, length, access)
     }
     #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn ObjectLabel(
+        &self,
+        identifier: types::GLenum,
+        name: types::GLuint,
+        length: types::GLsizei,
+        label: *const types::GLchar,
+    ) -> () {
+        __gl_imports::mem::transmute::<
+            _,
+            extern "system" fn(
+                types::GLenum,
+                types::GLuint,
+                types::GLsizei,
+                *const types::GLchar,
+            ) -> (),
+        >(self.ObjectLabel.f)(identifier, name, length, label)
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
     #[inline]
     pub unsafe fn PauseTransformFeedback(&self) -> () {
         __gl_imports::mem::transmute::<_, extern "system" fn() -> ()>(self.PauseTransformFeedback.f)(
@@ -4789,6 +4829,16 @@ impl Gles3 {
         )(factor, units)
     }
     #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn PopDebugGroup(&self) -> () {
+        __gl_imports::mem::transmute::<_, extern "system" fn() -> ()>(self.PopDebugGroup.f)()
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
     #[inline]
     pub unsafe fn ProgramBinary(
         &self,
@@ -4821,6 +4871,30 @@ impl Gles3 {
         >(self.ProgramParameteri.f)(program, pname, value)
     }
     #[allow(non_snake_case, unused_variables, dead_code)]
+    #[allow(
+        clippy::unused_unit,
+        clippy::missing_safety_doc,
+        clippy::missing_transmute_annotations
+    )]
+    #[inline]
+    pub unsafe fn PushDebugGroup(
+        &self,
+        source: types::GLenum,
+        id: types::GLuint,
+        length: types::GLsizei,
+        message: *const types::GLchar,
+    ) -> () {
+        __gl_imports::mem::transmute::<
+            _,
+            extern "system" fn(
+                types::GLenum,
+                types::GLuint,
+                types::GLsizei,
+                *const types::GLchar,
+            ) -> (),
+        >(self.PushDebugGroup.f)(source, id, length, message)
+    }
+    #[allow(non_snake_case, unused_variables, dead_code)]
     #[inline]
     pub unsafe fn ReadBuffer(
         &self,
@@ -86,6 +86,18 @@ impl RafxPipelineGles3 {
         self.gl_pipeline_info.program_id
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.root_signature
+            .gles3_root_signature()
+            .unwrap()
+            .device_context()
+            .gl_context()
+            .gl_label_program(self.gl_pipeline_info.program_id, name.as_ref())
+    }
+
     pub(crate) fn gl_pipeline_info(&self) -> &Arc<Gles3PipelineInfo> {
         &self.gl_pipeline_info
     }
@@ -189,6 +201,31 @@ impl RafxPipelineGles3 {
             }
         }
 
+        for push_constant in &gl_root_signature.inner.push_constants {
+            let uniform_block_index =
+                gl_context.gl_get_uniform_block_index(program_id, &push_constant.gl_name)?;
+            // The push constant might not be active in this program
+            if let Some(uniform_block_index) = uniform_block_index {
+                let size = gl_context.gl_get_active_uniform_blockiv(
+                    program_id,
+                    uniform_block_index,
+                    gles3_bindings::UNIFORM_BLOCK_DATA_SIZE,
+                )?;
+                if size as u32 > push_constant.buffer_size_in_bytes {
+                    Err(format!(
+                        "Push constant {:?} uniform block is {} bytes, but the push constant is only {} bytes",
+                        push_constant.name, size, push_constant.buffer_size_in_bytes
+                    ))?;
+                }
+
+                gl_context.gl_uniform_block_binding(
+                    program_id,
+                    uniform_block_index,
+                    push_constant.uniform_block_binding,
+                )?;
+            }
+        }
+
         let gl_topology = pipeline_def
             .primitive_topology
             .gles3_topology()
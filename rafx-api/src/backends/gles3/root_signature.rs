@@ -1,7 +1,8 @@
-use crate::gles3::{RafxDeviceContextGles3, RafxSamplerGles3};
+use crate::gles3::gles3_bindings;
+use crate::gles3::{BufferId, RafxDeviceContextGles3, RafxSamplerGles3, NONE_BUFFER};
 use crate::{
-    RafxDescriptorIndex, RafxPipelineType, RafxResourceType, RafxResult, RafxRootSignatureDef,
-    MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxDescriptorIndex, RafxPipelineType, RafxPushConstantIndex, RafxResourceType, RafxResult,
+    RafxRootSignatureDef, MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 use fnv::FnvHashMap;
 use std::ffi::CString;
@@ -61,6 +62,21 @@ pub(crate) struct DescriptorInfo {
     pub(crate) first_location_index: Option<u32>,
 }
 
+#[derive(Debug)]
+pub(crate) struct PushConstantInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) size_in_bytes: u32,
+
+    // --- gl-specific ---
+    pub(crate) gl_name: CString,
+
+    // Push constants are emulated with a hidden uniform buffer owned by the root signature. Its
+    // binding comes after all the uniform blocks used by descriptors.
+    pub(crate) uniform_block_binding: u32,
+    pub(crate) buffer_id: BufferId,
+    pub(crate) buffer_size_in_bytes: u32,
+}
+
 #[derive(Default, Debug)]
 pub(crate) struct DescriptorSetLayoutInfo {
     // Settable descriptors, immutable samplers are omitted
@@ -88,6 +104,8 @@ pub(crate) struct RafxRootSignatureGles3Inner {
     pub(crate) layouts: [DescriptorSetLayoutInfo; MAX_DESCRIPTOR_SET_LAYOUTS],
     pub(crate) descriptors: Vec<DescriptorInfo>,
     pub(crate) name_to_descriptor_index: FnvHashMap<String, RafxDescriptorIndex>,
+    pub(crate) push_constants: Vec<PushConstantInfo>,
+    pub(crate) name_to_push_constant_index: FnvHashMap<String, RafxPushConstantIndex>,
     //
     // --- gl-specific ---
     pub(crate) immutable_samplers: Vec<ImmutableSampler>,
@@ -97,6 +115,17 @@ pub(crate) struct RafxRootSignatureGles3Inner {
     pub(crate) location_names: Vec<CString>,
 }
 
+impl Drop for RafxRootSignatureGles3Inner {
+    fn drop(&mut self) {
+        let gl_context = self.device_context.gl_context();
+        for push_constant in &self.push_constants {
+            gl_context
+                .gl_destroy_buffer(push_constant.buffer_id)
+                .unwrap();
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct RafxRootSignatureGles3 {
     pub(crate) inner: Arc<RafxRootSignatureGles3Inner>,
@@ -146,6 +175,22 @@ impl RafxRootSignatureGles3 {
         self.inner.descriptors.get(descriptor_index.0 as usize)
     }
 
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        self.inner.name_to_push_constant_index.get(name).copied()
+    }
+
+    pub(crate) fn push_constant(
+        &self,
+        push_constant_index: RafxPushConstantIndex,
+    ) -> Option<&PushConstantInfo> {
+        self.inner
+            .push_constants
+            .get(push_constant_index.0 as usize)
+    }
+
     #[allow(dead_code)]
     pub(crate) fn uniform_block_binding(
         &self,
@@ -171,6 +216,10 @@ impl RafxRootSignatureGles3 {
         // Make sure all shaders are compatible/build lookup of shared data from them
         let (pipeline_type, merged_resources, _merged_resources_name_index_map) =
             crate::internal_shared::merge_resources(root_signature_def)?;
+        crate::internal_shared::validate_bindless_resources(
+            device_context.device_info(),
+            &merged_resources,
+        )?;
 
         let mut layouts = [
             DescriptorSetLayoutInfo::default(),
@@ -187,6 +236,7 @@ impl RafxRootSignatureGles3 {
 
         let mut descriptors = Vec::with_capacity(merged_resources.len());
         let mut name_to_descriptor_index = FnvHashMap::default();
+        let mut push_constant_resources = vec![];
 
         let mut location_names = Vec::<CString>::default();
 
@@ -196,6 +246,13 @@ impl RafxRootSignatureGles3 {
         for resource in &merged_resources {
             resource.validate()?;
 
+            // Push constants are not bound through descriptor sets, we create the uniform blocks
+            // for them once all the descriptors have been assigned a uniform block binding
+            if resource.resource_type == RafxResourceType::ROOT_CONSTANT {
+                push_constant_resources.push(resource);
+                continue;
+            }
+
             let element_count = resource.element_count_normalized();
 
             let descriptor_data_offset_in_set;
@@ -242,9 +299,6 @@ impl RafxRootSignatureGles3 {
                 }
             }
 
-            // Not currently supported
-            assert_ne!(resource.resource_type, RafxResourceType::ROOT_CONSTANT);
-
             // Verify set index is valid
             let immutable_sampler_def_index = crate::internal_shared::find_immutable_sampler_index(
                 root_signature_def.immutable_samplers,
@@ -384,6 +438,44 @@ impl RafxRootSignatureGles3 {
             total_texture_units += next_descriptor_data_texture_offset[i];
         }
 
+        let gl_context = device_context.gl_context();
+        let mut push_constants = Vec::with_capacity(push_constant_resources.len());
+        let mut name_to_push_constant_index = FnvHashMap::default();
+        for resource in push_constant_resources {
+            let push_constant_index = RafxPushConstantIndex(push_constants.len() as u32);
+            if let Some(name) = resource.name.as_ref() {
+                name_to_push_constant_index.insert(name.clone(), push_constant_index);
+            }
+
+            let gl_name = resource
+                .gles_name
+                .as_ref()
+                .ok_or("GL ES 3.0 push constants must have a gles_name")?;
+
+            // std140 uniform blocks are padded to 16 bytes
+            let buffer_size_in_bytes =
+                rafx_base::memory::round_size_up_to_alignment_u32(resource.size_in_bytes, 16);
+            let buffer_id = gl_context.gl_create_buffer()?;
+            gl_context.gl_bind_buffer(gles3_bindings::UNIFORM_BUFFER, buffer_id)?;
+            gl_context.gl_buffer_data(
+                gles3_bindings::UNIFORM_BUFFER,
+                buffer_size_in_bytes as u64,
+                std::ptr::null(),
+                gles3_bindings::DYNAMIC_DRAW,
+            )?;
+            gl_context.gl_bind_buffer(gles3_bindings::UNIFORM_BUFFER, NONE_BUFFER)?;
+
+            push_constants.push(PushConstantInfo {
+                name: resource.name.clone(),
+                size_in_bytes: resource.size_in_bytes,
+                gl_name: CString::new(gl_name.as_str()).unwrap(),
+                uniform_block_binding: (uniform_block_descriptors.len() + push_constants.len())
+                    as u32,
+                buffer_id,
+                buffer_size_in_bytes,
+            });
+        }
+
         let root_signature_id = NEXT_ROOT_SIGNATURE_ID.fetch_add(1, Ordering::Relaxed);
 
         for (texture_descriptor_index, sampler_name) in texture_descriptor_index_sampler_names {
@@ -398,6 +490,8 @@ impl RafxRootSignatureGles3 {
             layouts,
             descriptors,
             name_to_descriptor_index,
+            push_constants,
+            name_to_push_constant_index,
             immutable_samplers,
             uniform_block_descriptors,
             root_signature_id,
@@ -30,6 +30,21 @@ impl RafxApiMetal {
     /// behavior on the CPU for reasons other than interacting with the GPU.
     pub unsafe fn new(
         _window: &dyn HasRawWindowHandle,
+        api_def: &RafxApiDef,
+        metal_api_def: &RafxApiDefMetal,
+    ) -> RafxResult<Self> {
+        Self::new_headless(api_def, metal_api_def)
+    }
+
+    /// Create a device that is not associated with a window. Metal devices do not depend on a
+    /// window, so swapchains may still be created later.
+    ///
+    /// # Safety
+    ///
+    /// GPU programming is fundamentally unsafe, so all rafx APIs that interact with the GPU should
+    /// be considered unsafe. However, rafx APIs are only gated by unsafe if they can cause undefined
+    /// behavior on the CPU for reasons other than interacting with the GPU.
+    pub unsafe fn new_headless(
         _api_def: &RafxApiDef,
         _metal_api_def: &RafxApiDefMetal,
     ) -> RafxResult<Self> {
@@ -17,10 +17,22 @@ impl RafxBufferMetal {
         &self.buffer_def
     }
 
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.buffer.allocated_size() as u64
+    }
+
     pub fn metal_buffer(&self) -> &metal_rs::BufferRef {
         self.buffer.as_ref()
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.buffer.set_label(name.as_ref());
+        Ok(())
+    }
+
     pub fn map_buffer(&self) -> RafxResult<*mut u8> {
         if self.buffer_def.memory_usage == RafxMemoryUsage::GpuOnly {
             return Err("Cannot map GPU-only buffer")?;
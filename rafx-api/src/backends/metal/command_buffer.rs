@@ -1,14 +1,18 @@
 use crate::metal::{
     ArgumentBufferData, BarrierFlagsMetal, RafxBufferMetal, RafxCommandPoolMetal,
-    RafxDescriptorSetArrayMetal, RafxDescriptorSetHandleMetal, RafxPipelineMetal, RafxQueueMetal,
-    RafxRootSignatureMetal, RafxTextureMetal,
+    RafxDescriptorSetArrayMetal, RafxDescriptorSetHandleMetal, RafxPipelineMetal,
+    RafxQueryPoolMetal, RafxQueueMetal, RafxRootSignatureMetal, RafxTextureMetal,
 };
 use crate::{
-    RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorRenderTargetBinding,
-    RafxCommandBufferDef, RafxDepthStencilRenderTargetBinding, RafxExtents3D,
-    RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPipelineType, RafxResourceState,
-    RafxResult, RafxTextureBarrier, RafxVertexBufferBinding,
+    RafxBufferBarrier, RafxCmdBlitParams, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorRenderTargetBinding, RafxCommandBufferDef,
+    RafxDepthStencilRenderTargetBinding, RafxDrawIndexedIndirectCommand, RafxDrawIndirectCommand,
+    RafxExtents3D, RafxIndexBufferBinding, RafxIndexType, RafxLoadOp, RafxPipelineType,
+    RafxPushConstantIndex, RafxResourceState, RafxResult, RafxShaderStageFlags,
+    RafxSubpassContents, RafxSubpassDef, RafxTextureBarrier, RafxVertexBufferBinding,
+    METAL_PUSH_CONSTANT_BUFFER_INDEX,
 };
+use cocoa_foundation::foundation::NSUInteger;
 use fnv::FnvHashSet;
 use metal_rs::{
     MTLBlitOption, MTLIndexType, MTLOrigin, MTLPrimitiveType, MTLRenderStages, MTLResourceUsage,
@@ -35,6 +39,9 @@ pub struct RafxCommandBufferMetalInner {
     compute_threads_per_group_x: u32,
     compute_threads_per_group_y: u32,
     compute_threads_per_group_z: u32,
+    // Metal debug groups are scoped to an encoder, so we track the open groups and re-push them
+    // onto every encoder that is created while they are open
+    debug_groups: Vec<String>,
 }
 
 unsafe impl Send for RafxCommandBufferMetalInner {}
@@ -120,6 +127,7 @@ impl RafxCommandBufferMetal {
             current_index_buffer_byte_offset: 0,
             current_index_buffer_type: MTLIndexType::UInt16,
             current_index_buffer_stride: 0,
+            debug_groups: Default::default(),
         };
 
         Ok(RafxCommandBufferMetal {
@@ -134,10 +142,18 @@ impl RafxCommandBufferMetal {
             let mut inner = self.inner.borrow_mut();
             inner.command_buffer = Some(command_buffer.to_owned());
             inner.last_pipeline_type = None;
+            inner.debug_groups.clear();
             Ok(())
         })
     }
 
+    pub fn begin_secondary(
+        &self,
+        _primary_command_buffer: &RafxCommandBufferMetal,
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported by the Metal backend")?
+    }
+
     pub fn end(&self) -> RafxResult<()> {
         objc::rc::autoreleasepool(|| self.end_current_encoders(true))
     }
@@ -252,6 +268,7 @@ impl RafxCommandBufferMetal {
             let cmd_buffer = inner.command_buffer.as_ref().unwrap();
             let render_encoder = cmd_buffer.new_render_command_encoder(descriptor);
             inner.render_encoder = Some(render_encoder.to_owned());
+            Self::push_debug_groups(inner.render_encoder.as_ref().unwrap(), &inner.debug_groups);
             self.wait_for_barriers(&*inner)?;
             // set heaps?
 
@@ -291,6 +308,7 @@ impl RafxCommandBufferMetal {
                 queue.add_barrier_flags(BarrierFlagsMetal::FENCE);
             }
 
+            Self::pop_debug_groups(&render_encoder, &inner.debug_groups);
             render_encoder.end_encoding();
         } else if let Some(compute_encoder) = inner.compute_encoder.take() {
             if !barrier_flags.is_empty() || force_barrier {
@@ -298,6 +316,7 @@ impl RafxCommandBufferMetal {
                 queue.add_barrier_flags(BarrierFlagsMetal::FENCE);
             }
 
+            Self::pop_debug_groups(&compute_encoder, &inner.debug_groups);
             compute_encoder.end_encoding();
         } else if let Some(blit_encoder) = inner.blit_encoder.take() {
             if !barrier_flags.is_empty() || force_barrier {
@@ -305,12 +324,82 @@ impl RafxCommandBufferMetal {
                 queue.add_barrier_flags(BarrierFlagsMetal::FENCE);
             }
 
+            Self::pop_debug_groups(&blit_encoder, &inner.debug_groups);
             blit_encoder.end_encoding();
         }
 
         Ok(())
     }
 
+    fn active_encoder(inner: &RafxCommandBufferMetalInner) -> Option<&metal_rs::CommandEncoderRef> {
+        if let Some(render_encoder) = &inner.render_encoder {
+            Some(render_encoder)
+        } else if let Some(compute_encoder) = &inner.compute_encoder {
+            Some(compute_encoder)
+        } else if let Some(blit_encoder) = &inner.blit_encoder {
+            Some(blit_encoder)
+        } else {
+            None
+        }
+    }
+
+    fn push_debug_groups(
+        encoder: &metal_rs::CommandEncoderRef,
+        debug_groups: &[String],
+    ) {
+        for debug_group in debug_groups {
+            encoder.push_debug_group(debug_group);
+        }
+    }
+
+    fn pop_debug_groups(
+        encoder: &metal_rs::CommandEncoderRef,
+        debug_groups: &[String],
+    ) {
+        for _ in debug_groups {
+            encoder.pop_debug_group();
+        }
+    }
+
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(encoder) = Self::active_encoder(&*inner) {
+            encoder.push_debug_group(name);
+        }
+
+        inner.debug_groups.push(name.to_string());
+        Ok(())
+    }
+
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.debug_groups.pop().is_none() {
+            return Err("cmd_pop_debug_group called without a matching cmd_push_debug_group")?;
+        }
+
+        if let Some(encoder) = Self::active_encoder(&*inner) {
+            encoder.pop_debug_group();
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        // Signposts must be inserted into an encoder. If none is active, the marker is dropped
+        let inner = self.inner.borrow();
+        if let Some(encoder) = Self::active_encoder(&*inner) {
+            encoder.insert_debug_signpost(name);
+        }
+
+        Ok(())
+    }
+
     fn wait_for_barriers(
         &self,
         inner: &RafxCommandBufferMetalInner,
@@ -335,11 +424,49 @@ impl RafxCommandBufferMetal {
         Ok(())
     }
 
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        _color_targets: &[RafxColorRenderTargetBinding],
+        _depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        _subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        Err("Subpasses are not supported by the Metal backend")?
+    }
+
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
+    ) -> RafxResult<()> {
+        if contents != RafxSubpassContents::Inline {
+            Err("Secondary command buffers are not supported by the Metal backend")?;
+        }
+
+        if subpasses.is_empty() {
+            self.cmd_begin_render_pass(color_targets, depth_target)
+        } else {
+            self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+        }
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        Err("Subpasses are not supported by the Metal backend")?
+    }
+
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         // no action necessary
         Ok(())
     }
 
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        _command_buffers: &[&RafxCommandBufferMetal],
+    ) -> RafxResult<()> {
+        Err("Secondary command buffers are not supported by the Metal backend")?
+    }
+
     pub fn cmd_set_viewport(
         &self,
         x: f32,
@@ -456,6 +583,10 @@ impl RafxCommandBufferMetal {
                             .unwrap()
                             .new_compute_command_encoder();
                         inner.compute_encoder = Some(compute_encoder.to_owned());
+                        Self::push_debug_groups(
+                            inner.compute_encoder.as_ref().unwrap(),
+                            &inner.debug_groups,
+                        );
                     }
 
                     let compute_encoder_info = pipeline.compute_encoder_info.as_ref().unwrap();
@@ -629,6 +760,52 @@ impl RafxCommandBufferMetal {
         Ok(())
     }
 
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignatureMetal,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        let push_constant = root_signature
+            .push_constant(push_constant_index)
+            .ok_or("Push constant index not found in root signature")?;
+
+        crate::internal_shared::validate_push_constant_data(
+            push_constant.name.as_deref(),
+            push_constant.size_in_bytes,
+            data,
+        )?;
+
+        let inner = self.inner.borrow();
+        let buffer_index = METAL_PUSH_CONSTANT_BUFFER_INDEX as NSUInteger;
+        let length = data.len() as NSUInteger;
+        let bytes = data.as_ptr() as *const std::ffi::c_void;
+        match root_signature.pipeline_type() {
+            RafxPipelineType::Graphics => {
+                let render_encoder = inner
+                    .render_encoder
+                    .as_ref()
+                    .ok_or("Must begin render pass before setting graphics push constants")?;
+                let stages = push_constant.used_in_shader_stages;
+                if stages.intersects(RafxShaderStageFlags::VERTEX) {
+                    render_encoder.set_vertex_bytes(buffer_index, length, bytes);
+                }
+                if stages.intersects(RafxShaderStageFlags::FRAGMENT) {
+                    render_encoder.set_fragment_bytes(buffer_index, length, bytes);
+                }
+            }
+            RafxPipelineType::Compute => {
+                let compute_encoder = inner
+                    .compute_encoder
+                    .as_ref()
+                    .ok_or("Must bind compute pipeline before setting compute push constants")?;
+                compute_encoder.set_bytes(buffer_index, length, bytes);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_draw(
         &self,
         vertex_count: u32,
@@ -806,6 +983,97 @@ impl RafxCommandBufferMetal {
         Ok(())
     }
 
+    // Metal has no multi-draw indirect, so each command in the buffer is issued as its own draw
+    pub fn cmd_draw_indirect(
+        &self,
+        indirect_buffer: &RafxBufferMetal,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        let inner = self.inner.borrow();
+        let render_encoder = inner.render_encoder.as_ref().unwrap();
+        let stride = std::mem::size_of::<RafxDrawIndirectCommand>() as u64;
+        for draw_index in 0..draw_count as u64 {
+            render_encoder.draw_primitives_indirect(
+                inner.primitive_type,
+                indirect_buffer.metal_buffer(),
+                (indirect_buffer_offset_in_bytes + draw_index * stride) as _,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        indirect_buffer: &RafxBufferMetal,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        let inner = self.inner.borrow();
+        let render_encoder = inner.render_encoder.as_ref().unwrap();
+        let stride = std::mem::size_of::<RafxDrawIndexedIndirectCommand>() as u64;
+        for draw_index in 0..draw_count as u64 {
+            render_encoder.draw_indexed_primitives_indirect(
+                inner.primitive_type,
+                inner.current_index_buffer_type,
+                inner.current_index_buffer.as_ref().unwrap(),
+                inner.current_index_buffer_byte_offset as _,
+                indirect_buffer.metal_buffer(),
+                (indirect_buffer_offset_in_bytes + draw_index * stride) as _,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferMetal,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferMetal,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indirect_count is not supported by the metal backend")?
+    }
+
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        _indirect_buffer: &RafxBufferMetal,
+        _indirect_buffer_offset_in_bytes: u64,
+        _count_buffer: &RafxBufferMetal,
+        _count_buffer_offset_in_bytes: u64,
+        _max_draw_count: u32,
+    ) -> RafxResult<()> {
+        Err("cmd_draw_indexed_indirect_count is not supported by the metal backend")?
+    }
+
+    pub fn cmd_dispatch_indirect(
+        &self,
+        indirect_buffer: &RafxBufferMetal,
+        indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        let inner = self.inner.borrow();
+        self.wait_for_barriers(&*inner)?;
+        let thread_per_group = MTLSize {
+            width: inner.compute_threads_per_group_x as _,
+            height: inner.compute_threads_per_group_y as _,
+            depth: inner.compute_threads_per_group_z as _,
+        };
+
+        inner
+            .compute_encoder
+            .as_ref()
+            .unwrap()
+            .dispatch_thread_groups_indirect(
+                indirect_buffer.metal_buffer(),
+                indirect_buffer_offset_in_bytes as _,
+                thread_per_group,
+            );
+        Ok(())
+    }
+
     pub fn cmd_resource_barrier(
         &self,
         buffer_barriers: &[RafxBufferBarrier],
@@ -859,6 +1127,10 @@ impl RafxCommandBufferMetal {
                             .unwrap()
                             .new_blit_command_encoder();
                         inner.blit_encoder = Some(encoder.to_owned());
+                        Self::push_debug_groups(
+                            inner.blit_encoder.as_ref().unwrap(),
+                            &inner.debug_groups,
+                        );
                         Ok(inner.blit_encoder.as_ref().unwrap().as_ref())
                     });
                 result?
@@ -895,6 +1167,10 @@ impl RafxCommandBufferMetal {
                             .unwrap()
                             .new_blit_command_encoder();
                         inner.blit_encoder = Some(encoder.to_owned());
+                        Self::push_debug_groups(
+                            inner.blit_encoder.as_ref().unwrap(),
+                            &inner.debug_groups,
+                        );
                         Ok(inner.blit_encoder.as_ref().unwrap().as_ref())
                     });
                 result?
@@ -947,4 +1223,122 @@ impl RafxCommandBufferMetal {
         );
         Ok(())
     }
+
+    pub fn cmd_blit_image(
+        &self,
+        _src_texture: &RafxTextureMetal,
+        _dst_texture: &RafxTextureMetal,
+        _params: &RafxCmdBlitParams,
+    ) -> RafxResult<()> {
+        Err("Blitting images is not supported by the Metal backend")?
+    }
+
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureMetal,
+        dst_buffer: &RafxBufferMetal,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let mut inner = self.inner.borrow_mut();
+        let blit_encoder = inner.blit_encoder.as_ref();
+        let blit_encoder = match blit_encoder {
+            Some(x) => x,
+            None => {
+                let result: RafxResult<&metal_rs::BlitCommandEncoderRef> =
+                    objc::rc::autoreleasepool(|| {
+                        Self::do_end_current_encoders(&self.queue, &mut *inner, false)?;
+                        let encoder = inner
+                            .command_buffer
+                            .as_ref()
+                            .unwrap()
+                            .new_blit_command_encoder();
+                        inner.blit_encoder = Some(encoder.to_owned());
+                        Self::push_debug_groups(
+                            inner.blit_encoder.as_ref().unwrap(),
+                            &inner.debug_groups,
+                        );
+                        Ok(inner.blit_encoder.as_ref().unwrap().as_ref())
+                    });
+                result?
+            }
+        };
+
+        let texture_def = src_texture.texture_def();
+        let width = 1.max(texture_def.extents.width >> params.mip_level);
+        let height = 1.max(texture_def.extents.height >> params.mip_level);
+        let depth = 1.max(texture_def.extents.depth >> params.mip_level);
+
+        let device_info = self.queue.device_context().device_info();
+        let destination_bytes_per_row =
+            crate::internal_shared::texture_copy_row_pitch(device_info, texture_def.format, width);
+        let block_height_in_pixels = texture_def.format.block_height_in_pixels();
+        let height_in_blocks =
+            rafx_base::memory::round_size_up_to_alignment_u32(height, block_height_in_pixels)
+                / block_height_in_pixels;
+        let destination_bytes_per_image = height_in_blocks * destination_bytes_per_row;
+
+        let source_size = MTLSize {
+            width: width as _,
+            height: height as _,
+            depth: depth as _,
+        };
+
+        blit_encoder.copy_from_texture_to_buffer(
+            src_texture.metal_texture(),
+            params.array_layer as _,
+            params.mip_level as _,
+            MTLOrigin { x: 0, y: 0, z: 0 },
+            source_size,
+            dst_buffer.metal_buffer(),
+            params.buffer_offset as _,
+            destination_bytes_per_row as _,
+            destination_bytes_per_image as _,
+            MTLBlitOption::empty(),
+        );
+        Ok(())
+    }
+
+    pub fn cmd_reset_query_pool(
+        &self,
+        _query_pool: &RafxQueryPoolMetal,
+        _first_query: u32,
+        _query_count: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the metal backend")?
+    }
+
+    pub fn cmd_write_timestamp(
+        &self,
+        _query_pool: &RafxQueryPoolMetal,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the metal backend")?
+    }
+
+    pub fn cmd_begin_query(
+        &self,
+        _query_pool: &RafxQueryPoolMetal,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the metal backend")?
+    }
+
+    pub fn cmd_end_query(
+        &self,
+        _query_pool: &RafxQueryPoolMetal,
+        _query_index: u32,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the metal backend")?
+    }
+
+    pub fn cmd_resolve_queries(
+        &self,
+        _query_pool: &RafxQueryPoolMetal,
+        _first_query: u32,
+        _query_count: u32,
+        _dst_buffer: &RafxBufferMetal,
+        _dst_offset: u64,
+    ) -> RafxResult<()> {
+        Err("Query pools are not supported by the metal backend")?
+    }
 }
@@ -1,8 +1,9 @@
 use crate::{
     RafxBufferDef, RafxComputePipelineDef, RafxDescriptorSetArrayDef, RafxDeviceContext,
-    RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxQueueType, RafxResourceType,
-    RafxResult, RafxRootSignatureDef, RafxSampleCount, RafxSamplerDef, RafxShaderModuleDefMetal,
-    RafxShaderStageDef, RafxSwapchainDef, RafxTextureDef,
+    RafxDeviceInfo, RafxFormat, RafxGraphicsPipelineDef, RafxMemoryBudget, RafxMemoryHeapBudget,
+    RafxQueryPoolDef, RafxQueueType, RafxResourceType, RafxResult, RafxRootSignatureDef,
+    RafxSampleCount, RafxSamplerDef, RafxShaderModuleDefMetal, RafxShaderStageDef,
+    RafxSwapchainDef, RafxTextureDef,
 };
 use raw_window_handle::HasRawWindowHandle;
 use std::sync::Arc;
@@ -10,9 +11,11 @@ use std::sync::Arc;
 use crate::metal::features::MetalFeatures;
 use crate::metal::{
     RafxBufferMetal, RafxDescriptorSetArrayMetal, RafxFenceMetal, RafxPipelineMetal,
-    RafxQueueMetal, RafxRootSignatureMetal, RafxSamplerMetal, RafxSemaphoreMetal, RafxShaderMetal,
-    RafxShaderModuleMetal, RafxSwapchainMetal, RafxTextureMetal,
+    RafxQueryPoolMetal, RafxQueueMetal, RafxRootSignatureMetal, RafxSamplerMetal,
+    RafxSemaphoreMetal, RafxShaderMetal, RafxShaderModuleMetal, RafxSwapchainMetal,
+    RafxTextureMetal,
 };
+use metal_rs::MTLArgumentBuffersTier;
 
 #[cfg(debug_assertions)]
 #[cfg(feature = "track-device-contexts")]
@@ -48,6 +51,22 @@ impl Drop for RafxDeviceContextMetalInner {
 
 impl RafxDeviceContextMetalInner {
     pub fn new() -> RafxResult<Self> {
+        let device = metal_rs::Device::system_default().expect("no device found");
+
+        let metal_features = MetalFeatures::from_device(device.as_ref());
+
+        log::debug!("Device: {}", metal_features.device_name);
+        log::debug!(
+            "GPU family (apple={:?} mac={:?} common={:?}",
+            metal_features.gpu_family_apple,
+            metal_features.gpu_family_mac,
+            metal_features.gpu_family_common
+        );
+
+        // Bindless descriptors need argument buffers that can hold large arrays of resources
+        let supports_bindless_descriptors =
+            metal_features.argument_buffers_tier == MTLArgumentBuffersTier::Tier2;
+
         let device_info = RafxDeviceInfo {
             supports_multithreaded_usage: true,
             // pretty sure this is consistent across macOS device (maybe not M1, not sure)
@@ -58,6 +77,32 @@ impl RafxDeviceContextMetalInner {
             upload_buffer_texture_row_alignment: 1,
             supports_clamp_to_border_color: true, //TODO: Check for iOS support
             max_vertex_attribute_count: 31,
+            // Query pools are not implemented for metal yet
+            timestamp_period: 1.0,
+            supports_timestamp_queries: false,
+            supports_occlusion_queries: false,
+            supports_pipeline_statistics_queries: false,
+            supports_draw_indirect: true,
+            // Emulated by issuing one indirect draw per command
+            supports_multi_draw_indirect: true,
+            // Would require indirect command buffers
+            supports_draw_indirect_count: false,
+            supports_dispatch_indirect: true,
+            // Memoryless textures require an Apple GPU
+            supports_transient_attachments: cfg!(any(target_os = "ios", target_arch = "aarch64")),
+            // Would require placing textures in an MTLHeap
+            supports_resource_aliasing: false,
+            supports_subpasses: false,
+            supports_secondary_command_buffers: false,
+            supports_blit_images: false,
+            supports_pipeline_cache_data: false,
+            supports_bindless_descriptors,
+            // Tier 2 argument buffers can reference up to 500,000 textures
+            max_bindless_descriptor_count: if supports_bindless_descriptors {
+                500_000
+            } else {
+                0
+            },
         };
 
         #[cfg(debug_assertions)]
@@ -69,18 +114,6 @@ impl RafxDeviceContextMetalInner {
             all_contexts
         };
 
-        let device = metal_rs::Device::system_default().expect("no device found");
-
-        let metal_features = MetalFeatures::from_device(device.as_ref());
-
-        log::debug!("Device: {}", metal_features.device_name);
-        log::debug!(
-            "GPU family (apple={:?} mac={:?} common={:?}",
-            metal_features.gpu_family_apple,
-            metal_features.gpu_family_mac,
-            metal_features.gpu_family_common
-        );
-
         Ok(RafxDeviceContextMetalInner {
             device_info,
             device,
@@ -175,6 +208,33 @@ impl RafxDeviceContextMetal {
         &self.inner.device_info
     }
 
+    // Pipeline caches are not supported by the Metal backend, so there is never any data to persist
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        Ok(Vec::default())
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        _data: &[u8],
+    ) -> RafxResult<bool> {
+        Ok(false)
+    }
+
+    // Metal has a single memory pool that is shared by the whole device
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        let device = self.device();
+        let budget_in_bytes = device.recommended_max_working_set_size();
+        Ok(RafxMemoryBudget {
+            heaps: vec![RafxMemoryHeapBudget {
+                is_device_local: true,
+                heap_size_in_bytes: budget_in_bytes,
+                budget_in_bytes,
+                usage_in_bytes: device.current_allocated_size() as u64,
+            }],
+            is_budget_reported_by_driver: true,
+        })
+    }
+
     pub fn device(&self) -> &metal_rs::Device {
         &self.inner.device
     }
@@ -229,6 +289,13 @@ impl RafxDeviceContextMetal {
         RafxSamplerMetal::new(self, sampler_def)
     }
 
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolMetal> {
+        RafxQueryPoolMetal::new(self, query_pool_def)
+    }
+
     pub fn create_texture(
         &self,
         texture_def: &RafxTextureDef,
@@ -43,6 +43,9 @@ pub use descriptor_set_array::*;
 mod sampler;
 pub use sampler::*;
 
+mod query_pool;
+pub use query_pool::*;
+
 mod pipeline;
 pub use pipeline::*;
 
@@ -66,6 +66,15 @@ impl RafxPipelineMetal {
         &self.root_signature
     }
 
+    pub fn set_debug_name(
+        &self,
+        _name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        // Metal pipeline state labels can only be set on the descriptor before the pipeline is
+        // created, so there is nothing to do here
+        Ok(())
+    }
+
     pub fn metal_render_pipeline(&self) -> Option<&metal_rs::RenderPipelineStateRef> {
         match &self.pipeline {
             MetalPipelineState::Graphics(pipeline) => Some(pipeline.as_ref()),
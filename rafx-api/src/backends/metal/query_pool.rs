@@ -0,0 +1,29 @@
+use crate::metal::RafxDeviceContextMetal;
+use crate::{RafxQueryPoolDef, RafxResult};
+
+/// Query pools are not yet supported by the metal backend. Creating one will return an error.
+#[derive(Debug, Clone)]
+pub struct RafxQueryPoolMetal {
+    query_pool_def: RafxQueryPoolDef,
+}
+
+impl RafxQueryPoolMetal {
+    pub fn query_pool_def(&self) -> &RafxQueryPoolDef {
+        &self.query_pool_def
+    }
+
+    pub fn new(
+        _device_context: &RafxDeviceContextMetal,
+        _query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolMetal> {
+        return Err("Query pools are not supported by the metal backend")?;
+    }
+
+    pub fn get_results(
+        &self,
+        _first_query: u32,
+        _query_count: u32,
+    ) -> RafxResult<Option<Vec<u64>>> {
+        return Err("Query pools are not supported by the metal backend")?;
+    }
+}
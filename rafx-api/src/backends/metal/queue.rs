@@ -33,6 +33,11 @@ impl RafxQueueMetal {
         self.inner.queue_id
     }
 
+    // There is a single queue family, so resources are never transferred between queues
+    pub fn queue_family_index(&self) -> u32 {
+        0
+    }
+
     //
     // These barrier flag helpers are not meant to be threadsafe, the Rafx API assumes command
     // buffers and the queues they come from are not concurrently accessed. (Even if we were careful
@@ -1,7 +1,7 @@
 use crate::metal::{RafxDeviceContextMetal, RafxSamplerMetal};
 use crate::{
-    RafxDescriptorIndex, RafxPipelineType, RafxResourceType, RafxResult, RafxRootSignatureDef,
-    MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxDescriptorIndex, RafxPipelineType, RafxPushConstantIndex, RafxResourceType, RafxResult,
+    RafxRootSignatureDef, RafxShaderStageFlags, MAX_DESCRIPTOR_SET_LAYOUTS,
 };
 use cocoa_foundation::foundation::NSUInteger;
 use fnv::FnvHashMap;
@@ -40,6 +40,13 @@ pub(crate) struct DescriptorInfo {
     pub(crate) argument_buffer_id: NSUInteger,
 }
 
+#[derive(Clone, Debug)]
+pub(crate) struct PushConstantInfo {
+    pub(crate) name: Option<String>,
+    pub(crate) size_in_bytes: u32,
+    pub(crate) used_in_shader_stages: RafxShaderStageFlags,
+}
+
 #[derive(Default, Debug)]
 pub(crate) struct DescriptorSetLayoutInfo {
     // Settable descriptors, immutable samplers are omitted
@@ -64,6 +71,8 @@ pub(crate) struct RafxRootSignatureMetalInner {
     pub(crate) layouts: [DescriptorSetLayoutInfo; MAX_DESCRIPTOR_SET_LAYOUTS],
     pub(crate) descriptors: Vec<DescriptorInfo>,
     pub(crate) name_to_descriptor_index: FnvHashMap<String, RafxDescriptorIndex>,
+    pub(crate) push_constants: Vec<PushConstantInfo>,
+    pub(crate) name_to_push_constant_index: FnvHashMap<String, RafxPushConstantIndex>,
 
     // --- metal-specific ---
     // Keeps them in scope so they don't drop
@@ -119,6 +128,22 @@ impl RafxRootSignatureMetal {
         self.inner.descriptors.get(descriptor_index.0 as usize)
     }
 
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        self.inner.name_to_push_constant_index.get(name).copied()
+    }
+
+    pub(crate) fn push_constant(
+        &self,
+        push_constant_index: RafxPushConstantIndex,
+    ) -> Option<&PushConstantInfo> {
+        self.inner
+            .push_constants
+            .get(push_constant_index.0 as usize)
+    }
+
     pub fn new(
         device_context: &RafxDeviceContextMetal,
         root_signature_def: &RafxRootSignatureDef,
@@ -138,6 +163,10 @@ impl RafxRootSignatureMetal {
         // Make sure all shaders are compatible/build lookup of shared data from them
         let (pipeline_type, mut merged_resources, _merged_resources_name_index_map) =
             crate::internal_shared::merge_resources(root_signature_def)?;
+        crate::internal_shared::validate_bindless_resources(
+            device_context.device_info(),
+            &merged_resources,
+        )?;
 
         merged_resources.sort_by(|lhs, rhs| lhs.binding.cmp(&rhs.binding));
 
@@ -154,12 +183,34 @@ impl RafxRootSignatureMetal {
 
         let mut descriptors = Vec::with_capacity(merged_resources.len());
         let mut name_to_descriptor_index = FnvHashMap::default();
+        let mut push_constants = vec![];
+        let mut name_to_push_constant_index = FnvHashMap::default();
 
         for resource in &merged_resources {
             resource.validate()?;
 
-            // Not currently supported
-            assert_ne!(resource.resource_type, RafxResourceType::ROOT_CONSTANT);
+            // Push constants are emulated with setBytes at METAL_PUSH_CONSTANT_BUFFER_INDEX, so
+            // they do not occupy a slot in an argument buffer
+            if resource.resource_type == RafxResourceType::ROOT_CONSTANT {
+                if !push_constants.is_empty() {
+                    Err(format!(
+                        "Push constant {:?} cannot be used because metal supports only one push constant block per root signature",
+                        resource.name
+                    ))?;
+                }
+
+                let push_constant_index = RafxPushConstantIndex(push_constants.len() as u32);
+                if let Some(name) = resource.name.as_ref() {
+                    name_to_push_constant_index.insert(name.clone(), push_constant_index);
+                }
+
+                push_constants.push(PushConstantInfo {
+                    name: resource.name.clone(),
+                    size_in_bytes: resource.size_in_bytes,
+                    used_in_shader_stages: resource.used_in_shader_stages,
+                });
+                continue;
+            }
 
             // Verify set index is valid
 
@@ -216,7 +267,8 @@ impl RafxRootSignatureMetal {
                 // Add it to the descriptor list
                 descriptors.push(DescriptorInfo {
                     name: resource.name.clone(),
-                    resource_type: resource.resource_type,
+                    // Bindless descriptors are plain arrays in the argument buffer
+                    resource_type: resource.resource_type - RafxResourceType::BINDLESS,
                     //texture_dimensions: resource.texture_dimensions,
                     set_index: resource.set_index,
                     binding: resource.binding,
@@ -288,6 +340,8 @@ impl RafxRootSignatureMetal {
             layouts,
             descriptors,
             name_to_descriptor_index,
+            push_constants,
+            name_to_push_constant_index,
             argument_buffer_resource_usages,
             argument_descriptors,
         };
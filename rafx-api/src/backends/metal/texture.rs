@@ -3,7 +3,7 @@ use crate::{
     RafxMemoryUsage, RafxResourceType, RafxResult, RafxSampleCount, RafxTextureDef,
     RafxTextureDimensions,
 };
-use metal_rs::{MTLTextureType, MTLTextureUsage};
+use metal_rs::{MTLResourceOptions, MTLStorageMode, MTLTextureType, MTLTextureUsage};
 use std::hash::{Hash, Hasher};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -78,10 +78,26 @@ impl RafxTextureMetal {
         self.inner.image.metal_texture()
     }
 
+    // Textures that wrap an existing image (like a drawable) do not own its memory
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        match &self.inner.image {
+            RafxRawImageMetal::Owned(texture) => texture.allocated_size() as u64,
+            RafxRawImageMetal::Ref(_) => 0,
+        }
+    }
+
     pub fn metal_mip_level_uav_views(&self) -> &[metal_rs::Texture] {
         &self.inner.mip_level_uav_views
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.metal_texture().set_label(name.as_ref());
+        Ok(())
+    }
+
     pub fn new(
         device_context: &RafxDeviceContextMetal,
         texture_def: &RafxTextureDef,
@@ -158,6 +174,14 @@ impl RafxTextureMetal {
             descriptor.set_storage_mode(RafxMemoryUsage::GpuOnly.mtl_storage_mode());
             descriptor.set_cpu_cache_mode(RafxMemoryUsage::GpuOnly.mtl_cpu_cache_mode());
             descriptor.set_resource_options(RafxMemoryUsage::GpuOnly.mtl_resource_options());
+            if texture_def
+                .resource_type
+                .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+                && device_context.device_info().supports_transient_attachments
+            {
+                descriptor.set_storage_mode(MTLStorageMode::Memoryless);
+                descriptor.set_resource_options(MTLResourceOptions::StorageModeMemoryless);
+            }
             descriptor.set_texture_type(mtl_texture_type);
             descriptor.set_array_length(mtl_array_length as _);
             descriptor.set_sample_count(texture_def.sample_count.into());
@@ -42,6 +42,12 @@ pub struct RafxApiDefVulkan {
     /// Used to enable/disable validation at runtime. Not all APIs allow this. Validation is helpful
     /// during development but very expensive. Applications should not ship with validation enabled.
     pub validation_mode: RafxValidationMode,
+
+    /// Create the instance and device without surface or swapchain extensions. Swapchains cannot
+    /// be created on a headless device, but textures can still be used as render targets. This is
+    /// useful for offline rendering, compute-only tools, and automated tests. Devices created
+    /// with `RafxApiVulkan::new_headless` are always headless.
+    pub headless: bool,
     // The OS-specific layers/extensions are already included. Debug layers/extension are included
     // if enable_validation is true
     //TODO: Additional instance layer names
@@ -55,6 +61,7 @@ impl Default for RafxApiDefVulkan {
             app_name: CString::new("Rafx Application").unwrap(),
             link_method: Default::default(),
             validation_mode: Default::default(),
+            headless: false,
         }
     }
 }
@@ -86,9 +93,41 @@ impl RafxApiVulkan {
     /// behavior on the CPU for reasons other than interacting with the GPU.
     pub unsafe fn new(
         window: &dyn HasRawWindowHandle,
+        api_def: &RafxApiDef,
+        vk_api_def: &RafxApiDefVulkan,
+    ) -> RafxResult<Self> {
+        let window = if vk_api_def.headless {
+            None
+        } else {
+            Some(window)
+        };
+
+        Self::new_internal(window, api_def, vk_api_def)
+    }
+
+    /// Create a device that is not associated with a window. Surface and swapchain extensions are
+    /// not enabled, regardless of `RafxApiDefVulkan::headless`.
+    ///
+    /// # Safety
+    ///
+    /// GPU programming is fundamentally unsafe, so all rafx APIs that interact with the GPU should
+    /// be considered unsafe. However, rafx APIs are only gated by unsafe if they can cause undefined
+    /// behavior on the CPU for reasons other than interacting with the GPU.
+    pub unsafe fn new_headless(
+        api_def: &RafxApiDef,
+        vk_api_def: &RafxApiDefVulkan,
+    ) -> RafxResult<Self> {
+        Self::new_internal(None, api_def, vk_api_def)
+    }
+
+    unsafe fn new_internal(
+        window: Option<&dyn HasRawWindowHandle>,
         _api_def: &RafxApiDef,
         vk_api_def: &RafxApiDefVulkan,
     ) -> RafxResult<Self> {
+        let headless = window.is_none();
+        log::debug!("Headless: {}", headless);
+
         let link_method = vk_api_def.link_method;
         let app_name = vk_api_def.app_name.clone();
 
@@ -119,7 +158,7 @@ impl RafxApiVulkan {
             validation_layer_debug_report_flags,
         )?;
 
-        let inner = Arc::new(RafxDeviceContextVulkanInner::new(&instance)?);
+        let inner = Arc::new(RafxDeviceContextVulkanInner::new(&instance, headless)?);
         let device_context = RafxDeviceContextVulkan::new(inner)?;
 
         Ok(RafxApiVulkan {
@@ -17,8 +17,13 @@ pub struct RafxBufferVulkan {
     buffer_raw: Option<RafxBufferRaw>,
 
     buffer_def: RafxBufferDef,
+    allocation_size_in_bytes: u64,
     uniform_texel_view: Option<vk::BufferView>,
     storage_texel_view: Option<vk::BufferView>,
+
+    // Only set for buffers created with new_aliased(). The buffer is bound to this memory rather
+    // than an allocation of its own
+    aliased_memory: Option<VkAliasedMemory>,
 }
 
 impl RafxBufferVulkan {
@@ -44,6 +49,18 @@ impl RafxBufferVulkan {
         &self.buffer_def
     }
 
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.allocation_size_in_bytes
+    }
+
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.device_context
+            .set_debug_name(self.vk_buffer(), name.as_ref())
+    }
+
     pub fn map_buffer(&self) -> RafxResult<*mut u8> {
         let ptr = self
             .device_context
@@ -112,27 +129,7 @@ impl RafxBufferVulkan {
         buffer_def: &RafxBufferDef,
     ) -> RafxResult<Self> {
         buffer_def.verify();
-        let mut allocation_size = buffer_def.size;
-        if buffer_def
-            .resource_type
-            .intersects(RafxResourceType::UNIFORM_BUFFER)
-        {
-            allocation_size = rafx_base::memory::round_size_up_to_alignment_u64(
-                buffer_def.size,
-                device_context.limits().min_uniform_buffer_offset_alignment,
-            )
-        }
-
-        let mut usage_flags = super::util::resource_type_buffer_usage_flags(
-            buffer_def.resource_type,
-            buffer_def.format != RafxFormat::UNDEFINED,
-        );
-
-        if buffer_def.memory_usage == RafxMemoryUsage::GpuOnly
-            || buffer_def.memory_usage == RafxMemoryUsage::CpuToGpu
-        {
-            usage_flags |= vk::BufferUsageFlags::TRANSFER_DST;
-        }
+        let buffer_info = Self::buffer_create_info(device_context, buffer_def);
 
         let mut flags = vk_mem::AllocationCreateFlags::NONE;
         if buffer_def.always_mapped {
@@ -149,13 +146,6 @@ impl RafxBufferVulkan {
             user_data: None,
         };
 
-        assert_ne!(allocation_size, 0);
-
-        let buffer_info = vk::BufferCreateInfo::builder()
-            .size(allocation_size)
-            .usage(usage_flags)
-            .sharing_mode(vk::SharingMode::EXCLUSIVE);
-
         //TODO: Better way of handling allocator errors
         let (buffer, allocation, allocation_info) = device_context
             .allocator()
@@ -174,6 +164,133 @@ impl RafxBufferVulkan {
             buffer_def.always_mapped
         );
 
+        Self::create_from_buffer(
+            device_context,
+            buffer_raw,
+            allocation_info,
+            buffer_def,
+            buffer_info.usage,
+            None,
+        )
+    }
+
+    // All buffers are placed in the same memory, so only one of them may be in use at a time. The
+    // memory is sized to fit the largest buffer. Aliased buffers must be GpuOnly.
+    pub fn new_aliased(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<Self>> {
+        let device = device_context.device();
+
+        let mut buffers = Vec::with_capacity(buffer_defs.len());
+        let mut memory_requirements = Vec::with_capacity(buffer_defs.len());
+        for buffer_def in buffer_defs {
+            buffer_def.verify();
+            assert_eq!(buffer_def.memory_usage, RafxMemoryUsage::GpuOnly);
+            assert!(!buffer_def.always_mapped);
+
+            let buffer_info = Self::buffer_create_info(device_context, buffer_def);
+            unsafe {
+                let buffer = device.create_buffer(&buffer_info, None)?;
+                buffers.push((buffer, buffer_info.usage));
+                memory_requirements.push(device.get_buffer_memory_requirements(buffer));
+            }
+        }
+
+        let aliased_memory = VkAliasedMemory::new(
+            device_context,
+            &memory_requirements,
+            vk::MemoryPropertyFlags::empty(),
+        )
+        .and_then(|aliased_memory| {
+            for &(buffer, _) in &buffers {
+                aliased_memory.bind_buffer(buffer)?;
+            }
+
+            Ok(aliased_memory)
+        });
+
+        let aliased_memory = match aliased_memory {
+            Ok(aliased_memory) => aliased_memory,
+            Err(e) => {
+                for (buffer, _) in buffers {
+                    unsafe {
+                        device.destroy_buffer(buffer, None);
+                    }
+                }
+
+                return Err(e);
+            }
+        };
+
+        let allocation_info = device_context
+            .allocator()
+            .get_allocation_info(&aliased_memory.allocation())?;
+
+        let mut aliased_buffers = Vec::with_capacity(buffer_defs.len());
+        for ((buffer, usage_flags), buffer_def) in buffers.into_iter().zip(buffer_defs) {
+            let buffer_raw = RafxBufferRaw {
+                buffer,
+                allocation: aliased_memory.allocation(),
+            };
+
+            aliased_buffers.push(Self::create_from_buffer(
+                device_context,
+                buffer_raw,
+                allocation_info.clone(),
+                buffer_def,
+                usage_flags,
+                Some(aliased_memory.clone()),
+            )?);
+        }
+
+        Ok(aliased_buffers)
+    }
+
+    fn buffer_create_info(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_def: &RafxBufferDef,
+    ) -> vk::BufferCreateInfo {
+        let mut allocation_size = buffer_def.size;
+        if buffer_def
+            .resource_type
+            .intersects(RafxResourceType::UNIFORM_BUFFER)
+        {
+            allocation_size = rafx_base::memory::round_size_up_to_alignment_u64(
+                buffer_def.size,
+                device_context.limits().min_uniform_buffer_offset_alignment,
+            )
+        }
+
+        let mut usage_flags = super::util::resource_type_buffer_usage_flags(
+            buffer_def.resource_type,
+            buffer_def.format != RafxFormat::UNDEFINED,
+        );
+
+        if buffer_def.memory_usage == RafxMemoryUsage::GpuOnly
+            || buffer_def.memory_usage == RafxMemoryUsage::CpuToGpu
+            || buffer_def.memory_usage == RafxMemoryUsage::GpuToCpu
+        {
+            usage_flags |= vk::BufferUsageFlags::TRANSFER_DST;
+        }
+
+        assert_ne!(allocation_size, 0);
+
+        vk::BufferCreateInfo::builder()
+            .size(allocation_size)
+            .usage(usage_flags)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .build()
+    }
+
+    fn create_from_buffer(
+        device_context: &RafxDeviceContextVulkan,
+        buffer_raw: RafxBufferRaw,
+        allocation_info: vk_mem::AllocationInfo,
+        buffer_def: &RafxBufferDef,
+        usage_flags: vk::BufferUsageFlags,
+        aliased_memory: Option<VkAliasedMemory>,
+    ) -> RafxResult<Self> {
         // let mut buffer_offset = 0;
         // if buffer_def.resource_type.intersects(RafxResourceType::BUFFER | RafxResourceType::BUFFER_READ_WRITE) {
         //     buffer_offset = buffer_def.struct_stride * buffer_def.first_element;
@@ -229,13 +346,27 @@ impl RafxBufferVulkan {
             None
         };
 
+        // Aliased buffers share a single allocation, so only count the memory this buffer requires
+        let allocation_size_in_bytes = if aliased_memory.is_some() {
+            unsafe {
+                device_context
+                    .device()
+                    .get_buffer_memory_requirements(buffer_raw.buffer)
+                    .size
+            }
+        } else {
+            allocation_info.get_size() as u64
+        };
+
         Ok(RafxBufferVulkan {
             device_context: device_context.clone(),
             allocation_info: TrustCell::new(allocation_info),
             buffer_raw: Some(buffer_raw),
             buffer_def: buffer_def.clone(),
+            allocation_size_in_bytes,
             uniform_texel_view,
             storage_texel_view,
+            aliased_memory,
         })
     }
 }
@@ -263,10 +394,17 @@ impl Drop for RafxBufferVulkan {
                 self.buffer_def.always_mapped
             );
 
-            self.device_context
-                .allocator()
-                .destroy_buffer(buffer_raw.buffer, &buffer_raw.allocation)
-                .unwrap();
+            if self.aliased_memory.is_some() {
+                // The shared memory is freed once every buffer that aliases it is dropped
+                unsafe {
+                    device.destroy_buffer(buffer_raw.buffer, None);
+                }
+            } else {
+                self.device_context
+                    .allocator()
+                    .destroy_buffer(buffer_raw.buffer, &buffer_raw.allocation)
+                    .unwrap();
+            }
         }
 
         log::trace!("destroyed RafxBufferVulkanInner");
@@ -2,7 +2,21 @@ use crate::vulkan::*;
 use crate::*;
 use ash::version::DeviceV1_0;
 use ash::vk;
+use std::ffi::CString;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+// The renderpass being recorded by a primary command buffer. Secondary command buffers need this
+// to continue the current subpass
+#[derive(Debug, Copy, Clone)]
+struct ActiveRenderpassVulkan {
+    vk_renderpass: vk::RenderPass,
+    vk_framebuffer: vk::Framebuffer,
+    subpass_index: u32,
+    contents: RafxSubpassContents,
+    width: u32,
+    height: u32,
+}
 
 #[derive(Debug)]
 pub struct RafxCommandBufferVulkan {
@@ -11,7 +25,9 @@ pub struct RafxCommandBufferVulkan {
     vk_command_buffer: vk::CommandBuffer,
     queue_type: RafxQueueType,
     queue_family_index: u32,
+    is_secondary: bool,
     has_active_renderpass: AtomicBool,
+    active_renderpass: Mutex<Option<ActiveRenderpassVulkan>>,
 }
 
 impl Into<RafxCommandBuffer> for RafxCommandBufferVulkan {
@@ -53,7 +69,9 @@ impl RafxCommandBufferVulkan {
             vk_command_buffer,
             queue_type: command_pool.queue_type(),
             queue_family_index: command_pool.queue_family_index(),
+            is_secondary: command_buffer_def.is_secondary,
             has_active_renderpass: AtomicBool::new(false),
+            active_renderpass: Mutex::new(None),
         })
     }
 
@@ -62,6 +80,10 @@ impl RafxCommandBufferVulkan {
     }
 
     pub fn begin(&self) -> RafxResult<()> {
+        if self.is_secondary {
+            Err("Secondary command buffers must be started with begin_secondary")?;
+        }
+
         //TODO: Use one-time-submit?
         let command_buffer_usage_flags = vk::CommandBufferUsageFlags::empty();
 
@@ -76,6 +98,58 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn begin_secondary(
+        &self,
+        primary_command_buffer: &RafxCommandBufferVulkan,
+    ) -> RafxResult<()> {
+        if !self.is_secondary || primary_command_buffer.is_secondary {
+            Err("begin_secondary must be called on a secondary command buffer with a primary command buffer")?;
+        }
+
+        // Only continue the renderpass if its subpass contents are recorded in secondary command
+        // buffers
+        let active_renderpass = primary_command_buffer
+            .active_renderpass
+            .lock()
+            .unwrap()
+            .filter(|x| x.contents == RafxSubpassContents::SecondaryCommandBuffers);
+
+        let mut command_buffer_usage_flags = vk::CommandBufferUsageFlags::empty();
+        let mut inheritance_info = vk::CommandBufferInheritanceInfo::builder();
+        if let Some(active_renderpass) = &active_renderpass {
+            command_buffer_usage_flags |= vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE;
+            inheritance_info = inheritance_info
+                .render_pass(active_renderpass.vk_renderpass)
+                .subpass(active_renderpass.subpass_index)
+                .framebuffer(active_renderpass.vk_framebuffer);
+        }
+
+        let begin_info = vk::CommandBufferBeginInfo::builder()
+            .flags(command_buffer_usage_flags)
+            .inheritance_info(&*inheritance_info);
+
+        unsafe {
+            self.device_context
+                .device()
+                .begin_command_buffer(self.vk_command_buffer, &*begin_info)?;
+        }
+
+        // Dynamic state is not inherited from the primary command buffer
+        if let Some(active_renderpass) = &active_renderpass {
+            self.cmd_set_viewport(
+                0.0,
+                0.0,
+                active_renderpass.width as f32,
+                active_renderpass.height as f32,
+                0.0,
+                1.0,
+            )?;
+            self.cmd_set_scissor(0, 0, active_renderpass.width, active_renderpass.height)?;
+        }
+
+        Ok(())
+    }
+
     pub fn end(&self) -> RafxResult<()> {
         if self.has_active_renderpass.load(Ordering::Relaxed) {
             unsafe {
@@ -87,6 +161,8 @@ impl RafxCommandBufferVulkan {
             self.has_active_renderpass.store(false, Ordering::Relaxed);
         }
 
+        *self.active_renderpass.lock().unwrap() = None;
+
         unsafe {
             self.device_context
                 .device()
@@ -110,6 +186,30 @@ impl RafxCommandBufferVulkan {
         &self,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+    ) -> RafxResult<()> {
+        self.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, &[])
+    }
+
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        self.cmd_begin_render_pass_with_contents(
+            color_targets,
+            depth_target,
+            subpasses,
+            RafxSubpassContents::Inline,
+        )
+    }
+
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
     ) -> RafxResult<()> {
         if self.has_active_renderpass.load(Ordering::Relaxed) {
             self.cmd_end_render_pass()?;
@@ -127,12 +227,14 @@ impl RafxCommandBufferVulkan {
                 &self.device_context,
                 color_targets,
                 depth_target.as_ref(),
+                subpasses,
             )?;
             let framebuffer = resource_cache.framebuffer_cache.get_or_create_framebuffer(
                 &self.device_context,
                 &renderpass,
                 color_targets,
                 depth_target.as_ref(),
+                subpasses,
             )?;
 
             (renderpass, framebuffer)
@@ -229,23 +331,50 @@ impl RafxCommandBufferVulkan {
             self.device_context.device().cmd_begin_render_pass(
                 self.vk_command_buffer,
                 &*begin_renderpass_create_info,
-                vk::SubpassContents::INLINE,
+                contents.into(),
             );
         }
 
         self.has_active_renderpass.store(true, Ordering::Relaxed);
-
-        self.cmd_set_viewport(
-            0.0,
-            0.0,
-            framebuffer.width() as f32,
-            framebuffer.height() as f32,
-            0.0,
-            1.0,
-        )
-        .unwrap();
-        self.cmd_set_scissor(0, 0, framebuffer.width(), framebuffer.height())
+        *self.active_renderpass.lock().unwrap() = Some(ActiveRenderpassVulkan {
+            vk_renderpass: renderpass.vk_renderpass(),
+            vk_framebuffer: framebuffer.vk_framebuffer(),
+            subpass_index: 0,
+            contents,
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+        });
+
+        // Secondary command buffers set their own viewport and scissor
+        if contents == RafxSubpassContents::Inline {
+            self.cmd_set_viewport(
+                0.0,
+                0.0,
+                framebuffer.width() as f32,
+                framebuffer.height() as f32,
+                0.0,
+                1.0,
+            )
             .unwrap();
+            self.cmd_set_scissor(0, 0, framebuffer.width(), framebuffer.height())
+                .unwrap();
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        let mut active_renderpass = self.active_renderpass.lock().unwrap();
+        let active_renderpass = active_renderpass
+            .as_mut()
+            .ok_or("cmd_next_subpass must be called within a renderpass")?;
+        active_renderpass.subpass_index += 1;
+
+        unsafe {
+            self.device_context
+                .device()
+                .cmd_next_subpass(self.vk_command_buffer, active_renderpass.contents.into());
+        }
 
         Ok(())
     }
@@ -258,6 +387,70 @@ impl RafxCommandBufferVulkan {
             self.has_active_renderpass.store(false, Ordering::Relaxed);
         }
 
+        *self.active_renderpass.lock().unwrap() = None;
+
+        Ok(())
+    }
+
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        command_buffers: &[&RafxCommandBufferVulkan],
+    ) -> RafxResult<()> {
+        if self.is_secondary || command_buffers.iter().any(|x| !x.is_secondary) {
+            Err("cmd_execute_secondary_command_buffers must be called on a primary command buffer with secondary command buffers")?;
+        }
+
+        let vk_command_buffers: Vec<_> = command_buffers
+            .iter()
+            .map(|x| x.vk_command_buffer)
+            .collect();
+
+        unsafe {
+            self.device_context
+                .device()
+                .cmd_execute_commands(self.vk_command_buffer, &vk_command_buffers);
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        if let Some(debug_utils) = self.device_context.debug_utils() {
+            let name = CString::new(name).map_err(|_| "Debug name may not contain a nul byte")?;
+            let label = vk::DebugUtilsLabelEXT::builder().label_name(&name);
+            unsafe {
+                debug_utils.cmd_begin_debug_utils_label(self.vk_command_buffer, &*label);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        if let Some(debug_utils) = self.device_context.debug_utils() {
+            unsafe {
+                debug_utils.cmd_end_debug_utils_label(self.vk_command_buffer);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        if let Some(debug_utils) = self.device_context.debug_utils() {
+            let name = CString::new(name).map_err(|_| "Debug name may not contain a nul byte")?;
+            let label = vk::DebugUtilsLabelEXT::builder().label_name(&name);
+            unsafe {
+                debug_utils.cmd_insert_debug_utils_label(self.vk_command_buffer, &*label);
+            }
+        }
+
         Ok(())
     }
 
@@ -421,6 +614,36 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignatureVulkan,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        let push_constant = root_signature
+            .push_constant(push_constant_index)
+            .ok_or("Push constant index not found in root signature")?;
+        let vk_push_constant_range = &push_constant.vk_push_constant_range;
+
+        crate::internal_shared::validate_push_constant_data(
+            push_constant.name.as_deref(),
+            vk_push_constant_range.size,
+            data,
+        )?;
+
+        unsafe {
+            self.device_context.device().cmd_push_constants(
+                self.vk_command_buffer,
+                root_signature.vk_pipeline_layout(),
+                vk_push_constant_range.stage_flags,
+                vk_push_constant_range.offset,
+                data,
+            )
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_draw(
         &self,
         vertex_count: u32,
@@ -519,6 +742,116 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn cmd_draw_indirect(
+        &self,
+        indirect_buffer: &RafxBufferVulkan,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_draw_indirect(
+                self.vk_command_buffer,
+                indirect_buffer.vk_buffer(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+                std::mem::size_of::<RafxDrawIndirectCommand>() as u32,
+            )
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        indirect_buffer: &RafxBufferVulkan,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_draw_indexed_indirect(
+                self.vk_command_buffer,
+                indirect_buffer.vk_buffer(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+                std::mem::size_of::<RafxDrawIndexedIndirectCommand>() as u32,
+            )
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indirect_count(
+        &self,
+        indirect_buffer: &RafxBufferVulkan,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBufferVulkan,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        let draw_indirect_count = self
+            .device_context
+            .draw_indirect_count()
+            .ok_or("cmd_draw_indirect_count requires VK_KHR_draw_indirect_count")?;
+
+        unsafe {
+            draw_indirect_count.cmd_draw_indirect_count(
+                self.vk_command_buffer,
+                indirect_buffer.vk_buffer(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.vk_buffer(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+                std::mem::size_of::<RafxDrawIndirectCommand>() as u32,
+            )
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        indirect_buffer: &RafxBufferVulkan,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBufferVulkan,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        let draw_indirect_count = self
+            .device_context
+            .draw_indirect_count()
+            .ok_or("cmd_draw_indexed_indirect_count requires VK_KHR_draw_indirect_count")?;
+
+        unsafe {
+            draw_indirect_count.cmd_draw_indexed_indirect_count(
+                self.vk_command_buffer,
+                indirect_buffer.vk_buffer(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.vk_buffer(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+                std::mem::size_of::<RafxDrawIndexedIndirectCommand>() as u32,
+            )
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_dispatch_indirect(
+        &self,
+        indirect_buffer: &RafxBufferVulkan,
+        indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_dispatch_indirect(
+                self.vk_command_buffer,
+                indirect_buffer.vk_buffer(),
+                indirect_buffer_offset_in_bytes,
+            )
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_resource_barrier(
         &self,
         buffer_barriers: &[RafxBufferBarrier],
@@ -535,10 +868,52 @@ impl RafxCommandBufferVulkan {
         let mut src_access_flags = vk::AccessFlags::empty();
         let mut dst_access_flags = vk::AccessFlags::empty();
 
+        // Returns the (src, dst) queue family indices of a barrier, or None if the barrier can be
+        // dropped. A transition between queues of the same family is not an ownership transfer, so
+        // the release barrier alone performs the transition (including any layout change) and the
+        // semaphore between the submissions makes it visible to the acquiring queue. Otherwise the
+        // release and acquire barriers describe the same transition, which is performed once.
+        fn queue_family_indices(
+            device_context: &RafxDeviceContextVulkan,
+            self_queue_family_index: u32,
+            queue_transition: &RafxBarrierQueueTransition,
+        ) -> Option<(u32, u32)> {
+            let (src_queue_family_index, dst_queue_family_index) = match queue_transition {
+                RafxBarrierQueueTransition::ReleaseTo(dst_queue_type) => (
+                    self_queue_family_index,
+                    super::util::queue_type_to_family_index(device_context, *dst_queue_type),
+                ),
+                RafxBarrierQueueTransition::AcquireFrom(src_queue_type) => (
+                    super::util::queue_type_to_family_index(device_context, *src_queue_type),
+                    self_queue_family_index,
+                ),
+                RafxBarrierQueueTransition::None => {
+                    return Some((vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED))
+                }
+            };
+
+            if src_queue_family_index != dst_queue_family_index {
+                Some((src_queue_family_index, dst_queue_family_index))
+            } else if let RafxBarrierQueueTransition::AcquireFrom(_) = queue_transition {
+                None
+            } else {
+                Some((vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED))
+            }
+        }
+
         for barrier in buffer_barriers {
+            let (src_queue_family_index, dst_queue_family_index) = match queue_family_indices(
+                &self.device_context,
+                self.queue_family_index,
+                &barrier.queue_transition,
+            ) {
+                Some(queue_family_indices) => queue_family_indices,
+                None => continue,
+            };
+
             let buffer = barrier.buffer.vk_buffer().unwrap();
 
-            let mut vk_buffer_barrier = vk::BufferMemoryBarrier::builder()
+            let vk_buffer_barrier = vk::BufferMemoryBarrier::builder()
                 .src_access_mask(super::util::resource_state_to_access_flags(
                     barrier.src_state,
                 ))
@@ -548,31 +923,10 @@ impl RafxCommandBufferVulkan {
                 .buffer(buffer.vk_buffer())
                 .size(vk::WHOLE_SIZE)
                 .offset(0)
+                .src_queue_family_index(src_queue_family_index)
+                .dst_queue_family_index(dst_queue_family_index)
                 .build();
 
-            match &barrier.queue_transition {
-                RafxBarrierQueueTransition::ReleaseTo(dst_queue_type) => {
-                    vk_buffer_barrier.src_queue_family_index = self.queue_family_index;
-                    vk_buffer_barrier.dst_queue_family_index =
-                        super::util::queue_type_to_family_index(
-                            &self.device_context,
-                            *dst_queue_type,
-                        );
-                }
-                RafxBarrierQueueTransition::AcquireFrom(src_queue_type) => {
-                    vk_buffer_barrier.src_queue_family_index =
-                        super::util::queue_type_to_family_index(
-                            &self.device_context,
-                            *src_queue_type,
-                        );
-                    vk_buffer_barrier.dst_queue_family_index = self.queue_family_index;
-                }
-                RafxBarrierQueueTransition::None => {
-                    vk_buffer_barrier.src_queue_family_index = vk::QUEUE_FAMILY_IGNORED;
-                    vk_buffer_barrier.dst_queue_family_index = vk::QUEUE_FAMILY_IGNORED;
-                }
-            }
-
             src_access_flags |= vk_buffer_barrier.src_access_mask;
             dst_access_flags |= vk_buffer_barrier.dst_access_mask;
 
@@ -609,31 +963,16 @@ impl RafxCommandBufferVulkan {
             subresource_range
         }
 
-        fn set_queue_family_indices(
-            vk_image_barrier: &mut vk::ImageMemoryBarrier,
-            device_context: &RafxDeviceContextVulkan,
-            self_queue_family_index: u32,
-            queue_transition: &RafxBarrierQueueTransition,
-        ) {
-            match queue_transition {
-                RafxBarrierQueueTransition::ReleaseTo(dst_queue_type) => {
-                    vk_image_barrier.src_queue_family_index = self_queue_family_index;
-                    vk_image_barrier.dst_queue_family_index =
-                        super::util::queue_type_to_family_index(device_context, *dst_queue_type);
-                }
-                RafxBarrierQueueTransition::AcquireFrom(src_queue_type) => {
-                    vk_image_barrier.src_queue_family_index =
-                        super::util::queue_type_to_family_index(device_context, *src_queue_type);
-                    vk_image_barrier.dst_queue_family_index = self_queue_family_index;
-                }
-                RafxBarrierQueueTransition::None => {
-                    vk_image_barrier.src_queue_family_index = vk::QUEUE_FAMILY_IGNORED;
-                    vk_image_barrier.dst_queue_family_index = vk::QUEUE_FAMILY_IGNORED;
-                }
-            }
-        }
-
         for barrier in texture_barriers {
+            let (src_queue_family_index, dst_queue_family_index) = match queue_family_indices(
+                &self.device_context,
+                self.queue_family_index,
+                &barrier.queue_transition,
+            ) {
+                Some(queue_family_indices) => queue_family_indices,
+                None => continue,
+            };
+
             let texture = barrier.texture.vk_texture().unwrap();
 
             let subresource_range =
@@ -663,7 +1002,7 @@ impl RafxCommandBufferVulkan {
                 new_layout
             );
 
-            let mut vk_image_barrier = vk::ImageMemoryBarrier::builder()
+            let vk_image_barrier = vk::ImageMemoryBarrier::builder()
                 .src_access_mask(super::util::resource_state_to_access_flags(
                     barrier.src_state,
                 ))
@@ -674,15 +1013,10 @@ impl RafxCommandBufferVulkan {
                 .new_layout(new_layout)
                 .image(texture.vk_image())
                 .subresource_range(subresource_range)
+                .src_queue_family_index(src_queue_family_index)
+                .dst_queue_family_index(dst_queue_family_index)
                 .build();
 
-            set_queue_family_indices(
-                &mut vk_image_barrier,
-                &self.device_context,
-                self.queue_family_index,
-                &barrier.queue_transition,
-            );
-
             src_access_flags |= vk_image_barrier.src_access_mask;
             dst_access_flags |= vk_image_barrier.dst_access_mask;
 
@@ -776,6 +1110,170 @@ impl RafxCommandBufferVulkan {
         Ok(())
     }
 
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTextureVulkan,
+        dst_buffer: &RafxBufferVulkan,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        let texture_def = src_texture.texture_def();
+
+        let width = 1.max(texture_def.extents.width >> params.mip_level);
+        let height = 1.max(texture_def.extents.height >> params.mip_level);
+        let depth = 1.max(texture_def.extents.depth >> params.mip_level);
+
+        // buffer_row_length is specified in texels, so the row pitch must be a whole number of
+        // blocks
+        let format = texture_def.format;
+        let block_size_in_bytes = format.block_or_pixel_size_in_bytes();
+        let row_pitch = crate::internal_shared::texture_copy_row_pitch(
+            self.device_context.device_info(),
+            format,
+            width,
+        );
+        if row_pitch % block_size_in_bytes != 0 {
+            Err(format!(
+                "Row pitch {} for format {:?} is not a multiple of the block size {}",
+                row_pitch, format, block_size_in_bytes
+            ))?;
+        }
+
+        let buffer_row_length = row_pitch / block_size_in_bytes * format.block_width_in_pixels();
+
+        unsafe {
+            self.device_context.device().cmd_copy_image_to_buffer(
+                self.vk_command_buffer,
+                src_texture.vk_image(),
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst_buffer.vk_buffer(),
+                &[vk::BufferImageCopy {
+                    image_extent: vk::Extent3D {
+                        width,
+                        height,
+                        depth,
+                    },
+                    image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: src_texture.vk_aspect_mask(),
+                        mip_level: params.mip_level as u32,
+                        base_array_layer: params.array_layer as u32,
+                        layer_count: 1,
+                    },
+                    buffer_offset: params.buffer_offset,
+                    buffer_image_height: 0,
+                    buffer_row_length,
+                }],
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_reset_query_pool(
+        &self,
+        query_pool: &RafxQueryPoolVulkan,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<()> {
+        assert!(
+            !self.has_active_renderpass.load(Ordering::Relaxed),
+            "cmd_reset_query_pool may not be called if inside render pass"
+        );
+
+        unsafe {
+            self.device_context.device().cmd_reset_query_pool(
+                self.vk_command_buffer,
+                query_pool.vk_query_pool(),
+                first_query,
+                query_count,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_write_timestamp(
+        &self,
+        query_pool: &RafxQueryPoolVulkan,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_write_timestamp(
+                self.vk_command_buffer,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                query_pool.vk_query_pool(),
+                query_index,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_begin_query(
+        &self,
+        query_pool: &RafxQueryPoolVulkan,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_begin_query(
+                self.vk_command_buffer,
+                query_pool.vk_query_pool(),
+                query_index,
+                vk::QueryControlFlags::empty(),
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_end_query(
+        &self,
+        query_pool: &RafxQueryPoolVulkan,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        unsafe {
+            self.device_context.device().cmd_end_query(
+                self.vk_command_buffer,
+                query_pool.vk_query_pool(),
+                query_index,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn cmd_resolve_queries(
+        &self,
+        query_pool: &RafxQueryPoolVulkan,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &RafxBufferVulkan,
+        dst_offset: u64,
+    ) -> RafxResult<()> {
+        assert!(
+            !self.has_active_renderpass.load(Ordering::Relaxed),
+            "cmd_resolve_queries may not be called if inside render pass"
+        );
+
+        let values_per_query = query_pool.query_pool_def().query_type.values_per_query();
+        let stride = values_per_query as u64 * std::mem::size_of::<u64>() as u64;
+
+        unsafe {
+            self.device_context.device().cmd_copy_query_pool_results(
+                self.vk_command_buffer,
+                query_pool.vk_query_pool(),
+                first_query,
+                query_count,
+                dst_buffer.vk_buffer(),
+                dst_offset,
+                stride,
+                vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT,
+            );
+        }
+
+        Ok(())
+    }
+
     pub fn cmd_blit_image(
         &self,
         src_texture: &RafxTextureVulkan,
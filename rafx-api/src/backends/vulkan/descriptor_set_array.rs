@@ -122,8 +122,26 @@ impl RafxDescriptorSetArrayVulkan {
             // }
         }
 
-        let descriptor_sets =
-            heap.allocate_descriptor_sets(device_context.device(), &descriptors_set_layouts)?;
+        let layout = &root_signature.inner.layouts[layout_index];
+        let descriptor_sets = if layout.update_after_bind {
+            let pool_sizes: Vec<_> = layout
+                .update_after_bind_pool_sizes
+                .iter()
+                .map(|pool_size| vk::DescriptorPoolSize {
+                    ty: pool_size.ty,
+                    descriptor_count: pool_size.descriptor_count
+                        * descriptor_set_array_def.array_length as u32,
+                })
+                .collect();
+
+            heap.allocate_update_after_bind_descriptor_sets(
+                device_context.device(),
+                &descriptors_set_layouts,
+                &pool_sizes,
+            )?
+        } else {
+            heap.allocate_descriptor_sets(device_context.device(), &descriptors_set_layouts)?
+        };
 
         // let dynamic_size_offset = if dynamic_offset_count > 0 {
         //     assert_eq!(1, dynamic_offset_count);
@@ -429,6 +447,54 @@ impl RafxDescriptorSetArrayVulkan {
                         .build(),
                 );
             }
+            RafxResourceType::INPUT_ATTACHMENT => {
+                let textures = update.elements.textures.ok_or_else(||
+                    format!(
+                        "Tried to update binding {:?} (set: {:?} binding: {} name: {:?} type: {:?}) but the texture element list was None",
+                        update.descriptor_key,
+                        descriptor.set_index,
+                        descriptor.binding,
+                        descriptor.name,
+                        descriptor.resource_type,
+                    )
+                )?;
+                let begin_index =
+                    (descriptor_first_update_data + update.dst_element_offset) as usize;
+                assert!(begin_index + textures.len() <= self.update_data.update_data_count);
+
+                // Modify the update data
+                let mut next_index = begin_index;
+                for texture in textures {
+                    let image_info = &mut self.update_data.image_infos[next_index];
+                    next_index += 1;
+
+                    let vk_texture = texture.vk_texture().unwrap();
+                    image_info.image_view = vk_texture.vk_srv_view().ok_or_else(|| {
+                        format!(
+                            "Tried to update binding {:?} (set: {:?} binding: {} name: {:?} type: {:?}) as an input attachment but there is no srv view",
+                            update.descriptor_key,
+                            descriptor.set_index,
+                            descriptor.binding,
+                            descriptor.name,
+                            descriptor.resource_type,
+                        )
+                    })?;
+
+                    // Must match the layout the subpass reading the attachment uses
+                    image_info.image_layout = if vk_texture.texture_def().format.has_depth() {
+                        vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+                    } else {
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+                    };
+                }
+
+                // Queue a descriptor write
+                self.pending_writes.push(
+                    write_descriptor_builder
+                        .image_info(&self.update_data.image_infos[begin_index..next_index])
+                        .build(),
+                );
+            }
             RafxResourceType::TEXTURE_READ_WRITE => {
                 let textures = update.elements.textures.ok_or_else(||
                     format!(
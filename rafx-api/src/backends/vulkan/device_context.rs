@@ -1,18 +1,24 @@
 use super::internal::*;
+use crate::internal_shared::{
+    decode_pipeline_cache_data, encode_pipeline_cache_data, PipelineCacheDataIdentity,
+    PIPELINE_CACHE_DATA_BACKEND_VULKAN,
+};
 use crate::*;
 use ash::version::{DeviceV1_0, InstanceV1_0};
 use ash::vk;
 use raw_window_handle::HasRawWindowHandle;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
 
 use crate::vulkan::{
     RafxBufferVulkan, RafxDescriptorSetArrayVulkan, RafxFenceVulkan, RafxPipelineVulkan,
-    RafxQueueVulkan, RafxRootSignatureVulkan, RafxSamplerVulkan, RafxSemaphoreVulkan,
-    RafxShaderModuleVulkan, RafxShaderVulkan, RafxSwapchainVulkan, RafxTextureVulkan,
+    RafxQueryPoolVulkan, RafxQueueVulkan, RafxRootSignatureVulkan, RafxSamplerVulkan,
+    RafxSemaphoreVulkan, RafxShaderModuleVulkan, RafxShaderVulkan, RafxSwapchainVulkan,
+    RafxTextureVulkan,
 };
+use ash::extensions::ext::DebugUtils;
 use ash::extensions::khr;
 use fnv::FnvHashMap;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 #[cfg(debug_assertions)]
 #[cfg(feature = "track-device-contexts")]
 use std::sync::atomic::AtomicU64;
@@ -61,6 +67,20 @@ pub struct PhysicalDeviceInfo {
     pub features: vk::PhysicalDeviceFeatures,
     pub extension_properties: Vec<ash::vk::ExtensionProperties>,
     pub all_queue_families: Vec<ash::vk::QueueFamilyProperties>,
+    // True if VK_EXT_descriptor_indexing supports everything needed for bindless descriptors
+    pub supports_bindless_descriptors: bool,
+    pub max_bindless_descriptor_count: u32,
+}
+
+impl PhysicalDeviceInfo {
+    pub fn supports_extension(
+        &self,
+        extension_name: &CStr,
+    ) -> bool {
+        self.extension_properties.iter().any(|extension| unsafe {
+            CStr::from_ptr(extension.extension_name.as_ptr()) == extension_name
+        })
+    }
 }
 
 #[derive(Default, Clone, Debug)]
@@ -80,13 +100,23 @@ pub struct RafxDeviceContextVulkanInner {
     // lock ensures that the present operations for those swapchains do not occur concurrently
     pub(crate) dedicated_present_queue_lock: Mutex<()>,
 
+    // If true, the swapchain extension was not enabled and swapchains cannot be created
+    pub(crate) headless: bool,
+
     device: ash::Device,
     allocator: vk_mem::Allocator,
+    // Used when creating all pipelines. Merging data into it requires exclusive access, so
+    // pipeline creation holds a read lock and merging holds a write lock
+    pipeline_cache: RwLock<vk::PipelineCache>,
     destroyed: AtomicBool,
     entry: Arc<VkEntry>,
     instance: ash::Instance,
     physical_device: vk::PhysicalDevice,
     physical_device_info: PhysicalDeviceInfo,
+    // Only present if VK_EXT_debug_utils was enabled on the instance
+    debug_utils: Option<DebugUtils>,
+    // Only present if VK_KHR_draw_indirect_count is supported by the device
+    draw_indirect_count: Option<khr::DrawIndirectCount>,
 
     #[cfg(debug_assertions)]
     #[cfg(feature = "track-device-contexts")]
@@ -103,6 +133,8 @@ impl Drop for RafxDeviceContextVulkanInner {
             unsafe {
                 log::trace!("destroying device");
                 self.allocator.destroy();
+                self.device
+                    .destroy_pipeline_cache(*self.pipeline_cache.get_mut().unwrap(), None);
                 self.device.destroy_device(None);
                 //self.surface_loader.destroy_surface(self.surface, None);
                 log::trace!("destroyed device");
@@ -112,7 +144,10 @@ impl Drop for RafxDeviceContextVulkanInner {
 }
 
 impl RafxDeviceContextVulkanInner {
-    pub fn new(instance: &VkInstance) -> RafxResult<Self> {
+    pub fn new(
+        instance: &VkInstance,
+        headless: bool,
+    ) -> RafxResult<Self> {
         let physical_device_type_priority = vec![
             PhysicalDeviceType::DiscreteGpu,
             PhysicalDeviceType::IntegratedGpu,
@@ -137,6 +172,7 @@ impl RafxDeviceContextVulkanInner {
             physical_device,
             &physical_device_info,
             &queue_requirements,
+            headless,
         )?;
 
         let queue_allocator = VkQueueAllocatorSet::new(
@@ -157,8 +193,28 @@ impl RafxDeviceContextVulkanInner {
 
         let allocator = vk_mem::Allocator::new(&allocator_create_info)?;
 
+        let pipeline_cache = unsafe {
+            logical_device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::default(), None)?
+        };
+
         let limits = &physical_device_info.properties.limits;
 
+        // Transient attachments can only avoid allocating memory if there is a memory type that
+        // supports lazy allocation. This is common on tile-based (mobile) GPUs
+        let memory_properties = unsafe {
+            instance
+                .instance
+                .get_physical_device_memory_properties(physical_device)
+        };
+        let supports_lazily_allocated_memory = memory_properties.memory_types
+            [..memory_properties.memory_type_count as usize]
+            .iter()
+            .any(|memory_type| {
+                memory_type
+                    .property_flags
+                    .contains(vk::MemoryPropertyFlags::LAZILY_ALLOCATED)
+            });
+
         let device_info = RafxDeviceInfo {
             supports_multithreaded_usage: true,
             min_uniform_buffer_offset_alignment: limits.min_uniform_buffer_offset_alignment as u32,
@@ -168,6 +224,42 @@ impl RafxDeviceContextVulkanInner {
                 as u32,
             supports_clamp_to_border_color: true,
             max_vertex_attribute_count: limits.max_vertex_input_attributes,
+            timestamp_period: limits.timestamp_period,
+            supports_timestamp_queries: limits.timestamp_compute_and_graphics != vk::FALSE,
+            supports_occlusion_queries: true,
+            supports_pipeline_statistics_queries: physical_device_info
+                .features
+                .pipeline_statistics_query
+                != vk::FALSE,
+            supports_draw_indirect: true,
+            supports_multi_draw_indirect: physical_device_info.features.multi_draw_indirect
+                != vk::FALSE,
+            supports_draw_indirect_count: physical_device_info
+                .supports_extension(khr::DrawIndirectCount::name()),
+            supports_dispatch_indirect: true,
+            supports_transient_attachments: supports_lazily_allocated_memory,
+            supports_resource_aliasing: true,
+            supports_subpasses: true,
+            supports_secondary_command_buffers: true,
+            supports_blit_images: true,
+            supports_pipeline_cache_data: true,
+            supports_bindless_descriptors: physical_device_info.supports_bindless_descriptors,
+            max_bindless_descriptor_count: physical_device_info.max_bindless_descriptor_count,
+        };
+
+        let draw_indirect_count = if device_info.supports_draw_indirect_count {
+            Some(khr::DrawIndirectCount::new(
+                &instance.instance,
+                &logical_device,
+            ))
+        } else {
+            None
+        };
+
+        let debug_utils = if instance.debug_utils_enabled {
+            Some(DebugUtils::new(&*instance.entry, &instance.instance))
+        } else {
+            None
         };
 
         let resource_cache = RafxDeviceVulkanResourceCache::default();
@@ -188,12 +280,16 @@ impl RafxDeviceContextVulkanInner {
             device_info,
             queue_allocator,
             dedicated_present_queue_lock: Mutex::default(),
+            headless,
             entry: instance.entry.clone(),
             instance: instance.instance.clone(),
             physical_device,
             physical_device_info,
+            debug_utils,
+            draw_indirect_count,
             device: logical_device,
             allocator,
+            pipeline_cache: RwLock::new(pipeline_cache),
             destroyed: AtomicBool::new(false),
 
             #[cfg(debug_assertions)]
@@ -311,6 +407,35 @@ impl RafxDeviceContextVulkan {
         &self.inner.physical_device_info
     }
 
+    pub fn debug_utils(&self) -> Option<&DebugUtils> {
+        self.inner.debug_utils.as_ref()
+    }
+
+    pub fn draw_indirect_count(&self) -> Option<&khr::DrawIndirectCount> {
+        self.inner.draw_indirect_count.as_ref()
+    }
+
+    // Names the object in tools like RenderDoc. Does nothing if VK_EXT_debug_utils is not enabled
+    pub(crate) fn set_debug_name<T: vk::Handle>(
+        &self,
+        handle: T,
+        name: &str,
+    ) -> RafxResult<()> {
+        if let Some(debug_utils) = &self.inner.debug_utils {
+            let name = CString::new(name).map_err(|_| "Debug name may not contain a nul byte")?;
+            let name_info = vk::DebugUtilsObjectNameInfoEXT::builder()
+                .object_type(T::TYPE)
+                .object_handle(handle.as_raw())
+                .object_name(&name);
+
+            unsafe {
+                debug_utils.debug_utils_set_object_name(self.device().handle(), &*name_info)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn limits(&self) -> &vk::PhysicalDeviceLimits {
         &self.physical_device_info().properties.limits
     }
@@ -319,6 +444,119 @@ impl RafxDeviceContextVulkan {
         &self.inner.allocator
     }
 
+    // Hold the returned guard while creating pipelines with the cache
+    pub(crate) fn pipeline_cache(&self) -> RwLockReadGuard<vk::PipelineCache> {
+        self.inner.pipeline_cache.read().unwrap()
+    }
+
+    fn pipeline_cache_data_identity(&self) -> PipelineCacheDataIdentity {
+        let properties = &self.physical_device_info().properties;
+        PipelineCacheDataIdentity {
+            backend: PIPELINE_CACHE_DATA_BACKEND_VULKAN,
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+            driver_version: properties.driver_version,
+            pipeline_cache_uuid: properties.pipeline_cache_uuid,
+        }
+    }
+
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        let payload = unsafe {
+            self.device()
+                .get_pipeline_cache_data(*self.inner.pipeline_cache.read().unwrap())?
+        };
+
+        Ok(encode_pipeline_cache_data(
+            &self.pipeline_cache_data_identity(),
+            &payload,
+        ))
+    }
+
+    pub fn merge_pipeline_cache_data(
+        &self,
+        data: &[u8],
+    ) -> RafxResult<bool> {
+        let payload = match decode_pipeline_cache_data(&self.pipeline_cache_data_identity(), data) {
+            Some(payload) => payload,
+            None => return Ok(false),
+        };
+
+        let create_info = vk::PipelineCacheCreateInfo::builder().initial_data(payload);
+        unsafe {
+            let src_cache = self.device().create_pipeline_cache(&*create_info, None)?;
+            let result = {
+                let dst_cache = self.inner.pipeline_cache.write().unwrap();
+                self.device()
+                    .merge_pipeline_caches(*dst_cache, &[src_cache])
+            };
+            self.device().destroy_pipeline_cache(src_cache, None);
+            result?;
+        }
+
+        Ok(true)
+    }
+
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        let instance = &self.inner.instance;
+        let physical_device = self.inner.physical_device;
+        let is_budget_reported_by_driver = self
+            .inner
+            .physical_device_info
+            .supports_extension(vk::ExtMemoryBudgetFn::name());
+
+        let mut memory_budget_properties = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let memory_properties = unsafe {
+            if is_budget_reported_by_driver {
+                let mut memory_properties2 = vk::PhysicalDeviceMemoryProperties2::builder()
+                    .push_next(&mut memory_budget_properties);
+                instance.get_physical_device_memory_properties2(
+                    physical_device,
+                    &mut memory_properties2,
+                );
+                memory_properties2.memory_properties
+            } else {
+                instance.get_physical_device_memory_properties(physical_device)
+            }
+        };
+
+        // Without VK_EXT_memory_budget, report the memory allocated by this process and use the
+        // same heuristic as vk-mem for the budget (80% of the heap)
+        let allocator_stats = if is_budget_reported_by_driver {
+            None
+        } else {
+            Some(self.allocator().calculate_stats()?)
+        };
+
+        let mut heaps = Vec::with_capacity(memory_properties.memory_heap_count as usize);
+        for heap_index in 0..memory_properties.memory_heap_count as usize {
+            let heap = &memory_properties.memory_heaps[heap_index];
+            let (budget_in_bytes, usage_in_bytes) = if let Some(stats) = &allocator_stats {
+                let heap_stats = &stats.memoryHeap[heap_index];
+                (
+                    heap.size * 8 / 10,
+                    heap_stats.usedBytes + heap_stats.unusedBytes,
+                )
+            } else {
+                (
+                    memory_budget_properties.heap_budget[heap_index],
+                    memory_budget_properties.heap_usage[heap_index],
+                )
+            };
+
+            heaps.push(RafxMemoryHeapBudget {
+                is_device_local: heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL),
+                heap_size_in_bytes: heap.size,
+                budget_in_bytes,
+                usage_in_bytes,
+            });
+        }
+
+        Ok(RafxMemoryBudget {
+            heaps,
+            is_budget_reported_by_driver,
+        })
+    }
+
     pub fn queue_allocator(&self) -> &VkQueueAllocatorSet {
         &self.inner.queue_allocator
     }
@@ -331,6 +569,11 @@ impl RafxDeviceContextVulkan {
         &self.inner.dedicated_present_queue_lock
     }
 
+    /// Returns true if the device was created without swapchain support
+    pub fn is_headless(&self) -> bool {
+        self.inner.headless
+    }
+
     pub fn new(
         // instance: &VkInstance,
         // window: &dyn HasRawWindowHandle,
@@ -366,6 +609,10 @@ impl RafxDeviceContextVulkan {
         raw_window_handle: &dyn HasRawWindowHandle,
         swapchain_def: &RafxSwapchainDef,
     ) -> RafxResult<RafxSwapchainVulkan> {
+        if self.inner.headless {
+            return Err("Swapchains cannot be created on a headless device")?;
+        }
+
         RafxSwapchainVulkan::new(self, raw_window_handle, swapchain_def)
     }
 
@@ -383,6 +630,13 @@ impl RafxDeviceContextVulkan {
         RafxSamplerVulkan::new(self, sampler_def)
     }
 
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolVulkan> {
+        RafxQueryPoolVulkan::new(self, query_pool_def)
+    }
+
     pub fn create_texture(
         &self,
         texture_def: &RafxTextureDef,
@@ -390,6 +644,13 @@ impl RafxDeviceContextVulkan {
         RafxTextureVulkan::new(self, texture_def)
     }
 
+    pub fn create_aliased_textures(
+        &self,
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTextureVulkan>> {
+        RafxTextureVulkan::new_aliased(self, texture_defs)
+    }
+
     pub fn create_buffer(
         &self,
         buffer_def: &RafxBufferDef,
@@ -397,6 +658,13 @@ impl RafxDeviceContextVulkan {
         RafxBufferVulkan::new(self, buffer_def)
     }
 
+    pub fn create_aliased_buffers(
+        &self,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<RafxBufferVulkan>> {
+        RafxBufferVulkan::new_aliased(self, buffer_defs)
+    }
+
     pub fn create_shader(
         &self,
         stages: Vec<RafxShaderStageDef>,
@@ -617,6 +885,8 @@ fn query_physical_device_info(
         unsafe { instance.get_physical_device_features(device) };
     let all_queue_families: Vec<ash::vk::QueueFamilyProperties> =
         unsafe { instance.get_physical_device_queue_family_properties(device) };
+    let (supports_bindless_descriptors, max_bindless_descriptor_count) =
+        query_bindless_descriptor_support(instance, device, &extensions);
 
     let queue_family_indices = find_queue_families(&all_queue_families)?;
     if let Some(queue_family_indices) = queue_family_indices {
@@ -653,6 +923,8 @@ fn query_physical_device_info(
             extension_properties: extensions,
             features,
             all_queue_families,
+            supports_bindless_descriptors,
+            max_bindless_descriptor_count,
         };
 
         log::trace!("{:#?}", properties);
@@ -790,11 +1062,64 @@ fn find_queue_families(
     }
 }
 
+// Bindless descriptors are non-uniformly indexed, partially bound arrays of sampled images and
+// storage buffers that can be written while bound
+fn query_bindless_descriptor_support(
+    instance: &ash::Instance,
+    device: ash::vk::PhysicalDevice,
+    extensions: &[ash::vk::ExtensionProperties],
+) -> (bool, u32) {
+    let supports_descriptor_indexing = extensions.iter().any(|extension| unsafe {
+        CStr::from_ptr(extension.extension_name.as_ptr()) == vk::ExtDescriptorIndexingFn::name()
+    });
+
+    if !supports_descriptor_indexing {
+        return (false, 0);
+    }
+
+    let mut descriptor_indexing_features = vk::PhysicalDeviceDescriptorIndexingFeatures::default();
+    let mut descriptor_indexing_properties =
+        vk::PhysicalDeviceDescriptorIndexingProperties::default();
+    unsafe {
+        let mut features2 =
+            vk::PhysicalDeviceFeatures2::builder().push_next(&mut descriptor_indexing_features);
+        instance.get_physical_device_features2(device, &mut features2);
+
+        let mut properties2 =
+            vk::PhysicalDeviceProperties2::builder().push_next(&mut descriptor_indexing_properties);
+        instance.get_physical_device_properties2(device, &mut properties2);
+    }
+
+    let features = &descriptor_indexing_features;
+    let supports_bindless_descriptors = features.shader_sampled_image_array_non_uniform_indexing
+        != vk::FALSE
+        && features.shader_storage_buffer_array_non_uniform_indexing != vk::FALSE
+        && features.descriptor_binding_sampled_image_update_after_bind != vk::FALSE
+        && features.descriptor_binding_storage_buffer_update_after_bind != vk::FALSE
+        && features.descriptor_binding_update_unused_while_pending != vk::FALSE
+        && features.descriptor_binding_partially_bound != vk::FALSE
+        && features.runtime_descriptor_array != vk::FALSE;
+
+    if !supports_bindless_descriptors {
+        return (false, 0);
+    }
+
+    let properties = &descriptor_indexing_properties;
+    let max_bindless_descriptor_count = properties
+        .max_per_stage_descriptor_update_after_bind_sampled_images
+        .min(properties.max_per_stage_descriptor_update_after_bind_storage_buffers)
+        .min(properties.max_descriptor_set_update_after_bind_sampled_images)
+        .min(properties.max_descriptor_set_update_after_bind_storage_buffers);
+
+    (true, max_bindless_descriptor_count)
+}
+
 fn create_logical_device(
     instance: &ash::Instance,
     physical_device: ash::vk::PhysicalDevice,
     physical_device_info: &PhysicalDeviceInfo,
     queue_requirements: &VkQueueRequirements,
+    headless: bool,
 ) -> RafxResult<ash::Device> {
     //TODO: Ideally we would set up validation layers for the logical device too.
 
@@ -802,7 +1127,10 @@ fn create_logical_device(
         CStr::from_bytes_with_nul(b"VK_KHR_portability_subset\0").expect("Wrong extension string")
     }
 
-    let mut device_extension_names = vec![khr::Swapchain::name().as_ptr()];
+    let mut device_extension_names = vec![];
+    if !headless {
+        device_extension_names.push(khr::Swapchain::name().as_ptr());
+    }
 
     // Add VK_KHR_portability_subset if the extension exists (this is mandated by spec)
     let portability_subset_extension_name = khr_portability_subset_extension_name();
@@ -815,13 +1143,37 @@ fn create_logical_device(
         }
     }
 
+    // Optional, used by cmd_draw_indirect_count/cmd_draw_indexed_indirect_count
+    if physical_device_info.supports_extension(khr::DrawIndirectCount::name()) {
+        device_extension_names.push(khr::DrawIndirectCount::name().as_ptr());
+    }
+
+    // Optional, used by RafxResourceType::BINDLESS
+    if physical_device_info.supports_bindless_descriptors {
+        device_extension_names.push(vk::ExtDescriptorIndexingFn::name().as_ptr());
+    }
+
+    // Optional, used by memory_budget()
+    if physical_device_info.supports_extension(vk::ExtMemoryBudgetFn::name()) {
+        device_extension_names.push(vk::ExtMemoryBudgetFn::name().as_ptr());
+    }
+
     // Features enabled here by default are supported very widely (only unsupported devices on
     // vulkan.gpuinfo.org are SwiftShader, a software renderer.
     let features = vk::PhysicalDeviceFeatures::builder()
         .sampler_anisotropy(true)
         .sample_rate_shading(true)
         // Used for debug drawing lines/points
-        .fill_mode_non_solid(true);
+        .fill_mode_non_solid(true)
+        // Optional, used by RafxQueryType::PipelineStatistics
+        .pipeline_statistics_query(
+            physical_device_info.features.pipeline_statistics_query != vk::FALSE,
+        )
+        // Optional, allows draw_count > 1 and a non-zero first_instance in indirect draws
+        .multi_draw_indirect(physical_device_info.features.multi_draw_indirect != vk::FALSE)
+        .draw_indirect_first_instance(
+            physical_device_info.features.draw_indirect_first_instance != vk::FALSE,
+        );
 
     let mut queue_families_to_create = FnvHashMap::default();
     for (&queue_family_index, &count) in &queue_requirements.queue_counts {
@@ -838,11 +1190,24 @@ fn create_logical_device(
         })
         .collect();
 
-    let device_create_info = vk::DeviceCreateInfo::builder()
+    let mut descriptor_indexing_features = vk::PhysicalDeviceDescriptorIndexingFeatures::builder()
+        .shader_sampled_image_array_non_uniform_indexing(true)
+        .shader_storage_buffer_array_non_uniform_indexing(true)
+        .descriptor_binding_sampled_image_update_after_bind(true)
+        .descriptor_binding_storage_buffer_update_after_bind(true)
+        .descriptor_binding_update_unused_while_pending(true)
+        .descriptor_binding_partially_bound(true)
+        .runtime_descriptor_array(true);
+
+    let mut device_create_info = vk::DeviceCreateInfo::builder()
         .queue_create_infos(&queue_infos)
         .enabled_extension_names(&device_extension_names)
         .enabled_features(&features);
 
+    if physical_device_info.supports_bindless_descriptors {
+        device_create_info = device_create_info.push_next(&mut descriptor_indexing_features);
+    }
+
     let device: ash::Device =
         unsafe { instance.create_device(physical_device, &device_create_info, None)? };
 
@@ -0,0 +1,110 @@
+use crate::vulkan::RafxDeviceContextVulkan;
+use crate::RafxResult;
+use ash::vk;
+use std::sync::Arc;
+
+// A single allocation shared by several textures or buffers. The resources alias each other, so
+// only one of them may hold meaningful data at a time. The memory is freed when the last resource
+// that uses it is dropped.
+#[derive(Debug)]
+pub(crate) struct VkAliasedMemoryInner {
+    device_context: RafxDeviceContextVulkan,
+    allocation: vk_mem::Allocation,
+}
+
+impl Drop for VkAliasedMemoryInner {
+    fn drop(&mut self) {
+        log::trace!("destroying VkAliasedMemory");
+        self.device_context
+            .allocator()
+            .free_memory(&self.allocation)
+            .unwrap();
+        log::trace!("destroyed VkAliasedMemory");
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct VkAliasedMemory {
+    inner: Arc<VkAliasedMemoryInner>,
+}
+
+impl VkAliasedMemory {
+    // Allocates memory that satisfies the requirements of every resource that will be bound to it
+    pub fn new(
+        device_context: &RafxDeviceContextVulkan,
+        all_memory_requirements: &[vk::MemoryRequirements],
+        preferred_flags: vk::MemoryPropertyFlags,
+    ) -> RafxResult<Self> {
+        let mut memory_requirements = vk::MemoryRequirements {
+            size: 0,
+            alignment: 1,
+            memory_type_bits: !0,
+        };
+
+        for requirements in all_memory_requirements {
+            memory_requirements.size = memory_requirements.size.max(requirements.size);
+            memory_requirements.alignment =
+                memory_requirements.alignment.max(requirements.alignment);
+            memory_requirements.memory_type_bits &= requirements.memory_type_bits;
+        }
+
+        if memory_requirements.memory_type_bits == 0 {
+            return Err("No memory type is compatible with all of the aliased resources")?;
+        }
+
+        let allocation_create_info = vk_mem::AllocationCreateInfo {
+            usage: vk_mem::MemoryUsage::GpuOnly,
+            flags: vk_mem::AllocationCreateFlags::NONE,
+            required_flags: vk::MemoryPropertyFlags::empty(),
+            preferred_flags,
+            memory_type_bits: 0, // Already restricted by memory_requirements
+            pool: None,
+            user_data: None,
+        };
+
+        let (allocation, _allocation_info) = device_context
+            .allocator()
+            .allocate_memory(&memory_requirements, &allocation_create_info)?;
+
+        log::trace!(
+            "Allocated {} bytes of memory shared by {} aliased resources",
+            memory_requirements.size,
+            all_memory_requirements.len()
+        );
+
+        let inner = VkAliasedMemoryInner {
+            device_context: device_context.clone(),
+            allocation,
+        };
+
+        Ok(VkAliasedMemory {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn bind_image(
+        &self,
+        image: vk::Image,
+    ) -> RafxResult<()> {
+        self.inner
+            .device_context
+            .allocator()
+            .bind_image_memory(image, &self.inner.allocation)?;
+        Ok(())
+    }
+
+    pub fn bind_buffer(
+        &self,
+        buffer: vk::Buffer,
+    ) -> RafxResult<()> {
+        self.inner
+            .device_context
+            .allocator()
+            .bind_buffer_memory(buffer, &self.inner.allocation)?;
+        Ok(())
+    }
+
+    pub fn allocation(&self) -> vk_mem::Allocation {
+        self.inner.allocation
+    }
+}
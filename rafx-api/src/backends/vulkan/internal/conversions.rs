@@ -3,7 +3,7 @@ use crate::{
     RafxCompareOp, RafxCullMode, RafxDepthStencilClearValue, RafxFillMode, RafxFilterType,
     RafxFrontFace, RafxIndexType, RafxLoadOp, RafxMemoryUsage, RafxMipMapMode,
     RafxPrimitiveTopology, RafxSampleCount, RafxShaderStageFlags, RafxStencilOp, RafxStoreOp,
-    RafxVertexAttributeRate,
+    RafxSubpassContents, RafxVertexAttributeRate,
 };
 use ash::vk;
 
@@ -115,6 +115,17 @@ impl Into<vk::AttachmentStoreOp> for RafxStoreOp {
     }
 }
 
+impl Into<vk::SubpassContents> for RafxSubpassContents {
+    fn into(self) -> vk::SubpassContents {
+        match self {
+            RafxSubpassContents::Inline => vk::SubpassContents::INLINE,
+            RafxSubpassContents::SecondaryCommandBuffers => {
+                vk::SubpassContents::SECONDARY_COMMAND_BUFFERS
+            }
+        }
+    }
+}
+
 impl Into<vk::PrimitiveTopology> for RafxPrimitiveTopology {
     fn into(self) -> vk::PrimitiveTopology {
         match self {
@@ -34,7 +34,7 @@ impl Default for RafxDescriptorHeapPoolConfig {
             storage_buffers: 1024,
             dynamic_uniform_buffers: 1024,
             dynamic_storage_buffers: 0,
-            input_attachments: 0,
+            input_attachments: 1024,
         }
     }
 }
@@ -89,6 +89,9 @@ impl RafxDescriptorHeapPoolConfig {
 struct RafxDescriptorHeapVulkanInner {
     heap_pool_config: RafxDescriptorHeapPoolConfig,
     pools: Vec<vk::DescriptorPool>,
+    // Sets with update-after-bind bindings must come from a pool created with UPDATE_AFTER_BIND.
+    // They are rare and large, so each allocation gets its own exactly-sized pool
+    update_after_bind_pools: Vec<vk::DescriptorPool>,
 }
 
 impl RafxDescriptorHeapVulkanInner {
@@ -103,6 +106,14 @@ impl RafxDescriptorHeapVulkanInner {
         }
 
         self.pools.clear();
+
+        for &pool in &self.update_after_bind_pools {
+            unsafe {
+                device.destroy_descriptor_pool(pool, None);
+            }
+        }
+
+        self.update_after_bind_pools.clear();
     }
 }
 
@@ -111,6 +122,7 @@ impl Drop for RafxDescriptorHeapVulkanInner {
         // Assert that everything was destroyed. (We can't do it automatically since we don't have
         // a reference to the device)
         assert!(self.pools.is_empty());
+        assert!(self.update_after_bind_pools.is_empty());
     }
 }
 
@@ -130,6 +142,7 @@ impl RafxDescriptorHeapVulkan {
         let inner = RafxDescriptorHeapVulkanInner {
             heap_pool_config,
             pools: vec![pool],
+            update_after_bind_pools: vec![],
         };
 
         Ok(RafxDescriptorHeapVulkan {
@@ -177,4 +190,29 @@ impl RafxDescriptorHeapVulkan {
         allocate_info.descriptor_pool = pool;
         Ok(unsafe { device.allocate_descriptor_sets(&allocate_info)? })
     }
+
+    pub(crate) fn allocate_update_after_bind_descriptor_sets(
+        &self,
+        device: &ash::Device,
+        set_layouts: &[vk::DescriptorSetLayout],
+        pool_sizes: &[vk::DescriptorPoolSize],
+    ) -> RafxResult<Vec<vk::DescriptorSet>> {
+        let mut heap = self.inner.lock().unwrap();
+
+        let pool = unsafe {
+            device.create_descriptor_pool(
+                &*vk::DescriptorPoolCreateInfo::builder()
+                    .flags(vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND)
+                    .max_sets(set_layouts.len() as u32)
+                    .pool_sizes(pool_sizes),
+                None,
+            )?
+        };
+        heap.update_after_bind_pools.push(pool);
+
+        let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(set_layouts);
+        Ok(unsafe { device.allocate_descriptor_sets(&allocate_info)? })
+    }
 }
@@ -22,6 +22,7 @@ impl RafxFramebufferVulkanCache {
     pub(crate) fn framebuffer_hash(
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> u64 {
         let mut hasher = FnvHasher::default();
         for color_target in color_targets {
@@ -55,6 +56,9 @@ impl RafxFramebufferVulkanCache {
             depth_target.mip_slice.hash(&mut hasher);
             depth_target.array_slice.hash(&mut hasher);
         }
+
+        // The subpasses affect which renderpasses the framebuffer is compatible with
+        subpasses.hash(&mut hasher);
         hasher.finish()
     }
 
@@ -106,11 +110,12 @@ impl RafxFramebufferVulkanCache {
         renderpass: &RafxRenderpassVulkan,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<RafxFramebufferVulkan> {
         //
         // Hash it
         //
-        let hash = Self::framebuffer_hash(color_targets, depth_target);
+        let hash = Self::framebuffer_hash(color_targets, depth_target, subpasses);
 
         self.cache.get_or_create(hash, || {
             Self::create_framebuffer(device_context, renderpass, color_targets, depth_target)
@@ -17,6 +17,8 @@ pub struct VkInstance {
     pub entry: Arc<VkEntry>,
     pub instance: ash::Instance,
     pub debug_reporter: Option<VkDebugReporter>,
+    // True if VK_EXT_debug_utils is enabled, which is required for object names and debug labels
+    pub debug_utils_enabled: bool,
 }
 
 #[derive(Debug)]
@@ -59,10 +61,11 @@ impl From<vk::Result> for VkCreateInstanceError {
 }
 
 impl VkInstance {
-    /// Creates a vulkan instance.
+    /// Creates a vulkan instance. If no window is provided, surface extensions are not enabled and
+    /// the instance can only be used for headless rendering.
     pub fn new(
         entry: VkEntry,
-        window: &dyn HasRawWindowHandle,
+        window: Option<&dyn HasRawWindowHandle>,
         app_name: &CString,
         require_validation_layers_present: bool,
         validation_layer_debug_report_flags: vk::DebugUtilsMessageSeverityFlagsEXT,
@@ -110,7 +113,23 @@ impl VkInstance {
             .api_version(vulkan_version);
 
         let mut layer_names = vec![];
-        let mut extension_names = ash_window::enumerate_required_extensions(window)?;
+        let mut extension_names = if let Some(window) = window {
+            ash_window::enumerate_required_extensions(window)?
+        } else {
+            vec![]
+        };
+
+        // The debug extension is also used to name objects and label command buffers for tools like
+        // RenderDoc, so enable it whenever it's available
+        let debug_extension = DebugUtils::name();
+        let has_debug_extension = extensions.iter().any(|extension| unsafe {
+            debug_extension == CStr::from_ptr(extension.extension_name.as_ptr())
+        });
+
+        if has_debug_extension {
+            extension_names.push(DebugUtils::name());
+        }
+
         if !validation_layer_debug_report_flags.is_empty() {
             // Find the best validation layer that's available
             let best_validation_layer = VkInstance::find_best_validation_layer(&layers);
@@ -123,11 +142,6 @@ impl VkInstance {
                 }
             }
 
-            let debug_extension = DebugUtils::name();
-            let has_debug_extension = extensions.iter().any(|extension| unsafe {
-                debug_extension == CStr::from_ptr(extension.extension_name.as_ptr())
-            });
-
             if !has_debug_extension {
                 if require_validation_layers_present {
                     log::error!("Could not find the debug extension. Check that the vulkan SDK has been installed or disable validation.");
@@ -140,7 +154,6 @@ impl VkInstance {
             if let Some(best_validation_layer) = best_validation_layer {
                 if has_debug_extension {
                     layer_names.push(best_validation_layer);
-                    extension_names.push(DebugUtils::name());
                 }
             }
         }
@@ -163,20 +176,22 @@ impl VkInstance {
         let instance: ash::Instance = unsafe { entry.create_instance(&create_info, None)? };
 
         // Setup the debug callback for the validation layer
-        let debug_reporter = if !validation_layer_debug_report_flags.is_empty() {
-            Some(Self::setup_vulkan_debug_callback(
-                &entry,
-                &instance,
-                validation_layer_debug_report_flags,
-            )?)
-        } else {
-            None
-        };
+        let debug_reporter =
+            if has_debug_extension && !validation_layer_debug_report_flags.is_empty() {
+                Some(Self::setup_vulkan_debug_callback(
+                    &entry,
+                    &instance,
+                    validation_layer_debug_report_flags,
+                )?)
+            } else {
+                None
+            };
 
         Ok(VkInstance {
             entry: Arc::new(entry),
             instance,
             debug_reporter,
+            debug_utils_enabled: has_debug_extension,
         })
     }
 
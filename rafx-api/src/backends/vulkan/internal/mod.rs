@@ -1,3 +1,6 @@
+mod aliased_memory;
+pub(crate) use aliased_memory::*;
+
 mod debug_reporter;
 pub(crate) use debug_reporter::*;
 
@@ -1,5 +1,8 @@
 use crate::vulkan::RafxDeviceContextVulkan;
-use crate::{RafxFormat, RafxLoadOp, RafxResult, RafxSampleCount, RafxStoreOp};
+use crate::{
+    RafxFormat, RafxLoadOp, RafxResult, RafxSampleCount, RafxStoreOp, RafxSubpassDef,
+    RafxSubpassInputAttachment,
+};
 use ash::version::DeviceV1_0;
 use ash::vk;
 use std::sync::Arc;
@@ -31,6 +34,8 @@ pub(crate) struct RafxRenderpassVulkanDef {
     pub(crate) resolve_attachments: Vec<Option<RafxRenderpassVulkanResolveAttachment>>,
     pub(crate) depth_attachment: Option<RafxRenderpassVulkanDepthAttachment>,
     pub(crate) sample_count: RafxSampleCount,
+    // If empty, the renderpass has a single subpass that uses all attachments
+    pub(crate) subpasses: Vec<RafxSubpassDef>,
 }
 
 pub(crate) struct RafxRenderpassVulkanInner {
@@ -168,6 +173,15 @@ impl RafxRenderpassVulkan {
             );
         }
 
+        if !renderpass_def.subpasses.is_empty() {
+            return Self::new_with_subpasses(
+                device_context,
+                renderpass_def,
+                &attachments,
+                depth_stencil_attachment_ref.map(|x| x.attachment),
+            );
+        }
+
         let mut subpass_description = vk::SubpassDescription::builder()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
             .color_attachments(&color_attachment_refs);
@@ -202,4 +216,190 @@ impl RafxRenderpassVulkan {
             inner: Arc::new(inner),
         })
     }
+
+    fn new_with_subpasses(
+        device_context: &RafxDeviceContextVulkan,
+        renderpass_def: &RafxRenderpassVulkanDef,
+        attachments: &[vk::AttachmentDescription],
+        depth_attachment_index: Option<u32>,
+    ) -> RafxResult<Self> {
+        if renderpass_def
+            .resolve_attachments
+            .iter()
+            .any(|x| x.is_some())
+        {
+            Err("Resolve attachments are not supported in renderpasses with multiple subpasses")?;
+        }
+
+        // Attachment references must outlive the subpass descriptions that point at them
+        let mut color_attachment_refs = Vec::with_capacity(renderpass_def.subpasses.len());
+        let mut input_attachment_refs = Vec::with_capacity(renderpass_def.subpasses.len());
+        let mut depth_stencil_attachment_refs = Vec::with_capacity(renderpass_def.subpasses.len());
+        let mut preserve_attachments = Vec::with_capacity(renderpass_def.subpasses.len());
+
+        for (subpass_index, subpass) in renderpass_def.subpasses.iter().enumerate() {
+            let reads_depth = subpass
+                .input_attachments
+                .contains(&RafxSubpassInputAttachment::DepthStencil);
+
+            color_attachment_refs.push(
+                subpass
+                    .color_attachments
+                    .iter()
+                    .map(|&attachment| {
+                        vk::AttachmentReference::builder()
+                            .attachment(attachment)
+                            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+                            .build()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+
+            let mut refs = Vec::with_capacity(subpass.input_attachments.len());
+            for input_attachment in &subpass.input_attachments {
+                refs.push(match input_attachment {
+                    RafxSubpassInputAttachment::Color(attachment) => {
+                        if subpass.color_attachments.contains(attachment) {
+                            Err("A subpass cannot read a color attachment that it also writes")?;
+                        }
+
+                        vk::AttachmentReference::builder()
+                            .attachment(*attachment)
+                            .layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                            .build()
+                    }
+                    RafxSubpassInputAttachment::DepthStencil => vk::AttachmentReference::builder()
+                        .attachment(depth_attachment_index.ok_or(
+                            "A subpass reads the depth/stencil attachment but the renderpass has none",
+                        )?)
+                        .layout(vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+                        .build(),
+                });
+            }
+            input_attachment_refs.push(refs);
+
+            // If the depth/stencil target is also read as an input attachment, it must be read-only
+            depth_stencil_attachment_refs.push(
+                depth_attachment_index
+                    .filter(|_| subpass.use_depth_stencil_attachment)
+                    .map(|attachment| {
+                        let layout = if reads_depth {
+                            vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL
+                        } else {
+                            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
+                        };
+
+                        vk::AttachmentReference::builder()
+                            .attachment(attachment)
+                            .layout(layout)
+                            .build()
+                    }),
+            );
+
+            // Attachments not touched by this subpass must be preserved if a later subpass uses
+            // them
+            let mut preserve = vec![];
+            for attachment in 0..attachments.len() as u32 {
+                let is_depth = Some(attachment) == depth_attachment_index;
+                let used_by_subpass = |subpass: &RafxSubpassDef| {
+                    if is_depth {
+                        subpass.use_depth_stencil_attachment
+                            || subpass
+                                .input_attachments
+                                .contains(&RafxSubpassInputAttachment::DepthStencil)
+                    } else {
+                        subpass.color_attachments.contains(&attachment)
+                            || subpass
+                                .input_attachments
+                                .contains(&RafxSubpassInputAttachment::Color(attachment))
+                    }
+                };
+
+                if !used_by_subpass(subpass)
+                    && renderpass_def.subpasses[..subpass_index]
+                        .iter()
+                        .any(used_by_subpass)
+                    && renderpass_def.subpasses[subpass_index + 1..]
+                        .iter()
+                        .any(used_by_subpass)
+                {
+                    preserve.push(attachment);
+                }
+            }
+            preserve_attachments.push(preserve);
+        }
+
+        let mut subpass_descriptions = Vec::with_capacity(renderpass_def.subpasses.len());
+        for subpass_index in 0..renderpass_def.subpasses.len() {
+            let mut subpass_description = vk::SubpassDescription::builder()
+                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                .color_attachments(&color_attachment_refs[subpass_index])
+                .input_attachments(&input_attachment_refs[subpass_index])
+                .preserve_attachments(&preserve_attachments[subpass_index]);
+
+            if let Some(depth_stencil_attachment_ref) =
+                depth_stencil_attachment_refs[subpass_index].as_ref()
+            {
+                subpass_description =
+                    subpass_description.depth_stencil_attachment(depth_stencil_attachment_ref);
+            }
+
+            subpass_descriptions.push(subpass_description.build());
+        }
+
+        // Conservatively make each subpass wait for attachment writes of the previous one. These
+        // are by-region, so tile-based GPUs can keep the attachments in tile memory
+        let mut dependencies = Vec::with_capacity(renderpass_def.subpasses.len());
+        for subpass_index in 1..renderpass_def.subpasses.len() as u32 {
+            dependencies.push(
+                vk::SubpassDependency::builder()
+                    .src_subpass(subpass_index - 1)
+                    .dst_subpass(subpass_index)
+                    .src_stage_mask(
+                        vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                            | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                    )
+                    .dst_stage_mask(
+                        vk::PipelineStageFlags::FRAGMENT_SHADER
+                            | vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+                            | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS
+                            | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                    )
+                    .src_access_mask(
+                        vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                    )
+                    .dst_access_mask(
+                        vk::AccessFlags::INPUT_ATTACHMENT_READ
+                            | vk::AccessFlags::COLOR_ATTACHMENT_READ
+                            | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ
+                            | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                    )
+                    .dependency_flags(vk::DependencyFlags::BY_REGION)
+                    .build(),
+            );
+        }
+
+        let renderpass_create_info = vk::RenderPassCreateInfo::builder()
+            .attachments(attachments)
+            .subpasses(&subpass_descriptions)
+            .dependencies(&dependencies);
+
+        let renderpass = unsafe {
+            device_context
+                .device()
+                .create_render_pass(&*renderpass_create_info, None)?
+        };
+
+        let inner = RafxRenderpassVulkanInner {
+            device_context: device_context.clone(),
+            renderpass,
+        };
+
+        Ok(RafxRenderpassVulkan {
+            inner: Arc::new(inner),
+        })
+    }
 }
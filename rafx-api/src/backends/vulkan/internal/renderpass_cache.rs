@@ -23,6 +23,7 @@ impl RafxRenderpassVulkanCache {
     pub(crate) fn renderpass_hash(
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> u64 {
         let mut hasher = FnvHasher::default();
         for color_target in color_targets {
@@ -41,6 +42,8 @@ impl RafxRenderpassVulkanCache {
             depth_target.stencil_load_op.hash(&mut hasher);
             depth_target.depth_load_op.hash(&mut hasher);
         }
+
+        subpasses.hash(&mut hasher);
         hasher.finish()
     }
 
@@ -48,6 +51,7 @@ impl RafxRenderpassVulkanCache {
         device_context: &RafxDeviceContextVulkan,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<RafxRenderpassVulkan> {
         let sample_count = if let Some(depth_target) = &depth_target {
             depth_target.texture.texture_def().sample_count
@@ -97,6 +101,7 @@ impl RafxRenderpassVulkanCache {
                 resolve_attachments,
                 depth_attachment,
                 sample_count,
+                subpasses: subpasses.to_vec(),
             },
         )
     }
@@ -106,14 +111,15 @@ impl RafxRenderpassVulkanCache {
         device_context: &RafxDeviceContextVulkan,
         color_targets: &[RafxColorRenderTargetBinding],
         depth_target: Option<&RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
     ) -> RafxResult<RafxRenderpassVulkan> {
         //
         // Hash it
         //
-        let hash = Self::renderpass_hash(color_targets, depth_target);
+        let hash = Self::renderpass_hash(color_targets, depth_target, subpasses);
 
         self.cache.get_or_create(hash, || {
-            Self::create_renderpass(device_context, color_targets, depth_target)
+            Self::create_renderpass(device_context, color_targets, depth_target, subpasses)
         })
     }
 }
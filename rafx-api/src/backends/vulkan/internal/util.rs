@@ -67,6 +67,10 @@ pub(crate) fn resource_type_image_usage_flags(
         usage_flags |= vk::ImageUsageFlags::STORAGE;
     }
 
+    if resource_type.intersects(RafxResourceType::INPUT_ATTACHMENT) {
+        usage_flags |= vk::ImageUsageFlags::INPUT_ATTACHMENT;
+    }
+
     usage_flags
 }
 
@@ -43,6 +43,9 @@ pub use pipeline::*;
 mod sampler;
 pub use sampler::*;
 
+mod query_pool;
+pub use query_pool::*;
+
 mod descriptor_set_array;
 pub use descriptor_set_array::*;
 
@@ -42,6 +42,17 @@ impl RafxPipelineVulkan {
         self.pipeline
     }
 
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.root_signature
+            .vk_root_signature()
+            .unwrap()
+            .device_context()
+            .set_debug_name(self.pipeline, name.as_ref())
+    }
+
     pub fn new_graphics_pipeline(
         device_context: &RafxDeviceContextVulkan,
         pipeline_def: &RafxGraphicsPipelineDef,
@@ -53,8 +64,13 @@ impl RafxPipelineVulkan {
 
         // image layouts and load/store ops don't affect compatibility
         // https://www.khronos.org/registry/vulkan/specs/1.1-extensions/html/chap9.html#renderpass-compatibility
-        let color_attachments: Vec<_> = pipeline_def
-            .color_formats
+        let render_pass_color_formats = if let Some(subpass_layout) = pipeline_def.subpass_layout {
+            &subpass_layout.render_pass_color_formats[..]
+        } else {
+            pipeline_def.color_formats
+        };
+
+        let color_attachments: Vec<_> = render_pass_color_formats
             .iter()
             .map(|&format| RafxRenderpassVulkanColorAttachment {
                 format,
@@ -79,12 +95,23 @@ impl RafxPipelineVulkan {
             None
         };
 
+        // The subpass structure of the renderpass does affect compatibility
+        let (subpasses, subpass_index) = if let Some(subpass_layout) = pipeline_def.subpass_layout {
+            (
+                subpass_layout.subpasses.clone(),
+                subpass_layout.subpass_index,
+            )
+        } else {
+            (vec![], 0)
+        };
+
         // Temporary renderpass, required to create pipeline but don't need to keep it
         let renderpass = device_context.create_renderpass(&RafxRenderpassVulkanDef {
             color_attachments,
             resolve_attachments,
             depth_attachment,
             sample_count: pipeline_def.sample_count,
+            subpasses,
         })?;
 
         let mut entry_point_names = vec![];
@@ -196,7 +223,7 @@ impl RafxPipelineVulkan {
             .dynamic_state(&dynamic_states_create_info)
             .layout(vk_root_signature.vk_pipeline_layout())
             .render_pass(renderpass.vk_renderpass())
-            .subpass(0)
+            .subpass(subpass_index)
             .base_pipeline_handle(vk::Pipeline::null())
             .base_pipeline_index(-1)
             .build();
@@ -210,7 +237,7 @@ impl RafxPipelineVulkan {
 
         let pipeline = unsafe {
             match device_context.device().create_graphics_pipelines(
-                vk::PipelineCache::null(),
+                *device_context.pipeline_cache(),
                 &[pipeline_create_info],
                 None,
             ) {
@@ -268,7 +295,7 @@ impl RafxPipelineVulkan {
 
         let pipeline = unsafe {
             match device_context.device().create_compute_pipelines(
-                vk::PipelineCache::null(),
+                *device_context.pipeline_cache(),
                 &[pipeline_create_info],
                 None,
             ) {
@@ -0,0 +1,147 @@
+use crate::vulkan::RafxDeviceContextVulkan;
+use crate::*;
+use ash::version::DeviceV1_0;
+use ash::vk;
+use std::sync::Arc;
+
+pub struct RafxQueryPoolVulkanInner {
+    device_context: RafxDeviceContextVulkan,
+    query_pool: vk::QueryPool,
+    query_pool_def: RafxQueryPoolDef,
+}
+
+impl Drop for RafxQueryPoolVulkanInner {
+    fn drop(&mut self) {
+        unsafe {
+            self.device_context
+                .device()
+                .destroy_query_pool(self.query_pool, None);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RafxQueryPoolVulkan {
+    inner: Arc<RafxQueryPoolVulkanInner>,
+}
+
+impl std::fmt::Debug for RafxQueryPoolVulkan {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        f.debug_struct("RafxQueryPoolVulkan")
+            .field("query_pool", &self.inner.query_pool)
+            .field("query_pool_def", &self.inner.query_pool_def)
+            .finish()
+    }
+}
+
+impl RafxQueryPoolVulkan {
+    pub fn vk_query_pool(&self) -> vk::QueryPool {
+        self.inner.query_pool
+    }
+
+    pub fn query_pool_def(&self) -> &RafxQueryPoolDef {
+        &self.inner.query_pool_def
+    }
+
+    pub(crate) fn pipeline_statistic_flags() -> vk::QueryPipelineStatisticFlags {
+        // Must stay in the same order as the fields of RafxPipelineStatistics
+        vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES
+            | vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES
+            | vk::QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS
+            | vk::QueryPipelineStatisticFlags::CLIPPING_INVOCATIONS
+            | vk::QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES
+            | vk::QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS
+            | vk::QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS
+    }
+
+    pub fn new(
+        device_context: &RafxDeviceContextVulkan,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPoolVulkan> {
+        let device_info = device_context.device_info();
+        let (query_type, pipeline_statistics) = match query_pool_def.query_type {
+            RafxQueryType::Timestamp => {
+                if !device_info.supports_timestamp_queries {
+                    Err("Timestamp queries are not supported by this device")?;
+                }
+                (
+                    vk::QueryType::TIMESTAMP,
+                    vk::QueryPipelineStatisticFlags::empty(),
+                )
+            }
+            RafxQueryType::Occlusion => {
+                if !device_info.supports_occlusion_queries {
+                    Err("Occlusion queries are not supported by this device")?;
+                }
+                (
+                    vk::QueryType::OCCLUSION,
+                    vk::QueryPipelineStatisticFlags::empty(),
+                )
+            }
+            RafxQueryType::PipelineStatistics => {
+                if !device_info.supports_pipeline_statistics_queries {
+                    Err("Pipeline statistics queries are not supported by this device")?;
+                }
+                (
+                    vk::QueryType::PIPELINE_STATISTICS,
+                    Self::pipeline_statistic_flags(),
+                )
+            }
+        };
+
+        let query_pool_create_info = vk::QueryPoolCreateInfo::builder()
+            .query_type(query_type)
+            .query_count(query_pool_def.query_count)
+            .pipeline_statistics(pipeline_statistics);
+
+        let query_pool = unsafe {
+            device_context
+                .device()
+                .create_query_pool(&*query_pool_create_info, None)?
+        };
+
+        let inner = RafxQueryPoolVulkanInner {
+            device_context: device_context.clone(),
+            query_pool,
+            query_pool_def: query_pool_def.clone(),
+        };
+
+        Ok(RafxQueryPoolVulkan {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn get_results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<Option<Vec<u64>>> {
+        let values_per_query = self.inner.query_pool_def.query_type.values_per_query();
+        let mut results = vec![0_u64; (query_count * values_per_query) as usize];
+        let stride = values_per_query as usize * std::mem::size_of::<u64>();
+
+        // Call the function pointer directly because ash assumes one value per query
+        let device = self.inner.device_context.device();
+        let result = unsafe {
+            device.fp_v1_0().get_query_pool_results(
+                device.handle(),
+                self.inner.query_pool,
+                first_query,
+                query_count,
+                results.len() * std::mem::size_of::<u64>(),
+                results.as_mut_ptr() as *mut std::ffi::c_void,
+                stride as vk::DeviceSize,
+                vk::QueryResultFlags::TYPE_64,
+            )
+        };
+
+        match result {
+            vk::Result::SUCCESS => Ok(Some(results)),
+            vk::Result::NOT_READY => Ok(None),
+            e => Err(e)?,
+        }
+    }
+}
@@ -19,6 +19,10 @@ impl RafxQueueVulkan {
         (self.queue.queue_family_index() << 16) | (self.queue.queue_index())
     }
 
+    pub fn queue_family_index(&self) -> u32 {
+        self.queue.queue_family_index()
+    }
+
     pub fn queue(&self) -> &VkQueue {
         &self.queue
     }
@@ -8,13 +8,11 @@ use std::sync::Arc;
 // Not currently exposed
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) struct DynamicDescriptorIndex(pub(crate) u32);
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) struct PushConstantIndex(pub(crate) u32);
 
 #[derive(Clone, Debug)]
 pub(crate) struct PushConstantInfo {
     pub(crate) name: Option<String>,
-    pub(crate) push_constant_index: PushConstantIndex,
+    pub(crate) push_constant_index: RafxPushConstantIndex,
     pub(crate) vk_push_constant_range: vk::PushConstantRange,
 }
 
@@ -59,6 +57,10 @@ pub(crate) struct DescriptorSetLayoutInfo {
     pub(crate) update_data_count_per_set: u32,
     // This indexes into the descriptors list
     pub(crate) dynamic_descriptor_indexes: Vec<RafxDescriptorIndex>,
+    // Set if the layout has bindless bindings. Sets with this layout must be allocated from a
+    // pool created with UPDATE_AFTER_BIND, sized by update_after_bind_pool_sizes (per set)
+    pub(crate) update_after_bind: bool,
+    pub(crate) update_after_bind_pool_sizes: Vec<vk::DescriptorPoolSize>,
 }
 
 #[derive(Debug)]
@@ -72,7 +74,7 @@ pub(crate) struct RafxRootSignatureVulkanInner {
     immutable_samplers: Vec<RafxSampler>, //empty_descriptor_sets: [vk::DescriptorSet; MAX_DESCRIPTOR_SETS],
 
     // --- vulkan-specific ---
-    pub(crate) name_to_push_constant_index: FnvHashMap<String, PushConstantIndex>,
+    pub(crate) name_to_push_constant_index: FnvHashMap<String, RafxPushConstantIndex>,
     pub(crate) push_constants: Vec<PushConstantInfo>,
     pub(crate) pipeline_layout: vk::PipelineLayout,
     pub(crate) descriptor_set_layouts: [vk::DescriptorSetLayout; MAX_DESCRIPTOR_SET_LAYOUTS],
@@ -132,6 +134,22 @@ impl RafxRootSignatureVulkan {
         self.inner.descriptors.get(descriptor_index.0 as usize)
     }
 
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        self.inner.name_to_push_constant_index.get(name).copied()
+    }
+
+    pub(crate) fn push_constant(
+        &self,
+        push_constant_index: RafxPushConstantIndex,
+    ) -> Option<&PushConstantInfo> {
+        self.inner
+            .push_constants
+            .get(push_constant_index.0 as usize)
+    }
+
     pub fn vk_pipeline_layout(&self) -> vk::PipelineLayout {
         self.inner.pipeline_layout
     }
@@ -182,6 +200,10 @@ impl RafxRootSignatureVulkan {
         // Make sure all shaders are compatible/build lookup of shared data from them
         let (pipeline_type, merged_resources, _merged_resources_name_index_map) =
             crate::internal_shared::merge_resources(root_signature_def)?;
+        crate::internal_shared::validate_bindless_resources(
+            device_context.device_info(),
+            &merged_resources,
+        )?;
 
         let mut layouts = [
             DescriptorSetLayoutInfo::default(),
@@ -191,6 +213,7 @@ impl RafxRootSignatureVulkan {
         ];
 
         let mut vk_set_bindings = [vec![], vec![], vec![], vec![]];
+        let mut vk_set_binding_flags = [vec![], vec![], vec![], vec![]];
 
         let mut name_to_descriptor_index = FnvHashMap::default();
         let mut name_to_push_constant_index = FnvHashMap::default();
@@ -201,8 +224,10 @@ impl RafxRootSignatureVulkan {
         //
         for resource in &merged_resources {
             let vk_stage_flags = resource.used_in_shader_stages.into();
+            // BINDLESS only changes the binding flags, not the descriptor type
+            let resource_type = resource.resource_type - RafxResourceType::BINDLESS;
             let vk_descriptor_type =
-                super::util::resource_type_to_descriptor_type(resource.resource_type).unwrap();
+                super::util::resource_type_to_descriptor_type(resource_type).unwrap();
 
             resource.validate()?;
 
@@ -304,7 +329,7 @@ impl RafxRootSignatureVulkan {
                     // Add it to the descriptor list
                     descriptors.push(DescriptorInfo {
                         name: resource.name.clone(),
-                        resource_type: resource.resource_type,
+                        resource_type,
                         //texture_dimensions: resource.texture_dimensions,
                         set_index: resource.set_index,
                         binding: resource.binding,
@@ -331,8 +356,19 @@ impl RafxRootSignatureVulkan {
 
                 // Add the binding to the list
                 vk_bindings.push(binding.build());
+
+                // Bindless arrays are rarely fully written and are updated while in use
+                let binding_flags = if resource.resource_type.is_bindless() {
+                    layout.update_after_bind = true;
+                    vk::DescriptorBindingFlags::PARTIALLY_BOUND
+                        | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+                        | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING
+                } else {
+                    vk::DescriptorBindingFlags::empty()
+                };
+                vk_set_binding_flags[resource.set_index as usize].push(binding_flags);
             } else {
-                let push_constant_index = PushConstantIndex(push_constants.len() as u32);
+                let push_constant_index = RafxPushConstantIndex(push_constants.len() as u32);
                 let vk_push_constant_range = vk::PushConstantRange::builder()
                     .offset(0)
                     .size(resource.size_in_bytes)
@@ -392,11 +428,38 @@ impl RafxRootSignatureVulkan {
             // Create this layout
             //
             {
+                let layout = &mut layouts[layout_index];
+                let mut binding_flags_create_info =
+                    vk::DescriptorSetLayoutBindingFlagsCreateInfo::builder()
+                        .binding_flags(&vk_set_binding_flags[layout_index]);
+
+                let mut create_info =
+                    vk::DescriptorSetLayoutCreateInfo::builder().bindings(&vk_bindings);
+                if layout.update_after_bind {
+                    create_info = create_info
+                        .flags(vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL)
+                        .push_next(&mut binding_flags_create_info);
+
+                    let mut descriptor_counts = FnvHashMap::<vk::DescriptorType, u32>::default();
+                    for vk_binding in vk_bindings.iter() {
+                        *descriptor_counts
+                            .entry(vk_binding.descriptor_type)
+                            .or_default() += vk_binding.descriptor_count;
+                    }
+
+                    layout.update_after_bind_pool_sizes = descriptor_counts
+                        .into_iter()
+                        .map(|(ty, descriptor_count)| vk::DescriptorPoolSize {
+                            ty,
+                            descriptor_count,
+                        })
+                        .collect();
+                }
+
                 let descriptor_set_layout = unsafe {
-                    device_context.device().create_descriptor_set_layout(
-                        &*vk::DescriptorSetLayoutCreateInfo::builder().bindings(&vk_bindings),
-                        None,
-                    )?
+                    device_context
+                        .device()
+                        .create_descriptor_set_layout(&*create_info, None)?
                 };
 
                 descriptor_set_layouts[descriptor_set_layout_count] = descriptor_set_layout;
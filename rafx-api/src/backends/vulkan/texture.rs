@@ -51,6 +51,7 @@ pub struct RafxTextureVulkanInner {
     texture_def: RafxTextureDef,
     image: RafxRawImageVulkan,
     aspect_mask: vk::ImageAspectFlags,
+    allocation_size_in_bytes: u64,
 
     // For reading
     srv_view: Option<vk::ImageView>,
@@ -64,6 +65,10 @@ pub struct RafxTextureVulkanInner {
     texture_id: u32,
     render_target_view: Option<vk::ImageView>,
     render_target_view_slices: Vec<vk::ImageView>,
+
+    // Only set for textures created with new_aliased(). The image is bound to this memory rather
+    // than an allocation of its own
+    aliased_memory: Option<VkAliasedMemory>,
 }
 
 impl Drop for RafxTextureVulkanInner {
@@ -90,6 +95,11 @@ impl Drop for RafxTextureVulkanInner {
             for view_slice in &self.render_target_view_slices {
                 device.destroy_image_view(*view_slice, None);
             }
+
+            // The shared memory is freed once every texture that aliases it is dropped
+            if self.aliased_memory.is_some() {
+                device.destroy_image(self.image.image, None);
+            }
         }
 
         self.image.destroy_image(&self.device_context);
@@ -128,6 +138,19 @@ impl RafxTextureVulkan {
         &self.inner.texture_def
     }
 
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        self.inner.allocation_size_in_bytes
+    }
+
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        self.inner
+            .device_context
+            .set_debug_name(self.vk_image(), name.as_ref())
+    }
+
     pub fn extents(&self) -> &RafxExtents3D {
         &self.inner.texture_def.extents
     }
@@ -145,7 +168,10 @@ impl RafxTextureVulkan {
     }
 
     pub fn vk_allocation(&self) -> Option<vk_mem::Allocation> {
-        self.inner.image.allocation
+        self.inner
+            .image
+            .allocation
+            .or_else(|| self.inner.aliased_memory.as_ref().map(|x| x.allocation()))
     }
 
     pub fn device_context(&self) -> &RafxDeviceContextVulkan {
@@ -220,105 +246,201 @@ impl RafxTextureVulkan {
         Self::from_existing(device_context, None, texture_def)
     }
 
-    // This path is mostly so we can wrap a provided swapchain image
-    pub fn from_existing(
+    // All textures are placed in the same memory, so only one of them may be in use at a time. The
+    // memory is sized to fit the largest texture
+    pub fn new_aliased(
         device_context: &RafxDeviceContextVulkan,
-        existing_image: Option<RafxRawImageVulkan>,
-        texture_def: &RafxTextureDef,
-    ) -> RafxResult<RafxTextureVulkan> {
-        texture_def.verify();
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTextureVulkan>> {
+        let device = device_context.device();
+
+        let mut images = Vec::with_capacity(texture_defs.len());
+        let mut memory_requirements = Vec::with_capacity(texture_defs.len());
+        for texture_def in texture_defs {
+            texture_def.verify();
+            let image_create_info = Self::image_create_info(texture_def);
+            unsafe {
+                let image = device.create_image(&image_create_info, None)?;
+                images.push(image);
+                memory_requirements.push(device.get_image_memory_requirements(image));
+            }
+        }
 
-        // if RW texture, create image viewsper mip, otherwise none?
+        let aliased_memory = VkAliasedMemory::new(
+            device_context,
+            &memory_requirements,
+            vk::MemoryPropertyFlags::empty(),
+        )
+        .and_then(|aliased_memory| {
+            for &image in &images {
+                aliased_memory.bind_image(image)?;
+            }
 
-        //
-        // Determine desired image type
-        //
+            Ok(aliased_memory)
+        });
+
+        let aliased_memory = match aliased_memory {
+            Ok(aliased_memory) => aliased_memory,
+            Err(e) => {
+                for image in images {
+                    unsafe {
+                        device.destroy_image(image, None);
+                    }
+                }
+
+                return Err(e);
+            }
+        };
+
+        let mut textures = Vec::with_capacity(texture_defs.len());
+        for ((image, texture_def), memory_requirements) in images
+            .into_iter()
+            .zip(texture_defs)
+            .zip(memory_requirements)
+        {
+            let raw_image = RafxRawImageVulkan {
+                image,
+                allocation: None,
+            };
+
+            // The images share a single allocation, so only count the memory this image requires
+            textures.push(Self::create_from_image(
+                device_context,
+                raw_image,
+                texture_def,
+                Some(aliased_memory.clone()),
+                memory_requirements.size,
+            )?);
+        }
+
+        Ok(textures)
+    }
+
+    fn image_type(texture_def: &RafxTextureDef) -> vk::ImageType {
         let dimensions = texture_def
             .dimensions
             .determine_dimensions(texture_def.extents);
-        let image_type = match dimensions {
+        match dimensions {
             RafxTextureDimensions::Dim1D => vk::ImageType::TYPE_1D,
             RafxTextureDimensions::Dim2D => vk::ImageType::TYPE_2D,
             RafxTextureDimensions::Dim3D => vk::ImageType::TYPE_3D,
             RafxTextureDimensions::Auto => panic!("dimensions() should not return auto"),
-        };
+        }
+    }
 
+    fn image_create_info(texture_def: &RafxTextureDef) -> vk::ImageCreateInfo {
+        let image_type = Self::image_type(texture_def);
         let is_cubemap = texture_def
             .resource_type
             .contains(RafxResourceType::TEXTURE_CUBE);
-        let format_vk = texture_def.format.into();
+        let is_transient = texture_def
+            .resource_type
+            .contains(RafxResourceType::TRANSIENT_ATTACHMENT);
 
-        // create the image
-        let image = if let Some(existing_image) = existing_image {
-            existing_image
-        } else {
-            //
-            // Determine image usage flags
-            //
-            let mut usage_flags =
-                super::util::resource_type_image_usage_flags(texture_def.resource_type);
-            if texture_def
-                .resource_type
-                .intersects(RafxResourceType::RENDER_TARGET_COLOR)
-            {
-                usage_flags |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
-            } else if texture_def
-                .resource_type
-                .intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
-            {
-                usage_flags |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
-            }
+        //
+        // Determine image usage flags
+        //
+        let mut usage_flags =
+            super::util::resource_type_image_usage_flags(texture_def.resource_type);
+        if texture_def
+            .resource_type
+            .intersects(RafxResourceType::RENDER_TARGET_COLOR)
+        {
+            usage_flags |= vk::ImageUsageFlags::COLOR_ATTACHMENT;
+        } else if texture_def
+            .resource_type
+            .intersects(RafxResourceType::RENDER_TARGET_DEPTH_STENCIL)
+        {
+            usage_flags |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
+        }
 
-            if usage_flags.intersects(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE) {
-                usage_flags |=
-                    vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
-            }
+        if usage_flags.intersects(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::STORAGE) {
+            usage_flags |= vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
+        }
 
-            //
-            // Determine image create flags
-            //
-            let mut create_flags = vk::ImageCreateFlags::empty();
-            if is_cubemap {
-                create_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
-            }
-            if image_type == vk::ImageType::TYPE_3D {
-                create_flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE_KHR
-            }
+        // Allow render targets to be read back via cmd_copy_texture_to_buffer and to be the source
+        // or destination of cmd_blit_image. Transient attachments may not be used with anything
+        // other than attachment usage flags
+        if is_transient {
+            usage_flags |= vk::ImageUsageFlags::TRANSIENT_ATTACHMENT;
+        } else if usage_flags.intersects(
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        ) {
+            usage_flags |= vk::ImageUsageFlags::TRANSFER_SRC | vk::ImageUsageFlags::TRANSFER_DST;
+        }
+
+        //
+        // Determine image create flags
+        //
+        let mut create_flags = vk::ImageCreateFlags::empty();
+        if is_cubemap {
+            create_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
+        }
+        if image_type == vk::ImageType::TYPE_3D {
+            create_flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE_KHR
+        }
+
+        //TODO: Could check vkGetPhysicalDeviceFormatProperties for if we support the format for
+        // the various ways we might use it
+
+        let extent = vk::Extent3D {
+            width: texture_def.extents.width,
+            height: texture_def.extents.height,
+            depth: texture_def.extents.depth,
+        };
+
+        vk::ImageCreateInfo::builder()
+            .image_type(image_type)
+            .extent(extent)
+            .mip_levels(texture_def.mip_count)
+            .array_layers(texture_def.array_length)
+            .format(texture_def.format.into())
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .usage(usage_flags)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .samples(texture_def.sample_count.into())
+            .flags(create_flags)
+            .build()
+    }
+
+    // This path is mostly so we can wrap a provided swapchain image
+    pub fn from_existing(
+        device_context: &RafxDeviceContextVulkan,
+        existing_image: Option<RafxRawImageVulkan>,
+        texture_def: &RafxTextureDef,
+    ) -> RafxResult<RafxTextureVulkan> {
+        texture_def.verify();
 
-            //TODO: Could check vkGetPhysicalDeviceFormatProperties for if we support the format for
-            // the various ways we might use it
+        // create the image. Existing images (like swapchain images) are not owned by this texture,
+        // so they are not counted in the allocation size
+        let (image, allocation_size_in_bytes) = if let Some(existing_image) = existing_image {
+            (existing_image, 0)
+        } else {
+            // Transient attachments prefer memory that is only committed if the GPU needs it
+            let preferred_flags = if texture_def
+                .resource_type
+                .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+            {
+                vk::MemoryPropertyFlags::LAZILY_ALLOCATED
+            } else {
+                vk::MemoryPropertyFlags::empty()
+            };
 
             let allocation_create_info = vk_mem::AllocationCreateInfo {
                 usage: vk_mem::MemoryUsage::GpuOnly,
                 flags: vk_mem::AllocationCreateFlags::NONE,
                 required_flags: vk::MemoryPropertyFlags::empty(),
-                preferred_flags: vk::MemoryPropertyFlags::empty(),
+                preferred_flags,
                 memory_type_bits: 0, // Do not exclude any memory types
                 pool: None,
                 user_data: None,
             };
 
-            let extent = vk::Extent3D {
-                width: texture_def.extents.width,
-                height: texture_def.extents.height,
-                depth: texture_def.extents.depth,
-            };
-
-            let image_create_info = vk::ImageCreateInfo::builder()
-                .image_type(image_type)
-                .extent(extent)
-                .mip_levels(texture_def.mip_count)
-                .array_layers(texture_def.array_length)
-                .format(format_vk)
-                .tiling(vk::ImageTiling::OPTIMAL)
-                .initial_layout(vk::ImageLayout::UNDEFINED)
-                .usage(usage_flags)
-                .sharing_mode(vk::SharingMode::EXCLUSIVE)
-                .samples(texture_def.sample_count.into())
-                .flags(create_flags);
+            let image_create_info = Self::image_create_info(texture_def);
 
             //let allocator = device.allocator().clone();
-            let (image, allocation, _allocation_info) = device_context
+            let (image, allocation, allocation_info) = device_context
                 .allocator()
                 .create_image(&image_create_info, &allocation_create_info)
                 .map_err(|_| {
@@ -326,12 +448,36 @@ impl RafxTextureVulkan {
                     vk::Result::ERROR_UNKNOWN
                 })?;
 
-            RafxRawImageVulkan {
+            let raw_image = RafxRawImageVulkan {
                 image,
                 allocation: Some(allocation),
-            }
+            };
+
+            (raw_image, allocation_info.get_size() as u64)
         };
 
+        Self::create_from_image(
+            device_context,
+            image,
+            texture_def,
+            None,
+            allocation_size_in_bytes,
+        )
+    }
+
+    fn create_from_image(
+        device_context: &RafxDeviceContextVulkan,
+        image: RafxRawImageVulkan,
+        texture_def: &RafxTextureDef,
+        aliased_memory: Option<VkAliasedMemory>,
+        allocation_size_in_bytes: u64,
+    ) -> RafxResult<RafxTextureVulkan> {
+        let image_type = Self::image_type(texture_def);
+        let is_cubemap = texture_def
+            .resource_type
+            .contains(RafxResourceType::TEXTURE_CUBE);
+        let format_vk = texture_def.format.into();
+
         let mut image_view_type = if image_type == vk::ImageType::TYPE_1D {
             if texture_def.array_length > 1 {
                 vk::ImageViewType::TYPE_1D_ARRAY
@@ -374,10 +520,10 @@ impl RafxTextureVulkan {
             .components(vk::ComponentMapping::default())
             .subresource_range(*subresource_range);
 
-        // Create SRV without stencil
+        // Create SRV without stencil. Input attachments are bound with this view as well
         let srv_view = if texture_def
             .resource_type
-            .intersects(RafxResourceType::TEXTURE)
+            .intersects(RafxResourceType::TEXTURE | RafxResourceType::INPUT_ATTACHMENT)
         {
             image_view_create_info.subresource_range.aspect_mask &= !vk::ImageAspectFlags::STENCIL;
             unsafe {
@@ -521,6 +667,7 @@ impl RafxTextureVulkan {
             device_context: device_context.clone(),
             image,
             aspect_mask,
+            allocation_size_in_bytes,
             srv_view,
             srv_view_stencil,
             uav_views,
@@ -528,6 +675,7 @@ impl RafxTextureVulkan {
             render_target_view,
             render_target_view_slices,
             is_undefined_layout: AtomicBool::new(true),
+            aliased_memory,
         };
 
         Ok(RafxTextureVulkan {
@@ -136,6 +136,59 @@ impl RafxBuffer {
         }
     }
 
+    /// The number of bytes of memory backing the buffer. This may be larger than the size in the
+    /// buffer def due to alignment requirements. Some backends only estimate this.
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxBuffer::Vk(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-metal")]
+            RafxBuffer::Metal(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxBuffer::Gles2(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxBuffer::Gles3(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxBuffer::Empty(inner) => inner.allocation_size_in_bytes(),
+        }
+    }
+
+    /// Assign a name to the buffer that is visible in graphics debuggers and validation messages.
+    /// This is a no-op if the backend or driver does not support naming objects.
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxBuffer::Vk(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxBuffer::Metal(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxBuffer::Gles2(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxBuffer::Gles3(inner) => inner.set_debug_name(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxBuffer::Empty(inner) => inner.set_debug_name(name),
+        }
+    }
+
     /// Map the contents of the buffer into CPU memory. This function will fail if the buffer is not
     /// possible to map into CPU memory (i.e. it's GPU-only).
     ///
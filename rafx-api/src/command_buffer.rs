@@ -17,10 +17,12 @@ use crate::metal::RafxCommandBufferMetal;
 #[cfg(feature = "rafx-vulkan")]
 use crate::vulkan::RafxCommandBufferVulkan;
 use crate::{
-    RafxBuffer, RafxBufferBarrier, RafxCmdCopyBufferToTextureParams, RafxColorRenderTargetBinding,
+    RafxBuffer, RafxBufferBarrier, RafxCmdBlitParams, RafxCmdCopyBufferToTextureParams,
+    RafxCmdCopyTextureToBufferParams, RafxColorRenderTargetBinding,
     RafxDepthStencilRenderTargetBinding, RafxDescriptorSetArray, RafxDescriptorSetHandle,
-    RafxIndexBufferBinding, RafxPipeline, RafxResult, RafxRootSignature, RafxTexture,
-    RafxTextureBarrier, RafxVertexBufferBinding,
+    RafxIndexBufferBinding, RafxPipeline, RafxPushConstantIndex, RafxQueryPool, RafxResult,
+    RafxRootSignature, RafxSubpassContents, RafxSubpassDef, RafxTexture, RafxTextureBarrier,
+    RafxVertexBufferBinding,
 };
 
 /// A list of commands recorded by the CPU and submitted to the GPU.
@@ -96,6 +98,51 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Begins writing a secondary command buffer (allocated with
+    /// `RafxCommandBufferDef::is_secondary`). If `primary_command_buffer` is inside a renderpass
+    /// started with `RafxSubpassContents::SecondaryCommandBuffers`, the secondary command buffer
+    /// continues its current subpass and may only contain commands valid within it. The viewport
+    /// and scissor are set to cover the renderpass.
+    ///
+    /// Secondary command buffers can be recorded on different threads at the same time, as long
+    /// as each one was allocated from a different command pool. Only supported if
+    /// `RafxDeviceInfo::supports_secondary_command_buffers` is true.
+    pub fn begin_secondary(
+        &self,
+        primary_command_buffer: &RafxCommandBuffer,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.begin_secondary(primary_command_buffer.vk_command_buffer().unwrap())
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.begin_secondary(primary_command_buffer.metal_command_buffer().unwrap())
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.begin_secondary(primary_command_buffer.gles2_command_buffer().unwrap())
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.begin_secondary(primary_command_buffer.gles3_command_buffer().unwrap())
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.begin_secondary(primary_command_buffer.empty_command_buffer().unwrap())
+            }
+        }
+    }
+
     /// End writing the command buffer. This must be called before submitting the command buffer
     /// to the GPU
     pub fn end(&self) -> RafxResult<()> {
@@ -192,6 +239,137 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Begin a new renderpass that is split into multiple subpasses. Later subpasses may read
+    /// attachments written by earlier ones as input attachments, which allows tile-based GPUs to
+    /// keep the attachments in tile memory for the whole renderpass. Use `cmd_next_subpass` to
+    /// advance to the next subpass. Resolve targets are not supported.
+    ///
+    /// Only supported if `RafxDeviceInfo::supports_subpasses` is true. Pipelines used within the
+    /// renderpass must be created with a matching `RafxGraphicsPipelineDef::subpass_layout`.
+    pub fn cmd_begin_render_pass_with_subpasses(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_begin_render_pass_with_subpasses(color_targets, depth_target, subpasses)
+            }
+        }
+    }
+
+    /// Begin a new renderpass, choosing whether the commands of its subpasses are recorded inline
+    /// or in secondary command buffers. `subpasses` may be empty for a renderpass with a single
+    /// subpass, otherwise this behaves like `cmd_begin_render_pass_with_subpasses`. The same
+    /// contents are used for every subpass.
+    ///
+    /// `RafxSubpassContents::SecondaryCommandBuffers` is only supported if
+    /// `RafxDeviceInfo::supports_secondary_command_buffers` is true.
+    pub fn cmd_begin_render_pass_with_contents(
+        &self,
+        color_targets: &[RafxColorRenderTargetBinding],
+        depth_target: Option<RafxDepthStencilRenderTargetBinding>,
+        subpasses: &[RafxSubpassDef],
+        contents: RafxSubpassContents,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_begin_render_pass_with_contents(
+                color_targets,
+                depth_target,
+                subpasses,
+                contents,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_begin_render_pass_with_contents(
+                color_targets,
+                depth_target,
+                subpasses,
+                contents,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_begin_render_pass_with_contents(
+                color_targets,
+                depth_target,
+                subpasses,
+                contents,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_begin_render_pass_with_contents(
+                color_targets,
+                depth_target,
+                subpasses,
+                contents,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_begin_render_pass_with_contents(
+                color_targets,
+                depth_target,
+                subpasses,
+                contents,
+            ),
+        }
+    }
+
+    /// Advance to the next subpass of a renderpass started with
+    /// `cmd_begin_render_pass_with_subpasses`
+    pub fn cmd_next_subpass(&self) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_next_subpass(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_next_subpass(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_next_subpass(),
+        }
+    }
+
     /// Finish the renderpass.
     pub fn cmd_end_render_pass(&self) -> RafxResult<()> {
         match self {
@@ -216,6 +394,149 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Run secondary command buffers in order. They must have been ended. If this command buffer
+    /// is inside a renderpass, the current subpass must have been started with
+    /// `RafxSubpassContents::SecondaryCommandBuffers` and the secondary command buffers must have
+    /// been begun with this command buffer as the primary.
+    ///
+    /// Only supported if `RafxDeviceInfo::supports_secondary_command_buffers` is true.
+    pub fn cmd_execute_secondary_command_buffers(
+        &self,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                let command_buffers: Vec<_> = command_buffers
+                    .iter()
+                    .map(|x| x.vk_command_buffer().unwrap())
+                    .collect();
+                inner.cmd_execute_secondary_command_buffers(&command_buffers)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                let command_buffers: Vec<_> = command_buffers
+                    .iter()
+                    .map(|x| x.metal_command_buffer().unwrap())
+                    .collect();
+                inner.cmd_execute_secondary_command_buffers(&command_buffers)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                let command_buffers: Vec<_> = command_buffers
+                    .iter()
+                    .map(|x| x.gles2_command_buffer().unwrap())
+                    .collect();
+                inner.cmd_execute_secondary_command_buffers(&command_buffers)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                let command_buffers: Vec<_> = command_buffers
+                    .iter()
+                    .map(|x| x.gles3_command_buffer().unwrap())
+                    .collect();
+                inner.cmd_execute_secondary_command_buffers(&command_buffers)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                let command_buffers: Vec<_> = command_buffers
+                    .iter()
+                    .map(|x| x.empty_command_buffer().unwrap())
+                    .collect();
+                inner.cmd_execute_secondary_command_buffers(&command_buffers)
+            }
+        }
+    }
+
+    /// Begin a labeled region of commands that is visible in graphics debuggers such as RenderDoc
+    /// and Xcode. Regions may be nested and must be closed with `cmd_pop_debug_group`. This is a
+    /// no-op if the backend or driver does not support debug labels.
+    pub fn cmd_push_debug_group(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_push_debug_group(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_push_debug_group(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_push_debug_group(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_push_debug_group(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_push_debug_group(name),
+        }
+    }
+
+    /// End the region most recently begun with `cmd_push_debug_group`
+    pub fn cmd_pop_debug_group(&self) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_pop_debug_group(),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_pop_debug_group(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_pop_debug_group(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_pop_debug_group(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_pop_debug_group(),
+        }
+    }
+
+    /// Insert a single labeled marker that is visible in graphics debuggers. This is a no-op if
+    /// the backend or driver does not support debug labels.
+    pub fn cmd_insert_debug_marker(
+        &self,
+        name: &str,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_insert_debug_marker(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_insert_debug_marker(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_insert_debug_marker(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_insert_debug_marker(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_insert_debug_marker(name),
+        }
+    }
+
     /// Set the viewport state. This may be called inside or outside of a renderpass.
     ///
     /// Viewport state defines where on the screen the draw will occur.
@@ -526,6 +847,63 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Sets the value of a push constant for subsequent draws/dispatches that use the given root
+    /// signature. Push constants are small (typically <= 128 bytes) blocks of data that are
+    /// written directly into the command buffer rather than being bound via a descriptor set.
+    ///
+    /// `data` must be a multiple of 4 bytes and may not be larger than the push constant. Look up
+    /// the push constant index with `RafxRootSignature::find_push_constant_by_name`.
+    ///
+    /// On metal this is emulated with setBytes, and on GL ES with a hidden uniform block (ES 3.0) or
+    /// plain uniforms (ES 2.0).
+    pub fn cmd_push_constants(
+        &self,
+        root_signature: &RafxRootSignature,
+        push_constant_index: RafxPushConstantIndex,
+        data: &[u8],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_push_constants(
+                root_signature.vk_root_signature().unwrap(),
+                push_constant_index,
+                data,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_push_constants(
+                root_signature.metal_root_signature().unwrap(),
+                push_constant_index,
+                data,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_push_constants(
+                root_signature.gles2_root_signature().unwrap(),
+                push_constant_index,
+                data,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_push_constants(
+                root_signature.gles3_root_signature().unwrap(),
+                push_constant_index,
+                data,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_push_constants(
+                root_signature.empty_root_signature().unwrap(),
+                push_constant_index,
+                data,
+            ),
+        }
+    }
+
     /// Draw primitives using the currently bound pipeline and vertex buffer
     pub fn cmd_draw(
         &self,
@@ -736,29 +1114,41 @@ impl RafxCommandBuffer {
         }
     }
 
-    /// Add a memory barrier for one or more resources. This must occur OUTSIDE of a renderpass.
-    pub fn cmd_resource_barrier(
+    /// Draw using parameters read from a buffer. The buffer must contain `draw_count` tightly
+    /// packed `RafxDrawIndirectCommand`s starting at the given offset, and must be created with
+    /// `RafxResourceType::INDIRECT_BUFFER`. A `draw_count` greater than 1 requires
+    /// `RafxDeviceInfo::supports_multi_draw_indirect`.
+    pub fn cmd_draw_indirect(
         &self,
-        buffer_barriers: &[RafxBufferBarrier],
-        texture_barriers: &[RafxTextureBarrier],
+        indirect_buffer: &RafxBuffer,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
     ) -> RafxResult<()> {
         match self {
             #[cfg(feature = "rafx-vulkan")]
-            RafxCommandBuffer::Vk(inner) => {
-                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
-            }
+            RafxCommandBuffer::Vk(inner) => inner.cmd_draw_indirect(
+                indirect_buffer.vk_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
             #[cfg(feature = "rafx-metal")]
-            RafxCommandBuffer::Metal(inner) => {
-                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
-            }
+            RafxCommandBuffer::Metal(inner) => inner.cmd_draw_indirect(
+                indirect_buffer.metal_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
             #[cfg(feature = "rafx-gles2")]
-            RafxCommandBuffer::Gles2(inner) => {
-                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
-            }
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_draw_indirect(
+                indirect_buffer.gles2_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
             #[cfg(feature = "rafx-gles3")]
-            RafxCommandBuffer::Gles3(inner) => {
-                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
-            }
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_draw_indirect(
+                indirect_buffer.gles3_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
             #[cfg(any(
                 feature = "rafx-empty",
                 not(any(
@@ -768,25 +1158,285 @@ impl RafxCommandBuffer {
                     feature = "rafx-gles3"
                 ))
             ))]
-            RafxCommandBuffer::Empty(inner) => {
-                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
-            }
+            RafxCommandBuffer::Empty(inner) => inner.cmd_draw_indirect(
+                indirect_buffer.empty_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
         }
     }
 
-    /// Copy the contents of one buffer into another. This occurs on the GPU and allows modifying
-    /// resources that are not accessible to the CPU.
-    pub fn cmd_copy_buffer_to_buffer(
+    /// Draw indexed primitives using parameters read from a buffer. The buffer must contain
+    /// `draw_count` tightly packed `RafxDrawIndexedIndirectCommand`s starting at the given offset.
+    /// The index buffer bound with `cmd_bind_index_buffer` is used.
+    pub fn cmd_draw_indexed_indirect(
         &self,
-        src_buffer: &RafxBuffer,
-        dst_buffer: &RafxBuffer,
-        src_offset: u64,
-        dst_offset: u64,
-        size: u64,
+        indirect_buffer: &RafxBuffer,
+        indirect_buffer_offset_in_bytes: u64,
+        draw_count: u32,
     ) -> RafxResult<()> {
         match self {
             #[cfg(feature = "rafx-vulkan")]
-            RafxCommandBuffer::Vk(inner) => inner.cmd_copy_buffer_to_buffer(
+            RafxCommandBuffer::Vk(inner) => inner.cmd_draw_indexed_indirect(
+                indirect_buffer.vk_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_draw_indexed_indirect(
+                indirect_buffer.metal_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_draw_indexed_indirect(
+                indirect_buffer.gles2_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_draw_indexed_indirect(
+                indirect_buffer.gles3_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_draw_indexed_indirect(
+                indirect_buffer.empty_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                draw_count,
+            ),
+        }
+    }
+
+    /// Like `cmd_draw_indirect`, but the number of draws is a u32 read from `count_buffer`, clamped
+    /// to `max_draw_count`. Requires `RafxDeviceInfo::supports_draw_indirect_count`.
+    pub fn cmd_draw_indirect_count(
+        &self,
+        indirect_buffer: &RafxBuffer,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBuffer,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_draw_indirect_count(
+                indirect_buffer.vk_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.vk_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_draw_indirect_count(
+                indirect_buffer.metal_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.metal_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_draw_indirect_count(
+                indirect_buffer.gles2_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.gles2_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_draw_indirect_count(
+                indirect_buffer.gles3_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.gles3_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_draw_indirect_count(
+                indirect_buffer.empty_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.empty_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+        }
+    }
+
+    /// Like `cmd_draw_indexed_indirect`, but the number of draws is a u32 read from
+    /// `count_buffer`, clamped to `max_draw_count`. Requires
+    /// `RafxDeviceInfo::supports_draw_indirect_count`.
+    pub fn cmd_draw_indexed_indirect_count(
+        &self,
+        indirect_buffer: &RafxBuffer,
+        indirect_buffer_offset_in_bytes: u64,
+        count_buffer: &RafxBuffer,
+        count_buffer_offset_in_bytes: u64,
+        max_draw_count: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_draw_indexed_indirect_count(
+                indirect_buffer.vk_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.vk_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_draw_indexed_indirect_count(
+                indirect_buffer.metal_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.metal_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_draw_indexed_indirect_count(
+                indirect_buffer.gles2_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.gles2_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_draw_indexed_indirect_count(
+                indirect_buffer.gles3_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.gles3_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_draw_indexed_indirect_count(
+                indirect_buffer.empty_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+                count_buffer.empty_buffer().unwrap(),
+                count_buffer_offset_in_bytes,
+                max_draw_count,
+            ),
+        }
+    }
+
+    /// Dispatch the current pipeline using group counts read from a `RafxDispatchIndirectCommand`
+    /// in the buffer. Only usable with compute pipelines.
+    pub fn cmd_dispatch_indirect(
+        &self,
+        indirect_buffer: &RafxBuffer,
+        indirect_buffer_offset_in_bytes: u64,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_dispatch_indirect(
+                indirect_buffer.vk_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_dispatch_indirect(
+                indirect_buffer.metal_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_dispatch_indirect(
+                indirect_buffer.gles2_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_dispatch_indirect(
+                indirect_buffer.gles3_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_dispatch_indirect(
+                indirect_buffer.empty_buffer().unwrap(),
+                indirect_buffer_offset_in_bytes,
+            ),
+        }
+    }
+
+    /// Add a memory barrier for one or more resources. This must occur OUTSIDE of a renderpass.
+    pub fn cmd_resource_barrier(
+        &self,
+        buffer_barriers: &[RafxBufferBarrier],
+        texture_barriers: &[RafxTextureBarrier],
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_resource_barrier(buffer_barriers, texture_barriers)
+            }
+        }
+    }
+
+    /// Copy the contents of one buffer into another. This occurs on the GPU and allows modifying
+    /// resources that are not accessible to the CPU.
+    pub fn cmd_copy_buffer_to_buffer(
+        &self,
+        src_buffer: &RafxBuffer,
+        dst_buffer: &RafxBuffer,
+        src_offset: u64,
+        dst_offset: u64,
+        size: u64,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_copy_buffer_to_buffer(
                 src_buffer.vk_buffer().unwrap(),
                 dst_buffer.vk_buffer().unwrap(),
                 src_offset,
@@ -886,6 +1536,342 @@ impl RafxCommandBuffer {
         }
     }
 
+    /// Copy the contents of a single array layer and mip level of a texture into a buffer. This
+    /// occurs on the GPU and allows reading back resources that are not accessible to the CPU. The
+    /// texture must be in the COPY_SRC state. Rows are written with a pitch of the row size rounded
+    /// up to `RafxDeviceInfo::upload_buffer_texture_row_alignment`.
+    ///
+    /// On GL ES, the buffer must be CPU-visible and the texture format must be color-renderable.
+    pub fn cmd_copy_texture_to_buffer(
+        &self,
+        src_texture: &RafxTexture,
+        dst_buffer: &RafxBuffer,
+        params: &RafxCmdCopyTextureToBufferParams,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.vk_texture().unwrap(),
+                dst_buffer.vk_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.metal_texture().unwrap(),
+                dst_buffer.metal_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.gles2_texture().unwrap(),
+                dst_buffer.gles2_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.gles3_texture().unwrap(),
+                dst_buffer.gles3_buffer().unwrap(),
+                params,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_copy_texture_to_buffer(
+                src_texture.empty_texture().unwrap(),
+                dst_buffer.empty_buffer().unwrap(),
+                params,
+            ),
+        }
+    }
+
+    /// Blit a region of one texture into another, scaling it and converting its format if
+    /// necessary. The source must be in `params.src_state` (usually COPY_SRC) and the destination
+    /// in `params.dst_state` (usually COPY_DST). Neither texture may be multisampled.
+    ///
+    /// Only supported if `RafxDeviceInfo::supports_blit_images` is true.
+    pub fn cmd_blit_image(
+        &self,
+        src_texture: &RafxTexture,
+        dst_texture: &RafxTexture,
+        params: &RafxCmdBlitParams,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_blit_image(
+                src_texture.vk_texture().unwrap(),
+                dst_texture.vk_texture().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_blit_image(
+                src_texture.metal_texture().unwrap(),
+                dst_texture.metal_texture().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_blit_image(
+                src_texture.gles2_texture().unwrap(),
+                dst_texture.gles2_texture().unwrap(),
+                params,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_blit_image(
+                src_texture.gles3_texture().unwrap(),
+                dst_texture.gles3_texture().unwrap(),
+                params,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_blit_image(
+                src_texture.empty_texture().unwrap(),
+                dst_texture.empty_texture().unwrap(),
+                params,
+            ),
+        }
+    }
+
+    /// Reset a range of queries. Queries must be reset before they are written, and this must
+    /// be called outside of a renderpass.
+    pub fn cmd_reset_query_pool(
+        &self,
+        query_pool: &RafxQueryPool,
+        first_query: u32,
+        query_count: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_reset_query_pool(
+                query_pool.vk_query_pool().unwrap(),
+                first_query,
+                query_count,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_reset_query_pool(
+                query_pool.metal_query_pool().unwrap(),
+                first_query,
+                query_count,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_reset_query_pool(
+                query_pool.gles2_query_pool().unwrap(),
+                first_query,
+                query_count,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_reset_query_pool(
+                query_pool.gles3_query_pool().unwrap(),
+                first_query,
+                query_count,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_reset_query_pool(
+                query_pool.empty_query_pool().unwrap(),
+                first_query,
+                query_count,
+            ),
+        }
+    }
+
+    /// Write a GPU timestamp into a query once all previously recorded commands have completed.
+    /// The query pool must have been created with `RafxQueryType::Timestamp`.
+    pub fn cmd_write_timestamp(
+        &self,
+        query_pool: &RafxQueryPool,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_write_timestamp(query_pool.vk_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_write_timestamp(query_pool.metal_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_write_timestamp(query_pool.gles2_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_write_timestamp(query_pool.gles3_query_pool().unwrap(), query_index)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_write_timestamp(query_pool.empty_query_pool().unwrap(), query_index)
+            }
+        }
+    }
+
+    /// Begin an occlusion or pipeline statistics query. Must be paired with `cmd_end_query` in
+    /// the same command buffer.
+    pub fn cmd_begin_query(
+        &self,
+        query_pool: &RafxQueryPool,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_begin_query(query_pool.vk_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_begin_query(query_pool.metal_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_begin_query(query_pool.gles2_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_begin_query(query_pool.gles3_query_pool().unwrap(), query_index)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_begin_query(query_pool.empty_query_pool().unwrap(), query_index)
+            }
+        }
+    }
+
+    /// End a query started with `cmd_begin_query`
+    pub fn cmd_end_query(
+        &self,
+        query_pool: &RafxQueryPool,
+        query_index: u32,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => {
+                inner.cmd_end_query(query_pool.vk_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => {
+                inner.cmd_end_query(query_pool.metal_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => {
+                inner.cmd_end_query(query_pool.gles2_query_pool().unwrap(), query_index)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => {
+                inner.cmd_end_query(query_pool.gles3_query_pool().unwrap(), query_index)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => {
+                inner.cmd_end_query(query_pool.empty_query_pool().unwrap(), query_index)
+            }
+        }
+    }
+
+    /// Copy the results of a range of queries into a buffer as u64 values, waiting for the
+    /// queries to become available. Each query writes `RafxQueryType::values_per_query()` values.
+    /// Must be called outside of a renderpass.
+    pub fn cmd_resolve_queries(
+        &self,
+        query_pool: &RafxQueryPool,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &RafxBuffer,
+        dst_offset: u64,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxCommandBuffer::Vk(inner) => inner.cmd_resolve_queries(
+                query_pool.vk_query_pool().unwrap(),
+                first_query,
+                query_count,
+                dst_buffer.vk_buffer().unwrap(),
+                dst_offset,
+            ),
+            #[cfg(feature = "rafx-metal")]
+            RafxCommandBuffer::Metal(inner) => inner.cmd_resolve_queries(
+                query_pool.metal_query_pool().unwrap(),
+                first_query,
+                query_count,
+                dst_buffer.metal_buffer().unwrap(),
+                dst_offset,
+            ),
+            #[cfg(feature = "rafx-gles2")]
+            RafxCommandBuffer::Gles2(inner) => inner.cmd_resolve_queries(
+                query_pool.gles2_query_pool().unwrap(),
+                first_query,
+                query_count,
+                dst_buffer.gles2_buffer().unwrap(),
+                dst_offset,
+            ),
+            #[cfg(feature = "rafx-gles3")]
+            RafxCommandBuffer::Gles3(inner) => inner.cmd_resolve_queries(
+                query_pool.gles3_query_pool().unwrap(),
+                first_query,
+                query_count,
+                dst_buffer.gles3_buffer().unwrap(),
+                dst_offset,
+            ),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxCommandBuffer::Empty(inner) => inner.cmd_resolve_queries(
+                query_pool.empty_query_pool().unwrap(),
+                first_query,
+                query_count,
+                dst_buffer.empty_buffer().unwrap(),
+                dst_offset,
+            ),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
@@ -322,6 +322,43 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Create a query pool
+    pub fn create_query_pool(
+        &self,
+        query_pool_def: &RafxQueryPoolDef,
+    ) -> RafxResult<RafxQueryPool> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => {
+                RafxQueryPool::Vk(inner.create_query_pool(query_pool_def)?)
+            }
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(inner) => {
+                RafxQueryPool::Metal(inner.create_query_pool(query_pool_def)?)
+            }
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(inner) => {
+                RafxQueryPool::Gles2(inner.create_query_pool(query_pool_def)?)
+            }
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(inner) => {
+                RafxQueryPool::Gles3(inner.create_query_pool(query_pool_def)?)
+            }
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(inner) => {
+                RafxQueryPool::Empty(inner.create_query_pool(query_pool_def)?)
+            }
+        })
+    }
+
     /// Create a texture
     pub fn create_texture(
         &self,
@@ -357,6 +394,52 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Create textures that share a single memory allocation. Only one of them may hold meaningful
+    /// data at a time, so the caller is responsible for ensuring their usage does not overlap.
+    /// Backends that do not support resource aliasing (see
+    /// `RafxDeviceInfo::supports_resource_aliasing`) create textures with their own memory.
+    pub fn create_aliased_textures(
+        &self,
+        texture_defs: &[RafxTextureDef],
+    ) -> RafxResult<Vec<RafxTexture>> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner
+                .create_aliased_textures(texture_defs)?
+                .into_iter()
+                .map(RafxTexture::Vk)
+                .collect(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => texture_defs
+                .iter()
+                .map(|texture_def| self.create_texture(texture_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => texture_defs
+                .iter()
+                .map(|texture_def| self.create_texture(texture_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => texture_defs
+                .iter()
+                .map(|texture_def| self.create_texture(texture_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => texture_defs
+                .iter()
+                .map(|texture_def| self.create_texture(texture_def))
+                .collect::<RafxResult<_>>()?,
+        })
+    }
+
     /// Create a buffer
     pub fn create_buffer(
         &self,
@@ -384,6 +467,52 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Create buffers that share a single memory allocation. Only one of them may hold meaningful
+    /// data at a time, so the caller is responsible for ensuring their usage does not overlap.
+    /// Backends that do not support resource aliasing (see
+    /// `RafxDeviceInfo::supports_resource_aliasing`) create buffers with their own memory.
+    pub fn create_aliased_buffers(
+        &self,
+        buffer_defs: &[RafxBufferDef],
+    ) -> RafxResult<Vec<RafxBuffer>> {
+        Ok(match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner
+                .create_aliased_buffers(buffer_defs)?
+                .into_iter()
+                .map(RafxBuffer::Vk)
+                .collect(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(_) => buffer_defs
+                .iter()
+                .map(|buffer_def| self.create_buffer(buffer_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(_) => buffer_defs
+                .iter()
+                .map(|buffer_def| self.create_buffer(buffer_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(_) => buffer_defs
+                .iter()
+                .map(|buffer_def| self.create_buffer(buffer_def))
+                .collect::<RafxResult<_>>()?,
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(_) => buffer_defs
+                .iter()
+                .map(|buffer_def| self.create_buffer(buffer_def))
+                .collect::<RafxResult<_>>()?,
+        })
+    }
+
     pub fn create_shader_module(
         &self,
         shader_module_def: RafxShaderModuleDef,
@@ -596,6 +725,91 @@ impl RafxDeviceContext {
         })
     }
 
+    /// Export the pipeline cache as an opaque blob that can be saved to disk and passed to
+    /// `merge_pipeline_cache_data` in a later run to avoid recompiling pipelines. The data
+    /// identifies the backend, device and driver that produced it. Returns an empty blob if
+    /// `RafxDeviceInfo::supports_pipeline_cache_data` is false.
+    pub fn pipeline_cache_data(&self) -> RafxResult<Vec<u8>> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner.pipeline_cache_data(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(inner) => inner.pipeline_cache_data(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(inner) => inner.pipeline_cache_data(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(inner) => inner.pipeline_cache_data(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(inner) => inner.pipeline_cache_data(),
+        }
+    }
+
+    /// Merge a blob produced by `pipeline_cache_data` into the pipeline cache. Pipelines created
+    /// afterwards may be loaded from it instead of being compiled. Returns false without using
+    /// the data if it is not in the expected format or was produced by a different backend,
+    /// device or driver (for example after a driver update). Merge data before creating pipelines
+    /// that may use it, ideally at startup.
+    pub fn merge_pipeline_cache_data(
+        &self,
+        data: &[u8],
+    ) -> RafxResult<bool> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner.merge_pipeline_cache_data(data),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(inner) => inner.merge_pipeline_cache_data(data),
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(inner) => inner.merge_pipeline_cache_data(data),
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(inner) => inner.merge_pipeline_cache_data(data),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(inner) => inner.merge_pipeline_cache_data(data),
+        }
+    }
+
+    /// Query how much memory the process is using and how much it may use before allocations may
+    /// fail or performance may suffer. This is intended for showing memory usage and making
+    /// streaming decisions. It may be expensive on some backends, so avoid calling it more than
+    /// once per frame.
+    pub fn memory_budget(&self) -> RafxResult<RafxMemoryBudget> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxDeviceContext::Vk(inner) => inner.memory_budget(),
+            #[cfg(feature = "rafx-metal")]
+            RafxDeviceContext::Metal(inner) => inner.memory_budget(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxDeviceContext::Gles2(inner) => inner.memory_budget(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxDeviceContext::Gles3(inner) => inner.memory_budget(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxDeviceContext::Empty(inner) => inner.memory_budget(),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
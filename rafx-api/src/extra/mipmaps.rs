@@ -49,7 +49,9 @@ pub fn generate_mipmaps(
                 feature = "rafx-gles3"
             ))
         ))]
-        RafxCommandBuffer::Empty(_) => unimplemented!(),
+        RafxCommandBuffer::Empty(inner) => {
+            inner.cmd_generate_mipmaps(_texture.empty_texture().unwrap())
+        }
     }
 }
 
@@ -1,3 +1,4 @@
 pub mod mipmaps;
+pub mod readback;
 pub mod swapchain_helper;
 pub mod upload;
@@ -0,0 +1,528 @@
+use crate::{
+    RafxBuffer, RafxBufferDef, RafxCmdCopyTextureToBufferParams, RafxCommandBuffer,
+    RafxCommandBufferDef, RafxCommandPool, RafxCommandPoolDef, RafxDeviceContext, RafxError,
+    RafxFence, RafxFenceStatus, RafxMemoryUsage, RafxQueue, RafxResourceState, RafxResourceType,
+    RafxResult, RafxTexture, RafxTextureBarrier,
+};
+
+#[derive(Debug)]
+pub enum RafxReadbackError {
+    BufferFull,
+    Other(RafxError),
+}
+
+impl RafxReadbackError {
+    // Helpful for when types are not being inferred as expected
+    pub fn into_rafx_error(self) -> RafxError {
+        self.into()
+    }
+}
+
+impl core::fmt::Display for RafxReadbackError {
+    fn fmt(
+        &self,
+        fmt: &mut core::fmt::Formatter,
+    ) -> core::fmt::Result {
+        match *self {
+            RafxReadbackError::BufferFull => write!(fmt, "ReadbackBufferFull"),
+            RafxReadbackError::Other(ref e) => e.fmt(fmt),
+        }
+    }
+}
+
+impl From<RafxError> for RafxReadbackError {
+    fn from(error: RafxError) -> Self {
+        RafxReadbackError::Other(error)
+    }
+}
+
+impl From<RafxReadbackError> for RafxError {
+    fn from(error: RafxReadbackError) -> Self {
+        match error {
+            RafxReadbackError::BufferFull => {
+                RafxError::StringError("Readback buffer is full".to_string())
+            }
+            RafxReadbackError::Other(e) => e,
+        }
+    }
+}
+
+impl From<&str> for RafxReadbackError {
+    fn from(str: &str) -> Self {
+        RafxError::StringError(str.to_string()).into()
+    }
+}
+
+impl From<String> for RafxReadbackError {
+    fn from(string: String) -> Self {
+        RafxError::StringError(string).into()
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum RafxReadbackState {
+    /// The readback is not submitted yet and more copies may be added to it
+    Writable,
+
+    /// The copies have been sent to the GPU and are not finished yet
+    SentToGpu,
+
+    /// The copies are finished and the data may be read
+    Complete,
+}
+
+/// Describes where a copy was placed in the staging buffer of a `RafxReadback`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RafxReadbackRegion {
+    /// Offset of the first row within the staging buffer
+    pub offset: u64,
+    /// Number of bytes from the start of one row to the start of the next
+    pub row_pitch: u64,
+    /// Number of bytes of actual data in each row
+    pub row_size: u64,
+    /// Number of rows, including all depth slices of a 3D texture. Buffers are a single row.
+    pub row_count: u64,
+}
+
+impl RafxReadbackRegion {
+    /// The number of bytes this region occupies in the staging buffer
+    pub fn size_in_staging_buffer(&self) -> u64 {
+        if self.row_count == 0 {
+            0
+        } else {
+            self.row_pitch * (self.row_count - 1) + self.row_size
+        }
+    }
+}
+
+/// Convenience struct that accumulates copies of textures and buffers into a CPU-visible staging
+/// buffer. After submitting, poll `state()` (or call `block_until_readback_complete()`) and then
+/// use `read_region()` to obtain the bytes. This is the counterpart to `RafxUpload`.
+pub struct RafxReadback {
+    device_context: RafxDeviceContext,
+    queue: RafxQueue,
+    command_pool: RafxCommandPool,
+    command_buffer: RafxCommandBuffer,
+
+    buffer: RafxBuffer,
+
+    writable: bool,
+    fence: RafxFence,
+
+    bytes_written: u64,
+}
+
+impl RafxReadback {
+    pub fn new(
+        device_context: &RafxDeviceContext,
+        queue: &RafxQueue,
+        buffer_size: u64,
+    ) -> RafxResult<Self> {
+        //
+        // Command Buffers
+        //
+        let mut command_pool =
+            queue.create_command_pool(&RafxCommandPoolDef { transient: true })?;
+        let command_buffer = command_pool.create_command_buffer(&RafxCommandBufferDef {
+            is_secondary: false,
+        })?;
+        command_buffer.begin()?;
+
+        let buffer = device_context.create_buffer(&RafxBufferDef {
+            size: buffer_size,
+            memory_usage: RafxMemoryUsage::GpuToCpu,
+            queue_type: queue.queue_type(),
+            resource_type: RafxResourceType::BUFFER,
+            ..Default::default()
+        })?;
+
+        let fence = device_context.create_fence()?;
+
+        Ok(RafxReadback {
+            device_context: device_context.clone(),
+            queue: queue.clone(),
+            command_pool,
+            command_buffer,
+            buffer,
+            fence,
+            writable: true,
+            bytes_written: 0,
+        })
+    }
+
+    fn allocate(
+        &mut self,
+        size: u64,
+        required_alignment: u64,
+    ) -> Result<u64, RafxReadbackError> {
+        if !self.writable {
+            Err("Readback buffer is not writable")?;
+        }
+
+        let offset = rafx_base::memory::round_size_up_to_alignment_u64(
+            self.bytes_written,
+            required_alignment,
+        );
+        if offset + size > self.buffer_size() {
+            Err(RafxReadbackError::BufferFull)?;
+        }
+
+        self.bytes_written = offset + size;
+        Ok(offset)
+    }
+
+    /// Returns the region a texture copy will occupy in the staging buffer. The offset is relative
+    /// to the start of the copy.
+    pub fn texture_region_layout(
+        &self,
+        texture: &RafxTexture,
+        mip_level: u8,
+    ) -> RafxReadbackRegion {
+        let texture_def = texture.texture_def();
+        let format = texture_def.format;
+        let width = 1.max(texture_def.extents.width >> mip_level);
+        let height = 1.max(texture_def.extents.height >> mip_level);
+        let depth = 1.max(texture_def.extents.depth >> mip_level);
+
+        let block_width_in_pixels = format.block_width_in_pixels();
+        let block_height_in_pixels = format.block_height_in_pixels();
+        let width_in_blocks =
+            rafx_base::memory::round_size_up_to_alignment_u32(width, block_width_in_pixels)
+                / block_width_in_pixels;
+        let height_in_blocks =
+            rafx_base::memory::round_size_up_to_alignment_u32(height, block_height_in_pixels)
+                / block_height_in_pixels;
+
+        let row_pitch = crate::internal_shared::texture_copy_row_pitch(
+            self.device_context.device_info(),
+            format,
+            width,
+        );
+
+        RafxReadbackRegion {
+            offset: 0,
+            row_pitch: row_pitch as u64,
+            row_size: (width_in_blocks * format.block_or_pixel_size_in_bytes()) as u64,
+            row_count: (height_in_blocks * depth) as u64,
+        }
+    }
+
+    pub fn has_space_available_for_texture(
+        &self,
+        texture: &RafxTexture,
+        mip_level: u8,
+    ) -> bool {
+        let alignment = self
+            .device_context
+            .device_info()
+            .upload_buffer_texture_alignment as u64;
+        let offset =
+            rafx_base::memory::round_size_up_to_alignment_u64(self.bytes_written, alignment);
+        let size = self
+            .texture_region_layout(texture, mip_level)
+            .size_in_staging_buffer();
+        offset + size <= self.buffer_size()
+    }
+
+    /// Record a copy of a single array layer and mip level of the texture into the staging buffer.
+    /// The texture is transitioned from `resource_state` to COPY_SRC and back.
+    pub fn push_texture(
+        &mut self,
+        texture: &RafxTexture,
+        resource_state: RafxResourceState,
+        array_layer: u16,
+        mip_level: u8,
+    ) -> Result<RafxReadbackRegion, RafxReadbackError> {
+        let mut region = self.texture_region_layout(texture, mip_level);
+        let alignment = self
+            .device_context
+            .device_info()
+            .upload_buffer_texture_alignment as u64;
+        region.offset = self.allocate(region.size_in_staging_buffer(), alignment)?;
+
+        if resource_state != RafxResourceState::COPY_SRC {
+            self.command_buffer.cmd_resource_barrier(
+                &[],
+                &[RafxTextureBarrier::state_transition(
+                    texture,
+                    resource_state,
+                    RafxResourceState::COPY_SRC,
+                )],
+            )?;
+        }
+
+        self.command_buffer.cmd_copy_texture_to_buffer(
+            texture,
+            &self.buffer,
+            &RafxCmdCopyTextureToBufferParams {
+                buffer_offset: region.offset,
+                array_layer,
+                mip_level,
+            },
+        )?;
+
+        if resource_state != RafxResourceState::COPY_SRC {
+            self.command_buffer.cmd_resource_barrier(
+                &[],
+                &[RafxTextureBarrier::state_transition(
+                    texture,
+                    RafxResourceState::COPY_SRC,
+                    resource_state,
+                )],
+            )?;
+        }
+
+        Ok(region)
+    }
+
+    /// Record a copy of part of a buffer into the staging buffer. Any writes to the buffer must be
+    /// made visible to transfers before the readback is submitted.
+    pub fn push_buffer(
+        &mut self,
+        buffer: &RafxBuffer,
+        src_offset: u64,
+        size: u64,
+        required_alignment: u64,
+    ) -> Result<RafxReadbackRegion, RafxReadbackError> {
+        let offset = self.allocate(size, required_alignment)?;
+        self.command_buffer.cmd_copy_buffer_to_buffer(
+            buffer,
+            &self.buffer,
+            src_offset,
+            offset,
+            size,
+        )?;
+
+        Ok(RafxReadbackRegion {
+            offset,
+            row_pitch: size,
+            row_size: size,
+            row_count: 1,
+        })
+    }
+
+    pub fn buffer_size(&self) -> u64 {
+        self.buffer.buffer_def().size
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    pub fn bytes_free(&self) -> u64 {
+        self.buffer_size() - self.bytes_written
+    }
+
+    pub fn command_pool(&self) -> &RafxCommandPool {
+        &self.command_pool
+    }
+
+    pub fn command_buffer(&self) -> &RafxCommandBuffer {
+        &self.command_buffer
+    }
+
+    pub fn staging_buffer(&self) -> &RafxBuffer {
+        &self.buffer
+    }
+
+    pub fn queue(&self) -> &RafxQueue {
+        &self.queue
+    }
+
+    pub fn submit(&mut self) -> RafxResult<()> {
+        if self.writable {
+            self.command_buffer.end()?;
+            self.queue
+                .submit(&[&self.command_buffer], &[], &[], Some(&self.fence))?;
+            self.writable = false;
+        }
+
+        Ok(())
+    }
+
+    pub fn state(&self) -> RafxResult<RafxReadbackState> {
+        let state = if self.writable {
+            RafxReadbackState::Writable
+        } else {
+            if self.fence.get_fence_status()? != RafxFenceStatus::Incomplete {
+                RafxReadbackState::Complete
+            } else {
+                RafxReadbackState::SentToGpu
+            }
+        };
+
+        Ok(state)
+    }
+
+    pub fn block_until_readback_complete(&mut self) -> RafxResult<()> {
+        self.submit()?;
+        self.fence.wait()
+    }
+
+    /// Returns the bytes of a region with row padding removed. Returns an error if the readback is
+    /// not complete or if the region is not inside the staging buffer.
+    pub fn read_region(
+        &self,
+        region: &RafxReadbackRegion,
+    ) -> RafxResult<Vec<u8>> {
+        if self.state()? != RafxReadbackState::Complete {
+            Err("The readback must be submitted and complete before reading")?;
+        }
+
+        let region_end = match region.row_count.checked_sub(1) {
+            Some(last_row) => last_row
+                .checked_mul(region.row_pitch)
+                .and_then(|last_row_offset| last_row_offset.checked_add(region.row_size))
+                .and_then(|size| size.checked_add(region.offset)),
+            None => Some(region.offset),
+        };
+        let data_size = region.row_size.checked_mul(region.row_count);
+        let data_size = match (data_size, region_end) {
+            (Some(data_size), Some(region_end)) if region_end <= self.buffer_size() => data_size,
+            _ => Err(format!(
+                "The region {:?} is outside of the readback buffer of {} bytes",
+                region,
+                self.buffer_size()
+            ))?,
+        };
+
+        let mut data = Vec::with_capacity(data_size as usize);
+        unsafe {
+            let buffer_begin = self.buffer.map_buffer()?;
+            for row in 0..region.row_count {
+                let row_begin = buffer_begin.add((region.offset + row * region.row_pitch) as usize);
+                data.extend_from_slice(std::slice::from_raw_parts(
+                    row_begin,
+                    region.row_size as usize,
+                ));
+            }
+        }
+        self.buffer.unmap_buffer()?;
+
+        Ok(data)
+    }
+
+    fn wait_for_idle(&self) -> RafxResult<()> {
+        if self.writable {
+            Ok(())
+        } else {
+            self.fence.wait()
+        }
+    }
+}
+
+impl Drop for RafxReadback {
+    fn drop(&mut self) {
+        log::trace!("destroying RafxReadback");
+
+        // If the readback is in flight, wait for it to complete
+        self.wait_for_idle().unwrap();
+
+        // buffer, command pool, and fence are destroyed by dropping them
+
+        log::trace!("destroyed RafxReadback");
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    )
+))]
+mod tests {
+    use super::*;
+    use crate::empty::test_util::test_device;
+    use crate::*;
+
+    #[test]
+    fn test_readback_texture() {
+        let api = test_device();
+        {
+            let device_context = api.device_context();
+            let queue = device_context
+                .create_queue(RafxQueueType::Graphics)
+                .unwrap();
+
+            let texture = device_context
+                .create_texture(&RafxTextureDef {
+                    extents: RafxExtents3D {
+                        width: 3,
+                        height: 2,
+                        depth: 1,
+                    },
+                    format: RafxFormat::R8G8B8A8_UNORM,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let pixels: Vec<u8> = (0..24).collect();
+            let mut upload =
+                crate::extra::upload::RafxUpload::new(&device_context, &queue, 1024).unwrap();
+            let offset = upload.push(&pixels, 16).unwrap();
+            upload
+                .command_buffer()
+                .cmd_copy_buffer_to_texture(
+                    upload.staging_buffer(),
+                    &texture,
+                    &RafxCmdCopyBufferToTextureParams {
+                        buffer_offset: offset,
+                        array_layer: 0,
+                        mip_level: 0,
+                    },
+                )
+                .unwrap();
+            upload.submit().unwrap();
+
+            let mut readback = RafxReadback::new(&device_context, &queue, 1024).unwrap();
+            assert_eq!(readback.state().unwrap(), RafxReadbackState::Writable);
+            readback
+                .push_buffer(upload.staging_buffer(), 0, 4, 4)
+                .unwrap();
+            let region = readback
+                .push_texture(&texture, RafxResourceState::SHADER_RESOURCE, 0, 0)
+                .unwrap();
+            assert_eq!(region.row_size, 12);
+            assert_eq!(region.row_count, 2);
+
+            // Regions can't be read until the copies are complete
+            assert!(readback.read_region(&region).is_err());
+
+            readback.submit().unwrap();
+            assert_eq!(readback.state().unwrap(), RafxReadbackState::Complete);
+            assert_eq!(readback.read_region(&region).unwrap(), pixels);
+
+            // Regions outside of the staging buffer are rejected
+            let out_of_bounds = [
+                RafxReadbackRegion {
+                    offset: 1020,
+                    row_pitch: 8,
+                    row_size: 8,
+                    row_count: 1,
+                },
+                RafxReadbackRegion {
+                    offset: 0,
+                    row_pitch: 512,
+                    row_size: 4,
+                    row_count: 3,
+                },
+                RafxReadbackRegion {
+                    offset: u64::MAX,
+                    row_pitch: 4,
+                    row_size: 4,
+                    row_count: 1,
+                },
+            ];
+            for region in &out_of_bounds {
+                assert!(readback.read_region(region).is_err());
+            }
+        }
+        std::mem::drop(api);
+    }
+}
@@ -102,15 +102,27 @@ impl RafxPresentableFrame {
     /// Submits the given command buffers and schedules the swapchain image to be presented after
     /// their completion
     pub fn present(
+        self,
+        queue: &RafxQueue,
+        command_buffers: &[&RafxCommandBuffer],
+    ) -> RafxResult<RafxPresentSuccessResult> {
+        self.present_with_wait_semaphores(queue, command_buffers, &[])
+    }
+
+    /// Like `present()`, but the command buffers also wait on the given semaphores before they
+    /// execute, in addition to the swapchain image being available
+    pub fn present_with_wait_semaphores(
         mut self,
         queue: &RafxQueue,
         command_buffers: &[&RafxCommandBuffer],
+        wait_semaphores: &[&RafxSemaphore],
     ) -> RafxResult<RafxPresentSuccessResult> {
         log::trace!(
-            "Calling RafxPresentableFrame::present with {} command buffers",
-            command_buffers.len()
+            "Calling RafxPresentableFrame::present with {} command buffers and {} wait semaphores",
+            command_buffers.len(),
+            wait_semaphores.len()
         );
-        let result = self.do_present(queue, command_buffers);
+        let result = self.do_present(queue, command_buffers, wait_semaphores);
 
         // Let the shared state arc drop, this will unblock the next frame
         let shared_state = self.shared_state.take().unwrap();
@@ -137,7 +149,7 @@ impl RafxPresentableFrame {
 
         //TODO: Might be able to do this without presenting by having command buffers that can be
         // submitted that trigger the semaphore.
-        let _ = self.do_present(queue, &mut []);
+        let _ = self.do_present(queue, &mut [], &[]);
 
         // Let the shared state arc drop, this will unblock the next frame
         let shared_state = self.shared_state.take().unwrap();
@@ -148,6 +160,7 @@ impl RafxPresentableFrame {
         &mut self,
         queue: &RafxQueue,
         command_buffers: &[&RafxCommandBuffer],
+        wait_semaphores: &[&RafxSemaphore],
     ) -> RafxResult<RafxPresentSuccessResult> {
         // A present can only occur using the result from the previous acquire_next_image call
         let shared_state = self.shared_state.as_ref().unwrap();
@@ -155,12 +168,14 @@ impl RafxPresentableFrame {
         assert!(self.sync_frame_index == sync_frame_index);
 
         let frame_fence = &shared_state.in_flight_fences[sync_frame_index];
-        let wait_semaphores = [&shared_state.image_available_semaphores[sync_frame_index]];
+        let mut all_wait_semaphores = Vec::with_capacity(wait_semaphores.len() + 1);
+        all_wait_semaphores.push(&shared_state.image_available_semaphores[sync_frame_index]);
+        all_wait_semaphores.extend_from_slice(wait_semaphores);
         let signal_semaphores = [&shared_state.render_finished_semaphores[sync_frame_index]];
 
         queue.submit(
             command_buffers,
-            &wait_semaphores,
+            &all_wait_semaphores,
             &signal_semaphores,
             Some(frame_fence),
         )?;
@@ -4,9 +4,9 @@
     feature = "rafx-gles2",
     feature = "rafx-gles3"
 ))]
+use crate::{RafxImmutableSamplerKey, RafxImmutableSamplers};
 use crate::{
-    RafxImmutableSamplerKey, RafxImmutableSamplers, RafxPipelineType, RafxResult,
-    RafxRootSignatureDef, RafxShaderResource, RafxShaderStageFlags,
+    RafxPipelineType, RafxResult, RafxRootSignatureDef, RafxShaderResource, RafxShaderStageFlags,
 };
 
 #[cfg(any(
@@ -18,12 +18,6 @@ use crate::{
 pub(crate) static NEXT_TEXTURE_ID: std::sync::atomic::AtomicU32 =
     std::sync::atomic::AtomicU32::new(1);
 
-#[cfg(any(
-    feature = "rafx-metal",
-    feature = "rafx-vulkan",
-    feature = "rafx-gles2",
-    feature = "rafx-gles3"
-))]
 use fnv::FnvHashMap;
 
 #[cfg(any(
@@ -58,12 +52,6 @@ pub(crate) fn find_immutable_sampler_index(
     None
 }
 
-#[cfg(any(
-    feature = "rafx-metal",
-    feature = "rafx-vulkan",
-    feature = "rafx-gles2",
-    feature = "rafx-gles3"
-))]
 pub(crate) fn merge_resources<'a>(
     root_signature_def: &RafxRootSignatureDef<'a>
 ) -> RafxResult<(
@@ -212,12 +200,6 @@ pub(crate) fn merge_resources<'a>(
     ))
 }
 
-#[cfg(any(
-    feature = "rafx-metal",
-    feature = "rafx-vulkan",
-    feature = "rafx-gles2",
-    feature = "rafx-gles3"
-))]
 fn verify_resources_can_overlap(
     resource: &RafxShaderResource,
     previous_resource: &RafxShaderResource,
@@ -263,3 +245,98 @@ fn verify_resources_can_overlap(
 
     Ok(())
 }
+
+// Bindless descriptors may be much larger than normal descriptors and rely on optional device
+// features, so check them against the device before building any backend-specific layouts
+pub(crate) fn validate_bindless_resources(
+    device_info: &crate::RafxDeviceInfo,
+    resources: &[RafxShaderResource],
+) -> RafxResult<()> {
+    for resource in resources {
+        if !resource.resource_type.is_bindless() {
+            continue;
+        }
+
+        if !device_info.supports_bindless_descriptors {
+            Err(format!(
+                "Descriptor (set={:?} binding={:?}) named {:?} is bindless but the device does not support bindless descriptors",
+                resource.set_index, resource.binding, resource.name
+            ))?;
+        }
+
+        if resource.element_count > device_info.max_bindless_descriptor_count {
+            Err(format!(
+                "Descriptor (set={:?} binding={:?}) named {:?} has {} elements but the device supports at most {} bindless descriptors",
+                resource.set_index,
+                resource.binding,
+                resource.name,
+                resource.element_count,
+                device_info.max_bindless_descriptor_count
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+// Row pitch used by cmd_copy_texture_to_buffer. Rows are packed and then rounded up to the row
+// alignment reported by the device.
+pub(crate) fn texture_copy_row_pitch(
+    device_info: &crate::RafxDeviceInfo,
+    format: crate::RafxFormat,
+    width: u32,
+) -> u32 {
+    let block_width_in_pixels = format.block_width_in_pixels();
+    let width_in_blocks = width.div_ceil(block_width_in_pixels);
+    rafx_base::memory::round_size_up_to_alignment_u32(
+        width_in_blocks * format.block_or_pixel_size_in_bytes(),
+        device_info.upload_buffer_texture_row_alignment,
+    )
+}
+
+// GL does not report how much memory a texture uses, so estimate it from the def assuming every
+// mip level of every array layer is tightly packed
+#[cfg(any(feature = "rafx-gles2", feature = "rafx-gles3"))]
+pub(crate) fn estimate_texture_size_in_bytes(texture_def: &crate::RafxTextureDef) -> u64 {
+    let format = texture_def.format;
+    let block_size = format.block_or_pixel_size_in_bytes() as u64;
+    let block_width = format.block_width_in_pixels();
+    let block_height = format.block_height_in_pixels();
+
+    let mut size_per_layer = 0;
+    for mip_level in 0..texture_def.mip_count {
+        let width = (texture_def.extents.width >> mip_level).max(1);
+        let height = (texture_def.extents.height >> mip_level).max(1);
+        let depth = (texture_def.extents.depth >> mip_level).max(1);
+        let width_in_blocks = width.div_ceil(block_width) as u64;
+        let height_in_blocks = height.div_ceil(block_height) as u64;
+        size_per_layer += width_in_blocks * height_in_blocks * depth as u64 * block_size;
+    }
+
+    size_per_layer * texture_def.array_length as u64
+}
+
+pub(crate) fn validate_push_constant_data(
+    push_constant_name: Option<&str>,
+    push_constant_size_in_bytes: u32,
+    data: &[u8],
+) -> RafxResult<()> {
+    if !data.len().is_multiple_of(4) {
+        Err(format!(
+            "Push constant data for {:?} is {} bytes, but must be a multiple of 4 bytes",
+            push_constant_name,
+            data.len()
+        ))?;
+    }
+
+    if data.len() > push_constant_size_in_bytes as usize {
+        Err(format!(
+            "Push constant data for {:?} is {} bytes, but the push constant is only {} bytes",
+            push_constant_name,
+            data.len(),
+            push_constant_size_in_bytes
+        ))?;
+    }
+
+    Ok(())
+}
@@ -6,3 +6,26 @@ pub mod gl_window;
 
 mod misc;
 pub(crate) use misc::*;
+
+#[cfg(any(
+    feature = "rafx-vulkan",
+    feature = "rafx-empty",
+    not(any(
+        feature = "rafx-metal",
+        feature = "rafx-vulkan",
+        feature = "rafx-gles2",
+        feature = "rafx-gles3"
+    ))
+))]
+mod pipeline_cache_data;
+#[cfg(any(
+    feature = "rafx-vulkan",
+    feature = "rafx-empty",
+    not(any(
+        feature = "rafx-metal",
+        feature = "rafx-vulkan",
+        feature = "rafx-gles2",
+        feature = "rafx-gles3"
+    ))
+))]
+pub(crate) use pipeline_cache_data::*;
@@ -0,0 +1,115 @@
+use std::convert::TryInto;
+
+// Pipeline cache data returned by RafxDeviceContext::pipeline_cache_data() starts with this header.
+// Drivers are expected to reject pipeline cache data they did not produce, but some crash or
+// return garbage instead, so we never pass data to the driver unless the header matches the
+// device and driver exactly.
+//
+// Layout (little endian):
+// - magic: [u8; 8]
+// - version: u32
+// - backend: u32
+// - vendor_id: u32
+// - device_id: u32
+// - driver_version: u32
+// - pipeline_cache_uuid: [u8; 16]
+// - payload_size: u64
+// - payload: [u8; payload_size]
+const PIPELINE_CACHE_DATA_MAGIC: [u8; 8] = *b"RAFXPIPE";
+const PIPELINE_CACHE_DATA_VERSION: u32 = 1;
+const PIPELINE_CACHE_DATA_HEADER_SIZE: usize = 8 + 4 * 5 + 16 + 8;
+
+/// Identifies the backend, device, and driver that produced pipeline cache data
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct PipelineCacheDataIdentity {
+    pub backend: u32,
+    pub vendor_id: u32,
+    pub device_id: u32,
+    pub driver_version: u32,
+    pub pipeline_cache_uuid: [u8; 16],
+}
+
+// Values for PipelineCacheDataIdentity::backend. These are persisted, so never reuse a value
+#[cfg(feature = "rafx-vulkan")]
+pub(crate) const PIPELINE_CACHE_DATA_BACKEND_VULKAN: u32 = 1;
+#[cfg(any(
+    feature = "rafx-empty",
+    not(any(
+        feature = "rafx-metal",
+        feature = "rafx-vulkan",
+        feature = "rafx-gles2",
+        feature = "rafx-gles3"
+    ))
+))]
+pub(crate) const PIPELINE_CACHE_DATA_BACKEND_EMPTY: u32 = 2;
+
+pub(crate) fn encode_pipeline_cache_data(
+    identity: &PipelineCacheDataIdentity,
+    payload: &[u8],
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(PIPELINE_CACHE_DATA_HEADER_SIZE + payload.len());
+    data.extend_from_slice(&PIPELINE_CACHE_DATA_MAGIC);
+    data.extend_from_slice(&PIPELINE_CACHE_DATA_VERSION.to_le_bytes());
+    data.extend_from_slice(&identity.backend.to_le_bytes());
+    data.extend_from_slice(&identity.vendor_id.to_le_bytes());
+    data.extend_from_slice(&identity.device_id.to_le_bytes());
+    data.extend_from_slice(&identity.driver_version.to_le_bytes());
+    data.extend_from_slice(&identity.pipeline_cache_uuid);
+    data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+// Returns the payload if the data is well-formed and was produced by the given backend, device,
+// and driver. Otherwise logs the reason and returns None.
+pub(crate) fn decode_pipeline_cache_data<'a>(
+    identity: &PipelineCacheDataIdentity,
+    data: &'a [u8],
+) -> Option<&'a [u8]> {
+    if data.len() < PIPELINE_CACHE_DATA_HEADER_SIZE || data[0..8] != PIPELINE_CACHE_DATA_MAGIC {
+        log::info!("Ignoring pipeline cache data, it is not in the expected format");
+        return None;
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let version = read_u32(8);
+    if version != PIPELINE_CACHE_DATA_VERSION {
+        log::info!(
+            "Ignoring pipeline cache data, version is {} but expected {}",
+            version,
+            PIPELINE_CACHE_DATA_VERSION
+        );
+        return None;
+    }
+
+    let data_identity = PipelineCacheDataIdentity {
+        backend: read_u32(12),
+        vendor_id: read_u32(16),
+        device_id: read_u32(20),
+        driver_version: read_u32(24),
+        pipeline_cache_uuid: data[28..44].try_into().unwrap(),
+    };
+
+    if data_identity != *identity {
+        log::info!(
+            "Ignoring pipeline cache data, it was produced by a different backend, device, or driver. Data: {:?} Current: {:?}",
+            data_identity,
+            identity
+        );
+        return None;
+    }
+
+    let payload_size = u64::from_le_bytes(data[44..52].try_into().unwrap());
+    let payload = &data[PIPELINE_CACHE_DATA_HEADER_SIZE..];
+    if payload.len() as u64 != payload_size {
+        log::info!(
+            "Ignoring pipeline cache data, payload is {} bytes but expected {}",
+            payload.len(),
+            payload_size
+        );
+        return None;
+    }
+
+    Some(payload)
+}
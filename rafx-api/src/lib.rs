@@ -49,6 +49,14 @@
 //! let api = RafxApi::new_vulkan(...);
 //! ```
 //!
+//! A device that is not associated with a window can be created with `RafxApi::new_headless()`.
+//! This is useful for offline rendering, compute-only tools, and tests. Swapchains are not
+//! available on headless devices, so render to a `RafxTexture` instead.
+//!
+//! Enabling the `rafx-empty` feature adds a CPU reference backend (`RafxApi::new_empty()`). It does
+//! not require a GPU: copies are executed in host memory and all other commands are recorded so
+//! that they can be inspected by tests.
+//!
 //! After initialization, most interaction will be via `RafxDeviceContext` Call
 //! `RafxApi::device_context()` on the the api object to obtain a cloneable handle that can be
 //! used from multiple threads.
@@ -260,6 +268,16 @@ pub use backends::gles3::RafxApiDefGles3;
     ))
 ))]
 pub use backends::empty;
+#[cfg(any(
+    feature = "rafx-empty",
+    not(any(
+        feature = "rafx-metal",
+        feature = "rafx-vulkan",
+        feature = "rafx-gles2",
+        feature = "rafx-gles3"
+    ))
+))]
+pub use backends::empty::RafxApiDefEmpty;
 
 //
 // Public modules
@@ -286,6 +304,7 @@ mod descriptor_set_array;
 mod device_context;
 mod fence;
 mod pipeline;
+mod query_pool;
 mod queue;
 mod root_signature;
 mod sampler;
@@ -306,6 +325,10 @@ pub const MAX_DESCRIPTOR_SET_LAYOUTS: usize = 4;
 pub const MAX_RENDER_TARGET_ATTACHMENTS: usize = 8;
 // Vulkan guarantees up to 16
 pub const MAX_VERTEX_INPUT_BINDINGS: usize = 16;
+/// The metal buffer index that push constants are bound to. Argument buffers for descriptor sets
+/// use the indices before it and vertex buffers are bound counting down from 30. (Rafx shader
+/// processor remaps push constants to this index when producing MSL)
+pub const METAL_PUSH_CONSTANT_BUFFER_INDEX: u32 = MAX_DESCRIPTOR_SET_LAYOUTS as u32;
 
 //
 // Exported public API
@@ -320,6 +343,7 @@ pub use error::*;
 pub use extra::swapchain_helper::*;
 pub use fence::*;
 pub use pipeline::*;
+pub use query_pool::*;
 pub use queue::*;
 pub use root_signature::*;
 pub use sampler::*;
@@ -16,7 +16,7 @@ use crate::gles3::RafxPipelineGles3;
 use crate::metal::RafxPipelineMetal;
 #[cfg(feature = "rafx-vulkan")]
 use crate::vulkan::RafxPipelineVulkan;
-use crate::{RafxPipelineType, RafxRootSignature};
+use crate::{RafxPipelineType, RafxResult, RafxRootSignature};
 
 /// Represents a complete GPU configuration for executing work.
 ///
@@ -78,6 +78,34 @@ impl RafxPipeline {
         }
     }
 
+    /// Assign a name to the pipeline that is visible in graphics debuggers and validation messages.
+    /// This is a no-op if the backend or driver does not support naming objects.
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxPipeline::Vk(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxPipeline::Metal(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxPipeline::Gles2(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxPipeline::Gles3(inner) => inner.set_debug_name(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxPipeline::Empty(inner) => inner.set_debug_name(name),
+        }
+    }
+
     /// Returns the root signature used to create the pipeline
     pub fn root_signature(&self) -> &RafxRootSignature {
         match self {
@@ -104,6 +104,32 @@ impl RafxQueue {
         }
     }
 
+    /// Returns the family this queue belongs to. Resources used on queues of different families
+    /// must have their ownership transferred between the queues with RafxBarrierQueueTransition.
+    /// Backends other than vulkan have a single queue family.
+    pub fn queue_family_index(&self) -> u32 {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxQueue::Vk(inner) => inner.queue_family_index(),
+            #[cfg(feature = "rafx-metal")]
+            RafxQueue::Metal(inner) => inner.queue_family_index(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxQueue::Gles2(inner) => inner.queue_family_index(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxQueue::Gles3(inner) => inner.queue_family_index(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxQueue::Empty(inner) => inner.queue_family_index(),
+        }
+    }
+
     /// Get the type of queue that this is
     pub fn queue_type(&self) -> RafxQueueType {
         match self {
@@ -128,6 +128,26 @@ impl RafxShaderResource {
                     self.set_index, self.binding, self.name,
                 ))?;
             }
+
+            if self.resource_type.is_bindless() {
+                let base_type = self.resource_type - RafxResourceType::BINDLESS;
+                if base_type != RafxResourceType::TEXTURE
+                    && base_type != RafxResourceType::BUFFER
+                    && base_type != RafxResourceType::BUFFER_READ_WRITE
+                {
+                    Err(format!(
+                        "binding (set={:?} binding={:?} name={:?} type={:?}) is bindless but only TEXTURE, BUFFER, and BUFFER_READ_WRITE may be bindless",
+                        self.set_index, self.binding, self.name, self.resource_type
+                    ))?;
+                }
+
+                if self.element_count == 0 {
+                    Err(format!(
+                        "binding (set={:?} binding={:?} name={:?} type={:?}) is bindless but has zero element_count",
+                        self.set_index, self.binding, self.name, self.resource_type
+                    ))?;
+                }
+            }
         }
 
         Ok(())
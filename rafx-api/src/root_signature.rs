@@ -16,7 +16,7 @@ use crate::gles3::RafxRootSignatureGles3;
 use crate::metal::RafxRootSignatureMetal;
 #[cfg(feature = "rafx-vulkan")]
 use crate::vulkan::RafxRootSignatureVulkan;
-use crate::RafxPipelineType;
+use crate::{RafxPipelineType, RafxPushConstantIndex};
 
 /// Represents the full "layout" or "interface" of a shader (or set of shaders.)
 ///
@@ -69,6 +69,34 @@ impl RafxRootSignature {
         }
     }
 
+    /// Find the push constant with the given name. The returned index can be passed to
+    /// `RafxCommandBuffer::cmd_push_constants`.
+    pub fn find_push_constant_by_name(
+        &self,
+        name: &str,
+    ) -> Option<RafxPushConstantIndex> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxRootSignature::Vk(inner) => inner.find_push_constant_by_name(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxRootSignature::Metal(inner) => inner.find_push_constant_by_name(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxRootSignature::Gles2(inner) => inner.find_push_constant_by_name(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxRootSignature::Gles3(inner) => inner.find_push_constant_by_name(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxRootSignature::Empty(inner) => inner.find_push_constant_by_name(name),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
@@ -169,8 +169,16 @@ impl RafxShader {
         }
     }
 
-    #[cfg(feature = "rafx-metal")]
-    pub fn empty_shader(&self) -> Option<&RafxShaderMetal> {
+    #[cfg(any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    ))]
+    pub fn empty_shader(&self) -> Option<&RafxShaderEmpty> {
         match self {
             #[cfg(feature = "rafx-vulkan")]
             RafxShader::Vk(_) => None,
@@ -16,7 +16,7 @@ use crate::gles3::RafxTextureGles3;
 use crate::metal::RafxTextureMetal;
 #[cfg(feature = "rafx-vulkan")]
 use crate::vulkan::RafxTextureVulkan;
-use crate::RafxTextureDef;
+use crate::{RafxResult, RafxTextureDef};
 
 /// An image that can be used by the GPU.
 ///
@@ -68,6 +68,60 @@ impl RafxTexture {
         }
     }
 
+    /// The number of bytes of memory backing the texture, including all mips and array layers. This
+    /// is 0 for textures that do not own their memory (like swapchain images). Some backends only
+    /// estimate this.
+    pub fn allocation_size_in_bytes(&self) -> u64 {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxTexture::Vk(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-metal")]
+            RafxTexture::Metal(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-gles2")]
+            RafxTexture::Gles2(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(feature = "rafx-gles3")]
+            RafxTexture::Gles3(inner) => inner.allocation_size_in_bytes(),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxTexture::Empty(inner) => inner.allocation_size_in_bytes(),
+        }
+    }
+
+    /// Assign a name to the texture that is visible in graphics debuggers and validation messages.
+    /// This is a no-op if the backend or driver does not support naming objects.
+    pub fn set_debug_name(
+        &self,
+        name: impl AsRef<str>,
+    ) -> RafxResult<()> {
+        match self {
+            #[cfg(feature = "rafx-vulkan")]
+            RafxTexture::Vk(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-metal")]
+            RafxTexture::Metal(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles2")]
+            RafxTexture::Gles2(inner) => inner.set_debug_name(name),
+            #[cfg(feature = "rafx-gles3")]
+            RafxTexture::Gles3(inner) => inner.set_debug_name(name),
+            #[cfg(any(
+                feature = "rafx-empty",
+                not(any(
+                    feature = "rafx-metal",
+                    feature = "rafx-vulkan",
+                    feature = "rafx-gles2",
+                    feature = "rafx-gles3"
+                ))
+            ))]
+            RafxTexture::Empty(inner) => inner.set_debug_name(name),
+        }
+    }
+
     /// Get the underlying vulkan API object. This provides access to any internally created
     /// vulkan objects.
     #[cfg(feature = "rafx-vulkan")]
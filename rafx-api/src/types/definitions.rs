@@ -211,6 +211,17 @@ impl RafxTextureDef {
                     .intersects(RafxResourceType::TEXTURE_READ_WRITE)),
             "Cannot use depth stencil as UAV"
         );
+
+        if self
+            .resource_type
+            .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+        {
+            // Input attachments are read within the renderpass, so they may still be transient
+            assert!(
+                self.resource_type.is_render_target() && !self.resource_type.is_texture(),
+                "Transient attachments may only be used as render targets or input attachments"
+            );
+        }
     }
 }
 
@@ -229,6 +240,13 @@ pub struct RafxCommandBufferDef {
     pub is_secondary: bool,
 }
 
+/// Used to create a `RafxQueryPool`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RafxQueryPoolDef {
+    pub query_type: RafxQueryType,
+    pub query_count: u32,
+}
+
 /// Used to create a `RafxSwapchain`
 #[derive(Clone, Debug)]
 pub struct RafxSwapchainDef {
@@ -641,6 +659,42 @@ impl RafxBlendState {
     }
 }
 
+/// An attachment of the renderpass that a subpass reads as an input attachment
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+pub enum RafxSubpassInputAttachment {
+    /// Index into the color targets passed when beginning the renderpass
+    Color(u32),
+    /// The depth/stencil target of the renderpass
+    DepthStencil,
+}
+
+/// Describes one subpass of a renderpass started with `cmd_begin_render_pass_with_subpasses`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+pub struct RafxSubpassDef {
+    /// Indices into the color targets of the renderpass. The position within this list is the
+    /// output location in the shader
+    pub color_attachments: Vec<u32>,
+    /// Attachments read by the subpass. The position within this list is the
+    /// input_attachment_index in the shader
+    pub input_attachments: Vec<RafxSubpassInputAttachment>,
+    /// Bind the depth/stencil target of the renderpass. If the subpass also reads it as an input
+    /// attachment, it is bound read-only
+    pub use_depth_stencil_attachment: bool,
+}
+
+/// Identifies the subpass a graphics pipeline will be used in. Pipelines created without a subpass
+/// layout can only be used in renderpasses that have a single subpass
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
+pub struct RafxSubpassLayout {
+    /// Formats of all color targets of the renderpass (not just the ones written by the subpass)
+    pub render_pass_color_formats: Vec<RafxFormat>,
+    pub subpasses: Vec<RafxSubpassDef>,
+    pub subpass_index: u32,
+}
+
 /// Used to create a `RafxPipeline` for graphics operations
 #[derive(Debug)]
 pub struct RafxGraphicsPipelineDef<'a> {
@@ -654,6 +708,9 @@ pub struct RafxGraphicsPipelineDef<'a> {
     pub color_formats: &'a [RafxFormat],
     pub depth_stencil_format: Option<RafxFormat>,
     pub sample_count: RafxSampleCount,
+    /// Required if the pipeline is used in a renderpass with multiple subpasses. color_formats
+    /// must then list the formats of the color attachments written by the subpass
+    pub subpass_layout: Option<&'a RafxSubpassLayout>,
     //indirect_commands_enable: bool
 }
 
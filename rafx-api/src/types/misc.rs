@@ -35,6 +35,7 @@ impl Default for RafxValidationMode {
 
 /// Information about the device, mostly limits, requirements (like memory alignment), and flags to
 /// indicate whether certain features are supported
+#[derive(Clone)]
 pub struct RafxDeviceInfo {
     pub supports_multithreaded_usage: bool,
 
@@ -47,6 +48,40 @@ pub struct RafxDeviceInfo {
     pub supports_clamp_to_border_color: bool,
 
     pub max_vertex_attribute_count: u32,
+
+    // Number of nanoseconds per tick of a timestamp query result
+    pub timestamp_period: f32,
+    pub supports_timestamp_queries: bool,
+    pub supports_occlusion_queries: bool,
+    pub supports_pipeline_statistics_queries: bool,
+
+    // cmd_draw_indirect/cmd_draw_indexed_indirect with a draw_count of 1
+    pub supports_draw_indirect: bool,
+    // cmd_draw_indirect/cmd_draw_indexed_indirect with a draw_count greater than 1
+    pub supports_multi_draw_indirect: bool,
+    // cmd_draw_indirect_count/cmd_draw_indexed_indirect_count
+    pub supports_draw_indirect_count: bool,
+    pub supports_dispatch_indirect: bool,
+    // Textures with RafxResourceType::TRANSIENT_ATTACHMENT may avoid allocating memory
+    pub supports_transient_attachments: bool,
+    // create_aliased_textures()/create_aliased_buffers() place all resources in a single
+    // allocation. Otherwise each resource gets its own memory
+    pub supports_resource_aliasing: bool,
+    // cmd_begin_render_pass_with_subpasses/cmd_next_subpass and RafxResourceType::INPUT_ATTACHMENT.
+    // Not supported on metal or GL ES, so the render graph doesn't merge nodes into subpasses there
+    // and runs every node in its own renderpass.
+    pub supports_subpasses: bool,
+    // begin_secondary/cmd_execute_secondary_command_buffers and RafxSubpassContents::SecondaryCommandBuffers
+    pub supports_secondary_command_buffers: bool,
+    // cmd_blit_image
+    pub supports_blit_images: bool,
+    // pipeline_cache_data/merge_pipeline_cache_data persist compiled pipelines across runs
+    pub supports_pipeline_cache_data: bool,
+    // Descriptors with RafxResourceType::BINDLESS. (VK_EXT_descriptor_indexing on vulkan, tier 2
+    // argument buffers on metal)
+    pub supports_bindless_descriptors: bool,
+    // Largest element_count allowed for a single RafxResourceType::BINDLESS descriptor
+    pub max_bindless_descriptor_count: u32,
     //max_vertex_input_binding_count: u32,
     // max_root_signature_dwords: u32,
     // wave_lane_count: u32,
@@ -72,6 +107,103 @@ pub enum RafxQueueType {
     Transfer,
 }
 
+/// The kind of data collected by a `RafxQueryPool`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RafxQueryType {
+    /// Each query stores a single u64 GPU timestamp. Multiply the difference between two
+    /// timestamps by `RafxDeviceInfo::timestamp_period` to get nanoseconds.
+    Timestamp,
+
+    /// Each query stores a single u64 count of samples that passed depth/stencil testing
+    Occlusion,
+
+    /// Each query stores `RAFX_PIPELINE_STATISTICS_VALUE_COUNT` u64 values, see
+    /// `RafxPipelineStatistics`
+    PipelineStatistics,
+}
+
+impl RafxQueryType {
+    /// The number of u64 values written per query of this type
+    pub fn values_per_query(self) -> u32 {
+        match self {
+            RafxQueryType::Timestamp => 1,
+            RafxQueryType::Occlusion => 1,
+            RafxQueryType::PipelineStatistics => RAFX_PIPELINE_STATISTICS_VALUE_COUNT,
+        }
+    }
+}
+
+pub const RAFX_PIPELINE_STATISTICS_VALUE_COUNT: u32 = 7;
+
+/// The values collected by a `RafxQueryType::PipelineStatistics` query, in the order they are
+/// written by `get_results()`/`cmd_resolve_queries()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct RafxPipelineStatistics {
+    pub input_assembly_vertices: u64,
+    pub input_assembly_primitives: u64,
+    pub vertex_shader_invocations: u64,
+    pub clipping_invocations: u64,
+    pub clipping_primitives: u64,
+    pub fragment_shader_invocations: u64,
+    pub compute_shader_invocations: u64,
+}
+
+impl RafxPipelineStatistics {
+    pub fn from_results(values: &[u64]) -> Self {
+        assert_eq!(values.len(), RAFX_PIPELINE_STATISTICS_VALUE_COUNT as usize);
+        RafxPipelineStatistics {
+            input_assembly_vertices: values[0],
+            input_assembly_primitives: values[1],
+            vertex_shader_invocations: values[2],
+            clipping_invocations: values[3],
+            clipping_primitives: values[4],
+            fragment_shader_invocations: values[5],
+            compute_shader_invocations: values[6],
+        }
+    }
+}
+
+/// Memory usage and budget of a single memory heap, see `RafxMemoryBudget`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct RafxMemoryHeapBudget {
+    /// True if the heap is local to the GPU (i.e. VRAM on a discrete GPU)
+    pub is_device_local: bool,
+    /// Total size of the heap
+    pub heap_size_in_bytes: u64,
+    /// How much memory this process can allocate from the heap before allocations may fail or
+    /// performance may suffer
+    pub budget_in_bytes: u64,
+    /// How much memory this process has currently allocated from the heap
+    pub usage_in_bytes: u64,
+}
+
+/// Memory usage and budget of the device, returned by `RafxDeviceContext::memory_budget()`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RafxMemoryBudget {
+    pub heaps: Vec<RafxMemoryHeapBudget>,
+    /// False if the driver does not report a budget (for example, VK_EXT_memory_budget is not
+    /// supported) and it was estimated from the heap size instead
+    pub is_budget_reported_by_driver: bool,
+}
+
+impl RafxMemoryBudget {
+    pub fn device_local_budget_in_bytes(&self) -> u64 {
+        self.heaps
+            .iter()
+            .filter(|heap| heap.is_device_local)
+            .map(|heap| heap.budget_in_bytes)
+            .sum()
+    }
+
+    pub fn device_local_usage_in_bytes(&self) -> u64 {
+        self.heaps
+            .iter()
+            .filter(|heap| heap.is_device_local)
+            .map(|heap| heap.usage_in_bytes)
+            .sum()
+    }
+}
+
 /// The color space an image data is in. The correct color space often varies between texture types
 /// (like normal maps vs. albedo maps).
 #[derive(Copy, Clone, Debug)]
@@ -213,6 +345,16 @@ bitflags::bitflags! {
         const RENDER_TARGET_COLOR = 1<<23;
         /// A depth/stencil attachment in a renderpass
         const RENDER_TARGET_DEPTH_STENCIL = 1<<24;
+        /// A render target whose contents never leave the renderpass it is used in. It may only be
+        /// used as an attachment, and must be cleared or not loaded and must not be stored. Similar
+        /// to vulkan's TRANSIENT_ATTACHMENT usage flag and metal's memoryless storage mode
+        const TRANSIENT_ATTACHMENT = 1<<25;
+        /// Modifies a TEXTURE, BUFFER, or BUFFER_READ_WRITE descriptor to be a large array that
+        /// does not need to be fully written and may be written after it is bound, as long as the
+        /// written elements are not in use by the GPU. Similar to vulkan's PARTIALLY_BOUND and
+        /// UPDATE_AFTER_BIND descriptor binding flags. Requires
+        /// RafxDeviceInfo::supports_bindless_descriptors
+        const BINDLESS = 1<<26;
     }
 }
 
@@ -234,6 +376,10 @@ impl RafxResourceType {
     pub fn is_texture(self) -> bool {
         self.intersects(RafxResourceType::TEXTURE | RafxResourceType::TEXTURE_READ_WRITE)
     }
+
+    pub fn is_bindless(self) -> bool {
+        self.intersects(RafxResourceType::BINDLESS)
+    }
 }
 
 bitflags::bitflags! {
@@ -357,6 +503,7 @@ pub enum RafxPipelineType {
 
 /// Affects how quickly vertex attributes are consumed from buffers, similar to VkVertexInputRate
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
 pub enum RafxVertexAttributeRate {
     Vertex,
     Instance,
@@ -400,6 +547,18 @@ impl Default for RafxStoreOp {
     }
 }
 
+/// How the commands of each subpass of a render pass are recorded
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Default)]
+pub enum RafxSubpassContents {
+    /// Commands are recorded directly into the command buffer that began the render pass
+    #[default]
+    Inline,
+
+    /// Commands are recorded into secondary command buffers (started with `begin_secondary`) and
+    /// run with `cmd_execute_secondary_command_buffers`
+    SecondaryCommandBuffers,
+}
+
 /// How to intepret vertex data into a form of geometry. Similar to VkPrimitiveTopology
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
@@ -425,6 +584,39 @@ impl Default for RafxIndexType {
     }
 }
 
+/// The layout of a single draw read by `cmd_draw_indirect`. Matches VkDrawIndirectCommand and
+/// MTLDrawPrimitivesIndirectArguments
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RafxDrawIndirectCommand {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub first_vertex: u32,
+    pub first_instance: u32,
+}
+
+/// The layout of a single draw read by `cmd_draw_indexed_indirect`. Matches
+/// VkDrawIndexedIndirectCommand and MTLDrawIndexedPrimitivesIndirectArguments
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RafxDrawIndexedIndirectCommand {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    pub first_instance: u32,
+}
+
+/// The layout of the arguments read by `cmd_dispatch_indirect`. Matches VkDispatchIndirectCommand
+/// and MTLDispatchThreadgroupsIndirectArguments
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RafxDispatchIndirectCommand {
+    pub group_count_x: u32,
+    pub group_count_y: u32,
+    pub group_count_z: u32,
+}
+
 /// Affects blending. Similar to VkBlendFactor
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde-support", derive(Serialize, Deserialize))]
@@ -760,6 +952,15 @@ pub struct RafxCmdCopyBufferToTextureParams {
     pub mip_level: u8,
 }
 
+/// Parameters for copying a texture to a buffer. Rows are written with a pitch of the row size
+/// rounded up to `RafxDeviceInfo::upload_buffer_texture_row_alignment`.
+#[derive(Default)]
+pub struct RafxCmdCopyTextureToBufferParams {
+    pub buffer_offset: u64,
+    pub array_layer: u16,
+    pub mip_level: u8,
+}
+
 /// Parameters for blitting one image to another (vulkan backend only)
 pub struct RafxCmdBlitParams {
     pub src_state: RafxResourceState,
@@ -777,6 +978,12 @@ pub struct RafxCmdBlitParams {
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct RafxDescriptorIndex(pub(crate) u32);
 
+/// A rafx-specific index that refers to a particular push constant range in a root signature.
+/// Query it by name during startup with `RafxRootSignature::find_push_constant_by_name` and pass
+/// it to `RafxCommandBuffer::cmd_push_constants`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RafxPushConstantIndex(pub(crate) u32);
+
 /// Selects a particular descriptor in a descriptor set
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum RafxDescriptorKey<'a> {
@@ -73,6 +73,10 @@ impl AssetManager {
         transfer_queue: &RafxQueue,
     ) -> Self {
         let resource_manager = ResourceManager::new(device_context, render_registry);
+        let material_instance_descriptor_sets = DescriptorSetAllocator::new(
+            device_context,
+            resource_manager.resources().memory_tracker(),
+        );
 
         AssetManager {
             device_context: device_context.clone(),
@@ -83,7 +87,7 @@ impl AssetManager {
                 graphics_queue.clone(),
                 transfer_queue.clone(),
             ),
-            material_instance_descriptor_sets: DescriptorSetAllocator::new(device_context),
+            material_instance_descriptor_sets,
             graphics_queue: graphics_queue.clone(),
             transfer_queue: transfer_queue.clone(),
 
@@ -17,6 +17,10 @@ pub struct PhysicalBufferId(pub(super) usize);
 #[derive(Debug, Copy, Clone)]
 pub struct RenderGraphOutputBufferId(pub(super) usize);
 
+/// Unique ID provided for any buffer passed into the graph from outside as an external buffer
+#[derive(Debug, Copy, Clone)]
+pub struct RenderGraphExternalBufferId(pub(super) usize);
+
 /// Unique ID for a particular version of a buffer. Any time a buffer is modified, a new version is
 /// produced
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -49,6 +53,7 @@ impl RenderGraphBufferResource {
 pub enum RenderGraphBufferUser {
     Node(RenderGraphNodeId),
     Output(RenderGraphOutputBufferId),
+    Input(RenderGraphExternalBufferId),
 }
 
 /// A usage of a particular buffer
@@ -201,7 +206,7 @@ impl RenderGraphBufferConstraint {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum RenderGraphBufferUsageType {
     Create,
-    //Input,
+    Input,
     Read,
     ModifyRead,
     ModifyWrite,
@@ -216,7 +221,7 @@ impl RenderGraphBufferUsageType {
             RenderGraphBufferUsageType::Output => true,
             RenderGraphBufferUsageType::ModifyRead => false,
             RenderGraphBufferUsageType::Create => false,
-            // RenderGraphBufferUsageType::Input => false,
+            RenderGraphBufferUsageType::Input => false,
             RenderGraphBufferUsageType::ModifyWrite => false,
         }
     }
@@ -225,9 +230,9 @@ impl RenderGraphBufferUsageType {
 /// Information about a specific version of the buffer.
 #[derive(Debug)]
 pub struct RenderGraphBufferResourceVersionInfo {
-    /// What node created the buffer (keep in mind these are virtual buffers, not buffers provided
-    /// from outside the graph. So every buffer will have a creator node)
-    pub(super) creator_node: RenderGraphNodeId,
+    /// What node created the buffer. This is None if the version is an external buffer provided
+    /// from outside the graph, which is not produced by any node
+    pub(super) creator_node: Option<RenderGraphNodeId>,
 
     pub(super) create_usage: RenderGraphBufferUsageId,
     pub(super) read_usages: Vec<RenderGraphBufferUsageId>,
@@ -235,7 +240,7 @@ pub struct RenderGraphBufferResourceVersionInfo {
 
 impl RenderGraphBufferResourceVersionInfo {
     pub(super) fn new(
-        creator: RenderGraphNodeId,
+        creator: Option<RenderGraphNodeId>,
         create_usage: RenderGraphBufferUsageId,
     ) -> Self {
         RenderGraphBufferResourceVersionInfo {
@@ -245,6 +250,16 @@ impl RenderGraphBufferResourceVersionInfo {
         }
     }
 
+    // for redirect_buffer_usage
+    pub(super) fn remove_read_usage(
+        &mut self,
+        usage: RenderGraphBufferUsageId,
+    ) {
+        if let Some(position) = self.read_usages.iter().position(|x| *x == usage) {
+            self.read_usages.swap_remove(position);
+        }
+    }
+
     pub(super) fn add_read_usage(
         &mut self,
         usage: RenderGraphBufferUsageId,
@@ -4,22 +4,33 @@ use crate::resources::{ImageViewResource, ResourceArc};
 use crate::BufferResource;
 use fnv::{FnvHashMap, FnvHashSet};
 use rafx_api::{
-    RafxColorClearValue, RafxDepthStencilClearValue, RafxResourceState, RafxResourceType,
-    RafxResult,
+    RafxColorClearValue, RafxDepthStencilClearValue, RafxDeviceInfo, RafxResourceState,
+    RafxResourceType, RafxResult,
 };
 
-#[derive(Copy, Clone)]
+/// The queue a node is scheduled on. Nodes on queues other than DefaultGraphics are recorded into
+/// separate submissions that may run in parallel with graphics work. The `RafxQueue` for each
+/// queue is provided with `PreparedRenderGraph::set_queue()`. If no queue was provided, the
+/// submission runs on the graphics queue instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RenderGraphQueue {
     DefaultGraphics,
+    AsyncCompute,
+    Transfer,
     Index(u32),
 }
 
-// /// An image that is being provided to the render graph that can be read from
-// #[derive(Debug)]
-// pub struct RenderGraphInputImage {
-//     pub usage: RenderGraphImageUsageId,
-//     pub specification: RenderGraphImageSpecification,
-// }
+/// An image that is being provided to the render graph that can be read from (and modified). The
+/// image is owned outside the graph, so it is never aliased with or reused for other resources
+#[derive(Debug)]
+pub struct RenderGraphExternalImage {
+    pub usage: RenderGraphImageUsageId,
+    pub specification: RenderGraphImageSpecification,
+    pub src_image: ResourceArc<ImageViewResource>,
+
+    pub(super) initial_state: RafxResourceState,
+    pub(super) final_state: RafxResourceState,
+}
 
 /// An image that is being provided to the render graph that can be written to
 #[derive(Debug)]
@@ -32,12 +43,17 @@ pub struct RenderGraphOutputImage {
     pub(super) final_state: RafxResourceState,
 }
 
-// /// A buffer that is being provided to the render graph that can be read from
-// #[derive(Debug)]
-// pub struct RenderGraphInputBuffer {
-//     pub usage: RenderGraphBufferUsageId,
-//     pub specification: RenderGraphBufferSpecification,
-// }
+/// A buffer that is being provided to the render graph that can be read from (and modified). The
+/// buffer is owned outside the graph, so it is never aliased with or reused for other resources
+#[derive(Debug)]
+pub struct RenderGraphExternalBuffer {
+    pub usage: RenderGraphBufferUsageId,
+    pub specification: RenderGraphBufferSpecification,
+    pub src_buffer: ResourceArc<BufferResource>,
+
+    pub(super) initial_state: RafxResourceState,
+    pub(super) final_state: RafxResourceState,
+}
 
 /// A buffer that is being provided to the render graph that can be written to
 #[derive(Debug)]
@@ -70,8 +86,9 @@ pub struct RenderGraphBuilder {
     pub(super) image_usages: Vec<RenderGraphImageUsage>,
     pub(super) buffer_usages: Vec<RenderGraphBufferUsage>,
 
-    /// Images that are passed into the graph that can be read from
-    //pub(super) input_images: Vec<RenderGraphInputImage>,
+    /// Images/buffers that are passed into the graph that can be read from
+    pub(super) external_images: Vec<RenderGraphExternalImage>,
+    pub(super) external_buffers: Vec<RenderGraphExternalBuffer>,
 
     /// Images that are passed into the graph to be written to.
     pub(super) output_images: Vec<RenderGraphOutputImage>,
@@ -84,6 +101,12 @@ pub struct RenderGraphBuilder {
         FnvHashMap<RenderGraphNodeId, RenderGraphNodeVisitNodeCallback>,
     pub(super) render_phase_dependencies:
         FnvHashMap<RenderGraphNodeId, FnvHashSet<RenderPhaseIndex>>,
+
+    /// If true, the plan will keep a RenderGraphExport describing the graph and how it was planned
+    pub(super) export_enabled: bool,
+
+    /// If true, the plan checks the graph for mistakes that don't prevent it from being planned
+    pub(super) validation_enabled: bool,
 }
 
 impl RenderGraphBuilder {
@@ -128,7 +151,7 @@ impl RenderGraphBuilder {
 
         let mut resource = RenderGraphImageResource::new();
 
-        let version_info = RenderGraphImageResourceVersionInfo::new(create_node, usage_id);
+        let version_info = RenderGraphImageResourceVersionInfo::new(Some(create_node), usage_id);
         resource.versions.push(version_info);
 
         // Add it to the graph
@@ -206,7 +229,8 @@ impl RenderGraphBuilder {
             view_options,
         );
 
-        let version_info = RenderGraphImageResourceVersionInfo::new(modify_node, write_usage_id);
+        let version_info =
+            RenderGraphImageResourceVersionInfo::new(Some(modify_node), write_usage_id);
         self.image_resources[read_version_id.index]
             .versions
             .push(version_info);
@@ -555,6 +579,39 @@ impl RenderGraphBuilder {
         usage
     }
 
+    /// Read an image written by an earlier node as a subpass input attachment. If the two nodes
+    /// are merged into a single renderpass, the image is read from tile memory at the current
+    /// pixel. Otherwise (for example if the nodes are not compatible or the device does not
+    /// support subpasses) all input attachments of the node are bound as sampled textures
+    /// instead, so shaders used on platforms without subpass support must sample them.
+    ///
+    /// Nodes are only merged when `RafxDeviceInfo::supports_subpasses` is true. Metal and GL ES
+    /// don't support subpasses yet, so on those backends every node runs in its own renderpass,
+    /// input attachments are always sampled and tile-based GPUs using Metal don't benefit from
+    /// merging.
+    pub fn read_input_attachment(
+        &mut self,
+        node: RenderGraphNodeId,
+        image: RenderGraphImageUsageId,
+        input_attachment_index: usize,
+        mut constraint: RenderGraphImageConstraint,
+        view_options: RenderGraphImageViewOptions,
+    ) -> RenderGraphImageUsageId {
+        constraint.resource_type |= RafxResourceType::INPUT_ATTACHMENT;
+
+        // Add the read to the graph
+        let usage = self.add_image_read(node, image, constraint, view_options);
+
+        let node_input_attachments = &mut self.node_mut(node).input_attachments;
+        if node_input_attachments.len() <= input_attachment_index {
+            node_input_attachments.resize_with(input_attachment_index + 1, || None);
+        }
+
+        assert!(node_input_attachments[input_attachment_index].is_none());
+        node_input_attachments[input_attachment_index] = Some(usage);
+        usage
+    }
+
     pub fn set_output_image(
         &mut self,
         image_id: RenderGraphImageUsageId,
@@ -588,6 +645,51 @@ impl RenderGraphBuilder {
         output_image_id
     }
 
+    /// Pass an image owned outside the graph into it (for example, the previous frame's color
+    /// for TAA or a shadow atlas that persists across frames.) The image is expected to be in
+    /// `initial_state` when the graph begins executing and will be transitioned to `final_state`
+    /// after the last pass that uses it. The returned usage can be read or modified by nodes like
+    /// any other image. Modifications are always stored, even if nothing in the graph reads them.
+    pub fn read_external_image(
+        &mut self,
+        src_image: ResourceArc<ImageViewResource>,
+        specification: RenderGraphImageSpecification,
+        view_options: RenderGraphImageViewOptions,
+        initial_state: RafxResourceState,
+        final_state: RafxResourceState,
+    ) -> RenderGraphImageUsageId {
+        let external_image_id = RenderGraphExternalImageId(self.external_images.len());
+
+        let version_id = RenderGraphImageVersionId {
+            index: self.image_resources.len(),
+            version: 0,
+        };
+        let usage_id = self.add_image_usage(
+            RenderGraphImageUser::Input(external_image_id),
+            version_id,
+            RenderGraphImageUsageType::Input,
+            view_options,
+        );
+
+        let mut resource = RenderGraphImageResource::new();
+
+        // No node creates an external image
+        let version_info = RenderGraphImageResourceVersionInfo::new(None, usage_id);
+        resource.versions.push(version_info);
+        self.image_resources.push(resource);
+
+        let external_image = RenderGraphExternalImage {
+            usage: usage_id,
+            specification,
+            src_image,
+            initial_state,
+            final_state,
+        };
+
+        self.external_images.push(external_image);
+        usage_id
+    }
+
     //NOTE: While the buffer aspect flags may seem redundant with subresource_range here, the
     // subresource_range should indicate the buffer view's supported aspects and the provided
     // buffer aspect flags the aspects that are actually being used
@@ -624,7 +726,7 @@ impl RenderGraphBuilder {
 
         let mut resource = RenderGraphBufferResource::new();
 
-        let version_info = RenderGraphBufferResourceVersionInfo::new(create_node, usage_id);
+        let version_info = RenderGraphBufferResourceVersionInfo::new(Some(create_node), usage_id);
         resource.versions.push(version_info);
 
         // Add it to the graph
@@ -696,7 +798,8 @@ impl RenderGraphBuilder {
             RenderGraphBufferUsageType::ModifyWrite,
         );
 
-        let version_info = RenderGraphBufferResourceVersionInfo::new(modify_node, write_usage_id);
+        let version_info =
+            RenderGraphBufferResourceVersionInfo::new(Some(modify_node), write_usage_id);
         self.buffer_resources[read_version_id.index]
             .versions
             .push(version_info);
@@ -839,6 +942,52 @@ impl RenderGraphBuilder {
         output_buffer_id
     }
 
+    /// Pass a buffer owned outside the graph into it (for example, a buffer produced by compute
+    /// work outside the graph.) The buffer is expected to be in `initial_state` when the graph
+    /// begins executing and will be transitioned to `final_state` after the last pass that uses
+    /// it. The returned usage can be read or modified by nodes like any other buffer.
+    pub fn read_external_buffer(
+        &mut self,
+        src_buffer: ResourceArc<BufferResource>,
+        specification: RenderGraphBufferSpecification,
+        initial_state: RafxResourceState,
+        final_state: RafxResourceState,
+    ) -> RenderGraphBufferUsageId {
+        if specification.resource_type == RafxResourceType::UNDEFINED {
+            panic!("An external buffer with empty resource_type in the specification is almost certainly a mistake.");
+        }
+
+        let external_buffer_id = RenderGraphExternalBufferId(self.external_buffers.len());
+
+        let version_id = RenderGraphBufferVersionId {
+            index: self.buffer_resources.len(),
+            version: 0,
+        };
+        let usage_id = self.add_buffer_usage(
+            RenderGraphBufferUser::Input(external_buffer_id),
+            version_id,
+            RenderGraphBufferUsageType::Input,
+        );
+
+        let mut resource = RenderGraphBufferResource::new();
+
+        // No node creates an external buffer
+        let version_info = RenderGraphBufferResourceVersionInfo::new(None, usage_id);
+        resource.versions.push(version_info);
+        self.buffer_resources.push(resource);
+
+        let external_buffer = RenderGraphExternalBuffer {
+            usage: usage_id,
+            specification,
+            src_buffer,
+            initial_state,
+            final_state,
+        };
+
+        self.external_buffers.push(external_buffer);
+        usage_id
+    }
+
     // Add a node which can use resources
     pub fn add_node(
         &mut self,
@@ -920,6 +1069,9 @@ impl RenderGraphBuilder {
         assert!(old.is_none());
     }
 
+    /// Declares that the node writes `PhaseT`. A renderpass is recorded into secondary command
+    /// buffers (so that submit nodes can be written on multiple threads) only if one of its nodes
+    /// depends on a render phase that the write thread pool splits into chunks.
     pub fn add_render_phase_dependency<PhaseT: RenderPhase>(
         &mut self,
         node_id: RenderGraphNodeId,
@@ -977,6 +1129,13 @@ impl RenderGraphBuilder {
         &self.image_resources[version.index].versions[version.version]
     }
 
+    pub(super) fn image_subresource(
+        &self,
+        usage_id: RenderGraphImageUsageId,
+    ) -> RenderGraphImageSubresource {
+        RenderGraphImageSubresource::from_view_options(&self.image_usages[usage_id.0].view_options)
+    }
+
     pub(super) fn image_version_info_mut(
         &mut self,
         usage_id: RenderGraphImageUsageId,
@@ -1000,6 +1159,16 @@ impl RenderGraphBuilder {
         self.image_resources[version.index].versions[version.version].create_usage
     }
 
+    // External images are the only resources whose first version has no creator node
+    pub(super) fn is_external_image(
+        &self,
+        usage: RenderGraphImageUsageId,
+    ) -> bool {
+        self.image_resource(usage).versions[0]
+            .creator_node
+            .is_none()
+    }
+
     pub(super) fn redirect_image_usage(
         &mut self,
         usage: RenderGraphImageUsageId,
@@ -1008,6 +1177,7 @@ impl RenderGraphBuilder {
     ) {
         self.image_resources[from.index].versions[from.version].remove_read_usage(usage);
         self.image_resources[to.index].versions[to.version].add_read_usage(usage);
+        self.image_usages[usage.0].version = to;
     }
 
     //
@@ -1070,8 +1240,63 @@ impl RenderGraphBuilder {
         self.buffer_resources[version.index].versions[version.version].create_usage
     }
 
-    pub fn build_plan(self) -> RenderGraphPlan {
+    // External buffers are the only resources whose first version has no creator node
+    pub(super) fn is_external_buffer(
+        &self,
+        usage: RenderGraphBufferUsageId,
+    ) -> bool {
+        self.buffer_resource(usage).versions[0]
+            .creator_node
+            .is_none()
+    }
+
+    pub(super) fn redirect_buffer_usage(
+        &mut self,
+        usage: RenderGraphBufferUsageId,
+        from: RenderGraphBufferVersionId,
+        to: RenderGraphBufferVersionId,
+    ) {
+        self.buffer_resources[from.index].versions[from.version].remove_read_usage(usage);
+        self.buffer_resources[to.index].versions[to.version].add_read_usage(usage);
+        self.buffer_usages[usage.0].version = to;
+    }
+
+    /// Describe the nodes and resources added to the graph so far. This does not include the plan
+    /// (passes, physical resources, barriers, etc.), use `set_export_enabled()` to get an export
+    /// that includes it.
+    pub fn export(&self) -> RenderGraphExport {
+        RenderGraphExport::new(self)
+    }
+
+    /// If enabled, building the plan also produces a RenderGraphExport of the graph and its plan.
+    /// It can be retrieved with `RenderGraphPlan::export()` or `PreparedRenderGraph::export()`.
+    /// This is disabled by default as it adds work to every plan.
+    pub fn set_export_enabled(
+        &mut self,
+        export_enabled: bool,
+    ) {
+        self.export_enabled = export_enabled;
+    }
+
+    /// If enabled, building the plan also checks for nodes that modify the same image/buffer and
+    /// for writes that nothing reads. Modifying the same resource twice fails planning, unused
+    /// writes are logged and can be retrieved with `RenderGraphPlan::validation_warnings()`. This
+    /// is disabled by default as it adds work to every plan.
+    pub fn set_validation_enabled(
+        &mut self,
+        validation_enabled: bool,
+    ) {
+        self.validation_enabled = validation_enabled;
+    }
+
+    /// Nodes are only merged into subpasses if the device supports them (not on Metal or GL ES).
+    /// Returns an error if the graph is invalid, for example if it has a cycle or an image's
+    /// format can't be determined.
+    pub fn build_plan(
+        self,
+        device_info: &RafxDeviceInfo,
+    ) -> RenderGraphResult<RenderGraphPlan> {
         profiling::scope!("Build Plan");
-        RenderGraphPlan::new(self)
+        RenderGraphPlan::new(self, device_info)
     }
 }
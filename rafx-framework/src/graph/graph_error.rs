@@ -0,0 +1,298 @@
+use super::*;
+use rafx_api::RafxError;
+
+pub type RenderGraphResult<T> = Result<T, RenderGraphError>;
+
+/// A problem with a graph that prevents it from being planned. Each error names the node and
+/// image/buffer involved so that the code building the graph can be found.
+#[derive(Debug, Clone)]
+pub enum RenderGraphError {
+    /// The nodes that use resources produced by this node are also upstream of it
+    Cycle {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+    },
+    /// A node creates an image with constraints that conflict with each other
+    ConflictingImageConstraints {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+    },
+    /// A node creates a buffer with constraints that conflict with each other
+    ConflictingBufferConstraints {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+    },
+    /// Nothing that writes or reads the image specifies enough (at least a format) to create it.
+    /// `node` is None if the usage is an output or external image.
+    UnknownImageSpecification {
+        node: Option<RenderGraphNodeId>,
+        node_name: Option<RenderGraphNodeName>,
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+        usage_type: RenderGraphImageUsageType,
+    },
+    /// Nothing that writes or reads the buffer specifies enough to create it. `node` is None if
+    /// the usage is an output or external buffer.
+    UnknownBufferSpecification {
+        node: Option<RenderGraphNodeId>,
+        node_name: Option<RenderGraphNodeName>,
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+        usage_type: RenderGraphBufferUsageType,
+    },
+    /// An image is read with a specification that the graph can't produce from the one it was
+    /// written with
+    IncompatibleImageSpecification {
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+        usage: RenderGraphImageUsageId,
+        written: RenderGraphImageSpecification,
+        read: RenderGraphImageSpecification,
+        reason: &'static str,
+    },
+    /// A buffer is read with a specification that the graph can't produce from the one it was
+    /// written with
+    IncompatibleBufferSpecification {
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+        usage: RenderGraphBufferUsageId,
+        written: RenderGraphBufferSpecification,
+        read: RenderGraphBufferSpecification,
+        reason: &'static str,
+    },
+    /// Two nodes modify the same version of an image. Each modify needs the data written before
+    /// it, so at most one node may modify a version.
+    ImageWriteAfterWrite {
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+        first_node: RenderGraphNodeId,
+        first_node_name: Option<RenderGraphNodeName>,
+        second_node: RenderGraphNodeId,
+        second_node_name: Option<RenderGraphNodeName>,
+    },
+    /// Two nodes modify the same version of a buffer
+    BufferWriteAfterWrite {
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+        first_node: RenderGraphNodeId,
+        first_node_name: Option<RenderGraphNodeName>,
+        second_node: RenderGraphNodeId,
+        second_node_name: Option<RenderGraphNodeName>,
+    },
+    /// An external image was also set as an output image. Use the final_state of the external
+    /// image instead.
+    ExternalImageIsOutput {
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+    },
+    /// An external buffer was also set as an output buffer. Use the final_state of the external
+    /// buffer instead.
+    ExternalBufferIsOutput {
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+    },
+    /// An output image's specification has an empty resource_type
+    OutputImageWithoutResourceType {
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+    },
+    /// A node that uses an output image is not in the final submission of the graph. The caller
+    /// waits for output images (such as a swapchain image) to become available only when it
+    /// submits the final submission, so nodes that use them may not be followed by nodes on other
+    /// queues.
+    OutputImageUsedBeforeFinalSubmission {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+    },
+    /// The attachments of a node have different sample counts
+    MismatchedSampleCounts {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+    },
+    /// A node writes an image that nothing reads. Only reported if validation is enabled.
+    UnusedImageWrite {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+        image: RenderGraphImageUsageId,
+        image_name: Option<RenderGraphResourceName>,
+    },
+    /// A node writes a buffer that nothing reads. Only reported if validation is enabled.
+    UnusedBufferWrite {
+        node: RenderGraphNodeId,
+        node_name: Option<RenderGraphNodeName>,
+        buffer: RenderGraphBufferUsageId,
+        buffer_name: Option<RenderGraphResourceName>,
+    },
+}
+
+impl std::error::Error for RenderGraphError {}
+
+impl core::fmt::Display for RenderGraphError {
+    fn fmt(
+        &self,
+        fmt: &mut core::fmt::Formatter,
+    ) -> core::fmt::Result {
+        match self {
+            RenderGraphError::Cycle { node, node_name } => write!(
+                fmt,
+                "Node {:?} {:?} depends on its own output",
+                node, node_name
+            ),
+            RenderGraphError::ConflictingImageConstraints {
+                node,
+                node_name,
+                image,
+                image_name,
+            } => write!(
+                fmt,
+                "Node {:?} {:?} creates image {:?} {:?} with conflicting constraints",
+                node, node_name, image, image_name
+            ),
+            RenderGraphError::ConflictingBufferConstraints {
+                node,
+                node_name,
+                buffer,
+                buffer_name,
+            } => write!(
+                fmt,
+                "Node {:?} {:?} creates buffer {:?} {:?} with conflicting constraints",
+                node, node_name, buffer, buffer_name
+            ),
+            RenderGraphError::UnknownImageSpecification {
+                node,
+                node_name,
+                image,
+                image_name,
+                usage_type,
+            } => write!(
+                fmt,
+                "Not enough information in the graph to determine the specification for image {:?} {:?} used as {:?} by node {:?} {:?}. Set a format on the node that writes it or on this usage",
+                image, image_name, usage_type, node, node_name
+            ),
+            RenderGraphError::UnknownBufferSpecification {
+                node,
+                node_name,
+                buffer,
+                buffer_name,
+                usage_type,
+            } => write!(
+                fmt,
+                "Not enough information in the graph to determine the specification for buffer {:?} {:?} used as {:?} by node {:?} {:?}. Set a size on the node that writes it or on this usage",
+                buffer, buffer_name, usage_type, node, node_name
+            ),
+            RenderGraphError::IncompatibleImageSpecification {
+                image,
+                image_name,
+                usage,
+                written,
+                read,
+                reason,
+            } => write!(
+                fmt,
+                "Image {:?} {:?} is read by usage {:?} with a specification that is incompatible with how it was written: {}. Written: {:?} Read: {:?}",
+                image, image_name, usage, reason, written, read
+            ),
+            RenderGraphError::IncompatibleBufferSpecification {
+                buffer,
+                buffer_name,
+                usage,
+                written,
+                read,
+                reason,
+            } => write!(
+                fmt,
+                "Buffer {:?} {:?} is read by usage {:?} with a specification that is incompatible with how it was written: {}. Written: {:?} Read: {:?}",
+                buffer, buffer_name, usage, reason, written, read
+            ),
+            RenderGraphError::ImageWriteAfterWrite {
+                image,
+                image_name,
+                first_node,
+                first_node_name,
+                second_node,
+                second_node_name,
+            } => write!(
+                fmt,
+                "Image {:?} {:?} is modified by both node {:?} {:?} and node {:?} {:?}. Modify the image written by the first node in the second node instead",
+                image, image_name, first_node, first_node_name, second_node, second_node_name
+            ),
+            RenderGraphError::BufferWriteAfterWrite {
+                buffer,
+                buffer_name,
+                first_node,
+                first_node_name,
+                second_node,
+                second_node_name,
+            } => write!(
+                fmt,
+                "Buffer {:?} {:?} is modified by both node {:?} {:?} and node {:?} {:?}. Modify the buffer written by the first node in the second node instead",
+                buffer, buffer_name, first_node, first_node_name, second_node, second_node_name
+            ),
+            RenderGraphError::ExternalImageIsOutput { image, image_name } => write!(
+                fmt,
+                "External image {:?} {:?} cannot also be set as an output image. Use the final_state of the external image instead",
+                image, image_name
+            ),
+            RenderGraphError::ExternalBufferIsOutput {
+                buffer,
+                buffer_name,
+            } => write!(
+                fmt,
+                "External buffer {:?} {:?} cannot also be set as an output buffer. Use the final_state of the external buffer instead",
+                buffer, buffer_name
+            ),
+            RenderGraphError::OutputImageWithoutResourceType { image, image_name } => write!(
+                fmt,
+                "Output image {:?} {:?} has an empty resource_type in its specification",
+                image, image_name
+            ),
+            RenderGraphError::OutputImageUsedBeforeFinalSubmission {
+                node,
+                node_name,
+                image,
+                image_name,
+            } => write!(
+                fmt,
+                "Node {:?} {:?} uses output image {:?} {:?} but is not in the final submission of the graph. Nodes that use output images must run after all nodes on other queues",
+                node, node_name, image, image_name
+            ),
+            RenderGraphError::MismatchedSampleCounts { node, node_name } => write!(
+                fmt,
+                "Node {:?} {:?} has attachments with different sample counts",
+                node, node_name
+            ),
+            RenderGraphError::UnusedImageWrite {
+                node,
+                node_name,
+                image,
+                image_name,
+            } => write!(
+                fmt,
+                "Node {:?} {:?} writes image {:?} {:?} but nothing reads it",
+                node, node_name, image, image_name
+            ),
+            RenderGraphError::UnusedBufferWrite {
+                node,
+                node_name,
+                buffer,
+                buffer_name,
+            } => write!(
+                fmt,
+                "Node {:?} {:?} writes buffer {:?} {:?} but nothing reads it",
+                node, node_name, buffer, buffer_name
+            ),
+        }
+    }
+}
+
+impl From<RenderGraphError> for RafxError {
+    fn from(error: RenderGraphError) -> Self {
+        RafxError::StringError(error.to_string())
+    }
+}
@@ -0,0 +1,686 @@
+use super::*;
+use crate::graph::graph_buffer::{PhysicalBufferId, RenderGraphBufferUser};
+use crate::graph::graph_image::{PhysicalImageId, RenderGraphImageUser};
+use crate::graph::graph_pass::{PrepassBufferBarrier, PrepassImageBarrier, RenderGraphOutputPass};
+use fnv::FnvHashMap;
+use serde::Serialize;
+use std::fmt::Write;
+
+//
+// A description of a render graph intended for debugging tools. Everything is stored as plain
+// indices and strings so that it can be written to JSON and diffed between builds. Indices match
+// the ids used by the graph (RenderGraphNodeId, RenderGraphImageUsageId, etc.) and the pass and
+// submission indices of the plan.
+//
+
+/// A node registered in the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportNode {
+    pub id: usize,
+    pub name: Option<String>,
+    pub queue: String,
+}
+
+/// A version of an image or buffer. Every write produces a new version
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportResourceVersion {
+    pub version: usize,
+    /// None if the version was provided from outside the graph
+    pub creator_node: Option<usize>,
+    pub create_usage: usize,
+    pub read_usages: Vec<usize>,
+}
+
+/// An image or buffer registered in the graph, before it has been assigned to a physical resource
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportResource {
+    pub id: usize,
+    pub name: Option<String>,
+    pub versions: Vec<RenderGraphExportResourceVersion>,
+}
+
+/// A read or write of a resource version
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportUsage {
+    pub id: usize,
+    pub resource: usize,
+    pub version: usize,
+    pub usage_type: String,
+    /// The node, output or external input that uses the resource, i.e. "Node(3)"
+    pub user: String,
+    /// Set if the user is a node
+    pub node: Option<usize>,
+    pub array_slice: Option<u16>,
+    pub mip_slice: Option<u8>,
+}
+
+/// A barrier inserted before or after a pass
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportBarrier {
+    /// Physical image or buffer id, depending on which list the barrier is in
+    pub physical_resource: usize,
+    pub array_slice: Option<u16>,
+    pub mip_slice: Option<u8>,
+    pub old_state: String,
+    pub new_state: String,
+    pub queue_transition: String,
+}
+
+impl From<&PrepassImageBarrier> for RenderGraphExportBarrier {
+    fn from(barrier: &PrepassImageBarrier) -> Self {
+        RenderGraphExportBarrier {
+            physical_resource: barrier.image.0,
+            array_slice: barrier.array_slice,
+            mip_slice: barrier.mip_slice,
+            old_state: format!("{:?}", barrier.old_state),
+            new_state: format!("{:?}", barrier.new_state),
+            queue_transition: format!("{:?}", barrier.queue_transition),
+        }
+    }
+}
+
+impl From<&PrepassBufferBarrier> for RenderGraphExportBarrier {
+    fn from(barrier: &PrepassBufferBarrier) -> Self {
+        RenderGraphExportBarrier {
+            physical_resource: barrier.buffer.0,
+            array_slice: None,
+            mip_slice: None,
+            old_state: format!("{:?}", barrier.old_state),
+            new_state: format!("{:?}", barrier.new_state),
+            queue_transition: format!("{:?}", barrier.queue_transition),
+        }
+    }
+}
+
+/// A pass produced by the plan. Nodes that were merged into the same pass are listed together in
+/// execution order.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportPass {
+    pub index: usize,
+    pub name: Option<String>,
+    pub is_renderpass: bool,
+    pub nodes: Vec<usize>,
+    pub submission: Option<usize>,
+    pub pre_pass_image_barriers: Vec<RenderGraphExportBarrier>,
+    pub pre_pass_buffer_barriers: Vec<RenderGraphExportBarrier>,
+    pub post_pass_image_barriers: Vec<RenderGraphExportBarrier>,
+    pub post_pass_buffer_barriers: Vec<RenderGraphExportBarrier>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportSubmission {
+    pub index: usize,
+    pub queue: String,
+    pub passes: Vec<usize>,
+    pub wait_for: Vec<usize>,
+    pub runs_in_parallel_with: Vec<usize>,
+}
+
+/// An image or buffer that will be allocated by the graph or provided by the caller
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportPhysicalResource {
+    pub id: usize,
+    pub name: Option<String>,
+    /// "Intermediate", "Output" or "External"
+    pub kind: String,
+    /// Only set for intermediate resources, as the others are provided by the caller
+    pub specification: Option<String>,
+    /// The usages that were assigned to this resource
+    pub usages: Vec<usize>,
+    /// Other physical resources that share this resource's memory
+    pub aliased_with: Vec<usize>,
+}
+
+/// The result of planning the graph
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExportPlan {
+    pub culled_nodes: Vec<usize>,
+    pub passes: Vec<RenderGraphExportPass>,
+    pub submissions: Vec<RenderGraphExportSubmission>,
+    pub physical_images: Vec<RenderGraphExportPhysicalResource>,
+    pub physical_buffers: Vec<RenderGraphExportPhysicalResource>,
+}
+
+/// A description of a render graph that can be written as a graphviz DOT graph or as JSON. The
+/// plan is only included if the export was produced by `RenderGraphPlan::export()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderGraphExport {
+    pub nodes: Vec<RenderGraphExportNode>,
+    pub images: Vec<RenderGraphExportResource>,
+    pub buffers: Vec<RenderGraphExportResource>,
+    pub image_usages: Vec<RenderGraphExportUsage>,
+    pub buffer_usages: Vec<RenderGraphExportUsage>,
+    pub plan: Option<RenderGraphExportPlan>,
+}
+
+impl RenderGraphExport {
+    pub(super) fn new(graph: &RenderGraphBuilder) -> Self {
+        let nodes = graph
+            .nodes
+            .iter()
+            .map(|node| RenderGraphExportNode {
+                id: node.id().0,
+                name: node.name().map(|x| x.to_string()),
+                queue: format!("{:?}", node.queue),
+            })
+            .collect();
+
+        let images = graph
+            .image_resources
+            .iter()
+            .enumerate()
+            .map(|(index, resource)| RenderGraphExportResource {
+                id: index,
+                name: resource.name.map(|x| x.to_string()),
+                versions: resource
+                    .versions
+                    .iter()
+                    .enumerate()
+                    .map(|(version, info)| RenderGraphExportResourceVersion {
+                        version,
+                        creator_node: info.creator_node.map(|x| x.0),
+                        create_usage: info.create_usage.0,
+                        read_usages: info.read_usages.iter().map(|x| x.0).collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let buffers = graph
+            .buffer_resources
+            .iter()
+            .enumerate()
+            .map(|(index, resource)| RenderGraphExportResource {
+                id: index,
+                name: resource.name.map(|x| x.to_string()),
+                versions: resource
+                    .versions
+                    .iter()
+                    .enumerate()
+                    .map(|(version, info)| RenderGraphExportResourceVersion {
+                        version,
+                        creator_node: info.creator_node.map(|x| x.0),
+                        create_usage: info.create_usage.0,
+                        read_usages: info.read_usages.iter().map(|x| x.0).collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let image_usages = graph
+            .image_usages
+            .iter()
+            .enumerate()
+            .map(|(index, usage)| RenderGraphExportUsage {
+                id: index,
+                resource: usage.version.index,
+                version: usage.version.version,
+                usage_type: format!("{:?}", usage.usage_type),
+                user: match usage.user {
+                    RenderGraphImageUser::Node(node) => format!("Node({})", node.0),
+                    RenderGraphImageUser::Output(output) => format!("Output({})", output.0),
+                    RenderGraphImageUser::Input(input) => format!("External({})", input.0),
+                },
+                node: match usage.user {
+                    RenderGraphImageUser::Node(node) => Some(node.0),
+                    _ => None,
+                },
+                array_slice: usage.view_options.array_slice,
+                mip_slice: usage.view_options.mip_slice,
+            })
+            .collect();
+
+        let buffer_usages = graph
+            .buffer_usages
+            .iter()
+            .enumerate()
+            .map(|(index, usage)| RenderGraphExportUsage {
+                id: index,
+                resource: usage.version.index,
+                version: usage.version.version,
+                usage_type: format!("{:?}", usage.usage_type),
+                user: match usage.user {
+                    RenderGraphBufferUser::Node(node) => format!("Node({})", node.0),
+                    RenderGraphBufferUser::Output(output) => format!("Output({})", output.0),
+                    RenderGraphBufferUser::Input(input) => format!("External({})", input.0),
+                },
+                node: match usage.user {
+                    RenderGraphBufferUser::Node(node) => Some(node.0),
+                    _ => None,
+                },
+                array_slice: None,
+                mip_slice: None,
+            })
+            .collect();
+
+        RenderGraphExport {
+            nodes,
+            images,
+            buffers,
+            image_usages,
+            buffer_usages,
+            plan: None,
+        }
+    }
+
+    // Called once the plan is complete to add the passes, submissions and physical resources
+    pub(super) fn add_plan(
+        &mut self,
+        plan: &RenderGraphPlan,
+    ) {
+        let culled_nodes = self
+            .nodes
+            .iter()
+            .map(|node| node.id)
+            .filter(|&node| {
+                !plan
+                    .node_to_pass_index
+                    .contains_key(&RenderGraphNodeId(node))
+            })
+            .collect();
+
+        let mut pass_submissions = vec![None; plan.passes.len()];
+        let mut submissions = Vec::with_capacity(plan.submissions.len());
+        for (index, submission) in plan.submissions.iter().enumerate() {
+            for &pass_index in &submission.passes {
+                pass_submissions[pass_index] = Some(index);
+            }
+
+            submissions.push(RenderGraphExportSubmission {
+                index,
+                queue: format!("{:?}", submission.queue),
+                passes: submission.passes.clone(),
+                wait_for: submission.wait_for.clone(),
+                runs_in_parallel_with: submission.runs_in_parallel_with.clone(),
+            });
+        }
+
+        let passes = plan
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                let pre_pass_barrier = pass.pre_pass_barrier();
+                let post_pass_barrier = pass.post_pass_barrier();
+                RenderGraphExportPass {
+                    index,
+                    name: pass.debug_name().map(|x| x.to_string()),
+                    is_renderpass: matches!(pass, RenderGraphOutputPass::Renderpass(_)),
+                    nodes: pass.nodes().iter().map(|x| x.0).collect(),
+                    submission: pass_submissions[index],
+                    pre_pass_image_barriers: pre_pass_barrier
+                        .map(|x| x.image_barriers.iter().map(Into::into).collect())
+                        .unwrap_or_default(),
+                    pre_pass_buffer_barriers: pre_pass_barrier
+                        .map(|x| x.buffer_barriers.iter().map(Into::into).collect())
+                        .unwrap_or_default(),
+                    post_pass_image_barriers: post_pass_barrier
+                        .map(|x| x.image_barriers.iter().map(Into::into).collect())
+                        .unwrap_or_default(),
+                    post_pass_buffer_barriers: post_pass_barrier
+                        .map(|x| x.buffer_barriers.iter().map(Into::into).collect())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        //
+        // Physical images
+        //
+        let physical_image_count = plan
+            .image_views
+            .iter()
+            .map(|x| x.physical_image)
+            .chain(plan.intermediate_images.keys().copied())
+            .map(|x| x.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let mut physical_images: Vec<_> = (0..physical_image_count)
+            .map(|index| {
+                let physical_image = PhysicalImageId(index);
+                RenderGraphExportPhysicalResource {
+                    id: index,
+                    name: plan.physical_image_names.get(&physical_image).cloned(),
+                    kind: "Intermediate".to_string(),
+                    specification: plan
+                        .intermediate_images
+                        .get(&physical_image)
+                        .map(|x| format!("{:?}", x)),
+                    usages: Default::default(),
+                    aliased_with: Default::default(),
+                }
+            })
+            .collect();
+
+        for image_view in plan.output_images.keys() {
+            let physical_image = plan.image_views[image_view.0].physical_image;
+            physical_images[physical_image.0].kind = "Output".to_string();
+        }
+
+        for image_view in plan.external_images.keys() {
+            let physical_image = plan.image_views[image_view.0].physical_image;
+            physical_images[physical_image.0].kind = "External".to_string();
+        }
+
+        for (usage, image_view) in &plan.image_usage_to_view {
+            let physical_image = plan.image_views[image_view.0].physical_image;
+            physical_images[physical_image.0].usages.push(usage.0);
+        }
+
+        for aliased_images in &plan.aliased_images {
+            for physical_image in aliased_images {
+                physical_images[physical_image.0].aliased_with = aliased_images
+                    .iter()
+                    .filter(|x| *x != physical_image)
+                    .map(|x| x.0)
+                    .collect();
+            }
+        }
+
+        //
+        // Physical buffers
+        //
+        let physical_buffer_count = plan
+            .buffer_usage_to_physical
+            .values()
+            .chain(plan.intermediate_buffers.keys())
+            .map(|x| x.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let mut physical_buffers: Vec<_> = (0..physical_buffer_count)
+            .map(|index| {
+                let physical_buffer = PhysicalBufferId(index);
+                RenderGraphExportPhysicalResource {
+                    id: index,
+                    name: plan.physical_buffer_names.get(&physical_buffer).cloned(),
+                    kind: "Intermediate".to_string(),
+                    specification: plan
+                        .intermediate_buffers
+                        .get(&physical_buffer)
+                        .map(|x| format!("{:?}", x)),
+                    usages: Default::default(),
+                    aliased_with: Default::default(),
+                }
+            })
+            .collect();
+
+        for physical_buffer in plan.output_buffers.keys() {
+            physical_buffers[physical_buffer.0].kind = "Output".to_string();
+        }
+
+        for physical_buffer in plan.external_buffers.keys() {
+            physical_buffers[physical_buffer.0].kind = "External".to_string();
+        }
+
+        for (usage, physical_buffer) in &plan.buffer_usage_to_physical {
+            physical_buffers[physical_buffer.0].usages.push(usage.0);
+        }
+
+        for aliased_buffers in &plan.aliased_buffers {
+            for physical_buffer in aliased_buffers {
+                physical_buffers[physical_buffer.0].aliased_with = aliased_buffers
+                    .iter()
+                    .filter(|x| *x != physical_buffer)
+                    .map(|x| x.0)
+                    .collect();
+            }
+        }
+
+        // Usages come out of hash maps, sort them so that exports of the same graph are identical
+        for physical_resource in physical_images.iter_mut().chain(&mut physical_buffers) {
+            physical_resource.usages.sort_unstable();
+        }
+
+        self.plan = Some(RenderGraphExportPlan {
+            culled_nodes,
+            passes,
+            submissions,
+            physical_images,
+            physical_buffers,
+        });
+    }
+
+    /// Write the export as JSON
+    pub fn to_json(&self) -> String {
+        // Can't fail, the export only contains strings, numbers, options and vecs
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Write the export as a graphviz DOT graph. Nodes are boxes and resource versions are
+    /// ellipses. Culled nodes are dashed. If the plan is included, nodes are grouped by
+    /// submission and labeled with their pass and barriers, and resource versions are labeled
+    /// with the physical resource they were assigned to.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        // Writing to a String can't fail
+        self.write_dot(&mut dot).unwrap();
+        dot
+    }
+
+    fn write_dot(
+        &self,
+        dot: &mut String,
+    ) -> std::fmt::Result {
+        writeln!(dot, "digraph render_graph {{")?;
+        writeln!(dot, "  rankdir=LR;")?;
+        writeln!(dot, "  node [fontname=\"monospace\", fontsize=10];")?;
+
+        let mut node_passes = vec![None; self.nodes.len()];
+        let mut image_usage_physical = vec![None; self.image_usages.len()];
+        let mut buffer_usage_physical = vec![None; self.buffer_usages.len()];
+        if let Some(plan) = &self.plan {
+            for pass in &plan.passes {
+                for &node in &pass.nodes {
+                    node_passes[node] = Some(pass);
+                }
+            }
+
+            for physical_image in &plan.physical_images {
+                for &usage in &physical_image.usages {
+                    image_usage_physical[usage] = Some(physical_image.id);
+                }
+            }
+
+            for physical_buffer in &plan.physical_buffers {
+                for &usage in &physical_buffer.usages {
+                    buffer_usage_physical[usage] = Some(physical_buffer.id);
+                }
+            }
+        }
+
+        //
+        // Nodes, grouped by the submission they were scheduled into
+        //
+        let mut submission_nodes = FnvHashMap::<Option<usize>, Vec<usize>>::default();
+        for node in &self.nodes {
+            let submission = node_passes[node.id].and_then(|x| x.submission);
+            submission_nodes
+                .entry(submission)
+                .or_default()
+                .push(node.id);
+        }
+
+        let mut submissions: Vec<_> = submission_nodes.into_iter().collect();
+        submissions.sort_by_key(|(submission, _)| *submission);
+        for (submission, nodes) in submissions {
+            let indent = if let Some(submission) = submission {
+                let queue = &self.plan.as_ref().unwrap().submissions[submission].queue;
+                writeln!(dot, "  subgraph cluster_submission_{} {{", submission)?;
+                writeln!(
+                    dot,
+                    "    label=\"submission {} ({})\";",
+                    submission,
+                    escape_dot(queue)
+                )?;
+                "    "
+            } else {
+                "  "
+            };
+
+            for node_id in nodes {
+                let node = &self.nodes[node_id];
+                let mut label = format!(
+                    "node {}: {}\\n{}",
+                    node.id,
+                    escape_dot(node.name.as_deref().unwrap_or("unnamed")),
+                    escape_dot(&node.queue)
+                );
+
+                let mut style = "solid";
+                if let Some(pass) = node_passes[node.id] {
+                    write!(
+                        label,
+                        "\\npass {}{}",
+                        pass.index,
+                        if pass.is_renderpass {
+                            " (renderpass)"
+                        } else {
+                            " (compute)"
+                        }
+                    )?;
+                    write_dot_barriers(&mut label, "pre image", &pass.pre_pass_image_barriers)?;
+                    write_dot_barriers(&mut label, "pre buffer", &pass.pre_pass_buffer_barriers)?;
+                    write_dot_barriers(&mut label, "post image", &pass.post_pass_image_barriers)?;
+                    write_dot_barriers(&mut label, "post buffer", &pass.post_pass_buffer_barriers)?;
+                } else if self.plan.is_some() {
+                    label.push_str("\\nculled");
+                    style = "dashed";
+                }
+
+                writeln!(
+                    dot,
+                    "{}node_{} [shape=box, style={}, label=\"{}\"];",
+                    indent, node.id, style, label
+                )?;
+            }
+
+            if submission.is_some() {
+                writeln!(dot, "  }}")?;
+            }
+        }
+
+        //
+        // Resource versions, with edges from the node that created them to the nodes that read them
+        //
+        self.write_dot_resources(
+            dot,
+            "image",
+            &self.images,
+            &self.image_usages,
+            &image_usage_physical,
+        )?;
+        self.write_dot_resources(
+            dot,
+            "buffer",
+            &self.buffers,
+            &self.buffer_usages,
+            &buffer_usage_physical,
+        )?;
+
+        //
+        // Semaphore waits between submissions
+        //
+        if let Some(plan) = &self.plan {
+            for submission in &plan.submissions {
+                for &wait_for in &submission.wait_for {
+                    let signal_pass = plan.submissions[wait_for].passes.last();
+                    let wait_pass = submission.passes.first();
+                    if let (Some(&signal_pass), Some(&wait_pass)) = (signal_pass, wait_pass) {
+                        writeln!(
+                            dot,
+                            "  node_{} -> node_{} [style=dotted, color=red, label=\"semaphore\"];",
+                            plan.passes[signal_pass].nodes[0], plan.passes[wait_pass].nodes[0]
+                        )?;
+                    }
+                }
+            }
+        }
+
+        writeln!(dot, "}}")
+    }
+
+    fn write_dot_resources(
+        &self,
+        dot: &mut String,
+        resource_type: &str,
+        resources: &[RenderGraphExportResource],
+        usages: &[RenderGraphExportUsage],
+        usage_physical: &[Option<usize>],
+    ) -> std::fmt::Result {
+        for resource in resources {
+            for version in &resource.versions {
+                let version_id = format!("{}_{}_v{}", resource_type, resource.id, version.version);
+                let mut label = format!(
+                    "{} {}: {}\\nv{}",
+                    resource_type,
+                    resource.id,
+                    escape_dot(resource.name.as_deref().unwrap_or("unnamed")),
+                    version.version
+                );
+
+                if let Some(physical) = usage_physical[version.create_usage] {
+                    write!(label, "\\nphysical {} {}", resource_type, physical)?;
+                }
+
+                writeln!(
+                    dot,
+                    "  {} [shape=ellipse, label=\"{}\"];",
+                    version_id, label
+                )?;
+
+                let create_usage = &usages[version.create_usage];
+                if let Some(creator_node) = version.creator_node {
+                    writeln!(
+                        dot,
+                        "  node_{} -> {} [label=\"{}\"];",
+                        creator_node, version_id, create_usage.usage_type
+                    )?;
+                }
+
+                for &read_usage in &version.read_usages {
+                    let usage = &usages[read_usage];
+                    // Outputs are not nodes, they are shown on the version's label instead
+                    if let Some(node) = usage.node {
+                        writeln!(
+                            dot,
+                            "  {} -> node_{} [label=\"{}\"];",
+                            version_id, node, usage.usage_type
+                        )?;
+                    } else {
+                        writeln!(
+                            dot,
+                            "  {} [xlabel=\"{}\"];",
+                            version_id,
+                            escape_dot(&usage.user)
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_dot_barriers(
+    label: &mut String,
+    description: &str,
+    barriers: &[RenderGraphExportBarrier],
+) -> std::fmt::Result {
+    for barrier in barriers {
+        write!(
+            label,
+            "\\n{} barrier {}: {} -> {}",
+            description,
+            barrier.physical_resource,
+            escape_dot(&barrier.old_state),
+            escape_dot(&barrier.new_state)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
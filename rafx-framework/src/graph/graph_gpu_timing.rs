@@ -0,0 +1,193 @@
+use crate::graph::graph_node::{RenderGraphNodeId, RenderGraphNodeName};
+use rafx_api::{
+    RafxCommandBuffer, RafxDeviceContext, RafxQueryPool, RafxQueryPoolDef, RafxQueryType,
+    RafxResult,
+};
+use std::sync::Arc;
+
+/// A node executed by a timed pass
+#[derive(Debug, Clone)]
+pub struct RenderGraphGpuTimedNode {
+    pub node_id: RenderGraphNodeId,
+    pub debug_name: Option<RenderGraphNodeName>,
+}
+
+/// GPU time spent executing a single pass of the graph. Nodes that were merged into subpasses of
+/// one renderpass are timed together, so `nodes` lists every node the time covers.
+#[derive(Debug, Clone)]
+pub struct RenderGraphPassGpuTime {
+    pub debug_name: Option<RenderGraphNodeName>,
+    pub nodes: Vec<RenderGraphGpuTimedNode>,
+    pub milliseconds: f64,
+}
+
+/// Per-pass GPU times for a single execution of a render graph, in execution order. The Display
+/// impl formats this as a table.
+#[derive(Debug, Clone, Default)]
+pub struct RenderGraphGpuTimings {
+    pub passes: Vec<RenderGraphPassGpuTime>,
+}
+
+impl RenderGraphGpuTimings {
+    pub fn total_milliseconds(&self) -> f64 {
+        self.passes.iter().map(|x| x.milliseconds).sum()
+    }
+}
+
+impl RenderGraphPassGpuTime {
+    // The pass's name, followed by the names of all of its nodes if there is more than one
+    fn display_name(&self) -> String {
+        let debug_name = self.debug_name.unwrap_or("unnamed");
+        if self.nodes.len() <= 1 {
+            return debug_name.to_string();
+        }
+
+        let node_names: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|x| x.debug_name.unwrap_or("unnamed"))
+            .collect();
+        format!("{} ({})", debug_name, node_names.join(", "))
+    }
+}
+
+impl std::fmt::Display for RenderGraphGpuTimings {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let names: Vec<_> = self.passes.iter().map(|x| x.display_name()).collect();
+        let name_width = names
+            .iter()
+            .map(|x| x.len())
+            .max()
+            .unwrap_or(0)
+            .max("pass".len());
+
+        writeln!(
+            f,
+            "{:<width$}  {:>10}",
+            "pass",
+            "GPU ms",
+            width = name_width
+        )?;
+        for (name, pass) in names.iter().zip(&self.passes) {
+            writeln!(
+                f,
+                "{:<width$}  {:>10.3}",
+                name,
+                pass.milliseconds,
+                width = name_width
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<width$}  {:>10.3}",
+            "total",
+            self.total_milliseconds(),
+            width = name_width
+        )
+    }
+}
+
+struct RenderGraphGpuTimerInner {
+    query_pool: RafxQueryPool,
+    timestamp_period: f32,
+    passes: Vec<(Option<RenderGraphNodeName>, Vec<RenderGraphGpuTimedNode>)>,
+}
+
+/// Measures the GPU time of each pass in a `PreparedRenderGraph`. Created by
+/// `PreparedRenderGraph::enable_gpu_timing()`. Every pass is wrapped in a pair of timestamp
+/// queries when the graph is executed. Timestamps can't be written between subpasses that are
+/// recorded into secondary command buffers, so nodes merged into one renderpass are not timed
+/// individually.
+///
+/// The timer owns the query pool that timestamps are written to, so it must be kept alive until
+/// the frame's fence has signaled.
+#[derive(Clone)]
+pub struct RenderGraphGpuTimer {
+    inner: Arc<RenderGraphGpuTimerInner>,
+}
+
+impl RenderGraphGpuTimer {
+    pub(super) fn new(
+        device_context: &RafxDeviceContext,
+        passes: Vec<(Option<RenderGraphNodeName>, Vec<RenderGraphGpuTimedNode>)>,
+    ) -> RafxResult<Self> {
+        let device_info = device_context.device_info();
+        if !device_info.supports_timestamp_queries {
+            Err("GPU timing requires timestamp queries, which this device does not support")?;
+        }
+
+        // Query pools may not be empty
+        let query_pool = device_context.create_query_pool(&RafxQueryPoolDef {
+            query_type: RafxQueryType::Timestamp,
+            query_count: (passes.len() as u32 * 2).max(1),
+        })?;
+
+        let inner = RenderGraphGpuTimerInner {
+            query_pool,
+            timestamp_period: device_info.timestamp_period,
+            passes,
+        };
+
+        Ok(RenderGraphGpuTimer {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub(super) fn query_pool(&self) -> &RafxQueryPool {
+        &self.inner.query_pool
+    }
+
+    // Resets every query in the pool. This must be recorded once per execution of the graph,
+    // before any timestamp is written and outside of a render pass.
+    pub(super) fn reset_queries(
+        &self,
+        command_buffer: &RafxCommandBuffer,
+    ) -> RafxResult<()> {
+        let query_pool = &self.inner.query_pool;
+        command_buffer.cmd_reset_query_pool(query_pool, 0, query_pool.query_pool_def().query_count)
+    }
+
+    fn query_count(&self) -> u32 {
+        self.inner.passes.len() as u32 * 2
+    }
+
+    // Timestamps written before and after the pass at the given index
+    pub(super) fn pass_query_indices(pass_index: usize) -> (u32, u32) {
+        (pass_index as u32 * 2, pass_index as u32 * 2 + 1)
+    }
+
+    /// Returns the GPU time of each pass, or None if the GPU has not finished executing the graph
+    /// yet. This does not block.
+    pub fn read_timings(&self) -> RafxResult<Option<RenderGraphGpuTimings>> {
+        if self.inner.passes.is_empty() {
+            return Ok(Some(RenderGraphGpuTimings::default()));
+        }
+
+        let results = match self.inner.query_pool.get_results(0, self.query_count())? {
+            Some(results) => results,
+            None => return Ok(None),
+        };
+
+        let nanoseconds_per_tick = self.inner.timestamp_period as f64;
+        let passes = self
+            .inner
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(pass_index, (debug_name, nodes))| {
+                let (begin, end) = Self::pass_query_indices(pass_index);
+                let ticks = results[end as usize].saturating_sub(results[begin as usize]);
+                RenderGraphPassGpuTime {
+                    debug_name: *debug_name,
+                    nodes: nodes.clone(),
+                    milliseconds: ticks as f64 * nanoseconds_per_tick / 1_000_000.0,
+                }
+            })
+            .collect();
+
+        Ok(Some(RenderGraphGpuTimings { passes }))
+    }
+}
@@ -21,6 +21,10 @@ pub struct PhysicalImageViewId(pub(super) usize);
 #[derive(Debug, Copy, Clone)]
 pub struct RenderGraphOutputImageId(pub(super) usize);
 
+/// Unique ID provided for any image passed into the graph from outside as an external image
+#[derive(Debug, Copy, Clone)]
+pub struct RenderGraphExternalImageId(pub(super) usize);
+
 /// Unique ID for a particular version of an image. Any time an image is modified, a new version is
 /// produced
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -60,6 +64,7 @@ pub struct RenderGraphImageView {
 pub enum RenderGraphImageUser {
     Node(RenderGraphNodeId),
     Output(RenderGraphOutputImageId),
+    Input(RenderGraphExternalImageId),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -120,6 +125,43 @@ impl RenderGraphImageViewOptions {
     }
 }
 
+/// The array layers and mips of a physical image touched by a usage. None includes all of them.
+/// Resource states are tracked per layer and mip so that different slices of the same image can be
+/// used in different ways (i.e. sampling one mip while rendering to the next)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderGraphImageSubresource {
+    pub array_slice: Option<u16>,
+    pub mip_slice: Option<u8>,
+}
+
+impl RenderGraphImageSubresource {
+    pub(super) fn from_view_options(view_options: &RenderGraphImageViewOptions) -> Self {
+        RenderGraphImageSubresource {
+            array_slice: view_options.array_slice,
+            mip_slice: view_options.mip_slice,
+        }
+    }
+
+    /// Returns true if any layer and mip is included in both subresources
+    pub fn overlaps(
+        &self,
+        other: &RenderGraphImageSubresource,
+    ) -> bool {
+        fn slices_overlap<T: PartialEq>(
+            a: Option<T>,
+            b: Option<T>,
+        ) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+        }
+
+        slices_overlap(self.array_slice, other.array_slice)
+            && slices_overlap(self.mip_slice, other.mip_slice)
+    }
+}
+
 /// A usage of a particular image
 #[derive(Debug)]
 pub struct RenderGraphImageUsage {
@@ -183,6 +225,39 @@ impl RenderGraphImageSpecification {
 
         true
     }
+
+    /// Estimate the memory required by an image with this specification. This is based on the
+    /// format, extents, layers, mips and samples. It does not include any padding or alignment
+    /// the device may require, so the real allocation may be somewhat larger.
+    pub fn estimate_size_in_bytes(
+        &self,
+        swapchain_surface_info: &SwapchainSurfaceInfo,
+    ) -> u64 {
+        let extents = self.extents.into_rafx_extents(swapchain_surface_info);
+        let block_size = self.format.block_or_pixel_size_in_bytes() as u64;
+        let block_width = self.format.block_width_in_pixels();
+        let block_height = self.format.block_height_in_pixels();
+        let sample_count = match self.samples {
+            RafxSampleCount::SampleCount1 => 1,
+            RafxSampleCount::SampleCount2 => 2,
+            RafxSampleCount::SampleCount4 => 4,
+            RafxSampleCount::SampleCount8 => 8,
+            RafxSampleCount::SampleCount16 => 16,
+        };
+
+        let mut layer_size = 0;
+        for mip_level in 0..self.mip_count.max(1) {
+            let width = (extents.width >> mip_level).max(1);
+            let height = (extents.height >> mip_level).max(1);
+            let depth = (extents.depth >> mip_level).max(1);
+
+            let blocks_wide = width.div_ceil(block_width) as u64;
+            let blocks_high = height.div_ceil(block_height) as u64;
+            layer_size += blocks_wide * blocks_high * depth as u64 * block_size;
+        }
+
+        layer_size * self.layer_count.max(1) as u64 * sample_count
+    }
 }
 
 /// Constraints on an image. Constraints are set per-field and start out None (i.e. unconstrained)
@@ -355,7 +430,7 @@ impl RenderGraphImageConstraint {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum RenderGraphImageUsageType {
     Create,
-    //Input,
+    Input,
     Read,
     ModifyRead,
     ModifyWrite,
@@ -370,7 +445,7 @@ impl RenderGraphImageUsageType {
             RenderGraphImageUsageType::Output => true,
             RenderGraphImageUsageType::ModifyRead => false,
             RenderGraphImageUsageType::Create => false,
-            //RenderGraphImageUsageType::Input => false,
+            RenderGraphImageUsageType::Input => false,
             RenderGraphImageUsageType::ModifyWrite => false,
         }
     }
@@ -379,9 +454,9 @@ impl RenderGraphImageUsageType {
 /// Information about a specific version of the image.
 #[derive(Debug)]
 pub struct RenderGraphImageResourceVersionInfo {
-    /// What node created the image (keep in mind these are virtual images, not images provided
-    /// from outside the graph. So every image will have a creator node)
-    pub(super) creator_node: RenderGraphNodeId,
+    /// What node created the image. This is None if the version is an external image provided
+    /// from outside the graph, which is not produced by any node
+    pub(super) creator_node: Option<RenderGraphNodeId>,
 
     pub(super) create_usage: RenderGraphImageUsageId,
     pub(super) read_usages: Vec<RenderGraphImageUsageId>,
@@ -389,7 +464,7 @@ pub struct RenderGraphImageResourceVersionInfo {
 
 impl RenderGraphImageResourceVersionInfo {
     pub(super) fn new(
-        creator: RenderGraphNodeId,
+        creator: Option<RenderGraphNodeId>,
         create_usage: RenderGraphImageUsageId,
     ) -> Self {
         RenderGraphImageResourceVersionInfo {
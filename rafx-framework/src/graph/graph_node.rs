@@ -115,6 +115,20 @@ impl std::fmt::Debug for RenderGraphPassResolveAttachmentInfo {
     }
 }
 
+/// Work performed by a node the graph inserted to convert a resource into the specification its
+/// readers require
+#[derive(Debug, Copy, Clone)]
+pub enum RenderGraphNodeConversion {
+    BlitImage {
+        src: RenderGraphImageUsageId,
+        dst: RenderGraphImageUsageId,
+    },
+    CopyBuffer {
+        src: RenderGraphBufferUsageId,
+        dst: RenderGraphBufferUsageId,
+    },
+}
+
 //
 // Graph nodes represent a "schedulable" event, generally a renderpass. It reads/writes resources.
 //
@@ -140,6 +154,13 @@ pub struct RenderGraphNode {
     pub(super) resolve_attachments: Vec<Option<RenderGraphPassResolveAttachmentInfo>>,
 
     pub(super) sampled_images: Vec<RenderGraphImageUsageId>,
+
+    // Indexed by input attachment index. If any can't be read as a subpass input, they are all
+    // moved to sampled_images when the plan is built
+    pub(super) input_attachments: Vec<Option<RenderGraphImageUsageId>>,
+
+    // Set if the graph inserted this node to blit or copy a resource
+    pub(super) conversion: Option<RenderGraphNodeConversion>,
 }
 
 impl std::fmt::Debug for RenderGraphNode {
@@ -160,6 +181,8 @@ impl std::fmt::Debug for RenderGraphNode {
             .field("depth_attachment", &self.depth_attachment)
             .field("resolve_attachments", &self.resolve_attachments)
             .field("sampled_images", &self.sampled_images)
+            .field("input_attachments", &self.input_attachments)
+            .field("conversion", &self.conversion)
             .finish()
     }
 }
@@ -185,6 +208,8 @@ impl RenderGraphNode {
             depth_attachment: Default::default(),
             resolve_attachments: Default::default(),
             sampled_images: Default::default(),
+            input_attachments: Default::default(),
+            conversion: None,
         }
     }
 
@@ -1,12 +1,14 @@
 use crate::graph::graph_buffer::PhysicalBufferId;
-use crate::graph::graph_image::{PhysicalImageId, PhysicalImageViewId, VirtualImageId};
+use crate::graph::graph_image::{
+    PhysicalImageId, PhysicalImageViewId, RenderGraphImageSubresource, VirtualImageId,
+};
 use crate::graph::graph_node::RenderGraphNodeName;
-use crate::graph::{RenderGraphImageUsageId, RenderGraphNodeId};
+use crate::graph::{RenderGraphImageUsageId, RenderGraphNodeId, RenderGraphQueue};
 use crate::GraphicsPipelineRenderTargetMeta;
 use fnv::FnvHashMap;
 use rafx_api::{
     RafxColorClearValue, RafxDepthStencilClearValue, RafxFormat, RafxLoadOp, RafxResourceState,
-    RafxSampleCount, RafxStoreOp,
+    RafxSampleCount, RafxStoreOp, RafxSubpassDef,
 };
 
 /// Information provided per image used in a pass to properly synchronize access to it from
@@ -26,7 +28,8 @@ impl RenderGraphPassImageBarriers {
 /// merged to be subpasses within a single pass.
 #[derive(Debug)]
 pub struct RenderGraphNodeResourceBarriers {
-    pub(super) image_barriers: FnvHashMap<PhysicalImageId, RenderGraphPassImageBarriers>,
+    pub(super) image_barriers:
+        FnvHashMap<(PhysicalImageId, RenderGraphImageSubresource), RenderGraphPassImageBarriers>,
     pub(super) buffer_barriers: FnvHashMap<PhysicalBufferId, RenderGraphPassBufferBarriers>,
 }
 
@@ -128,11 +131,34 @@ pub struct PostpassBarrier {
     // resolve? probably do that in rafx api level
 }
 
+/// Transfers ownership of a resource between queues. This is resolved to a
+/// RafxBarrierQueueTransition when the graph is executed, once the RafxQueue that will run each
+/// RenderGraphQueue is known
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum RenderGraphQueueTransition {
+    #[default]
+    None,
+    ReleaseTo(RenderGraphQueue),
+    AcquireFrom(RenderGraphQueue),
+}
+
 #[derive(Debug)]
 pub struct PrepassImageBarrier {
     pub image: PhysicalImageId,
+    pub array_slice: Option<u16>,
+    pub mip_slice: Option<u8>,
     pub old_state: RafxResourceState,
     pub new_state: RafxResourceState,
+    pub queue_transition: RenderGraphQueueTransition,
+}
+
+impl PrepassImageBarrier {
+    pub fn subresource(&self) -> RenderGraphImageSubresource {
+        RenderGraphImageSubresource {
+            array_slice: self.array_slice,
+            mip_slice: self.mip_slice,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -140,17 +166,27 @@ pub struct PrepassBufferBarrier {
     pub buffer: PhysicalBufferId,
     pub old_state: RafxResourceState,
     pub new_state: RafxResourceState,
+    pub queue_transition: RenderGraphQueueTransition,
 }
 
-/// Metadata required to create a renderpass
+/// A single node within a renderpass. All indices refer to the attachments of the renderpass that
+/// contains this subpass.
 #[derive(Debug)]
-pub struct RenderGraphRenderPass {
+pub struct RenderGraphSubpass {
     pub(super) node_id: RenderGraphNodeId,
-    pub(super) attachments: Vec<RenderGraphPassAttachment>,
 
     pub(super) color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS], // could ref back to node
     pub(super) resolve_attachments: [Option<usize>; MAX_RESOLVE_ATTACHMENTS],
     pub(super) depth_attachment: Option<usize>,
+    pub(super) input_attachments: Vec<Option<usize>>,
+}
+
+/// Metadata required to create a renderpass. Compatible nodes that run back-to-back are merged
+/// into a single renderpass as subpasses, in execution order
+#[derive(Debug)]
+pub struct RenderGraphRenderPass {
+    pub(super) subpasses: Vec<RenderGraphSubpass>,
+    pub(super) attachments: Vec<RenderGraphPassAttachment>,
 
     // For when we want to do layout transitions on non-attachments
     pub(super) pre_pass_barrier: Option<PrepassBarrier>,
@@ -161,6 +197,7 @@ pub struct RenderGraphRenderPass {
 pub struct RenderGraphComputePass {
     pub(super) node: RenderGraphNodeId,
     pub(super) pre_pass_barrier: Option<PrepassBarrier>,
+    pub(super) post_pass_barrier: Option<PostpassBarrier>,
 }
 
 #[derive(Debug)]
@@ -170,9 +207,19 @@ pub enum RenderGraphPass {
 }
 
 impl RenderGraphPass {
-    pub fn node(&self) -> RenderGraphNodeId {
+    /// All nodes executed by this pass, in execution order
+    pub fn nodes(&self) -> Vec<RenderGraphNodeId> {
+        match self {
+            RenderGraphPass::Renderpass(renderpass) => {
+                renderpass.subpasses.iter().map(|x| x.node_id).collect()
+            }
+            RenderGraphPass::Compute(compute_pass) => vec![compute_pass.node],
+        }
+    }
+
+    pub fn first_node(&self) -> RenderGraphNodeId {
         match self {
-            RenderGraphPass::Renderpass(renderpass) => renderpass.node_id,
+            RenderGraphPass::Renderpass(renderpass) => renderpass.subpasses[0].node_id,
             RenderGraphPass::Compute(compute_pass) => compute_pass.node,
         }
     }
@@ -188,6 +235,20 @@ impl RenderGraphPass {
             }
         }
     }
+
+    pub fn pre_pass_barrier_mut(&mut self) -> &mut Option<PrepassBarrier> {
+        match self {
+            RenderGraphPass::Renderpass(renderpass) => &mut renderpass.pre_pass_barrier,
+            RenderGraphPass::Compute(compute_pass) => &mut compute_pass.pre_pass_barrier,
+        }
+    }
+
+    pub fn post_pass_barrier_mut(&mut self) -> &mut Option<PostpassBarrier> {
+        match self {
+            RenderGraphPass::Renderpass(renderpass) => &mut renderpass.post_pass_barrier,
+            RenderGraphPass::Compute(compute_pass) => &mut compute_pass.post_pass_barrier,
+        }
+    }
 }
 
 pub struct RenderGraphColorRenderTarget {
@@ -214,15 +275,22 @@ pub struct RenderGraphDepthStencilRenderTarget {
     pub mip_slice: Option<u8>,
 }
 
-pub struct RenderGraphOutputRenderPass {
+pub struct RenderGraphOutputSubpass {
     pub(super) node_id: RenderGraphNodeId,
+    pub(super) debug_name: Option<RenderGraphNodeName>,
+    pub(super) render_target_meta: GraphicsPipelineRenderTargetMeta,
+}
+
+pub struct RenderGraphOutputRenderPass {
+    pub(super) subpasses: Vec<RenderGraphOutputSubpass>,
     pub(super) pre_pass_barrier: Option<PrepassBarrier>,
     pub(super) post_pass_barrier: Option<PostpassBarrier>,
     pub(super) debug_name: Option<RenderGraphNodeName>,
     pub(super) attachment_images: Vec<PhysicalImageViewId>,
     pub(super) color_render_targets: Vec<RenderGraphColorRenderTarget>,
     pub(super) depth_stencil_render_target: Option<RenderGraphDepthStencilRenderTarget>,
-    pub(super) render_target_meta: GraphicsPipelineRenderTargetMeta,
+    // Empty if the pass has a single subpass
+    pub(super) subpass_defs: Vec<RafxSubpassDef>,
 }
 
 impl std::fmt::Debug for RenderGraphOutputRenderPass {
@@ -253,10 +321,13 @@ pub enum RenderGraphOutputPass {
 }
 
 impl RenderGraphOutputPass {
-    pub fn node(&self) -> RenderGraphNodeId {
+    /// All nodes executed by this pass, in execution order
+    pub fn nodes(&self) -> Vec<RenderGraphNodeId> {
         match self {
-            RenderGraphOutputPass::Renderpass(pass) => pass.node_id,
-            RenderGraphOutputPass::Compute(pass) => pass.node,
+            RenderGraphOutputPass::Renderpass(pass) => {
+                pass.subpasses.iter().map(|x| x.node_id).collect()
+            }
+            RenderGraphOutputPass::Compute(pass) => vec![pass.node],
         }
     }
 
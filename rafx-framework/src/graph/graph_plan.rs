@@ -7,7 +7,11 @@ use crate::render_features::RenderPhaseIndex;
 use crate::{BufferResource, GraphicsPipelineRenderTargetMeta};
 use crate::{ImageViewResource, ResourceArc};
 use fnv::{FnvHashMap, FnvHashSet};
-use rafx_api::{RafxFormat, RafxLoadOp, RafxResourceState, RafxSampleCount, RafxStoreOp};
+use rafx_api::{
+    RafxCmdBlitParams, RafxDeviceInfo, RafxExtents3D, RafxFormat, RafxLoadOp, RafxResourceState,
+    RafxResourceType, RafxResult, RafxSampleCount, RafxStoreOp, RafxSubpassDef,
+    RafxSubpassInputAttachment, RafxSubpassLayout,
+};
 
 // Recursively called to topologically sort the nodes to determine execution order. See
 // determine_node_order which kicks this off.
@@ -19,10 +23,10 @@ fn visit_node(
     visiting: &mut Vec<bool>,
     visiting_stack: &mut Vec<RenderGraphNodeId>,
     ordered_list: &mut Vec<RenderGraphNodeId>,
-) {
+) -> RenderGraphResult<()> {
     // This node is already visited and inserted into ordered_list
     if visited[node_id.0] {
-        return;
+        return Ok(());
     }
 
     // This node is already being visited higher up in the stack. This indicates a cycle in the
@@ -33,7 +37,10 @@ fn visit_node(
         for v in visiting_stack.iter().rev() {
             log::trace!("{:?}", graph.node(*v));
         }
-        panic!("Graph has a cycle");
+        return Err(RenderGraphError::Cycle {
+            node: node_id,
+            node_name: graph.node(node_id).name(),
+        });
     }
 
     // When we enter the node, mark the node as being in-progress of being visited to help
@@ -51,63 +58,73 @@ fn visit_node(
     // Visit all the nodes we aren't delaying
     //
     for read in &node.image_reads {
-        let upstream_node = graph.image_version_info(read.image).creator_node;
-        visit_node(
-            graph,
-            upstream_node,
-            visited,
-            visiting,
-            visiting_stack,
-            ordered_list,
-        );
+        // External resources are not created by a node, so there is nothing upstream to visit
+        if let Some(upstream_node) = graph.image_version_info(read.image).creator_node {
+            visit_node(
+                graph,
+                upstream_node,
+                visited,
+                visiting,
+                visiting_stack,
+                ordered_list,
+            )?;
+        }
     }
 
     for modify in &node.image_modifies {
-        let upstream_node = graph.image_version_info(modify.input).creator_node;
-        visit_node(
-            graph,
-            upstream_node,
-            visited,
-            visiting,
-            visiting_stack,
-            ordered_list,
-        );
+        // External resources are not created by a node, so there is nothing upstream to visit
+        if let Some(upstream_node) = graph.image_version_info(modify.input).creator_node {
+            visit_node(
+                graph,
+                upstream_node,
+                visited,
+                visiting,
+                visiting_stack,
+                ordered_list,
+            )?;
+        }
     }
 
     for sampled_image in &node.sampled_images {
-        let upstream_node = graph.image_version_info(*sampled_image).creator_node;
-        visit_node(
-            graph,
-            upstream_node,
-            visited,
-            visiting,
-            visiting_stack,
-            ordered_list,
-        );
+        // External resources are not created by a node, so there is nothing upstream to visit
+        if let Some(upstream_node) = graph.image_version_info(*sampled_image).creator_node {
+            visit_node(
+                graph,
+                upstream_node,
+                visited,
+                visiting,
+                visiting_stack,
+                ordered_list,
+            )?;
+        }
     }
 
     for read in &node.buffer_reads {
-        let upstream_node = graph.buffer_version_info(read.buffer).creator_node;
-        visit_node(
-            graph,
-            upstream_node,
-            visited,
-            visiting,
-            visiting_stack,
-            ordered_list,
-        );
+        // External resources are not created by a node, so there is nothing upstream to visit
+        if let Some(upstream_node) = graph.buffer_version_info(read.buffer).creator_node {
+            visit_node(
+                graph,
+                upstream_node,
+                visited,
+                visiting,
+                visiting_stack,
+                ordered_list,
+            )?;
+        }
     }
 
     for modify in &node.buffer_modifies {
-        let upstream_node = graph.buffer_version_info(modify.input).creator_node;
-        visit_node(
-            graph,
-            upstream_node,
-            visited,
-            visiting,
-            visiting_stack,
-            ordered_list,
-        );
+        // External resources are not created by a node, so there is nothing upstream to visit
+        if let Some(upstream_node) = graph.buffer_version_info(modify.input).creator_node {
+            visit_node(
+                graph,
+                upstream_node,
+                visited,
+                visiting,
+                visiting_stack,
+                ordered_list,
+            )?;
+        }
     }
 
     // All our pre-requisites were visited, so it's now safe to push this node onto the
@@ -119,6 +136,8 @@ fn visit_node(
     //log::trace!("  End visit {:?}", node_id);
     visiting_stack.pop();
     visiting[node_id.0] = false;
+
+    Ok(())
 }
 
 //
@@ -126,7 +145,7 @@ fn visit_node(
 // by following the graph from the outputs backwards.
 //
 #[profiling::function]
-fn determine_node_order(graph: &RenderGraphBuilder) -> Vec<RenderGraphNodeId> {
+fn determine_node_order(graph: &RenderGraphBuilder) -> RenderGraphResult<Vec<RenderGraphNodeId>> {
     // As we depth-first traverse nodes, mark them as visiting and push them onto this stack.
     // We will use this to detect and print out cycles
     let mut visiting = vec![false; graph.nodes.len()];
@@ -140,48 +159,199 @@ fn determine_node_order(graph: &RenderGraphBuilder) -> Vec<RenderGraphNodeId> {
     // Iterate all the images we need to output. This will visit all the nodes we need to execute,
     // potentially leaving out nodes we can cull.
     for output_image_id in &graph.output_images {
-        // Find the node that creates the output image
-        let output_node = graph.image_version_info(output_image_id.usage).creator_node;
-        log::trace!(
-            "Traversing dependencies of output image created by node {:?} {:?}",
-            output_node,
-            graph.node(output_node).name()
-        );
+        // Find the node that creates the output image. (An external image passed straight
+        // through to an output has no creator.)
+        if let Some(output_node) = graph.image_version_info(output_image_id.usage).creator_node {
+            log::trace!(
+                "Traversing dependencies of output image created by node {:?} {:?}",
+                output_node,
+                graph.node(output_node).name()
+            );
 
-        visit_node(
-            graph,
-            output_node,
-            &mut visited,
-            &mut visiting,
-            &mut visiting_stack,
-            &mut ordered_list,
-        );
+            visit_node(
+                graph,
+                output_node,
+                &mut visited,
+                &mut visiting,
+                &mut visiting_stack,
+                &mut ordered_list,
+            )?;
+        }
     }
 
     // Iterate all the buffers we need to output. This will visit all the nodes we need to execute,
     // potentially leaving out nodes we can cull.
     for output_buffer_id in &graph.output_buffers {
         // Find the node that creates the output buffer
-        let output_node = graph
+        if let Some(output_node) = graph
             .buffer_version_info(output_buffer_id.usage)
-            .creator_node;
-        log::trace!(
-            "Traversing dependencies of output buffer created by node {:?} {:?}",
-            output_node,
-            graph.node(output_node).name()
-        );
+            .creator_node
+        {
+            log::trace!(
+                "Traversing dependencies of output buffer created by node {:?} {:?}",
+                output_node,
+                graph.node(output_node).name()
+            );
 
-        visit_node(
-            graph,
-            output_node,
-            &mut visited,
-            &mut visiting,
-            &mut visiting_stack,
-            &mut ordered_list,
-        );
+            visit_node(
+                graph,
+                output_node,
+                &mut visited,
+                &mut visiting,
+                &mut visiting_stack,
+                &mut ordered_list,
+            )?;
+        }
+    }
+
+    // Writes to external images and buffers persist after the graph executes, so the node that
+    // produces the last version of an external resource must not be culled
+    for external_image in &graph.external_images {
+        let versions = &graph.image_resource(external_image.usage).versions;
+        if let Some(last_write_node) = versions.last().unwrap().creator_node {
+            log::trace!(
+                "Traversing dependencies of external image modified by node {:?} {:?}",
+                last_write_node,
+                graph.node(last_write_node).name()
+            );
+
+            visit_node(
+                graph,
+                last_write_node,
+                &mut visited,
+                &mut visiting,
+                &mut visiting_stack,
+                &mut ordered_list,
+            )?;
+        }
+    }
+
+    for external_buffer in &graph.external_buffers {
+        let versions = &graph.buffer_resource(external_buffer.usage).versions;
+        if let Some(last_write_node) = versions.last().unwrap().creator_node {
+            log::trace!(
+                "Traversing dependencies of external buffer modified by node {:?} {:?}",
+                last_write_node,
+                graph.node(last_write_node).name()
+            );
+
+            visit_node(
+                graph,
+                last_write_node,
+                &mut visited,
+                &mut visiting,
+                &mut visiting_stack,
+                &mut ordered_list,
+            )?;
+        }
+    }
+
+    Ok(ordered_list)
+}
+
+//
+// Optional checks for mistakes in how the graph was built. Modifying the same version of a
+// resource from more than one node is an error. Writes that nothing reads are returned as warnings
+// since they only waste work.
+//
+#[profiling::function]
+fn validate_graph(
+    graph: &RenderGraphBuilder,
+    node_execution_order: &[RenderGraphNodeId],
+) -> RenderGraphResult<Vec<RenderGraphError>> {
+    log::trace!("Validate graph");
+
+    for image_resource in &graph.image_resources {
+        for version in &image_resource.versions {
+            let write_count = version
+                .read_usages
+                .iter()
+                .filter(|usage| !graph.image_usages[usage.0].usage_type.is_read_only())
+                .count();
+            if write_count > 1 {
+                return Err(image_write_after_write(graph, version.create_usage));
+            }
+        }
+    }
+
+    for buffer_resource in &graph.buffer_resources {
+        for version in &buffer_resource.versions {
+            let write_count = version
+                .read_usages
+                .iter()
+                .filter(|usage| !graph.buffer_usages[usage.0].usage_type.is_read_only())
+                .count();
+            if write_count > 1 {
+                return Err(buffer_write_after_write(graph, version.create_usage));
+            }
+        }
+    }
+
+    // Outputs always count as a reader, so a write is only unused if no scheduled node reads it
+    let is_scheduled = |node_id: &RenderGraphNodeId| node_execution_order.contains(node_id);
+
+    let mut warnings = Vec::default();
+    for node_id in node_execution_order {
+        let node = graph.node(*node_id);
+
+        let written_images = node
+            .image_creates
+            .iter()
+            .map(|x| x.image)
+            .chain(node.image_modifies.iter().map(|x| x.output));
+        for written_image in written_images {
+            let is_read = graph
+                .image_version_info(written_image)
+                .read_usages
+                .iter()
+                .any(|usage| match &graph.image_usages[usage.0].user {
+                    RenderGraphImageUser::Node(reader) => is_scheduled(reader),
+                    RenderGraphImageUser::Output(_) => true,
+                    RenderGraphImageUser::Input(_) => true,
+                });
+
+            if !is_read {
+                warnings.push(RenderGraphError::UnusedImageWrite {
+                    node: *node_id,
+                    node_name: node.name(),
+                    image: written_image,
+                    image_name: graph.image_resource(written_image).name,
+                });
+            }
+        }
+
+        let written_buffers = node
+            .buffer_creates
+            .iter()
+            .map(|x| x.buffer)
+            .chain(node.buffer_modifies.iter().map(|x| x.output));
+        for written_buffer in written_buffers {
+            let is_read = graph
+                .buffer_version_info(written_buffer)
+                .read_usages
+                .iter()
+                .any(|usage| match &graph.buffer_usages[usage.0].user {
+                    RenderGraphBufferUser::Node(reader) => is_scheduled(reader),
+                    RenderGraphBufferUser::Output(_) => true,
+                    RenderGraphBufferUser::Input(_) => true,
+                });
+
+            if !is_read {
+                warnings.push(RenderGraphError::UnusedBufferWrite {
+                    node: *node_id,
+                    node_name: node.name(),
+                    buffer: written_buffer,
+                    buffer_name: graph.buffer_resource(written_buffer).name,
+                });
+            }
+        }
+    }
+
+    for warning in &warnings {
+        log::warn!("{}", warning);
     }
 
-    ordered_list
+    Ok(warnings)
 }
 
 /// The specification for the image by image usage
@@ -219,7 +389,7 @@ impl DetermineConstraintsResult {
 fn determine_constraints(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
-) -> DetermineConstraintsResult {
+) -> RenderGraphResult<DetermineConstraintsResult> {
     let mut image_version_states: FnvHashMap<RenderGraphImageUsageId, RenderGraphImageConstraint> =
         Default::default();
 
@@ -236,19 +406,19 @@ fn determine_constraints(
     // Propagate input image state specifications into images. Inputs are fully specified and
     // their constraints will never be overwritten
     //
-    // for input_image in &graph.input_images {
-    //     log::trace!(
-    //         "    Image {:?} {:?}",
-    //         input_image,
-    //         graph.image_resource(input_image.usage).name
-    //     );
-    //     image_version_states
-    //         .entry(graph.get_create_usage(input_image.usage))
-    //         .or_default()
-    //         .set(&input_image.specification);
-    //
-    //     // Don't bother setting usage constraint for 0
-    // }
+    for external_image in &graph.external_images {
+        log::trace!(
+            "    Image {:?} {:?}",
+            external_image,
+            graph.image_resource(external_image.usage).name
+        );
+        image_version_states
+            .entry(graph.image_version_create_usage(external_image.usage))
+            .or_default()
+            .set(&external_image.specification);
+
+        // Don't bother setting usage constraint for 0
+    }
 
     log::trace!("  Set up input buffers");
 
@@ -256,19 +426,19 @@ fn determine_constraints(
     // Propagate input buffer state specifications into buffers. Inputs are fully specified and
     // their constraints will never be overwritten
     //
-    // for input_buffer in &graph.input_buffers {
-    //     log::trace!(
-    //         "    Buffer {:?} {:?}",
-    //         input_buffer,
-    //         graph.buffer_resource(input_buffer.usage).name
-    //     );
-    //     buffer_version_states
-    //         .entry(graph.get_create_usage(input_buffer.usage))
-    //         .or_default()
-    //         .set(&input_buffer.specification);
-    //
-    //     // Don't bother setting usage constraint for 0
-    // }
+    for external_buffer in &graph.external_buffers {
+        log::trace!(
+            "    Buffer {:?} {:?}",
+            external_buffer,
+            graph.buffer_resource(external_buffer.usage).name
+        );
+        buffer_version_states
+            .entry(graph.buffer_version_create_usage(external_buffer.usage))
+            .or_default()
+            .set(&external_buffer.specification);
+
+        // Don't bother setting usage constraint for 0
+    }
 
     log::trace!("  Propagate constraints FORWARD");
 
@@ -287,12 +457,8 @@ fn determine_constraints(
         //
         for image_create in &node.image_creates {
             // An image cannot be created within the graph and imported externally at the same
-            // time. (The code here assumes no input and will not produce correct results if there
-            // is an input image)
-            //TODO: Input images are broken, we don't properly represent an image being created
-            // vs. receiving an input. We probably need to make creator in
-            // RenderGraphImageResourceVersionInfo Option or an enum with input/create options
-            //assert!(graph.image_version_info(image_create.image).input_image.is_none());
+            // time. External images are a separate resource with no creator node
+            debug_assert!(!graph.is_external_image(image_create.image));
 
             log::trace!(
                 "      Create image {:?} {:?}",
@@ -306,7 +472,12 @@ fn determine_constraints(
 
             if !version_state.try_merge(&image_create.constraint) {
                 // Should not happen as this should be our first visit to this image
-                panic!("Unexpected constraints on image being created");
+                return Err(RenderGraphError::ConflictingImageConstraints {
+                    node: *node_id,
+                    node_name: node.name(),
+                    image: image_create.image,
+                    image_name: graph.image_resource(image_create.image).name,
+                });
             }
 
             log::trace!(
@@ -323,12 +494,8 @@ fn determine_constraints(
         //
         for buffer_create in &node.buffer_creates {
             // A buffer cannot be created within the graph and imported externally at the same
-            // time. (The code here assumes no input and will not produce correct results if there
-            // is an input buffer)
-            //TODO: Input buffers are broken, we don't properly represent a buffer being created
-            // vs. receiving an input. We probably need to make creator in
-            // RenderGraphImageResourceVersionInfo Option or an enum with input/create options
-            //assert!(graph.buffer_version_info(buffer_create.buffer).input_buffer.is_none());
+            // time. External buffers are a separate resource with no creator node
+            debug_assert!(!graph.is_external_buffer(buffer_create.buffer));
 
             log::trace!(
                 "      Create buffer {:?} {:?}",
@@ -342,7 +509,12 @@ fn determine_constraints(
 
             if !version_state.try_merge(&buffer_create.constraint) {
                 // Should not happen as this should be our first visit to this buffer
-                panic!("Unexpected constraints on buffer being created");
+                return Err(RenderGraphError::ConflictingBufferConstraints {
+                    node: *node_id,
+                    node_name: node.name(),
+                    buffer: buffer_create.buffer,
+                    buffer_name: graph.buffer_resource(buffer_create.buffer).name,
+                });
             }
 
             log::trace!(
@@ -506,14 +678,11 @@ fn determine_constraints(
             if let Some(spec) = image_read_constraint.try_convert_to_specification() {
                 image_version_states.insert(image_read.image, spec.into());
             } else {
-                panic!(
-                    "Not enough information in the graph to determine the specification for image {:?} {:?} being read by node {:?} {:?}. Constraints are: {:?}",
-                    image_read.image,
-                    graph.image_resource(image_read.image).name,
-                    node.id(),
-                    node.name(),
+                log::trace!(
+                    "        Constraints are: {:?}",
                     image_version_states.get(&image_read.image)
                 );
+                return Err(unknown_image_specification(graph, image_read.image));
             }
         }
 
@@ -546,14 +715,11 @@ fn determine_constraints(
             if let Some(spec) = buffer_read_constraint.try_convert_to_specification() {
                 buffer_version_states.insert(buffer_read.buffer, spec.into());
             } else {
-                panic!(
-                    "Not enough information in the graph to determine the specification for buffer {:?} {:?} being read by node {:?} {:?}. Constraints are: {:?}",
-                    buffer_read.buffer,
-                    graph.buffer_resource(buffer_read.buffer).name,
-                    node.id(),
-                    node.name(),
+                log::trace!(
+                    "        Constraints are: {:?}",
                     buffer_version_states.get(&buffer_read.buffer)
                 );
+                return Err(unknown_buffer_specification(graph, buffer_read.buffer));
             }
         }
 
@@ -610,17 +776,61 @@ fn determine_constraints(
 
     let mut image_specs = FnvHashMap::default();
     for (k, v) in image_version_states {
-        image_specs.insert(k, v.try_convert_to_specification().unwrap());
+        let spec = v
+            .try_convert_to_specification()
+            .ok_or_else(|| unknown_image_specification(graph, k))?;
+        image_specs.insert(k, spec);
     }
 
     let mut buffer_specs = FnvHashMap::default();
     for (k, v) in buffer_version_states {
-        buffer_specs.insert(k, v.try_convert_to_specification().unwrap());
+        let spec = v
+            .try_convert_to_specification()
+            .ok_or_else(|| unknown_buffer_specification(graph, k))?;
+        buffer_specs.insert(k, spec);
     }
 
-    DetermineConstraintsResult {
+    Ok(DetermineConstraintsResult {
         images: image_specs,
         buffers: buffer_specs,
+    })
+}
+
+fn unknown_image_specification(
+    graph: &RenderGraphBuilder,
+    usage: RenderGraphImageUsageId,
+) -> RenderGraphError {
+    let usage_info = &graph.image_usages[usage.0];
+    let node = match usage_info.user {
+        RenderGraphImageUser::Node(node) => Some(node),
+        _ => None,
+    };
+
+    RenderGraphError::UnknownImageSpecification {
+        node,
+        node_name: node.and_then(|node| graph.node(node).name()),
+        image: usage,
+        image_name: graph.image_resource(usage).name,
+        usage_type: usage_info.usage_type,
+    }
+}
+
+fn unknown_buffer_specification(
+    graph: &RenderGraphBuilder,
+    usage: RenderGraphBufferUsageId,
+) -> RenderGraphError {
+    let usage_info = graph.buffer_usage(usage);
+    let node = match usage_info.user {
+        RenderGraphBufferUser::Node(node) => Some(node),
+        _ => None,
+    };
+
+    RenderGraphError::UnknownBufferSpecification {
+        node,
+        node_name: node.and_then(|node| graph.node(node).name()),
+        buffer: usage,
+        buffer_name: graph.buffer_resource(usage).name,
+        usage_type: usage_info.usage_type,
     }
 }
 
@@ -634,8 +844,9 @@ fn insert_resolves(
     graph: &mut RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
     constraint_results: &mut DetermineConstraintsResult,
-) {
+) -> Vec<RenderGraphPlanConversion> {
     log::trace!("Insert resolves in graph where necessary");
+    let mut conversions = Vec::default();
     for node_id in node_execution_order {
         let mut resolves_to_add = Vec::default();
 
@@ -678,9 +889,10 @@ fn insert_resolves(
                             constraint_results.image_specification(*read_usage).unwrap();
                         if *read_spec == *write_spec {
                             continue;
-                        } else if *read_spec == resolve_spec {
+                        } else if read_spec.samples == RafxSampleCount::SampleCount1 {
+                            // If the read needs other changes, insert_conversions will blit the
+                            // resolved image
                             usages_to_move.push(*read_usage);
-                            break;
                         } else {
                             log::trace!(
                                 "        incompatibility cannot be fixed via renderpass resolve"
@@ -693,6 +905,7 @@ fn insert_resolves(
                     if !usages_to_move.is_empty() {
                         resolves_to_add.push((
                             color_attachment_index,
+                            write_image,
                             resolve_spec,
                             usages_to_move,
                         ));
@@ -701,7 +914,8 @@ fn insert_resolves(
             }
         }
 
-        for (resolve_attachment_index, resolve_spec, usages_to_move) in resolves_to_add {
+        for (resolve_attachment_index, write_image, resolve_spec, usages_to_move) in resolves_to_add
+        {
             log::trace!(
                 "        ADDING RESOLVE FOR NODE {:?} ATTACHMENT {}",
                 node_id,
@@ -726,8 +940,366 @@ fn insert_resolves(
                 );
                 graph.redirect_image_usage(usage, from, to)
             }
+
+            conversions.push(RenderGraphPlanConversion {
+                kind: RenderGraphConversionKind::Resolve,
+                node_id: *node_id,
+                resource_name: graph.image_resource(write_image).name,
+            });
+        }
+    }
+
+    conversions
+}
+
+//
+// This function finds images and buffers that are read with a different specification than they
+// were written with, which can happen when constraints conflict (for example, an image rendered
+// at one size/format and sampled at another.) A node is inserted for every distinct
+// specification the readers need. It blits the image (or copies the buffer) into a new resource,
+// and the reads are redirected to it. Limitations:
+// - Images can only be converted if the device supports blits (RafxDeviceInfo::supports_blit_images)
+// - Blits only write the first mip level, so the new image can't have more than one mip level
+// - The sample count and layer count of an image can't change (MSAA color attachments are
+//   resolved by insert_resolves instead)
+// - Depth/stencil images can't be blitted
+// - A buffer can only be copied into a buffer that is the same size or smaller
+//
+#[profiling::function]
+fn insert_conversions(
+    graph: &mut RenderGraphBuilder,
+    device_info: &RafxDeviceInfo,
+    node_execution_order: &mut Vec<RenderGraphNodeId>,
+    constraint_results: &mut DetermineConstraintsResult,
+) -> RenderGraphResult<Vec<RenderGraphPlanConversion>> {
+    log::trace!("Insert conversions in graph where necessary");
+
+    // Every image/buffer version produced by a node or passed into the graph
+    let mut written_images = Vec::default();
+    let mut written_buffers = Vec::default();
+    for external_image in &graph.external_images {
+        written_images.push(external_image.usage);
+    }
+
+    for external_buffer in &graph.external_buffers {
+        written_buffers.push(external_buffer.usage);
+    }
+
+    for node_id in node_execution_order.iter() {
+        let node = graph.node(*node_id);
+        written_images.extend(node.image_creates.iter().map(|x| x.image));
+        written_images.extend(node.image_modifies.iter().map(|x| x.output));
+        written_buffers.extend(node.buffer_creates.iter().map(|x| x.buffer));
+        written_buffers.extend(node.buffer_modifies.iter().map(|x| x.output));
+    }
+
+    let node_order_index: FnvHashMap<RenderGraphNodeId, usize> = node_execution_order
+        .iter()
+        .enumerate()
+        .map(|(index, node_id)| (*node_id, index))
+        .collect();
+
+    // The inserted nodes, and the node each must be scheduled before
+    let mut conversion_nodes: Vec<(Option<RenderGraphNodeId>, RenderGraphNodeId)> = Vec::default();
+    let mut conversions = Vec::default();
+
+    for written_image in written_images {
+        let write_spec = match constraint_results.image_specification(written_image) {
+            Some(write_spec) => write_spec.clone(),
+            None => continue,
+        };
+
+        // Group the reads that need a different specification by the specification they need
+        let mut reads_by_spec: Vec<(RenderGraphImageSpecification, Vec<RenderGraphImageUsageId>)> =
+            Vec::default();
+        let read_usages = &graph.image_version_info(written_image).read_usages;
+        for read_usage in read_usages {
+            // If the reader of this image was culled, we may not have determined a spec
+            let read_spec = match constraint_results.image_specification(*read_usage) {
+                Some(read_spec) => read_spec,
+                None => continue,
+            };
+
+            // Reads that only differ in resource type can share the written image
+            if write_spec.can_merge(read_spec) {
+                continue;
+            }
+
+            if let Some((_, usages)) = reads_by_spec.iter_mut().find(|(spec, _)| spec == read_spec)
+            {
+                usages.push(*read_usage);
+            } else {
+                reads_by_spec.push((read_spec.clone(), vec![*read_usage]));
+            }
+        }
+
+        if reads_by_spec.is_empty() {
+            continue;
+        }
+
+        // A conversion must run before any node reads (or modifies) the version it copies from.
+        // This is None if only outputs read the version.
+        let before_node = read_usages
+            .iter()
+            .filter_map(|usage| match graph.image_usages[usage.0].user {
+                RenderGraphImageUser::Node(node_id) => Some(node_id),
+                _ => None,
+            })
+            .min_by_key(|node_id| node_order_index[node_id]);
+
+        let resource_name = graph.image_resource(written_image).name;
+        for (read_spec, usages_to_move) in reads_by_spec {
+            log::trace!(
+                "  Image {:?} {:?} needs a blit",
+                written_image,
+                resource_name
+            );
+            log::trace!("    written: {:?}", write_spec);
+            log::trace!("    read   : {:?}", read_spec);
+
+            let unsupported_reason = if !device_info.supports_blit_images {
+                Some("blitting images is not supported by this device")
+            } else if write_spec.samples != RafxSampleCount::SampleCount1
+                || read_spec.samples != RafxSampleCount::SampleCount1
+            {
+                // Only MSAA color attachments can be resolved to a non-MSAA image
+                Some("multisampled images can't be blitted")
+            } else if write_spec.format.has_depth_or_stencil()
+                || read_spec.format.has_depth_or_stencil()
+            {
+                Some("depth/stencil images can't be blitted")
+            } else if write_spec.layer_count != read_spec.layer_count {
+                Some("a blit can't change the layer count")
+            } else if read_spec.mip_count != 1 {
+                Some("a blit only writes the first mip level")
+            } else {
+                None
+            };
+
+            if let Some(reason) = unsupported_reason {
+                return Err(RenderGraphError::IncompatibleImageSpecification {
+                    image: written_image,
+                    image_name: resource_name,
+                    usage: usages_to_move[0],
+                    written: write_spec,
+                    read: read_spec,
+                    reason,
+                });
+            }
+
+            let node_id = graph.add_node("Blit image", RenderGraphQueue::DefaultGraphics);
+            let src = graph.add_image_read(
+                node_id,
+                written_image,
+                write_spec.clone().into(),
+                Default::default(),
+            );
+            constraint_results.images.insert(src, write_spec.clone());
+
+            let dst = graph.add_image_create(node_id, read_spec.clone().into(), Default::default());
+            constraint_results.images.insert(dst, read_spec);
+            if let Some(resource_name) = resource_name {
+                graph.set_image_name(dst, resource_name);
+            }
+
+            graph.node_mut(node_id).conversion =
+                Some(RenderGraphNodeConversion::BlitImage { src, dst });
+            graph.set_compute_callback(node_id, move |args| blit_image(args, src, dst));
+
+            for usage in usages_to_move {
+                let from = graph.image_usages[usage.0].version;
+                let to = graph.image_usages[dst.0].version;
+                log::trace!("    MOVE USAGE {:?} from {:?} to {:?}", usage, from, to);
+                graph.redirect_image_usage(usage, from, to);
+            }
+
+            conversion_nodes.push((before_node, node_id));
+            conversions.push(RenderGraphPlanConversion {
+                kind: RenderGraphConversionKind::BlitImage,
+                node_id,
+                resource_name,
+            });
+        }
+    }
+
+    for written_buffer in written_buffers {
+        let write_spec = match constraint_results.buffer_specification(written_buffer) {
+            Some(write_spec) => write_spec.clone(),
+            None => continue,
+        };
+
+        // Group the reads that need a different specification by the specification they need
+        let mut reads_by_spec: Vec<(
+            RenderGraphBufferSpecification,
+            Vec<RenderGraphBufferUsageId>,
+        )> = Vec::default();
+        let read_usages = &graph.buffer_version_info(written_buffer).read_usages;
+        for read_usage in read_usages {
+            // If the reader of this buffer was culled, we may not have determined a spec
+            let read_spec = match constraint_results.buffer_specification(*read_usage) {
+                Some(read_spec) => read_spec,
+                None => continue,
+            };
+
+            // Reads that only differ in resource type can share the written buffer
+            if write_spec.can_merge(read_spec) {
+                continue;
+            }
+
+            if let Some((_, usages)) = reads_by_spec.iter_mut().find(|(spec, _)| spec == read_spec)
+            {
+                usages.push(*read_usage);
+            } else {
+                reads_by_spec.push((read_spec.clone(), vec![*read_usage]));
+            }
+        }
+
+        if reads_by_spec.is_empty() {
+            continue;
+        }
+
+        // A conversion must run before any node reads (or modifies) the version it copies from.
+        // This is None if only outputs read the version.
+        let before_node = read_usages
+            .iter()
+            .filter_map(|usage| match graph.buffer_usages[usage.0].user {
+                RenderGraphBufferUser::Node(node_id) => Some(node_id),
+                _ => None,
+            })
+            .min_by_key(|node_id| node_order_index[node_id]);
+
+        let resource_name = graph.buffer_resource(written_buffer).name;
+        for (read_spec, usages_to_move) in reads_by_spec {
+            log::trace!(
+                "  Buffer {:?} {:?} needs a copy",
+                written_buffer,
+                resource_name
+            );
+            log::trace!("    written: {:?}", write_spec);
+            log::trace!("    read   : {:?}", read_spec);
+
+            if read_spec.size > write_spec.size {
+                return Err(RenderGraphError::IncompatibleBufferSpecification {
+                    buffer: written_buffer,
+                    buffer_name: resource_name,
+                    usage: usages_to_move[0],
+                    written: write_spec,
+                    read: read_spec,
+                    reason: "a buffer copy can't fill a larger buffer",
+                });
+            }
+
+            let node_id = graph.add_node("Copy buffer", RenderGraphQueue::DefaultGraphics);
+            let src = graph.add_buffer_read(node_id, written_buffer, write_spec.clone().into());
+            constraint_results.buffers.insert(src, write_spec.clone());
+
+            let dst = graph.add_buffer_create(node_id, read_spec.clone().into());
+            constraint_results.buffers.insert(dst, read_spec);
+            if let Some(resource_name) = resource_name {
+                graph.set_buffer_name(dst, resource_name);
+            }
+
+            graph.node_mut(node_id).conversion =
+                Some(RenderGraphNodeConversion::CopyBuffer { src, dst });
+            graph.set_compute_callback(node_id, move |args| copy_buffer(args, src, dst));
+
+            for usage in usages_to_move {
+                let from = graph.buffer_usages[usage.0].version;
+                let to = graph.buffer_usages[dst.0].version;
+                log::trace!("    MOVE USAGE {:?} from {:?} to {:?}", usage, from, to);
+                graph.redirect_buffer_usage(usage, from, to);
+            }
+
+            conversion_nodes.push((before_node, node_id));
+            conversions.push(RenderGraphPlanConversion {
+                kind: RenderGraphConversionKind::CopyBuffer,
+                node_id,
+                resource_name,
+            });
+        }
+    }
+
+    //
+    // Schedule the inserted nodes. Conversions only needed by outputs run at the end of the graph
+    //
+    if !conversion_nodes.is_empty() {
+        let mut order = Vec::with_capacity(node_execution_order.len() + conversion_nodes.len());
+        for node_id in node_execution_order.iter() {
+            order.extend(
+                conversion_nodes
+                    .iter()
+                    .filter(|(before_node, _)| *before_node == Some(*node_id))
+                    .map(|(_, conversion_node)| *conversion_node),
+            );
+            order.push(*node_id);
         }
+
+        order.extend(
+            conversion_nodes
+                .iter()
+                .filter(|(before_node, _)| before_node.is_none())
+                .map(|(_, conversion_node)| *conversion_node),
+        );
+
+        *node_execution_order = order;
     }
+
+    Ok(conversions)
+}
+
+// Callback for nodes inserted by insert_conversions to blit an image
+fn blit_image(
+    args: VisitComputeNodeArgs,
+    src: RenderGraphImageUsageId,
+    dst: RenderGraphImageUsageId,
+) -> RafxResult<()> {
+    let src_texture = args
+        .graph_context
+        .image_view(src)
+        .unwrap()
+        .get_raw()
+        .image
+        .get_raw()
+        .image;
+    let dst_texture = args
+        .graph_context
+        .image_view(dst)
+        .unwrap()
+        .get_raw()
+        .image
+        .get_raw()
+        .image;
+
+    args.command_buffer.cmd_blit_image(
+        &src_texture,
+        &dst_texture,
+        &RafxCmdBlitParams {
+            src_state: RafxResourceState::COPY_SRC,
+            dst_state: RafxResourceState::COPY_DST,
+            src_extents: [RafxExtents3D::default(), src_texture.texture_def().extents],
+            dst_extents: [RafxExtents3D::default(), dst_texture.texture_def().extents],
+            src_mip_level: 0,
+            dst_mip_level: 0,
+            array_slices: None,
+        },
+    )
+}
+
+// Callback for nodes inserted by insert_conversions to copy a buffer
+fn copy_buffer(
+    args: VisitComputeNodeArgs,
+    src: RenderGraphBufferUsageId,
+    dst: RenderGraphBufferUsageId,
+) -> RafxResult<()> {
+    let src_buffer = args.graph_context.buffer(src).unwrap().get_raw().buffer;
+    let dst_buffer = args.graph_context.buffer(dst).unwrap().get_raw().buffer;
+    let size = src_buffer
+        .buffer_def()
+        .size
+        .min(dst_buffer.buffer_def().size);
+
+    args.command_buffer
+        .cmd_copy_buffer_to_buffer(&src_buffer, &dst_buffer, 0, 0, size)
 }
 
 /// Assignment of usages to actual images. This allows a single image to be passed through a
@@ -741,16 +1313,16 @@ pub struct AssignVirtualResourcesResult {
 //
 // The graph is built with the assumption that every image is immutable. However in most cases we
 // can easily pass the same image through multiple passes saving memory and the need to copy data.
-// This function finds places where we can trivially forward an image from one pass to another. In
-// the future, cases where this is not possible might be handled by copying the image. (Needed if
-// there are multiple downstream consumers modifying the image or if the format needs to change.
+// This function finds places where we can trivially forward an image from one pass to another.
+// Reads that need a different format/size were already redirected to a copy of the resource by
+// insert_conversions. Multiple downstream consumers modifying the same image are not supported yet.
 //
 #[profiling::function]
 fn assign_virtual_resources(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
     constraint_results: &mut DetermineConstraintsResult,
-) -> AssignVirtualResourcesResult {
+) -> RenderGraphResult<AssignVirtualResourcesResult> {
     #[derive(Default)]
     struct VirtualImageIdAllocator {
         next_id: usize,
@@ -785,23 +1357,116 @@ fn assign_virtual_resources(
     let mut virtual_image_id_allocator = VirtualImageIdAllocator::default();
     let mut virtual_buffer_id_allocator = VirtualBufferIdAllocator::default();
 
-    //TODO: Associate input images here? We can wait until we decide which images are shared
-    log::trace!("Associate images written by nodes with virtual images");
-    for node in node_execution_order.iter() {
-        let node = graph.node(*node);
-        log::trace!("  node {:?} {:?}", node.id().0, node.name());
+    //
+    // External images and buffers always get their own virtual resource. All usages of the initial
+    // version share it, since the graph can't copy the data into a different resource
+    //
+    log::trace!("Associate external images with virtual images");
+    for external_image in &graph.external_images {
+        let virtual_image = virtual_image_id_allocator.allocate();
+        log::trace!(
+            "  External {:?} will use image {:?}",
+            external_image.usage,
+            virtual_image
+        );
+        image_usage_to_virtual.insert(external_image.usage, virtual_image);
+
+        let external_spec = constraint_results
+            .image_specification(external_image.usage)
+            .unwrap();
+        let external_version_info = graph.image_version_info(external_image.usage);
+        let write_count = external_version_info
+            .read_usages
+            .iter()
+            .filter(|usage| !graph.image_usages[usage.0].usage_type.is_read_only())
+            .count();
+        if write_count > 1 {
+            // The render graph cannot copy an external image to give each writer its own image
+            return Err(image_write_after_write(graph, external_image.usage));
+        }
 
-        // A list of all images we write to from this node. We will try to share the images
-        // being written forward into the nodes of downstream reads. This can chain such that
-        // the same image is shared by many nodes
-        let mut written_images = vec![];
-        let mut written_buffers = vec![];
+        for usage_resource_id in &external_version_info.read_usages {
+            // If the reader of this image was culled, we may not have determined a spec
+            if let Some(usage_spec) = constraint_results.image_specification(*usage_resource_id) {
+                if *usage_spec != *external_spec {
+                    return Err(RenderGraphError::IncompatibleImageSpecification {
+                        image: external_image.usage,
+                        image_name: graph.image_resource(external_image.usage).name,
+                        usage: *usage_resource_id,
+                        written: external_spec.clone(),
+                        read: usage_spec.clone(),
+                        reason: "an external image can't be converted",
+                    });
+                }
 
-        //
-        // Handle images created by this node
-        //
-        for image_create in &node.image_creates {
-            // An image that's created always allocates an image (we reuse these if they are compatible
+                let overwritten_image =
+                    image_usage_to_virtual.insert(*usage_resource_id, virtual_image);
+                assert!(overwritten_image.is_none());
+            }
+        }
+    }
+
+    log::trace!("Associate external buffers with virtual buffers");
+    for external_buffer in &graph.external_buffers {
+        let virtual_buffer = virtual_buffer_id_allocator.allocate();
+        log::trace!(
+            "  External {:?} will use buffer {:?}",
+            external_buffer.usage,
+            virtual_buffer
+        );
+        buffer_usage_to_virtual.insert(external_buffer.usage, virtual_buffer);
+
+        let external_spec = constraint_results
+            .buffer_specification(external_buffer.usage)
+            .unwrap();
+        let external_version_info = graph.buffer_version_info(external_buffer.usage);
+        let write_count = external_version_info
+            .read_usages
+            .iter()
+            .filter(|usage| !graph.buffer_usages[usage.0].usage_type.is_read_only())
+            .count();
+        if write_count > 1 {
+            // The render graph cannot copy an external buffer to give each writer its own buffer
+            return Err(buffer_write_after_write(graph, external_buffer.usage));
+        }
+
+        for usage_resource_id in &external_version_info.read_usages {
+            // If the reader of this buffer was culled, we may not have determined a spec
+            if let Some(usage_spec) = constraint_results.buffer_specification(*usage_resource_id) {
+                if *usage_spec != *external_spec {
+                    return Err(RenderGraphError::IncompatibleBufferSpecification {
+                        buffer: external_buffer.usage,
+                        buffer_name: graph.buffer_resource(external_buffer.usage).name,
+                        usage: *usage_resource_id,
+                        written: external_spec.clone(),
+                        read: usage_spec.clone(),
+                        reason: "an external buffer can't be converted",
+                    });
+                }
+
+                let overwritten_buffer =
+                    buffer_usage_to_virtual.insert(*usage_resource_id, virtual_buffer);
+                assert!(overwritten_buffer.is_none());
+            }
+        }
+    }
+
+    log::trace!("Associate images written by nodes with virtual images");
+    for node in node_execution_order.iter() {
+        let node = graph.node(*node);
+        log::trace!("  node {:?} {:?}", node.id().0, node.name());
+
+        // A list of all images we write to from this node. We will try to share the images
+        // being written forward into the nodes of downstream reads. This can chain such that
+        // the same image is shared by many nodes
+        let mut written_images = vec![];
+        let mut written_buffers = vec![];
+
+        //
+        // Handle images created by this node
+        //
+        for image_create in &node.image_creates {
+            // An image that's created always allocates an image (we reuse these if they are compatible
             // and lifetimes don't overlap)
             let virtual_image = virtual_image_id_allocator.allocate();
             log::trace!(
@@ -976,11 +1641,22 @@ fn assign_virtual_resources(
 
                     assert!(overwritten_image.is_none());
 
-                    //TODO: One issue (aside from not doing any blits right now) is that images created in this way
-                    // aren't included in the assign_physical_images logic
-                    println!("      written: {:?}", written_spec);
-                    println!("      usage  : {:?}", usage_spec);
-                    panic!("Render graph does not currently support blit from one image to another to fix image compatibility");
+                    //TODO: Images created in this way aren't included in the assign_physical_images
+                    // logic, and nothing copies the data into them. Conflicting specifications are
+                    // handled by insert_conversions, so this is only reached by multiple writers
+                    // or reads that differ only in resource type
+                    if !specifications_match {
+                        return Err(RenderGraphError::IncompatibleImageSpecification {
+                            image: written_image,
+                            image_name: graph.image_resource(written_image).name,
+                            usage: *usage_resource_id,
+                            written: written_spec.clone(),
+                            read: usage_spec.clone(),
+                            reason: "the image can't be shared or converted",
+                        });
+                    } else {
+                        return Err(image_write_after_write(graph, written_image));
+                    }
                 }
             }
         }
@@ -1066,19 +1742,342 @@ fn assign_virtual_resources(
 
                     assert!(overwritten_buffer.is_none());
 
-                    //TODO: One issue (aside from not doing any copies right now) is that buffers created in this way
-                    // aren't included in the assign_physical_buffers logic
-                    panic!("Render graph does not currently support blit from one buffer to another to fix buffer compatibility");
+                    //TODO: Buffers created in this way aren't included in the assign_physical_buffers
+                    // logic, and nothing copies the data into them. Conflicting specifications are
+                    // handled by insert_conversions, so this is only reached by multiple writers
+                    // or reads that differ only in resource type
+                    if !specifications_match {
+                        return Err(RenderGraphError::IncompatibleBufferSpecification {
+                            buffer: written_buffer,
+                            buffer_name: graph.buffer_resource(written_buffer).name,
+                            usage: *usage_resource_id,
+                            written: written_spec.clone(),
+                            read: usage_spec.clone(),
+                            reason: "the buffer can't be shared or converted",
+                        });
+                    } else {
+                        return Err(buffer_write_after_write(graph, written_buffer));
+                    }
                 }
             }
         }
     }
 
     // vulkan image layouts: https://github.com/nannou-org/nannou/issues/271#issuecomment-465876622
-    AssignVirtualResourcesResult {
+    Ok(AssignVirtualResourcesResult {
         image_usage_to_virtual,
         buffer_usage_to_virtual,
+    })
+}
+
+// Builds an error naming the first two nodes that modify the given version of an image
+fn image_write_after_write(
+    graph: &RenderGraphBuilder,
+    image: RenderGraphImageUsageId,
+) -> RenderGraphError {
+    let writers: Vec<_> = graph
+        .image_version_info(image)
+        .read_usages
+        .iter()
+        .filter(|usage| !graph.image_usages[usage.0].usage_type.is_read_only())
+        .filter_map(|usage| match graph.image_usages[usage.0].user {
+            RenderGraphImageUser::Node(node_id) => Some(node_id),
+            _ => None,
+        })
+        .collect();
+
+    RenderGraphError::ImageWriteAfterWrite {
+        image,
+        image_name: graph.image_resource(image).name,
+        first_node: writers[0],
+        first_node_name: graph.node(writers[0]).name(),
+        second_node: writers[1],
+        second_node_name: graph.node(writers[1]).name(),
+    }
+}
+
+// Builds an error naming the first two nodes that modify the given version of a buffer
+fn buffer_write_after_write(
+    graph: &RenderGraphBuilder,
+    buffer: RenderGraphBufferUsageId,
+) -> RenderGraphError {
+    let writers: Vec<_> = graph
+        .buffer_version_info(buffer)
+        .read_usages
+        .iter()
+        .filter(|usage| !graph.buffer_usages[usage.0].usage_type.is_read_only())
+        .filter_map(|usage| match graph.buffer_usages[usage.0].user {
+            RenderGraphBufferUser::Node(node_id) => Some(node_id),
+            _ => None,
+        })
+        .collect();
+
+    RenderGraphError::BufferWriteAfterWrite {
+        buffer,
+        buffer_name: graph.buffer_resource(buffer).name,
+        first_node: writers[0],
+        first_node_name: graph.node(writers[0]).name(),
+        second_node: writers[1],
+        second_node_name: graph.node(writers[1]).name(),
+    }
+}
+
+// Returns every image usage a node binds as a color, resolve, or depth attachment
+fn node_attachment_usages(node: &RenderGraphNode) -> Vec<RenderGraphImageUsageId> {
+    let mut usages = Vec::default();
+    for color_attachment in node.color_attachments.iter().flatten() {
+        usages.extend(color_attachment.read_image);
+        usages.extend(color_attachment.write_image);
+    }
+
+    for resolve_attachment in node.resolve_attachments.iter().flatten() {
+        usages.push(resolve_attachment.write_image);
+    }
+
+    if let Some(depth_attachment) = &node.depth_attachment {
+        usages.extend(depth_attachment.read_image);
+        usages.extend(depth_attachment.write_image);
+    }
+
+    usages
+}
+
+// Returns every image usage a node reads, writes, or creates
+fn node_image_usages(node: &RenderGraphNode) -> Vec<RenderGraphImageUsageId> {
+    let mut usages = Vec::default();
+    usages.extend(node.image_creates.iter().map(|x| x.image));
+    usages.extend(node.image_reads.iter().map(|x| x.image));
+    for image_modify in &node.image_modifies {
+        usages.push(image_modify.input);
+        usages.push(image_modify.output);
+    }
+
+    usages
+}
+
+// Returns true if any node other than the ones in the pass (or an output image) reads the version
+// of the image produced by the given write
+fn is_read_outside_of_pass(
+    graph: &RenderGraphBuilder,
+    write_image: RenderGraphImageUsageId,
+    pass_nodes: &[RenderGraphNodeId],
+) -> bool {
+    graph
+        .image_version_info(write_image)
+        .read_usages
+        .iter()
+        .any(|read_usage| match graph.image_usages[read_usage.0].user {
+            RenderGraphImageUser::Node(node_id) => !pass_nodes.contains(&node_id),
+            _ => true,
+        })
+}
+
+// Determines which input attachments of a node can be read as subpass inputs if the node is
+// appended to the given nodes. This is only possible if the input was written as an attachment by
+// one of those nodes.
+fn find_subpass_inputs(
+    graph: &RenderGraphBuilder,
+    virtual_resources: &AssignVirtualResourcesResult,
+    pass_nodes: &[RenderGraphNodeId],
+    node_id: RenderGraphNodeId,
+) -> Vec<RenderGraphImageUsageId> {
+    let mut pass_attachments = FnvHashSet::default();
+    for pass_node in pass_nodes {
+        for usage in node_attachment_usages(graph.node(*pass_node)) {
+            pass_attachments.insert(virtual_resources.image_usage_to_virtual[&usage]);
+        }
+    }
+
+    let node = graph.node(node_id);
+    let mut node_color_attachments = FnvHashSet::default();
+    for color_attachment in node.color_attachments.iter().flatten() {
+        for usage in color_attachment
+            .read_image
+            .iter()
+            .chain(color_attachment.write_image.iter())
+        {
+            node_color_attachments.insert(virtual_resources.image_usage_to_virtual[usage]);
+        }
+    }
+
+    // If any input can't be read as a subpass input, all of them are sampled so that the node
+    // only needs a single variant of its shaders
+    let mut subpass_inputs = Vec::default();
+    for &usage in node.input_attachments.iter().flatten() {
+        let creator_node = graph.image_version_info(usage).creator_node;
+        let virtual_image = virtual_resources.image_usage_to_virtual[&usage];
+        if creator_node.is_some_and(|x| pass_nodes.contains(&x))
+            && pass_attachments.contains(&virtual_image)
+            && !node_color_attachments.contains(&virtual_image)
+        {
+            subpass_inputs.push(usage);
+        } else {
+            return Vec::default();
+        }
+    }
+
+    subpass_inputs
+}
+
+// Returns true if the node can be appended to the given nodes as another subpass of the same
+// renderpass
+fn can_merge_into_pass(
+    graph: &RenderGraphBuilder,
+    constraints: &DetermineConstraintsResult,
+    virtual_resources: &AssignVirtualResourcesResult,
+    pass_nodes: &[RenderGraphNodeId],
+    node_id: RenderGraphNodeId,
+) -> bool {
+    let node = graph.node(node_id);
+    let first_node = graph.node(pass_nodes[0]);
+    if node.queue != first_node.queue {
+        return false;
+    }
+
+    // Resolves are performed at the end of a single-subpass renderpass
+    if !node.resolve_attachments.is_empty()
+        || pass_nodes
+            .iter()
+            .any(|x| !graph.node(*x).resolve_attachments.is_empty())
+    {
+        return false;
+    }
+
+    // All attachments in a renderpass must share the same framebuffer dimensions
+    let node_attachments = node_attachment_usages(node);
+    let first_attachment = node_attachment_usages(first_node)[0];
+    let first_specification = &constraints.images[&first_attachment];
+    let first_subresource = graph.image_subresource(first_attachment);
+    for usage in &node_attachments {
+        let specification = &constraints.images[usage];
+        if specification.extents != first_specification.extents
+            || specification.samples != first_specification.samples
+            || graph.image_subresource(*usage) != first_subresource
+        {
+            return false;
+        }
+    }
+
+    // A renderpass has at most one depth attachment
+    let depth_virtual_image = |depth_attachment: &RenderGraphPassDepthAttachmentInfo| {
+        let usage = depth_attachment
+            .read_image
+            .or(depth_attachment.write_image)
+            .unwrap();
+        virtual_resources.image_usage_to_virtual[&usage]
+    };
+    if let Some(node_depth) = node.depth_attachment.as_ref().map(depth_virtual_image) {
+        for pass_node in pass_nodes {
+            if let Some(pass_depth) = graph
+                .node(*pass_node)
+                .depth_attachment
+                .as_ref()
+                .map(depth_virtual_image)
+            {
+                if pass_depth != node_depth {
+                    return false;
+                }
+            }
+        }
+    }
+
+    let mut pass_attachments = FnvHashSet::default();
+    for pass_node in pass_nodes {
+        for usage in node_attachment_usages(graph.node(*pass_node)) {
+            pass_attachments.insert(virtual_resources.image_usage_to_virtual[&usage]);
+        }
+    }
+
+    // Clearing an image that an earlier subpass already used would discard its contents
+    for color_attachment in node.color_attachments.iter().flatten() {
+        if color_attachment.clear_color_value.is_some() {
+            let usage = color_attachment.write_image.unwrap();
+            if pass_attachments.contains(&virtual_resources.image_usage_to_virtual[&usage]) {
+                return false;
+            }
+        }
+    }
+
+    if let Some(depth_attachment) = &node.depth_attachment {
+        if depth_attachment.clear_depth_stencil_value.is_some()
+            && pass_attachments.contains(&depth_virtual_image(depth_attachment))
+        {
+            return false;
+        }
+    }
+
+    // Merging is only worthwhile if the node continues working on the same attachments
+    let subpass_inputs = find_subpass_inputs(graph, virtual_resources, pass_nodes, node_id);
+    let shares_attachment = node_attachments
+        .iter()
+        .any(|x| pass_attachments.contains(&virtual_resources.image_usage_to_virtual[x]));
+    if !shares_attachment && subpass_inputs.is_empty() {
+        return false;
+    }
+
+    for usage in &node_attachments {
+        pass_attachments.insert(virtual_resources.image_usage_to_virtual[usage]);
+    }
+
+    // Attachments can't be sampled or otherwise accessed within the renderpass that writes them.
+    // Input attachments that can't be read as subpass inputs will be sampled.
+    let mut merged_nodes = pass_nodes.to_vec();
+    merged_nodes.push(node_id);
+    for (merged_node_index, merged_node_id) in merged_nodes.iter().enumerate() {
+        let merged_node = graph.node(*merged_node_id);
+        let attachment_usages = node_attachment_usages(merged_node);
+        let subpass_inputs = find_subpass_inputs(
+            graph,
+            virtual_resources,
+            &merged_nodes[0..merged_node_index],
+            *merged_node_id,
+        );
+
+        for usage in node_image_usages(merged_node) {
+            if attachment_usages.contains(&usage) || subpass_inputs.contains(&usage) {
+                continue;
+            }
+
+            if pass_attachments.contains(&virtual_resources.image_usage_to_virtual[&usage]) {
+                return false;
+            }
+        }
+    }
+
+    // Buffers written by one subpass can't be accessed by another within the same renderpass
+    let buffer_writes = |node: &RenderGraphNode| {
+        let mut writes = FnvHashSet::default();
+        for buffer_create in &node.buffer_creates {
+            writes.insert(virtual_resources.buffer_usage_to_virtual[&buffer_create.buffer]);
+        }
+        for buffer_modify in &node.buffer_modifies {
+            writes.insert(virtual_resources.buffer_usage_to_virtual[&buffer_modify.output]);
+        }
+        writes
+    };
+    let buffer_accesses = |node: &RenderGraphNode| {
+        let mut accesses = buffer_writes(node);
+        for buffer_read in &node.buffer_reads {
+            accesses.insert(virtual_resources.buffer_usage_to_virtual[&buffer_read.buffer]);
+        }
+        for buffer_modify in &node.buffer_modifies {
+            accesses.insert(virtual_resources.buffer_usage_to_virtual[&buffer_modify.input]);
+        }
+        accesses
+    };
+
+    let node_buffer_writes = buffer_writes(node);
+    let node_buffer_accesses = buffer_accesses(node);
+    for pass_node in pass_nodes {
+        let pass_node = graph.node(*pass_node);
+        if !buffer_writes(pass_node).is_disjoint(&node_buffer_accesses)
+            || !buffer_accesses(pass_node).is_disjoint(&node_buffer_writes)
+        {
+            return false;
+        }
     }
+
+    true
 }
 
 //
@@ -1086,21 +2085,27 @@ fn assign_virtual_resources(
 // determined here along with stage/access/queue family barrier info. (The barrier info is used
 // later.. some of the invalidates/flushes can be merged.)
 //
+// Consecutive render nodes that work on the same attachments are merged into a single renderpass
+// with a subpass per node, if the device supports it. Input attachments that can't be read as
+// subpass inputs are changed to sampled images here.
+//
 #[profiling::function]
 fn build_physical_passes(
-    graph: &RenderGraphBuilder,
+    graph: &mut RenderGraphBuilder,
+    device_info: &RafxDeviceInfo,
     node_execution_order: &[RenderGraphNodeId],
-    constraints: &DetermineConstraintsResult,
+    constraints: &mut DetermineConstraintsResult,
     virtual_resources: &AssignVirtualResourcesResult,
 ) -> Vec<RenderGraphPass> {
     #[derive(Debug)]
     enum PassNode {
-        RenderNode(RenderGraphNodeId),
+        RenderNode(Vec<RenderGraphNodeId>),
         ComputeNode(RenderGraphNodeId),
     }
 
     // All passes
     let mut pass_nodes = Vec::default();
+    let mut subpass_inputs = FnvHashSet::default();
 
     for node_id in node_execution_order {
         let node = graph.node(*node_id);
@@ -1109,11 +2114,69 @@ fn build_physical_passes(
         let is_compute = node.color_attachments.is_empty() && node.depth_attachment.is_none();
         debug_assert_eq!(is_compute && !node.resolve_attachments.is_empty(), false);
 
-        // If this is a compute node, store it as a compute pass, otherwise buffer it for later
+        // If this is a compute node, store it as a compute pass, otherwise try to append it to
+        // the previous renderpass
         if is_compute {
             pass_nodes.push(PassNode::ComputeNode(*node_id));
         } else {
-            pass_nodes.push(PassNode::RenderNode(*node_id));
+            if device_info.supports_subpasses {
+                if let Some(PassNode::RenderNode(renderpass_nodes)) = pass_nodes.last_mut() {
+                    if can_merge_into_pass(
+                        graph,
+                        constraints,
+                        virtual_resources,
+                        renderpass_nodes,
+                        *node_id,
+                    ) {
+                        subpass_inputs.extend(find_subpass_inputs(
+                            graph,
+                            virtual_resources,
+                            renderpass_nodes,
+                            *node_id,
+                        ));
+                        renderpass_nodes.push(*node_id);
+                        continue;
+                    }
+                }
+            }
+
+            pass_nodes.push(PassNode::RenderNode(vec![*node_id]));
+        }
+    }
+
+    //
+    // Any input attachment that won't be read as a subpass input is sampled instead
+    //
+    for node_id in node_execution_order {
+        let node = graph.node_mut(*node_id);
+        if node
+            .input_attachments
+            .iter()
+            .flatten()
+            .all(|x| subpass_inputs.contains(x))
+        {
+            continue;
+        }
+
+        let sampled_inputs: Vec<_> = node.input_attachments.drain(..).flatten().collect();
+        for usage in sampled_inputs {
+            log::trace!(
+                "  Input attachment {:?} of node {:?} will be sampled",
+                usage,
+                node_id
+            );
+            node.sampled_images.push(usage);
+
+            let virtual_image = virtual_resources.image_usage_to_virtual[&usage];
+            for (other_usage, other_virtual_image) in &virtual_resources.image_usage_to_virtual {
+                if *other_virtual_image == virtual_image {
+                    constraints
+                        .images
+                        .get_mut(other_usage)
+                        .unwrap()
+                        .resource_type |= RafxResourceType::TEXTURE;
+                }
+            }
         }
     }
 
@@ -1162,196 +2225,207 @@ fn build_physical_passes(
                 passes.push(RenderGraphPass::Compute(RenderGraphComputePass {
                     node: compute_node,
                     pre_pass_barrier: Default::default(),
+                    post_pass_barrier: Default::default(),
                 }));
             }
-            PassNode::RenderNode(renderpass_node) => {
+            PassNode::RenderNode(renderpass_nodes) => {
                 let mut renderpass_attachments = Vec::default();
+                let mut subpasses = Vec::default();
 
-                log::trace!("    subpass node: {:?}", renderpass_node);
-                let subpass_node = graph.node(renderpass_node);
+                for &renderpass_node in &renderpass_nodes {
+                    log::trace!("    subpass node: {:?}", renderpass_node);
+                    let subpass_node = graph.node(renderpass_node);
 
-                // Don't create a subpass if there are no attachments
-                if subpass_node.color_attachments.is_empty()
-                    && subpass_node.depth_attachment.is_none()
-                {
-                    assert!(subpass_node.resolve_attachments.is_empty());
-                    log::trace!("      Not generating a subpass - no attachments");
-                    continue;
-                }
+                    let mut subpass_color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
+                        Default::default();
+                    let mut subpass_resolve_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
+                        Default::default();
+                    let mut subpass_depth_attachment = Default::default();
+
+                    for (color_attachment_index, color_attachment) in
+                        subpass_node.color_attachments.iter().enumerate()
+                    {
+                        if let Some(color_attachment) = color_attachment {
+                            let read_or_write_usage = color_attachment
+                                .read_image
+                                .or(color_attachment.write_image)
+                                .unwrap();
+                            let virtual_image = virtual_resources
+                                .image_usage_to_virtual
+                                .get(&read_or_write_usage)
+                                .unwrap();
+
+                            let specification =
+                                constraints.images.get(&read_or_write_usage).unwrap();
+                            log::trace!("      virtual attachment (color): {:?}", virtual_image);
+
+                            let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
+                                &mut renderpass_attachments,
+                                read_or_write_usage,
+                                *virtual_image, /*, subresource_range*/
+                            );
+                            subpass_color_attachments[color_attachment_index] =
+                                Some(pass_attachment_index);
+
+                            let mut attachment = &mut renderpass_attachments[pass_attachment_index];
+                            if is_first_usage {
+                                // Check if we load or clear
+                                if color_attachment.clear_color_value.is_some() {
+                                    attachment.load_op = RafxLoadOp::Clear;
+                                    attachment.clear_color = Some(AttachmentClearValue::Color(
+                                        color_attachment.clear_color_value.unwrap(),
+                                    ))
+                                } else if color_attachment.read_image.is_some() {
+                                    attachment.load_op = RafxLoadOp::Load;
+                                }
+
+                                attachment.format = specification.format.into();
+                                attachment.samples = specification.samples.into();
+                            };
 
-                let mut pass_color_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
-                    Default::default();
-                let mut pass_resolve_attachments: [Option<usize>; MAX_COLOR_ATTACHMENTS] =
-                    Default::default();
-                let mut pass_depth_attachment = Default::default();
+                            // Writes to external images must be stored even if no node reads them.
+                            // Writes only read by later subpasses of this pass don't need to be
+                            // stored.
+                            if let Some(write_image) = color_attachment.write_image {
+                                if is_read_outside_of_pass(graph, write_image, &renderpass_nodes)
+                                    || graph.is_external_image(write_image)
+                                {
+                                    attachment.store_op = RafxStoreOp::Store;
+                                }
+                            }
+                        }
+                    }
 
-                for (color_attachment_index, color_attachment) in
-                    subpass_node.color_attachments.iter().enumerate()
-                {
-                    if let Some(color_attachment) = color_attachment {
-                        let read_or_write_usage = color_attachment
+                    for (resolve_attachment_index, resolve_attachment) in
+                        subpass_node.resolve_attachments.iter().enumerate()
+                    {
+                        if let Some(resolve_attachment) = resolve_attachment {
+                            let write_image = resolve_attachment.write_image;
+                            let virtual_image = virtual_resources
+                                .image_usage_to_virtual
+                                .get(&write_image)
+                                .unwrap();
+                            //let version_id = graph.image_version_id(write_image);
+                            let specification = constraints.images.get(&write_image).unwrap();
+                            log::trace!("      virtual attachment (resolve): {:?}", virtual_image);
+
+                            let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
+                                &mut renderpass_attachments,
+                                write_image,
+                                *virtual_image, /*, subresource_range*/
+                            );
+                            subpass_resolve_attachments[resolve_attachment_index] =
+                                Some(pass_attachment_index);
+
+                            assert!(is_first_usage); // Not sure if this assert is valid
+                            let mut attachment = &mut renderpass_attachments[pass_attachment_index];
+                            attachment.format = specification.format.into();
+                            attachment.samples = specification.samples.into();
+
+                            //TODO: Should we skip resolving if there is no reader?
+                            let store_op =
+                                if !graph.image_version_info(write_image).read_usages.is_empty() {
+                                    RafxStoreOp::Store
+                                } else {
+                                    RafxStoreOp::DontCare
+                                };
+
+                            attachment.store_op = store_op;
+                            attachment.stencil_store_op = RafxStoreOp::DontCare;
+                        }
+                    }
+
+                    if let Some(depth_attachment) = &subpass_node.depth_attachment {
+                        let read_or_write_usage = depth_attachment
                             .read_image
-                            .or(color_attachment.write_image)
+                            .or(depth_attachment.write_image)
                             .unwrap();
                         let virtual_image = virtual_resources
                             .image_usage_to_virtual
                             .get(&read_or_write_usage)
                             .unwrap();
-
                         let specification = constraints.images.get(&read_or_write_usage).unwrap();
-                        log::trace!("      virtual attachment (color): {:?}", virtual_image);
+                        log::trace!("      virtual attachment (depth): {:?}", virtual_image);
 
                         let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
                             &mut renderpass_attachments,
                             read_or_write_usage,
                             *virtual_image, /*, subresource_range*/
                         );
-                        pass_color_attachments[color_attachment_index] =
-                            Some(pass_attachment_index);
+                        subpass_depth_attachment = Some(pass_attachment_index);
 
                         let mut attachment = &mut renderpass_attachments[pass_attachment_index];
                         if is_first_usage {
                             // Check if we load or clear
-                            if color_attachment.clear_color_value.is_some() {
-                                attachment.load_op = RafxLoadOp::Clear;
-                                attachment.clear_color = Some(AttachmentClearValue::Color(
-                                    color_attachment.clear_color_value.unwrap(),
-                                ))
-                            } else if color_attachment.read_image.is_some() {
-                                attachment.load_op = RafxLoadOp::Load;
+                            //TODO: Support load_op for stencil
+
+                            if depth_attachment.clear_depth_stencil_value.is_some() {
+                                if depth_attachment.has_depth {
+                                    attachment.load_op = RafxLoadOp::Clear;
+                                }
+                                if depth_attachment.has_stencil {
+                                    attachment.stencil_load_op = RafxLoadOp::Clear;
+                                }
+                                attachment.clear_color = Some(AttachmentClearValue::DepthStencil(
+                                    depth_attachment.clear_depth_stencil_value.unwrap(),
+                                ));
+                            } else if depth_attachment.read_image.is_some() {
+                                if depth_attachment.has_depth {
+                                    attachment.load_op = RafxLoadOp::Load;
+                                }
+
+                                if depth_attachment.has_stencil {
+                                    attachment.stencil_load_op = RafxLoadOp::Load;
+                                }
                             }
 
                             attachment.format = specification.format.into();
                             attachment.samples = specification.samples.into();
                         };
 
-                        let store_op = if let Some(write_image) = color_attachment.write_image {
-                            if !graph.image_version_info(write_image).read_usages.is_empty() {
-                                RafxStoreOp::Store
-                            } else {
-                                RafxStoreOp::DontCare
-                            }
-                        } else {
-                            RafxStoreOp::DontCare
-                        };
-
-                        attachment.store_op = store_op;
-                        attachment.stencil_store_op = RafxStoreOp::DontCare;
-                    }
-                }
-
-                for (resolve_attachment_index, resolve_attachment) in
-                    subpass_node.resolve_attachments.iter().enumerate()
-                {
-                    if let Some(resolve_attachment) = resolve_attachment {
-                        let write_image = resolve_attachment.write_image;
-                        let virtual_image = virtual_resources
-                            .image_usage_to_virtual
-                            .get(&write_image)
-                            .unwrap();
-                        //let version_id = graph.image_version_id(write_image);
-                        let specification = constraints.images.get(&write_image).unwrap();
-                        log::trace!("      virtual attachment (resolve): {:?}", virtual_image);
-
-                        let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
-                            &mut renderpass_attachments,
-                            write_image,
-                            *virtual_image, /*, subresource_range*/
-                        );
-                        pass_resolve_attachments[resolve_attachment_index] =
-                            Some(pass_attachment_index);
-
-                        assert!(is_first_usage); // Not sure if this assert is valid
-                        let mut attachment = &mut renderpass_attachments[pass_attachment_index];
-                        attachment.format = specification.format.into();
-                        attachment.samples = specification.samples.into();
-
-                        //TODO: Should we skip resolving if there is no reader?
-                        let store_op =
-                            if !graph.image_version_info(write_image).read_usages.is_empty() {
-                                RafxStoreOp::Store
-                            } else {
-                                RafxStoreOp::DontCare
-                            };
-
-                        attachment.store_op = store_op;
-                        attachment.stencil_store_op = RafxStoreOp::DontCare;
-                    }
-                }
-
-                if let Some(depth_attachment) = &subpass_node.depth_attachment {
-                    let read_or_write_usage = depth_attachment
-                        .read_image
-                        .or(depth_attachment.write_image)
-                        .unwrap();
-                    let virtual_image = virtual_resources
-                        .image_usage_to_virtual
-                        .get(&read_or_write_usage)
-                        .unwrap();
-                    let specification = constraints.images.get(&read_or_write_usage).unwrap();
-                    log::trace!("      virtual attachment (depth): {:?}", virtual_image);
-
-                    let (pass_attachment_index, is_first_usage) = find_or_insert_attachment(
-                        &mut renderpass_attachments,
-                        read_or_write_usage,
-                        *virtual_image, /*, subresource_range*/
-                    );
-                    pass_depth_attachment = Some(pass_attachment_index);
-
-                    let mut attachment = &mut renderpass_attachments[pass_attachment_index];
-                    if is_first_usage {
-                        // Check if we load or clear
-                        //TODO: Support load_op for stencil
-
-                        if depth_attachment.clear_depth_stencil_value.is_some() {
-                            if depth_attachment.has_depth {
-                                attachment.load_op = RafxLoadOp::Clear;
-                            }
-                            if depth_attachment.has_stencil {
-                                attachment.stencil_load_op = RafxLoadOp::Clear;
-                            }
-                            attachment.clear_color = Some(AttachmentClearValue::DepthStencil(
-                                depth_attachment.clear_depth_stencil_value.unwrap(),
-                            ));
-                        } else if depth_attachment.read_image.is_some() {
-                            if depth_attachment.has_depth {
-                                attachment.load_op = RafxLoadOp::Load;
-                            }
-
-                            if depth_attachment.has_stencil {
-                                attachment.stencil_load_op = RafxLoadOp::Load;
+                        if let Some(write_image) = depth_attachment.write_image {
+                            if is_read_outside_of_pass(graph, write_image, &renderpass_nodes)
+                                || graph.is_external_image(write_image)
+                            {
+                                if depth_attachment.has_depth {
+                                    attachment.store_op = RafxStoreOp::Store;
+                                }
+
+                                if depth_attachment.has_stencil {
+                                    attachment.stencil_store_op = RafxStoreOp::Store;
+                                }
                             }
                         }
-
-                        attachment.format = specification.format.into();
-                        attachment.samples = specification.samples.into();
-                    };
-
-                    let store_op = if let Some(write_image) = depth_attachment.write_image {
-                        if !graph.image_version_info(write_image).read_usages.is_empty() {
-                            RafxStoreOp::Store
-                        } else {
-                            RafxStoreOp::DontCare
-                        }
-                    } else {
-                        RafxStoreOp::DontCare
-                    };
-
-                    if depth_attachment.has_depth {
-                        attachment.store_op = store_op;
                     }
 
-                    if depth_attachment.has_stencil {
-                        attachment.stencil_store_op = store_op;
-                    }
+                    // Subpass inputs were written as attachments by earlier subpasses
+                    let subpass_input_attachments = subpass_node
+                        .input_attachments
+                        .iter()
+                        .map(|input_attachment| {
+                            input_attachment.map(|usage| {
+                                let virtual_image =
+                                    virtual_resources.image_usage_to_virtual[&usage];
+                                renderpass_attachments
+                                    .iter()
+                                    .position(|x| x.virtual_image == virtual_image)
+                                    .unwrap()
+                            })
+                        })
+                        .collect();
+
+                    subpasses.push(RenderGraphSubpass {
+                        node_id: renderpass_node,
+                        color_attachments: subpass_color_attachments,
+                        resolve_attachments: subpass_resolve_attachments,
+                        depth_attachment: subpass_depth_attachment,
+                        input_attachments: subpass_input_attachments,
+                    });
                 }
 
                 passes.push(RenderGraphPass::Renderpass(RenderGraphRenderPass {
-                    node_id: renderpass_node,
+                    subpasses,
                     attachments: renderpass_attachments,
-                    color_attachments: pass_color_attachments,
-                    depth_attachment: pass_depth_attachment,
-                    resolve_attachments: pass_resolve_attachments,
                     pre_pass_barrier: None,
                     post_pass_barrier: None,
                 }));
@@ -1387,7 +2461,7 @@ fn assign_physical_resources(
     constraints: &DetermineConstraintsResult,
     virtual_resources: &AssignVirtualResourcesResult,
     passes: &mut [RenderGraphPass],
-) -> AssignPhysicalResourcesResult {
+) -> RenderGraphResult<AssignPhysicalResourcesResult> {
     log::trace!("-- Assign physical resources --");
     struct PhysicalImageReuseRequirements {
         virtual_id: VirtualImageId,
@@ -1484,111 +2558,110 @@ fn assign_physical_resources(
     // Walk through all image/buffer usages to determine their lifetimes
     //
     for (pass_index, pass) in passes.iter().enumerate() {
-        let subpass_node_id = pass.node();
-        let node = graph.node(subpass_node_id);
+        for subpass_node_id in pass.nodes() {
+            let node = graph.node(subpass_node_id);
+
+            for image_modify in &node.image_modifies {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_modify.input,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_modify.output,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_modify in &node.image_modifies {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_modify.input,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_modify.output,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_read in &node.image_reads {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_read.image,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_read in &node.image_reads {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_read.image,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
-
-        for image_create in &node.image_creates {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                image_create.image,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_create in &node.image_creates {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    image_create.image,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for image_sample in &node.sampled_images {
-            add_or_modify_reuse_image_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                *image_sample,
-                &mut image_reuse_requirements,
-                &mut image_reuse_requirements_lookup,
-            );
-        }
+            for image_sample in &node.sampled_images {
+                add_or_modify_reuse_image_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    *image_sample,
+                    &mut image_reuse_requirements,
+                    &mut image_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_modify in &node.buffer_modifies {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_modify.input,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_modify.output,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-        }
+            for buffer_modify in &node.buffer_modifies {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_modify.input,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_modify.output,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_read in &node.buffer_reads {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_read.buffer,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
-        }
+            for buffer_read in &node.buffer_reads {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_read.buffer,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
 
-        for buffer_create in &node.buffer_creates {
-            add_or_modify_reuse_buffer_requirements(
-                virtual_resources,
-                constraints,
-                pass_index,
-                buffer_create.buffer,
-                &mut buffer_reuse_requirements,
-                &mut buffer_reuse_requirements_lookup,
-            );
+            for buffer_create in &node.buffer_creates {
+                add_or_modify_reuse_buffer_requirements(
+                    virtual_resources,
+                    constraints,
+                    pass_index,
+                    buffer_create.buffer,
+                    &mut buffer_reuse_requirements,
+                    &mut buffer_reuse_requirements_lookup,
+                );
+            }
         }
     }
 
-    //TODO: Find transients
-    //TODO: Mark input images as non-reuse?
-    //TODO: Stay in same queue?
-
     struct PhysicalImage {
         specification: RenderGraphImageSpecification,
         last_node_pass_index: usize,
         can_be_reused: bool,
+        // True if every virtual image assigned to this physical image is only used in one pass
+        used_within_single_pass: bool,
     }
 
     struct PhysicalBuffer {
@@ -1611,6 +2684,7 @@ fn assign_physical_resources(
             specification: output_image.specification.clone(),
             last_node_pass_index: passes.len() - 1,
             can_be_reused: false, // Should be safe to allow reuse? But last_node_pass_index effectively makes this never reuse
+            used_within_single_pass: false,
         });
 
         let virtual_id = virtual_resources.image_usage_to_virtual[&output_image.usage];
@@ -1648,6 +2722,66 @@ fn assign_physical_resources(
         );
     }
 
+    //
+    // Allocate physical IDs for all external images. These are owned outside the graph, so they
+    // can never be reused for another virtual image
+    //
+    for external_image in &graph.external_images {
+        let virtual_id = virtual_resources.image_usage_to_virtual[&external_image.usage];
+        if image_virtual_to_physical.contains_key(&virtual_id) {
+            return Err(RenderGraphError::ExternalImageIsOutput {
+                image: external_image.usage,
+                image_name: graph.image_resource(external_image.usage).name,
+            });
+        }
+
+        let physical_image_id = PhysicalImageId(physical_images.len());
+        physical_images.push(PhysicalImage {
+            specification: constraints.images[&external_image.usage].clone(),
+            last_node_pass_index: passes.len() - 1,
+            can_be_reused: false,
+            used_within_single_pass: false,
+        });
+
+        image_virtual_to_physical.insert(virtual_id, physical_image_id);
+        log::trace!(
+            "  External Image {:?} -> {:?} Used in passes [{}:{}]",
+            virtual_id,
+            physical_image_id,
+            0,
+            passes.len() - 1
+        );
+    }
+
+    //
+    // Allocate physical IDs for all external buffers
+    //
+    for external_buffer in &graph.external_buffers {
+        let virtual_id = virtual_resources.buffer_usage_to_virtual[&external_buffer.usage];
+        if buffer_virtual_to_physical.contains_key(&virtual_id) {
+            return Err(RenderGraphError::ExternalBufferIsOutput {
+                buffer: external_buffer.usage,
+                buffer_name: graph.buffer_resource(external_buffer.usage).name,
+            });
+        }
+
+        let physical_buffer_id = PhysicalBufferId(physical_buffers.len());
+        physical_buffers.push(PhysicalBuffer {
+            specification: constraints.buffers[&external_buffer.usage].clone(),
+            last_node_pass_index: passes.len() - 1,
+            can_be_reused: false,
+        });
+
+        buffer_virtual_to_physical.insert(virtual_id, physical_buffer_id);
+        log::trace!(
+            "  External Buffer {:?} -> {:?} Used in passes [{}:{}]",
+            virtual_id,
+            physical_buffer_id,
+            0,
+            passes.len() - 1
+        );
+    }
+
     //
     // Determine the minimal set of physical images needed to represent all our virtual images,
     // given that virtual images can use the same physical image if their lifetimes don't overlap
@@ -1657,10 +2791,13 @@ fn assign_physical_resources(
     //
     for reuse_requirements in &image_reuse_requirements {
         if image_virtual_to_physical.contains_key(&reuse_requirements.virtual_id) {
-            // May already have been registered by output image
+            // May already have been registered by output or external image
             continue;
         }
 
+        let used_within_single_pass =
+            reuse_requirements.first_node_pass_index == reuse_requirements.last_node_pass_index;
+
         // See if we can reuse with an existing physical image
         let mut physical_image_id = None;
         for (physical_image_index, physical_image) in physical_images.iter_mut().enumerate() {
@@ -1672,6 +2809,7 @@ fn assign_physical_resources(
                     .try_merge(&reuse_requirements.specification)
                 {
                     physical_image.last_node_pass_index = reuse_requirements.last_node_pass_index;
+                    physical_image.used_within_single_pass &= used_within_single_pass;
                     physical_image_id = Some(PhysicalImageId(physical_image_index));
                     log::trace!(
                         "  Intermediate Image (Reuse) {:?} -> {:?} Used in passes [{}:{}]",
@@ -1692,6 +2830,7 @@ fn assign_physical_resources(
                 specification: reuse_requirements.specification.clone(),
                 last_node_pass_index: reuse_requirements.last_node_pass_index,
                 can_be_reused: true,
+                used_within_single_pass,
             });
 
             log::trace!(
@@ -1709,7 +2848,7 @@ fn assign_physical_resources(
 
     for reuse_requirements in &buffer_reuse_requirements {
         if buffer_virtual_to_physical.contains_key(&reuse_requirements.virtual_id) {
-            // May already have been registered by output buffer
+            // May already have been registered by output or external buffer
             continue;
         }
 
@@ -1759,13 +2898,35 @@ fn assign_physical_resources(
         buffer_virtual_to_physical.insert(reuse_requirements.virtual_id, physical_buffer_id);
     }
 
+    //
+    // Intermediate images that are only ever used as attachments within a single pass never need
+    // their contents to leave that pass. These become transient attachments, which tile-based GPUs
+    // can keep entirely in on-chip memory.
+    //
+    let mut transient_images = FnvHashSet::default();
+    for (physical_image_index, physical_image) in physical_images.iter_mut().enumerate() {
+        let resource_type = physical_image.specification.resource_type;
+        if physical_image.can_be_reused
+            && physical_image.used_within_single_pass
+            && resource_type.is_render_target()
+            && !resource_type.is_texture()
+        {
+            physical_image.specification.resource_type |= RafxResourceType::TRANSIENT_ATTACHMENT;
+            transient_images.insert(PhysicalImageId(physical_image_index));
+            log::trace!(
+                "  Intermediate Image {:?} is a transient attachment",
+                PhysicalImageId(physical_image_index)
+            );
+        }
+    }
+
     //
     // Create a lookup to get physical image from usage
     //
     let mut image_usage_to_physical = FnvHashMap::default();
     for (&usage, virtual_image) in &virtual_resources.image_usage_to_virtual {
-        //TODO: This was breaking in a test because an output image had no usage flags and we
-        // never assigned the output image a physical ID since it wasn't in a pass
+        // Output images with no usage flags used to be missing here. RenderGraphPlan::new now
+        // rejects them up front with OutputImageWithoutResourceType
         image_usage_to_physical.insert(usage, image_virtual_to_physical[virtual_image]);
     }
 
@@ -1822,6 +2983,13 @@ fn assign_physical_resources(
                 let image_view_id = image_usage_to_image_view[&attachment.usage];
                 attachment.image = Some(physical_image);
                 attachment.image_view = Some(image_view_id);
+
+                // The contents of a transient attachment are discarded at the end of the pass
+                if transient_images.contains(&physical_image) {
+                    debug_assert_ne!(attachment.load_op, RafxLoadOp::Load);
+                    attachment.store_op = RafxStoreOp::DontCare;
+                    attachment.stencil_store_op = RafxStoreOp::DontCare;
+                }
             }
         }
     }
@@ -1842,7 +3010,7 @@ fn assign_physical_resources(
         .map(|x| x.specification)
         .collect();
 
-    AssignPhysicalResourcesResult {
+    Ok(AssignPhysicalResourcesResult {
         image_usage_to_physical,
         image_virtual_to_physical,
         image_usage_to_image_view,
@@ -1851,14 +3019,14 @@ fn assign_physical_resources(
         buffer_usage_to_physical,
         buffer_virtual_to_physical,
         buffer_specifications,
-    }
+    })
 }
 
 #[profiling::function]
 fn build_node_barriers(
     graph: &RenderGraphBuilder,
     node_execution_order: &[RenderGraphNodeId],
-    _constraints: &DetermineConstraintsResult,
+    constraints: &DetermineConstraintsResult,
     physical_resources: &AssignPhysicalResourcesResult,
 ) -> FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers> {
     let mut resource_barriers =
@@ -1866,11 +3034,49 @@ fn build_node_barriers(
 
     for node_id in node_execution_order {
         let node = graph.node(*node_id);
-        let mut image_node_barriers: FnvHashMap<PhysicalImageId, RenderGraphPassImageBarriers> =
-            Default::default();
+        let mut image_node_barriers: FnvHashMap<
+            (PhysicalImageId, RenderGraphImageSubresource),
+            RenderGraphPassImageBarriers,
+        > = Default::default();
         let mut buffer_node_barriers: FnvHashMap<PhysicalBufferId, RenderGraphPassBufferBarriers> =
             Default::default();
 
+        // Nodes inserted to convert a resource access it with transfer operations. These are
+        // added first so that the states below don't replace them
+        match node.conversion {
+            Some(RenderGraphNodeConversion::BlitImage { src, dst }) => {
+                for &(usage, resource_state) in &[
+                    (src, RafxResourceState::COPY_SRC),
+                    (dst, RafxResourceState::COPY_DST),
+                ] {
+                    let physical_image = physical_resources
+                        .image_usage_to_physical
+                        .get(&usage)
+                        .unwrap();
+
+                    image_node_barriers
+                        .entry((*physical_image, graph.image_subresource(usage)))
+                        .or_insert_with(|| RenderGraphPassImageBarriers::new(resource_state));
+                }
+            }
+            Some(RenderGraphNodeConversion::CopyBuffer { src, dst }) => {
+                for &(usage, resource_state) in &[
+                    (src, RafxResourceState::COPY_SRC),
+                    (dst, RafxResourceState::COPY_DST),
+                ] {
+                    let physical_buffer = physical_resources
+                        .buffer_usage_to_physical
+                        .get(&usage)
+                        .unwrap();
+
+                    buffer_node_barriers
+                        .entry(*physical_buffer)
+                        .or_insert_with(|| RenderGraphPassBufferBarriers::new(resource_state));
+                }
+            }
+            None => {}
+        }
+
         for color_attachment in &node.color_attachments {
             if let Some(color_attachment) = color_attachment {
                 let read_or_write_usage = color_attachment
@@ -1883,7 +3089,10 @@ fn build_node_barriers(
                     .unwrap();
 
                 image_node_barriers
-                    .entry(*physical_image)
+                    .entry((
+                        *physical_image,
+                        graph.image_subresource(read_or_write_usage),
+                    ))
                     .or_insert_with(|| {
                         RenderGraphPassImageBarriers::new(RafxResourceState::RENDER_TARGET)
                     });
@@ -1898,7 +3107,10 @@ fn build_node_barriers(
                     .unwrap();
 
                 image_node_barriers
-                    .entry(*physical_image)
+                    .entry((
+                        *physical_image,
+                        graph.image_subresource(resolve_attachment.write_image),
+                    ))
                     .or_insert_with(|| {
                         RenderGraphPassImageBarriers::new(RafxResourceState::RENDER_TARGET)
                     });
@@ -1917,7 +3129,10 @@ fn build_node_barriers(
             //let version_id = graph.image_version_id(read_or_write_usage);
 
             image_node_barriers
-                .entry(*physical_image)
+                .entry((
+                    *physical_image,
+                    graph.image_subresource(read_or_write_usage),
+                ))
                 .or_insert_with(|| {
                     RenderGraphPassImageBarriers::new(RafxResourceState::DEPTH_WRITE)
                 });
@@ -1930,12 +3145,31 @@ fn build_node_barriers(
                 .unwrap();
 
             image_node_barriers
-                .entry(*physical_image)
+                .entry((*physical_image, graph.image_subresource(*sampled_image)))
                 .or_insert_with(|| {
                     RenderGraphPassImageBarriers::new(RafxResourceState::PIXEL_SHADER_RESOURCE)
                 });
         }
 
+        // Subpass inputs stay in the same state as the attachment that an earlier subpass wrote.
+        // The renderpass handles the layout change within the pass.
+        for input_attachment in node.input_attachments.iter().flatten() {
+            let physical_image = physical_resources
+                .image_usage_to_physical
+                .get(input_attachment)
+                .unwrap();
+
+            let resource_state = if constraints.images[input_attachment].format.has_depth() {
+                RafxResourceState::DEPTH_WRITE
+            } else {
+                RafxResourceState::RENDER_TARGET
+            };
+
+            image_node_barriers
+                .entry((*physical_image, graph.image_subresource(*input_attachment)))
+                .or_insert_with(|| RenderGraphPassImageBarriers::new(resource_state));
+        }
+
         for buffer_create in &node.buffer_creates {
             let physical_buffer = physical_resources
                 .buffer_usage_to_physical
@@ -1987,6 +3221,206 @@ fn build_node_barriers(
     resource_barriers
 }
 
+/// The range of passes that use a physical resource
+#[derive(Debug, Copy, Clone)]
+pub(super) struct RenderGraphResourceLifetime {
+    pub(super) first_pass_index: usize,
+    pub(super) last_pass_index: usize,
+    // None if the resource is used on more than one queue
+    pub(super) queue: Option<RenderGraphQueue>,
+}
+
+impl RenderGraphResourceLifetime {
+    fn new(
+        pass_index: usize,
+        queue: RenderGraphQueue,
+    ) -> Self {
+        RenderGraphResourceLifetime {
+            first_pass_index: pass_index,
+            last_pass_index: pass_index,
+            queue: Some(queue),
+        }
+    }
+
+    fn add_usage(
+        &mut self,
+        pass_index: usize,
+        queue: RenderGraphQueue,
+    ) {
+        self.last_pass_index = pass_index;
+        if self.queue != Some(queue) {
+            self.queue = None;
+        }
+    }
+}
+
+struct AssignMemoryAliasesResult {
+    image_lifetimes: FnvHashMap<PhysicalImageId, RenderGraphResourceLifetime>,
+    buffer_lifetimes: FnvHashMap<PhysicalBufferId, RenderGraphResourceLifetime>,
+
+    // Each list of resources shares a single allocation, and is sorted by first use
+    aliased_images: Vec<Vec<PhysicalImageId>>,
+    aliased_buffers: Vec<Vec<PhysicalBufferId>>,
+}
+
+//
+// Physical images/buffers are only reused when their specifications match. This goes further and
+// lets intermediate resources with non-overlapping lifetimes share memory, even if their formats
+// or extents differ. The size of each resource is not known until the swapchain extents are, so
+// this only minimizes the number of allocations. Each allocation will be as large as the largest
+// resource that uses it.
+//
+#[profiling::function]
+fn assign_memory_aliases(
+    graph: &RenderGraphBuilder,
+    physical_resources: &AssignPhysicalResourcesResult,
+    node_barriers: &FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers>,
+    passes: &[RenderGraphPass],
+) -> AssignMemoryAliasesResult {
+    log::trace!("-- Assign memory aliases --");
+
+    //
+    // Determine which passes and queues use each physical resource
+    //
+    let mut image_lifetimes = FnvHashMap::<PhysicalImageId, RenderGraphResourceLifetime>::default();
+    let mut buffer_lifetimes =
+        FnvHashMap::<PhysicalBufferId, RenderGraphResourceLifetime>::default();
+
+    for (pass_index, pass) in passes.iter().enumerate() {
+        for node_id in pass.nodes() {
+            let queue = graph.node(node_id).queue;
+            let node_barriers = &node_barriers[&node_id];
+
+            for (physical_image, _) in node_barriers.image_barriers.keys() {
+                image_lifetimes
+                    .entry(*physical_image)
+                    .or_insert_with(|| RenderGraphResourceLifetime::new(pass_index, queue))
+                    .add_usage(pass_index, queue);
+            }
+
+            for physical_buffer in node_barriers.buffer_barriers.keys() {
+                buffer_lifetimes
+                    .entry(*physical_buffer)
+                    .or_insert_with(|| RenderGraphResourceLifetime::new(pass_index, queue))
+                    .add_usage(pass_index, queue);
+            }
+        }
+    }
+
+    //
+    // Output and external resources are provided by the caller, and transient attachments may
+    // not have memory at all. None of these can share memory with other resources.
+    //
+    let mut provided_images = FnvHashSet::default();
+    for output_image in &graph.output_images {
+        provided_images.insert(physical_resources.image_usage_to_physical[&output_image.usage]);
+    }
+    for external_image in &graph.external_images {
+        provided_images.insert(physical_resources.image_usage_to_physical[&external_image.usage]);
+    }
+
+    let mut provided_buffers = FnvHashSet::default();
+    for output_buffer in &graph.output_buffers {
+        provided_buffers.insert(physical_resources.buffer_usage_to_physical[&output_buffer.usage]);
+    }
+    for external_buffer in &graph.external_buffers {
+        provided_buffers
+            .insert(physical_resources.buffer_usage_to_physical[&external_buffer.usage]);
+    }
+
+    let mut image_candidates = Vec::default();
+    for (index, specification) in physical_resources.image_specifications.iter().enumerate() {
+        let physical_image = PhysicalImageId(index);
+        if provided_images.contains(&physical_image)
+            || specification
+                .resource_type
+                .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+        {
+            continue;
+        }
+
+        if let Some(lifetime) = image_lifetimes.get(&physical_image) {
+            image_candidates.push((physical_image, *lifetime));
+        }
+    }
+
+    let mut buffer_candidates = Vec::default();
+    for index in 0..physical_resources.buffer_specifications.len() {
+        let physical_buffer = PhysicalBufferId(index);
+        if provided_buffers.contains(&physical_buffer) {
+            continue;
+        }
+
+        if let Some(lifetime) = buffer_lifetimes.get(&physical_buffer) {
+            buffer_candidates.push((physical_buffer, *lifetime));
+        }
+    }
+
+    //
+    // Greedily place each resource in the first allocation that is no longer in use. Resources
+    // used on more than one queue are never aliased because passes on different queues are not
+    // ordered relative to each other. For the same reason, an allocation is only ever used by a
+    // single queue.
+    //
+    fn assign_aliases<T: Copy + std::fmt::Debug>(
+        mut candidates: Vec<(T, RenderGraphResourceLifetime)>
+    ) -> Vec<Vec<T>> {
+        struct SharedAllocation<T> {
+            queue: RenderGraphQueue,
+            last_pass_index: usize,
+            resources: Vec<T>,
+        }
+
+        candidates.sort_by_key(|(_, lifetime)| lifetime.first_pass_index);
+
+        let mut allocations = Vec::<SharedAllocation<T>>::default();
+        for (resource, lifetime) in candidates {
+            let queue = match lifetime.queue {
+                Some(queue) => queue,
+                None => continue,
+            };
+
+            let allocation = allocations.iter_mut().find(|allocation| {
+                allocation.queue == queue && allocation.last_pass_index < lifetime.first_pass_index
+            });
+
+            if let Some(allocation) = allocation {
+                log::trace!(
+                    "  {:?} aliases {:?} Used in passes [{}:{}]",
+                    resource,
+                    allocation.resources.last().unwrap(),
+                    lifetime.first_pass_index,
+                    lifetime.last_pass_index
+                );
+                allocation.last_pass_index = lifetime.last_pass_index;
+                allocation.resources.push(resource);
+            } else {
+                allocations.push(SharedAllocation {
+                    queue,
+                    last_pass_index: lifetime.last_pass_index,
+                    resources: vec![resource],
+                });
+            }
+        }
+
+        allocations
+            .into_iter()
+            .map(|allocation| allocation.resources)
+            .filter(|resources| resources.len() > 1)
+            .collect()
+    }
+
+    let aliased_images = assign_aliases(image_candidates);
+    let aliased_buffers = assign_aliases(buffer_candidates);
+
+    AssignMemoryAliasesResult {
+        image_lifetimes,
+        buffer_lifetimes,
+        aliased_images,
+        aliased_buffers,
+    }
+}
+
 // * At this point we know images/image views, format, samples, load/store ops. We also know what
 //   needs to be flushed/invalidated
 // * We want to determine layouts and the validates/flushes we actually need to insert. Essentially
@@ -1998,6 +3432,7 @@ fn build_pass_barriers(
     _node_execution_order: &[RenderGraphNodeId],
     _constraints: &DetermineConstraintsResult,
     physical_resources: &AssignPhysicalResourcesResult,
+    memory_aliases: &AssignMemoryAliasesResult,
     node_barriers: &FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers>,
     passes: &mut [RenderGraphPass],
 ) {
@@ -2006,15 +3441,135 @@ fn build_pass_barriers(
     //
     // We will walk through all nodes keeping track of memory access as we go
     //
+    struct SubresourceTransition {
+        array_slice: Option<u16>,
+        mip_slice: Option<u8>,
+        old_state: RafxResourceState,
+    }
+
+    // Image state is tracked for every array layer and mip
     struct ImageState {
-        resource_state: RafxResourceState,
+        layer_count: u32,
+        mip_count: u32,
+        // Indexed by layer * mip_count + mip
+        resource_states: Vec<RafxResourceState>,
     }
 
-    impl Default for ImageState {
-        fn default() -> Self {
+    impl ImageState {
+        fn new(specification: &RenderGraphImageSpecification) -> Self {
+            let layer_count = specification.layer_count.max(1);
+            let mip_count = specification.mip_count.max(1);
             ImageState {
-                resource_state: RafxResourceState::UNDEFINED,
+                layer_count,
+                mip_count,
+                resource_states: vec![
+                    RafxResourceState::UNDEFINED;
+                    (layer_count * mip_count) as usize
+                ],
+            }
+        }
+
+        fn subresource_indices(
+            &self,
+            subresource: RenderGraphImageSubresource,
+        ) -> Vec<(u32, u32)> {
+            let layers = match subresource.array_slice {
+                Some(array_slice) => array_slice as u32..array_slice as u32 + 1,
+                None => 0..self.layer_count,
+            };
+
+            let mut indices = Vec::default();
+            for layer in layers {
+                let mips = match subresource.mip_slice {
+                    Some(mip_slice) => mip_slice as u32..mip_slice as u32 + 1,
+                    None => 0..self.mip_count,
+                };
+
+                for mip in mips {
+                    indices.push((layer, mip));
+                }
+            }
+
+            indices
+        }
+
+        // Moves the subresource to the new state, returning the transitions required to do so. If
+        // the whole subresource was in the same state this is a single transition, otherwise there
+        // is one per layer and mip that changes
+        fn transition(
+            &mut self,
+            subresource: RenderGraphImageSubresource,
+            new_state: RafxResourceState,
+        ) -> Vec<SubresourceTransition> {
+            let indices = self.subresource_indices(subresource);
+            let mip_count = self.mip_count;
+            let state_index = |layer: u32, mip: u32| (layer * mip_count + mip) as usize;
+
+            let first_state = self.resource_states[state_index(indices[0].0, indices[0].1)];
+            let is_uniform = indices
+                .iter()
+                .all(|(layer, mip)| self.resource_states[state_index(*layer, *mip)] == first_state);
+
+            let mut transitions = Vec::default();
+            if is_uniform {
+                if first_state != new_state {
+                    transitions.push(SubresourceTransition {
+                        array_slice: subresource.array_slice,
+                        mip_slice: subresource.mip_slice,
+                        old_state: first_state,
+                    });
+                }
+            } else {
+                for &(layer, mip) in &indices {
+                    let old_state = self.resource_states[state_index(layer, mip)];
+                    if old_state != new_state {
+                        transitions.push(SubresourceTransition {
+                            array_slice: Some(layer as u16),
+                            mip_slice: Some(mip as u8),
+                            old_state,
+                        });
+                    }
+                }
+            }
+
+            for (layer, mip) in indices {
+                self.resource_states[state_index(layer, mip)] = new_state;
+            }
+
+            transitions
+        }
+
+        // The state of every subresource that has been used, merged into a single entry if they
+        // all match
+        fn current_states(&self) -> Vec<SubresourceTransition> {
+            let first_state = self.resource_states[0];
+            if self.resource_states.iter().all(|x| *x == first_state) {
+                if first_state == RafxResourceState::UNDEFINED {
+                    return vec![];
+                }
+
+                return vec![SubresourceTransition {
+                    array_slice: None,
+                    mip_slice: None,
+                    old_state: first_state,
+                }];
             }
+
+            let mut states = Vec::default();
+            for layer in 0..self.layer_count {
+                for mip in 0..self.mip_count {
+                    let old_state = self.resource_states[(layer * self.mip_count + mip) as usize];
+                    if old_state != RafxResourceState::UNDEFINED {
+                        states.push(SubresourceTransition {
+                            array_slice: Some(layer as u16),
+                            mip_slice: Some(mip as u8),
+                            old_state,
+                        });
+                    }
+                }
+            }
+
+            states
         }
     }
 
@@ -2031,12 +3586,11 @@ fn build_pass_barriers(
     }
 
     //TODO: to support subpass, probably need image states for each previous subpass
-    // TODO: This is coarse-grained over the whole image. Ideally it would be per-layer and per-mip
-    let mut image_states: Vec<ImageState> =
-        Vec::with_capacity(physical_resources.image_specifications.len());
-    image_states.resize_with(physical_resources.image_specifications.len(), || {
-        Default::default()
-    });
+    let mut image_states: Vec<ImageState> = physical_resources
+        .image_specifications
+        .iter()
+        .map(ImageState::new)
+        .collect();
 
     let mut buffer_states: Vec<BufferState> =
         Vec::with_capacity(physical_resources.buffer_specifications.len());
@@ -2044,6 +3598,39 @@ fn build_pass_barriers(
         Default::default()
     });
 
+    // External resources are provided in a known state when the graph begins executing
+    for external_image in &graph.external_images {
+        let physical_image = physical_resources.image_usage_to_physical[&external_image.usage];
+        for resource_state in &mut image_states[physical_image.0].resource_states {
+            *resource_state = external_image.initial_state;
+        }
+    }
+
+    for external_buffer in &graph.external_buffers {
+        let physical_buffer = physical_resources.buffer_usage_to_physical[&external_buffer.usage];
+        buffer_states[physical_buffer.0].resource_state = external_buffer.initial_state;
+    }
+
+    // The last pass that touches each resource, used to transition external resources to their
+    // final state
+    let mut image_last_pass_index = FnvHashMap::<PhysicalImageId, usize>::default();
+    let mut buffer_last_pass_index = FnvHashMap::<PhysicalBufferId, usize>::default();
+
+    // The resource that used the same memory before each aliased resource
+    let mut image_alias_predecessors = FnvHashMap::<PhysicalImageId, PhysicalImageId>::default();
+    for aliased_images in &memory_aliases.aliased_images {
+        for pair in aliased_images.windows(2) {
+            image_alias_predecessors.insert(pair[1], pair[0]);
+        }
+    }
+
+    let mut buffer_alias_predecessors = FnvHashMap::<PhysicalBufferId, PhysicalBufferId>::default();
+    for aliased_buffers in &memory_aliases.aliased_buffers {
+        for pair in aliased_buffers.windows(2) {
+            buffer_alias_predecessors.insert(pair[1], pair[0]);
+        }
+    }
+
     for (pass_index, pass) in passes.iter_mut().enumerate() {
         log::trace!("pass {}", pass_index);
 
@@ -2053,13 +3640,10 @@ fn build_pass_barriers(
             attachment_initial_state.resize_with(pass.attachments.len(), || None);
         }
 
-        //let nodes: Vec<_> = pass.nodes().iter().copied().collect();
-        //for (subpass_index, subpass_node_id) in nodes.iter().enumerate() {
-        let subpass_node_id = pass.node();
-        let node_barriers = &node_barriers[&subpass_node_id];
-
         struct ImageTransition {
             physical_image_id: PhysicalImageId,
+            array_slice: Option<u16>,
+            mip_slice: Option<u8>,
             old_state: RafxResourceState,
             new_state: RafxResourceState,
         }
@@ -2071,77 +3655,145 @@ fn build_pass_barriers(
         }
 
         let mut image_transitions = Vec::default();
-        // Look at all the images we read and determine what invalidates we need
-        for (physical_image_id, image_barrier) in &node_barriers.image_barriers {
-            log::trace!("    image {:?}", physical_image_id);
-            let image_state = &mut image_states[physical_image_id.0];
+        let mut first_used_images = Vec::default();
+        let mut buffer_transitions = Vec::default();
+        let mut first_used_buffers = Vec::default();
+
+        // Subpasses of a renderpass are merged such that their barriers don't conflict, so all
+        // transitions can happen before the renderpass begins
+        for subpass_node_id in pass.nodes() {
+            let node_barriers = &node_barriers[&subpass_node_id];
+
+            // Look at all the images we read and determine what invalidates we need
+            for ((physical_image_id, subresource), image_barrier) in &node_barriers.image_barriers {
+                log::trace!("    image {:?} {:?}", physical_image_id, subresource);
+                if image_last_pass_index
+                    .insert(*physical_image_id, pass_index)
+                    .is_none()
+                {
+                    first_used_images.push(*physical_image_id);
+                }
+                let image_state = &mut image_states[physical_image_id.0];
 
-            let resource_state_change = image_state.resource_state != image_barrier.resource_state;
-            if resource_state_change {
-                log::trace!(
-                    "      state change! {:?} -> {:?}",
-                    image_state.resource_state,
-                    image_barrier.resource_state
-                );
+                for transition in image_state.transition(*subresource, image_barrier.resource_state)
+                {
+                    log::trace!(
+                        "      state change! {:?} -> {:?} (array slice {:?}, mip slice {:?})",
+                        transition.old_state,
+                        image_barrier.resource_state,
+                        transition.array_slice,
+                        transition.mip_slice
+                    );
 
-                if resource_state_change {
                     image_transitions.push(ImageTransition {
                         physical_image_id: *physical_image_id,
-                        old_state: image_state.resource_state,
+                        array_slice: transition.array_slice,
+                        mip_slice: transition.mip_slice,
+                        old_state: transition.old_state,
                         new_state: image_barrier.resource_state,
                     });
                 }
 
-                image_state.resource_state = image_barrier.resource_state;
-            }
-
-            // Set the initial layout for the attachment, but only if it's the first time we've seen it
-            //TODO: This is bad and does not properly handle an image being used in multiple ways requiring
-            // multiple layouts
-            if let RenderGraphPass::Renderpass(pass) = pass {
-                for (attachment_index, attachment) in &mut pass.attachments.iter_mut().enumerate() {
-                    //log::trace!("      attachment {:?}", attachment.image);
-                    if attachment.image.unwrap() == *physical_image_id {
-                        if attachment_initial_state[attachment_index].is_none() {
-                            //log::trace!("        initial layout {:?}", image_barrier.layout);
-                            attachment_initial_state[attachment_index] =
-                                Some(image_state.resource_state.into());
-
-                            // Use an image barrier before the pass to transition the layout,
-                            // so we will already be in the correct layout before starting the
-                            // pass.
-                            attachment.initial_state = image_barrier.resource_state.into();
-                        }
+                // Set the initial layout for the attachment, but only if it's the first time we've seen it
+                //TODO: This is bad and does not properly handle an image being used in multiple ways requiring
+                // multiple layouts
+                if let RenderGraphPass::Renderpass(pass) = pass {
+                    for (attachment_index, attachment) in
+                        &mut pass.attachments.iter_mut().enumerate()
+                    {
+                        //log::trace!("      attachment {:?}", attachment.image);
+                        if attachment.image.unwrap() == *physical_image_id
+                            && graph.image_subresource(attachment.usage) == *subresource
+                        {
+                            if attachment_initial_state[attachment_index].is_none() {
+                                //log::trace!("        initial layout {:?}", image_barrier.layout);
+                                attachment_initial_state[attachment_index] =
+                                    Some(image_barrier.resource_state.into());
+
+                                // Use an image barrier before the pass to transition the layout,
+                                // so we will already be in the correct layout before starting the
+                                // pass.
+                                attachment.initial_state = image_barrier.resource_state.into();
+                            }
 
-                        attachment.final_state = image_barrier.resource_state.into();
-                        break;
+                            attachment.final_state = image_barrier.resource_state.into();
+                            break;
+                        }
                     }
                 }
             }
-        }
 
-        // Look at all the buffers we read and determine what invalidates we need
-        let mut buffer_transitions = Vec::default();
-        for (physical_buffer_id, buffer_barrier) in &node_barriers.buffer_barriers {
-            log::trace!("    buffer {:?}", physical_buffer_id);
-            let buffer_state = &mut buffer_states[physical_buffer_id.0];
+            // Look at all the buffers we read and determine what invalidates we need
+            for (physical_buffer_id, buffer_barrier) in &node_barriers.buffer_barriers {
+                log::trace!("    buffer {:?}", physical_buffer_id);
+                if buffer_last_pass_index
+                    .insert(*physical_buffer_id, pass_index)
+                    .is_none()
+                {
+                    first_used_buffers.push(*physical_buffer_id);
+                }
+                let buffer_state = &mut buffer_states[physical_buffer_id.0];
+
+                let resource_state_change =
+                    buffer_state.resource_state != buffer_barrier.resource_state;
+                if resource_state_change {
+                    log::trace!(
+                        "      state change! {:?} -> {:?}",
+                        buffer_state.resource_state,
+                        buffer_barrier.resource_state
+                    );
+
+                    buffer_transitions.push(BufferTransition {
+                        physical_buffer_id: *physical_buffer_id,
+                        old_state: buffer_state.resource_state,
+                        new_state: buffer_barrier.resource_state,
+                    });
+
+                    buffer_state.resource_state = buffer_barrier.resource_state;
+                }
+            }
+        }
 
-            let resource_state_change =
-                buffer_state.resource_state != buffer_barrier.resource_state;
-            if resource_state_change {
+        //
+        // A resource that takes over memory from another resource must wait for all access to the
+        // previous resource to finish. A barrier on the previous resource that keeps it in its
+        // current state is recorded in the same batch as the first transition of the new
+        // resource, which orders the two.
+        //
+        for physical_image_id in first_used_images {
+            if let Some(&previous_image_id) = image_alias_predecessors.get(&physical_image_id) {
                 log::trace!(
-                    "      state change! {:?} -> {:?}",
-                    buffer_state.resource_state,
-                    buffer_barrier.resource_state
+                    "    image {:?} takes over memory from {:?}",
+                    physical_image_id,
+                    previous_image_id
                 );
+                for state in image_states[previous_image_id.0].current_states() {
+                    image_transitions.push(ImageTransition {
+                        physical_image_id: previous_image_id,
+                        array_slice: state.array_slice,
+                        mip_slice: state.mip_slice,
+                        old_state: state.old_state,
+                        new_state: state.old_state,
+                    });
+                }
+            }
+        }
 
-                buffer_transitions.push(BufferTransition {
-                    physical_buffer_id: *physical_buffer_id,
-                    old_state: buffer_state.resource_state,
-                    new_state: buffer_barrier.resource_state,
-                });
-
-                buffer_state.resource_state = buffer_barrier.resource_state;
+        for physical_buffer_id in first_used_buffers {
+            if let Some(&previous_buffer_id) = buffer_alias_predecessors.get(&physical_buffer_id) {
+                log::trace!(
+                    "    buffer {:?} takes over memory from {:?}",
+                    physical_buffer_id,
+                    previous_buffer_id
+                );
+                let resource_state = buffer_states[previous_buffer_id.0].resource_state;
+                if resource_state != RafxResourceState::UNDEFINED {
+                    buffer_transitions.push(BufferTransition {
+                        physical_buffer_id: previous_buffer_id,
+                        old_state: resource_state,
+                        new_state: resource_state,
+                    });
+                }
             }
         }
 
@@ -2151,8 +3803,11 @@ fn build_pass_barriers(
                 assert_ne!(image_transition.new_state, RafxResourceState::UNDEFINED);
                 PrepassImageBarrier {
                     image: image_transition.physical_image_id,
+                    array_slice: image_transition.array_slice,
+                    mip_slice: image_transition.mip_slice,
                     old_state: image_transition.old_state,
                     new_state: image_transition.new_state,
+                    queue_transition: RenderGraphQueueTransition::None,
                 }
             })
             .collect();
@@ -2165,6 +3820,7 @@ fn build_pass_barriers(
                     buffer: buffer_transition.physical_buffer_id,
                     old_state: buffer_transition.old_state,
                     new_state: buffer_transition.new_state,
+                    queue_transition: RenderGraphQueueTransition::None,
                 }
             })
             .collect();
@@ -2181,8 +3837,10 @@ fn build_pass_barriers(
         // TODO: Figure out how to handle output images
         // TODO: This only works if no one else reads it?
         log::trace!("Check for output images");
+        let pass_nodes = pass.nodes();
         for (output_image_index, output_image) in graph.output_images.iter().enumerate() {
-            if graph.image_version_info(output_image.usage).creator_node == subpass_node_id {
+            let creator_node = graph.image_version_info(output_image.usage).creator_node;
+            if let Some(subpass_node_id) = creator_node.filter(|x| pass_nodes.contains(x)) {
                 let output_physical_image =
                     physical_resources.image_usage_to_physical[&output_image.usage];
                 log::trace!(
@@ -2203,20 +3861,27 @@ fn build_pass_barriers(
                             log::trace!("  attachment {}", attachment_index);
 
                             if attachment.final_state != output_image.final_state {
+                                let subresource = graph.image_subresource(attachment.usage);
                                 image_barriers.push(PrepassImageBarrier {
                                     image: attachment.image.unwrap(),
+                                    array_slice: subresource.array_slice,
+                                    mip_slice: subresource.mip_slice,
                                     old_state: attachment.final_state.into(),
                                     new_state: output_image.final_state.into(),
+                                    queue_transition: RenderGraphQueueTransition::None,
                                 })
                             }
                         }
                     }
 
                     if !image_barriers.is_empty() {
-                        pass.post_pass_barrier = Some(PostpassBarrier {
-                            buffer_barriers: vec![],
-                            image_barriers,
-                        });
+                        pass.post_pass_barrier
+                            .get_or_insert_with(|| PostpassBarrier {
+                                buffer_barriers: vec![],
+                                image_barriers: vec![],
+                            })
+                            .image_barriers
+                            .append(&mut image_barriers);
                     }
                 }
                 //TODO: Need a 0 -> EXTERNAL dependency here?
@@ -2225,13 +3890,462 @@ fn build_pass_barriers(
 
         //TODO: Need to do a dependency? Maybe by adding a flush?
     }
+
+    //
+    // Leave external resources in the state the caller expects. The transition is placed after the
+    // last pass that uses the resource (or the last pass in the graph if none of them do)
+    //
+    log::trace!("Transition external resources to their final states");
+    for external_image in &graph.external_images {
+        let physical_image = physical_resources.image_usage_to_physical[&external_image.usage];
+        let whole_image = RenderGraphImageSubresource {
+            array_slice: None,
+            mip_slice: None,
+        };
+        let transitions =
+            image_states[physical_image.0].transition(whole_image, external_image.final_state);
+        if transitions.is_empty() {
+            continue;
+        }
+
+        let pass_index = image_last_pass_index
+            .get(&physical_image)
+            .copied()
+            .unwrap_or(passes.len() - 1);
+        log::trace!(
+            "  External image {:?} -> {:?} after pass {}",
+            physical_image,
+            external_image.final_state,
+            pass_index
+        );
+
+        let post_pass_barrier = passes[pass_index]
+            .post_pass_barrier_mut()
+            .get_or_insert_with(|| PostpassBarrier {
+                image_barriers: vec![],
+                buffer_barriers: vec![],
+            });
+
+        for transition in transitions {
+            post_pass_barrier.image_barriers.push(PrepassImageBarrier {
+                image: physical_image,
+                array_slice: transition.array_slice,
+                mip_slice: transition.mip_slice,
+                old_state: transition.old_state,
+                new_state: external_image.final_state,
+                queue_transition: RenderGraphQueueTransition::None,
+            });
+        }
+    }
+
+    for external_buffer in &graph.external_buffers {
+        let physical_buffer = physical_resources.buffer_usage_to_physical[&external_buffer.usage];
+        let old_state = buffer_states[physical_buffer.0].resource_state;
+        if old_state == external_buffer.final_state {
+            continue;
+        }
+
+        let pass_index = buffer_last_pass_index
+            .get(&physical_buffer)
+            .copied()
+            .unwrap_or(passes.len() - 1);
+        log::trace!(
+            "  External buffer {:?} {:?} -> {:?} after pass {}",
+            physical_buffer,
+            old_state,
+            external_buffer.final_state,
+            pass_index
+        );
+
+        passes[pass_index]
+            .post_pass_barrier_mut()
+            .get_or_insert_with(|| PostpassBarrier {
+                image_barriers: vec![],
+                buffer_barriers: vec![],
+            })
+            .buffer_barriers
+            .push(PrepassBufferBarrier {
+                buffer: physical_buffer,
+                old_state,
+                new_state: external_buffer.final_state,
+                queue_transition: RenderGraphQueueTransition::None,
+            });
+    }
+}
+
+// Split the passes into submissions. Consecutive passes on the same queue are recorded into the
+// same submission. When a pass uses a resource that was last used on a different queue, its
+// submission waits on the submission that last used it and ownership of the resource is released
+// after the earlier pass and acquired before the later one.
+#[profiling::function]
+fn build_submissions(
+    graph: &RenderGraphBuilder,
+    physical_resources: &AssignPhysicalResourcesResult,
+    node_barriers: &FnvHashMap<RenderGraphNodeId, RenderGraphNodeResourceBarriers>,
+    passes: &mut [RenderGraphPass],
+) -> RenderGraphResult<Vec<RenderGraphPlanSubmission>> {
+    log::trace!("-- build_submissions --");
+
+    #[derive(Copy, Clone)]
+    struct LastAccess {
+        submission_index: usize,
+        pass_index: usize,
+        queue: RenderGraphQueue,
+        resource_state: RafxResourceState,
+    }
+
+    fn add_wait(
+        submission: &mut RenderGraphPlanSubmission,
+        wait_for: usize,
+    ) {
+        if !submission.wait_for.contains(&wait_for) {
+            submission.wait_for.push(wait_for);
+        }
+    }
+
+    let mut submissions = Vec::<RenderGraphPlanSubmission>::default();
+    let mut image_accesses =
+        FnvHashMap::<PhysicalImageId, Vec<(RenderGraphImageSubresource, LastAccess)>>::default();
+    let mut buffer_last_access = FnvHashMap::<PhysicalBufferId, LastAccess>::default();
+
+    for pass_index in 0..passes.len() {
+        // All nodes merged into a pass run on the same queue
+        let queue = graph.node(passes[pass_index].first_node()).queue;
+
+        if submissions.last().map(|x| x.queue) != Some(queue) {
+            submissions.push(RenderGraphPlanSubmission {
+                queue,
+                passes: Default::default(),
+                wait_for: Default::default(),
+                runs_in_parallel_with: Default::default(),
+            });
+        }
+
+        let submission_index = submissions.len() - 1;
+        submissions[submission_index].passes.push(pass_index);
+
+        for node_id in passes[pass_index].nodes() {
+            let node_barriers = &node_barriers[&node_id];
+            for ((physical_image, subresource), image_barrier) in &node_barriers.image_barriers {
+                // Find the last access to any overlapping layer or mip of the image
+                let accesses = image_accesses.entry(*physical_image).or_default();
+                let last_access = accesses
+                    .iter()
+                    .rev()
+                    .find(|(x, _)| x.overlaps(subresource))
+                    .map(|(_, x)| *x);
+
+                accesses.push((
+                    *subresource,
+                    LastAccess {
+                        submission_index,
+                        pass_index,
+                        queue,
+                        resource_state: image_barrier.resource_state,
+                    },
+                ));
+
+                let last_access = match last_access {
+                    Some(last_access) if last_access.queue != queue => last_access,
+                    _ => continue,
+                };
+
+                log::trace!(
+                    "  image {:?} {:?} moves from {:?} (pass {}) to {:?} (pass {})",
+                    physical_image,
+                    subresource,
+                    last_access.queue,
+                    last_access.pass_index,
+                    queue,
+                    pass_index
+                );
+
+                add_wait(
+                    &mut submissions[submission_index],
+                    last_access.submission_index,
+                );
+
+                // If build_pass_barriers already added transitions for this subresource (possibly
+                // split per layer and mip), turn them into acquires. Otherwise add one that only
+                // transfers ownership.
+                let pre_pass_barrier = passes[pass_index]
+                    .pre_pass_barrier_mut()
+                    .get_or_insert_with(|| PrepassBarrier {
+                        image_barriers: vec![],
+                        buffer_barriers: vec![],
+                    });
+
+                let mut release_barriers = Vec::default();
+                for barrier in &mut pre_pass_barrier.image_barriers {
+                    if barrier.image == *physical_image
+                        && barrier.subresource().overlaps(subresource)
+                    {
+                        barrier.queue_transition =
+                            RenderGraphQueueTransition::AcquireFrom(last_access.queue);
+                        release_barriers.push(PrepassImageBarrier {
+                            image: *physical_image,
+                            array_slice: barrier.array_slice,
+                            mip_slice: barrier.mip_slice,
+                            old_state: barrier.old_state,
+                            new_state: barrier.new_state,
+                            queue_transition: RenderGraphQueueTransition::ReleaseTo(queue),
+                        });
+                    }
+                }
+
+                if release_barriers.is_empty() {
+                    pre_pass_barrier.image_barriers.push(PrepassImageBarrier {
+                        image: *physical_image,
+                        array_slice: subresource.array_slice,
+                        mip_slice: subresource.mip_slice,
+                        old_state: image_barrier.resource_state,
+                        new_state: image_barrier.resource_state,
+                        queue_transition: RenderGraphQueueTransition::AcquireFrom(
+                            last_access.queue,
+                        ),
+                    });
+                    release_barriers.push(PrepassImageBarrier {
+                        image: *physical_image,
+                        array_slice: subresource.array_slice,
+                        mip_slice: subresource.mip_slice,
+                        old_state: image_barrier.resource_state,
+                        new_state: image_barrier.resource_state,
+                        queue_transition: RenderGraphQueueTransition::ReleaseTo(queue),
+                    });
+                }
+
+                passes[last_access.pass_index]
+                    .post_pass_barrier_mut()
+                    .get_or_insert_with(|| PostpassBarrier {
+                        image_barriers: vec![],
+                        buffer_barriers: vec![],
+                    })
+                    .image_barriers
+                    .append(&mut release_barriers);
+            }
+
+            for (physical_buffer, buffer_barrier) in &node_barriers.buffer_barriers {
+                let access = LastAccess {
+                    submission_index,
+                    pass_index,
+                    queue,
+                    resource_state: buffer_barrier.resource_state,
+                };
+
+                let last_access = match buffer_last_access.insert(*physical_buffer, access) {
+                    Some(last_access) if last_access.queue != queue => last_access,
+                    _ => continue,
+                };
+
+                log::trace!(
+                    "  buffer {:?} moves from {:?} (pass {}) to {:?} (pass {})",
+                    physical_buffer,
+                    last_access.queue,
+                    last_access.pass_index,
+                    queue,
+                    pass_index
+                );
+
+                add_wait(
+                    &mut submissions[submission_index],
+                    last_access.submission_index,
+                );
+
+                passes[last_access.pass_index]
+                    .post_pass_barrier_mut()
+                    .get_or_insert_with(|| PostpassBarrier {
+                        image_barriers: vec![],
+                        buffer_barriers: vec![],
+                    })
+                    .buffer_barriers
+                    .push(PrepassBufferBarrier {
+                        buffer: *physical_buffer,
+                        old_state: last_access.resource_state,
+                        new_state: buffer_barrier.resource_state,
+                        queue_transition: RenderGraphQueueTransition::ReleaseTo(queue),
+                    });
+
+                let pre_pass_barrier = passes[pass_index]
+                    .pre_pass_barrier_mut()
+                    .get_or_insert_with(|| PrepassBarrier {
+                        image_barriers: vec![],
+                        buffer_barriers: vec![],
+                    });
+                if let Some(barrier) = pre_pass_barrier
+                    .buffer_barriers
+                    .iter_mut()
+                    .find(|x| x.buffer == *physical_buffer)
+                {
+                    barrier.queue_transition =
+                        RenderGraphQueueTransition::AcquireFrom(last_access.queue);
+                } else {
+                    pre_pass_barrier.buffer_barriers.push(PrepassBufferBarrier {
+                        buffer: *physical_buffer,
+                        old_state: last_access.resource_state,
+                        new_state: buffer_barrier.resource_state,
+                        queue_transition: RenderGraphQueueTransition::AcquireFrom(
+                            last_access.queue,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    // Output and external resources are handed back to the caller, who will use them on the
+    // graphics queue. Ownership is not transferred back, so they should be last used by a graphics
+    // node.
+    let provided_images = graph
+        .output_images
+        .iter()
+        .map(|x| x.usage)
+        .chain(graph.external_images.iter().map(|x| x.usage));
+    for usage in provided_images {
+        let physical_image = physical_resources.image_usage_to_physical[&usage];
+        let last_access = image_accesses
+            .get(&physical_image)
+            .and_then(|x| x.last())
+            .map(|(_, x)| x);
+        if let Some(last_access) = last_access {
+            if last_access.queue != RenderGraphQueue::DefaultGraphics {
+                log::warn!(
+                    "Image {:?} is provided by the caller but is last used on queue {:?}",
+                    usage,
+                    last_access.queue
+                );
+            }
+        }
+    }
+
+    let provided_buffers = graph
+        .output_buffers
+        .iter()
+        .map(|x| x.usage)
+        .chain(graph.external_buffers.iter().map(|x| x.usage));
+    for usage in provided_buffers {
+        let physical_buffer = physical_resources.buffer_usage_to_physical[&usage];
+        if let Some(last_access) = buffer_last_access.get(&physical_buffer) {
+            if last_access.queue != RenderGraphQueue::DefaultGraphics {
+                log::warn!(
+                    "Buffer {:?} is provided by the caller but is last used on queue {:?}",
+                    usage,
+                    last_access.queue
+                );
+            }
+        }
+    }
+
+    //
+    // The caller submits the last submission with the frame's fence, so it must be on the
+    // graphics queue and must not finish before any other submission does
+    //
+    if submissions.last().map(|x| x.queue) != Some(RenderGraphQueue::DefaultGraphics) {
+        submissions.push(RenderGraphPlanSubmission {
+            queue: RenderGraphQueue::DefaultGraphics,
+            passes: Default::default(),
+            wait_for: Default::default(),
+            runs_in_parallel_with: Default::default(),
+        });
+    }
+
+    // The caller only waits for output images (such as a swapchain image) to be available when
+    // it submits the final submission, so every pass that uses one must be recorded into it
+    let final_submission_index = submissions.len() - 1;
+    for output_image in &graph.output_images {
+        let physical_image = physical_resources.image_usage_to_physical[&output_image.usage];
+        let accesses = image_accesses.get(&physical_image).into_iter().flatten();
+        for (_, access) in accesses {
+            if access.submission_index != final_submission_index {
+                let node = passes[access.pass_index].first_node();
+                return Err(RenderGraphError::OutputImageUsedBeforeFinalSubmission {
+                    node,
+                    node_name: graph.node(node).name(),
+                    image: output_image.usage,
+                    image_name: graph.image_resource(output_image.usage).name,
+                });
+            }
+        }
+    }
+
+    // A submission is ordered after everything it waits on and after earlier submissions on the
+    // same queue. Submissions are always created after their dependencies, so this can be done in
+    // a single pass.
+    let mut dependencies = Vec::<FnvHashSet<usize>>::with_capacity(submissions.len());
+    for submission_index in 0..submissions.len() {
+        let mut submission_dependencies = FnvHashSet::default();
+        let previous_on_queue = (0..submission_index)
+            .rev()
+            .find(|x| submissions[*x].queue == submissions[submission_index].queue);
+        for &direct_dependency in submissions[submission_index]
+            .wait_for
+            .iter()
+            .chain(previous_on_queue.iter())
+        {
+            submission_dependencies.insert(direct_dependency);
+            submission_dependencies.extend(dependencies[direct_dependency].iter().copied());
+        }
+
+        dependencies.push(submission_dependencies);
+    }
+
+    for submission_index in 0..final_submission_index {
+        let is_last_on_queue = !submissions[submission_index + 1..]
+            .iter()
+            .any(|x| x.queue == submissions[submission_index].queue);
+        if is_last_on_queue && !dependencies[final_submission_index].contains(&submission_index) {
+            add_wait(&mut submissions[final_submission_index], submission_index);
+            let submission_dependencies = dependencies[submission_index].clone();
+            dependencies[final_submission_index].insert(submission_index);
+            dependencies[final_submission_index].extend(submission_dependencies);
+        }
+    }
+
+    for i in 0..submissions.len() {
+        for j in 0..submissions.len() {
+            if i != j && !dependencies[i].contains(&j) && !dependencies[j].contains(&i) {
+                submissions[i].runs_in_parallel_with.push(j);
+            }
+        }
+    }
+
+    Ok(submissions)
+}
+
+fn print_submissions(
+    graph: &RenderGraphBuilder,
+    passes: &[RenderGraphPass],
+    submissions: &[RenderGraphPlanSubmission],
+) {
+    log::trace!("Submissions:");
+    for (submission_index, submission) in submissions.iter().enumerate() {
+        log::trace!(
+            "  Submission {} on {:?}",
+            submission_index,
+            submission.queue
+        );
+        log::trace!("    waits for: {:?}", submission.wait_for);
+        log::trace!(
+            "    runs in parallel with: {:?}",
+            submission.runs_in_parallel_with
+        );
+        for pass_index in &submission.passes {
+            for node_id in passes[*pass_index].nodes() {
+                log::trace!(
+                    "    pass {} node {:?} {:?}",
+                    pass_index,
+                    node_id,
+                    graph.node(node_id).name()
+                );
+            }
+        }
+    }
 }
 
 #[profiling::function]
 fn create_output_passes(
     graph: &RenderGraphBuilder,
     passes: Vec<RenderGraphPass>,
-) -> Vec<RenderGraphOutputPass> {
+) -> RenderGraphResult<Vec<RenderGraphOutputPass>> {
     let mut renderpasses = Vec::with_capacity(passes.len());
 
     for pass in passes {
@@ -2245,46 +4359,11 @@ fn create_output_passes(
                     .map(|attachment| attachment.image_view.unwrap())
                     .collect();
 
-                let debug_name = graph.node(pass.node_id).name;
-
-                let mut color_formats = vec![];
-                let mut sample_count = None;
-                for color_attachment in &pass.color_attachments {
-                    if let Some(color_attachment) = color_attachment {
-                        color_formats.push(pass.attachments[*color_attachment].format);
-
-                        let expected_sample_count = pass.attachments[*color_attachment].samples;
-                        if let Some(sample_count) = sample_count {
-                            assert_eq!(sample_count, expected_sample_count, "Render node has color attachments with different sample counts, this is unsupported.");
-                        } else {
-                            sample_count = Some(expected_sample_count);
-                        }
-                    }
-                }
-
-                let mut depth_format = None;
-                if let Some(depth_attachment) = pass.depth_attachment {
-                    depth_format = Some(pass.attachments[depth_attachment].format);
-
-                    let expected_sample_count = pass.attachments[depth_attachment].samples;
-                    if let Some(sample_count) = sample_count {
-                        assert_eq!(sample_count, expected_sample_count, "Render node has color attachment and depth attachment with different sample counts, this is unsupported.");
-                    } else {
-                        sample_count = Some(expected_sample_count);
-                    }
-                }
-
-                let render_target_meta = GraphicsPipelineRenderTargetMeta::new(
-                    color_formats,
-                    depth_format,
-                    sample_count.unwrap(),
-                );
-
-                let mut color_render_targets = Vec::with_capacity(MAX_COLOR_ATTACHMENTS);
+                let debug_name = graph.node(pass.subpasses[0].node_id).name;
 
-                for (color_index, attachment_index) in pass.color_attachments.iter().enumerate() {
-                    if let Some(attachment_index) = attachment_index {
-                        let attachment = &pass.attachments[*attachment_index]; //.image.unwrap();
+                let color_render_target =
+                    |attachment: &RenderGraphPassAttachment,
+                     resolve_attachment: Option<&RenderGraphPassAttachment>| {
                         let attachment_usage = &graph.image_usages[attachment.usage.0];
                         let array_slice = attachment_usage.view_options.array_slice;
                         let mip_slice = attachment_usage.view_options.mip_slice;
@@ -2293,10 +4372,7 @@ fn create_output_passes(
                         let mut resolve_array_slice = None;
                         let mut resolve_mip_slice = None;
                         let mut resolve_store_op = RafxStoreOp::DontCare;
-                        if let Some(resolve_attachment_index) =
-                            pass.resolve_attachments[color_index]
-                        {
-                            let resolve_attachment = &pass.attachments[resolve_attachment_index]; //.image.unwrap();
+                        if let Some(resolve_attachment) = resolve_attachment {
                             let resolve_attachment_usage =
                                 &graph.image_usages[resolve_attachment.usage.0];
                             resolve_image = Some(resolve_attachment.image.unwrap());
@@ -2305,7 +4381,7 @@ fn create_output_passes(
                             resolve_store_op = resolve_attachment.store_op;
                         }
 
-                        color_render_targets.push(RenderGraphColorRenderTarget {
+                        RenderGraphColorRenderTarget {
                             image: attachment.image.unwrap(),
                             load_op: attachment.load_op,
                             store_op: attachment.store_op,
@@ -2320,12 +4396,77 @@ fn create_output_passes(
                             resolve_store_op,
                             resolve_array_slice,
                             resolve_mip_slice,
+                        }
+                    };
+
+                let mut color_render_targets = Vec::with_capacity(MAX_COLOR_ATTACHMENTS);
+                let mut render_pass_color_formats = Vec::default();
+                let mut subpass_defs = Vec::default();
+
+                // Index of the color render target used for each attachment
+                let mut color_target_indices = vec![None; pass.attachments.len()];
+
+                if pass.subpasses.len() == 1 {
+                    let subpass = &pass.subpasses[0];
+                    for (color_index, attachment_index) in
+                        subpass.color_attachments.iter().enumerate()
+                    {
+                        if let Some(attachment_index) = attachment_index {
+                            let resolve_attachment = subpass.resolve_attachments[color_index]
+                                .map(|x| &pass.attachments[x]);
+                            color_target_indices[*attachment_index] =
+                                Some(color_render_targets.len() as u32);
+                            color_render_targets.push(color_render_target(
+                                &pass.attachments[*attachment_index],
+                                resolve_attachment,
+                            ));
+                        }
+                    }
+                } else {
+                    // Merged renderpasses never have resolve attachments, so every attachment
+                    // other than depth is a color target
+                    for (attachment_index, attachment) in pass.attachments.iter().enumerate() {
+                        if pass
+                            .subpasses
+                            .iter()
+                            .any(|x| x.depth_attachment == Some(attachment_index))
+                        {
+                            continue;
+                        }
+
+                        color_target_indices[attachment_index] =
+                            Some(color_render_targets.len() as u32);
+                        color_render_targets.push(color_render_target(attachment, None));
+                        render_pass_color_formats.push(attachment.format);
+                    }
+
+                    for subpass in &pass.subpasses {
+                        subpass_defs.push(RafxSubpassDef {
+                            color_attachments: subpass
+                                .color_attachments
+                                .iter()
+                                .flatten()
+                                .map(|x| color_target_indices[*x].unwrap())
+                                .collect(),
+                            input_attachments: subpass
+                                .input_attachments
+                                .iter()
+                                .flatten()
+                                .map(|x| match color_target_indices[*x] {
+                                    Some(color_target_index) => {
+                                        RafxSubpassInputAttachment::Color(color_target_index)
+                                    }
+                                    None => RafxSubpassInputAttachment::DepthStencil,
+                                })
+                                .collect(),
+                            use_depth_stencil_attachment: subpass.depth_attachment.is_some(),
                         });
                     }
                 }
 
                 let mut depth_stencil_render_target = None;
-                if let Some(attachment_index) = pass.depth_attachment {
+                let pass_depth_attachment = pass.subpasses.iter().find_map(|x| x.depth_attachment);
+                if let Some(attachment_index) = pass_depth_attachment {
                     let attachment = &pass.attachments[attachment_index];
                     let array_slice = graph.image_usages[attachment.usage.0]
                         .view_options
@@ -2349,15 +4490,80 @@ fn create_output_passes(
                     });
                 }
 
+                // Pipelines used in a subpass must be created with the formats of the attachments
+                // that subpass writes, and also need to know the layout of the whole renderpass
+                let mut subpasses = Vec::with_capacity(pass.subpasses.len());
+                for (subpass_index, subpass) in pass.subpasses.iter().enumerate() {
+                    let mut color_formats = vec![];
+                    let mut sample_count = None;
+                    for color_attachment in &subpass.color_attachments {
+                        if let Some(color_attachment) = color_attachment {
+                            color_formats.push(pass.attachments[*color_attachment].format);
+
+                            let expected_sample_count = pass.attachments[*color_attachment].samples;
+                            if let Some(sample_count) = sample_count {
+                                if sample_count != expected_sample_count {
+                                    return Err(RenderGraphError::MismatchedSampleCounts {
+                                        node: subpass.node_id,
+                                        node_name: graph.node(subpass.node_id).name,
+                                    });
+                                }
+                            } else {
+                                sample_count = Some(expected_sample_count);
+                            }
+                        }
+                    }
+
+                    let mut depth_format = None;
+                    if let Some(depth_attachment) = subpass.depth_attachment {
+                        depth_format = Some(pass.attachments[depth_attachment].format);
+
+                        let expected_sample_count = pass.attachments[depth_attachment].samples;
+                        if let Some(sample_count) = sample_count {
+                            if sample_count != expected_sample_count {
+                                return Err(RenderGraphError::MismatchedSampleCounts {
+                                    node: subpass.node_id,
+                                    node_name: graph.node(subpass.node_id).name,
+                                });
+                            }
+                        } else {
+                            sample_count = Some(expected_sample_count);
+                        }
+                    }
+
+                    let subpass_layout = if subpass_defs.is_empty() {
+                        None
+                    } else {
+                        Some(RafxSubpassLayout {
+                            render_pass_color_formats: render_pass_color_formats.clone(),
+                            subpasses: subpass_defs.clone(),
+                            subpass_index: subpass_index as u32,
+                        })
+                    };
+
+                    let render_target_meta = GraphicsPipelineRenderTargetMeta::new(
+                        color_formats,
+                        depth_format,
+                        sample_count.unwrap(),
+                        subpass_layout,
+                    );
+
+                    subpasses.push(RenderGraphOutputSubpass {
+                        node_id: subpass.node_id,
+                        debug_name: graph.node(subpass.node_id).name,
+                        render_target_meta,
+                    });
+                }
+
                 let output_pass = RenderGraphOutputRenderPass {
-                    node_id: pass.node_id,
+                    subpasses,
                     attachment_images,
                     pre_pass_barrier: pass.pre_pass_barrier,
                     post_pass_barrier: pass.post_pass_barrier,
                     debug_name,
                     color_render_targets,
                     depth_stencil_render_target,
-                    render_target_meta,
+                    subpass_defs,
                 };
 
                 renderpasses.push(RenderGraphOutputPass::Renderpass(output_pass));
@@ -2366,7 +4572,7 @@ fn create_output_passes(
                 let output_pass = RenderGraphOutputComputePass {
                     node: pass.node,
                     pre_pass_barrier: pass.pre_pass_barrier,
-                    post_pass_barrier: None,
+                    post_pass_barrier: pass.post_pass_barrier,
                     debug_name: graph.node(pass.node).name,
                 };
 
@@ -2375,7 +4581,7 @@ fn create_output_passes(
         }
     }
 
-    renderpasses
+    Ok(renderpasses)
 }
 
 #[allow(dead_code)]
@@ -2463,8 +4669,13 @@ fn print_node_barriers(
     for (node_id, barriers) in node_barriers.iter() {
         log::trace!("  pass {:?}", node_id);
         log::trace!("    resource states");
-        for (physical_id, barriers) in &barriers.image_barriers {
-            log::trace!("      {:?}: {:?}", physical_id, barriers.resource_state);
+        for ((physical_id, subresource), barriers) in &barriers.image_barriers {
+            log::trace!(
+                "      {:?} {:?}: {:?}",
+                physical_id,
+                subresource,
+                barriers.resource_state
+            );
         }
 
         for (physical_id, barriers) in &barriers.buffer_barriers {
@@ -2478,22 +4689,22 @@ fn verify_unculled_image_usages_specifications_exist(
     graph: &RenderGraphBuilder,
     node_execution_order: &Vec<RenderGraphNodeId>,
     constraint_results: &DetermineConstraintsResult,
-) {
+) -> RenderGraphResult<()> {
     for (_image_index, image_resource) in graph.image_resources.iter().enumerate() {
         //log::trace!("  Image {:?} {:?}", image_index, image_resource.name);
         for (_version_index, version) in image_resource.versions.iter().enumerate() {
             // Check the write usage for this version
-            if node_execution_order.contains(&version.creator_node)
+            let is_scheduled = match version.creator_node {
+                Some(creator_node) => node_execution_order.contains(&creator_node),
+                None => true,
+            };
+            if is_scheduled
                 && constraint_results
                     .images
                     .get(&version.create_usage)
                     .is_none()
             {
-                let usage_info = &graph.image_usages[version.create_usage.0];
-                panic!(
-                    "Could not determine specification for image {:?} use by {:?} for {:?}",
-                    version.create_usage, usage_info.user, usage_info.usage_type
-                );
+                return Err(unknown_image_specification(graph, version.create_usage));
             }
 
             // Check the read usages for this version
@@ -2502,30 +4713,78 @@ fn verify_unculled_image_usages_specifications_exist(
                 let is_scheduled = match &usage_info.user {
                     RenderGraphImageUser::Node(node_id) => node_execution_order.contains(node_id),
                     RenderGraphImageUser::Output(_) => true,
+                    RenderGraphImageUser::Input(_) => true,
                 };
 
                 if is_scheduled && constraint_results.images.get(usage).is_none() {
-                    panic!(
-                        "Could not determine specification for image {:?} used by {:?} for {:?}",
-                        usage, usage_info.user, usage_info.usage_type
-                    );
+                    return Err(unknown_image_specification(graph, *usage));
                 }
             }
         }
     }
+
+    Ok(())
 }
 
-#[allow(dead_code)]
-fn print_final_images(
-    output_images: &FnvHashMap<PhysicalImageViewId, RenderGraphPlanOutputImage>,
-    intermediate_images: &FnvHashMap<PhysicalImageId, RenderGraphImageSpecification>,
-) {
-    log::trace!("-- IMAGES --");
-    for (physical_id, intermediate_image_spec) in intermediate_images {
-        log::trace!(
-            "Intermediate Image: {:?} {:?}",
-            physical_id,
-            intermediate_image_spec
+// Physical resources may be shared by several virtual resources, so join all the names given to
+// resources that were assigned to the same physical resource
+fn determine_physical_image_names(
+    graph: &RenderGraphBuilder,
+    image_usage_to_physical: &FnvHashMap<RenderGraphImageUsageId, PhysicalImageId>,
+) -> FnvHashMap<PhysicalImageId, String> {
+    let mut names = FnvHashMap::<PhysicalImageId, Vec<RenderGraphResourceName>>::default();
+    for (&usage, &physical_image) in image_usage_to_physical {
+        if let Some(name) = graph.image_resource(usage).name {
+            let names = names.entry(physical_image).or_default();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|(physical_image, mut names)| {
+            names.sort_unstable();
+            (physical_image, names.join("/"))
+        })
+        .collect()
+}
+
+fn determine_physical_buffer_names(
+    graph: &RenderGraphBuilder,
+    buffer_usage_to_physical: &FnvHashMap<RenderGraphBufferUsageId, PhysicalBufferId>,
+) -> FnvHashMap<PhysicalBufferId, String> {
+    let mut names = FnvHashMap::<PhysicalBufferId, Vec<RenderGraphResourceName>>::default();
+    for (&usage, &physical_buffer) in buffer_usage_to_physical {
+        if let Some(name) = graph.buffer_resource(usage).name {
+            let names = names.entry(physical_buffer).or_default();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    names
+        .into_iter()
+        .map(|(physical_buffer, mut names)| {
+            names.sort_unstable();
+            (physical_buffer, names.join("/"))
+        })
+        .collect()
+}
+
+#[allow(dead_code)]
+fn print_final_images(
+    output_images: &FnvHashMap<PhysicalImageViewId, RenderGraphPlanOutputImage>,
+    intermediate_images: &FnvHashMap<PhysicalImageId, RenderGraphImageSpecification>,
+) {
+    log::trace!("-- IMAGES --");
+    for (physical_id, intermediate_image_spec) in intermediate_images {
+        log::trace!(
+            "Intermediate Image: {:?} {:?}",
+            physical_id,
+            intermediate_image_spec
         );
     }
     for (physical_id, output_image) in output_images {
@@ -2544,79 +4803,82 @@ fn print_final_image_usage(
     for (pass_index, pass) in renderpasses.iter().enumerate() {
         log::debug!("pass {}", pass_index);
 
-        let node = graph.node(pass.node());
-        log::debug!("  subpass {:?} {:?}", pass.node(), node.name);
+        for node_id in pass.nodes() {
+            let node = graph.node(node_id);
+            log::debug!("  subpass {:?} {:?}", node_id, node.name);
 
-        for (color_attachment_index, color_attachment) in node.color_attachments.iter().enumerate()
-        {
-            if let Some(color_attachment) = color_attachment {
-                let read_or_write = color_attachment
+            for (color_attachment_index, color_attachment) in
+                node.color_attachments.iter().enumerate()
+            {
+                if let Some(color_attachment) = color_attachment {
+                    let read_or_write = color_attachment
+                        .read_image
+                        .or_else(|| color_attachment.write_image)
+                        .unwrap();
+                    let physical_image =
+                        assign_physical_resources_result.image_usage_to_physical[&read_or_write];
+                    let write_name = color_attachment
+                        .write_image
+                        .map(|x| graph.image_resource(x).name)
+                        .flatten();
+                    log::debug!(
+                        "    Color Attachment {}: {:?} Name: {:?} Constraints: {:?}",
+                        color_attachment_index,
+                        physical_image,
+                        write_name,
+                        constraint_results.images[&read_or_write]
+                    );
+                }
+            }
+
+            for (resolve_attachment_index, resolve_attachment) in
+                node.resolve_attachments.iter().enumerate()
+            {
+                if let Some(resolve_attachment) = resolve_attachment {
+                    let physical_image = assign_physical_resources_result.image_usage_to_physical
+                        [&resolve_attachment.write_image];
+                    let write_name = graph.image_resource(resolve_attachment.write_image).name;
+                    log::debug!(
+                        "    Resolve Attachment {}: {:?} Name: {:?} Constraints: {:?}",
+                        resolve_attachment_index,
+                        physical_image,
+                        write_name,
+                        constraint_results.images[&resolve_attachment.write_image]
+                    );
+                }
+            }
+
+            if let Some(depth_attachment) = &node.depth_attachment {
+                let read_or_write = depth_attachment
                     .read_image
-                    .or_else(|| color_attachment.write_image)
+                    .or_else(|| depth_attachment.write_image)
                     .unwrap();
                 let physical_image =
                     assign_physical_resources_result.image_usage_to_physical[&read_or_write];
-                let write_name = color_attachment
+                let write_name = depth_attachment
                     .write_image
                     .map(|x| graph.image_resource(x).name)
                     .flatten();
                 log::debug!(
-                    "    Color Attachment {}: {:?} Name: {:?} Constraints: {:?}",
-                    color_attachment_index,
+                    "    Depth Attachment: {:?} Name: {:?} Constraints: {:?}",
                     physical_image,
                     write_name,
                     constraint_results.images[&read_or_write]
                 );
             }
-        }
 
-        for (resolve_attachment_index, resolve_attachment) in
-            node.resolve_attachments.iter().enumerate()
-        {
-            if let Some(resolve_attachment) = resolve_attachment {
-                let physical_image = assign_physical_resources_result.image_usage_to_physical
-                    [&resolve_attachment.write_image];
-                let write_name = graph.image_resource(resolve_attachment.write_image).name;
+            for sampled_image in &node.sampled_images {
+                let physical_image =
+                    assign_physical_resources_result.image_usage_to_physical[sampled_image];
+                let write_name = graph.image_resource(*sampled_image).name;
                 log::debug!(
-                    "    Resolve Attachment {}: {:?} Name: {:?} Constraints: {:?}",
-                    resolve_attachment_index,
+                    "    Sampled: {:?} Name: {:?} Constraints: {:?}",
                     physical_image,
                     write_name,
-                    constraint_results.images[&resolve_attachment.write_image]
+                    constraint_results.images[sampled_image]
                 );
             }
         }
-
-        if let Some(depth_attachment) = &node.depth_attachment {
-            let read_or_write = depth_attachment
-                .read_image
-                .or_else(|| depth_attachment.write_image)
-                .unwrap();
-            let physical_image =
-                assign_physical_resources_result.image_usage_to_physical[&read_or_write];
-            let write_name = depth_attachment
-                .write_image
-                .map(|x| graph.image_resource(x).name)
-                .flatten();
-            log::debug!(
-                "    Depth Attachment: {:?} Name: {:?} Constraints: {:?}",
-                physical_image,
-                write_name,
-                constraint_results.images[&read_or_write]
-            );
-        }
-
-        for sampled_image in &node.sampled_images {
-            let physical_image =
-                assign_physical_resources_result.image_usage_to_physical[sampled_image];
-            let write_name = graph.image_resource(*sampled_image).name;
-            log::debug!(
-                "    Sampled: {:?} Name: {:?} Constraints: {:?}",
-                physical_image,
-                write_name,
-                constraint_results.images[sampled_image]
-            );
-        }
     }
     for output_image in &graph.output_images {
         let physical_image =
@@ -2643,12 +4905,98 @@ pub struct RenderGraphPlanOutputBuffer {
     pub dst_buffer: ResourceArc<BufferResource>,
 }
 
+#[derive(Debug)]
+pub struct RenderGraphPlanExternalImage {
+    pub src_image: ResourceArc<ImageViewResource>,
+}
+
+#[derive(Debug)]
+pub struct RenderGraphPlanExternalBuffer {
+    pub src_buffer: ResourceArc<BufferResource>,
+}
+
+/// A group of consecutive passes that are recorded into one command buffer and submitted to the
+/// same queue
+#[derive(Debug)]
+pub struct RenderGraphPlanSubmission {
+    pub(super) queue: RenderGraphQueue,
+    pub(super) passes: Vec<usize>,
+    pub(super) wait_for: Vec<usize>,
+    pub(super) runs_in_parallel_with: Vec<usize>,
+}
+
+impl RenderGraphPlanSubmission {
+    pub fn queue(&self) -> RenderGraphQueue {
+        self.queue
+    }
+
+    /// Indices of the passes recorded into this submission, in execution order
+    pub fn passes(&self) -> &[usize] {
+        &self.passes
+    }
+
+    /// Indices of the submissions that must finish before this one starts. Each of these is
+    /// signaled with a semaphore.
+    pub fn wait_for(&self) -> &[usize] {
+        &self.wait_for
+    }
+
+    /// Indices of the submissions that are not ordered relative to this one, and so may execute
+    /// on the GPU at the same time
+    pub fn runs_in_parallel_with(&self) -> &[usize] {
+        &self.runs_in_parallel_with
+    }
+}
+
+/// How the graph converts a resource that is read with a different specification than it was
+/// written with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderGraphConversionKind {
+    /// A resolve attachment was added to the node that writes a multisampled color attachment
+    Resolve,
+    /// A node was added that blits the image into a new image
+    BlitImage,
+    /// A node was added that copies the buffer into a new buffer
+    CopyBuffer,
+}
+
+/// A resolve, blit, or copy the graph inserted to fix conflicting resource specifications
+#[derive(Debug, Clone)]
+pub struct RenderGraphPlanConversion {
+    pub kind: RenderGraphConversionKind,
+    /// The node that performs the conversion. For blits and copies, this node was added by the
+    /// graph.
+    pub node_id: RenderGraphNodeId,
+    pub resource_name: Option<RenderGraphResourceName>,
+}
+
+/// Estimated memory required by the intermediate images and buffers of a render graph. Image sizes
+/// are estimated from their specifications and do not include padding or alignment the device
+/// may require.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RenderGraphMemoryStats {
+    /// Memory required if every intermediate resource had its own allocation
+    pub total_bytes: u64,
+    /// Memory required when resources with non-overlapping lifetimes share an allocation. This is
+    /// what the graph allocates on devices that support resource aliasing.
+    pub aliased_bytes: u64,
+    /// The most memory used by resources that are alive during any single pass. No assignment of
+    /// resources to allocations can require less than this.
+    pub peak_bytes: u64,
+    /// Memory required by transient attachments. This is not included in the other totals, as
+    /// devices that support transient attachments may never allocate it.
+    pub transient_attachment_bytes: u64,
+}
+
 /// The final output of a render graph, which will be consumed by PreparedRenderGraph. This just
 /// includes the computed metadata and does not allocate resources.
 pub struct RenderGraphPlan {
     pub(super) passes: Vec<RenderGraphOutputPass>,
+    pub(super) submissions: Vec<RenderGraphPlanSubmission>,
     pub(super) output_images: FnvHashMap<PhysicalImageViewId, RenderGraphPlanOutputImage>,
     pub(super) output_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanOutputBuffer>,
+    pub(super) external_images: FnvHashMap<PhysicalImageViewId, RenderGraphPlanExternalImage>,
+    pub(super) external_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanExternalBuffer>,
     pub(super) intermediate_images: FnvHashMap<PhysicalImageId, RenderGraphImageSpecification>,
     pub(super) intermediate_buffers: FnvHashMap<PhysicalBufferId, RenderGraphBufferSpecification>,
     pub(super) image_views: Vec<RenderGraphImageView>, // index by physical image view id
@@ -2656,19 +5004,124 @@ pub struct RenderGraphPlan {
     pub(super) _image_usage_to_physical: FnvHashMap<RenderGraphImageUsageId, PhysicalImageId>,
     pub(super) image_usage_to_view: FnvHashMap<RenderGraphImageUsageId, PhysicalImageViewId>,
     pub(super) buffer_usage_to_physical: FnvHashMap<RenderGraphBufferUsageId, PhysicalBufferId>,
+    pub(super) physical_image_names: FnvHashMap<PhysicalImageId, String>,
+    pub(super) physical_buffer_names: FnvHashMap<PhysicalBufferId, String>,
+    pub(super) image_lifetimes: FnvHashMap<PhysicalImageId, RenderGraphResourceLifetime>,
+    pub(super) buffer_lifetimes: FnvHashMap<PhysicalBufferId, RenderGraphResourceLifetime>,
+    // Intermediate resources that share a single allocation, sorted by first use
+    pub(super) aliased_images: Vec<Vec<PhysicalImageId>>,
+    pub(super) aliased_buffers: Vec<Vec<PhysicalBufferId>>,
+    pub(super) conversions: Vec<RenderGraphPlanConversion>,
+    pub(super) validation_warnings: Vec<RenderGraphError>,
 
     // callbacks
     pub(super) visit_node_callbacks:
         FnvHashMap<RenderGraphNodeId, RenderGraphNodeVisitNodeCallback>,
-    pub(super) _render_phase_dependencies:
+    pub(super) render_phase_dependencies:
         FnvHashMap<RenderGraphNodeId, FnvHashSet<RenderPhaseIndex>>,
+
+    pub(super) export: Option<RenderGraphExport>,
 }
 
 impl RenderGraphPlan {
+    /// A description of the graph and this plan for debugging tools. This is only available if
+    /// `RenderGraphBuilder::set_export_enabled()` was called before building the plan.
+    pub fn export(&self) -> Option<&RenderGraphExport> {
+        self.export.as_ref()
+    }
+
+    /// Resolves, blits and copies that were inserted because resources were read with a different
+    /// specification than they were written with
+    pub fn conversions(&self) -> &[RenderGraphPlanConversion] {
+        &self.conversions
+    }
+
+    /// Writes that nothing reads. This is only populated if
+    /// `RenderGraphBuilder::set_validation_enabled()` was called before building the plan.
+    pub fn validation_warnings(&self) -> &[RenderGraphError] {
+        &self.validation_warnings
+    }
+
+    /// Estimate how much memory the graph's intermediate resources need when rendering to a
+    /// surface of the given size. Output and external resources are provided by the caller and
+    /// are not included.
+    pub fn memory_stats(
+        &self,
+        swapchain_surface_info: &SwapchainSurfaceInfo,
+    ) -> RenderGraphMemoryStats {
+        let mut stats = RenderGraphMemoryStats::default();
+
+        // Memory used by resources that are alive during each pass
+        let mut pass_bytes = vec![0; self.passes.len()];
+        let mut add_to_passes = |lifetime: Option<&RenderGraphResourceLifetime>, size: u64| {
+            if let Some(lifetime) = lifetime {
+                for bytes in &mut pass_bytes[lifetime.first_pass_index..=lifetime.last_pass_index] {
+                    *bytes += size;
+                }
+            }
+        };
+
+        let mut image_sizes = FnvHashMap::default();
+        for (physical_image, specification) in &self.intermediate_images {
+            let size = specification.estimate_size_in_bytes(swapchain_surface_info);
+            if specification
+                .resource_type
+                .contains(RafxResourceType::TRANSIENT_ATTACHMENT)
+            {
+                stats.transient_attachment_bytes += size;
+                continue;
+            }
+
+            stats.total_bytes += size;
+            add_to_passes(self.image_lifetimes.get(physical_image), size);
+            image_sizes.insert(*physical_image, size);
+        }
+
+        let mut buffer_sizes = FnvHashMap::default();
+        for (physical_buffer, specification) in &self.intermediate_buffers {
+            stats.total_bytes += specification.size;
+            add_to_passes(
+                self.buffer_lifetimes.get(physical_buffer),
+                specification.size,
+            );
+            buffer_sizes.insert(*physical_buffer, specification.size);
+        }
+
+        stats.peak_bytes = pass_bytes.into_iter().max().unwrap_or(0);
+
+        // Resources that share memory only need as much as the largest of them
+        stats.aliased_bytes = stats.total_bytes;
+        for aliased_images in &self.aliased_images {
+            let sizes = aliased_images.iter().map(|x| image_sizes[x]);
+            stats.aliased_bytes -= sizes.clone().sum::<u64>() - sizes.max().unwrap_or(0);
+        }
+
+        for aliased_buffers in &self.aliased_buffers {
+            let sizes = aliased_buffers.iter().map(|x| buffer_sizes[x]);
+            stats.aliased_bytes -= sizes.clone().sum::<u64>() - sizes.max().unwrap_or(0);
+        }
+
+        stats
+    }
+
     #[profiling::function]
-    pub(super) fn new(mut graph: RenderGraphBuilder) -> RenderGraphPlan {
+    pub(super) fn new(
+        mut graph: RenderGraphBuilder,
+        device_info: &RafxDeviceInfo,
+    ) -> RenderGraphResult<RenderGraphPlan> {
         log::trace!("-- Create render graph plan --");
 
+        // Output images are the images the graph is rendering to, so they must specify how
+        // they're used. Otherwise the graph may never assign them a physical image.
+        for output_image in &graph.output_images {
+            if output_image.specification.resource_type == RafxResourceType::UNDEFINED {
+                return Err(RenderGraphError::OutputImageWithoutResourceType {
+                    image: output_image.usage,
+                    image_name: graph.image_resource(output_image.usage).name,
+                });
+            }
+        }
+
         //
         // Walk backwards through the DAG, starting from the output images, through all the upstream
         // dependencies of those images. We are doing a depth first search. Nodes that make no
@@ -2676,7 +5129,7 @@ impl RenderGraphPlan {
         // an implementation detail, we try to put renderpass merge candidates adjacent to each
         // other in this list
         //
-        let node_execution_order = determine_node_order(&graph);
+        let mut node_execution_order = determine_node_order(&graph)?;
 
         // Print out the execution order
         log::trace!("Execution order of unculled nodes:");
@@ -2684,18 +5137,28 @@ impl RenderGraphPlan {
             log::trace!("  Node {:?} {:?}", node, graph.node(*node).name());
         }
 
+        //
+        // If enabled, check for mistakes in how the graph was built before resolves and conversions
+        // add nodes to it
+        //
+        let validation_warnings = if graph.validation_enabled {
+            validate_graph(&graph, &node_execution_order)?
+        } else {
+            Vec::default()
+        };
+
         //
         // Traverse the graph to determine specifications for all images that will be used. This
         // iterates forwards and backwards through the node graph. This allows us to specify
         // attributes about images (like format, sample count) in key areas and infer it elsewhere.
         // If there is not enough information to infer then the render graph cannot be used and
-        // building it will panic.
+        // building it will fail.
         //
-        let mut constraint_results = determine_constraints(&graph, &node_execution_order);
+        let mut constraint_results = determine_constraints(&graph, &node_execution_order)?;
 
         // Look at all image versions and ensure a constraint exists for usages where the node was
         // not culled
-        //RenderGraphPlan::verify_unculled_image_usages_specifications_exist(&graph, &node_execution_order, &constraint_results);
+        //verify_unculled_image_usages_specifications_exist(&graph, &node_execution_order, &constraint_results)?;
 
         // Print out the constraints assigned to images
         //print_image_constraints(&graph, &mut constraint_results);
@@ -2704,7 +5167,19 @@ impl RenderGraphPlan {
         // Add resolves to the graph - this will occur when a renderpass outputs a multisample image
         // to a renderpass that is expecting a non-multisampled image.
         //
-        insert_resolves(&mut graph, &node_execution_order, &mut constraint_results);
+        let mut conversions =
+            insert_resolves(&mut graph, &node_execution_order, &mut constraint_results);
+
+        //
+        // Add blits/copies to the graph - this will occur when a resource is read with a different
+        // specification than it was written with, and a resolve can't fix it
+        //
+        conversions.extend(insert_conversions(
+            &mut graph,
+            device_info,
+            &mut node_execution_order,
+            &mut constraint_results,
+        )?);
 
         // Print the cases where we can't reuse images
         //print_image_compatibility(&graph, &constraint_results);
@@ -2714,15 +5189,16 @@ impl RenderGraphPlan {
         // if we are not reusing or aliasing. (We reuse when we assign physical indexes)
         //
         let assign_virtual_images_result =
-            assign_virtual_resources(&graph, &node_execution_order, &mut constraint_results);
+            assign_virtual_resources(&graph, &node_execution_order, &mut constraint_results)?;
 
         //
         // Combine nodes into passes where possible
         //
         let mut passes = build_physical_passes(
-            &graph,
+            &mut graph,
+            device_info,
             &node_execution_order,
-            &constraint_results,
+            &mut constraint_results,
             &assign_virtual_images_result,
         );
 
@@ -2735,7 +5211,7 @@ impl RenderGraphPlan {
             &constraint_results,
             &assign_virtual_images_result,
             &mut passes,
-        );
+        )?;
 
         // log::trace!("Merged Renderpasses:");
         // for (index, pass) in passes.iter().enumerate() {
@@ -2762,8 +5238,18 @@ impl RenderGraphPlan {
 
         print_node_barriers(&node_barriers);
 
-        //TODO: Figure out in/out layouts for passes? Maybe insert some other fixes? Drop transient
-        // images?
+        //
+        // Let intermediate resources with non-overlapping lifetimes share memory, even if their
+        // specifications differ
+        //
+        let memory_aliases = assign_memory_aliases(
+            &graph,
+            &assign_physical_resources_result,
+            &node_barriers,
+            &passes,
+        );
+
+        //TODO: Figure out in/out layouts for passes? Maybe insert some other fixes?
 
         //
         // Combine the node barriers to produce the dependencies for subpasses and determine/handle
@@ -2774,10 +5260,24 @@ impl RenderGraphPlan {
             &node_execution_order,
             &constraint_results,
             &assign_physical_resources_result,
+            &memory_aliases,
             &node_barriers,
             &mut passes,
         );
 
+        //
+        // Group the passes into submissions by queue, adding semaphore waits and queue ownership
+        // transfers wherever a resource moves between queues
+        //
+        let submissions = build_submissions(
+            &graph,
+            &assign_physical_resources_result,
+            &node_barriers,
+            &mut passes,
+        )?;
+
+        print_submissions(&graph, &passes, &submissions);
+
         // log::trace!("Merged Renderpasses:");
         // for (index, pass) in passes.iter().enumerate() {
         //     log::trace!("  pass {}", index);
@@ -2795,33 +5295,20 @@ impl RenderGraphPlan {
         //     }
         // }
 
-        //TODO: Cull images that only exist within the lifetime of a single pass? (just passed among
-        // subpasses)
-
-        //TODO: Allocation of images
-        // alias_images(
-        //     &graph,
-        //     &node_execution_order,
-        //     &constraint_results,
-        //     &assign_physical_resources_result,
-        //     &node_barriers,
-        //     &passes,
-        // );
-
         //
         // Produce the final output data. This mainly includes a descriptor object that can be
         // passed into the resource system to create the renderpass but also includes other metadata
         // required to push them through the command queue
         //
-        let output_passes = create_output_passes(&graph, passes);
+        let output_passes = create_output_passes(&graph, passes)?;
 
         //
-        // Separate the output images from the intermediate images (the rendergraph will be
-        // responsible for allocating the intermediate images)
+        // Separate the output and external images from the intermediate images (the rendergraph
+        // will be responsible for allocating the intermediate images)
         //
         let mut output_images: FnvHashMap<PhysicalImageViewId, RenderGraphPlanOutputImage> =
             Default::default();
-        let mut output_image_physical_ids = FnvHashSet::default();
+        let mut provided_image_physical_ids = FnvHashSet::default();
         for output_image in &graph.output_images {
             let output_image_view =
                 assign_physical_resources_result.image_usage_to_image_view[&output_image.usage];
@@ -2834,14 +5321,14 @@ impl RenderGraphPlan {
                 },
             );
 
-            output_image_physical_ids.insert(
+            provided_image_physical_ids.insert(
                 assign_physical_resources_result.image_views[output_image_view.0].physical_image,
             );
         }
 
         let mut output_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanOutputBuffer> =
             Default::default();
-        let mut output_buffer_physical_ids = FnvHashSet::default();
+        let mut provided_buffer_physical_ids = FnvHashSet::default();
         for output_buffer in &graph.output_buffers {
             let output_buffer_id =
                 assign_physical_resources_result.buffer_usage_to_physical[&output_buffer.usage];
@@ -2854,7 +5341,44 @@ impl RenderGraphPlan {
                 },
             );
 
-            output_buffer_physical_ids.insert(output_buffer_id);
+            provided_buffer_physical_ids.insert(output_buffer_id);
+        }
+
+        //
+        // External resources are also provided by the caller and are never allocated by the graph
+        //
+        let mut external_images: FnvHashMap<PhysicalImageViewId, RenderGraphPlanExternalImage> =
+            Default::default();
+        for external_image in &graph.external_images {
+            let external_image_view =
+                assign_physical_resources_result.image_usage_to_image_view[&external_image.usage];
+
+            external_images.insert(
+                external_image_view,
+                RenderGraphPlanExternalImage {
+                    src_image: external_image.src_image.clone(),
+                },
+            );
+
+            provided_image_physical_ids.insert(
+                assign_physical_resources_result.image_views[external_image_view.0].physical_image,
+            );
+        }
+
+        let mut external_buffers: FnvHashMap<PhysicalBufferId, RenderGraphPlanExternalBuffer> =
+            Default::default();
+        for external_buffer in &graph.external_buffers {
+            let external_buffer_id =
+                assign_physical_resources_result.buffer_usage_to_physical[&external_buffer.usage];
+
+            external_buffers.insert(
+                external_buffer_id,
+                RenderGraphPlanExternalBuffer {
+                    src_buffer: external_buffer.src_buffer.clone(),
+                },
+            );
+
+            provided_buffer_physical_ids.insert(external_buffer_id);
         }
 
         let mut intermediate_images: FnvHashMap<PhysicalImageId, RenderGraphImageSpecification> =
@@ -2865,7 +5389,7 @@ impl RenderGraphPlan {
             .enumerate()
         {
             let physical_image = PhysicalImageId(index);
-            if output_image_physical_ids.contains(&physical_image) {
+            if provided_image_physical_ids.contains(&physical_image) {
                 continue;
             }
 
@@ -2880,7 +5404,7 @@ impl RenderGraphPlan {
             .enumerate()
         {
             let physical_buffer = PhysicalBufferId(index);
-            if output_buffer_physical_ids.contains(&physical_buffer) {
+            if provided_buffer_physical_ids.contains(&physical_buffer) {
                 continue;
             }
 
@@ -2908,13 +5432,34 @@ impl RenderGraphPlan {
         //
         let mut node_to_pass_index = FnvHashMap::default();
         for (pass_index, pass) in output_passes.iter().enumerate() {
-            node_to_pass_index.insert(pass.node(), pass_index);
+            for node_id in pass.nodes() {
+                node_to_pass_index.insert(node_id, pass_index);
+            }
         }
 
-        RenderGraphPlan {
+        let physical_image_names = determine_physical_image_names(
+            &graph,
+            &assign_physical_resources_result.image_usage_to_physical,
+        );
+        let physical_buffer_names = determine_physical_buffer_names(
+            &graph,
+            &assign_physical_resources_result.buffer_usage_to_physical,
+        );
+
+        // The plan takes ownership of parts of the graph, so describe the graph before creating it
+        let export = if graph.export_enabled {
+            Some(RenderGraphExport::new(&graph))
+        } else {
+            None
+        };
+
+        let mut plan = RenderGraphPlan {
             passes: output_passes,
+            submissions,
             output_images,
             output_buffers,
+            external_images,
+            external_buffers,
             intermediate_images,
             intermediate_buffers,
             image_views: assign_physical_resources_result.image_views,
@@ -2922,9 +5467,1040 @@ impl RenderGraphPlan {
             _image_usage_to_physical: assign_physical_resources_result.image_usage_to_physical,
             image_usage_to_view: assign_physical_resources_result.image_usage_to_image_view,
             buffer_usage_to_physical: assign_physical_resources_result.buffer_usage_to_physical,
+            physical_image_names,
+            physical_buffer_names,
+            image_lifetimes: memory_aliases.image_lifetimes,
+            buffer_lifetimes: memory_aliases.buffer_lifetimes,
+            aliased_images: memory_aliases.aliased_images,
+            aliased_buffers: memory_aliases.aliased_buffers,
+            conversions,
+            validation_warnings,
 
             visit_node_callbacks: graph.visit_node_callbacks,
-            _render_phase_dependencies: graph.render_phase_dependencies,
+            render_phase_dependencies: graph.render_phase_dependencies,
+            export: None,
+        };
+
+        if let Some(mut export) = export {
+            export.add_plan(&plan);
+            plan.export = Some(export);
+        }
+
+        Ok(plan)
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    )
+))]
+pub(crate) mod tests {
+    use super::*;
+    use crate::render_features::RenderRegistryBuilder;
+    use crate::{ResourceContext, ResourceManager};
+    use rafx_api::{RafxApi, RafxBufferDef, RafxDeviceContext, RafxExtents2D, RafxTextureDef};
+
+    pub(crate) const TEST_FORMAT: RafxFormat = RafxFormat::R8G8B8A8_UNORM;
+    pub(crate) const TEST_EXTENTS: RafxExtents3D = RafxExtents3D {
+        width: 64,
+        height: 64,
+        depth: 1,
+    };
+
+    // The resource manager is declared first so that it is dropped before the api
+    pub(crate) struct TestDevice {
+        pub(crate) resource_manager: ResourceManager,
+        pub(crate) api: RafxApi,
+    }
+
+    impl TestDevice {
+        pub(crate) fn device_context(&self) -> RafxDeviceContext {
+            self.api.device_context()
+        }
+
+        pub(crate) fn resource_context(&self) -> ResourceContext {
+            self.resource_manager.resource_context()
+        }
+
+        pub(crate) fn build_plan(
+            &self,
+            graph: RenderGraphBuilder,
+        ) -> RenderGraphResult<RenderGraphPlan> {
+            graph.build_plan(self.device_context().device_info())
+        }
+
+        // Creates a texture with the test format and extents and returns a view of it
+        pub(crate) fn create_image_view(
+            &self,
+            resource_type: RafxResourceType,
+        ) -> ResourceArc<ImageViewResource> {
+            let texture = self
+                .device_context()
+                .create_texture(&RafxTextureDef {
+                    extents: TEST_EXTENTS,
+                    format: TEST_FORMAT,
+                    resource_type,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let resources = self.resource_manager.resources();
+            let image_resource = resources.insert_image(texture);
+            resources
+                .get_or_create_image_view(&image_resource, None)
+                .unwrap()
+        }
+
+        // Outputs the image to a new texture, which keeps the nodes that write it from being
+        // culled
+        pub(crate) fn set_output_image(
+            &self,
+            graph: &mut RenderGraphBuilder,
+            image: RenderGraphImageUsageId,
+        ) -> RenderGraphOutputImageId {
+            let resource_type = RafxResourceType::TEXTURE | RafxResourceType::RENDER_TARGET_COLOR;
+            graph.set_output_image(
+                image,
+                self.create_image_view(resource_type),
+                image_specification(resource_type),
+                Default::default(),
+                RafxResourceState::SHADER_RESOURCE,
+            )
+        }
+    }
+
+    // The specification of the images created by TestDevice::create_image_view()
+    pub(crate) fn image_specification(
+        resource_type: RafxResourceType
+    ) -> RenderGraphImageSpecification {
+        RenderGraphImageSpecification {
+            samples: RafxSampleCount::SampleCount1,
+            format: TEST_FORMAT,
+            resource_type,
+            extents: RenderGraphImageExtents::Custom(
+                TEST_EXTENTS.width,
+                TEST_EXTENTS.height,
+                TEST_EXTENTS.depth,
+            ),
+            layer_count: 1,
+            mip_count: 1,
+        }
+    }
+
+    pub(crate) fn test_device() -> TestDevice {
+        let api = RafxApi::new_empty(&Default::default(), &Default::default()).unwrap();
+        let render_registry = RenderRegistryBuilder::default().build();
+        let resource_manager = ResourceManager::new(&api.device_context(), &render_registry);
+        TestDevice {
+            resource_manager,
+            api,
+        }
+    }
+
+    pub(crate) fn color_constraint() -> RenderGraphImageConstraint {
+        RenderGraphImageConstraint {
+            format: Some(TEST_FORMAT),
+            extents: Some(RenderGraphImageExtents::Custom(
+                TEST_EXTENTS.width,
+                TEST_EXTENTS.height,
+                TEST_EXTENTS.depth,
+            )),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn storage_buffer_constraint() -> RenderGraphBufferConstraint {
+        RenderGraphBufferConstraint {
+            size: Some(256),
+            ..Default::default()
+        }
+    }
+
+    // A compute node on the async compute queue writes a buffer that a graphics node reads while
+    // drawing to the output image
+    pub(crate) fn compute_to_graphics_graph(test_device: &TestDevice) -> RenderGraphBuilder {
+        let mut graph = RenderGraphBuilder::default();
+
+        let simulate = graph.add_node("simulate", RenderGraphQueue::AsyncCompute);
+        let particles = graph.create_storage_buffer(simulate, storage_buffer_constraint());
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(draw, particles, Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        graph
+    }
+
+    #[test]
+    fn test_compute_to_graphics_handoff() {
+        let test_device = test_device();
+        let plan = test_device
+            .build_plan(compute_to_graphics_graph(&test_device))
+            .unwrap();
+
+        // The compute pass runs first in its own submission, and the graphics submission waits on
+        // it
+        assert_eq!(plan.passes.len(), 2);
+        assert_eq!(plan.submissions.len(), 2);
+        assert_eq!(plan.submissions[0].queue, RenderGraphQueue::AsyncCompute);
+        assert_eq!(plan.submissions[0].passes, vec![0]);
+        assert!(plan.submissions[0].wait_for.is_empty());
+        assert_eq!(plan.submissions[1].queue, RenderGraphQueue::DefaultGraphics);
+        assert_eq!(plan.submissions[1].passes, vec![1]);
+        assert_eq!(plan.submissions[1].wait_for, vec![0]);
+        assert!(plan.submissions[0].runs_in_parallel_with.is_empty());
+
+        // The buffer is released by the compute pass and acquired by the graphics pass
+        let release = &plan.passes[0].post_pass_barrier().unwrap().buffer_barriers;
+        assert_eq!(release.len(), 1);
+        assert_eq!(
+            release[0].queue_transition,
+            RenderGraphQueueTransition::ReleaseTo(RenderGraphQueue::DefaultGraphics)
+        );
+
+        let acquire = &plan.passes[1].pre_pass_barrier().unwrap().buffer_barriers;
+        assert_eq!(acquire.len(), 1);
+        assert_eq!(acquire[0].buffer, release[0].buffer);
+        assert_eq!(
+            acquire[0].queue_transition,
+            RenderGraphQueueTransition::AcquireFrom(RenderGraphQueue::AsyncCompute)
+        );
+        assert_eq!(acquire[0].old_state, release[0].old_state);
+        assert_eq!(acquire[0].new_state, release[0].new_state);
+    }
+
+    #[test]
+    fn test_submissions_on_several_queues() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+
+        // The graphics node reads a buffer written on the transfer queue and another written on
+        // the compute queue. Neither depends on the other.
+        let upload = graph.add_node("upload", RenderGraphQueue::Transfer);
+        let uploaded = graph.create_storage_buffer(upload, storage_buffer_constraint());
+
+        let simulate = graph.add_node("simulate", RenderGraphQueue::AsyncCompute);
+        let particles = graph.create_storage_buffer(simulate, storage_buffer_constraint());
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(draw, uploaded, Default::default());
+        graph.read_storage_buffer(draw, particles, Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // The graphics submission is last and waits on both other queues, which are not ordered
+        // relative to each other
+        assert_eq!(plan.submissions.len(), 3);
+        let final_submission = &plan.submissions[2];
+        assert_eq!(final_submission.queue, RenderGraphQueue::DefaultGraphics);
+        assert_eq!(
+            final_submission.passes,
+            vec![plan.node_to_pass_index[&draw]]
+        );
+
+        let mut wait_for = final_submission.wait_for.clone();
+        wait_for.sort_unstable();
+        assert_eq!(wait_for, vec![0, 1]);
+        assert_eq!(plan.submissions[0].runs_in_parallel_with, vec![1]);
+        assert_eq!(plan.submissions[1].runs_in_parallel_with, vec![0]);
+        assert!(final_submission.runs_in_parallel_with.is_empty());
+    }
+
+    #[test]
+    fn test_output_image_before_final_submission() {
+        let test_device = test_device();
+        let mut graph = compute_to_graphics_graph(&test_device);
+
+        // A compute node that reads the particles after they are drawn and writes an output
+        // buffer. It is ordered after the graphics node, so the graphics node that draws the
+        // output image is no longer in the final submission.
+        let histogram_node = graph.add_node("histogram", RenderGraphQueue::AsyncCompute);
+        let histogram = graph.create_storage_buffer(histogram_node, storage_buffer_constraint());
+        let output_buffer = test_device.resource_context().resources().insert_buffer(
+            test_device
+                .device_context()
+                .create_buffer(&RafxBufferDef {
+                    size: 256,
+                    resource_type: RafxResourceType::BUFFER_READ_WRITE,
+                    ..Default::default()
+                })
+                .unwrap(),
+        );
+        graph.set_output_buffer(
+            histogram,
+            output_buffer,
+            RenderGraphBufferSpecification {
+                size: 256,
+                resource_type: RafxResourceType::BUFFER_READ_WRITE,
+            },
+        );
+
+        match test_device.build_plan(graph) {
+            Err(RenderGraphError::OutputImageUsedBeforeFinalSubmission { node_name, .. }) => {
+                assert_eq!(node_name, Some("draw"))
+            }
+            Err(error) => panic!("Unexpected error: {}", error),
+            Ok(_) => panic!("Expected the plan to fail"),
+        }
+    }
+
+    fn external_buffer_specification() -> RenderGraphBufferSpecification {
+        RenderGraphBufferSpecification {
+            size: 256,
+            resource_type: RafxResourceType::BUFFER_READ_WRITE,
+        }
+    }
+
+    // Passes a new buffer into the graph in the COPY_DST state, as if it was just uploaded
+    fn read_external_buffer(
+        test_device: &TestDevice,
+        graph: &mut RenderGraphBuilder,
+    ) -> RenderGraphBufferUsageId {
+        let buffer = test_device.resource_context().resources().insert_buffer(
+            test_device
+                .device_context()
+                .create_buffer(&RafxBufferDef {
+                    size: 256,
+                    resource_type: RafxResourceType::BUFFER_READ_WRITE,
+                    ..Default::default()
+                })
+                .unwrap(),
+        );
+
+        graph.read_external_buffer(
+            buffer,
+            external_buffer_specification(),
+            RafxResourceState::COPY_DST,
+            RafxResourceState::SHADER_RESOURCE,
+        )
+    }
+
+    #[test]
+    fn test_external_buffer_states() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+        let particles = read_external_buffer(&test_device, &mut graph);
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(draw, particles, Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // The graph uses the provided buffer instead of allocating one
+        let physical_buffer = plan.buffer_usage_to_physical[&particles];
+        assert!(plan.external_buffers.contains_key(&physical_buffer));
+        assert!(!plan.intermediate_buffers.contains_key(&physical_buffer));
+
+        // The buffer is transitioned from its initial state before the pass that reads it and to
+        // its final state after it
+        let pre_pass = &plan.passes[0].pre_pass_barrier().unwrap().buffer_barriers;
+        assert_eq!(pre_pass.len(), 1);
+        assert_eq!(pre_pass[0].buffer, physical_buffer);
+        assert_eq!(pre_pass[0].old_state, RafxResourceState::COPY_DST);
+        assert_eq!(pre_pass[0].new_state, RafxResourceState::UNORDERED_ACCESS);
+
+        let post_pass = &plan.passes[0].post_pass_barrier().unwrap().buffer_barriers;
+        assert_eq!(post_pass.len(), 1);
+        assert_eq!(post_pass[0].buffer, physical_buffer);
+        assert_eq!(post_pass[0].old_state, RafxResourceState::UNORDERED_ACCESS);
+        assert_eq!(post_pass[0].new_state, RafxResourceState::SHADER_RESOURCE);
+    }
+
+    #[test]
+    fn test_external_buffer_modified_twice() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+        let particles = read_external_buffer(&test_device, &mut graph);
+
+        let simulate = graph.add_node("simulate", RenderGraphQueue::DefaultGraphics);
+        let simulated = graph.modify_storage_buffer(simulate, particles, Default::default());
+        let emit = graph.add_node("emit", RenderGraphQueue::DefaultGraphics);
+        let emitted = graph.modify_storage_buffer(emit, particles, Default::default());
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(draw, simulated, Default::default());
+        graph.read_storage_buffer(draw, emitted, Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        // The graph can't give each writer its own copy of a buffer it doesn't own
+        match test_device.build_plan(graph) {
+            Err(RenderGraphError::BufferWriteAfterWrite {
+                first_node_name,
+                second_node_name,
+                ..
+            }) => {
+                assert_eq!(first_node_name, Some("simulate"));
+                assert_eq!(second_node_name, Some("emit"));
+            }
+            Err(error) => panic!("Unexpected error: {}", error),
+            Ok(_) => panic!("Expected the plan to fail"),
+        }
+    }
+
+    #[test]
+    fn test_external_image_specification_mismatch() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+        let history = graph.read_external_image(
+            test_device.create_image_view(RafxResourceType::TEXTURE),
+            image_specification(RafxResourceType::TEXTURE),
+            Default::default(),
+            RafxResourceState::SHADER_RESOURCE,
+            RafxResourceState::SHADER_RESOURCE,
+        );
+
+        // The node samples the image as an array, which a blit of the provided image can't produce
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(
+            draw,
+            history,
+            RenderGraphImageConstraint {
+                layer_count: Some(2),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        match test_device.build_plan(graph) {
+            Err(RenderGraphError::IncompatibleImageSpecification {
+                image,
+                written,
+                read,
+                ..
+            }) => {
+                assert_eq!(image, history);
+                assert_eq!(written.layer_count, 1);
+                assert_eq!(read.layer_count, 2);
+            }
+            Err(error) => panic!("Unexpected error: {}", error),
+            Ok(_) => panic!("Expected the plan to fail"),
+        }
+    }
+
+    // Returns (array slice, mip slice, old state, new state) of each barrier for the image before
+    // the pass that runs the node
+    fn pre_pass_image_transitions(
+        plan: &RenderGraphPlan,
+        node: RenderGraphNodeId,
+        image: RenderGraphImageUsageId,
+    ) -> Vec<(
+        Option<u16>,
+        Option<u8>,
+        RafxResourceState,
+        RafxResourceState,
+    )> {
+        let physical_image = plan.image_views[plan.image_usage_to_view[&image].0].physical_image;
+        let pass = &plan.passes[plan.node_to_pass_index[&node]];
+        let mut transitions: Vec<_> = pass
+            .pre_pass_barrier()
+            .map(|x| x.image_barriers.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(|x| x.image == physical_image)
+            .map(|x| (x.array_slice, x.mip_slice, x.old_state, x.new_state))
+            .collect();
+        transitions.sort_by_key(|x| x.1);
+        transitions
+    }
+
+    #[test]
+    fn test_barriers_between_passes() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+
+        let shadow = graph.add_node("shadow", RenderGraphQueue::DefaultGraphics);
+        let shadow_map =
+            graph.create_color_attachment(shadow, 0, None, color_constraint(), Default::default());
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        let sampled = graph.sample_image(draw, shadow_map, Default::default(), Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // An attachment can't be sampled in the renderpass that writes it
+        assert_eq!(plan.passes.len(), 2);
+        assert_eq!(
+            pre_pass_image_transitions(&plan, shadow, shadow_map),
+            vec![(
+                None,
+                None,
+                RafxResourceState::UNDEFINED,
+                RafxResourceState::RENDER_TARGET
+            )]
+        );
+        assert_eq!(
+            pre_pass_image_transitions(&plan, draw, sampled),
+            vec![(
+                None,
+                None,
+                RafxResourceState::RENDER_TARGET,
+                RafxResourceState::PIXEL_SHADER_RESOURCE
+            )]
+        );
+    }
+
+    #[test]
+    fn test_barriers_per_mip() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+        let mip_chain_constraint = RenderGraphImageConstraint {
+            mip_count: Some(2),
+            ..color_constraint()
+        };
+
+        let mip0 = graph.add_node("mip0", RenderGraphQueue::DefaultGraphics);
+        let mip_chain = graph.create_color_attachment(
+            mip0,
+            0,
+            None,
+            mip_chain_constraint.clone(),
+            RenderGraphImageViewOptions::mip_slice(0),
+        );
+
+        // Downsamples mip 0 into mip 1
+        let mip1 = graph.add_node("mip1", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(
+            mip1,
+            mip_chain,
+            Default::default(),
+            RenderGraphImageViewOptions::mip_slice(0),
+        );
+        let mip_chain = graph.modify_color_attachment(
+            mip1,
+            mip_chain,
+            0,
+            None,
+            mip_chain_constraint,
+            RenderGraphImageViewOptions::mip_slice(1),
+        );
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.sample_image(draw, mip_chain, Default::default(), Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // Each mip is transitioned separately while it is written
+        assert_eq!(
+            pre_pass_image_transitions(&plan, mip1, mip_chain),
+            vec![
+                (
+                    None,
+                    Some(0),
+                    RafxResourceState::RENDER_TARGET,
+                    RafxResourceState::PIXEL_SHADER_RESOURCE
+                ),
+                (
+                    None,
+                    Some(1),
+                    RafxResourceState::UNDEFINED,
+                    RafxResourceState::RENDER_TARGET
+                ),
+            ]
+        );
+
+        // Sampling the whole image only transitions the mip that isn't already readable
+        assert_eq!(
+            pre_pass_image_transitions(&plan, draw, mip_chain),
+            vec![(
+                Some(0),
+                Some(1),
+                RafxResourceState::RENDER_TARGET,
+                RafxResourceState::PIXEL_SHADER_RESOURCE
+            )]
+        );
+    }
+
+    #[test]
+    fn test_transient_buffers_are_aliased() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+        let buffer_constraint = |size| RenderGraphBufferConstraint {
+            size: Some(size),
+            ..Default::default()
+        };
+
+        // Each node reads the buffer written by the previous one. The buffers have different sizes
+        // so that they can't be reused as the same physical buffer.
+        let first = graph.add_node("first", RenderGraphQueue::DefaultGraphics);
+        let first_buffer = graph.create_storage_buffer(first, buffer_constraint(256));
+
+        let second = graph.add_node("second", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(second, first_buffer, Default::default());
+        let second_buffer = graph.create_storage_buffer(second, buffer_constraint(512));
+
+        let third = graph.add_node("third", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(third, second_buffer, Default::default());
+        let third_buffer = graph.create_storage_buffer(third, buffer_constraint(1024));
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        graph.read_storage_buffer(draw, third_buffer, Default::default());
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // The first buffer is no longer used when the third is written
+        assert_eq!(
+            plan.aliased_buffers,
+            vec![vec![
+                plan.buffer_usage_to_physical[&first_buffer],
+                plan.buffer_usage_to_physical[&third_buffer]
+            ]]
+        );
+        assert!(plan.aliased_images.is_empty());
+
+        let memory_stats = plan.memory_stats(&SwapchainSurfaceInfo {
+            extents: RafxExtents2D {
+                width: TEST_EXTENTS.width,
+                height: TEST_EXTENTS.height,
+            },
+            format: TEST_FORMAT,
+        });
+        assert_eq!(
+            memory_stats,
+            RenderGraphMemoryStats {
+                total_bytes: 256 + 512 + 1024,
+                aliased_bytes: 512 + 1024,
+                peak_bytes: 512 + 1024,
+                transient_attachment_bytes: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let test_device = test_device();
+        let mut graph = compute_to_graphics_graph(&test_device);
+
+        // Nothing reads this node's buffer, so it is culled
+        let unused = graph.add_node("unused", RenderGraphQueue::DefaultGraphics);
+        graph.create_storage_buffer(unused, storage_buffer_constraint());
+
+        graph.set_export_enabled(true);
+        let plan = test_device.build_plan(graph).unwrap();
+        let export = plan.export().unwrap();
+
+        let node_names: Vec<_> = export.nodes.iter().map(|x| x.name.as_deref()).collect();
+        assert_eq!(
+            node_names,
+            vec![Some("simulate"), Some("draw"), Some("unused")]
+        );
+
+        let export_plan = export.plan.as_ref().unwrap();
+        assert_eq!(export_plan.culled_nodes, vec![unused.0]);
+        assert_eq!(export_plan.passes.len(), 2);
+        assert_eq!(export_plan.passes[0].nodes, vec![0]);
+        assert_eq!(export_plan.passes[0].submission, Some(0));
+        assert_eq!(export_plan.passes[0].post_pass_buffer_barriers.len(), 1);
+        assert_eq!(export_plan.passes[1].nodes, vec![1]);
+        assert!(export_plan.passes[1].is_renderpass);
+        assert_eq!(export_plan.passes[1].pre_pass_buffer_barriers.len(), 1);
+        assert_eq!(export_plan.submissions.len(), 2);
+        assert_eq!(export_plan.submissions[1].wait_for, vec![0]);
+
+        let json: serde_json::Value = serde_json::from_str(&export.to_json()).unwrap();
+        assert_eq!(json["nodes"][1]["name"], "draw");
+        assert_eq!(json["plan"]["culled_nodes"][0], unused.0);
+
+        let dot = export.to_dot();
+        assert!(dot.starts_with("digraph render_graph {"));
+        assert!(dot.contains("simulate"));
+        assert!(dot.contains("draw"));
+    }
+
+    #[test]
+    fn test_export_disabled_by_default() {
+        let test_device = test_device();
+        let plan = test_device
+            .build_plan(compute_to_graphics_graph(&test_device))
+            .unwrap();
+        assert!(plan.export().is_none());
+    }
+
+    // A node writes a color attachment that the next node reads as an input attachment
+    fn deferred_lighting_graph(
+        test_device: &TestDevice
+    ) -> (
+        RenderGraphBuilder,
+        RenderGraphNodeId,
+        RenderGraphNodeId,
+        RenderGraphImageUsageId,
+    ) {
+        let mut graph = RenderGraphBuilder::default();
+
+        let gbuffer = graph.add_node("gbuffer", RenderGraphQueue::DefaultGraphics);
+        let albedo =
+            graph.create_color_attachment(gbuffer, 0, None, color_constraint(), Default::default());
+
+        let lighting = graph.add_node("lighting", RenderGraphQueue::DefaultGraphics);
+        let albedo_input = graph.read_input_attachment(
+            lighting,
+            albedo,
+            0,
+            Default::default(),
+            Default::default(),
+        );
+        let color = graph.create_color_attachment(
+            lighting,
+            0,
+            None,
+            color_constraint(),
+            Default::default(),
+        );
+        test_device.set_output_image(&mut graph, color);
+
+        (graph, gbuffer, lighting, albedo_input)
+    }
+
+    #[test]
+    fn test_subpass_merging() {
+        let test_device = test_device();
+        let (graph, gbuffer, lighting, albedo_input) = deferred_lighting_graph(&test_device);
+        let plan = test_device.build_plan(graph).unwrap();
+
+        // Both nodes run in one renderpass and the lighting subpass reads the gbuffer attachment
+        // as a subpass input
+        assert_eq!(plan.passes.len(), 1);
+        assert_eq!(plan.passes[0].nodes(), vec![gbuffer, lighting]);
+        let renderpass = match &plan.passes[0] {
+            RenderGraphOutputPass::Renderpass(renderpass) => renderpass,
+            RenderGraphOutputPass::Compute(_) => panic!("Expected a renderpass"),
+        };
+
+        let albedo_index = renderpass
+            .attachment_images
+            .iter()
+            .position(|x| *x == plan.image_usage_to_view[&albedo_input])
+            .unwrap() as u32;
+        assert_eq!(renderpass.subpass_defs.len(), 2);
+        assert_eq!(
+            renderpass.subpass_defs[0].color_attachments,
+            vec![albedo_index]
+        );
+        assert!(renderpass.subpass_defs[0].input_attachments.is_empty());
+        assert_eq!(
+            renderpass.subpass_defs[1].input_attachments,
+            vec![RafxSubpassInputAttachment::Color(albedo_index)]
+        );
+        assert_ne!(
+            renderpass.subpass_defs[1].color_attachments,
+            vec![albedo_index]
+        );
+    }
+
+    #[test]
+    fn test_subpasses_unsupported() {
+        let test_device = test_device();
+        let (graph, gbuffer, lighting, albedo_input) = deferred_lighting_graph(&test_device);
+        let mut device_info = test_device.device_context().device_info().clone();
+        device_info.supports_subpasses = false;
+        let plan = graph.build_plan(&device_info).unwrap();
+
+        // Each node gets its own renderpass and the input attachment is sampled instead
+        assert_eq!(plan.passes.len(), 2);
+        assert_eq!(plan.passes[0].nodes(), vec![gbuffer]);
+        assert_eq!(plan.passes[1].nodes(), vec![lighting]);
+        assert_eq!(
+            pre_pass_image_transitions(&plan, lighting, albedo_input),
+            vec![(
+                None,
+                None,
+                RafxResourceState::RENDER_TARGET,
+                RafxResourceState::PIXEL_SHADER_RESOURCE
+            )]
+        );
+    }
+
+    // A node samples an image with a different specification than the node before it rendered it
+    // with
+    fn image_conflict_graph(
+        test_device: &TestDevice,
+        read_constraint: RenderGraphImageConstraint,
+    ) -> (
+        RenderGraphBuilder,
+        RenderGraphImageUsageId,
+        RenderGraphImageUsageId,
+    ) {
+        let mut graph = RenderGraphBuilder::default();
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        let sampled = graph.sample_image(post, color, read_constraint, Default::default());
+        let post_color =
+            graph.create_color_attachment(post, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, post_color);
+
+        (graph, color, sampled)
+    }
+
+    #[test]
+    fn test_blit_unsupported() {
+        let test_device = test_device();
+        let (graph, color, sampled) = image_conflict_graph(
+            &test_device,
+            RenderGraphImageConstraint {
+                format: Some(RafxFormat::R16G16B16A16_SFLOAT),
+                ..color_constraint()
+            },
+        );
+        let mut device_info = test_device.device_context().device_info().clone();
+        device_info.supports_blit_images = false;
+
+        match graph.build_plan(&device_info) {
+            Err(RenderGraphError::IncompatibleImageSpecification { image, usage, .. }) => {
+                assert_eq!(image, color);
+                assert_eq!(usage, sampled);
+            }
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
         }
     }
+
+    #[test]
+    fn test_blit_multiple_mips_unsupported() {
+        let test_device = test_device();
+        let (graph, color, sampled) = image_conflict_graph(
+            &test_device,
+            RenderGraphImageConstraint {
+                format: Some(RafxFormat::R16G16B16A16_SFLOAT),
+                mip_count: Some(4),
+                ..color_constraint()
+            },
+        );
+
+        match test_device.build_plan(graph) {
+            Err(RenderGraphError::IncompatibleImageSpecification { image, usage, .. }) => {
+                assert_eq!(image, color);
+                assert_eq!(usage, sampled);
+            }
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        }
+    }
+
+    // A compute node reads a buffer with a different size than the node before it wrote it with
+    fn buffer_conflict_graph(
+        test_device: &TestDevice,
+        read_size: u64,
+    ) -> (
+        RenderGraphBuilder,
+        RenderGraphBufferUsageId,
+        RenderGraphBufferUsageId,
+    ) {
+        let mut graph = RenderGraphBuilder::default();
+
+        let simulate = graph.add_node("simulate", RenderGraphQueue::DefaultGraphics);
+        let particles = graph.create_storage_buffer(simulate, storage_buffer_constraint());
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        let read = graph.read_storage_buffer(
+            draw,
+            particles,
+            RenderGraphBufferConstraint {
+                size: Some(read_size),
+                ..Default::default()
+            },
+        );
+        let color =
+            graph.create_color_attachment(draw, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+
+        (graph, particles, read)
+    }
+
+    #[test]
+    fn test_copy_to_larger_buffer_unsupported() {
+        let test_device = test_device();
+        let (graph, particles, read) = buffer_conflict_graph(&test_device, 512);
+
+        match test_device.build_plan(graph) {
+            Err(RenderGraphError::IncompatibleBufferSpecification { buffer, usage, .. }) => {
+                assert_eq!(buffer, particles);
+                assert_eq!(usage, read);
+            }
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        }
+    }
+
+    fn physical_image(
+        plan: &RenderGraphPlan,
+        image: RenderGraphImageUsageId,
+    ) -> PhysicalImageId {
+        plan.image_views[plan.image_usage_to_view[&image].0].physical_image
+    }
+
+    // Checks that the plan inserted a single blit node that runs before the node reading `read`,
+    // and that the read was redirected to an image with `read_spec`
+    fn assert_blit_inserted(
+        plan: &RenderGraphPlan,
+        written: RenderGraphImageUsageId,
+        read: RenderGraphImageUsageId,
+        read_spec: &RenderGraphImageSpecification,
+    ) {
+        let blits: Vec<_> = plan
+            .conversions()
+            .iter()
+            .filter(|x| x.kind == RenderGraphConversionKind::BlitImage)
+            .collect();
+        assert_eq!(blits.len(), 1);
+
+        let blit_pass_index = plan.node_to_pass_index[&blits[0].node_id];
+        assert_eq!(
+            plan.passes[blit_pass_index].debug_name(),
+            Some("Blit image")
+        );
+        let post_pass_index = plan
+            .passes
+            .iter()
+            .position(|x| x.debug_name() == Some("post"))
+            .unwrap();
+        assert!(blit_pass_index < post_pass_index);
+
+        let read_image = physical_image(plan, read);
+        assert_ne!(read_image, physical_image(plan, written));
+        let spec = &plan.intermediate_images[&read_image];
+        assert_eq!(spec.format, read_spec.format);
+        assert_eq!(spec.extents, read_spec.extents);
+        assert_eq!(spec.samples, read_spec.samples);
+    }
+
+    #[test]
+    fn test_blit_format_conflict() {
+        let test_device = test_device();
+        let read_constraint = RenderGraphImageConstraint {
+            format: Some(RafxFormat::R16G16B16A16_SFLOAT),
+            ..color_constraint()
+        };
+        let (graph, color, sampled) = image_conflict_graph(&test_device, read_constraint);
+        let plan = test_device.build_plan(graph).unwrap();
+
+        let mut read_spec = image_specification(RafxResourceType::TEXTURE);
+        read_spec.format = RafxFormat::R16G16B16A16_SFLOAT;
+        assert_blit_inserted(&plan, color, sampled, &read_spec);
+    }
+
+    #[test]
+    fn test_blit_extents_conflict() {
+        let test_device = test_device();
+        let extents = RenderGraphImageExtents::Custom(32, 32, 1);
+        let (graph, color, sampled) = image_conflict_graph(
+            &test_device,
+            RenderGraphImageConstraint {
+                extents: Some(extents),
+                ..color_constraint()
+            },
+        );
+        let plan = test_device.build_plan(graph).unwrap();
+
+        let mut read_spec = image_specification(RafxResourceType::TEXTURE);
+        read_spec.extents = extents;
+        assert_blit_inserted(&plan, color, sampled, &read_spec);
+    }
+
+    #[test]
+    fn test_copy_to_smaller_buffer() {
+        let test_device = test_device();
+        let (graph, particles, read) = buffer_conflict_graph(&test_device, 128);
+        let plan = test_device.build_plan(graph).unwrap();
+
+        assert_eq!(plan.conversions().len(), 1);
+        let copy = &plan.conversions()[0];
+        assert_eq!(copy.kind, RenderGraphConversionKind::CopyBuffer);
+        let copy_pass_index = plan.node_to_pass_index[&copy.node_id];
+        assert_eq!(
+            plan.passes[copy_pass_index].debug_name(),
+            Some("Copy buffer")
+        );
+        let draw_pass_index = plan
+            .passes
+            .iter()
+            .position(|x| x.debug_name() == Some("draw"))
+            .unwrap();
+        assert!(copy_pass_index < draw_pass_index);
+
+        // The reader gets a buffer of the size it asked for
+        let read_buffer = plan.buffer_usage_to_physical[&read];
+        assert_ne!(read_buffer, plan.buffer_usage_to_physical[&particles]);
+        assert_eq!(plan.intermediate_buffers[&read_buffer].size, 128);
+    }
+
+    #[test]
+    fn test_resolve_then_blit() {
+        let test_device = test_device();
+        let mut graph = RenderGraphBuilder::default();
+
+        let draw = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        let color = graph.create_color_attachment(
+            draw,
+            0,
+            None,
+            RenderGraphImageConstraint {
+                samples: Some(RafxSampleCount::SampleCount4),
+                ..color_constraint()
+            },
+            Default::default(),
+        );
+
+        // The read isn't the resolved specification, but a resolve still produces the non-MSAA
+        // image that the blit converts
+        let post = graph.add_node("post", RenderGraphQueue::DefaultGraphics);
+        let sampled = graph.sample_image(
+            post,
+            color,
+            RenderGraphImageConstraint {
+                samples: Some(RafxSampleCount::SampleCount1),
+                format: Some(RafxFormat::R16G16B16A16_SFLOAT),
+                ..color_constraint()
+            },
+            Default::default(),
+        );
+        let post_color =
+            graph.create_color_attachment(post, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, post_color);
+
+        let plan = test_device.build_plan(graph).unwrap();
+
+        let conversion_kinds: Vec<_> = plan.conversions().iter().map(|x| x.kind).collect();
+        assert_eq!(
+            conversion_kinds,
+            vec![
+                RenderGraphConversionKind::Resolve,
+                RenderGraphConversionKind::BlitImage
+            ]
+        );
+        assert_eq!(plan.conversions()[0].node_id, draw);
+
+        let mut read_spec = image_specification(RafxResourceType::TEXTURE);
+        read_spec.format = RafxFormat::R16G16B16A16_SFLOAT;
+        assert_blit_inserted(&plan, color, sampled, &read_spec);
+    }
 }
@@ -4,9 +4,14 @@ use crate::graph::{
     RenderGraphBufferSpecification, RenderGraphImageSpecification, RenderGraphPlan,
     SwapchainSurfaceInfo,
 };
-use crate::{BufferResource, ImageResource, ImageViewResource, ResourceArc, ResourceLookupSet};
+use crate::{
+    BufferResource, ImageResource, ImageViewResource, ResourceArc, ResourceLookupSet,
+    ResourceMemoryCategory,
+};
 use fnv::FnvHashMap;
-use rafx_api::{RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResult, RafxTextureDef};
+use rafx_api::{
+    RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxResult, RafxSemaphore, RafxTextureDef,
+};
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Hash, PartialEq, Eq, Debug)]
@@ -30,9 +35,71 @@ struct RenderGraphCachedImage {
     image: ResourceArc<ImageResource>,
 }
 
+// A set of images that share one allocation. These are cached together since the memory can't be
+// split back up
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+struct RenderGraphCachedAliasedImagesKey {
+    specifications: Vec<RenderGraphImageSpecification>,
+    swapchain_surface_info: SwapchainSurfaceInfo,
+}
+
+struct RenderGraphCachedAliasedImages {
+    keep_until_frame: u64,
+    images: Vec<ResourceArc<ImageResource>>,
+}
+
+// A set of buffers that share one allocation
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
+struct RenderGraphCachedAliasedBuffersKey {
+    specifications: Vec<RenderGraphBufferSpecification>,
+}
+
+struct RenderGraphCachedAliasedBuffers {
+    keep_until_frame: u64,
+    buffers: Vec<ResourceArc<BufferResource>>,
+}
+
+fn intermediate_buffer_def(specification: &RenderGraphBufferSpecification) -> RafxBufferDef {
+    RafxBufferDef {
+        size: specification.size,
+        //alignment: specification.alignment,
+        memory_usage: RafxMemoryUsage::GpuOnly,
+        resource_type: specification.resource_type,
+        //initial_state: specification.initial_state,
+        ..Default::default()
+    }
+}
+
+fn intermediate_texture_def(
+    specification: &RenderGraphImageSpecification,
+    swapchain_surface_info: &SwapchainSurfaceInfo,
+) -> RafxTextureDef {
+    RafxTextureDef {
+        extents: specification
+            .extents
+            .into_rafx_extents(swapchain_surface_info),
+        array_length: specification.layer_count,
+        mip_count: specification.mip_count,
+        format: specification.format,
+        sample_count: specification.samples,
+        resource_type: specification.resource_type,
+        dimensions: Default::default(),
+    }
+}
+
+struct RenderGraphCachedSemaphore {
+    keep_until_frame: u64,
+    semaphore: Arc<RafxSemaphore>,
+}
+
 pub struct RenderGraphCacheInner {
     buffers: FnvHashMap<RenderGraphCachedBufferKey, Vec<RenderGraphCachedBuffer>>,
     images: FnvHashMap<RenderGraphCachedImageKey, Vec<RenderGraphCachedImage>>,
+    aliased_buffers:
+        FnvHashMap<RenderGraphCachedAliasedBuffersKey, Vec<RenderGraphCachedAliasedBuffers>>,
+    aliased_images:
+        FnvHashMap<RenderGraphCachedAliasedImagesKey, Vec<RenderGraphCachedAliasedImages>>,
+    semaphores: Vec<RenderGraphCachedSemaphore>,
     current_frame_index: u64,
     frames_to_persist: u64,
 }
@@ -42,6 +109,9 @@ impl RenderGraphCacheInner {
         RenderGraphCacheInner {
             buffers: Default::default(),
             images: Default::default(),
+            aliased_buffers: Default::default(),
+            aliased_images: Default::default(),
+            semaphores: Default::default(),
             current_frame_index: 0,
             frames_to_persist: max_frames_in_flight as u64 + 1,
         }
@@ -63,12 +133,61 @@ impl RenderGraphCacheInner {
 
         self.images.retain(|_k, v| !v.is_empty());
 
+        for value in self.aliased_buffers.values_mut() {
+            value.retain(|x| x.keep_until_frame > current_frame_index);
+        }
+
+        self.aliased_buffers.retain(|_k, v| !v.is_empty());
+
+        for value in self.aliased_images.values_mut() {
+            value.retain(|x| x.keep_until_frame > current_frame_index);
+        }
+
+        self.aliased_images.retain(|_k, v| !v.is_empty());
+
         self.current_frame_index += 1;
     }
 
     pub fn clear(&mut self) {
         self.buffers.clear();
         self.images.clear();
+        self.aliased_buffers.clear();
+        self.aliased_images.clear();
+        self.semaphores.clear();
+    }
+
+    // Semaphores used to synchronize submissions on different queues. A semaphore can be reused
+    // once the frames that may still be waiting on it have completed
+    pub(super) fn allocate_semaphores(
+        &mut self,
+        device_context: &RafxDeviceContext,
+        count: usize,
+    ) -> RafxResult<Vec<Arc<RafxSemaphore>>> {
+        let current_frame_index = self.current_frame_index;
+        let keep_until_frame = current_frame_index + self.frames_to_persist;
+
+        let mut semaphores = Vec::with_capacity(count);
+        for cached_semaphore in &mut self.semaphores {
+            if semaphores.len() == count {
+                break;
+            }
+
+            if cached_semaphore.keep_until_frame <= current_frame_index {
+                cached_semaphore.keep_until_frame = keep_until_frame;
+                semaphores.push(cached_semaphore.semaphore.clone());
+            }
+        }
+
+        while semaphores.len() < count {
+            let semaphore = Arc::new(device_context.create_semaphore()?);
+            self.semaphores.push(RenderGraphCachedSemaphore {
+                keep_until_frame,
+                semaphore: semaphore.clone(),
+            });
+            semaphores.push(semaphore);
+        }
+
+        Ok(semaphores)
     }
 
     pub(super) fn allocate_buffers(
@@ -93,9 +212,83 @@ impl RenderGraphCacheInner {
             buffer_resources.insert(physical_id, buffer.dst_buffer.clone());
         }
 
+        for (&physical_id, buffer) in &graph.external_buffers {
+            buffer_resources.insert(physical_id, buffer.src_buffer.clone());
+        }
+
+        // Buffers that share memory are allocated together, reusing a set of buffers from a
+        // previous frame if possible
+        let mut next_aliased_buffers_to_use =
+            FnvHashMap::<RenderGraphCachedAliasedBuffersKey, usize>::default();
+        for aliased_buffers in &graph.aliased_buffers {
+            let key = RenderGraphCachedAliasedBuffersKey {
+                specifications: aliased_buffers
+                    .iter()
+                    .map(|id| graph.intermediate_buffers[id].clone())
+                    .collect(),
+            };
+
+            let next_buffers_index = next_aliased_buffers_to_use.entry(key.clone()).or_insert(0);
+            let matching_cached_buffers = self.aliased_buffers.entry(key.clone()).or_default();
+
+            let buffers = if let Some(cached_buffers) =
+                matching_cached_buffers.get_mut(*next_buffers_index)
+            {
+                log::trace!(
+                    "  Aliased buffers {:?} - REUSE  (key: {:?}, index: {})",
+                    aliased_buffers,
+                    key,
+                    next_buffers_index
+                );
+
+                cached_buffers.keep_until_frame = keep_until_frame;
+                cached_buffers.buffers.clone()
+            } else {
+                let buffer_defs: Vec<_> = key
+                    .specifications
+                    .iter()
+                    .map(intermediate_buffer_def)
+                    .collect();
+                let buffers: Vec<_> = device_context
+                    .create_aliased_buffers(&buffer_defs)?
+                    .into_iter()
+                    .map(|buffer| {
+                        resources.insert_buffer_with_memory_category(
+                            buffer,
+                            ResourceMemoryCategory::RenderGraphTransient,
+                        )
+                    })
+                    .collect();
+
+                log::trace!(
+                    "  Aliased buffers {:?} - CREATE  (key: {:?}, index: {})",
+                    aliased_buffers,
+                    key,
+                    next_buffers_index
+                );
+
+                debug_assert_eq!(matching_cached_buffers.len(), *next_buffers_index);
+                matching_cached_buffers.push(RenderGraphCachedAliasedBuffers {
+                    keep_until_frame,
+                    buffers: buffers.clone(),
+                });
+                buffers
+            };
+            *next_buffers_index += 1;
+
+            for (&id, buffer) in aliased_buffers.iter().zip(buffers) {
+                buffer_resources.insert(id, buffer);
+            }
+        }
+
         // Iterate all intermediate buffers, assigning an existing buffer from a previous frame or
         // allocating a new one
         for (&id, specification) in &graph.intermediate_buffers {
+            // Skip buffers that share memory, they were allocated above
+            if buffer_resources.contains_key(&id) {
+                continue;
+            }
+
             let key = RenderGraphCachedBufferKey {
                 specification: specification.clone(),
             };
@@ -122,15 +315,12 @@ impl RenderGraphCacheInner {
                 buffer_resources.insert(id, cached_buffer.buffer.clone());
             } else {
                 // No unused buffer available, create one
-                let buffer = device_context.create_buffer(&RafxBufferDef {
-                    size: key.specification.size,
-                    //alignment: key.specification.alignment,
-                    memory_usage: RafxMemoryUsage::GpuOnly,
-                    resource_type: key.specification.resource_type,
-                    //initial_state: key.specification.initial_state,
-                    ..Default::default()
-                })?;
-                let buffer = resources.insert_buffer(buffer);
+                let buffer =
+                    device_context.create_buffer(&intermediate_buffer_def(&key.specification))?;
+                let buffer = resources.insert_buffer_with_memory_category(
+                    buffer,
+                    ResourceMemoryCategory::RenderGraphTransient,
+                );
 
                 log::trace!(
                     "  Buffer {:?} - CREATE {:?}  (key: {:?}, index: {})",
@@ -180,9 +370,86 @@ impl RenderGraphCacheInner {
             image_resources.insert(physical_id, image.dst_image.get_raw().image);
         }
 
+        for (id, image) in &graph.external_images {
+            let physical_id = graph.image_views[id.0].physical_image;
+            image_resources.insert(physical_id, image.src_image.get_raw().image);
+        }
+
+        // Images that share memory are allocated together, reusing a set of images from a
+        // previous frame if possible
+        let mut next_aliased_images_to_use =
+            FnvHashMap::<RenderGraphCachedAliasedImagesKey, usize>::default();
+        for aliased_images in &graph.aliased_images {
+            let key = RenderGraphCachedAliasedImagesKey {
+                specifications: aliased_images
+                    .iter()
+                    .map(|id| graph.intermediate_images[id].clone())
+                    .collect(),
+                swapchain_surface_info: swapchain_surface_info.clone(),
+            };
+
+            let next_images_index = next_aliased_images_to_use.entry(key.clone()).or_insert(0);
+            let matching_cached_images = self.aliased_images.entry(key.clone()).or_default();
+
+            let images =
+                if let Some(cached_images) = matching_cached_images.get_mut(*next_images_index) {
+                    log::trace!(
+                        "  Aliased images {:?} - REUSE  (key: {:?}, index: {})",
+                        aliased_images,
+                        key,
+                        next_images_index
+                    );
+
+                    cached_images.keep_until_frame = keep_until_frame;
+                    cached_images.images.clone()
+                } else {
+                    let texture_defs: Vec<_> = key
+                        .specifications
+                        .iter()
+                        .map(|specification| {
+                            intermediate_texture_def(specification, &key.swapchain_surface_info)
+                        })
+                        .collect();
+                    let images: Vec<_> = device_context
+                        .create_aliased_textures(&texture_defs)?
+                        .into_iter()
+                        .map(|image| {
+                            resources.insert_image_with_memory_category(
+                                image,
+                                ResourceMemoryCategory::RenderGraphTransient,
+                            )
+                        })
+                        .collect();
+
+                    log::trace!(
+                        "  Aliased images {:?} - CREATE  (key: {:?}, index: {})",
+                        aliased_images,
+                        key,
+                        next_images_index
+                    );
+
+                    debug_assert_eq!(matching_cached_images.len(), *next_images_index);
+                    matching_cached_images.push(RenderGraphCachedAliasedImages {
+                        keep_until_frame,
+                        images: images.clone(),
+                    });
+                    images
+                };
+            *next_images_index += 1;
+
+            for (&id, image) in aliased_images.iter().zip(images) {
+                image_resources.insert(id, image);
+            }
+        }
+
         // Iterate all intermediate images, assigning an existing image from a previous frame or
         // allocating a new one
         for (&id, specification) in &graph.intermediate_images {
+            // Skip images that share memory, they were allocated above
+            if image_resources.contains_key(&id) {
+                continue;
+            }
+
             let key = RenderGraphCachedImageKey {
                 specification: specification.clone(),
                 swapchain_surface_info: swapchain_surface_info.clone(),
@@ -210,21 +477,14 @@ impl RenderGraphCacheInner {
                 image_resources.insert(id, cached_image.image.clone());
             } else {
                 // No unused image available, create one
-                let extents = key
-                    .specification
-                    .extents
-                    .into_rafx_extents(&key.swapchain_surface_info);
-
-                let image = device_context.create_texture(&RafxTextureDef {
-                    extents,
-                    array_length: specification.layer_count,
-                    mip_count: specification.mip_count,
-                    format: specification.format,
-                    sample_count: specification.samples,
-                    resource_type: specification.resource_type,
-                    dimensions: Default::default(),
-                })?;
-                let image = resources.insert_image(image);
+                let image = device_context.create_texture(&intermediate_texture_def(
+                    &key.specification,
+                    &key.swapchain_surface_info,
+                ))?;
+                let image = resources.insert_image_with_memory_category(
+                    image,
+                    ResourceMemoryCategory::RenderGraphTransient,
+                );
 
                 log::trace!(
                     "  Image {:?} - CREATE {:?}  (key: {:?}, index: {})",
@@ -267,10 +527,16 @@ impl RenderGraphCacheInner {
             image_view_resources.insert(*id, image.dst_image.clone());
         }
 
+        // Same for external images. Other views of an external image (i.e. a different mip) are
+        // created from the underlying image below
+        for (id, image) in &graph.external_images {
+            image_view_resources.insert(*id, image.src_image.clone());
+        }
+
         for (id, view) in graph.image_views.iter().enumerate() {
             let id = PhysicalImageViewId(id);
 
-            // Skip output images (handled above). They already have ImageViewResources
+            // Skip output and external images (handled above). They already have ImageViewResources
             if image_view_resources.contains_key(&id) {
                 continue;
             }
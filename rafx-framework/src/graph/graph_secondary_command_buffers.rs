@@ -0,0 +1,249 @@
+use crate::resources::{DynCommandBuffer, DynCommandPool, DynCommandPoolAllocator};
+use rafx_api::{
+    RafxCommandBuffer, RafxCommandBufferDef, RafxCommandPoolDef, RafxQueue, RafxResult,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone)]
+struct RenderGraphViewport {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    depth_min: f32,
+    depth_max: f32,
+}
+
+#[derive(Copy, Clone)]
+struct RenderGraphScissor {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+struct RenderGraphSecondaryCommandBuffersState {
+    // Allocates the command buffers recorded on the thread that is visiting the node
+    command_pool: DynCommandPool,
+    // Command buffers in the order the primary command buffer will execute them
+    command_buffers: Vec<DynCommandBuffer>,
+    // Command buffers in command_buffers that were current before a split. They are ended when
+    // the subpass is executed, so copies of them held by the node remain valid until then.
+    unended_command_buffers: Vec<DynCommandBuffer>,
+    // The command buffer currently being recorded on the thread that is visiting the node
+    current_command_buffer: DynCommandBuffer,
+    // Set on every command buffer begun after cmd_set_viewport/cmd_set_scissor. Otherwise
+    // secondary command buffers start with a viewport and scissor that cover the renderpass.
+    viewport: Option<RenderGraphViewport>,
+    scissor: Option<RenderGraphScissor>,
+}
+
+struct RenderGraphSecondaryCommandBuffersInner {
+    primary_command_buffer: DynCommandBuffer,
+    queue: RafxQueue,
+    command_pool_allocator: DynCommandPoolAllocator,
+    state: Mutex<RenderGraphSecondaryCommandBuffersState>,
+}
+
+/// The secondary command buffers that record a subpass started with
+/// `RafxSubpassContents::SecondaryCommandBuffers`. Commands recorded while visiting the node go
+/// into the current command buffer. `PreparedRenderData::write_view_phase` records chunks of submit
+/// nodes into additional command buffers on other threads and then starts a new current command
+/// buffer after them. The primary command buffer executes all of them in order once the node has
+/// been visited.
+///
+/// Each secondary command buffer starts with a viewport and scissor covering the renderpass. Use
+/// `cmd_set_viewport`/`cmd_set_scissor` (or the same functions on `RenderJobCommandBufferContext`)
+/// to change them for every command buffer of the subpass, including those recorded on other
+/// threads.
+#[derive(Clone)]
+pub struct RenderGraphSecondaryCommandBuffers {
+    inner: Arc<RenderGraphSecondaryCommandBuffersInner>,
+}
+
+impl RenderGraphSecondaryCommandBuffers {
+    pub(super) fn new(
+        primary_command_buffer: &DynCommandBuffer,
+        queue: &RafxQueue,
+        command_pool_allocator: &DynCommandPoolAllocator,
+    ) -> RafxResult<Self> {
+        let mut command_pool = command_pool_allocator.allocate_dyn_pool(
+            queue,
+            &RafxCommandPoolDef { transient: true },
+            0,
+        )?;
+        let current_command_buffer =
+            Self::begin_secondary(&mut command_pool, primary_command_buffer, None, None)?;
+
+        let state = RenderGraphSecondaryCommandBuffersState {
+            command_pool,
+            command_buffers: Default::default(),
+            unended_command_buffers: Default::default(),
+            current_command_buffer,
+            viewport: None,
+            scissor: None,
+        };
+
+        let inner = RenderGraphSecondaryCommandBuffersInner {
+            primary_command_buffer: primary_command_buffer.clone(),
+            queue: queue.clone(),
+            command_pool_allocator: command_pool_allocator.clone(),
+            state: Mutex::new(state),
+        };
+
+        Ok(RenderGraphSecondaryCommandBuffers {
+            inner: Arc::new(inner),
+        })
+    }
+
+    fn begin_secondary(
+        command_pool: &mut DynCommandPool,
+        primary_command_buffer: &RafxCommandBuffer,
+        viewport: Option<RenderGraphViewport>,
+        scissor: Option<RenderGraphScissor>,
+    ) -> RafxResult<DynCommandBuffer> {
+        let command_buffer = command_pool
+            .allocate_dyn_command_buffer(&RafxCommandBufferDef { is_secondary: true })?;
+        command_buffer.begin_secondary(primary_command_buffer)?;
+
+        if let Some(viewport) = viewport {
+            command_buffer.cmd_set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                viewport.depth_min,
+                viewport.depth_max,
+            )?;
+        }
+
+        if let Some(scissor) = scissor {
+            command_buffer.cmd_set_scissor(scissor.x, scissor.y, scissor.width, scissor.height)?;
+        }
+
+        Ok(command_buffer)
+    }
+
+    /// The command buffer currently being recorded on the thread that is visiting the node
+    pub fn current_command_buffer(&self) -> DynCommandBuffer {
+        self.inner
+            .state
+            .lock()
+            .unwrap()
+            .current_command_buffer
+            .clone()
+    }
+
+    /// Sets the viewport on the current command buffer and every command buffer of the subpass
+    /// begun after it
+    pub fn cmd_set_viewport(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        depth_min: f32,
+        depth_max: f32,
+    ) -> RafxResult<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.viewport = Some(RenderGraphViewport {
+            x,
+            y,
+            width,
+            height,
+            depth_min,
+            depth_max,
+        });
+        state
+            .current_command_buffer
+            .cmd_set_viewport(x, y, width, height, depth_min, depth_max)
+    }
+
+    /// Sets the scissor on the current command buffer and every command buffer of the subpass
+    /// begun after it
+    pub fn cmd_set_scissor(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RafxResult<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        state.scissor = Some(RenderGraphScissor {
+            x,
+            y,
+            width,
+            height,
+        });
+        state
+            .current_command_buffer
+            .cmd_set_scissor(x, y, width, height)
+    }
+
+    /// Allocates and begins a secondary command buffer that continues the subpass. This may be
+    /// called from any thread. The returned pool must stay on the calling thread and be dropped
+    /// once the command buffer has been recorded.
+    pub fn allocate_command_buffer(&self) -> RafxResult<(DynCommandPool, DynCommandBuffer)> {
+        let (viewport, scissor) = {
+            let state = self.inner.state.lock().unwrap();
+            (state.viewport, state.scissor)
+        };
+
+        let mut command_pool = self.inner.command_pool_allocator.allocate_dyn_pool(
+            &self.inner.queue,
+            &RafxCommandPoolDef { transient: true },
+            0,
+        )?;
+        let command_buffer = Self::begin_secondary(
+            &mut command_pool,
+            &self.inner.primary_command_buffer,
+            viewport,
+            scissor,
+        )?;
+        Ok((command_pool, command_buffer))
+    }
+
+    /// Appends `command_buffers` after the current command buffer. They must have been ended.
+    /// Returns the new current command buffer, which must be used for any further commands.
+    /// The previous current command buffer is not ended until the subpass is executed, but
+    /// commands recorded into it execute before `command_buffers`.
+    pub fn append_command_buffers(
+        &self,
+        command_buffers: Vec<DynCommandBuffer>,
+    ) -> RafxResult<DynCommandBuffer> {
+        let mut state = self.inner.state.lock().unwrap();
+        let previous_command_buffer = state.current_command_buffer.clone();
+        state.command_buffers.push(previous_command_buffer.clone());
+        state.unended_command_buffers.push(previous_command_buffer);
+        state.command_buffers.extend(command_buffers);
+
+        let (viewport, scissor) = (state.viewport, state.scissor);
+        let current_command_buffer = Self::begin_secondary(
+            &mut state.command_pool,
+            &self.inner.primary_command_buffer,
+            viewport,
+            scissor,
+        )?;
+        state.current_command_buffer = current_command_buffer.clone();
+        Ok(current_command_buffer)
+    }
+
+    // Ends the current command buffer (and those that were current before a split) and executes
+    // all of the command buffers on the primary command buffer
+    pub(super) fn execute(&self) -> RafxResult<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        for command_buffer in state.unended_command_buffers.drain(..) {
+            command_buffer.end()?;
+        }
+        state.current_command_buffer.end()?;
+
+        let current_command_buffer = state.current_command_buffer.clone();
+        state.command_buffers.push(current_command_buffer);
+
+        let command_buffers: Vec<&RafxCommandBuffer> =
+            state.command_buffers.iter().map(|x| &**x).collect();
+        self.inner
+            .primary_command_buffer
+            .cmd_execute_secondary_command_buffers(&command_buffers)
+    }
+}
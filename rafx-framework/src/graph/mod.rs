@@ -7,6 +7,7 @@ pub use graph_image::RenderGraphImageConstraint;
 pub use graph_image::RenderGraphImageExtents;
 pub use graph_image::RenderGraphImageSpecification;
 pub use graph_image::RenderGraphImageUsageId;
+pub use graph_image::RenderGraphImageUsageType;
 pub use graph_image::RenderGraphImageViewOptions;
 use graph_image::*;
 
@@ -14,8 +15,13 @@ mod graph_buffer;
 pub use graph_buffer::RenderGraphBufferConstraint;
 pub use graph_buffer::RenderGraphBufferSpecification;
 pub use graph_buffer::RenderGraphBufferUsageId;
+pub use graph_buffer::RenderGraphBufferUsageType;
 use graph_buffer::*;
 
+mod graph_error;
+pub use graph_error::RenderGraphError;
+pub use graph_error::RenderGraphResult;
+
 mod graph_node;
 pub use graph_node::RenderGraphNodeId;
 use graph_node::*;
@@ -24,11 +30,36 @@ mod graph_pass;
 use graph_pass::*;
 
 mod graph_plan;
+pub use graph_plan::RenderGraphConversionKind;
+pub use graph_plan::RenderGraphMemoryStats;
 pub use graph_plan::RenderGraphPlan;
+pub use graph_plan::RenderGraphPlanConversion;
+pub use graph_plan::RenderGraphPlanSubmission;
+
+mod graph_export;
+pub use graph_export::RenderGraphExport;
+pub use graph_export::RenderGraphExportBarrier;
+pub use graph_export::RenderGraphExportNode;
+pub use graph_export::RenderGraphExportPass;
+pub use graph_export::RenderGraphExportPhysicalResource;
+pub use graph_export::RenderGraphExportPlan;
+pub use graph_export::RenderGraphExportResource;
+pub use graph_export::RenderGraphExportResourceVersion;
+pub use graph_export::RenderGraphExportSubmission;
+pub use graph_export::RenderGraphExportUsage;
 
 mod graph_resource_cache;
 pub use graph_resource_cache::RenderGraphCache;
 
+mod graph_gpu_timing;
+pub use graph_gpu_timing::RenderGraphGpuTimedNode;
+pub use graph_gpu_timing::RenderGraphGpuTimer;
+pub use graph_gpu_timing::RenderGraphGpuTimings;
+pub use graph_gpu_timing::RenderGraphPassGpuTime;
+
+mod graph_secondary_command_buffers;
+pub use graph_secondary_command_buffers::RenderGraphSecondaryCommandBuffers;
+
 mod prepared_graph;
 pub use prepared_graph::OnBeginExecuteGraphArgs;
 pub use prepared_graph::PreparedRenderGraph;
@@ -1,24 +1,32 @@
 use super::PhysicalImageId;
 use crate::graph::graph_buffer::PhysicalBufferId;
+use crate::graph::graph_gpu_timing::{RenderGraphGpuTimedNode, RenderGraphGpuTimer};
 use crate::graph::graph_image::PhysicalImageViewId;
 use crate::graph::graph_node::{RenderGraphNodeId, RenderGraphNodeName};
-use crate::graph::graph_pass::{PrepassBufferBarrier, PrepassImageBarrier, RenderGraphOutputPass};
-use crate::graph::graph_plan::RenderGraphPlan;
+use crate::graph::graph_pass::{
+    PrepassBufferBarrier, PrepassImageBarrier, RenderGraphOutputPass, RenderGraphQueueTransition,
+};
+use crate::graph::graph_plan::{
+    RenderGraphMemoryStats, RenderGraphPlan, RenderGraphPlanConversion, RenderGraphPlanSubmission,
+};
 use crate::graph::{
-    RenderGraphBufferUsageId, RenderGraphBuilder, RenderGraphImageUsageId,
-    RenderGraphNodeVisitNodeCallback,
+    RenderGraphBufferUsageId, RenderGraphBuilder, RenderGraphError, RenderGraphExport,
+    RenderGraphImageUsageId, RenderGraphNodeVisitNodeCallback, RenderGraphQueue,
+    RenderGraphSecondaryCommandBuffers,
 };
 use crate::render_features::{PreparedRenderData, RenderJobBeginExecuteGraphContext};
-use crate::resources::DynCommandBuffer;
+use crate::resources::{DynCommandBuffer, DynCommandPool, DynCommandPoolAllocator};
 use crate::{BufferResource, GraphicsPipelineRenderTargetMeta, ImageResource};
 use crate::{ImageViewResource, ResourceArc, ResourceContext};
 use fnv::FnvHashMap;
 use rafx_api::{
     RafxBarrierQueueTransition, RafxBufferBarrier, RafxColorRenderTargetBinding, RafxCommandBuffer,
     RafxCommandBufferDef, RafxCommandPoolDef, RafxDepthStencilRenderTargetBinding,
-    RafxDeviceContext, RafxExtents2D, RafxFormat, RafxQueue, RafxResult, RafxTextureBarrier,
+    RafxDeviceContext, RafxExtents2D, RafxFormat, RafxQueue, RafxResult, RafxSemaphore,
+    RafxSubpassContents, RafxTextureBarrier,
 };
 use std::hash::Hash;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct SwapchainSurfaceInfo {
@@ -71,9 +79,16 @@ pub struct VisitComputeNodeArgs<'graph, 'write> {
 }
 
 pub struct VisitRenderpassNodeArgs<'graph, 'write> {
+    /// The command buffer for the subpass. If `secondary_command_buffers` is set, this is the
+    /// first of its secondary command buffers.
     pub command_buffer: DynCommandBuffer,
     pub render_target_meta: GraphicsPipelineRenderTargetMeta,
     pub graph_context: RenderGraphContext<'graph, 'write>,
+    /// Set if the subpass is recorded into secondary command buffers, which allows
+    /// `PreparedRenderData::write_view_phase` to record submit nodes on other threads. This only
+    /// happens when a node in the pass depends (see `add_render_phase_dependency`) on a render
+    /// phase that the write thread pool splits into chunks.
+    pub secondary_command_buffers: Option<RenderGraphSecondaryCommandBuffers>,
 }
 
 /// Encapsulates a render graph plan and all resources required to execute it
@@ -84,6 +99,12 @@ pub struct PreparedRenderGraph {
     image_resources: FnvHashMap<PhysicalImageId, ResourceArc<ImageResource>>,
     image_view_resources: FnvHashMap<PhysicalImageViewId, ResourceArc<ImageViewResource>>,
     graph_plan: RenderGraphPlan,
+    swapchain_surface_info: SwapchainSurfaceInfo,
+    gpu_timer: Option<RenderGraphGpuTimer>,
+    queues: FnvHashMap<RenderGraphQueue, RafxQueue>,
+    semaphores: Vec<Arc<RafxSemaphore>>,
+    // Signaled by the GPU timer's query reset, waited on by the first submissions on other queues
+    timer_reset_semaphores: Vec<Arc<RafxSemaphore>>,
 }
 
 impl PreparedRenderGraph {
@@ -92,7 +113,14 @@ impl PreparedRenderGraph {
         node_id: RenderGraphNodeId,
     ) -> Option<RenderGraphNodeName> {
         let pass_index = *self.graph_plan.node_to_pass_index.get(&node_id)?;
-        self.graph_plan.passes[pass_index].debug_name()
+        match &self.graph_plan.passes[pass_index] {
+            RenderGraphOutputPass::Renderpass(pass) => pass
+                .subpasses
+                .iter()
+                .find(|x| x.node_id == node_id)
+                .and_then(|x| x.debug_name),
+            RenderGraphOutputPass::Compute(pass) => pass.debug_name,
+        }
     }
 
     pub fn new(
@@ -101,7 +129,7 @@ impl PreparedRenderGraph {
         graph: RenderGraphBuilder,
         swapchain_surface_info: &SwapchainSurfaceInfo,
     ) -> RafxResult<Self> {
-        let graph_plan = graph.build_plan();
+        let graph_plan = graph.build_plan(device_context.device_info())?;
         let mut cache_guard = resource_context.render_graph_cache().inner.lock().unwrap();
         let cache = &mut *cache_guard;
 
@@ -122,6 +150,37 @@ impl PreparedRenderGraph {
             &image_resources,
         )?;
 
+        let semaphore_count = graph_plan
+            .submissions
+            .iter()
+            .map(|x| x.wait_for.len())
+            .sum();
+        let semaphores = cache.allocate_semaphores(device_context, semaphore_count)?;
+
+        // Name the intermediate resources so they can be identified in graphics debuggers. Output
+        // resources are owned by the caller so they are left alone.
+        for physical_image in graph_plan.intermediate_images.keys() {
+            let name = match graph_plan.physical_image_names.get(physical_image) {
+                Some(name) => name.clone(),
+                None => format!("render graph image {}", physical_image.0),
+            };
+            image_resources[physical_image]
+                .get_raw()
+                .image
+                .set_debug_name(name)?;
+        }
+
+        for physical_buffer in graph_plan.intermediate_buffers.keys() {
+            let name = match graph_plan.physical_buffer_names.get(physical_buffer) {
+                Some(name) => name.clone(),
+                None => format!("render graph buffer {}", physical_buffer.0),
+            };
+            buffer_resources[physical_buffer]
+                .get_raw()
+                .buffer
+                .set_debug_name(name)?;
+        }
+
         Ok(PreparedRenderGraph {
             device_context: device_context.clone(),
             resource_context: resource_context.clone(),
@@ -129,9 +188,146 @@ impl PreparedRenderGraph {
             image_resources,
             image_view_resources,
             graph_plan,
+            swapchain_surface_info: swapchain_surface_info.clone(),
+            gpu_timer: None,
+            queues: Default::default(),
+            semaphores,
+            timer_reset_semaphores: Default::default(),
         })
     }
 
+    /// Sets the queue that nodes scheduled on `graph_queue` are submitted to. Submissions on a
+    /// queue that was not set run on the graphics queue passed to `execute_graph()`, which is also
+    /// always used for `RenderGraphQueue::DefaultGraphics`.
+    pub fn set_queue(
+        &mut self,
+        graph_queue: RenderGraphQueue,
+        queue: RafxQueue,
+    ) {
+        self.queues.insert(graph_queue, queue);
+    }
+
+    /// The command buffer submissions the graph is split into, in submission order
+    pub fn submissions(&self) -> &[RenderGraphPlanSubmission] {
+        &self.graph_plan.submissions
+    }
+
+    /// Resolves, blits and copies the graph inserted to fix conflicting resource specifications
+    pub fn conversions(&self) -> &[RenderGraphPlanConversion] {
+        self.graph_plan.conversions()
+    }
+
+    /// Writes that nothing reads, if validation was enabled on the graph builder
+    pub fn validation_warnings(&self) -> &[RenderGraphError] {
+        self.graph_plan.validation_warnings()
+    }
+
+    /// Estimated memory used by the graph's intermediate images and buffers
+    pub fn memory_stats(&self) -> RenderGraphMemoryStats {
+        self.graph_plan.memory_stats(&self.swapchain_surface_info)
+    }
+
+    /// A description of the graph and its plan for debugging tools. This is only available if
+    /// `RenderGraphBuilder::set_export_enabled()` was called before preparing the graph.
+    pub fn export(&self) -> Option<&RenderGraphExport> {
+        self.graph_plan.export()
+    }
+
+    fn submission_queue<'a>(
+        &'a self,
+        graph_queue: RenderGraphQueue,
+        graphics_queue: &'a RafxQueue,
+    ) -> &'a RafxQueue {
+        if graph_queue == RenderGraphQueue::DefaultGraphics {
+            return graphics_queue;
+        }
+
+        self.queues.get(&graph_queue).unwrap_or(graphics_queue)
+    }
+
+    // Ownership only needs to be transferred if the queues are in different queue families.
+    // Otherwise the semaphore between the submissions is enough.
+    fn resolve_queue_transition(
+        &self,
+        queue_transition: RenderGraphQueueTransition,
+        submission_queue: &RafxQueue,
+        graphics_queue: &RafxQueue,
+    ) -> RafxBarrierQueueTransition {
+        let other_queue_type = |graph_queue| {
+            let other_queue = self.submission_queue(graph_queue, graphics_queue);
+            if other_queue.queue_family_index() != submission_queue.queue_family_index() {
+                Some(other_queue.queue_type())
+            } else {
+                None
+            }
+        };
+
+        match queue_transition {
+            RenderGraphQueueTransition::None => RafxBarrierQueueTransition::None,
+            RenderGraphQueueTransition::ReleaseTo(dst_queue) => other_queue_type(dst_queue)
+                .map(RafxBarrierQueueTransition::ReleaseTo)
+                .unwrap_or(RafxBarrierQueueTransition::None),
+            RenderGraphQueueTransition::AcquireFrom(src_queue) => other_queue_type(src_queue)
+                .map(RafxBarrierQueueTransition::AcquireFrom)
+                .unwrap_or(RafxBarrierQueueTransition::None),
+        }
+    }
+
+    /// Wrap every pass in timestamp queries when the graph is executed. The returned timer
+    /// reports per-pass GPU time once the frame's fence has signaled, and must be kept alive
+    /// until then. Nodes merged into subpasses of one renderpass are reported together under
+    /// that pass. Returns None if the device does not support timestamp queries.
+    pub fn enable_gpu_timing(&mut self) -> RafxResult<Option<RenderGraphGpuTimer>> {
+        if !self.device_context.device_info().supports_timestamp_queries {
+            log::debug!("GPU timing is disabled because timestamp queries are not supported");
+            return Ok(None);
+        }
+
+        let passes = self
+            .graph_plan
+            .passes
+            .iter()
+            .map(|pass| {
+                let nodes = pass
+                    .nodes()
+                    .into_iter()
+                    .map(|node_id| RenderGraphGpuTimedNode {
+                        node_id,
+                        debug_name: self.node_debug_name(node_id),
+                    })
+                    .collect();
+                (pass.debug_name(), nodes)
+            })
+            .collect();
+
+        let gpu_timer = RenderGraphGpuTimer::new(&self.device_context, passes)?;
+
+        // Graphics submissions run after the reset because they are submitted after it on the same
+        // queue. Submissions on other queues that don't wait on another submission wait on the
+        // reset with a semaphore.
+        let timer_reset_semaphore_count = self
+            .graph_plan
+            .submissions
+            .iter()
+            .filter(|x| x.wait_for.is_empty() && x.queue != RenderGraphQueue::DefaultGraphics)
+            .count();
+        let mut cache = self
+            .resource_context
+            .render_graph_cache()
+            .inner
+            .lock()
+            .unwrap();
+        self.timer_reset_semaphores =
+            cache.allocate_semaphores(&self.device_context, timer_reset_semaphore_count)?;
+
+        self.gpu_timer = Some(gpu_timer.clone());
+        Ok(Some(gpu_timer))
+    }
+
+    pub fn gpu_timer(&self) -> Option<&RenderGraphGpuTimer> {
+        self.gpu_timer.as_ref()
+    }
+
     pub fn buffer(
         &self,
         buffer: RenderGraphBufferUsageId,
@@ -159,17 +355,36 @@ impl PreparedRenderGraph {
     fn insert_barriers(
         &self,
         command_buffer: &RafxCommandBuffer,
+        submission_queue: &RafxQueue,
+        graphics_queue: &RafxQueue,
         pass_buffer_barriers: &[PrepassBufferBarrier],
         pass_image_barriers: &[PrepassImageBarrier],
     ) -> RafxResult<()> {
         assert!(!pass_buffer_barriers.is_empty() || !pass_image_barriers.is_empty());
 
+        // If no ownership transfer is required, the release barrier already performed the
+        // transition and the acquire can be dropped
+        let is_redundant_acquire =
+            |queue_transition, resolved_queue_transition: &RafxBarrierQueueTransition| {
+                matches!(queue_transition, RenderGraphQueueTransition::AcquireFrom(_))
+                    && matches!(resolved_queue_transition, RafxBarrierQueueTransition::None)
+            };
+
         let mut buffer_barriers = Vec::with_capacity(pass_buffer_barriers.len());
         let buffers: Vec<_> = pass_buffer_barriers
             .iter()
             .map(|x| self.buffer_resources[&x.buffer].get_raw().buffer.clone())
             .collect();
         for (buffer_barrier, buffer) in pass_buffer_barriers.iter().zip(&buffers) {
+            let queue_transition = self.resolve_queue_transition(
+                buffer_barrier.queue_transition,
+                submission_queue,
+                graphics_queue,
+            );
+            if is_redundant_acquire(buffer_barrier.queue_transition, &queue_transition) {
+                continue;
+            }
+
             log::trace!(
                 "add buffer barrier for buffer {:?} state {:?} -> {:?}",
                 buffer_barrier.buffer,
@@ -181,7 +396,7 @@ impl PreparedRenderGraph {
                 buffer: buffer.as_ref(),
                 src_state: buffer_barrier.old_state,
                 dst_state: buffer_barrier.new_state,
-                queue_transition: RafxBarrierQueueTransition::None,
+                queue_transition,
             });
         }
 
@@ -191,20 +406,31 @@ impl PreparedRenderGraph {
             .map(|x| self.image_resources[&x.image].get_raw().image.clone())
             .collect();
         for (image_barrier, image) in pass_image_barriers.iter().zip(&images) {
+            let queue_transition = self.resolve_queue_transition(
+                image_barrier.queue_transition,
+                submission_queue,
+                graphics_queue,
+            );
+            if is_redundant_acquire(image_barrier.queue_transition, &queue_transition) {
+                continue;
+            }
+
             log::trace!(
-                "add image barrier for image {:?} state {:?} -> {:?}",
+                "add image barrier for image {:?} state {:?} -> {:?} (array slice {:?}, mip slice {:?})",
                 image_barrier.image,
                 image_barrier.old_state,
-                image_barrier.new_state
+                image_barrier.new_state,
+                image_barrier.array_slice,
+                image_barrier.mip_slice
             );
 
             image_barriers.push(RafxTextureBarrier {
                 texture: image,
                 src_state: image_barrier.old_state,
                 dst_state: image_barrier.new_state,
-                array_slice: None,
-                mip_slice: None,
-                queue_transition: RafxBarrierQueueTransition::None,
+                array_slice: image_barrier.array_slice,
+                mip_slice: image_barrier.mip_slice,
+                queue_transition,
             });
         }
 
@@ -216,6 +442,10 @@ impl PreparedRenderGraph {
         //     println!("{:?}", rt_barrier);
         // }
 
+        if buffer_barriers.is_empty() && image_barriers.is_empty() {
+            return Ok(());
+        }
+
         command_buffer.cmd_resource_barrier(&buffer_barriers, &image_barriers)
     }
 
@@ -265,163 +495,772 @@ impl PreparedRenderGraph {
         Ok(())
     }
 
-    pub fn execute_graph<'write>(
+    fn execute_pass(
+        &self,
+        pass_index: usize,
+        command_buffer: &DynCommandBuffer,
+        command_pool_allocator: &DynCommandPoolAllocator,
+        submission_queue: &RafxQueue,
+        graphics_queue: &RafxQueue,
+        render_graph_context: RenderGraphContext,
+    ) -> RafxResult<()> {
+        let pass = &self.graph_plan.passes[pass_index];
+
+        profiling::scope!("pass", pass.debug_name().unwrap_or("unnamed"));
+        log::trace!("Execute pass name: {:?}", pass.debug_name());
+
+        command_buffer.cmd_push_debug_group(pass.debug_name().unwrap_or("unnamed"))?;
+
+        if let Some(pre_pass_barrier) = pass.pre_pass_barrier() {
+            log::trace!(
+                "prepass barriers for pass {} {:?}",
+                pass_index,
+                pass.debug_name()
+            );
+            self.insert_barriers(
+                command_buffer,
+                submission_queue,
+                graphics_queue,
+                &pre_pass_barrier.buffer_barriers,
+                &pre_pass_barrier.image_barriers,
+            )?;
+        }
+
+        if let Some(gpu_timer) = &self.gpu_timer {
+            let (begin_query, _) = RenderGraphGpuTimer::pass_query_indices(pass_index);
+            command_buffer.cmd_write_timestamp(gpu_timer.query_pool(), begin_query)?;
+        }
+
+        match pass {
+            RenderGraphOutputPass::Renderpass(pass) => {
+                let color_images: Vec<_> = pass
+                    .color_render_targets
+                    .iter()
+                    .map(|x| self.image_resources[&x.image].get_raw().image.clone())
+                    .collect();
+
+                let resolve_images: Vec<_> = pass
+                    .color_render_targets
+                    .iter()
+                    .map(|x| {
+                        //x.map(|x| self.image_resources[&x.image].get_raw().image.clone())
+                        x.resolve_image
+                            .map(|x| self.image_resources[&x].get_raw().image.clone())
+                    })
+                    .collect();
+
+                let color_target_bindings: Vec<_> = pass
+                    .color_render_targets
+                    .iter()
+                    .enumerate()
+                    .map(
+                        |(color_image_index, color_image)| RafxColorRenderTargetBinding {
+                            texture: &color_images[color_image_index],
+                            clear_value: color_image.clear_value.clone(),
+                            load_op: color_image.load_op,
+                            store_op: color_image.store_op,
+                            array_slice: color_image.array_slice,
+                            mip_slice: color_image.mip_slice,
+                            resolve_target: resolve_images[color_image_index].as_ref(),
+                            resolve_store_op: color_image.resolve_store_op.into(),
+                            resolve_array_slice: color_image.resolve_array_slice,
+                            resolve_mip_slice: color_image.resolve_mip_slice,
+                        },
+                    )
+                    .collect();
+
+                let mut depth_stencil_image = None;
+                let depth_target_binding = pass.depth_stencil_render_target.as_ref().map(|x| {
+                    depth_stencil_image =
+                        Some(self.image_resources[&x.image].get_raw().image.clone());
+                    RafxDepthStencilRenderTargetBinding {
+                        texture: depth_stencil_image.as_ref().unwrap(),
+                        clear_value: x.clear_value.clone(),
+                        depth_load_op: x.depth_load_op,
+                        stencil_load_op: x.stencil_load_op,
+                        depth_store_op: x.depth_store_op,
+                        stencil_store_op: x.stencil_store_op,
+                        array_slice: x.array_slice,
+                        mip_slice: x.mip_slice,
+                    }
+                });
+
+                //println!("color bindings:\n{:#?}", color_target_bindings);
+                //println!("depth binding:\n{:#?}", depth_target_binding);
+
+                // Subpasses are recorded into secondary command buffers if a node in the pass
+                // depends on a render phase that will be written across multiple threads
+                let use_secondary_command_buffers = self
+                    .device_context
+                    .device_info()
+                    .supports_secondary_command_buffers
+                    && pass.subpasses.iter().any(|subpass| {
+                        self.graph_plan
+                            .render_phase_dependencies
+                            .get(&subpass.node_id)
+                            .map(|render_phases| {
+                                render_graph_context
+                                    .prepared_render_data()
+                                    .splits_render_phases(render_phases)
+                            })
+                            .unwrap_or(false)
+                    });
+
+                if use_secondary_command_buffers {
+                    command_buffer.cmd_begin_render_pass_with_contents(
+                        &color_target_bindings,
+                        depth_target_binding,
+                        &pass.subpass_defs,
+                        RafxSubpassContents::SecondaryCommandBuffers,
+                    )?;
+                } else if pass.subpass_defs.is_empty() {
+                    command_buffer
+                        .cmd_begin_render_pass(&color_target_bindings, depth_target_binding)?;
+                } else {
+                    command_buffer.cmd_begin_render_pass_with_subpasses(
+                        &color_target_bindings,
+                        depth_target_binding,
+                        &pass.subpass_defs,
+                    )?;
+                }
+
+                for (subpass_index, subpass) in pass.subpasses.iter().enumerate() {
+                    if subpass_index > 0 {
+                        command_buffer.cmd_next_subpass()?;
+                    }
+
+                    let secondary_command_buffers = if use_secondary_command_buffers {
+                        Some(RenderGraphSecondaryCommandBuffers::new(
+                            command_buffer,
+                            submission_queue,
+                            command_pool_allocator,
+                        )?)
+                    } else {
+                        None
+                    };
+
+                    let args = VisitRenderpassNodeArgs {
+                        render_target_meta: subpass.render_target_meta.clone(),
+                        graph_context: render_graph_context,
+                        command_buffer: secondary_command_buffers
+                            .as_ref()
+                            .map(|x| x.current_command_buffer())
+                            .unwrap_or_else(|| command_buffer.clone()),
+                        secondary_command_buffers: secondary_command_buffers.clone(),
+                    };
+
+                    self.visit_renderpass_node(subpass.node_id, args)?;
+
+                    if let Some(secondary_command_buffers) = secondary_command_buffers {
+                        secondary_command_buffers.execute()?;
+                    }
+                }
+
+                command_buffer.cmd_end_render_pass()?;
+            }
+            RenderGraphOutputPass::Compute(pass) => {
+                let args = VisitComputeNodeArgs {
+                    graph_context: render_graph_context,
+                    command_buffer: command_buffer.clone(),
+                };
+
+                self.visit_compute_node(pass.node, args)?;
+            }
+        }
+
+        if let Some(gpu_timer) = &self.gpu_timer {
+            let (_, end_query) = RenderGraphGpuTimer::pass_query_indices(pass_index);
+            command_buffer.cmd_write_timestamp(gpu_timer.query_pool(), end_query)?;
+        }
+
+        if let Some(post_pass_barrier) = pass.post_pass_barrier() {
+            log::trace!(
+                "postpass barriers for pass {} {:?}",
+                pass_index,
+                pass.debug_name()
+            );
+            self.insert_barriers(
+                command_buffer,
+                submission_queue,
+                graphics_queue,
+                &post_pass_barrier.buffer_barriers,
+                &post_pass_barrier.image_barriers,
+            )?;
+        }
+
+        command_buffer.cmd_pop_debug_group()?;
+
+        Ok(())
+    }
+
+    // Records all passes of a submission into a new command buffer
+    fn record_submission<'write>(
         &'write self,
-        prepared_render_data: PreparedRenderData<'write>,
-        queue: &RafxQueue,
-    ) -> RafxResult<Vec<DynCommandBuffer>> {
-        profiling::scope!("Execute Graph");
-        //
-        // Start a command writer. For now just do a single primary writer, later we can multithread this.
-        //
-        let mut command_writer = self
-            .resource_context
-            .create_dyn_command_pool_allocator()
-            .allocate_dyn_pool(queue, &RafxCommandPoolDef { transient: true }, 0)?;
+        submission_index: usize,
+        command_pool: &mut DynCommandPool,
+        command_pool_allocator: &DynCommandPoolAllocator,
+        submission_queue: &RafxQueue,
+        graphics_queue: &RafxQueue,
+        render_graph_context: RenderGraphContext<'_, 'write>,
+    ) -> RafxResult<DynCommandBuffer> {
+        let submissions = &self.graph_plan.submissions;
+        let submission = &submissions[submission_index];
 
-        let command_buffer = command_writer.allocate_dyn_command_buffer(&RafxCommandBufferDef {
+        let command_buffer = command_pool.allocate_dyn_command_buffer(&RafxCommandBufferDef {
             is_secondary: false,
         })?;
 
         command_buffer.begin()?;
 
+        // on_begin_execute_graph is called at the start of the first graphics submission. The
+        // final submission is always on the graphics queue.
+        let first_graphics_submission = submissions
+            .iter()
+            .position(|x| x.queue == RenderGraphQueue::DefaultGraphics)
+            .unwrap_or(submissions.len() - 1);
+        if submission_index == first_graphics_submission {
+            let args = OnBeginExecuteGraphArgs {
+                graph_context: render_graph_context,
+                command_buffer: command_buffer.clone(),
+            };
+
+            let mut write_context =
+                RenderJobBeginExecuteGraphContext::from_on_begin_execute_graph_args(&args);
+            args.graph_context
+                .prepared_render_data()
+                .on_begin_execute_graph(&mut write_context)?;
+        }
+
+        for &pass_index in &submission.passes {
+            self.execute_pass(
+                pass_index,
+                &command_buffer,
+                command_pool_allocator,
+                submission_queue,
+                graphics_queue,
+                render_graph_context,
+            )?;
+        }
+
+        command_buffer.end()?;
+        Ok(command_buffer)
+    }
+
+    /// Records and executes the graph. Submissions on queues other than the graphics queue (and
+    /// graphics submissions that must wait on them) are submitted here. The returned command
+    /// buffers are the last graphics submission, which the caller submits to `queue` with the
+    /// frame's semaphores and fence. That submission must also wait on the semaphores returned
+    /// with each command buffer, which are signaled by the submissions it depends on.
+    pub fn execute_graph<'write>(
+        &'write self,
+        prepared_render_data: PreparedRenderData<'write>,
+        queue: &RafxQueue,
+    ) -> RafxResult<Vec<(DynCommandBuffer, Vec<Arc<RafxSemaphore>>)>> {
+        profiling::scope!("Execute Graph");
+        let command_pool_allocator = self.resource_context.create_dyn_command_pool_allocator();
+
         let render_graph_context = RenderGraphContext {
             prepared_render_graph: &self,
             prepared_render_data: &prepared_render_data,
         };
 
-        let args = OnBeginExecuteGraphArgs {
-            graph_context: render_graph_context,
-            command_buffer: command_buffer.clone(),
-        };
+        //
+        // Assign a semaphore to every dependency between submissions
+        //
+        let submissions = &self.graph_plan.submissions;
+        let mut wait_semaphores: Vec<Vec<&Arc<RafxSemaphore>>> = vec![vec![]; submissions.len()];
+        let mut signal_semaphores: Vec<Vec<&RafxSemaphore>> = vec![vec![]; submissions.len()];
+        let mut semaphores = self.semaphores.iter();
+        for (submission_index, submission) in submissions.iter().enumerate() {
+            for &wait_for in &submission.wait_for {
+                let semaphore = semaphores.next().unwrap();
+                wait_semaphores[submission_index].push(semaphore);
+                signal_semaphores[wait_for].push(&**semaphore);
+            }
+        }
 
-        let mut write_context =
-            RenderJobBeginExecuteGraphContext::from_on_begin_execute_graph_args(&args);
-        args.graph_context
-            .prepared_render_data()
-            .on_begin_execute_graph(&mut write_context)?;
+        //
+        // The GPU timer's queries are reset in a graphics submission of their own, ahead of every
+        // submission that writes timestamps
+        //
+        if let Some(gpu_timer) = &self.gpu_timer {
+            let mut command_pool = command_pool_allocator.allocate_dyn_pool(
+                queue,
+                &RafxCommandPoolDef { transient: true },
+                0,
+            )?;
+            let command_buffer =
+                command_pool.allocate_dyn_command_buffer(&RafxCommandBufferDef {
+                    is_secondary: false,
+                })?;
+            command_buffer.begin()?;
+            gpu_timer.reset_queries(&command_buffer)?;
+            command_buffer.end()?;
+
+            let mut timer_reset_semaphores = self.timer_reset_semaphores.iter();
+            for (submission_index, submission) in submissions.iter().enumerate() {
+                if submission.wait_for.is_empty()
+                    && submission.queue != RenderGraphQueue::DefaultGraphics
+                {
+                    wait_semaphores[submission_index].push(timer_reset_semaphores.next().unwrap());
+                }
+            }
+
+            let signal_semaphores: Vec<&RafxSemaphore> =
+                self.timer_reset_semaphores.iter().map(|x| &**x).collect();
+            queue.submit(&[&*command_buffer], &[], &signal_semaphores, None)?;
+        }
+
+        let (_, other_submissions) = submissions.split_last().unwrap();
+        let final_submission_index = other_submissions.len();
+
+        for (submission_index, submission) in other_submissions.iter().enumerate() {
+            profiling::scope!("submission");
+            let submission_queue = self.submission_queue(submission.queue, queue);
+            let mut command_pool = command_pool_allocator.allocate_dyn_pool(
+                submission_queue,
+                &RafxCommandPoolDef { transient: true },
+                0,
+            )?;
+
+            let command_buffer = self.record_submission(
+                submission_index,
+                &mut command_pool,
+                &command_pool_allocator,
+                submission_queue,
+                queue,
+                render_graph_context,
+            )?;
+
+            let submission_wait_semaphores: Vec<&RafxSemaphore> = wait_semaphores[submission_index]
+                .iter()
+                .map(|x| &***x)
+                .collect();
+
+            submission_queue.submit(
+                &[&*command_buffer],
+                &submission_wait_semaphores,
+                &signal_semaphores[submission_index],
+                None,
+            )?;
+        }
 
         //
-        // Iterate through all passes
+        // The final submission is always on the graphics queue and is returned to the caller along
+        // with the semaphores it must wait on
         //
-        for (pass_index, pass) in self.graph_plan.passes.iter().enumerate() {
-            //TODO output pass is?
-            //TODO: add_compute_node/add_render_node?
-
-            profiling::scope!("pass", pass.debug_name().unwrap_or("unnamed"));
-            log::trace!("Execute pass name: {:?}", pass.debug_name());
-
-            let node_id = pass.node();
-
-            if let Some(pre_pass_barrier) = pass.pre_pass_barrier() {
-                log::trace!(
-                    "prepass barriers for pass {} {:?}",
-                    pass_index,
-                    pass.debug_name()
-                );
-                self.insert_barriers(
-                    &command_buffer,
-                    &pre_pass_barrier.buffer_barriers,
-                    &pre_pass_barrier.image_barriers,
-                )?;
+        let mut command_pool = command_pool_allocator.allocate_dyn_pool(
+            queue,
+            &RafxCommandPoolDef { transient: true },
+            0,
+        )?;
+
+        let command_buffer = self.record_submission(
+            final_submission_index,
+            &mut command_pool,
+            &command_pool_allocator,
+            queue,
+            queue,
+            render_graph_context,
+        )?;
+
+        let final_wait_semaphores = wait_semaphores[final_submission_index]
+            .iter()
+            .map(|x| (*x).clone())
+            .collect();
+
+        Ok(vec![(command_buffer, final_wait_semaphores)])
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    )
+))]
+mod tests {
+    use super::*;
+    use crate::graph::graph_plan::tests::*;
+    use crate::render_features::{
+        RenderFeatureDebugConstants, RenderFeatureFlagMask, RenderFeatureIndex, RenderFeatureMask,
+        RenderFeatureSubmitNode, RenderFeatureWriteJob, RenderJobCommandBufferContext,
+        RenderJobWriteContext, RenderJobWriteThreadPool, RenderPhase, RenderPhaseIndex,
+        RenderPhaseMask, RenderView, RenderViewDepthRange, SubmitNodeBlocks, SubmitNodeId,
+        ViewFrameIndex, ViewPhase, ViewPhaseSubmitNodeBlock,
+    };
+    use crate::visibility::VisibilityRegion;
+    use crate::RenderResources;
+    use rafx_api::empty::RafxCommandEmpty;
+    use rafx_api::{RafxQueueType, RafxSubpassContents};
+
+    fn prepare_graph(
+        test_device: &TestDevice,
+        graph: RenderGraphBuilder,
+    ) -> PreparedRenderGraph {
+        PreparedRenderGraph::new(
+            &test_device.device_context(),
+            &test_device.resource_context(),
+            graph,
+            &SwapchainSurfaceInfo {
+                extents: RafxExtents2D {
+                    width: TEST_EXTENTS.width,
+                    height: TEST_EXTENTS.height,
+                },
+                format: TEST_FORMAT,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_execute_compute_to_graphics() {
+        let test_device = test_device();
+        let device_context = test_device.device_context();
+        let resource_context = test_device.resource_context();
+        let graphics_queue = device_context
+            .create_queue(RafxQueueType::Graphics)
+            .unwrap();
+        let compute_queue = device_context.create_queue(RafxQueueType::Compute).unwrap();
+
+        let mut prepared_graph =
+            prepare_graph(&test_device, compute_to_graphics_graph(&test_device));
+        prepared_graph.set_queue(RenderGraphQueue::AsyncCompute, compute_queue);
+
+        // One semaphore signals the end of the compute submission to the graphics submission
+        assert_eq!(prepared_graph.semaphores.len(), 1);
+
+        let submit_node_blocks = SubmitNodeBlocks::default();
+        let render_resources = RenderResources::default();
+        let write_context = RenderJobWriteContext::new(resource_context, &render_resources);
+        let prepared_render_data =
+            PreparedRenderData::new(&submit_node_blocks, Vec::default(), write_context, None);
+
+        // The compute submission is submitted by the graph. The graphics submission is returned
+        // with the semaphore it waits on, rather than waiting in a submission of its own.
+        let submissions = prepared_graph
+            .execute_graph(prepared_render_data, &graphics_queue)
+            .unwrap();
+        assert_eq!(submissions.len(), 1);
+        let (command_buffer, wait_semaphores) = &submissions[0];
+        assert_eq!(wait_semaphores.len(), 1);
+        assert!(Arc::ptr_eq(
+            &wait_semaphores[0],
+            &prepared_graph.semaphores[0]
+        ));
+
+        let wait_semaphores: Vec<_> = wait_semaphores.iter().map(|x| &**x).collect();
+        graphics_queue
+            .submit(&[&**command_buffer], &wait_semaphores, &[], None)
+            .unwrap();
+        graphics_queue.wait_for_queue_idle().unwrap();
+    }
+
+    #[test]
+    fn test_gpu_timing_reset_before_other_queues() {
+        let test_device = test_device();
+        let device_context = test_device.device_context();
+        let resource_context = test_device.resource_context();
+        let graphics_queue = device_context
+            .create_queue(RafxQueueType::Graphics)
+            .unwrap();
+        let compute_queue = device_context.create_queue(RafxQueueType::Compute).unwrap();
+
+        let mut prepared_graph =
+            prepare_graph(&test_device, compute_to_graphics_graph(&test_device));
+        prepared_graph.set_queue(RenderGraphQueue::AsyncCompute, compute_queue);
+        let gpu_timer = prepared_graph.enable_gpu_timing().unwrap().unwrap();
+
+        // The compute submission doesn't wait on any other submission, so it waits on the query
+        // reset instead. The graphics submission runs after the reset on the same queue.
+        assert_eq!(prepared_graph.timer_reset_semaphores.len(), 1);
+
+        let submit_node_blocks = SubmitNodeBlocks::default();
+        let render_resources = RenderResources::default();
+        let write_context = RenderJobWriteContext::new(resource_context, &render_resources);
+        let prepared_render_data =
+            PreparedRenderData::new(&submit_node_blocks, Vec::default(), write_context, None);
+
+        let submissions = prepared_graph
+            .execute_graph(prepared_render_data, &graphics_queue)
+            .unwrap();
+        let (command_buffer, wait_semaphores) = &submissions[0];
+        let wait_semaphores: Vec<_> = wait_semaphores.iter().map(|x| &**x).collect();
+        graphics_queue
+            .submit(&[&**command_buffer], &wait_semaphores, &[], None)
+            .unwrap();
+        graphics_queue.wait_for_queue_idle().unwrap();
+
+        let timings = gpu_timer.read_timings().unwrap().unwrap();
+        assert_eq!(timings.passes.len(), prepared_graph.graph_plan.passes.len());
+    }
+
+    // A render phase with a fixed index, so that the test doesn't need a render registry
+    struct TestRenderPhase;
+
+    impl RenderPhase for TestRenderPhase {
+        fn set_render_phase_index(_index: RenderPhaseIndex) {}
+
+        fn render_phase_index() -> RenderPhaseIndex {
+            0
+        }
+
+        fn sort_submit_nodes(_submit_nodes: &mut Vec<RenderFeatureSubmitNode>) {}
+
+        fn render_phase_debug_name() -> &'static str {
+            "TestRenderPhase"
+        }
+    }
+
+    static TEST_FEATURE_DEBUG_CONSTANTS: RenderFeatureDebugConstants =
+        RenderFeatureDebugConstants {
+            feature_name: "TestRenderFeature",
+            begin_per_frame_extract: "begin_per_frame_extract",
+            extract_render_object_instance: "extract_render_object_instance",
+            extract_render_object_instance_per_view: "extract_render_object_instance_per_view",
+            end_per_view_extract: "end_per_view_extract",
+            end_per_frame_extract: "end_per_frame_extract",
+            begin_per_frame_prepare: "begin_per_frame_prepare",
+            prepare_render_object_instance: "prepare_render_object_instance",
+            prepare_render_object_instance_per_view: "prepare_render_object_instance_per_view",
+            end_per_view_prepare: "end_per_view_prepare",
+            end_per_frame_prepare: "end_per_frame_prepare",
+            on_begin_execute_graph: "on_begin_execute_graph",
+            render_submit_node: "render_submit_node",
+            apply_setup: "apply_setup",
+            revert_setup: "revert_setup",
+        };
+
+    // Draws submit node N with N + 1 vertices, so the order of the submit nodes can be read back
+    // from the submitted commands
+    struct TestWriteJob;
+
+    impl<'write> RenderFeatureWriteJob<'write> for TestWriteJob {
+        fn view_frame_index(
+            &self,
+            _view: &RenderView,
+        ) -> ViewFrameIndex {
+            0
+        }
+
+        fn render_submit_node(
+            &self,
+            write_context: &mut RenderJobCommandBufferContext,
+            _view_frame_index: ViewFrameIndex,
+            _render_phase_index: RenderPhaseIndex,
+            submit_node_id: SubmitNodeId,
+        ) -> RafxResult<()> {
+            write_context.command_buffer.cmd_draw(submit_node_id + 1, 0)
+        }
+
+        fn feature_debug_constants(&self) -> &'static RenderFeatureDebugConstants {
+            &TEST_FEATURE_DEBUG_CONSTANTS
+        }
+
+        fn feature_index(&self) -> RenderFeatureIndex {
+            0
+        }
+    }
+
+    // Writes each chunk on a new thread, last chunk first
+    struct TestWriteThreadPool {
+        chunk_size: Option<usize>,
+    }
+
+    impl RenderJobWriteThreadPool for TestWriteThreadPool {
+        fn write_chunk_size(
+            &self,
+            _view_phase: &ViewPhase,
+            _num_submit_nodes: usize,
+        ) -> Option<usize> {
+            self.chunk_size
+        }
+
+        fn run_write_chunks(
+            &self,
+            num_chunks: usize,
+            write_chunk: &(dyn Fn(usize) + Sync),
+        ) {
+            for chunk_index in (0..num_chunks).rev() {
+                std::thread::scope(|scope| {
+                    scope.spawn(|| write_chunk(chunk_index));
+                });
             }
+        }
+    }
 
-            match pass {
-                RenderGraphOutputPass::Renderpass(pass) => {
-                    let color_images: Vec<_> = pass
-                        .color_render_targets
-                        .iter()
-                        .map(|x| self.image_resources[&x.image].get_raw().image.clone())
-                        .collect();
-
-                    let resolve_images: Vec<_> = pass
-                        .color_render_targets
-                        .iter()
-                        .map(|x| {
-                            //x.map(|x| self.image_resources[&x.image].get_raw().image.clone())
-                            x.resolve_image
-                                .map(|x| self.image_resources[&x].get_raw().image.clone())
-                        })
-                        .collect();
-
-                    let color_target_bindings: Vec<_> = pass
-                        .color_render_targets
-                        .iter()
-                        .enumerate()
-                        .map(
-                            |(color_image_index, color_image)| RafxColorRenderTargetBinding {
-                                texture: &color_images[color_image_index],
-                                clear_value: color_image.clear_value.clone(),
-                                load_op: color_image.load_op,
-                                store_op: color_image.store_op,
-                                array_slice: color_image.array_slice,
-                                mip_slice: color_image.mip_slice,
-                                resolve_target: resolve_images[color_image_index].as_ref(),
-                                resolve_store_op: color_image.resolve_store_op.into(),
-                                resolve_array_slice: color_image.resolve_array_slice,
-                                resolve_mip_slice: color_image.resolve_mip_slice,
-                            },
-                        )
-                        .collect();
-
-                    let mut depth_stencil_image = None;
-                    let depth_target_binding = pass.depth_stencil_render_target.as_ref().map(|x| {
-                        depth_stencil_image =
-                            Some(self.image_resources[&x.image].get_raw().image.clone());
-                        RafxDepthStencilRenderTargetBinding {
-                            texture: depth_stencil_image.as_ref().unwrap(),
-                            clear_value: x.clear_value.clone(),
-                            depth_load_op: x.depth_load_op,
-                            stencil_load_op: x.stencil_load_op,
-                            depth_store_op: x.depth_store_op,
-                            stencil_store_op: x.stencil_store_op,
-                            array_slice: x.array_slice,
-                            mip_slice: x.mip_slice,
-                        }
-                    });
+    const TEST_SUBMIT_NODE_COUNT: u32 = 5;
+    const TEST_VIEWPORT_WIDTH: f32 = 32.0;
 
-                    //println!("color bindings:\n{:#?}", color_target_bindings);
-                    //println!("depth binding:\n{:#?}", depth_target_binding);
+    // Draws TEST_SUBMIT_NODE_COUNT submit nodes of TestRenderPhase in a renderpass and returns the
+    // commands submitted to the graphics queue
+    fn execute_render_phase_graph(
+        chunk_size: Option<usize>,
+        add_render_phase_dependency: bool,
+    ) -> Vec<RafxCommandEmpty> {
+        let test_device = test_device();
+        let device_context = test_device.device_context();
+        let resource_context = test_device.resource_context();
+        let graphics_queue = device_context
+            .create_queue(RafxQueueType::Graphics)
+            .unwrap();
 
-                    command_buffer
-                        .cmd_begin_render_pass(&color_target_bindings, depth_target_binding)?;
+        let visibility_region = VisibilityRegion::new();
+        let view = RenderView::new(
+            visibility_region.register_view_frustum(),
+            0,
+            glam::Vec3::ZERO,
+            glam::Mat4::IDENTITY,
+            glam::Mat4::IDENTITY,
+            (TEST_EXTENTS.width, TEST_EXTENTS.height),
+            RenderViewDepthRange::new(0.01, 100.0),
+            RenderPhaseMask::empty(),
+            RenderFeatureMask::empty(),
+            RenderFeatureFlagMask::empty(),
+            "test view".to_string(),
+        );
 
-                    let args = VisitRenderpassNodeArgs {
-                        render_target_meta: pass.render_target_meta.clone(),
-                        graph_context: render_graph_context,
-                        command_buffer: command_buffer.clone(),
-                    };
+        let mut graph = RenderGraphBuilder::default();
+        let node = graph.add_node("draw", RenderGraphQueue::DefaultGraphics);
+        let color =
+            graph.create_color_attachment(node, 0, None, color_constraint(), Default::default());
+        test_device.set_output_image(&mut graph, color);
+        if add_render_phase_dependency {
+            graph.add_render_phase_dependency::<TestRenderPhase>(node);
+        }
 
-                    self.visit_renderpass_node(node_id, args)?;
+        let callback_view = view.clone();
+        graph.set_renderpass_callback(node, move |args| {
+            let mut write_context =
+                RenderJobCommandBufferContext::from_graph_visit_render_pass_args(&args);
+            write_context.cmd_set_viewport(0.0, 0.0, TEST_VIEWPORT_WIDTH, 32.0, 0.0, 1.0)?;
+            args.graph_context
+                .prepared_render_data()
+                .write_view_phase::<TestRenderPhase>(&callback_view, &mut write_context)
+        });
 
-                    command_buffer.cmd_end_render_pass()?;
-                }
-                RenderGraphOutputPass::Compute(_pass) => {
-                    let args = VisitComputeNodeArgs {
-                        graph_context: render_graph_context,
-                        command_buffer: command_buffer.clone(),
-                    };
+        let prepared_graph = prepare_graph(&test_device, graph);
+
+        let view_phase = ViewPhase {
+            view_index: view.view_index(),
+            phase_index: TestRenderPhase::render_phase_index(),
+        };
+        let mut submit_node_block =
+            ViewPhaseSubmitNodeBlock::new(view_phase, TEST_SUBMIT_NODE_COUNT as usize);
+        for submit_node_id in 0..TEST_SUBMIT_NODE_COUNT {
+            submit_node_block.push_submit_node(RenderFeatureSubmitNode::new(
+                0,
+                submit_node_id,
+                0,
+                0.0,
+            ));
+        }
+        let mut submit_node_blocks = SubmitNodeBlocks::default();
+        submit_node_blocks.insert(view_phase, submit_node_block);
+
+        let write_thread_pool = TestWriteThreadPool { chunk_size };
+        let render_resources = RenderResources::default();
+        let write_context = RenderJobWriteContext::new(resource_context, &render_resources);
+        let write_job: Arc<dyn RenderFeatureWriteJob> = Arc::new(TestWriteJob);
+        let prepared_render_data = PreparedRenderData::new(
+            &submit_node_blocks,
+            vec![Some(write_job)],
+            write_context,
+            Some(&write_thread_pool),
+        );
+
+        let submissions = prepared_graph
+            .execute_graph(prepared_render_data, &graphics_queue)
+            .unwrap();
+        for (command_buffer, wait_semaphores) in &submissions {
+            let wait_semaphores: Vec<_> = wait_semaphores.iter().map(|x| &**x).collect();
+            graphics_queue
+                .submit(&[&**command_buffer], &wait_semaphores, &[], None)
+                .unwrap();
+        }
+
+        graphics_queue.empty_queue().unwrap().submitted_commands()
+    }
+
+    fn renderpass_contents(commands: &[RafxCommandEmpty]) -> RafxSubpassContents {
+        commands
+            .iter()
+            .find_map(|command| match command {
+                RafxCommandEmpty::BeginRenderPass { contents, .. } => Some(*contents),
+                _ => None,
+            })
+            .unwrap()
+    }
 
-                    self.visit_compute_node(node_id, args)?;
+    fn draw_vertex_counts(commands: &[RafxCommandEmpty]) -> Vec<u32> {
+        commands
+            .iter()
+            .filter_map(|command| match command {
+                RafxCommandEmpty::Draw { vertex_count, .. } => Some(*vertex_count),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_write_view_phase_across_thread_pool() {
+        // 5 submit nodes in chunks of 2 are written in 3 secondary command buffers
+        let commands = execute_render_phase_graph(Some(2), true);
+        assert_eq!(
+            renderpass_contents(&commands),
+            RafxSubpassContents::SecondaryCommandBuffers
+        );
+
+        let secondary_command_buffers = commands
+            .iter()
+            .find_map(|command| match command {
+                RafxCommandEmpty::ExecuteSecondaryCommandBuffers { command_buffers } => {
+                    Some(command_buffers)
                 }
-            }
+                _ => None,
+            })
+            .unwrap();
+
+        // The command buffer the callback started with, the 3 chunks and the command buffer that
+        // follows them
+        assert_eq!(secondary_command_buffers.len(), 5);
+        let chunk_draws: Vec<_> = secondary_command_buffers
+            .iter()
+            .map(|command_buffer| draw_vertex_counts(command_buffer))
+            .collect();
+        assert_eq!(
+            chunk_draws,
+            vec![vec![], vec![1, 2], vec![3, 4], vec![5], vec![]]
+        );
 
-            if let Some(post_pass_barrier) = pass.post_pass_barrier() {
-                log::trace!(
-                    "postpass barriers for pass {} {:?}",
-                    pass_index,
-                    pass.debug_name()
-                );
-                self.insert_barriers(
-                    &command_buffer,
-                    &post_pass_barrier.buffer_barriers,
-                    &post_pass_barrier.image_barriers,
-                )?;
+        // The viewport set by the callback is set in every command buffer, including the chunks
+        // written on other threads
+        for command_buffer in secondary_command_buffers {
+            match command_buffer.first() {
+                Some(RafxCommandEmpty::SetViewport { width, .. }) => {
+                    assert_eq!(*width, TEST_VIEWPORT_WIDTH)
+                }
+                _ => panic!("secondary command buffer doesn't start with the viewport"),
             }
         }
+    }
 
-        command_buffer.end()?;
+    #[test]
+    fn test_write_view_phase_inline_without_split() {
+        // The thread pool doesn't split the submit nodes
+        let commands = execute_render_phase_graph(Some(TEST_SUBMIT_NODE_COUNT as usize), true);
+        assert_eq!(renderpass_contents(&commands), RafxSubpassContents::Inline);
+        assert_eq!(draw_vertex_counts(&commands), vec![1, 2, 3, 4, 5]);
 
-        Ok(vec![command_buffer])
+        // The node doesn't declare that it writes the render phase
+        let commands = execute_render_phase_graph(Some(2), false);
+        assert_eq!(renderpass_contents(&commands), RafxSubpassContents::Inline);
+        assert_eq!(draw_vertex_counts(&commands), vec![1, 2, 3, 4, 5]);
     }
 }
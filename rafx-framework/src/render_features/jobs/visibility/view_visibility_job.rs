@@ -12,10 +12,12 @@ pub type VisibleRenderObjects = Pooled<VisibilityVecs>;
 /// and a `RenderObjectId`. If a `VisibilityObject` has multiple `RenderObject`s associated with it,
 /// the results will be returned as 0 or more `RenderViewObject`s. The visible `RenderObject`s will
 /// only contain `RenderObject`s associated with a `RenderFeature` included by the `RenderView`'s
-/// `RenderFeatureMask`.
+/// `RenderFeatureMask`. The visible volumes are returned as the `ObjectId` made from each volume's ID,
+/// including the volumes seen through portals.
 pub struct RenderViewVisibilityQuery {
     pub view: RenderView,
     pub per_view_render_objects: VisibleRenderObjects,
+    pub per_view_volumes: Vec<ObjectId>,
 }
 
 impl RenderViewVisibilityQuery {
@@ -102,10 +104,17 @@ impl<'a> ViewVisibilityJob<'a> {
             feature.sort_unstable_by_key(|render_object| render_object.render_object_id);
         }
 
+        let per_view_volumes = visibility_query
+            .volumes
+            .iter()
+            .map(|visibility_result| ObjectId::new(visibility_result.id))
+            .collect();
+
         let per_view_render_objects = render_objects;
         RenderViewVisibilityQuery {
             view: self.view().clone(),
             per_view_render_objects,
+            per_view_volumes,
         }
     }
 
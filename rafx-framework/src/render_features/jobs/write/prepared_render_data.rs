@@ -1,5 +1,7 @@
+use crate::graph::RenderGraphSecondaryCommandBuffers;
 use crate::render_features::render_features_prelude::*;
-use fnv::FnvHashMap;
+use crate::DynCommandBuffer;
+use fnv::{FnvHashMap, FnvHashSet};
 use rafx_api::RafxResult;
 use std::sync::Arc;
 
@@ -15,11 +17,13 @@ pub type RenderViewSubmitNodeCount = FnvHashMap<RenderViewIndex, Vec<usize>>;
 pub type SubmitNodeBlocks = FnvHashMap<ViewPhase, ViewPhaseSubmitNodeBlock>;
 
 /// The sorted `SubmitNode`s for each `RenderView` and `RenderPhase` with all relevant `RenderFeature`'s
-/// `RenderFeatureWriteJob`s.
+/// `RenderFeatureWriteJob`s. If a `RenderJobWriteThreadPool` is provided, `write_view_phase` may
+/// record the `SubmitNode`s on multiple threads.
 pub struct PreparedRenderData<'write> {
     submit_node_blocks: &'write SubmitNodeBlocks,
     write_jobs: Vec<Option<Arc<dyn RenderFeatureWriteJob<'write> + 'write>>>,
     _write_context: RenderJobWriteContext<'write>,
+    write_thread_pool: Option<&'write dyn RenderJobWriteThreadPool>,
 }
 
 impl<'write> PreparedRenderData<'write> {
@@ -27,14 +31,56 @@ impl<'write> PreparedRenderData<'write> {
         submit_node_blocks: &'write SubmitNodeBlocks,
         write_jobs: Vec<Option<Arc<dyn RenderFeatureWriteJob<'write> + 'write>>>,
         _write_context: RenderJobWriteContext<'write>,
+        write_thread_pool: Option<&'write dyn RenderJobWriteThreadPool>,
     ) -> Self {
         Self {
             submit_node_blocks,
             write_jobs,
             _write_context,
+            write_thread_pool,
         }
     }
 
+    pub fn write_thread_pool(&self) -> Option<&'write dyn RenderJobWriteThreadPool> {
+        self.write_thread_pool
+    }
+
+    // The number of submit nodes per chunk if the `RenderJobWriteThreadPool` splits the
+    // `ViewPhase` into more than one chunk
+    fn split_chunk_size(
+        &self,
+        view_phase: &ViewPhase,
+        num_submit_nodes: usize,
+    ) -> Option<usize> {
+        let chunk_size = self
+            .write_thread_pool?
+            .write_chunk_size(view_phase, num_submit_nodes)?
+            .max(1);
+
+        if chunk_size < num_submit_nodes {
+            Some(chunk_size)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if `write_view_phase` would split the `SubmitNode`s of any `RenderView` in one
+    /// of the `RenderPhase`s across threads. The render graph only records a renderpass into
+    /// secondary command buffers if this is true for the phases its nodes depend on.
+    pub fn splits_render_phases(
+        &self,
+        render_phases: &FnvHashSet<RenderPhaseIndex>,
+    ) -> bool {
+        self.submit_node_blocks
+            .iter()
+            .any(|(view_phase, submit_node_block)| {
+                render_phases.contains(&view_phase.phase_index)
+                    && self
+                        .split_chunk_size(view_phase, submit_node_block.len())
+                        .is_some()
+            })
+    }
+
     pub fn on_begin_execute_graph(
         &self,
         write_context: &mut RenderJobBeginExecuteGraphContext,
@@ -48,6 +94,10 @@ impl<'write> PreparedRenderData<'write> {
         Ok(())
     }
 
+    /// Writes the `SubmitNode`s of the `RenderView` and `RenderPhase`. If the render graph is
+    /// recording the subpass into secondary command buffers and the `RenderJobWriteThreadPool`
+    /// returns a chunk size that splits the `SubmitNode`s, they are recorded on multiple threads and
+    /// `write_context.command_buffer` is replaced with a new command buffer that follows them.
     pub fn write_view_phase<PhaseT: RenderPhase>(
         &self,
         view: &RenderView,
@@ -73,6 +123,92 @@ impl<'write> PreparedRenderData<'write> {
                 &[]
             };
 
+        if let (Some(write_thread_pool), Some(secondary_command_buffers)) = (
+            self.write_thread_pool,
+            write_context.secondary_command_buffers.clone(),
+        ) {
+            if let Some(chunk_size) = self.split_chunk_size(&view_phase, submit_nodes.len()) {
+                let chunks = submit_nodes.chunks(chunk_size).collect();
+                return self.write_submit_nodes_parallel(
+                    view,
+                    render_phase_index,
+                    chunks,
+                    write_thread_pool,
+                    &secondary_command_buffers,
+                    write_context,
+                );
+            }
+        }
+
+        self.write_submit_nodes(view, render_phase_index, submit_nodes, write_context)
+    }
+
+    fn write_submit_nodes_parallel(
+        &self,
+        view: &RenderView,
+        render_phase_index: RenderPhaseIndex,
+        chunks: Vec<&[RenderFeatureSubmitNode]>,
+        write_thread_pool: &dyn RenderJobWriteThreadPool,
+        secondary_command_buffers: &RenderGraphSecondaryCommandBuffers,
+        write_context: &mut RenderJobCommandBufferContext,
+    ) -> RafxResult<()> {
+        let results: Vec<Mutex<Option<RafxResult<DynCommandBuffer>>>> =
+            chunks.iter().map(|_| Mutex::new(None)).collect();
+
+        let resource_context = write_context.resource_context.clone();
+        let render_target_meta = write_context.render_target_meta.clone();
+
+        let write_chunk = |chunk_index: usize| {
+            profiling::scope!("write chunk");
+            let result = (|| -> RafxResult<DynCommandBuffer> {
+                // The pool is dropped once the chunk is recorded, but its command buffers remain
+                // valid until the frame is no longer in flight
+                let (_command_pool, command_buffer) =
+                    secondary_command_buffers.allocate_command_buffer()?;
+
+                let mut chunk_write_context = RenderJobCommandBufferContext::new(
+                    resource_context.clone(),
+                    command_buffer.clone(),
+                    render_target_meta.clone(),
+                );
+
+                self.write_submit_nodes(
+                    view,
+                    render_phase_index,
+                    chunks[chunk_index],
+                    &mut chunk_write_context,
+                )?;
+
+                command_buffer.end()?;
+                Ok(command_buffer)
+            })();
+
+            *results[chunk_index].lock() = Some(result);
+        };
+
+        write_thread_pool.run_write_chunks(chunks.len(), &write_chunk);
+
+        // Keep the command buffers in submit node order
+        let mut command_buffers = Vec::with_capacity(results.len());
+        for result in results {
+            let result = result
+                .into_inner()
+                .ok_or("RenderJobWriteThreadPool::run_write_chunks did not write every chunk")?;
+            command_buffers.push(result?);
+        }
+
+        write_context.command_buffer =
+            secondary_command_buffers.append_command_buffers(command_buffers)?;
+        Ok(())
+    }
+
+    fn write_submit_nodes(
+        &self,
+        view: &RenderView,
+        render_phase_index: RenderPhaseIndex,
+        submit_nodes: &[RenderFeatureSubmitNode],
+        write_context: &mut RenderJobCommandBufferContext,
+    ) -> RafxResult<()> {
         let mut previous_node_feature_index: i32 = -1;
         let mut previous_view_frame_index: Option<ViewFrameIndex> = None;
 
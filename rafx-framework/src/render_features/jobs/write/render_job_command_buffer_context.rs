@@ -1,12 +1,16 @@
-use crate::graph::VisitRenderpassNodeArgs;
+use crate::graph::{RenderGraphSecondaryCommandBuffers, VisitRenderpassNodeArgs};
 use crate::{DynCommandBuffer, GraphicsPipelineRenderTargetMeta, ResourceContext};
-use rafx_api::RafxDeviceContext;
+use rafx_api::{RafxDeviceContext, RafxResult};
 
 pub struct RenderJobCommandBufferContext {
     pub device_context: RafxDeviceContext,
     pub resource_context: ResourceContext,
     pub command_buffer: DynCommandBuffer,
     pub render_target_meta: GraphicsPipelineRenderTargetMeta,
+    /// Set if the subpass is recorded into secondary command buffers. `write_view_phase` may then
+    /// record submit nodes on other threads and replace `command_buffer`. A copy of the command
+    /// buffer taken earlier stays valid, but its commands execute before those submit nodes.
+    pub secondary_command_buffers: Option<RenderGraphSecondaryCommandBuffers>,
 }
 
 impl RenderJobCommandBufferContext {
@@ -20,16 +24,54 @@ impl RenderJobCommandBufferContext {
             resource_context,
             command_buffer,
             render_target_meta,
+            secondary_command_buffers: None,
         }
     }
 
     pub fn from_graph_visit_render_pass_args(
         args: &VisitRenderpassNodeArgs
     ) -> RenderJobCommandBufferContext {
-        RenderJobCommandBufferContext::new(
+        let mut write_context = RenderJobCommandBufferContext::new(
             args.graph_context.resource_context().clone(),
             args.command_buffer.clone(),
             args.render_target_meta.clone(),
-        )
+        );
+        write_context.secondary_command_buffers = args.secondary_command_buffers.clone();
+        write_context
+    }
+
+    /// Sets the viewport. If the subpass is recorded into secondary command buffers, it is also
+    /// set on the command buffers that `write_view_phase` records on other threads.
+    pub fn cmd_set_viewport(
+        &self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        depth_min: f32,
+        depth_max: f32,
+    ) -> RafxResult<()> {
+        if let Some(secondary_command_buffers) = &self.secondary_command_buffers {
+            secondary_command_buffers.cmd_set_viewport(x, y, width, height, depth_min, depth_max)
+        } else {
+            self.command_buffer
+                .cmd_set_viewport(x, y, width, height, depth_min, depth_max)
+        }
+    }
+
+    /// Sets the scissor. If the subpass is recorded into secondary command buffers, it is also
+    /// set on the command buffers that `write_view_phase` records on other threads.
+    pub fn cmd_set_scissor(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> RafxResult<()> {
+        if let Some(secondary_command_buffers) = &self.secondary_command_buffers {
+            secondary_command_buffers.cmd_set_scissor(x, y, width, height)
+        } else {
+            self.command_buffer.cmd_set_scissor(x, y, width, height)
+        }
     }
 }
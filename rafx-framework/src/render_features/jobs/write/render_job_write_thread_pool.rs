@@ -0,0 +1,28 @@
+use crate::render_features::ViewPhase;
+
+/// An application may implement `RenderJobWriteThreadPool` to split the `write` step of a
+/// `RenderView` and `RenderPhase` across threads. `PreparedRenderData::write_view_phase` divides the
+/// sorted `SubmitNode`s into chunks and records each chunk into its own secondary command buffer.
+/// The primary command buffer executes them in `SubmitNode` order, so the result is identical to
+/// writing every chunk on one thread.
+///
+/// Chunks are only recorded in parallel when the render graph pass records into secondary command
+/// buffers. This requires `RafxDeviceInfo::supports_secondary_command_buffers`.
+pub trait RenderJobWriteThreadPool: Sync + Send {
+    /// Returns the number of `SubmitNode`s to record in each chunk, or `None` to write the
+    /// `ViewPhase` on the calling thread. Each chunk calls `apply_setup` and `revert_setup` for the
+    /// `RenderFeature`s it contains, so very small chunks add overhead.
+    fn write_chunk_size(
+        &self,
+        view_phase: &ViewPhase,
+        num_submit_nodes: usize,
+    ) -> Option<usize>;
+
+    /// Calls `write_chunk` once for every chunk index in `0..num_chunks` and returns after all of
+    /// them finish. The calls may run in any order and on any thread.
+    fn run_write_chunks(
+        &self,
+        num_chunks: usize,
+        write_chunk: &(dyn Fn(usize) + Sync),
+    );
+}
@@ -85,6 +85,7 @@ pub mod render_features_prelude {
         PreparePerFrameContext, PreparePerViewContext, PrepareRenderObjectInstanceContext,
         PrepareRenderObjectInstancePerViewContext, RenderJobBeginExecuteGraphContext,
         RenderJobCommandBufferContext, RenderJobExtractAllocationContext, RenderJobExtractContext,
-        RenderJobPrepareContext, RenderJobWriteContext, RenderObjectsJobContext,
+        RenderJobPrepareContext, RenderJobWriteContext, RenderJobWriteThreadPool,
+        RenderObjectsJobContext,
     };
 }
@@ -69,12 +69,14 @@ pub trait RenderPhase {
 }
 
 pub struct RegisteredPhase {
+    name: String,
     sort_submit_nodes_callback: SubmitNodeSortFunction,
 }
 
 impl RegisteredPhase {
-    fn new<T: RenderPhase>() -> Self {
+    fn new<T: RenderPhase>(name: &str) -> Self {
         RegisteredPhase {
+            name: name.to_string(),
             sort_submit_nodes_callback: T::sort_submit_nodes,
         }
     }
@@ -121,7 +123,7 @@ impl RenderRegistryBuilder {
         T::set_render_phase_index(render_phase_index);
         let old = self
             .registered_phases
-            .insert(T::render_phase_index(), RegisteredPhase::new::<T>());
+            .insert(T::render_phase_index(), RegisteredPhase::new::<T>(name));
         assert!(old.is_none());
         let old = self
             .phase_name_to_index
@@ -168,6 +170,16 @@ impl RenderRegistry {
         self.inner.phase_name_to_index.get(name).copied()
     }
 
+    pub fn render_phase_name_from_index(
+        &self,
+        render_phase_index: RenderPhaseIndex,
+    ) -> Option<&str> {
+        self.inner
+            .registered_phases
+            .get(&render_phase_index)
+            .map(|x| x.name.as_str())
+    }
+
     pub fn submit_node_sort_function(
         &self,
         render_phase_index: RenderPhaseIndex,
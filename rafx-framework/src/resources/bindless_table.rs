@@ -0,0 +1,436 @@
+use crate::resources::resource_lookup::DescriptorSetLayoutKey;
+use crate::resources::{DescriptorSetLayoutResource, ResourceArc};
+use crossbeam_channel::{Receiver, Sender};
+use fnv::FnvHashMap;
+use rafx_api::*;
+use std::collections::VecDeque;
+use std::fmt::Formatter;
+use std::num::Wrapping;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum BindlessResourceKind {
+    Texture,
+    Buffer,
+}
+
+/// A slot in the bindless texture or buffer table. The index is what shaders use to look up the
+/// resource in the unbounded array. The generation is bumped every time the slot is reused, so
+/// stale indices can be detected when debugging.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BindlessIndex {
+    index: u32,
+    generation: u32,
+}
+
+impl BindlessIndex {
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+struct BindlessIndexArcInner {
+    bindless_index: BindlessIndex,
+    kind: BindlessResourceKind,
+    drop_tx: Sender<(BindlessResourceKind, u32)>,
+}
+
+impl Drop for BindlessIndexArcInner {
+    fn drop(&mut self) {
+        // The table may already be gone during shutdown, in which case there is nothing to free
+        let _ = self.drop_tx.send((self.kind, self.bindless_index.index));
+    }
+}
+
+/// A reference counted slot in the bindless table. The slot is freed when the last clone is
+/// dropped, and only reused after enough frames have passed that the GPU can't be reading it.
+#[derive(Clone)]
+pub struct BindlessIndexArc {
+    inner: Arc<BindlessIndexArcInner>,
+}
+
+impl BindlessIndexArc {
+    pub fn bindless_index(&self) -> BindlessIndex {
+        self.inner.bindless_index
+    }
+
+    pub fn index(&self) -> u32 {
+        self.inner.bindless_index.index
+    }
+}
+
+impl std::fmt::Debug for BindlessIndexArc {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("BindlessIndexArc")
+            .field("kind", &self.inner.kind)
+            .field("bindless_index", &self.inner.bindless_index)
+            .finish()
+    }
+}
+
+struct RetiredBindlessSlot {
+    index: u32,
+    reusable_on_frame: Wrapping<u32>,
+}
+
+// Generational slot allocator for one kind of bindless resource
+struct BindlessSlots<T> {
+    resources: Vec<Option<T>>,
+    generations: Vec<u32>,
+    free_list: Vec<u32>,
+    // Slots that were freed but may still be referenced by in-flight frames. These are in order
+    // of the frame they were freed on
+    retired: VecDeque<RetiredBindlessSlot>,
+}
+
+impl<T> Default for BindlessSlots<T> {
+    fn default() -> Self {
+        BindlessSlots {
+            resources: Default::default(),
+            generations: Default::default(),
+            free_list: Default::default(),
+            retired: Default::default(),
+        }
+    }
+}
+
+impl<T> BindlessSlots<T> {
+    fn allocate(
+        &mut self,
+        resource: T,
+    ) -> BindlessIndex {
+        let index = if let Some(index) = self.free_list.pop() {
+            self.resources[index as usize] = Some(resource);
+            index
+        } else {
+            let index = self.resources.len() as u32;
+            self.resources.push(Some(resource));
+            self.generations.push(0);
+            index
+        };
+
+        BindlessIndex {
+            index,
+            generation: self.generations[index as usize],
+        }
+    }
+
+    fn retire(
+        &mut self,
+        index: u32,
+        reusable_on_frame: Wrapping<u32>,
+    ) {
+        self.resources[index as usize] = None;
+        self.retired.push_back(RetiredBindlessSlot {
+            index,
+            reusable_on_frame,
+        });
+    }
+
+    fn reclaim_retired(
+        &mut self,
+        frame_index: Wrapping<u32>,
+    ) {
+        while let Some(retired) = self.retired.front() {
+            // Same wrapping trick as ResourceDropSink, a very high value means the frame passed
+            if retired.reusable_on_frame - frame_index > Wrapping(u32::MAX / 2) {
+                let index = retired.index;
+                self.retired.pop_front();
+                self.generations[index as usize] += 1;
+                self.free_list.push(index);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn get(
+        &self,
+        index: u32,
+    ) -> Option<&T> {
+        self.resources.get(index as usize).and_then(|x| x.as_ref())
+    }
+
+    fn iter_allocated(&self) -> impl Iterator<Item = u32> + '_ {
+        self.resources
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.is_some())
+            .map(|(i, _)| i as u32)
+    }
+}
+
+// A descriptor set containing the bindless arrays of a single descriptor set layout
+struct BindlessDescriptorSet {
+    // Keep the layout alive as long as the descriptor set exists
+    _descriptor_set_layout: ResourceArc<DescriptorSetLayoutResource>,
+    descriptor_set_array: RafxDescriptorSetArray,
+    // (binding, element_count)
+    texture_binding: Option<(u32, u32)>,
+    buffer_binding: Option<(u32, u32)>,
+    dirty_textures: Vec<u32>,
+    dirty_buffers: Vec<u32>,
+}
+
+impl BindlessDescriptorSet {
+    fn flush_updates(
+        &mut self,
+        textures: &BindlessSlots<RafxTexture>,
+        buffers: &BindlessSlots<Arc<RafxBuffer>>,
+    ) -> RafxResult<()> {
+        if let Some((binding, element_count)) = self.texture_binding {
+            for index in self.dirty_textures.drain(..) {
+                // Freed slots are left as-is, the array is partially bound
+                if let Some(texture) = textures.get(index) {
+                    if index >= element_count {
+                        log::warn!(
+                            "Bindless texture index {} exceeds the array size {} of binding {}, it will not be accessible",
+                            index,
+                            element_count,
+                            binding
+                        );
+                        continue;
+                    }
+
+                    self.descriptor_set_array
+                        .update_descriptor_set(&[RafxDescriptorUpdate {
+                            array_index: 0,
+                            descriptor_key: RafxDescriptorKey::Binding(binding),
+                            elements: RafxDescriptorElements {
+                                textures: Some(&[texture]),
+                                ..Default::default()
+                            },
+                            dst_element_offset: index,
+                            texture_bind_type: None,
+                        }])?;
+                }
+            }
+        } else {
+            self.dirty_textures.clear();
+        }
+
+        if let Some((binding, element_count)) = self.buffer_binding {
+            for index in self.dirty_buffers.drain(..) {
+                if let Some(buffer) = buffers.get(index) {
+                    if index >= element_count {
+                        log::warn!(
+                            "Bindless buffer index {} exceeds the array size {} of binding {}, it will not be accessible",
+                            index,
+                            element_count,
+                            binding
+                        );
+                        continue;
+                    }
+
+                    self.descriptor_set_array
+                        .update_descriptor_set(&[RafxDescriptorUpdate {
+                            array_index: 0,
+                            descriptor_key: RafxDescriptorKey::Binding(binding),
+                            elements: RafxDescriptorElements {
+                                buffers: Some(&[&**buffer]),
+                                ..Default::default()
+                            },
+                            dst_element_offset: index,
+                            texture_bind_type: None,
+                        }])?;
+                }
+            }
+        } else {
+            self.dirty_buffers.clear();
+        }
+
+        Ok(())
+    }
+}
+
+struct BindlessTableInner {
+    device_context: RafxDeviceContext,
+    textures: BindlessSlots<RafxTexture>,
+    buffers: BindlessSlots<Arc<RafxBuffer>>,
+    descriptor_sets: FnvHashMap<DescriptorSetLayoutKey, BindlessDescriptorSet>,
+    drop_tx: Sender<(BindlessResourceKind, u32)>,
+    drop_rx: Receiver<(BindlessResourceKind, u32)>,
+    max_frames_in_flight: Wrapping<u32>,
+    frame_index: Wrapping<u32>,
+}
+
+/// Global table of textures and buffers that shaders can index into through unbounded arrays
+/// (declared with the `@[bindless(N)]` shader annotation). Images and buffers inserted into the
+/// `ResourceLookupSet` are given a stable index here. Descriptor sets are created lazily for each
+/// descriptor set layout that contains bindless arrays and are kept up-to-date as resources are
+/// added.
+#[derive(Clone)]
+pub struct BindlessTable {
+    inner: Arc<Mutex<BindlessTableInner>>,
+}
+
+impl BindlessTable {
+    pub fn new(
+        device_context: &RafxDeviceContext,
+        max_frames_in_flight: u32,
+    ) -> Self {
+        let (drop_tx, drop_rx) = crossbeam_channel::unbounded();
+        let inner = BindlessTableInner {
+            device_context: device_context.clone(),
+            textures: Default::default(),
+            buffers: Default::default(),
+            descriptor_sets: Default::default(),
+            drop_tx,
+            drop_rx,
+            max_frames_in_flight: Wrapping(max_frames_in_flight),
+            frame_index: Wrapping(0),
+        };
+
+        BindlessTable {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .device_context
+            .device_info()
+            .supports_bindless_descriptors
+    }
+
+    pub(crate) fn insert_texture(
+        &self,
+        texture: &RafxTexture,
+    ) -> BindlessIndexArc {
+        let mut guard = self.inner.lock().unwrap();
+        let bindless_index = guard.textures.allocate(texture.clone());
+        for descriptor_set in guard.descriptor_sets.values_mut() {
+            descriptor_set.dirty_textures.push(bindless_index.index);
+        }
+
+        BindlessIndexArc {
+            inner: Arc::new(BindlessIndexArcInner {
+                bindless_index,
+                kind: BindlessResourceKind::Texture,
+                drop_tx: guard.drop_tx.clone(),
+            }),
+        }
+    }
+
+    pub(crate) fn insert_buffer(
+        &self,
+        buffer: &Arc<RafxBuffer>,
+    ) -> BindlessIndexArc {
+        let mut guard = self.inner.lock().unwrap();
+        let bindless_index = guard.buffers.allocate(buffer.clone());
+        for descriptor_set in guard.descriptor_sets.values_mut() {
+            descriptor_set.dirty_buffers.push(bindless_index.index);
+        }
+
+        BindlessIndexArc {
+            inner: Arc::new(BindlessIndexArcInner {
+                bindless_index,
+                kind: BindlessResourceKind::Buffer,
+                drop_tx: guard.drop_tx.clone(),
+            }),
+        }
+    }
+
+    /// Bind the bindless descriptor set for the given layout, creating it if necessary. Any
+    /// textures or buffers added since the last bind are written before binding.
+    pub fn cmd_bind_descriptor_set(
+        &self,
+        command_buffer: &RafxCommandBuffer,
+        descriptor_set_layout: &ResourceArc<DescriptorSetLayoutResource>,
+    ) -> RafxResult<()> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+
+        let layout = descriptor_set_layout.get_raw();
+        if !inner.descriptor_sets.contains_key(&layout.key) {
+            let descriptor_set = Self::create_descriptor_set(inner, descriptor_set_layout.clone())?;
+            inner.descriptor_sets.insert(layout.key, descriptor_set);
+        }
+
+        let descriptor_set = inner.descriptor_sets.get_mut(&layout.key).unwrap();
+        descriptor_set.flush_updates(&inner.textures, &inner.buffers)?;
+        command_buffer.cmd_bind_descriptor_set(&descriptor_set.descriptor_set_array, 0)
+    }
+
+    fn create_descriptor_set(
+        inner: &BindlessTableInner,
+        descriptor_set_layout: ResourceArc<DescriptorSetLayoutResource>,
+    ) -> RafxResult<BindlessDescriptorSet> {
+        let layout = descriptor_set_layout.get_raw();
+
+        let mut texture_binding = None;
+        let mut buffer_binding = None;
+        for binding in &layout.descriptor_set_layout_def.bindings {
+            let resource = &binding.resource;
+            if !resource.resource_type.is_bindless() {
+                continue;
+            }
+
+            let base_type = resource.resource_type - RafxResourceType::BINDLESS;
+            if base_type == RafxResourceType::TEXTURE {
+                if texture_binding.is_none() {
+                    texture_binding = Some((resource.binding, resource.element_count));
+                }
+            } else if buffer_binding.is_none() {
+                buffer_binding = Some((resource.binding, resource.element_count));
+            }
+        }
+
+        if texture_binding.is_none() && buffer_binding.is_none() {
+            return Err(format!(
+                "Descriptor set layout (set_index={}) has no bindless bindings",
+                layout.set_index
+            ))?;
+        }
+
+        let descriptor_set_array =
+            inner
+                .device_context
+                .create_descriptor_set_array(&RafxDescriptorSetArrayDef {
+                    root_signature: &layout.root_signature,
+                    set_index: layout.set_index,
+                    array_length: 1,
+                })?;
+
+        Ok(BindlessDescriptorSet {
+            _descriptor_set_layout: descriptor_set_layout,
+            descriptor_set_array,
+            texture_binding,
+            buffer_binding,
+            dirty_textures: inner.textures.iter_allocated().collect(),
+            dirty_buffers: inner.buffers.iter_allocated().collect(),
+        })
+    }
+
+    pub fn on_frame_complete(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+
+        inner.frame_index += Wrapping(1);
+        let reusable_on_frame = inner.frame_index + inner.max_frames_in_flight + Wrapping(1);
+        for (kind, index) in inner.drop_rx.try_iter() {
+            match kind {
+                BindlessResourceKind::Texture => inner.textures.retire(index, reusable_on_frame),
+                BindlessResourceKind::Buffer => inner.buffers.retire(index, reusable_on_frame),
+            }
+        }
+
+        inner.textures.reclaim_retired(inner.frame_index);
+        inner.buffers.reclaim_retired(inner.frame_index);
+    }
+
+    // This assumes that no GPU work remains that relies on the descriptor sets
+    pub fn destroy(&self) {
+        let mut guard = self.inner.lock().unwrap();
+        guard.descriptor_sets.clear();
+    }
+}
@@ -3,7 +3,7 @@ use super::ManagedDescriptorSetPool;
 use super::{DescriptorSetArc, FrameInFlightIndex};
 use crate::descriptor_sets::descriptor_set_pool_chunk::DescriptorSetWriter;
 use crate::resources::resource_lookup::{DescriptorSetLayoutResource, ResourceHash};
-use crate::resources::{DynDescriptorSet, ResourceArc};
+use crate::resources::{DynDescriptorSet, ResourceArc, ResourceMemoryTracker};
 use fnv::FnvHashMap;
 use rafx_api::{RafxDeviceContext, RafxResult};
 
@@ -11,42 +11,117 @@ use rafx_api::{RafxDeviceContext, RafxResult};
 pub struct DescriptorSetPoolMetrics {
     pub hash: ResourceHash,
     pub allocated_count: usize,
+    // Number of chunks currently allocated, each holds a fixed number of descriptor sets
+    pub chunk_count: usize,
+    // Number of chunks released for being idle since the pool was created
+    pub released_chunk_count: usize,
+    // Portion of descriptor sets in allocated chunks that are unused (0.0 to 1.0)
+    pub fragmentation: f32,
+    // True if new descriptor sets are being packed into the fullest chunks. Existing descriptor
+    // sets are never moved between chunks.
+    pub is_packing: bool,
 }
 
 #[derive(Debug)]
 pub struct DescriptorSetAllocatorMetrics {
     pub pools: Vec<DescriptorSetPoolMetrics>,
+    pub chunk_count: usize,
+    pub released_chunk_count: usize,
+    // Number of pools that are packing new descriptor sets into their fullest chunks
+    pub packing_pool_count: usize,
+}
+
+/// Controls how descriptor set pools give memory back as descriptor sets are freed
+#[derive(Debug, Copy, Clone)]
+pub struct DescriptorSetPoolTrimConfig {
+    /// Chunks that have had no descriptor sets allocated from them for this many frames are
+    /// released. None disables releasing chunks.
+    pub release_idle_chunks_after_frames: Option<u32>,
+
+    /// When the portion of unused descriptor sets in a pool's chunks exceeds this value (0.0 to
+    /// 1.0), new descriptor sets are allocated from the fullest chunks so that sparse chunks can
+    /// drain and be released. This does not move existing descriptor sets, so a sparse chunk is
+    /// only released once everything allocated from it has been freed.
+    pub packing_threshold: f32,
+}
+
+impl Default for DescriptorSetPoolTrimConfig {
+    fn default() -> Self {
+        DescriptorSetPoolTrimConfig {
+            release_idle_chunks_after_frames: Some(300),
+            packing_threshold: 0.5,
+        }
+    }
 }
 
 pub struct DescriptorSetAllocator {
     device_context: RafxDeviceContext,
+    memory_tracker: ResourceMemoryTracker,
     pools: FnvHashMap<ResourceHash, ManagedDescriptorSetPool>,
+    trim_config: DescriptorSetPoolTrimConfig,
 
     // This index represents the set of resources that will be written to when update() is called.
     frame_in_flight_index: FrameInFlightIndex,
+
+    // Incremented when on_frame_complete is called, used to determine how long chunks have been idle
+    frame_index: u64,
 }
 
 impl DescriptorSetAllocator {
-    pub fn new(device_context: &RafxDeviceContext) -> Self {
+    pub fn new(
+        device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
+    ) -> Self {
         DescriptorSetAllocator {
             device_context: device_context.clone(),
+            memory_tracker: memory_tracker.clone(),
             pools: Default::default(),
+            trim_config: Default::default(),
             frame_in_flight_index: 0,
+            frame_index: 0,
         }
     }
 
+    pub fn trim_config(&self) -> &DescriptorSetPoolTrimConfig {
+        &self.trim_config
+    }
+
+    pub fn set_trim_config(
+        &mut self,
+        trim_config: DescriptorSetPoolTrimConfig,
+    ) {
+        self.trim_config = trim_config;
+    }
+
     pub fn metrics(&self) -> DescriptorSetAllocatorMetrics {
         let mut registered_descriptor_sets_stats = Vec::with_capacity(self.pools.len());
+        let mut chunk_count = 0;
+        let mut released_chunk_count = 0;
+        let mut packing_pool_count = 0;
         for (hash, value) in &self.pools {
             let pool_stats = DescriptorSetPoolMetrics {
                 hash: *hash,
-                allocated_count: value.slab.allocated_count(),
+                allocated_count: value.allocated_count(),
+                chunk_count: value.chunk_count(),
+                released_chunk_count: value.released_chunk_count(),
+                fragmentation: value.fragmentation(),
+                is_packing: value.is_packing(&self.trim_config),
             };
+
+            chunk_count += pool_stats.chunk_count;
+            released_chunk_count += pool_stats.released_chunk_count;
+            if pool_stats.is_packing {
+                packing_pool_count += 1;
+            }
+
             registered_descriptor_sets_stats.push(pool_stats);
         }
 
         DescriptorSetAllocatorMetrics {
             pools: registered_descriptor_sets_stats,
+            chunk_count,
+            released_chunk_count,
+            packing_pool_count,
         }
     }
 
@@ -54,7 +129,11 @@ impl DescriptorSetAllocator {
     pub fn flush_changes(&mut self) -> RafxResult<()> {
         // Now process drops and flush writes to GPU
         for pool in self.pools.values_mut() {
-            pool.flush_changes(self.frame_in_flight_index)?;
+            pool.flush_changes(
+                self.frame_in_flight_index,
+                self.frame_index,
+                &self.trim_config,
+            )?;
         }
 
         Ok(())
@@ -65,6 +144,7 @@ impl DescriptorSetAllocator {
         // Bump frame in flight index
         self.frame_in_flight_index =
             super::add_to_frame_in_flight_index(self.frame_in_flight_index, 1);
+        self.frame_index += 1;
     }
 
     pub fn destroy(&mut self) -> RafxResult<()> {
@@ -95,11 +175,16 @@ impl DescriptorSetAllocator {
     fn get_or_create_pool_for_layout<'a>(
         pools: &'a mut FnvHashMap<ResourceHash, ManagedDescriptorSetPool>,
         device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
         descriptor_set_layout: &ResourceArc<DescriptorSetLayoutResource>,
     ) -> &'a mut ManagedDescriptorSetPool {
         let hash = descriptor_set_layout.get_hash().into();
         pools.entry(hash).or_insert_with(|| {
-            ManagedDescriptorSetPool::new(device_context, descriptor_set_layout.clone())
+            ManagedDescriptorSetPool::new(
+                device_context,
+                memory_tracker,
+                descriptor_set_layout.clone(),
+            )
         })
     }
 
@@ -111,9 +196,10 @@ impl DescriptorSetAllocator {
         Self::get_or_create_pool_for_layout(
             &mut self.pools,
             &self.device_context,
+            &self.memory_tracker,
             descriptor_set_layout,
         )
-        .insert_with_write_set(&self.device_context, write_set)
+        .insert_with_write_set(&self.device_context, &self.trim_config, write_set)
     }
 
     pub fn create_descriptor_set_with_writer<'a, T: DescriptorSetWriter<'a>>(
@@ -124,9 +210,10 @@ impl DescriptorSetAllocator {
         Self::get_or_create_pool_for_layout(
             &mut self.pools,
             &self.device_context,
+            &self.memory_tracker,
             descriptor_set_layout,
         )
-        .insert_with_writer(&self.device_context, args)
+        .insert_with_writer(&self.device_context, &self.trim_config, args)
     }
 
     pub fn create_descriptor_set<'a, T: DescriptorSetInitializer<'a>>(
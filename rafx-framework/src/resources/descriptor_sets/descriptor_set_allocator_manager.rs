@@ -1,4 +1,5 @@
 use super::DescriptorSetAllocator;
+use crate::ResourceMemoryTracker;
 use crossbeam_channel::{Receiver, Sender};
 use rafx_api::{RafxDeviceContext, RafxResult};
 use std::collections::VecDeque;
@@ -60,6 +61,7 @@ impl Drop for DescriptorSetAllocatorRef {
 // layouts.
 pub struct DescriptorSetAllocatorManagerInner {
     device_context: RafxDeviceContext,
+    memory_tracker: ResourceMemoryTracker,
     allocators: Mutex<VecDeque<Box<DescriptorSetAllocator>>>,
     drop_tx: Sender<DescriptorSetAllocatorRefInner>,
     drop_rx: Receiver<DescriptorSetAllocatorRefInner>,
@@ -67,11 +69,15 @@ pub struct DescriptorSetAllocatorManagerInner {
 }
 
 impl DescriptorSetAllocatorManagerInner {
-    fn new(device_context: RafxDeviceContext) -> Self {
+    fn new(
+        device_context: RafxDeviceContext,
+        memory_tracker: ResourceMemoryTracker,
+    ) -> Self {
         let (drop_tx, drop_rx) = crossbeam_channel::unbounded();
 
         DescriptorSetAllocatorManagerInner {
             device_context,
+            memory_tracker,
             allocators: Default::default(),
             drop_tx,
             drop_rx,
@@ -119,7 +125,10 @@ impl DescriptorSetAllocatorManagerInner {
         };
 
         let allocator = allocator.unwrap_or_else(|| {
-            let allocator = Box::new(DescriptorSetAllocator::new(&self.device_context));
+            let allocator = Box::new(DescriptorSetAllocator::new(
+                &self.device_context,
+                &self.memory_tracker,
+            ));
 
             DescriptorSetAllocatorRefInner {
                 allocator,
@@ -170,10 +179,14 @@ pub struct DescriptorSetAllocatorManager {
 }
 
 impl DescriptorSetAllocatorManager {
-    pub fn new(device_context: &RafxDeviceContext) -> Self {
+    pub fn new(
+        device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
+    ) -> Self {
         DescriptorSetAllocatorManager {
             inner: Arc::new(DescriptorSetAllocatorManagerInner::new(
                 device_context.clone(),
+                memory_tracker.clone(),
             )),
         }
     }
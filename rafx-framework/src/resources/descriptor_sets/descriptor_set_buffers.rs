@@ -1,4 +1,5 @@
 use crate::resources::descriptor_sets::{DescriptorSetBindingKey, MAX_DESCRIPTOR_SETS_PER_POOL};
+use crate::{ResourceMemoryAllocation, ResourceMemoryCategory, ResourceMemoryTracker};
 use fnv::FnvHashMap;
 use rafx_api::{
     RafxBuffer, RafxBufferDef, RafxDeviceContext, RafxMemoryUsage, RafxQueueType, RafxResourceType,
@@ -22,11 +23,14 @@ pub(super) struct DescriptorSetPoolRequiredBufferInfo {
 pub(super) struct DescriptorBindingBufferSet {
     pub(super) buffer: RafxBuffer,
     pub(super) buffer_info: DescriptorSetPoolRequiredBufferInfo,
+    // Counts the buffer's memory until the buffer is retired
+    _memory_allocation: ResourceMemoryAllocation,
 }
 
 impl DescriptorBindingBufferSet {
     fn new(
         device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
         buffer_info: &DescriptorSetPoolRequiredBufferInfo,
     ) -> RafxResult<Self> {
         //This is the only one we support right now
@@ -40,9 +44,15 @@ impl DescriptorBindingBufferSet {
             ..Default::default()
         })?;
 
+        let memory_allocation = memory_tracker.track(
+            ResourceMemoryCategory::DescriptorSetBuffers,
+            buffer.allocation_size_in_bytes(),
+        );
+
         Ok(DescriptorBindingBufferSet {
             buffer,
             buffer_info: buffer_info.clone(),
+            _memory_allocation: memory_allocation,
         })
     }
 }
@@ -57,12 +67,14 @@ pub(super) struct DescriptorLayoutBufferSet {
 impl DescriptorLayoutBufferSet {
     pub(super) fn new(
         device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
         buffer_infos: &[DescriptorSetPoolRequiredBufferInfo],
     ) -> RafxResult<Self> {
         let mut buffer_sets: FnvHashMap<DescriptorSetBindingKey, DescriptorBindingBufferSet> =
             Default::default();
         for buffer_info in buffer_infos {
-            let buffer = DescriptorBindingBufferSet::new(device_context, &buffer_info)?;
+            let buffer =
+                DescriptorBindingBufferSet::new(device_context, memory_tracker, &buffer_info)?;
             buffer_sets.insert(buffer_info.dst_element, buffer);
         }
 
@@ -2,17 +2,18 @@ use super::ManagedDescriptorSet;
 use super::ManagedDescriptorSetPoolChunk;
 use super::{
     DescriptorSetArc, DescriptorSetBindingKey, DescriptorSetPoolRequiredBufferInfo,
-    DescriptorSetWriteSet, FrameInFlightIndex, MAX_DESCRIPTOR_SETS_PER_POOL, MAX_FRAMES_IN_FLIGHT,
+    DescriptorSetPoolTrimConfig, DescriptorSetWriteSet, FrameInFlightIndex,
+    MAX_DESCRIPTOR_SETS_PER_POOL, MAX_FRAMES_IN_FLIGHT,
 };
 use crate::resources::descriptor_sets::descriptor_set_pool_chunk::DescriptorSetWriter;
 use crate::resources::resource_lookup::DescriptorSetLayoutResource;
 use crate::resources::ResourceArc;
-use crate::{DescriptorSetArrayPoolAllocator, ResourceDropSink};
+use crate::{DescriptorSetArrayPoolAllocator, ResourceDropSink, ResourceMemoryTracker};
 use crossbeam_channel::{Receiver, Sender};
 use rafx_api::{
     RafxBuffer, RafxDescriptorSetArrayDef, RafxDescriptorSetHandle, RafxDeviceContext, RafxResult,
 };
-use rafx_base::slab::{RawSlab, RawSlabKey};
+use rafx_base::slab::RawSlabKey;
 use std::collections::VecDeque;
 
 struct PendingDescriptorSetDrop {
@@ -20,12 +21,43 @@ struct PendingDescriptorSetDrop {
     live_until_frame: u32,
 }
 
-//TODO: This does not implement any form of defragmentation or trimming - it grows up to the high
-// watermark of concurrent number of allocated descriptor sets and remains that size
-pub(super) struct ManagedDescriptorSetPool {
-    // Keeps track of descriptor sets that are in use
-    pub(super) slab: RawSlab<ManagedDescriptorSet>,
+// Tracks which descriptor sets in a chunk are in use. The chunk itself is None if it was released
+// after being idle, and will be recreated if we need to allocate from it again.
+struct ManagedDescriptorSetPoolChunkSlot {
+    chunk: Option<ManagedDescriptorSetPoolChunk>,
+
+    // Number of descriptor sets that are in use or waiting for in-flight frames to complete
+    allocated_count: u32,
+
+    // Unused descriptor set indices within the chunk. Lowest indices are at the end
+    free_indices: Vec<u32>,
+
+    // The frame on which allocated_count dropped to zero
+    idle_since_frame: Option<u64>,
+}
+
+impl ManagedDescriptorSetPoolChunkSlot {
+    fn new() -> Self {
+        ManagedDescriptorSetPoolChunkSlot {
+            chunk: None,
+            allocated_count: 0,
+            free_indices: (0..MAX_DESCRIPTOR_SETS_PER_POOL).rev().collect(),
+            idle_since_frame: None,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.free_indices.is_empty()
+    }
+}
 
+// Descriptor sets are allocated from chunks of MAX_DESCRIPTOR_SETS_PER_POOL sets. Chunks that have
+// had no descriptor sets allocated for a while are released (see DescriptorSetPoolTrimConfig).
+// Descriptor sets can't be moved to another chunk once allocated since DescriptorSetArcs hold the
+// descriptor set handle directly, so the pool is never compacted. Instead, when the pool becomes
+// fragmented, new descriptor sets are packed into the fullest chunks, letting sparse chunks drain so
+// they can be released.
+pub(super) struct ManagedDescriptorSetPool {
     // Used to allow DescriptorSetArc to trigger dropping descriptor sets
     drop_tx: Sender<RawSlabKey<ManagedDescriptorSet>>,
     drop_rx: Receiver<RawSlabKey<ManagedDescriptorSet>>,
@@ -43,8 +75,17 @@ pub(super) struct ManagedDescriptorSetPool {
     // Metadata about buffers that back data in descriptor sets (this is an opt-in feature per binding)
     buffer_infos: Vec<DescriptorSetPoolRequiredBufferInfo>,
 
+    // Counts the memory of the buffers that back data in descriptor sets
+    memory_tracker: ResourceMemoryTracker,
+
     // The chunks that make up the pool. We allocate in batches as the pool becomes empty
-    chunks: Vec<ManagedDescriptorSetPoolChunk>,
+    chunk_slots: Vec<ManagedDescriptorSetPoolChunkSlot>,
+
+    // Number of descriptor sets that are in use or waiting for in-flight frames to complete
+    allocated_count: usize,
+
+    // Number of chunks that have been released for being idle since the pool was created
+    released_chunk_count: usize,
 
     // The drops that we will process later. This allows us to defer dropping bindings until
     // MAX_FRAMES_IN_FLIGHT frames have passed
@@ -54,6 +95,7 @@ pub(super) struct ManagedDescriptorSetPool {
 impl ManagedDescriptorSetPool {
     pub fn new(
         device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
         descriptor_set_layout: ResourceArc<DescriptorSetLayoutResource>,
     ) -> Self {
         let (drop_tx, drop_rx) = crossbeam_channel::unbounded();
@@ -114,46 +156,147 @@ impl ManagedDescriptorSetPool {
         }
 
         ManagedDescriptorSetPool {
-            slab: RawSlab::with_capacity(MAX_DESCRIPTOR_SETS_PER_POOL),
             drop_tx,
             drop_rx,
             descriptor_pool_allocator,
             descriptor_set_layout,
-            chunks: Default::default(),
+            chunk_slots: Default::default(),
+            allocated_count: 0,
+            released_chunk_count: 0,
             buffer_infos,
+            memory_tracker: memory_tracker.clone(),
             buffer_drop_sink: ResourceDropSink::new(MAX_FRAMES_IN_FLIGHT as u32),
             pending_drops: Default::default(),
         }
     }
 
+    pub fn allocated_count(&self) -> usize {
+        self.allocated_count
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_slots
+            .iter()
+            .filter(|slot| slot.chunk.is_some())
+            .count()
+    }
+
+    pub fn released_chunk_count(&self) -> usize {
+        self.released_chunk_count
+    }
+
+    // The portion of descriptor sets in allocated chunks that are unused
+    pub fn fragmentation(&self) -> f32 {
+        let capacity = self.chunk_count() * MAX_DESCRIPTOR_SETS_PER_POOL as usize;
+        if capacity == 0 {
+            0.0
+        } else {
+            1.0 - (self.allocated_count as f32 / capacity as f32)
+        }
+    }
+
+    pub fn is_packing(
+        &self,
+        trim_config: &DescriptorSetPoolTrimConfig,
+    ) -> bool {
+        self.chunk_count() > 1 && self.fragmentation() > trim_config.packing_threshold
+    }
+
     fn get_chunk_index(slab_key: &RawSlabKey<ManagedDescriptorSet>) -> usize {
         (slab_key.index() / MAX_DESCRIPTOR_SETS_PER_POOL) as usize
     }
 
+    fn find_chunk_index_for_allocation(
+        &self,
+        trim_config: &DescriptorSetPoolTrimConfig,
+    ) -> usize {
+        let is_allocated_and_not_full =
+            |slot: &ManagedDescriptorSetPoolChunkSlot| slot.chunk.is_some() && !slot.is_full();
+
+        if self.is_packing(trim_config) {
+            // Pack new descriptor sets into the fullest chunks so that sparse chunks drain
+            let fullest_chunk_index = self
+                .chunk_slots
+                .iter()
+                .enumerate()
+                .filter(|(_, slot)| is_allocated_and_not_full(slot))
+                .max_by_key(|(_, slot)| slot.allocated_count)
+                .map(|(chunk_index, _)| chunk_index);
+
+            if let Some(chunk_index) = fullest_chunk_index {
+                return chunk_index;
+            }
+        } else if let Some(chunk_index) =
+            self.chunk_slots.iter().position(is_allocated_and_not_full)
+        {
+            return chunk_index;
+        }
+
+        // No allocated chunk has space, re-use a released chunk slot or add a new one
+        self.chunk_slots
+            .iter()
+            .position(|slot| slot.chunk.is_none())
+            .unwrap_or(self.chunk_slots.len())
+    }
+
     fn get_next_unused_slab_key(
         &mut self,
         device_context: &RafxDeviceContext,
+        trim_config: &DescriptorSetPoolTrimConfig,
     ) -> RafxResult<RawSlabKey<ManagedDescriptorSet>> {
-        let registered_set = ManagedDescriptorSet {
-            // Don't have anything to store yet
-            //write_set: write_set.clone()
-        };
+        let chunk_index = self.find_chunk_index_for_allocation(trim_config);
+        if chunk_index == self.chunk_slots.len() {
+            self.chunk_slots
+                .push(ManagedDescriptorSetPoolChunkSlot::new());
+        }
 
-        // Use the slab allocator to find an unused index, determine the chunk index from that
-        let slab_key = self.slab.allocate(registered_set);
-        let chunk_index = Self::get_chunk_index(&slab_key);
+        let slot = &mut self.chunk_slots[chunk_index];
 
-        // Add more chunks if necessary
-        while chunk_index as usize >= self.chunks.len() {
-            self.chunks.push(ManagedDescriptorSetPoolChunk::new(
+        // Create the chunk if it is new or was previously released
+        if slot.chunk.is_none() {
+            slot.chunk = Some(ManagedDescriptorSetPoolChunk::new(
                 device_context,
+                &self.memory_tracker,
                 &self.buffer_infos,
                 &self.descriptor_set_layout,
                 &mut self.descriptor_pool_allocator,
             )?);
         }
 
-        Ok(slab_key)
+        let descriptor_index = slot.free_indices.pop().unwrap();
+        slot.allocated_count += 1;
+        slot.idle_since_frame = None;
+        self.allocated_count += 1;
+
+        Ok(RawSlabKey::new(
+            chunk_index as u32 * MAX_DESCRIPTOR_SETS_PER_POOL + descriptor_index,
+        ))
+    }
+
+    fn free_slab_key(
+        &mut self,
+        slab_key: RawSlabKey<ManagedDescriptorSet>,
+        frame_index: u64,
+    ) {
+        let slot = &mut self.chunk_slots[Self::get_chunk_index(&slab_key)];
+        slot.free_indices
+            .push(slab_key.index() % MAX_DESCRIPTOR_SETS_PER_POOL);
+        slot.allocated_count -= 1;
+        if slot.allocated_count == 0 {
+            slot.idle_since_frame = Some(frame_index);
+        }
+
+        self.allocated_count -= 1;
+    }
+
+    fn chunk_mut(
+        &mut self,
+        slab_key: &RawSlabKey<ManagedDescriptorSet>,
+    ) -> &mut ManagedDescriptorSetPoolChunk {
+        self.chunk_slots[Self::get_chunk_index(slab_key)]
+            .chunk
+            .as_mut()
+            .unwrap()
     }
 
     fn get_descriptor_arc(
@@ -172,13 +315,13 @@ impl ManagedDescriptorSetPool {
     pub fn insert_with_writer<'a, T: DescriptorSetWriter<'a>>(
         &mut self,
         device_context: &RafxDeviceContext,
+        trim_config: &DescriptorSetPoolTrimConfig,
         args: T,
     ) -> RafxResult<DescriptorSetArc> {
-        let slab_key = self.get_next_unused_slab_key(device_context)?;
-        let chunk_index = Self::get_chunk_index(&slab_key);
+        let slab_key = self.get_next_unused_slab_key(device_context, trim_config)?;
 
         let descriptor_set_handle = {
-            let mut writer_context = self.chunks[chunk_index].get_writer(slab_key)?;
+            let mut writer_context = self.chunk_mut(&slab_key).get_writer(slab_key)?;
             T::write_to(&mut writer_context, args);
             writer_context.handle()
         };
@@ -190,14 +333,15 @@ impl ManagedDescriptorSetPool {
     pub fn insert_with_write_set(
         &mut self,
         device_context: &RafxDeviceContext,
+        trim_config: &DescriptorSetPoolTrimConfig,
         write_set: DescriptorSetWriteSet,
     ) -> RafxResult<DescriptorSetArc> {
-        let slab_key = self.get_next_unused_slab_key(device_context)?;
-        let chunk_index = Self::get_chunk_index(&slab_key);
+        let slab_key = self.get_next_unused_slab_key(device_context, trim_config)?;
 
         // Insert the write into the chunk, it will be applied when update() is next called on it
-        let descriptor_set_handle =
-            self.chunks[chunk_index].schedule_write_set(slab_key, write_set);
+        let descriptor_set_handle = self
+            .chunk_mut(&slab_key)
+            .schedule_write_set(slab_key, write_set);
 
         // Return the ref-counted descriptor set
         Ok(self.get_descriptor_arc(slab_key, descriptor_set_handle))
@@ -207,6 +351,8 @@ impl ManagedDescriptorSetPool {
     pub fn flush_changes(
         &mut self,
         frame_in_flight_index: FrameInFlightIndex,
+        frame_index: u64,
+        trim_config: &DescriptorSetPoolTrimConfig,
     ) -> RafxResult<()> {
         // Route messages that indicate a dropped descriptor set to the chunk that owns it
         for dropped in self.drop_rx.try_iter() {
@@ -221,11 +367,13 @@ impl ManagedDescriptorSetPool {
 
         // Determine how many drops we can drain (we keep them around for MAX_FRAMES_IN_FLIGHT frames
         let mut pending_drops_to_drain = 0;
-        for pending_drop in &self.pending_drops {
+        while pending_drops_to_drain < self.pending_drops.len() {
             // If frame_in_flight_index matches or exceeds live_until_frame, then the result will be a very
             // high value due to wrapping a negative value to u32::MAX
+            let pending_drop = &self.pending_drops[pending_drops_to_drain];
             if pending_drop.live_until_frame == frame_in_flight_index {
-                self.slab.free(pending_drop.slab_key);
+                let slab_key = pending_drop.slab_key;
+                self.free_slab_key(slab_key, frame_index);
                 pending_drops_to_drain += 1;
             } else {
                 break;
@@ -245,24 +393,317 @@ impl ManagedDescriptorSetPool {
         self.pending_drops.drain(0..pending_drops_to_drain);
 
         // Commit pending writes/removes, rotate to the descriptor set for the next frame
-        for chunk in &mut self.chunks {
-            chunk.update()?;
+        for slot in &mut self.chunk_slots {
+            if let Some(chunk) = &mut slot.chunk {
+                chunk.update()?;
+            }
         }
 
+        if let Some(release_idle_chunks_after_frames) = trim_config.release_idle_chunks_after_frames
+        {
+            self.release_idle_chunks(frame_index, release_idle_chunks_after_frames);
+        }
+
+        self.buffer_drop_sink.on_frame_complete()?;
         self.descriptor_pool_allocator.update()
     }
 
-    pub fn destroy(&mut self) -> RafxResult<()> {
-        for chunk in &mut self.chunks {
-            chunk.destroy(
-                &mut self.descriptor_pool_allocator,
-                &mut self.buffer_drop_sink,
+    fn release_idle_chunks(
+        &mut self,
+        frame_index: u64,
+        release_idle_chunks_after_frames: u32,
+    ) {
+        let mut released_chunk_count = 0;
+        for slot in &mut self.chunk_slots {
+            let idle_since_frame = match slot.idle_since_frame {
+                Some(idle_since_frame) => idle_since_frame,
+                None => continue,
+            };
+
+            if frame_index - idle_since_frame < release_idle_chunks_after_frames as u64 {
+                continue;
+            }
+
+            if let Some(mut chunk) = slot.chunk.take() {
+                // Nothing in the chunk has been in use for release_idle_chunks_after_frames frames,
+                // so it is safe to drop once any frames that might still reference it complete
+                chunk.release(
+                    &mut self.descriptor_pool_allocator,
+                    &mut self.buffer_drop_sink,
+                );
+                released_chunk_count += 1;
+            }
+
+            slot.idle_since_frame = None;
+        }
+
+        // Released slots at the end can be removed entirely. Slots in the middle must remain since
+        // the chunk index is encoded in the slab keys of descriptor sets in later chunks
+        while self
+            .chunk_slots
+            .last()
+            .map(|slot| slot.chunk.is_none() && slot.allocated_count == 0)
+            .unwrap_or(false)
+        {
+            self.chunk_slots.pop();
+        }
+
+        if released_chunk_count > 0 {
+            log::debug!(
+                "Released {} idle descriptor set pool chunks for layout {:?}",
+                released_chunk_count,
+                self.descriptor_set_layout
             );
+            self.released_chunk_count += released_chunk_count;
+        }
+    }
+
+    pub fn destroy(&mut self) -> RafxResult<()> {
+        for slot in &mut self.chunk_slots {
+            if let Some(chunk) = &mut slot.chunk {
+                chunk.destroy(
+                    &mut self.descriptor_pool_allocator,
+                    &mut self.buffer_drop_sink,
+                );
+            }
         }
 
         self.descriptor_pool_allocator.destroy()?;
         self.buffer_drop_sink.destroy()?;
-        self.chunks.clear();
+        self.chunk_slots.clear();
         Ok(())
     }
 }
+
+#[cfg(all(
+    test,
+    any(
+        feature = "rafx-empty",
+        not(any(
+            feature = "rafx-metal",
+            feature = "rafx-vulkan",
+            feature = "rafx-gles2",
+            feature = "rafx-gles3"
+        ))
+    )
+))]
+mod tests {
+    use super::*;
+    use crate::resources::descriptor_sets::{
+        create_uninitialized_write_set_for_layout, DescriptorSetLayout, DescriptorSetLayoutBinding,
+    };
+    use crate::{ReflectedEntryPoint, ResourceLookupSet};
+    use rafx_api::{
+        RafxApi, RafxResourceType, RafxShaderPackage, RafxShaderResource, RafxShaderStageFlags,
+        RafxShaderStageReflection,
+    };
+
+    fn create_descriptor_set_layout(
+        resources: &ResourceLookupSet
+    ) -> ResourceArc<DescriptorSetLayoutResource> {
+        let shader_resource = RafxShaderResource {
+            resource_type: RafxResourceType::TEXTURE,
+            set_index: 0,
+            binding: 0,
+            element_count: 1,
+            used_in_shader_stages: RafxShaderStageFlags::FRAGMENT,
+            name: Some("texture".to_string()),
+            ..Default::default()
+        };
+
+        let shader_module = resources
+            .get_or_create_shader_module(&RafxShaderPackage::default(), None)
+            .unwrap();
+        let entry_point = ReflectedEntryPoint {
+            rafx_api_reflection: RafxShaderStageReflection {
+                shader_stage: RafxShaderStageFlags::FRAGMENT,
+                resources: vec![shader_resource.clone()],
+                compute_threads_per_group: None,
+                entry_point_name: "main".to_string(),
+            },
+            descriptor_set_layouts: Default::default(),
+            vertex_inputs: Default::default(),
+        };
+        let shader = resources
+            .get_or_create_shader(&[shader_module], &[&entry_point])
+            .unwrap();
+        let root_signature = resources
+            .get_or_create_root_signature(&[shader], &[], &[])
+            .unwrap();
+
+        resources
+            .get_or_create_descriptor_set_layout(
+                &root_signature,
+                0,
+                &DescriptorSetLayout {
+                    bindings: vec![DescriptorSetLayoutBinding {
+                        resource: shader_resource,
+                        ..Default::default()
+                    }],
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_release_idle_chunks() {
+        let mut api = RafxApi::new_empty(&Default::default(), &Default::default()).unwrap();
+        {
+            let device_context = api.device_context();
+            let resources = ResourceLookupSet::new(&device_context, MAX_FRAMES_IN_FLIGHT as u32);
+            let descriptor_set_layout = create_descriptor_set_layout(&resources);
+            let write_set = create_uninitialized_write_set_for_layout(
+                &descriptor_set_layout.get_raw().descriptor_set_layout_def,
+            );
+
+            let trim_config = DescriptorSetPoolTrimConfig {
+                release_idle_chunks_after_frames: Some(2),
+                ..Default::default()
+            };
+            let mut pool = ManagedDescriptorSetPool::new(
+                &device_context,
+                &Default::default(),
+                descriptor_set_layout,
+            );
+
+            // Fill two chunks and start a third
+            let mut descriptor_sets: Vec<_> = (0..MAX_DESCRIPTOR_SETS_PER_POOL * 2 + 1)
+                .map(|_| {
+                    pool.insert_with_write_set(&device_context, &trim_config, write_set.clone())
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(pool.chunk_count(), 3);
+            assert_eq!(
+                pool.allocated_count(),
+                MAX_DESCRIPTOR_SETS_PER_POOL as usize * 2 + 1
+            );
+
+            // Free everything except a descriptor set in the first chunk
+            descriptor_sets.truncate(1);
+
+            let mut frame_in_flight_index = 0;
+            for frame_index in 0..(MAX_FRAMES_IN_FLIGHT as u64 + 4) {
+                pool.flush_changes(frame_in_flight_index, frame_index, &trim_config)
+                    .unwrap();
+                frame_in_flight_index =
+                    super::super::add_to_frame_in_flight_index(frame_in_flight_index, 1);
+            }
+
+            // The idle chunks are released and the chunk still in use is kept
+            assert_eq!(pool.allocated_count(), 1);
+            assert_eq!(pool.chunk_count(), 1);
+            assert_eq!(pool.released_chunk_count(), 2);
+            assert_eq!(
+                pool.fragmentation(),
+                1.0 - 1.0 / MAX_DESCRIPTOR_SETS_PER_POOL as f32
+            );
+
+            // Released chunks are recreated when more descriptor sets are needed
+            descriptor_sets.extend((0..MAX_DESCRIPTOR_SETS_PER_POOL).map(|_| {
+                pool.insert_with_write_set(&device_context, &trim_config, write_set.clone())
+                    .unwrap()
+            }));
+            assert_eq!(pool.chunk_count(), 2);
+
+            std::mem::drop(descriptor_sets);
+            pool.destroy().unwrap();
+            std::mem::drop(pool);
+            resources.destroy().unwrap();
+        }
+        api.destroy().unwrap();
+    }
+
+    // Returns the number of descriptor sets allocated from each chunk after filling three chunks,
+    // freeing all but 1, MAX - 2 and 2 of their descriptor sets and allocating `new_count` more
+    fn allocate_into_fragmented_pool(
+        packing_threshold: f32,
+        new_count: u32,
+    ) -> Vec<u32> {
+        let mut api = RafxApi::new_empty(&Default::default(), &Default::default()).unwrap();
+        let chunk_allocated_counts = {
+            let device_context = api.device_context();
+            let resources = ResourceLookupSet::new(&device_context, MAX_FRAMES_IN_FLIGHT as u32);
+            let descriptor_set_layout = create_descriptor_set_layout(&resources);
+            let write_set = create_uninitialized_write_set_for_layout(
+                &descriptor_set_layout.get_raw().descriptor_set_layout_def,
+            );
+
+            // Chunks are never released so that only packing affects where sets are allocated
+            let trim_config = DescriptorSetPoolTrimConfig {
+                release_idle_chunks_after_frames: None,
+                packing_threshold,
+            };
+            let mut pool = ManagedDescriptorSetPool::new(
+                &device_context,
+                &Default::default(),
+                descriptor_set_layout,
+            );
+
+            let descriptor_sets: Vec<_> = (0..MAX_DESCRIPTOR_SETS_PER_POOL * 3)
+                .map(|_| {
+                    pool.insert_with_write_set(&device_context, &trim_config, write_set.clone())
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(pool.chunk_count(), 3);
+
+            let kept_counts = [1, MAX_DESCRIPTOR_SETS_PER_POOL - 2, 2];
+            let mut descriptor_sets: Vec<_> = descriptor_sets
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| {
+                    let index = *index as u32;
+                    index % MAX_DESCRIPTOR_SETS_PER_POOL
+                        < kept_counts[(index / MAX_DESCRIPTOR_SETS_PER_POOL) as usize]
+                })
+                .map(|(_, descriptor_set)| descriptor_set)
+                .collect();
+
+            let mut frame_in_flight_index = 0;
+            for frame_index in 0..(MAX_FRAMES_IN_FLIGHT as u64 + 1) {
+                pool.flush_changes(frame_in_flight_index, frame_index, &trim_config)
+                    .unwrap();
+                frame_in_flight_index =
+                    super::super::add_to_frame_in_flight_index(frame_in_flight_index, 1);
+            }
+            assert_eq!(
+                pool.allocated_count(),
+                MAX_DESCRIPTOR_SETS_PER_POOL as usize + 1
+            );
+
+            descriptor_sets.extend((0..new_count).map(|_| {
+                pool.insert_with_write_set(&device_context, &trim_config, write_set.clone())
+                    .unwrap()
+            }));
+
+            // Packing never moves existing descriptor sets or needs new chunks
+            assert_eq!(pool.chunk_count(), 3);
+            let chunk_allocated_counts = pool
+                .chunk_slots
+                .iter()
+                .map(|slot| slot.allocated_count)
+                .collect();
+
+            std::mem::drop(descriptor_sets);
+            pool.destroy().unwrap();
+            std::mem::drop(pool);
+            resources.destroy().unwrap();
+            chunk_allocated_counts
+        };
+        api.destroy().unwrap();
+        chunk_allocated_counts
+    }
+
+    #[test]
+    fn test_pack_into_fullest_chunks() {
+        let max = MAX_DESCRIPTOR_SETS_PER_POOL;
+
+        // Two thirds of the pool is unused, so new sets fill the fullest chunk first and then the
+        // next fullest, leaving the sparse first chunk to drain
+        assert_eq!(allocate_into_fragmented_pool(0.5, 3), vec![1, max, 3]);
+
+        // Without packing, new sets go into the first chunk with space
+        assert_eq!(allocate_into_fragmented_pool(1.0, 3), vec![4, max - 2, 2]);
+    }
+}
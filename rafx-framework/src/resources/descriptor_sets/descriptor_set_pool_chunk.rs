@@ -11,6 +11,7 @@ use crate::resources::descriptor_sets::DescriptorSetBindingKey;
 use crate::{
     BufferResource, DescriptorSetArrayPoolAllocator, DescriptorSetBindings,
     DescriptorSetLayoutResource, ImageViewResource, ResourceArc, ResourceDropSink,
+    ResourceMemoryTracker,
 };
 use fnv::FnvHashMap;
 use rafx_api::{
@@ -485,6 +486,7 @@ impl ManagedDescriptorSetPoolChunk {
     #[profiling::function]
     pub(super) fn new(
         device_context: &RafxDeviceContext,
+        memory_tracker: &ResourceMemoryTracker,
         buffer_info: &[DescriptorSetPoolRequiredBufferInfo],
         descriptor_set_layout: &ResourceArc<DescriptorSetLayoutResource>,
         allocator: &mut DescriptorSetArrayPoolAllocator,
@@ -492,7 +494,7 @@ impl ManagedDescriptorSetPoolChunk {
         let mut descriptor_set_array = allocator.allocate_pool()?;
 
         // Now allocate all the buffers that act as backing-stores for descriptor sets
-        let buffers = DescriptorLayoutBufferSet::new(device_context, buffer_info)?;
+        let buffers = DescriptorLayoutBufferSet::new(device_context, memory_tracker, buffer_info)?;
 
         // For every binding/buffer set
         for (binding_key, binding_buffers) in &buffers.buffer_sets {
@@ -540,6 +542,19 @@ impl ManagedDescriptorSetPoolChunk {
         }
     }
 
+    // Same as destroy(), but the descriptor set array is dropped instead of being reused. This is
+    // used when trimming idle chunks so that the memory is actually released.
+    pub(super) fn release(
+        &mut self,
+        pool_allocator: &mut DescriptorSetArrayPoolAllocator,
+        buffer_drop_sink: &mut ResourceDropSink<RafxBuffer>,
+    ) {
+        pool_allocator.destroy_pool(self.descriptor_set_array.take().unwrap());
+        for (_, buffer_set) in self.buffers.buffer_sets.drain() {
+            buffer_drop_sink.retire(buffer_set.buffer);
+        }
+    }
+
     pub(super) fn get_writer(
         &mut self,
         slab_key: RawSlabKey<ManagedDescriptorSet>,
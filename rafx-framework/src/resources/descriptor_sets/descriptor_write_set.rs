@@ -107,6 +107,11 @@ pub fn create_uninitialized_write_set_for_layout(
 ) -> DescriptorSetWriteSet {
     let mut write_set = DescriptorSetWriteSet::default();
     for binding in &layout.bindings {
+        // Bindless arrays are partially bound and written by BindlessTable
+        if binding.resource.resource_type.is_bindless() {
+            continue;
+        }
+
         for array_index in 0..binding.resource.element_count_normalized() {
             let element_write = DescriptorSetElementWrite {
                 has_immutable_sampler: binding.immutable_samplers.is_some(),
@@ -40,6 +40,7 @@ pub use descriptor_set_allocator::DescriptorSetAllocator;
 pub use descriptor_set_allocator::DescriptorSetAllocatorMetrics;
 pub use descriptor_set_allocator::DescriptorSetInitializer;
 pub use descriptor_set_allocator::DescriptorSetPoolMetrics;
+pub use descriptor_set_allocator::DescriptorSetPoolTrimConfig;
 
 mod descriptor_set_allocator_manager;
 use crate::{DescriptorSetLayoutResource, ResourceArc};
@@ -62,11 +63,9 @@ pub struct DescriptorSetWriteBuffer {
     pub elements: FnvHashMap<DescriptorSetElementKey, Vec<u8>>,
 }
 
-// Slab keys to identify descriptors can carry a payload. Anything we'd want to store per descriptor
-// set can go here, but don't have anything yet
-struct ManagedDescriptorSet {
-    //write_set: DescriptorSetWriteSet,
-}
+// Marker type for the slab keys that identify descriptor sets within a pool. Nothing is stored per
+// descriptor set yet
+enum ManagedDescriptorSet {}
 
 // We need to delay dropping descriptor sets for MAX_FRAMES_IN_FLIGHT frames
 type FrameInFlightIndex = u32;
@@ -104,7 +103,7 @@ pub fn what_to_bind(element_write: &DescriptorSetElementWrite) -> WhatToBind {
             what.bind_samplers = !element_write.has_immutable_sampler;
             what.bind_images = true;
         }
-        RafxResourceType::TEXTURE => {
+        RafxResourceType::TEXTURE | RafxResourceType::INPUT_ATTACHMENT => {
             what.bind_images = true;
         }
         RafxResourceType::UNIFORM_BUFFER => {
@@ -2,8 +2,8 @@ use super::ResourceId;
 use crate::resources::resource_arc::ResourceWithHash;
 use crate::resources::resource_lookup::ImageResource;
 use crate::resources::ResourceArc;
-use crate::ResourceDropSink;
 use crate::{BufferResource, ImageViewResource};
+use crate::{ResourceDropSink, ResourceMemoryCategory, ResourceMemoryTracker};
 use crossbeam_channel::{Receiver, Sender};
 use rafx_api::RafxTexture;
 use rafx_api::{RafxBuffer, RafxDeviceContext, RafxResult, RafxTextureBindType};
@@ -214,6 +214,7 @@ where
 // This is for providing per-frame allocation where the resource does not need to be
 pub struct DynResourceAllocatorSet {
     pub device_context: RafxDeviceContext,
+    pub memory_tracker: ResourceMemoryTracker,
     pub images: DynResourceAllocator<ImageResource>,
     pub image_views: DynResourceAllocator<ImageViewResource>,
     pub buffers: DynResourceAllocator<BufferResource>,
@@ -224,9 +225,16 @@ impl DynResourceAllocatorSet {
         &self,
         image: RafxTexture,
     ) -> ResourceArc<ImageResource> {
+        let memory_allocation = self.memory_tracker.track(
+            ResourceMemoryCategory::Dynamic,
+            image.allocation_size_in_bytes(),
+        );
+
         let image_resource = ImageResource {
             image_key: None,
             image,
+            bindless_index: None,
+            memory_allocation: Some(Arc::new(memory_allocation)),
         };
         self.images.insert(image_resource)
     }
@@ -257,9 +265,16 @@ impl DynResourceAllocatorSet {
         &self,
         buffer: RafxBuffer,
     ) -> ResourceArc<BufferResource> {
+        let memory_allocation = self.memory_tracker.track(
+            ResourceMemoryCategory::Dynamic,
+            buffer.allocation_size_in_bytes(),
+        );
+
         let buffer_resource = BufferResource {
             buffer_key: None,
             buffer: Arc::new(buffer),
+            bindless_index: None,
+            memory_allocation: Some(Arc::new(memory_allocation)),
         };
 
         self.buffers.insert(buffer_resource)
@@ -275,6 +290,7 @@ pub struct ResourceMetrics {
 
 pub struct DynResourceAllocatorSetProvider {
     pub device_context: RafxDeviceContext,
+    pub memory_tracker: ResourceMemoryTracker,
     pub images: DynResourceAllocatorProvider<ImageResource>,
     pub image_views: DynResourceAllocatorProvider<ImageViewResource>,
     pub buffers: DynResourceAllocatorProvider<BufferResource>,
@@ -284,6 +300,7 @@ impl DynResourceAllocatorSetProvider {
     pub fn get_allocator(&self) -> DynResourceAllocatorSet {
         DynResourceAllocatorSet {
             device_context: self.device_context.clone(),
+            memory_tracker: self.memory_tracker.clone(),
             images: self.images.create_allocator(),
             image_views: self.image_views.create_allocator(),
             buffers: self.buffers.create_allocator(),
@@ -293,6 +310,7 @@ impl DynResourceAllocatorSetProvider {
 
 pub struct DynResourceAllocatorSetManager {
     pub device_context: RafxDeviceContext,
+    pub memory_tracker: ResourceMemoryTracker,
     pub images: DynResourceAllocatorManager<ImageResource>,
     pub image_views: DynResourceAllocatorManager<ImageViewResource>,
     pub buffers: DynResourceAllocatorManager<BufferResource>,
@@ -302,9 +320,11 @@ impl DynResourceAllocatorSetManager {
     pub fn new(
         device_context: &RafxDeviceContext,
         max_frames_in_flight: u32,
+        memory_tracker: &ResourceMemoryTracker,
     ) -> Self {
         DynResourceAllocatorSetManager {
             device_context: device_context.clone(),
+            memory_tracker: memory_tracker.clone(),
             images: DynResourceAllocatorManager::new(max_frames_in_flight),
             image_views: DynResourceAllocatorManager::new(max_frames_in_flight),
             buffers: DynResourceAllocatorManager::new(max_frames_in_flight),
@@ -314,6 +334,7 @@ impl DynResourceAllocatorSetManager {
     pub fn create_allocator_provider(&self) -> DynResourceAllocatorSetProvider {
         DynResourceAllocatorSetProvider {
             device_context: self.device_context.clone(),
+            memory_tracker: self.memory_tracker.clone(),
             images: self.images.create_allocator_provider(),
             image_views: self.image_views.create_allocator_provider(),
             buffers: self.buffers.create_allocator_provider(),
@@ -323,6 +344,7 @@ impl DynResourceAllocatorSetManager {
     pub fn get_allocator(&self) -> DynResourceAllocatorSet {
         DynResourceAllocatorSet {
             device_context: self.device_context.clone(),
+            memory_tracker: self.memory_tracker.clone(),
             images: self.images.create_allocator(),
             image_views: self.image_views.create_allocator(),
             buffers: self.buffers.create_allocator(),
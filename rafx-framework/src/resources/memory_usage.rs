@@ -0,0 +1,148 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// What a tracked allocation is used for. Each category has its own byte total in
+/// `ResourceMemoryMetrics`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceMemoryCategory {
+    /// Intermediate images and buffers created by the render graph
+    RenderGraphTransient,
+    /// Images inserted into the `ResourceLookupSet` (textures loaded from assets, etc.)
+    AssetImages,
+    /// Buffers inserted into the `ResourceLookupSet` (meshes loaded from assets, etc.)
+    AssetBuffers,
+    /// Images and buffers created through a `DynResourceAllocatorSet`
+    Dynamic,
+    /// Buffers that back descriptors that are written by value (i.e. uniform data)
+    DescriptorSetBuffers,
+}
+
+const RESOURCE_MEMORY_CATEGORY_COUNT: usize = 5;
+
+impl ResourceMemoryCategory {
+    fn index(self) -> usize {
+        match self {
+            ResourceMemoryCategory::RenderGraphTransient => 0,
+            ResourceMemoryCategory::AssetImages => 1,
+            ResourceMemoryCategory::AssetBuffers => 2,
+            ResourceMemoryCategory::Dynamic => 3,
+            ResourceMemoryCategory::DescriptorSetBuffers => 4,
+        }
+    }
+}
+
+/// Bytes of GPU memory currently held by resources, per category
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceMemoryMetrics {
+    pub render_graph_transient_bytes: u64,
+    pub asset_image_bytes: u64,
+    pub asset_buffer_bytes: u64,
+    pub dynamic_bytes: u64,
+    pub descriptor_set_buffer_bytes: u64,
+}
+
+impl ResourceMemoryMetrics {
+    pub fn total_bytes(&self) -> u64 {
+        self.render_graph_transient_bytes
+            + self.asset_image_bytes
+            + self.asset_buffer_bytes
+            + self.dynamic_bytes
+            + self.descriptor_set_buffer_bytes
+    }
+}
+
+#[derive(Default)]
+struct ResourceMemoryTrackerInner {
+    allocated_bytes: [AtomicU64; RESOURCE_MEMORY_CATEGORY_COUNT],
+}
+
+/// Keeps a running total of the memory allocated for resources in each `ResourceMemoryCategory`.
+/// Sizes come from `RafxBuffer::allocation_size_in_bytes()` and
+/// `RafxTexture::allocation_size_in_bytes()`. Memory shared by aliased resources is counted once
+/// per resource, in proportion to what each resource requires.
+#[derive(Clone, Default)]
+pub struct ResourceMemoryTracker {
+    inner: Arc<ResourceMemoryTrackerInner>,
+}
+
+impl std::fmt::Debug for ResourceMemoryTracker {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("ResourceMemoryTracker")
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+impl ResourceMemoryTracker {
+    /// Adds the allocation to the category's total until the returned value is dropped
+    pub fn track(
+        &self,
+        category: ResourceMemoryCategory,
+        size_in_bytes: u64,
+    ) -> ResourceMemoryAllocation {
+        self.inner.allocated_bytes[category.index()].fetch_add(size_in_bytes, Ordering::Relaxed);
+        ResourceMemoryAllocation {
+            tracker: self.clone(),
+            category,
+            size_in_bytes,
+        }
+    }
+
+    pub fn allocated_bytes(
+        &self,
+        category: ResourceMemoryCategory,
+    ) -> u64 {
+        self.inner.allocated_bytes[category.index()].load(Ordering::Relaxed)
+    }
+
+    pub fn metrics(&self) -> ResourceMemoryMetrics {
+        ResourceMemoryMetrics {
+            render_graph_transient_bytes: self
+                .allocated_bytes(ResourceMemoryCategory::RenderGraphTransient),
+            asset_image_bytes: self.allocated_bytes(ResourceMemoryCategory::AssetImages),
+            asset_buffer_bytes: self.allocated_bytes(ResourceMemoryCategory::AssetBuffers),
+            dynamic_bytes: self.allocated_bytes(ResourceMemoryCategory::Dynamic),
+            descriptor_set_buffer_bytes: self
+                .allocated_bytes(ResourceMemoryCategory::DescriptorSetBuffers),
+        }
+    }
+}
+
+/// An allocation counted by a `ResourceMemoryTracker`. It is removed from the total when dropped.
+pub struct ResourceMemoryAllocation {
+    tracker: ResourceMemoryTracker,
+    category: ResourceMemoryCategory,
+    size_in_bytes: u64,
+}
+
+impl ResourceMemoryAllocation {
+    pub fn category(&self) -> ResourceMemoryCategory {
+        self.category
+    }
+
+    pub fn size_in_bytes(&self) -> u64 {
+        self.size_in_bytes
+    }
+}
+
+impl std::fmt::Debug for ResourceMemoryAllocation {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.debug_struct("ResourceMemoryAllocation")
+            .field("category", &self.category)
+            .field("size_in_bytes", &self.size_in_bytes)
+            .finish()
+    }
+}
+
+impl Drop for ResourceMemoryAllocation {
+    fn drop(&mut self) {
+        self.tracker.inner.allocated_bytes[self.category.index()]
+            .fetch_sub(self.size_in_bytes, Ordering::Relaxed);
+    }
+}
@@ -13,6 +13,7 @@ pub use resource_lookup::GraphicsPipelineResource;
 pub use resource_lookup::ImageKey;
 pub use resource_lookup::ImageResource;
 pub use resource_lookup::ImageViewResource;
+pub use resource_lookup::MaterialPassHash;
 pub use resource_lookup::MaterialPassResource;
 pub use resource_lookup::MaterialPassVertexInput;
 pub use resource_lookup::ResourceHash;
@@ -21,6 +22,17 @@ pub use resource_lookup::SamplerResource;
 pub use resource_lookup::ShaderModuleHash;
 pub use resource_lookup::ShaderModuleResource;
 
+mod bindless_table;
+pub use bindless_table::BindlessIndex;
+pub use bindless_table::BindlessIndexArc;
+pub use bindless_table::BindlessTable;
+
+mod memory_usage;
+pub use memory_usage::ResourceMemoryAllocation;
+pub use memory_usage::ResourceMemoryCategory;
+pub use memory_usage::ResourceMemoryMetrics;
+pub use memory_usage::ResourceMemoryTracker;
+
 mod dyn_resources;
 pub use dyn_resources::DynResourceAllocatorSet;
 pub use dyn_resources::DynResourceAllocatorSetProvider;
@@ -35,6 +47,7 @@ pub use descriptor_sets::DescriptorSetBindings;
 pub use descriptor_sets::DescriptorSetInitializer;
 pub use descriptor_sets::DescriptorSetLayout;
 pub use descriptor_sets::DescriptorSetLayoutBinding;
+pub use descriptor_sets::DescriptorSetPoolTrimConfig;
 pub use descriptor_sets::DescriptorSetWriteSet;
 pub use descriptor_sets::DescriptorSetWriter;
 pub use descriptor_sets::DescriptorSetWriterContext;
@@ -56,6 +69,9 @@ mod pipeline_cache;
 pub use pipeline_cache::GraphicsPipelineCache;
 pub use pipeline_cache::GraphicsPipelineRenderTargetMeta;
 pub use pipeline_cache::GraphicsPipelineRenderTargetMetaHash;
+pub use pipeline_cache::PipelineCacheData;
+pub use pipeline_cache::RecordedGraphicsPipeline;
+pub use pipeline_cache::RecordedVertexDataLayout;
 
 mod vertex_data;
 pub use vertex_data::VertexCopyError;
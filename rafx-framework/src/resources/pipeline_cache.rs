@@ -2,20 +2,26 @@ use crate::render_features::{
     RenderPhase, RenderPhaseIndex, RenderRegistry, MAX_RENDER_PHASE_COUNT,
 };
 use crate::resources::resource_arc::{ResourceId, WeakResourceArc};
-use crate::resources::vertex_data::{VertexDataSetLayout, VertexDataSetLayoutHash};
-use crate::{GraphicsPipelineResource, MaterialPassResource, ResourceArc, ResourceLookupSet};
+use crate::resources::vertex_data::{
+    VertexDataLayout, VertexDataSetLayout, VertexDataSetLayoutHash, VertexMember,
+};
+use crate::{
+    GraphicsPipelineResource, MaterialPassHash, MaterialPassResource, ResourceArc,
+    ResourceLookupSet,
+};
 use fnv::{FnvHashMap, FnvHashSet, FnvHasher};
 use rafx_api::{
-    RafxFormat, RafxResult, RafxSampleCount, RafxVertexLayout, RafxVertexLayoutAttribute,
-    RafxVertexLayoutBuffer,
+    RafxFormat, RafxPrimitiveTopology, RafxResult, RafxSampleCount, RafxSubpassLayout,
+    RafxVertexAttributeRate, RafxVertexLayout, RafxVertexLayoutAttribute, RafxVertexLayoutBuffer,
 };
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
 
 //TODO: Allow caching for N frames
 //TODO: Return a kind of ResourceArc for a cached pipeline. Allow dropping after N frames pass with
 // nothing request/using it
-//TODO: vulkan pipeline cache object
 
 //TODO: Remove Serialize/Deserialize
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -23,6 +29,8 @@ pub struct GraphicsPipelineRenderTargetMeta {
     color_formats: Vec<RafxFormat>,
     depth_stencil_format: Option<RafxFormat>,
     sample_count: RafxSampleCount,
+    // Set if the pipeline will be used in a renderpass with multiple subpasses
+    subpass_layout: Option<RafxSubpassLayout>,
     hash: GraphicsPipelineRenderTargetMetaHash,
 }
 
@@ -31,16 +39,19 @@ impl GraphicsPipelineRenderTargetMeta {
         color_formats: Vec<RafxFormat>,
         depth_stencil_format: Option<RafxFormat>,
         sample_count: RafxSampleCount,
+        subpass_layout: Option<RafxSubpassLayout>,
     ) -> Self {
         let hash = GraphicsPipelineRenderTargetMetaHash::new(
             &color_formats,
             depth_stencil_format,
             sample_count,
+            subpass_layout.as_ref(),
         );
         GraphicsPipelineRenderTargetMeta {
             color_formats,
             depth_stencil_format,
             sample_count,
+            subpass_layout,
             hash,
         }
     }
@@ -57,6 +68,10 @@ impl GraphicsPipelineRenderTargetMeta {
         self.sample_count
     }
 
+    pub fn subpass_layout(&self) -> Option<&RafxSubpassLayout> {
+        self.subpass_layout.as_ref()
+    }
+
     pub fn render_target_meta_hash(&self) -> GraphicsPipelineRenderTargetMetaHash {
         self.hash
     }
@@ -69,16 +84,204 @@ impl GraphicsPipelineRenderTargetMetaHash {
         color_formats: &[RafxFormat],
         depth_stencil_format: Option<RafxFormat>,
         sample_count: RafxSampleCount,
+        subpass_layout: Option<&RafxSubpassLayout>,
     ) -> Self {
         let mut hasher = FnvHasher::default();
         color_formats.hash(&mut hasher);
         depth_stencil_format.hash(&mut hasher);
         sample_count.hash(&mut hasher);
+        subpass_layout.hash(&mut hasher);
         let hash = hasher.finish();
         GraphicsPipelineRenderTargetMetaHash(hash)
     }
 }
 
+/// A vertex buffer binding of a `VertexDataSetLayout` in a `RecordedGraphicsPipeline`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedVertexDataLayout {
+    pub vertex_stride: usize,
+    pub vertex_rate: RafxVertexAttributeRate,
+    pub members: Vec<VertexMember>,
+}
+
+/// A graphics pipeline created by the `GraphicsPipelineCache`, described in a form that can be
+/// saved and used in a later run to create the same pipeline before it is first requested.
+/// Material passes and render phases are identified by content hash and name because resource
+/// IDs and phase indices are not stable across runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedGraphicsPipeline {
+    pub render_phase: String,
+    pub material_pass: MaterialPassHash,
+    pub color_formats: Vec<RafxFormat>,
+    pub depth_stencil_format: Option<RafxFormat>,
+    pub sample_count: RafxSampleCount,
+    pub subpass_layout: Option<RafxSubpassLayout>,
+    pub vertex_bindings: Vec<RecordedVertexDataLayout>,
+    pub primitive_topology: RafxPrimitiveTopology,
+}
+
+impl RecordedGraphicsPipeline {
+    fn new(
+        render_phase: &str,
+        material_pass: MaterialPassHash,
+        render_target_meta: &GraphicsPipelineRenderTargetMeta,
+        vertex_data_set_layout: &VertexDataSetLayout,
+    ) -> Self {
+        let vertex_bindings = vertex_data_set_layout
+            .bindings()
+            .iter()
+            .map(|binding| {
+                let mut members: Vec<_> = binding
+                    .members()
+                    .iter()
+                    .map(|(semantic, member)| VertexMember {
+                        semantic: semantic.clone(),
+                        format: member.format,
+                        byte_offset: member.byte_offset,
+                    })
+                    .collect();
+                members.sort_by(|a, b| a.semantic.cmp(&b.semantic));
+
+                RecordedVertexDataLayout {
+                    vertex_stride: binding.vertex_stride(),
+                    vertex_rate: binding.vertex_rate(),
+                    members,
+                }
+            })
+            .collect();
+
+        RecordedGraphicsPipeline {
+            render_phase: render_phase.to_string(),
+            material_pass,
+            color_formats: render_target_meta.color_formats().to_vec(),
+            depth_stencil_format: render_target_meta.depth_stencil_format(),
+            sample_count: render_target_meta.sample_count(),
+            subpass_layout: render_target_meta.subpass_layout().cloned(),
+            vertex_bindings,
+            primitive_topology: vertex_data_set_layout.primitive_topology(),
+        }
+    }
+
+    pub fn render_target_meta(&self) -> GraphicsPipelineRenderTargetMeta {
+        GraphicsPipelineRenderTargetMeta::new(
+            self.color_formats.clone(),
+            self.depth_stencil_format,
+            self.sample_count,
+            self.subpass_layout.clone(),
+        )
+    }
+
+    pub fn vertex_data_set_layout(&self) -> RafxResult<VertexDataSetLayout> {
+        // VertexDataLayout::new and VertexDataSetLayout::new panic on invalid members, so validate
+        // data that may have been loaded from disk first
+        let mut semantics = FnvHashSet::default();
+        let mut bindings = Vec::with_capacity(self.vertex_bindings.len());
+        for binding in &self.vertex_bindings {
+            for member in &binding.members {
+                let size = member.format.block_or_pixel_size_in_bytes() as usize;
+                if member.byte_offset + size > binding.vertex_stride
+                    || !semantics.insert(&member.semantic)
+                {
+                    Err(format!(
+                        "Recorded vertex member {:?} is not valid for a vertex stride of {} or is used more than once",
+                        member, binding.vertex_stride
+                    ))?;
+                }
+            }
+
+            bindings.push(VertexDataLayout::new(
+                binding.vertex_stride,
+                binding.vertex_rate,
+                &binding.members,
+            ));
+        }
+
+        Ok(VertexDataSetLayout::new(bindings, self.primitive_topology))
+    }
+}
+
+/// Everything needed to avoid compiling pipelines the first time they are used: the device's
+/// pipeline cache data and the graphics pipelines recorded by the `GraphicsPipelineCache`. See
+/// `ResourceManager::save_pipeline_cache` and `ResourceManager::load_pipeline_cache`.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineCacheData {
+    /// From `RafxDeviceContext::pipeline_cache_data`
+    pub device_pipeline_cache_data: Vec<u8>,
+    pub recorded_graphics_pipelines: Vec<RecordedGraphicsPipeline>,
+}
+
+impl PipelineCacheData {
+    // Layout: magic, version (u32), length of the JSON-encoded recorded pipelines (u64), the
+    // recorded pipelines, and then the device pipeline cache data
+    const MAGIC: [u8; 8] = *b"RAFXPCD\0";
+    const VERSION: u32 = 1;
+    const HEADER_SIZE: usize = 8 + 4 + 8;
+
+    pub fn to_bytes(&self) -> RafxResult<Vec<u8>> {
+        let recorded_graphics_pipelines = serde_json::to_vec(&self.recorded_graphics_pipelines)
+            .map_err(|e| format!("Failed to serialize recorded graphics pipelines: {}", e))?;
+
+        let mut data = Vec::with_capacity(
+            Self::HEADER_SIZE
+                + recorded_graphics_pipelines.len()
+                + self.device_pipeline_cache_data.len(),
+        );
+        data.extend_from_slice(&Self::MAGIC);
+        data.extend_from_slice(&Self::VERSION.to_le_bytes());
+        data.extend_from_slice(&(recorded_graphics_pipelines.len() as u64).to_le_bytes());
+        data.extend_from_slice(&recorded_graphics_pipelines);
+        data.extend_from_slice(&self.device_pipeline_cache_data);
+        Ok(data)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> RafxResult<Self> {
+        if data.len() < Self::HEADER_SIZE || data[0..8] != Self::MAGIC {
+            Err("Pipeline cache data is not in the expected format")?;
+        }
+
+        let version = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        if version != Self::VERSION {
+            Err(format!(
+                "Pipeline cache data version is {} but expected {}",
+                version,
+                Self::VERSION
+            ))?;
+        }
+
+        let recorded_graphics_pipelines_size =
+            u64::from_le_bytes(data[12..20].try_into().unwrap()) as usize;
+        let data = &data[Self::HEADER_SIZE..];
+        if recorded_graphics_pipelines_size > data.len() {
+            Err("Pipeline cache data is truncated")?;
+        }
+
+        let recorded_graphics_pipelines =
+            serde_json::from_slice(&data[..recorded_graphics_pipelines_size])
+                .map_err(|e| format!("Failed to deserialize recorded graphics pipelines: {}", e))?;
+
+        Ok(PipelineCacheData {
+            device_pipeline_cache_data: data[recorded_graphics_pipelines_size..].to_vec(),
+            recorded_graphics_pipelines,
+        })
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct RecordedGraphicsPipelineKey {
+    render_phase_index: RenderPhaseIndex,
+    material_pass: MaterialPassHash,
+    render_target_meta_hash: GraphicsPipelineRenderTargetMetaHash,
+    vertex_data_set_layout: VertexDataSetLayoutHash,
+}
+
+// A recorded pipeline that precache_pipelines_for_all_phases has not created yet
+struct PendingRecordedGraphicsPipeline {
+    render_phase_index: RenderPhaseIndex,
+    material_pass: MaterialPassHash,
+    render_target_meta: GraphicsPipelineRenderTargetMeta,
+    vertex_data_set_layout: VertexDataSetLayout,
+}
+
 #[derive(PartialEq, Eq, Hash)]
 struct CachedGraphicsPipelineKey {
     material_pass: ResourceId,
@@ -108,6 +311,13 @@ pub struct GraphicsPipelineCacheInner {
 
     cached_pipelines: FnvHashMap<CachedGraphicsPipelineKey, CachedGraphicsPipeline>,
 
+    // Every pipeline created by the cache or added with add_recorded_graphics_pipelines
+    recorded_pipelines: FnvHashMap<RecordedGraphicsPipelineKey, RecordedGraphicsPipeline>,
+    pending_recorded_pipelines: Vec<PendingRecordedGraphicsPipeline>,
+    // Set when a material pass is registered or a recorded pipeline is added, since either may
+    // allow more pending pipelines to be created
+    pending_recorded_pipelines_dirty: bool,
+
     current_frame_index: u64,
     frames_to_persist: u64,
 
@@ -166,6 +376,9 @@ impl GraphicsPipelineCache {
             render_target_meta_assignments,
             material_pass_assignments,
             cached_pipelines: Default::default(),
+            recorded_pipelines: Default::default(),
+            pending_recorded_pipelines: Default::default(),
+            pending_recorded_pipelines_dirty: false,
             current_frame_index: 0,
             frames_to_persist: DEFAULT_FRAMES_TO_PERSIST,
             #[cfg(debug_assertions)]
@@ -357,6 +570,7 @@ impl GraphicsPipelineCache {
 
         inner.material_pass_assignments[render_phase_index as usize]
             .insert(material_pass.get_hash(), material_pass.downgrade());
+        inner.pending_recorded_pipelines_dirty = true;
         //TODO: Do we need to mark this as a dirty material that may need to build additional
         // pipelines?
     }
@@ -436,151 +650,320 @@ impl GraphicsPipelineCache {
             })
             .or_else(|| {
                 if create_if_missing {
-                    log::debug!("Creating graphics pipeline");
-                    profiling::scope!("Create Pipeline");
-                    //let mut binding_descriptions = Vec::default();
-                    let mut vertex_layout_buffers =
-                        Vec::with_capacity(vertex_data_set_layout.bindings().len());
-                    for binding in vertex_data_set_layout.bindings() {
-                        vertex_layout_buffers.push(RafxVertexLayoutBuffer {
-                            rate: binding.vertex_rate(),
-                            stride: binding.vertex_stride() as u32,
-                        })
-                    }
+                    let pipeline = Self::create_graphics_pipeline(
+                        inner,
+                        key,
+                        material_pass,
+                        render_target_meta,
+                        vertex_data_set_layout,
+                    );
 
-                    //let mut attribute_descriptions = Vec::default();
-                    let mut vertex_layout_attributes =
-                        Vec::with_capacity(material_pass.get_raw().vertex_inputs.len());
-
-                    for vertex_input in &*material_pass.get_raw().vertex_inputs {
-                        let member = vertex_data_set_layout
-                            .member(&vertex_input.semantic)
-                            .ok_or_else(|| {
-                                let error_message = format!(
-                                    "Vertex data does not support this material. Missing data {}",
-                                    vertex_input.semantic
-                                );
-                                log::error!("{}", error_message);
-                                log::info!(
-                                    "  required inputs:\n{:#?}",
-                                    material_pass.get_raw().vertex_inputs
-                                );
-                                log::info!(
-                                    "  available inputs:\n{:#?}",
-                                    vertex_data_set_layout.members()
-                                );
-                                error_message
-                            })
-                            .ok()?;
-
-                        vertex_layout_attributes.push(RafxVertexLayoutAttribute {
-                            location: vertex_input.location,
-                            byte_offset: member.byte_offset as u32,
-                            buffer_index: member.binding as u32,
-                            format: member.format,
-                            gl_attribute_name: Some(vertex_input.gl_attribute_name.clone()),
-                        });
+                    if let Some(Ok(_)) = &pipeline {
+                        self.record_graphics_pipeline(
+                            inner,
+                            render_phase_index,
+                            material_pass,
+                            render_target_meta,
+                            vertex_data_set_layout,
+                        );
                     }
 
-                    let vertex_layout = RafxVertexLayout {
-                        attributes: vertex_layout_attributes,
-                        buffers: vertex_layout_buffers,
-                    };
+                    pipeline
+                } else {
+                    None
+                }
+            })
+    }
 
-                    log::trace!("Creating graphics pipeline. Setting up vertex formats:");
-                    log::trace!(
+    fn create_graphics_pipeline(
+        inner: &mut GraphicsPipelineCacheInner,
+        key: CachedGraphicsPipelineKey,
+        material_pass: &ResourceArc<MaterialPassResource>,
+        render_target_meta: &GraphicsPipelineRenderTargetMeta,
+        vertex_data_set_layout: &VertexDataSetLayout,
+    ) -> Option<RafxResult<ResourceArc<GraphicsPipelineResource>>> {
+        log::debug!("Creating graphics pipeline");
+        profiling::scope!("Create Pipeline");
+        //let mut binding_descriptions = Vec::default();
+        let mut vertex_layout_buffers = Vec::with_capacity(vertex_data_set_layout.bindings().len());
+        for binding in vertex_data_set_layout.bindings() {
+            vertex_layout_buffers.push(RafxVertexLayoutBuffer {
+                rate: binding.vertex_rate(),
+                stride: binding.vertex_stride() as u32,
+            })
+        }
+
+        //let mut attribute_descriptions = Vec::default();
+        let mut vertex_layout_attributes =
+            Vec::with_capacity(material_pass.get_raw().vertex_inputs.len());
+
+        for vertex_input in &*material_pass.get_raw().vertex_inputs {
+            let member = vertex_data_set_layout
+                .member(&vertex_input.semantic)
+                .ok_or_else(|| {
+                    let error_message = format!(
+                        "Vertex data does not support this material. Missing data {}",
+                        vertex_input.semantic
+                    );
+                    log::error!("{}", error_message);
+                    log::info!(
                         "  required inputs:\n{:#?}",
                         material_pass.get_raw().vertex_inputs
                     );
-                    log::trace!(
+                    log::info!(
                         "  available inputs:\n{:#?}",
                         vertex_data_set_layout.members()
                     );
+                    error_message
+                })
+                .ok()?;
+
+            vertex_layout_attributes.push(RafxVertexLayoutAttribute {
+                location: vertex_input.location,
+                byte_offset: member.byte_offset as u32,
+                buffer_index: member.binding as u32,
+                format: member.format,
+                gl_attribute_name: Some(vertex_input.gl_attribute_name.clone()),
+            });
+        }
 
-                    #[cfg(debug_assertions)]
-                    {
-                        inner.pipeline_create_count += 1;
-                    }
+        let vertex_layout = RafxVertexLayout {
+            attributes: vertex_layout_attributes,
+            buffers: vertex_layout_buffers,
+        };
 
-                    log::trace!("Create vertex layout {:#?}", vertex_layout);
-                    let pipeline = inner.resource_lookup_set.get_or_create_graphics_pipeline(
-                        &material_pass,
-                        render_target_meta,
-                        vertex_data_set_layout.primitive_topology(),
-                        &vertex_layout,
-                    );
+        log::trace!("Creating graphics pipeline. Setting up vertex formats:");
+        log::trace!(
+            "  required inputs:\n{:#?}",
+            material_pass.get_raw().vertex_inputs
+        );
+        log::trace!(
+            "  available inputs:\n{:#?}",
+            vertex_data_set_layout.members()
+        );
 
-                    if let Ok(pipeline) = pipeline {
-                        inner.cached_pipelines.insert(
-                            key,
-                            CachedGraphicsPipeline {
-                                graphics_pipeline: pipeline.clone(),
-                                //render_target_meta: render_target_meta.clone(),
-                                material_pass_resource: material_pass.downgrade(),
-                            },
-                        );
+        #[cfg(debug_assertions)]
+        {
+            inner.pipeline_create_count += 1;
+        }
 
-                        Some(Ok(pipeline))
-                    } else {
-                        Some(pipeline)
-                    }
-                } else {
-                    None
+        log::trace!("Create vertex layout {:#?}", vertex_layout);
+        let pipeline = inner.resource_lookup_set.get_or_create_graphics_pipeline(
+            material_pass,
+            render_target_meta,
+            vertex_data_set_layout.primitive_topology(),
+            &vertex_layout,
+        );
+
+        if let Ok(pipeline) = pipeline {
+            inner.cached_pipelines.insert(
+                key,
+                CachedGraphicsPipeline {
+                    graphics_pipeline: pipeline.clone(),
+                    //render_target_meta: render_target_meta.clone(),
+                    material_pass_resource: material_pass.downgrade(),
+                },
+            );
+
+            Some(Ok(pipeline))
+        } else {
+            Some(pipeline)
+        }
+    }
+
+    fn record_graphics_pipeline(
+        &self,
+        inner: &mut GraphicsPipelineCacheInner,
+        render_phase_index: RenderPhaseIndex,
+        material_pass: &ResourceArc<MaterialPassResource>,
+        render_target_meta: &GraphicsPipelineRenderTargetMeta,
+        vertex_data_set_layout: &VertexDataSetLayout,
+    ) {
+        let material_pass_hash = material_pass.get_raw().material_pass_key.hash();
+        let key = RecordedGraphicsPipelineKey {
+            render_phase_index,
+            material_pass: material_pass_hash,
+            render_target_meta_hash: render_target_meta.render_target_meta_hash(),
+            vertex_data_set_layout: vertex_data_set_layout.hash(),
+        };
+
+        if inner.recorded_pipelines.contains_key(&key) {
+            return;
+        }
+
+        if let Some(render_phase) = self
+            .render_registry
+            .render_phase_name_from_index(render_phase_index)
+        {
+            inner.recorded_pipelines.insert(
+                key,
+                RecordedGraphicsPipeline::new(
+                    render_phase,
+                    material_pass_hash,
+                    render_target_meta,
+                    vertex_data_set_layout,
+                ),
+            );
+        }
+    }
+
+    /// Returns every graphics pipeline this cache has created, and any added with
+    /// `add_recorded_graphics_pipelines`. Save them and pass them to
+    /// `add_recorded_graphics_pipelines` in a later run to create the pipelines at startup.
+    pub fn recorded_graphics_pipelines(&self) -> Vec<RecordedGraphicsPipeline> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        #[cfg(debug_assertions)]
+        {
+            inner.lock_call_count += 1;
+        }
+
+        inner.recorded_pipelines.values().cloned().collect()
+    }
+
+    /// Queue pipelines recorded in a previous run to be created by
+    /// `precache_pipelines_for_all_phases`. Pipelines for render phases that are not registered,
+    /// or with invalid vertex layouts, are skipped.
+    pub fn add_recorded_graphics_pipelines(
+        &self,
+        recorded_graphics_pipelines: Vec<RecordedGraphicsPipeline>,
+    ) {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        #[cfg(debug_assertions)]
+        {
+            inner.lock_call_count += 1;
+        }
+
+        for recorded in recorded_graphics_pipelines {
+            let render_phase_index = match self
+                .render_registry
+                .render_phase_index_from_name(&recorded.render_phase)
+            {
+                Some(render_phase_index) => render_phase_index,
+                None => {
+                    log::info!(
+                        "Skipping recorded graphics pipeline, render phase {} is not registered",
+                        recorded.render_phase
+                    );
+                    continue;
                 }
-            })
+            };
+
+            let vertex_data_set_layout = match recorded.vertex_data_set_layout() {
+                Ok(vertex_data_set_layout) => vertex_data_set_layout,
+                Err(e) => {
+                    log::warn!("Skipping recorded graphics pipeline: {:?}", e);
+                    continue;
+                }
+            };
+            let render_target_meta = recorded.render_target_meta();
+
+            let key = RecordedGraphicsPipelineKey {
+                render_phase_index,
+                material_pass: recorded.material_pass,
+                render_target_meta_hash: render_target_meta.render_target_meta_hash(),
+                vertex_data_set_layout: vertex_data_set_layout.hash(),
+            };
+
+            if inner.recorded_pipelines.contains_key(&key) {
+                continue;
+            }
+
+            inner
+                .pending_recorded_pipelines
+                .push(PendingRecordedGraphicsPipeline {
+                    render_phase_index,
+                    material_pass: recorded.material_pass,
+                    render_target_meta,
+                    vertex_data_set_layout,
+                });
+            inner.recorded_pipelines.insert(key, recorded);
+            inner.pending_recorded_pipelines_dirty = true;
+        }
     }
 
+    /// Creates the pipelines added with `add_recorded_graphics_pipelines` so that they are ready
+    /// before they are first requested. A recorded pipeline is created once a material pass with
+    /// the same content hash has been registered to its render phase, so this should be called
+    /// again as materials load. `ResourceManager::on_frame_complete` calls it every frame.
     pub fn precache_pipelines_for_all_phases(&self) -> RafxResult<()> {
         let mut guard = self.inner.lock().unwrap();
-        let _inner = &mut *guard;
+        let inner = &mut *guard;
         #[cfg(debug_assertions)]
         {
-            _inner.lock_call_count += 1;
+            inner.lock_call_count += 1;
         }
 
-        //TODO: Avoid iterating everything all the time
-        //TODO: This will have to be reworked to include vertex layout as part of the key. Current
-        // plan is to register vertex types in code with the registry and have materials reference
-        // them by name
-        /*
-        for render_phase_index in 0..MAX_RENDER_PHASE_COUNT {
-            for (render_target_meta, renderpass) in
-                &inner.render_target_meta_assignments[render_phase_index as usize]
-            {
-                for (material_pass_hash, material_pass) in
-                    &inner.material_pass_assignments[render_phase_index as usize]
-                {
-                    let key = CachedGraphicsPipelineKey {
-                        renderpass: *renderpass_hash,
-                        material_pass: *material_pass_hash,
-                    };
-
-                    if !inner.cached_pipelines.contains_key(&key) {
-                        if let Some(renderpass) = renderpass.renderpass.upgrade() {
-                            if let Some(material_pass) = material_pass.upgrade() {
-                                #[cfg(debug_assertions)]
-                                {
-                                    guard.pipeline_create_count += 1;
-                                }
-
-                                let pipeline = inner
-                                    .resource_lookup_set
-                                    .get_or_create_graphics_pipeline(&material_pass, &renderpass)?;
-                                inner.cached_pipelines.insert(
-                                    key,
-                                    CachedGraphicsPipeline {
-                                        graphics_pipeline: pipeline,
-                                        renderpass_resource: renderpass.downgrade(),
-                                        material_pass_resource: material_pass.downgrade(),
-                                    },
-                                );
-                            }
-                        }
-                    }
+        if !inner.pending_recorded_pipelines_dirty {
+            return Ok(());
+        }
+        inner.pending_recorded_pipelines_dirty = false;
+
+        let mut material_passes = FnvHashMap::default();
+        for (render_phase_index, assignments) in inner.material_pass_assignments.iter().enumerate()
+        {
+            for material_pass in assignments.values() {
+                if let Some(material_pass) = material_pass.upgrade() {
+                    let material_pass_hash = material_pass.get_raw().material_pass_key.hash();
+                    material_passes.insert(
+                        (render_phase_index as RenderPhaseIndex, material_pass_hash),
+                        material_pass,
+                    );
                 }
             }
         }
-        */
+
+        let pending_recorded_pipelines = std::mem::take(&mut inner.pending_recorded_pipelines);
+        for pending in pending_recorded_pipelines {
+            let material_pass =
+                match material_passes.get(&(pending.render_phase_index, pending.material_pass)) {
+                    Some(material_pass) => material_pass,
+                    None => {
+                        // The material may not have loaded yet
+                        inner.pending_recorded_pipelines.push(pending);
+                        continue;
+                    }
+                };
+
+            // Keep the render target meta registered until the pipeline has had a chance to be
+            // used, otherwise the pipeline would be dropped from the cache at the end of the frame
+            Self::do_register_renderpass_to_phase_index_per_frame(
+                inner,
+                &pending.render_target_meta,
+                pending.render_phase_index,
+            );
+
+            let key = CachedGraphicsPipelineKey {
+                material_pass: material_pass.get_hash(),
+                render_target_meta_hash: pending.render_target_meta.render_target_meta_hash(),
+                vertex_data_set_layout: pending.vertex_data_set_layout.hash(),
+            };
+
+            if inner.cached_pipelines.contains_key(&key) {
+                continue;
+            }
+
+            #[cfg(debug_assertions)]
+            {
+                Self::verify_data_set_layout_hash_unique(inner, &pending.vertex_data_set_layout);
+                Self::verify_render_target_meta_hash_unique(inner, &pending.render_target_meta);
+            }
+
+            // A recorded pipeline that can no longer be created (for example because the material
+            // changed) is not an error, it just won't be precached
+            match Self::create_graphics_pipeline(
+                inner,
+                key,
+                material_pass,
+                &pending.render_target_meta,
+                &pending.vertex_data_set_layout,
+            ) {
+                Some(Ok(_)) => {}
+                Some(Err(e)) => log::warn!("Failed to precache graphics pipeline: {:?}", e),
+                None => log::warn!("Failed to precache graphics pipeline"),
+            }
+        }
 
         Ok(())
     }
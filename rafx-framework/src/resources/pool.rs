@@ -13,6 +13,8 @@ pub trait PooledResourceImpl {
 struct PoolResourceInFlight<T: PooledResourceImpl> {
     pool: T,
     live_until_frame: Wrapping<u32>,
+    // If true, the pool is dropped instead of being reset and reused
+    destroy: bool,
 }
 
 /// This handles waiting for N frames to pass before resetting the pool. "Restting" could mean
@@ -89,6 +91,20 @@ impl<T: PooledResourceImpl> PooledResourceAllocator<T> {
         self.in_flight_pools.push_back(PoolResourceInFlight {
             pool,
             live_until_frame: self.frame_index + self.max_in_flight_frames + Wrapping(1),
+            destroy: false,
+        });
+    }
+
+    /// Schedule the pool to be dropped after we complete N frames. Unlike retire_pool, the pool
+    /// will not be reused. This allows releasing memory when fewer pools are needed.
+    pub fn destroy_pool(
+        &mut self,
+        pool: T,
+    ) {
+        self.in_flight_pools.push_back(PoolResourceInFlight {
+            pool,
+            live_until_frame: self.frame_index + self.max_in_flight_frames + Wrapping(1),
+            destroy: true,
         });
     }
 
@@ -112,6 +128,12 @@ impl<T: PooledResourceImpl> PooledResourceAllocator<T> {
         // Reset them and add them to the list of pools ready to be allocated
         let pools_to_reset: Vec<_> = self.in_flight_pools.drain(0..pools_to_drain).collect();
         for mut pool_to_reset in pools_to_reset {
+            if pool_to_reset.destroy {
+                self.created_pool_count -= 1;
+                std::mem::drop(pool_to_reset.pool);
+                continue;
+            }
+
             T::reset(&mut pool_to_reset.pool)?;
             self.reset_pools.push(pool_to_reset.pool);
         }
@@ -1,3 +1,7 @@
+use crate::resources::bindless_table::{BindlessIndexArc, BindlessTable};
+use crate::resources::memory_usage::{
+    ResourceMemoryAllocation, ResourceMemoryCategory, ResourceMemoryMetrics, ResourceMemoryTracker,
+};
 use crate::resources::pipeline_cache::GraphicsPipelineRenderTargetMeta;
 use crate::resources::resource_arc::{ResourceId, ResourceWithHash, WeakResourceArc};
 use crate::resources::DescriptorSetLayout;
@@ -470,6 +474,12 @@ pub struct MaterialPassKey {
     hash: MaterialPassHash,
 }
 
+impl MaterialPassKey {
+    pub fn hash(&self) -> MaterialPassHash {
+        self.hash
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GraphicsPipelineKey {
     hash: GraphicsPipelineHash,
@@ -522,6 +532,7 @@ pub struct ResourceMetrics {
     pub image_view_metrics: ResourceLookupMetric,
     pub sampler_metrics: ResourceLookupMetric,
     pub buffer_metrics: ResourceLookupMetric,
+    pub memory_metrics: ResourceMemoryMetrics,
 }
 
 #[derive(Debug, Clone)]
@@ -587,6 +598,10 @@ pub struct ImageResource {
     pub image: RafxTexture,
     // Dynamic resources have no key
     pub image_key: Option<ImageKey>,
+    // Only images inserted into the ResourceLookupSet that can be sampled have a bindless index
+    pub bindless_index: Option<BindlessIndexArc>,
+    // Counts the image's memory in the ResourceMemoryTracker until the image is dropped
+    pub memory_allocation: Option<Arc<ResourceMemoryAllocation>>,
 }
 
 #[derive(Debug, Clone)]
@@ -608,6 +623,10 @@ pub struct BufferResource {
     pub buffer: Arc<RafxBuffer>,
     // Dynamic resources have no key
     pub buffer_key: Option<BufferKey>,
+    // Only storage buffers inserted into the ResourceLookupSet have a bindless index
+    pub bindless_index: Option<BindlessIndexArc>,
+    // Counts the buffer's memory in the ResourceMemoryTracker until the buffer is dropped
+    pub memory_allocation: Option<Arc<ResourceMemoryAllocation>>,
 }
 
 //
@@ -634,6 +653,9 @@ pub struct ResourceLookupSetInner {
     samplers: ResourceLookup<SamplerKey, SamplerResource>,
     buffers: ResourceLookup<BufferKey, BufferResource>,
 
+    bindless_table: BindlessTable,
+    memory_tracker: ResourceMemoryTracker,
+
     // Used to generate keys for images/buffers
     next_image_id: AtomicU64,
     next_buffer_id: AtomicU64,
@@ -662,6 +684,8 @@ impl ResourceLookupSet {
             image_views: ResourceLookup::new(max_frames_in_flight),
             samplers: ResourceLookup::new(max_frames_in_flight),
             buffers: ResourceLookup::new(max_frames_in_flight),
+            bindless_table: BindlessTable::new(device_context, max_frames_in_flight),
+            memory_tracker: Default::default(),
             next_image_id: AtomicU64::new(0),
             next_buffer_id: AtomicU64::new(0),
         };
@@ -675,6 +699,16 @@ impl ResourceLookupSet {
         &self.inner.device_context
     }
 
+    pub fn bindless_table(&self) -> &BindlessTable {
+        &self.inner.bindless_table
+    }
+
+    /// Byte totals of the memory held by images and buffers, shared with the dynamic resource and
+    /// descriptor set allocators created by the `ResourceManager`
+    pub fn memory_tracker(&self) -> &ResourceMemoryTracker {
+        &self.inner.memory_tracker
+    }
+
     #[profiling::function]
     pub fn on_frame_complete(&self) -> RafxResult<()> {
         self.inner.images.on_frame_complete()?;
@@ -688,6 +722,7 @@ impl ResourceLookupSet {
         self.inner.material_passes.on_frame_complete()?;
         self.inner.graphics_pipelines.on_frame_complete()?;
         self.inner.compute_pipelines.on_frame_complete()?;
+        self.inner.bindless_table.on_frame_complete();
         Ok(())
     }
 
@@ -696,6 +731,7 @@ impl ResourceLookupSet {
     pub fn destroy(&self) -> RafxResult<()> {
         //WARNING: These need to be in order of dependencies to avoid frame-delays on destroying
         // resources.
+        self.inner.bindless_table.destroy();
         self.inner.compute_pipelines.destroy()?;
         self.inner.graphics_pipelines.destroy()?;
         self.inner.material_passes.destroy()?;
@@ -723,6 +759,7 @@ impl ResourceLookupSet {
             image_view_metrics: self.inner.image_views.metrics(),
             sampler_metrics: self.inner.samplers.metrics(),
             buffer_metrics: self.inner.buffers.metrics(),
+            memory_metrics: self.inner.memory_tracker.metrics(),
         }
     }
 
@@ -1016,6 +1053,7 @@ impl ResourceLookupSet {
                         color_formats: &render_target_meta.color_formats(),
                         depth_stencil_format: render_target_meta.depth_stencil_format(),
                         sample_count: render_target_meta.sample_count(),
+                        subpass_layout: render_target_meta.subpass_layout(),
                     },
                 )?;
 
@@ -1070,14 +1108,40 @@ impl ResourceLookupSet {
     pub fn insert_image(
         &self,
         image: RafxTexture,
+    ) -> ResourceArc<ImageResource> {
+        self.insert_image_with_memory_category(image, ResourceMemoryCategory::AssetImages)
+    }
+
+    /// Same as `insert_image`, but the image's memory is counted in the given category
+    pub fn insert_image_with_memory_category(
+        &self,
+        image: RafxTexture,
+        memory_category: ResourceMemoryCategory,
     ) -> ResourceArc<ImageResource> {
         let image_id = self.inner.next_image_id.fetch_add(1, Ordering::Relaxed);
 
         let image_key = ImageKey { id: image_id };
 
+        let bindless_index = if image
+            .texture_def()
+            .resource_type
+            .contains(RafxResourceType::TEXTURE)
+        {
+            Some(self.inner.bindless_table.insert_texture(&image))
+        } else {
+            None
+        };
+
+        let memory_allocation = self
+            .inner
+            .memory_tracker
+            .track(memory_category, image.allocation_size_in_bytes());
+
         let resource = ImageResource {
             image,
             image_key: Some(image_key),
+            bindless_index,
+            memory_allocation: Some(Arc::new(memory_allocation)),
         };
 
         self.inner
@@ -1094,13 +1158,40 @@ impl ResourceLookupSet {
     pub fn insert_buffer(
         &self,
         buffer: RafxBuffer,
+    ) -> ResourceArc<BufferResource> {
+        self.insert_buffer_with_memory_category(buffer, ResourceMemoryCategory::AssetBuffers)
+    }
+
+    /// Same as `insert_buffer`, but the buffer's memory is counted in the given category
+    pub fn insert_buffer_with_memory_category(
+        &self,
+        buffer: RafxBuffer,
+        memory_category: ResourceMemoryCategory,
     ) -> ResourceArc<BufferResource> {
         let buffer_id = self.inner.next_buffer_id.fetch_add(1, Ordering::Relaxed);
         let buffer_key = BufferKey { id: buffer_id };
 
+        let buffer = Arc::new(buffer);
+        let bindless_index = if buffer
+            .buffer_def()
+            .resource_type
+            .intersects(RafxResourceType::BUFFER | RafxResourceType::BUFFER_READ_WRITE)
+        {
+            Some(self.inner.bindless_table.insert_buffer(&buffer))
+        } else {
+            None
+        };
+
+        let memory_allocation = self
+            .inner
+            .memory_tracker
+            .track(memory_category, buffer.allocation_size_in_bytes());
+
         let resource = BufferResource {
-            buffer: Arc::new(buffer),
+            buffer,
             buffer_key: Some(buffer_key),
+            bindless_index,
+            memory_allocation: Some(Arc::new(memory_allocation)),
         };
 
         self.inner
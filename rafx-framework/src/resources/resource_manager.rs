@@ -3,7 +3,7 @@ use super::pipeline_cache;
 use super::resource_lookup;
 use crate::{
     DescriptorSetAllocatorProvider, DescriptorSetAllocatorRef, DynResourceAllocatorSet,
-    GraphicsPipelineCache, MAX_FRAMES_IN_FLIGHT,
+    GraphicsPipelineCache, PipelineCacheData, MAX_FRAMES_IN_FLIGHT,
 };
 
 use crate::graph::RenderGraphCache;
@@ -15,6 +15,7 @@ use crate::resources::dyn_resources::{
 };
 use crate::resources::resource_lookup::ResourceLookupSet;
 use rafx_api::{RafxDeviceContext, RafxResult};
+use std::path::Path;
 use std::sync::Arc;
 
 //TODO: Support descriptors that can be different per-view
@@ -95,10 +96,14 @@ impl ResourceManager {
             dyn_resource_allocators: DynResourceAllocatorSetManager::new(
                 device_context,
                 MAX_FRAMES_IN_FLIGHT as u32,
+                resources.memory_tracker(),
             ),
             resources: resources.clone(),
             render_graph_cache: RenderGraphCache::new(MAX_FRAMES_IN_FLIGHT as u32),
-            descriptor_set_allocator: DescriptorSetAllocatorManager::new(device_context),
+            descriptor_set_allocator: DescriptorSetAllocatorManager::new(
+                device_context,
+                resources.memory_tracker(),
+            ),
             graphics_pipeline_cache: GraphicsPipelineCache::new(render_registry, resources),
         }
     }
@@ -170,10 +175,74 @@ impl ResourceManager {
         }
     }
 
+    /// Returns the device's pipeline cache data and the graphics pipelines that have been used.
+    /// Pass it to `load_pipeline_cache_data` in a later run to avoid compiling pipelines when they
+    /// are first used.
+    pub fn pipeline_cache_data(&self) -> RafxResult<PipelineCacheData> {
+        Ok(PipelineCacheData {
+            device_pipeline_cache_data: self.device_context().pipeline_cache_data()?,
+            recorded_graphics_pipelines: self.graphics_pipeline_cache.recorded_graphics_pipelines(),
+        })
+    }
+
+    /// Merges the device's pipeline cache data (it is ignored if it came from a different device
+    /// or driver) and queues the recorded graphics pipelines to be created as soon as their
+    /// materials are loaded. Call this at startup, before materials are loaded.
+    pub fn load_pipeline_cache_data(
+        &self,
+        pipeline_cache_data: PipelineCacheData,
+    ) -> RafxResult<()> {
+        if !pipeline_cache_data.device_pipeline_cache_data.is_empty()
+            && !self
+                .device_context()
+                .merge_pipeline_cache_data(&pipeline_cache_data.device_pipeline_cache_data)?
+        {
+            log::info!("Device pipeline cache data was not used, pipelines will be recompiled");
+        }
+
+        self.graphics_pipeline_cache
+            .add_recorded_graphics_pipelines(pipeline_cache_data.recorded_graphics_pipelines);
+        Ok(())
+    }
+
+    /// Writes `pipeline_cache_data` to a file
+    pub fn save_pipeline_cache(
+        &self,
+        path: &Path,
+    ) -> RafxResult<()> {
+        let data = self.pipeline_cache_data()?.to_bytes()?;
+
+        // Write to a temporary file first so that a crash while writing can't leave a partial file
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, data)?;
+        std::fs::rename(&temp_path, path)?;
+        log::info!("Saved pipeline cache to {:?}", path);
+        Ok(())
+    }
+
+    /// Loads a file written by `save_pipeline_cache` with `load_pipeline_cache_data`. Returns false
+    /// if the file does not exist.
+    pub fn load_pipeline_cache(
+        &self,
+        path: &Path,
+    ) -> RafxResult<bool> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => Err(e)?,
+        };
+
+        self.load_pipeline_cache_data(PipelineCacheData::from_bytes(&data)?)?;
+        log::info!("Loaded pipeline cache from {:?}", path);
+        Ok(true)
+    }
+
     #[profiling::function]
     pub fn on_frame_complete(&mut self) -> RafxResult<()> {
         self.render_graph_cache.on_frame_complete();
         self.graphics_pipeline_cache.on_frame_complete();
+        self.graphics_pipeline_cache
+            .precache_pipelines_for_all_phases()?;
         self.resources.on_frame_complete()?;
         self.dyn_command_pool_allocator.on_frame_complete()?;
         self.dyn_resource_allocators.on_frame_complete()?;
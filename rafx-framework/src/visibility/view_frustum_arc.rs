@@ -6,7 +6,8 @@ use glam::Vec3;
 use parking_lot::{RwLock, RwLockReadGuard};
 use rafx_api::RafxError;
 use rafx_visibility::{
-    AsyncCommand, Projection, ViewFrustumHandle, VisibilityQuery, VisibilityWorldArc, ZoneHandle,
+    AsyncCommand, OcclusionCullingConfig, Projection, ViewFrustumHandle, VisibilityQuery,
+    VisibilityWorldArc, ZoneHandle,
 };
 use slotmap::SlotMap;
 use std::sync::Arc;
@@ -110,6 +111,29 @@ impl ViewFrustumArc {
         self
     }
 
+    /// Enables occlusion culling with the occluders in both the static and dynamic zones. Each
+    /// zone is culled with its own occluders.
+    pub fn set_occlusion(
+        &self,
+        config: Option<OcclusionCullingConfig>,
+    ) -> &Self {
+        let storage = self.inner.storage.read();
+
+        if let Some(static_view_frustum) =
+            self.view_frustum(&self.inner.static_view_frustum, &storage)
+        {
+            static_view_frustum.set_occlusion(config);
+        }
+
+        if let Some(dynamic_view_frustum) =
+            self.view_frustum(&self.inner.dynamic_view_frustum, &storage)
+        {
+            dynamic_view_frustum.set_occlusion(config);
+        }
+
+        self
+    }
+
     pub fn query_visibility(
         &mut self,
         visibility_config: &VisibilityConfig,
@@ -121,6 +145,7 @@ impl ViewFrustumArc {
 
             results.objects.clear();
             results.volumes.clear();
+            results.statistics = Default::default();
 
             let storage = self.inner.storage.read();
 
@@ -232,6 +257,16 @@ impl ViewFrustumObject {
         self
     }
 
+    pub fn set_occlusion(
+        &self,
+        config: Option<OcclusionCullingConfig>,
+    ) -> &Self {
+        self.commands
+            .send(AsyncCommand::SetViewFrustumOcclusion(self.handle, config))
+            .expect("Unable to send SetViewFrustumOcclusion command.");
+        self
+    }
+
     pub fn query_visibility(
         &self,
         results: &mut VisibilityQuery,
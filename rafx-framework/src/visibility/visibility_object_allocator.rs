@@ -2,7 +2,7 @@ use crate::visibility::view_frustum_arc::{ViewFrustumArc, ViewFrustumObject};
 use crate::visibility::visibility_object_arc::{CullModel, VisibilityObject, VisibilityObjectArc};
 use crate::visibility::ObjectId;
 use parking_lot::{RwLock, RwLockReadGuard};
-use rafx_visibility::{ModelHandle, VisibilityWorldArc, ZoneHandle};
+use rafx_visibility::{CullShape, ModelHandle, VisibilityWorldArc, ZoneHandle};
 use slotmap::SlotMap;
 use slotmap::{new_key_type, Key};
 use std::ops::Deref;
@@ -70,6 +70,9 @@ impl VisibilityObjectAllocator {
         let mut inner = self.visibility_world.inner.lock();
         match cull_model {
             CullModel::Mesh(polygons) => Some(inner.new_model(polygons)),
+            CullModel::OrientedMesh(polygons) => {
+                Some(inner.new_model_with_cull_shape(polygons, CullShape::OrientedBoundingBox))
+            }
             CullModel::Sphere(radius) => Some(inner.new_bounding_sphere(radius)),
             CullModel::Quad(width, height) => Some(inner.new_quad(width, height)),
             CullModel::VisibleBounds(bounds) => Some(inner.new_visible_bounds(bounds)),
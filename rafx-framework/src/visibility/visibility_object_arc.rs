@@ -11,6 +11,8 @@ use std::sync::Arc;
 
 pub enum CullModel {
     Mesh(PolygonSoup),
+    /// A `Mesh` that is also culled with its oriented bounding box.
+    OrientedMesh(PolygonSoup),
     VisibleBounds(VisibleBounds),
     Sphere(f32),
     Quad(f32, f32),
@@ -22,6 +24,10 @@ impl CullModel {
         CullModel::Mesh(polygon_soup)
     }
 
+    pub fn oriented_mesh(polygon_soup: PolygonSoup) -> CullModel {
+        CullModel::OrientedMesh(polygon_soup)
+    }
+
     pub fn visible_bounds(model: VisibleBounds) -> CullModel {
         CullModel::VisibleBounds(model)
     }
@@ -98,6 +104,17 @@ impl VisibilityObjectArc {
         self
     }
 
+    /// An occluder's cull model hides the objects behind it from views with occlusion culling.
+    pub fn set_occluder(
+        &self,
+        is_occluder: bool,
+    ) -> &Self {
+        let storage = self.inner.storage.read();
+        let object = storage.get(self.inner.id).unwrap();
+        object.set_occluder(is_occluder);
+        self
+    }
+
     pub fn set_transform(
         &self,
         translation: Vec3,
@@ -199,6 +216,16 @@ impl VisibilityObject {
         self
     }
 
+    pub fn set_occluder(
+        &self,
+        is_occluder: bool,
+    ) -> &Self {
+        self.commands
+            .send(AsyncCommand::SetObjectOccluder(self.handle, is_occluder))
+            .expect("Unable to send SetObjectOccluder command.");
+        self
+    }
+
     pub fn set_transform(
         &self,
         translation: Vec3,
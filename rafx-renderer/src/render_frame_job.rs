@@ -1,6 +1,6 @@
 use crate::{RenderFeaturePlugin, RendererThreadPool};
 use fnv::FnvBuildHasher;
-use rafx_api::{RafxCommandBuffer, RafxDeviceContext, RafxQueue};
+use rafx_api::{RafxCommandBuffer, RafxDeviceContext, RafxQueue, RafxSemaphore};
 use rafx_api::{RafxPresentableFrame, RafxResult};
 use rafx_framework::graph::PreparedRenderGraph;
 use rafx_framework::render_features::render_features_prelude::*;
@@ -53,8 +53,17 @@ impl RenderFrameJob {
         match result {
             Ok(command_buffers) => {
                 // ignore the error, we will receive it when we try to acquire the next image
-                let refs: Vec<&RafxCommandBuffer> = command_buffers.iter().map(|x| &**x).collect();
-                let _ = presentable_frame.present(&graphics_queue, &refs);
+                let refs: Vec<&RafxCommandBuffer> =
+                    command_buffers.iter().map(|(x, _)| &**x).collect();
+                let wait_semaphores: Vec<&RafxSemaphore> = command_buffers
+                    .iter()
+                    .flat_map(|(_, x)| x.iter().map(|x| &**x))
+                    .collect();
+                let _ = presentable_frame.present_with_wait_semaphores(
+                    &graphics_queue,
+                    &refs,
+                    &wait_semaphores,
+                );
             }
             Err(err) => {
                 log::error!("Render thread failed with error {:?}", err);
@@ -83,7 +92,7 @@ impl RenderFrameJob {
         render_views: Vec<RenderView>,
         feature_plugins: Arc<Vec<Arc<dyn RenderFeaturePlugin>>>,
         thread_pool: &mut dyn RendererThreadPool,
-    ) -> RafxResult<Vec<DynCommandBuffer>> {
+    ) -> RafxResult<Vec<(DynCommandBuffer, Vec<Arc<RafxSemaphore>>)>> {
         let t0 = rafx_base::Instant::now();
 
         //
@@ -151,8 +160,12 @@ impl RenderFrameJob {
                 )
             };
 
-            let prepared_render_data =
-                PreparedRenderData::new(&submit_node_blocks, write_jobs, write_context);
+            let prepared_render_data = PreparedRenderData::new(
+                &submit_node_blocks,
+                write_jobs,
+                write_context,
+                thread_pool.write_thread_pool(),
+            );
 
             {
                 profiling::scope!("Execute Render Graph");
@@ -507,13 +507,13 @@ impl Renderer {
                 frame_packet_size.view_packet_sizes.push(ViewPacketSize {
                     view: view_visibility_result.view.clone(),
                     num_render_object_instances: visible_render_objects.len(),
-                    num_volumes: 0, // TODO(dvd): Volumes
+                    num_volumes: view_visibility_result.per_view_volumes.len(),
                 });
             } else {
                 frame_packet_size.view_packet_sizes.push(ViewPacketSize {
                     view: view_visibility_result.view.clone(),
                     num_render_object_instances: 0,
-                    num_volumes: 0, // TODO(dvd): Volumes
+                    num_volumes: view_visibility_result.per_view_volumes.len(),
                 });
             }
         }
@@ -629,10 +629,9 @@ impl Renderer {
             }
 
             // TODO(dvd): One could imagine volumes having a bitfield mask and filtering on that.
-            // for per_view_volume in view_visibility_result.per_view_volumes.iter() {
-            //    let object_id = ObjectId::from(per_view_volume.id);
-            //    frame_packet.push_volume(per_frame_view_index, object_id);
-            // }
+            for object_id in view_visibility_result.per_view_volumes.iter() {
+                frame_packet.push_volume(view_frame_index, *object_id);
+            }
         }
     }
 
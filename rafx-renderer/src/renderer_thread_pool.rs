@@ -16,7 +16,9 @@ use std::sync::Arc;
 ///
 /// # Write
 ///
-/// The `write` step is not able to be parallelized in this release.
+/// The `write` step is parallelized by returning a `RenderJobWriteThreadPool` from
+/// `write_thread_pool`. The `RenderView`s and `RenderPhase`s of a render graph pass are then split
+/// into chunks and recorded into secondary command buffers on multiple threads.
 pub trait RendererThreadPool: Sync + Send {
     /// Each `RenderView` has an associated `ViewVisibilityJob` for calculating visible render objects
     /// from that `RenderView`.
@@ -71,5 +73,11 @@ pub trait RendererThreadPool: Sync + Send {
         finished_prepare_jobs: &Vec<Arc<dyn RenderFeaturePrepareJob<'prepare> + 'prepare>>,
     ) -> SubmitNodeBlocks;
 
+    /// Returns the `RenderJobWriteThreadPool` used to record the `SubmitNode`s of a `RenderView`
+    /// and `RenderPhase` on multiple threads, or `None` to write them on the render thread.
+    fn write_thread_pool(&self) -> Option<&dyn RenderJobWriteThreadPool> {
+        None
+    }
+
     fn clone_to_box(&mut self) -> Box<dyn RendererThreadPool>;
 }
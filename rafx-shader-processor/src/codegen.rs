@@ -515,6 +515,11 @@ fn create_binding_wrapper_binding_item(
         return Ok(());
     }
 
+    // Bindless tables are written through BindlessTable rather than per-material descriptor sets
+    if binding.annotations.bindless.is_some() {
+        return Ok(());
+    }
+
     // Find the binding in the reflection data
     let e = reflected_entry_point
         .descriptor_set_layouts
@@ -561,7 +566,8 @@ fn create_binding_wrapper_binding_item(
         }
         RafxResourceType::TEXTURE
         | RafxResourceType::TEXTURE_READ_WRITE
-        | RafxResourceType::COMBINED_IMAGE_SAMPLER => {
+        | RafxResourceType::COMBINED_IMAGE_SAMPLER
+        | RafxResourceType::INPUT_ATTACHMENT => {
             if e.resource.element_count_normalized() > 1 {
                 binding_wrapper_items.push(BindingWrapperItem {
                     binding_name,
@@ -451,8 +451,13 @@ fn process_glsl_shader(
         let shader_resources = ast.get_shader_resources()?;
 
         // Rename uniform blocks to be consistent with how they would appear in GL ES 3.0. This way
-        // we can consistently use the same GL name across both backends
-        for resource in &shader_resources.uniform_buffers {
+        // we can consistently use the same GL name across both backends. Push constants are
+        // emitted as a plain uniform struct too, so they get the same treatment.
+        for resource in shader_resources
+            .uniform_buffers
+            .iter()
+            .chain(&shader_resources.push_constant_buffers)
+        {
             let block_name = gles2_ast.get_name(resource.base_type_id)?;
             gles2_ast.set_name(
                 resource.base_type_id,
@@ -481,6 +486,8 @@ fn process_glsl_shader(
         spirv_cross_gles3_options.vulkan_semantics = false;
         spirv_cross_gles3_options.vertex.transform_clip_space = true;
         spirv_cross_gles3_options.vertex.invert_y = true;
+        // The GL ES 3.0 backend emulates push constants with a uniform block
+        spirv_cross_gles3_options.emit_push_constant_as_uniform_buffer = true;
 
         let shader_resources = ast.get_shader_resources()?;
 
@@ -27,6 +27,10 @@ pub(crate) struct SlotNameAnnotation(pub(crate) String);
 #[serde(rename = "semantic")]
 pub(crate) struct SemanticAnnotation(pub(crate) String);
 
+#[derive(Default, Deserialize, Debug)]
+#[serde(rename = "bindless")]
+pub(crate) struct BindlessAnnotation(pub(crate) u32);
+
 fn parse_ron_or_default<'de, T: Default + Deserialize<'de>>(data: &'de str) -> Result<T, String> {
     if !data.is_empty() {
         ron::de::from_str(&data)
@@ -81,6 +85,7 @@ pub(crate) struct BindingAnnotations {
     pub(crate) immutable_samplers: Option<ImmutableSamplersAnnotation>,
     pub(crate) slot_name: Option<SlotNameAnnotation>,
     pub(crate) semantic: Option<SemanticAnnotation>,
+    pub(crate) bindless: Option<BindlessAnnotation>,
 }
 
 impl BindingAnnotations {
@@ -117,6 +122,9 @@ impl BindingAnnotations {
                 "semantic" => {
                     parsed_annotations.semantic = Some(parse_ron_or_default(&annotation_data)?);
                 }
+                "bindless" => {
+                    parsed_annotations.bindless = Some(parse_ron_or_default(&annotation_data)?);
+                }
                 _ => {
                     return Err(format!(
                         "Annotation named '{}' not allowed for bindings",
@@ -10,7 +10,7 @@ use fnv::FnvHashMap;
 use rafx_api::{
     RafxAddressMode, RafxCompareOp, RafxFilterType, RafxGlUniformMember, RafxMipMapMode,
     RafxResourceType, RafxResult, RafxSamplerDef, RafxShaderResource, RafxShaderStageFlags,
-    RafxShaderStageReflection, MAX_DESCRIPTOR_SET_LAYOUTS,
+    RafxShaderStageReflection, MAX_DESCRIPTOR_SET_LAYOUTS, METAL_PUSH_CONSTANT_BUFFER_INDEX,
 };
 use spirv_cross::msl::{ResourceBinding, ResourceBindingLocation, SamplerData, SamplerLocation};
 use spirv_cross::spirv::{ExecutionModel, Type};
@@ -100,7 +100,7 @@ fn get_rafx_resource<TargetT>(
     ast: &spirv_cross::spirv::Ast<TargetT>,
     declarations: &super::parse_declarations::ParseDeclarationsResult,
     resource: &spirv_cross::spirv::Resource,
-    resource_type: RafxResourceType,
+    mut resource_type: RafxResourceType,
     stage_flags: RafxShaderStageFlags,
 ) -> RafxResult<RafxShaderResource>
 where
@@ -114,12 +114,33 @@ where
     let binding = ast
         .get_decoration(resource.id, spirv_cross::spirv::Decoration::Binding)
         .map_err(|_x| "could not get descriptor binding index from reflection data")?;
-    let element_count = get_descriptor_count_from_type(ast, resource.type_id)?;
+    let mut element_count = get_descriptor_count_from_type(ast, resource.type_id)?;
 
     let parsed_binding = declarations.bindings.iter().find(|x| x.parsed.layout_parts.binding == Some(binding as usize) && x.parsed.layout_parts.set == Some(set as usize))
         .or_else(|| declarations.bindings.iter().find(|x| x.parsed.instance_name == *resource.name))
         .ok_or_else(|| format!("A resource named {} in spirv reflection data was not matched up to a resource scanned in source code.", resource.name))?;
 
+    // Bindless bindings are declared as unbounded arrays in the shader. The annotation provides
+    // the number of descriptors to allocate for them
+    if let Some(annotation) = &parsed_binding.annotations.bindless {
+        if element_count != 0 {
+            Err(format!(
+                "Resource {} is annotated with @[bindless] but is not an unbounded array (i.e. textures[])",
+                resource.name
+            ))?;
+        }
+
+        if annotation.0 == 0 {
+            Err(format!(
+                "Resource {} is annotated with @[bindless] but does not specify a descriptor count (i.e. @[bindless(1024)])",
+                resource.name
+            ))?;
+        }
+
+        element_count = annotation.0;
+        resource_type |= RafxResourceType::BINDLESS;
+    }
+
     let slot_name = if let Some(annotation) = &parsed_binding.annotations.slot_name {
         Some(annotation.0.clone())
     } else {
@@ -134,6 +155,7 @@ where
             &parsed_binding.parsed.type_name,
             parsed_binding.parsed.type_name.clone(),
             0,
+            MemoryLayout::Std140,
             &mut gl_uniform_members,
         )?;
     }
@@ -321,17 +343,64 @@ where
         RafxResourceType::SAMPLER,
         stage_flags,
     )?;
+    get_reflected_bindings(
+        builtin_types,
+        user_types,
+        &mut bindings,
+        ast,
+        declarations,
+        &shader_resources.subpass_inputs,
+        RafxResourceType::INPUT_ATTACHMENT,
+        stage_flags,
+    )?;
 
     Ok(bindings)
 }
 
 //TODO: Exclude MSL constexpr samplers?
+// Inserts the binding into the map once for every stage the resource is used in
+fn insert_msl_resource_binding(
+    argument_buffer_assignments: &mut BTreeMap<ResourceBindingLocation, ResourceBinding>,
+    location: &ResourceBindingLocation,
+    binding: &ResourceBinding,
+    used_in_shader_stages: RafxShaderStageFlags,
+) {
+    let stages = [
+        (RafxShaderStageFlags::VERTEX, ExecutionModel::Vertex),
+        (RafxShaderStageFlags::FRAGMENT, ExecutionModel::Fragment),
+        (RafxShaderStageFlags::COMPUTE, ExecutionModel::Kernel),
+        (
+            RafxShaderStageFlags::TESSELLATION_CONTROL,
+            ExecutionModel::TessellationControl,
+        ),
+        (
+            RafxShaderStageFlags::TESSELLATION_EVALUATION,
+            ExecutionModel::TessellationEvaluation,
+        ),
+    ];
+
+    for (stage_flag, execution_model) in stages.iter() {
+        if used_in_shader_stages.intersects(*stage_flag) {
+            let mut location = location.clone();
+            location.stage = *execution_model;
+            argument_buffer_assignments.insert(location, binding.clone());
+        }
+    }
+}
+
 pub(crate) fn msl_assign_argument_buffer_ids(
     entry_points: &[ReflectedEntryPoint]
 ) -> RafxResult<BTreeMap<ResourceBindingLocation, ResourceBinding>> {
     let mut all_resources_lookup = FnvHashMap::<(u32, u32), RafxShaderResource>::default();
+    let mut push_constant_stages = RafxShaderStageFlags::empty();
     for entry_point in entry_points {
         for resource in &entry_point.rafx_api_reflection.resources {
+            // Push constants are not part of an argument buffer, they are handled below
+            if resource.resource_type == RafxResourceType::ROOT_CONSTANT {
+                push_constant_stages |= resource.used_in_shader_stages;
+                continue;
+            }
+
             let key = (resource.set_index, resource.binding);
             if let Some(old) = all_resources_lookup.get_mut(&key) {
                 if resource.resource_type != old.resource_type {
@@ -387,53 +456,41 @@ pub(crate) fn msl_assign_argument_buffer_ids(
             count: resource.element_count_normalized(),
         };
 
-        if resource
-            .used_in_shader_stages
-            .intersects(RafxShaderStageFlags::VERTEX)
-        {
-            let mut location = location.clone();
-            location.stage = ExecutionModel::Vertex;
-            argument_buffer_assignments.insert(location, new_binding.clone());
-        }
+        insert_msl_resource_binding(
+            &mut argument_buffer_assignments,
+            &location,
+            &new_binding,
+            resource.used_in_shader_stages,
+        );
 
-        if resource
-            .used_in_shader_stages
-            .intersects(RafxShaderStageFlags::FRAGMENT)
-        {
-            let mut location = location.clone();
-            location.stage = ExecutionModel::Fragment;
-            argument_buffer_assignments.insert(location, new_binding.clone());
-        }
-
-        if resource
-            .used_in_shader_stages
-            .intersects(RafxShaderStageFlags::COMPUTE)
-        {
-            let mut location = location.clone();
-            location.stage = ExecutionModel::Kernel;
-            argument_buffer_assignments.insert(location, new_binding.clone());
-        }
+        next_msl_argument_buffer_id[resource.set_index as usize] +=
+            resource.element_count_normalized();
+    }
 
-        if resource
-            .used_in_shader_stages
-            .intersects(RafxShaderStageFlags::TESSELLATION_CONTROL)
-        {
-            let mut location = location.clone();
-            location.stage = ExecutionModel::TessellationControl;
-            argument_buffer_assignments.insert(location, new_binding.clone());
-        }
+    // Push constants are emulated with setBytes at a fixed buffer index, after the argument buffers
+    if !push_constant_stages.is_empty() {
+        let location = ResourceBindingLocation {
+            // We'll overwrite the stage as needed when we insert into the map
+            stage: spirv_cross::spirv::ExecutionModel::TessellationEvaluation,
+            // SPIRV-Cross identifies push constants with this set/binding (kPushConstDescSet and
+            // kPushConstBinding)
+            desc_set: !0,
+            binding: 0,
+        };
 
-        if resource
-            .used_in_shader_stages
-            .intersects(RafxShaderStageFlags::TESSELLATION_EVALUATION)
-        {
-            let mut location = location.clone();
-            location.stage = ExecutionModel::TessellationEvaluation;
-            argument_buffer_assignments.insert(location, new_binding.clone());
-        }
+        let new_binding = ResourceBinding {
+            buffer_id: METAL_PUSH_CONSTANT_BUFFER_INDEX,
+            texture_id: 0,
+            sampler_id: 0,
+            count: 1,
+        };
 
-        next_msl_argument_buffer_id[resource.set_index as usize] +=
-            resource.element_count_normalized();
+        insert_msl_resource_binding(
+            &mut argument_buffer_assignments,
+            &location,
+            &new_binding,
+            push_constant_stages,
+        );
     }
 
     Ok(argument_buffer_assignments)
@@ -572,6 +629,7 @@ fn generate_gl_uniform_members(
     type_name: &str,
     prefix: String,
     offset: usize,
+    memory_layout: MemoryLayout,
     gl_uniform_members: &mut Vec<RafxGlUniformMember>,
 ) -> RafxResult<()> {
     if builtin_types.contains_key(type_name) {
@@ -593,7 +651,7 @@ fn generate_gl_uniform_members(
             user_types,
             &user_type.type_name,
             user_type,
-            MemoryLayout::Std140,
+            memory_layout,
         )?;
 
         for field in &*user_type.fields {
@@ -617,6 +675,7 @@ fn generate_gl_uniform_members(
                     &field.type_name,
                     member_full_name,
                     field_offset,
+                    memory_layout,
                     gl_uniform_members,
                 )?;
             } else {
@@ -631,6 +690,7 @@ fn generate_gl_uniform_members(
                         &field.type_name,
                         member_full_name,
                         field_offset,
+                        memory_layout,
                         gl_uniform_members,
                     )?;
                 }
@@ -744,21 +804,52 @@ where
         //TODO: This is using a list of push constants but I don't think multiple are allowed within
         // the same file
         for push_constant in &shader_resources.push_constant_buffers {
-            let push_constant_ranges = ast
-                .get_active_buffer_ranges(push_constant.id)
-                .map_err(|_x| "could not get active buffer ranges")?;
-            for push_constant_range in &push_constant_ranges {
-                let resource = RafxShaderResource {
-                    resource_type: RafxResourceType::ROOT_CONSTANT,
-                    size_in_bytes: push_constant_range.range as u32,
-                    used_in_shader_stages: stage_flags,
-                    name: Some(push_constant.name.clone()),
-                    ..Default::default()
-                };
-                resource.validate()?;
-
-                rafx_bindings.push(resource);
-            }
+            let parsed_binding = declarations
+                .bindings
+                .iter()
+                .find(|x| {
+                    x.parsed.layout_parts.push_constant
+                        && x.parsed.instance_name == *push_constant.name
+                })
+                .or_else(|| {
+                    declarations
+                        .bindings
+                        .iter()
+                        .find(|x| x.parsed.layout_parts.push_constant)
+                })
+                .ok_or_else(|| format!("A push constant named {} in spirv reflection data was not matched up to a push constant scanned in source code.", push_constant.name))?;
+
+            // The whole block is pushed at once, so the size is the declared size of the struct
+            // rather than the range that happens to be active in this stage
+            let size_in_bytes = ast
+                .get_declared_struct_size(push_constant.type_id)
+                .map_err(|_x| "could not get push constant size from reflection data")?;
+
+            // GL ES emulates push constants with uniforms, which are set member by member from the
+            // std430-packed push constant data
+            let mut gl_uniform_members = Vec::<RafxGlUniformMember>::default();
+            generate_gl_uniform_members(
+                &builtin_types,
+                &user_types,
+                &parsed_binding.parsed.type_name,
+                parsed_binding.parsed.type_name.clone(),
+                0,
+                MemoryLayout::Std430,
+                &mut gl_uniform_members,
+            )?;
+
+            let resource = RafxShaderResource {
+                resource_type: RafxResourceType::ROOT_CONSTANT,
+                size_in_bytes,
+                used_in_shader_stages: stage_flags,
+                name: Some(push_constant.name.clone()),
+                gles_name: Some(parsed_binding.parsed.type_name.clone()),
+                gles2_uniform_members: gl_uniform_members,
+                ..Default::default()
+            };
+            resource.validate()?;
+
+            rafx_bindings.push(resource);
         }
 
         //TODO: Store the type and verify that the format associated in the game i.e. R32G32B32 is
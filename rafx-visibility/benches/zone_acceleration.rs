@@ -0,0 +1,110 @@
+//! Compares `query_visibility` with the packed linear scan and with the loose octree. Run with
+//! `cargo bench -p rafx-visibility`.
+
+use glam::Vec3;
+use rafx_visibility::geometry::Transform;
+use rafx_visibility::{
+    DepthRange, ViewFrustumHandle, VisibilityQuery, VisibilityWorldArc, ZoneAcceleration,
+};
+use std::time::{Duration, Instant};
+
+const WORLD_HALF_SIZE: f32 = 4000.;
+const QUERY_ITERATIONS: u32 = 100;
+
+// NOTE(dvd): A small xorshift generator keeps the object layout identical between runs.
+struct Random(u64);
+
+impl Random {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn next_position(&mut self) -> Vec3 {
+        let mut coordinate = || (self.next_f32() * 2. - 1.) * WORLD_HALF_SIZE;
+        Vec3::new(coordinate(), coordinate() * 0.05, coordinate())
+    }
+}
+
+fn create_world(object_count: usize) -> (VisibilityWorldArc, ViewFrustumHandle) {
+    let world = VisibilityWorldArc::new();
+    let view_frustum = {
+        let mut inner = world.inner.lock();
+        let zone = inner.new_zone();
+
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for id in 0..object_count {
+            let object = inner.new_object();
+            inner.set_object_id(object, id as u64);
+            inner.set_object_position(
+                object,
+                Transform {
+                    translation: random.next_position(),
+                    ..Default::default()
+                },
+            );
+            inner.set_object_zone(object, Some(zone));
+        }
+
+        let view_frustum = inner.new_view_frustum();
+        inner.set_view_frustum_perspective(
+            view_frustum,
+            std::f32::consts::FRAC_PI_3,
+            16. / 9.,
+            0.1,
+            300.,
+            DepthRange::Normal,
+        );
+        inner.set_view_frustum_transforms(
+            view_frustum,
+            Vec3::new(0., 10., 0.),
+            Vec3::new(0., 10., -1.),
+            Vec3::Y,
+        );
+        inner.set_view_frustum_zone(view_frustum, Some(zone));
+        view_frustum
+    };
+
+    (world, view_frustum)
+}
+
+fn time_queries(
+    world: &VisibilityWorldArc,
+    view_frustum: ViewFrustumHandle,
+) -> (Duration, usize) {
+    let mut result = VisibilityQuery::default();
+    let start = Instant::now();
+    for _ in 0..QUERY_ITERATIONS {
+        result.objects.clear();
+        assert!(world.query_visibility(view_frustum, &mut result).is_ok());
+    }
+
+    (start.elapsed() / QUERY_ITERATIONS, result.objects.len())
+}
+
+fn main() {
+    for object_count in [10_000, 100_000, 500_000].iter().copied() {
+        let (world, view_frustum) = create_world(object_count);
+        let zone = *world
+            .inner
+            .lock()
+            .get_view_frustum_zone(view_frustum)
+            .unwrap();
+
+        let (linear_time, linear_visible) = time_queries(&world, view_frustum);
+
+        world
+            .inner
+            .lock()
+            .set_zone_acceleration(zone, ZoneAcceleration::LooseOctree { min_node_size: 16. });
+        let (octree_time, octree_visible) = time_queries(&world, view_frustum);
+
+        assert_eq!(linear_visible, octree_visible);
+        println!(
+            "{:>7} objects, {:>5} visible: linear scan {:>10.3?}, loose octree {:>10.3?}",
+            object_count, linear_visible, linear_time, octree_time
+        );
+    }
+}
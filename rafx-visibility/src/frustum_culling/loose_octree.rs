@@ -0,0 +1,345 @@
+use crate::geometry::{BoundingSphere, Frustum};
+use crate::{ObjectHandle, VisibilityResult, VisibleObjects};
+use glam::Vec3;
+use slotmap::SecondaryMap;
+
+#[derive(Copy, Clone)]
+struct OctreeObject {
+    handle: ObjectHandle,
+    id: u64,
+    sphere: BoundingSphere,
+}
+
+#[derive(Copy, Clone)]
+struct ObjectLocation {
+    node: usize,
+    index: usize,
+}
+
+struct OctreeNode {
+    center: Vec3,
+    // NOTE(dvd): The node owns objects whose center is inside `center +/- half_size`. The loose
+    // bounds of the node are `center +/- 2 * half_size` so any object with `radius <= half_size` fits.
+    half_size: f32,
+    parent: Option<usize>,
+    children: [Option<usize>; 8],
+    objects: Vec<OctreeObject>,
+    subtree_len: usize,
+}
+
+impl OctreeNode {
+    fn new(
+        center: Vec3,
+        half_size: f32,
+        parent: Option<usize>,
+    ) -> Self {
+        OctreeNode {
+            center,
+            half_size,
+            parent,
+            children: [None; 8],
+            objects: Vec::new(),
+            subtree_len: 0,
+        }
+    }
+
+    fn contains_center(
+        &self,
+        position: Vec3,
+    ) -> bool {
+        let offset = (position - self.center).abs();
+        offset.max_element() <= self.half_size
+    }
+
+    fn fits(
+        &self,
+        sphere: &BoundingSphere,
+    ) -> bool {
+        sphere.radius <= self.half_size && self.contains_center(sphere.position)
+    }
+
+    fn octant(
+        &self,
+        position: Vec3,
+    ) -> usize {
+        (position.x >= self.center.x) as usize
+            | ((position.y >= self.center.y) as usize) << 1
+            | ((position.z >= self.center.z) as usize) << 2
+    }
+
+    fn octant_center(
+        &self,
+        octant: usize,
+    ) -> Vec3 {
+        let quarter_size = self.half_size * 0.5;
+        let sign = |bit: usize| if octant & bit != 0 { 1. } else { -1. };
+        self.center + Vec3::new(sign(1), sign(2), sign(4)) * quarter_size
+    }
+}
+
+enum Containment {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+fn classify_aabb(
+    frustum: &Frustum,
+    center: Vec3,
+    extent: f32,
+) -> Containment {
+    let mut containment = Containment::Inside;
+    for plane in &frustum.planes {
+        let distance = plane.distance(center);
+        let radius = extent * plane.get_normal().abs().dot(Vec3::ONE);
+        if distance + radius <= 0. {
+            return Containment::Outside;
+        }
+
+        if distance - radius < 0. {
+            containment = Containment::Intersecting;
+        }
+    }
+
+    containment
+}
+
+/// A loose octree over the bounding spheres of the `Objects` in a `Zone`. Nodes are created as
+/// objects are inserted, and the root grows to enclose objects that are outside of it. Empty
+/// nodes are released when their last object is removed.
+pub(crate) struct LooseOctree {
+    nodes: Vec<OctreeNode>,
+    free_nodes: Vec<usize>,
+    root: Option<usize>,
+    objects: SecondaryMap<ObjectHandle, ObjectLocation>,
+    min_half_size: f32,
+}
+
+impl LooseOctree {
+    pub fn new(min_node_size: f32) -> Self {
+        assert!(min_node_size > 0.);
+        LooseOctree {
+            nodes: Vec::new(),
+            free_nodes: Vec::new(),
+            root: None,
+            objects: Default::default(),
+            min_half_size: min_node_size * 0.5,
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        handle: ObjectHandle,
+        id: u64,
+        sphere: BoundingSphere,
+    ) {
+        assert!(sphere.position.is_finite() && sphere.radius.is_finite());
+        assert!(!self.objects.contains_key(handle));
+
+        let mut node = self.grow_to_fit(&sphere);
+        loop {
+            let child_half_size = self.nodes[node].half_size * 0.5;
+            if child_half_size < self.min_half_size || sphere.radius > child_half_size {
+                break;
+            }
+
+            let octant = self.nodes[node].octant(sphere.position);
+            node = match self.nodes[node].children[octant] {
+                Some(child) => child,
+                None => {
+                    let center = self.nodes[node].octant_center(octant);
+                    let child =
+                        self.allocate_node(OctreeNode::new(center, child_half_size, Some(node)));
+                    self.nodes[node].children[octant] = Some(child);
+                    child
+                }
+            };
+        }
+
+        let index = self.nodes[node].objects.len();
+        self.nodes[node]
+            .objects
+            .push(OctreeObject { handle, id, sphere });
+        self.objects.insert(handle, ObjectLocation { node, index });
+
+        let mut ancestor = Some(node);
+        while let Some(current) = ancestor {
+            self.nodes[current].subtree_len += 1;
+            ancestor = self.nodes[current].parent;
+        }
+    }
+
+    /// Moves the object only if it no longer fits in its node.
+    pub fn update(
+        &mut self,
+        handle: ObjectHandle,
+        sphere: BoundingSphere,
+    ) {
+        let location = *self.objects.get(handle).unwrap();
+        let node = &mut self.nodes[location.node];
+        if node.fits(&sphere) {
+            node.objects[location.index].sphere = sphere;
+        } else {
+            let id = node.objects[location.index].id;
+            self.remove(handle);
+            self.insert(handle, id, sphere);
+        }
+    }
+
+    pub fn update_id(
+        &mut self,
+        handle: ObjectHandle,
+        id: u64,
+    ) {
+        let location = *self.objects.get(handle).unwrap();
+        self.nodes[location.node].objects[location.index].id = id;
+    }
+
+    pub fn remove(
+        &mut self,
+        handle: ObjectHandle,
+    ) -> bool {
+        let location = match self.objects.remove(handle) {
+            Some(location) => location,
+            None => return false,
+        };
+
+        let objects = &mut self.nodes[location.node].objects;
+        objects.swap_remove(location.index);
+        if location.index < objects.len() {
+            // NOTE(dvd): The last object in the node was moved into the removed object's slot.
+            let moved = objects[location.index].handle;
+            self.objects.get_mut(moved).unwrap().index = location.index;
+        }
+
+        let mut ancestor = Some(location.node);
+        while let Some(current) = ancestor {
+            self.nodes[current].subtree_len -= 1;
+            ancestor = self.nodes[current].parent;
+        }
+
+        // NOTE(dvd): Release the nodes that are now empty, starting from the bottom.
+        let mut node = location.node;
+        while self.nodes[node].subtree_len == 0 {
+            match self.nodes[node].parent {
+                Some(parent) => {
+                    let octant = self.nodes[parent].octant(self.nodes[node].center);
+                    self.nodes[parent].children[octant] = None;
+                    self.free_nodes.push(node);
+                    node = parent;
+                }
+                None => {
+                    self.nodes.clear();
+                    self.free_nodes.clear();
+                    self.root = None;
+                    break;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Pushes every object that is inside of the `frustum` and accepted by `filter` into `results`.
+    /// Nodes outside of the `frustum` are skipped, and the objects in nodes entirely inside of the
+    /// `frustum` are not tested individually.
+    pub fn collect_objects<F: Fn(&BoundingSphere) -> bool>(
+        &self,
+        view_frustum_position: Vec3,
+        frustum: &Frustum,
+        filter: F,
+        results: &mut VisibleObjects,
+    ) {
+        let root = match self.root {
+            Some(root) => root,
+            None => return,
+        };
+
+        let mut stack = vec![(root, false)];
+        while let Some((node, parent_inside)) = stack.pop() {
+            let node = &self.nodes[node];
+
+            let inside = parent_inside
+                || match classify_aabb(frustum, node.center, node.half_size * 2.) {
+                    Containment::Outside => continue,
+                    Containment::Intersecting => false,
+                    Containment::Inside => true,
+                };
+
+            for object in &node.objects {
+                if (inside || frustum.contains_sphere_slow(&object.sphere))
+                    && filter(&object.sphere)
+                {
+                    results.push(VisibilityResult::new(
+                        object.handle,
+                        object.id,
+                        view_frustum_position,
+                        object.sphere,
+                    ));
+                }
+            }
+
+            for child in node.children.iter().flatten() {
+                stack.push((*child, inside));
+            }
+        }
+    }
+
+    fn allocate_node(
+        &mut self,
+        node: OctreeNode,
+    ) -> usize {
+        if let Some(index) = self.free_nodes.pop() {
+            self.nodes[index] = node;
+            index
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    // NOTE(dvd): Returns the root after adding parents above it until the sphere fits.
+    fn grow_to_fit(
+        &mut self,
+        sphere: &BoundingSphere,
+    ) -> usize {
+        let mut root = match self.root {
+            Some(root) => root,
+            None => {
+                let mut half_size = self.min_half_size;
+                while half_size < sphere.radius {
+                    half_size *= 2.;
+                }
+
+                let root = self.allocate_node(OctreeNode::new(sphere.position, half_size, None));
+                self.root = Some(root);
+                return root;
+            }
+        };
+
+        while !self.nodes[root].fits(sphere) {
+            let old_root = &self.nodes[root];
+            let direction = sphere.position - old_root.center;
+            let sign = |value: f32| if value >= 0. { 1. } else { -1. };
+            let center = old_root.center
+                + Vec3::new(sign(direction.x), sign(direction.y), sign(direction.z))
+                    * old_root.half_size;
+            let half_size = old_root.half_size * 2.;
+            let old_root_center = old_root.center;
+            let subtree_len = old_root.subtree_len;
+
+            let mut new_root = OctreeNode::new(center, half_size, None);
+            new_root.subtree_len = subtree_len;
+
+            let new_root = self.allocate_node(new_root);
+            let octant = self.nodes[new_root].octant(old_root_center);
+            self.nodes[new_root].children[octant] = Some(root);
+            self.nodes[root].parent = Some(new_root);
+
+            root = new_root;
+            self.root = Some(root);
+        }
+
+        root
+    }
+}
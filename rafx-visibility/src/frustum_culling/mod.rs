@@ -1,3 +1,12 @@
+mod loose_octree;
+mod oriented_box_culling;
 mod packed_bounding_sphere_chunk;
+mod portal_frustum;
+mod shadow_caster_volume;
+pub(crate) use loose_octree::LooseOctree;
+pub(crate) use oriented_box_culling::cull_oriented_boxes;
+pub(crate) use packed_bounding_sphere_chunk::collect_shadow_casters;
 pub(crate) use packed_bounding_sphere_chunk::collect_visible_objects;
 pub(crate) use packed_bounding_sphere_chunk::PackedBoundingSphereChunk;
+pub(crate) use portal_frustum::{portal_frustum, PortalProjection};
+pub(crate) use shadow_caster_volume::{shadow_caster_volume, ShadowCasterLight};
@@ -0,0 +1,27 @@
+use crate::internal::ZoneOrientedBox;
+use crate::{ObjectHandle, VisibleObjects};
+use slotmap::SecondaryMap;
+
+/// Removes the `objects` from `first_object` onward whose `ZoneOrientedBox` is rejected by
+/// `is_visible`. `Objects` without a box are kept. Returns the number of removed `objects`.
+pub(crate) fn cull_oriented_boxes<F: Fn(&ZoneOrientedBox) -> bool>(
+    objects: &mut VisibleObjects,
+    first_object: usize,
+    oriented_boxes: &SecondaryMap<ObjectHandle, ZoneOrientedBox>,
+    is_visible: F,
+) -> usize {
+    if oriented_boxes.is_empty() || first_object == objects.len() {
+        return 0;
+    }
+
+    let candidates = objects.split_off(first_object);
+    let num_candidates = candidates.len();
+    objects.extend(candidates.into_iter().filter(
+        |object| match oriented_boxes.get(object.handle) {
+            Some(oriented_box) => is_visible(oriented_box),
+            None => true,
+        },
+    ));
+
+    num_candidates - (objects.len() - first_object)
+}
@@ -104,7 +104,6 @@ impl PackedBoundingSphereChunk {
         &self.metadata[index.0]
     }
 
-    #[allow(dead_code)]
     pub fn get(
         &self,
         index: usize,
@@ -177,14 +176,31 @@ pub(crate) fn collect_visible_objects(
 
         collect_visible_objects_fast(chunk, view_frustum_position, &planes, results);
     } else {
-        // TODO(dvd): Write other methods if we need non-standard frustum.
-        panic!(
-            "Implement new find_visible_objects_in_vec for frustum with {} planes.",
-            frustum.planes.len()
-        );
+        collect_visible_objects_slow(chunk, view_frustum_position, frustum, results);
     };
 }
 
+// NOTE(dvd): Frustums clipped to a portal may have any number of planes.
+fn collect_visible_objects_slow(
+    chunk: &PackedBoundingSphereChunk,
+    view_frustum_position: Vec3,
+    frustum: &Frustum,
+    results: &mut VisibleObjects,
+) {
+    for index in 0..chunk.len() {
+        let sphere = chunk.get(index);
+        if frustum.contains_sphere_slow(&sphere) {
+            let object = chunk.metadata[index];
+            results.push(VisibilityResult::new(
+                object.handle,
+                object.id,
+                view_frustum_position,
+                sphere,
+            ));
+        }
+    }
+}
+
 fn collect_visible_objects_fast(
     chunk: &PackedBoundingSphereChunk,
     view_frustum_position: Vec3,
@@ -230,6 +246,45 @@ fn collect_visible_objects_fast(
     }
 }
 
+pub(crate) fn collect_shadow_casters(
+    chunk: &PackedBoundingSphereChunk,
+    light_position: Vec3,
+    light_frustum: &Frustum,
+    caster_volumes: &[Frustum],
+    results: &mut VisibleObjects,
+) {
+    assert_eq!(light_frustum.planes.len(), 6);
+
+    let mut planes = [Vec4::ZERO; 6];
+    for (plane, light_plane) in planes.iter_mut().zip(&light_frustum.planes) {
+        *plane = light_plane.normal;
+    }
+
+    for index in 0..chunk.len() {
+        let internal_index = chunk.get_internal_index(Index(index));
+        let spheres = &chunk.spheres[internal_index.0];
+
+        // NOTE(dvd): The light frustum is the cheaper test, so reject with it first.
+        if (spheres.is_contained_by(&planes) & (1 << internal_index.1)) == 0 {
+            continue;
+        }
+
+        let sphere = spheres.get(internal_index.1);
+        if caster_volumes
+            .iter()
+            .any(|volume| volume.contains_sphere_slow(&sphere))
+        {
+            let object = chunk.metadata[index];
+            results.push(VisibilityResult::new(
+                object.handle,
+                object.id,
+                light_position,
+                sphere,
+            ));
+        }
+    }
+}
+
 struct Query<'a> {
     pub view_frustum_position: Vec3,
     pub spheres: &'a PackedBoundingSphere,
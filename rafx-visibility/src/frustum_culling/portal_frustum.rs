@@ -0,0 +1,111 @@
+use crate::geometry::{Frustum, Plane};
+use glam::Vec3;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum PortalProjection {
+    Perspective { eye_position: Vec3 },
+    Orthographic { view_direction: Vec3 },
+}
+
+// NOTE(dvd): Sutherland-Hodgman clipping of a convex polygon against each plane of the frustum.
+fn clip_polygon(
+    polygon: &[Vec3],
+    frustum: &Frustum,
+) -> Vec<Vec3> {
+    let mut output = polygon.to_vec();
+    for plane in &frustum.planes {
+        if output.is_empty() {
+            break;
+        }
+
+        let input = std::mem::take(&mut output);
+        for (index, current) in input.iter().enumerate() {
+            let next = input[(index + 1) % input.len()];
+            let current_distance = plane.distance(*current);
+            let next_distance = plane.distance(next);
+
+            if current_distance >= 0. {
+                output.push(*current);
+            }
+
+            if (current_distance >= 0.) != (next_distance >= 0.) {
+                let t = current_distance / (current_distance - next_distance);
+                output.push(*current + (next - *current) * t);
+            }
+        }
+    }
+
+    output
+}
+
+/// Returns the part of `frustum` that is visible through the convex `polygon`, or `None` if the
+/// `polygon` cannot be seen. The result is bounded by the plane of the `polygon`, the `far_plane`
+/// of the view, and a plane through each edge of the `polygon` after it is clipped to `frustum`.
+pub(crate) fn portal_frustum(
+    frustum: &Frustum,
+    far_plane: Plane,
+    projection: &PortalProjection,
+    polygon: &[Vec3],
+) -> Option<Frustum> {
+    let clipped = clip_polygon(polygon, frustum);
+    if clipped.len() < 3 {
+        return None;
+    }
+
+    let center =
+        clipped.iter().fold(Vec3::ZERO, |sum, vertex| sum + *vertex) / clipped.len() as f32;
+
+    // NOTE(dvd): Newell's method is robust to the extra vertices added by clipping.
+    let mut normal = Vec3::ZERO;
+    for (index, current) in clipped.iter().enumerate() {
+        let next = clipped[(index + 1) % clipped.len()];
+        normal += (*current - center).cross(next - center);
+    }
+
+    if normal.length_squared() <= f32::EPSILON {
+        return None;
+    }
+
+    // NOTE(dvd): If the view is in line with the portal, keep the whole frustum. This is always safe.
+    let facing = match projection {
+        PortalProjection::Perspective { eye_position } => normal.dot(center - *eye_position),
+        PortalProjection::Orthographic { view_direction } => normal.dot(*view_direction),
+    };
+
+    if facing.abs() <= f32::EPSILON {
+        return Some(frustum.clone());
+    }
+
+    let mut portal_frustum = Frustum::new(clipped.len() + 2);
+    portal_frustum
+        .planes
+        .push(Plane::new(normal * facing.signum(), center));
+    portal_frustum.planes.push(far_plane);
+
+    for (index, current) in clipped.iter().enumerate() {
+        let next = clipped[(index + 1) % clipped.len()];
+        let edge_normal = match projection {
+            PortalProjection::Perspective { eye_position } => {
+                (*current - *eye_position).cross(next - *eye_position)
+            }
+            PortalProjection::Orthographic { view_direction } => {
+                (next - *current).cross(*view_direction)
+            }
+        };
+
+        if edge_normal.length_squared() <= f32::EPSILON {
+            // NOTE(dvd): Skipping a plane only makes the frustum larger.
+            continue;
+        }
+
+        let mut plane = Plane::new(edge_normal, *current);
+        if plane.distance(center) < 0. {
+            plane = Plane::new(-edge_normal, *current);
+        }
+
+        portal_frustum.planes.push(plane);
+    }
+
+    portal_frustum.update();
+    Some(portal_frustum)
+}
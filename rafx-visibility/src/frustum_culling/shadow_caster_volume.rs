@@ -0,0 +1,114 @@
+use crate::geometry::{Frustum, Plane};
+use crate::ViewFrustum;
+use glam::{Vec3, Vec4};
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum ShadowCasterLight {
+    /// Light that travels in parallel rays, i.e. a light with an orthographic shadow projection.
+    /// `direction_to_light` points from the scene back toward the light.
+    Directional { direction_to_light: Vec3 },
+    /// Light that is emitted from a point, i.e. a spot light or one face of a point light.
+    Positional { position: Vec3 },
+}
+
+// NOTE(dvd): The corners of a frustum are where one plane from each group meets.
+const PLANE_GROUPS: [[usize; 2]; 3] = [
+    [ViewFrustum::NEAR, ViewFrustum::FAR],
+    [ViewFrustum::LEFT, ViewFrustum::RIGHT],
+    [ViewFrustum::TOP, ViewFrustum::BOTTOM],
+];
+
+fn intersect_planes(
+    p1: Vec4,
+    p2: Vec4,
+    p3: Vec4,
+) -> Vec3 {
+    let n1 = p1.truncate();
+    let n2 = p2.truncate();
+    let n3 = p3.truncate();
+
+    let denominator = n1.dot(n2.cross(n3));
+    (n2.cross(n3) * -p1.w + n3.cross(n1) * -p2.w + n1.cross(n2) * -p3.w) / denominator
+}
+
+/// Returns the volume that contains every point that can shadow a point in `frustum`. This is the
+/// `frustum` extruded toward the light. A sphere outside of the volume cannot cast a shadow into
+/// the `frustum`.
+pub(crate) fn shadow_caster_volume(
+    frustum: &Frustum,
+    light: &ShadowCasterLight,
+) -> Frustum {
+    assert_eq!(frustum.planes.len(), 6);
+
+    let planes = &frustum.planes;
+
+    let mut corners = Vec::with_capacity(8);
+    for &a in &PLANE_GROUPS[0] {
+        for &b in &PLANE_GROUPS[1] {
+            for &c in &PLANE_GROUPS[2] {
+                corners.push(intersect_planes(
+                    planes[a].normal,
+                    planes[b].normal,
+                    planes[c].normal,
+                ));
+            }
+        }
+    }
+
+    let interior_point = corners.iter().fold(Vec3::ZERO, |sum, corner| sum + *corner) / 8.;
+
+    // NOTE(dvd): A plane bounds the extruded volume if moving toward the light does not leave it.
+    let faces_light = |plane: &Plane| match light {
+        ShadowCasterLight::Directional { direction_to_light } => {
+            plane.dot(*direction_to_light) >= 0.
+        }
+        ShadowCasterLight::Positional { position } => plane.distance(*position) >= 0.,
+    };
+
+    let mut volume = Frustum::new(12);
+    for plane in planes {
+        if faces_light(plane) {
+            volume.planes.push(*plane);
+        }
+    }
+
+    // NOTE(dvd): Close the volume with a plane through each silhouette edge (an edge between a
+    // plane facing the light and one facing away) and the light.
+    for (group_a, group_b, group_c) in [(0, 1, 2), (0, 2, 1), (1, 2, 0)].iter().copied() {
+        for &a in &PLANE_GROUPS[group_a] {
+            for &b in &PLANE_GROUPS[group_b] {
+                if faces_light(&planes[a]) == faces_light(&planes[b]) {
+                    continue;
+                }
+
+                let [c1, c2] = PLANE_GROUPS[group_c];
+                let edge_start =
+                    intersect_planes(planes[a].normal, planes[b].normal, planes[c1].normal);
+                let edge_end =
+                    intersect_planes(planes[a].normal, planes[b].normal, planes[c2].normal);
+
+                let toward_light = match light {
+                    ShadowCasterLight::Directional { direction_to_light } => *direction_to_light,
+                    ShadowCasterLight::Positional { position } => *position - edge_start,
+                };
+
+                let normal = (edge_end - edge_start).cross(toward_light);
+                if normal.length_squared() <= f32::EPSILON {
+                    // NOTE(dvd): The light is in line with the edge. Skipping the plane only makes
+                    // the volume larger.
+                    continue;
+                }
+
+                let mut plane = Plane::new(normal, edge_start);
+                if plane.distance(interior_point) < 0. {
+                    plane = Plane::new(-normal, edge_start);
+                }
+
+                volume.planes.push(plane);
+            }
+        }
+    }
+
+    volume.update();
+    volume
+}
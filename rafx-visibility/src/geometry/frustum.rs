@@ -61,6 +61,25 @@ impl Frustum {
         return true;
     }
 
+    /// Returns `true` if the box with `center` and `half_axes` is at least partially inside of
+    /// the frustum. The `half_axes` do not need to be orthogonal.
+    #[inline(never)]
+    pub fn contains_box_slow(
+        &self,
+        center: Vec3,
+        half_axes: &[Vec3; 3],
+    ) -> bool {
+        assert!(!self.is_invalid);
+
+        self.planes.iter().all(|plane| {
+            let radius = half_axes
+                .iter()
+                .map(|axis| plane.dot(*axis).abs())
+                .sum::<f32>();
+            plane.distance(center) > -radius
+        })
+    }
+
     #[inline(never)]
     pub fn contains_sphere_fast(
         &self,
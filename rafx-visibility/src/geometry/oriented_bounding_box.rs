@@ -1,10 +1,51 @@
+use crate::geometry::Transform;
 use glam::{Quat, Vec3};
 use serde::Deserialize;
 use serde::Serialize;
 
+/// A box that is rotated by `rotation`. `min` and `max` are the corners of the box before it is rotated.
 #[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OrientedBoundingBox {
     pub min: Vec3,
     pub max: Vec3,
     pub rotation: Quat,
 }
+
+impl OrientedBoundingBox {
+    pub fn center(&self) -> Vec3 {
+        self.rotation * ((self.min + self.max) * 0.5)
+    }
+
+    /// Returns the vectors from the center of the box to the center of three of its faces.
+    pub fn half_axes(&self) -> [Vec3; 3] {
+        let half_extents = (self.max - self.min) * 0.5;
+        [
+            self.rotation * Vec3::new(half_extents.x, 0., 0.),
+            self.rotation * Vec3::new(0., half_extents.y, 0.),
+            self.rotation * Vec3::new(0., 0., half_extents.z),
+        ]
+    }
+
+    pub fn volume(&self) -> f32 {
+        let extents = self.max - self.min;
+        extents.x * extents.y * extents.z
+    }
+
+    /// Returns the center and half axes of the box after it is moved by `transform`. The half axes
+    /// are not orthogonal if the `transform` has a non-uniform scale.
+    pub fn transformed(
+        &self,
+        transform: &Transform,
+    ) -> (Vec3, [Vec3; 3]) {
+        let [x, y, z] = self.half_axes();
+        let transform_vector = |vector: Vec3| transform.rotation * (vector * transform.scale);
+        (
+            transform_vector(self.center()) + transform.translation,
+            [
+                transform_vector(x),
+                transform_vector(y),
+                transform_vector(z),
+            ],
+        )
+    }
+}
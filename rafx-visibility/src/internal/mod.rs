@@ -1,5 +1,5 @@
 mod zone;
-pub(crate) use zone::Zone;
+pub(crate) use zone::{Zone, ZoneOccluder, ZoneOrientedBox, ZoneVolume};
 
 mod visibility_object;
 pub(crate) use visibility_object::VisibilityObject;
@@ -8,6 +8,7 @@ pub struct VisibilityObject {
     pub zone: Option<ZoneHandle>,
     pub cull_model: Option<ModelHandle>,
     pub transform: Transform,
+    pub is_occluder: bool,
 }
 
 impl VisibilityObject {
@@ -1,9 +1,11 @@
-use crate::frustum_culling::PackedBoundingSphereChunk;
+use crate::frustum_culling::{LooseOctree, PackedBoundingSphereChunk};
 use crate::geometry::{BoundingSphere, Transform};
-use crate::internal::{VisibilityObject, Volume, Zone};
+use crate::internal::{VisibilityObject, Volume, Zone, ZoneOccluder, ZoneOrientedBox, ZoneVolume};
+use crate::occlusion_culling::transformed_triangles;
 use crate::{
-    DepthRange, ModelHandle, ObjectHandle, PolygonSoup, PolygonSoupIndex, ViewFrustum,
-    ViewFrustumHandle, VisibleBounds, VolumeHandle, ZoneHandle,
+    CullShape, DepthRange, ModelHandle, ObjectHandle, OcclusionCullingConfig, PolygonSoup,
+    PolygonSoupIndex, Portal, ViewFrustum, ViewFrustumHandle, VisibleBounds, VolumeHandle,
+    ZoneAcceleration, ZoneHandle,
 };
 use glam::Vec3;
 use parking_lot::RwLock;
@@ -16,15 +18,16 @@ pub struct VisibilityWorld {
 
     pub(crate) models: SlotMap<ModelHandle, VisibleBounds>,
     pub(crate) model_ref_counts: SecondaryMap<ModelHandle, u64>,
-    pub(crate) model_hashes: FxHashMap<u64, ModelHandle>,
+    pub(crate) model_hashes: FxHashMap<(u64, CullShape), ModelHandle>,
+    pub(crate) model_polygons: SecondaryMap<ModelHandle, PolygonSoup>,
 
     pub(crate) objects: DenseSlotMap<ObjectHandle, VisibilityObject>,
 
     pub(crate) view_frustums: DenseSlotMap<ViewFrustumHandle, Arc<RwLock<ViewFrustum>>>,
     pub(crate) view_frustum_ids: SecondaryMap<ViewFrustumHandle, u64>,
     pub(crate) view_frustum_zones: SecondaryMap<ViewFrustumHandle, ZoneHandle>,
+    pub(crate) view_frustum_occlusion: SecondaryMap<ViewFrustumHandle, OcclusionCullingConfig>,
 
-    #[allow(dead_code)]
     pub(crate) volumes: DenseSlotMap<VolumeHandle, Volume>,
 }
 
@@ -36,12 +39,14 @@ impl VisibilityWorld {
             models: Default::default(),
             model_ref_counts: Default::default(),
             model_hashes: Default::default(),
+            model_polygons: Default::default(),
 
             objects: Default::default(),
 
             view_frustums: Default::default(),
             view_frustum_ids: Default::default(),
             view_frustum_zones: Default::default(),
+            view_frustum_occlusion: Default::default(),
 
             volumes: Default::default(),
         }
@@ -64,7 +69,34 @@ impl VisibilityWorld {
     ) {
         let removed = self.zones.remove(zone).unwrap();
         assert_eq!(removed.objects.len(), 0);
-        assert_eq!(removed.volumes.len(), 0);
+        assert_eq!(removed.volumes.read().len(), 0);
+    }
+
+    /// Sets how the `Zone` organizes its `Objects` for visibility queries. A `LooseOctree` is
+    /// built from the `Objects` already in the `Zone` and is kept up to date as they move.
+    pub fn set_zone_acceleration(
+        &mut self,
+        zone: ZoneHandle,
+        acceleration: ZoneAcceleration,
+    ) {
+        let zone = self.zones.get_mut(zone).unwrap();
+        zone.octree = match acceleration {
+            ZoneAcceleration::LinearScan => None,
+            ZoneAcceleration::LooseOctree { min_node_size } => {
+                let chunks = zone.chunks.read();
+                let mut octree = LooseOctree::new(min_node_size);
+                for (handle, (chunk_idx, in_chunk_idx)) in zone.objects.iter() {
+                    let chunk = &chunks[*chunk_idx];
+                    octree.insert(
+                        handle,
+                        chunk.metadata(*in_chunk_idx).id,
+                        chunk.get(*in_chunk_idx),
+                    );
+                }
+
+                Some(Arc::new(RwLock::new(octree)))
+            }
+        };
     }
 
     // --------
@@ -144,6 +176,28 @@ impl VisibilityWorld {
         }
     }
 
+    /// Enables occlusion culling for the `ViewFrustum`. The occluder `Objects` in view are rasterized
+    /// into a depth buffer sized by the `config`, and `Objects` hidden behind them are not visible.
+    pub fn set_view_frustum_occlusion(
+        &mut self,
+        view_frustum: ViewFrustumHandle,
+        config: Option<OcclusionCullingConfig>,
+    ) {
+        if let Some(config) = config {
+            self.view_frustum_occlusion.insert(view_frustum, config);
+        } else {
+            self.view_frustum_occlusion.remove(view_frustum);
+        }
+    }
+
+    /// Returns the `ViewFrustum`'s occlusion culling settings, if it is enabled.
+    pub fn get_view_frustum_occlusion(
+        &self,
+        view_frustum: ViewFrustumHandle,
+    ) -> Option<&OcclusionCullingConfig> {
+        self.view_frustum_occlusion.get(view_frustum)
+    }
+
     /// Returns the `Object`'s `Zone`. A `ViewFrustum` must be in a `Zone` to query visibility.
     pub fn get_view_frustum_zone(
         &self,
@@ -183,6 +237,7 @@ impl VisibilityWorld {
         view_frustum: ViewFrustumHandle,
     ) {
         self.set_view_frustum_zone(view_frustum, None);
+        self.set_view_frustum_occlusion(view_frustum, None);
         self.view_frustums.remove(view_frustum);
     }
 
@@ -194,15 +249,26 @@ impl VisibilityWorld {
     pub fn new_model(
         &mut self,
         polygons: PolygonSoup,
+    ) -> ModelHandle {
+        self.new_model_with_cull_shape(polygons, CullShape::default())
+    }
+
+    /// Returns a handle to a `Model` created from `PolygonSoup` that is culled with `cull_shape`.
+    pub fn new_model_with_cull_shape(
+        &mut self,
+        polygons: PolygonSoup,
+        cull_shape: CullShape,
     ) -> ModelHandle {
         let hash = polygons.calculate_hash();
-        return if let Some(handle) = self.model_hashes.get(&hash) {
+        return if let Some(handle) = self.model_hashes.get(&(hash, cull_shape)) {
             // NOTE(dvd): Return the existing model.
             *handle
         } else {
-            // NOTE(dvd): Create a new model.
-            let handle = self.models.insert(VisibleBounds::new(hash, polygons));
-            self.model_hashes.insert(hash, handle);
+            // NOTE(dvd): Create a new model. The polygons are kept for objects that are occluders.
+            let bounds = VisibleBounds::new(hash, &polygons).with_cull_shape(cull_shape);
+            let handle = self.models.insert(bounds);
+            self.model_hashes.insert((hash, cull_shape), handle);
+            self.model_polygons.insert(handle, polygons);
             handle
         };
     }
@@ -212,14 +278,14 @@ impl VisibilityWorld {
         &mut self,
         bounds: VisibleBounds,
     ) -> ModelHandle {
-        let hash = bounds.hash;
-        return if let Some(handle) = self.model_hashes.get(&hash) {
+        let key = (bounds.hash, bounds.cull_shape);
+        return if let Some(handle) = self.model_hashes.get(&key) {
             // NOTE(dvd): Return the existing model.
             *handle
         } else {
             // NOTE(dvd): Store the model.
             let handle = self.models.insert(bounds);
-            self.model_hashes.insert(hash, handle);
+            self.model_hashes.insert(key, handle);
             handle
         };
     }
@@ -267,7 +333,9 @@ impl VisibilityWorld {
         }
 
         let removed_model = self.models.remove(model).unwrap();
-        self.model_hashes.remove(&removed_model.hash);
+        self.model_hashes
+            .remove(&(removed_model.hash, removed_model.cull_shape));
+        self.model_polygons.remove(model);
         true
     }
 
@@ -298,6 +366,10 @@ impl VisibilityWorld {
             let chunk: &mut PackedBoundingSphereChunk = chunks.get_mut(chunk_idx).unwrap();
 
             chunk.update_id(in_chunk_idx, id);
+
+            if let Some(octree) = &zone.octree {
+                octree.write().update_id(object.handle, id);
+            }
         }
     }
 
@@ -389,6 +461,30 @@ impl VisibilityWorld {
         self.objects.get(object).unwrap().cull_model
     }
 
+    /// Sets whether the `Object` is an occluder. An occluder's cull `Model` hides the `Objects` behind
+    /// it from a `ViewFrustum` with occlusion culling. The `Model` must be created with `new_model`.
+    pub fn set_object_occluder(
+        &mut self,
+        object: ObjectHandle,
+        is_occluder: bool,
+    ) {
+        let handle = object;
+        let object = self.objects.get_mut(object).unwrap();
+        object.is_occluder = is_occluder;
+
+        if let Some(zone) = object.zone {
+            self.internal_update_object_in_zone(handle, zone);
+        }
+    }
+
+    /// Returns `true` if the `Object` is an occluder.
+    pub fn is_object_occluder(
+        &self,
+        object: ObjectHandle,
+    ) -> bool {
+        self.objects.get(object).unwrap().is_occluder
+    }
+
     /// Destroying an `Object` will also remove it from the `Zone`.
     /// This will **NOT** destroy the cull `Model`.
     pub fn destroy_object(
@@ -404,83 +500,198 @@ impl VisibilityWorld {
     // Volumes
     // --------
 
-    /*
-    /// Creates a new `Volume`. An `Volume` must be in a `Zone` to be visible.
-    pub fn new_volume(&self) -> VolumeHandle {
-        unimplemented!();
+    /// Creates a new `Volume`. A `Volume` must be in a `Zone` to be visible.
+    pub fn new_volume(&mut self) -> VolumeHandle {
+        self.volumes
+            .insert_with_key(|handle| Volume::new(0, handle))
     }
 
     /// Sets the `Volume`'s ID. This is an arbitrary 64-bit number for use by the application.
     /// It should correspond to a game object ID, or a pointer, or an ECS entity ID.
     pub fn set_volume_id(
-        &self,
-        _volume: VolumeHandle,
-        _id: u64,
+        &mut self,
+        volume: VolumeHandle,
+        id: u64,
     ) {
-        unimplemented!();
+        let volume = self.volumes.get_mut(volume).unwrap();
+        volume.id = id;
+
+        if volume.zone.is_some() {
+            let handle = volume.handle;
+            self.internal_update_volume_in_zone(handle);
+        }
     }
 
-    /// Sets the `Volume`'s `Zone`. An `Volume` must be in a `Zone` to be visible.
+    /// Sets the `Volume`'s `Zone`. A `Volume` must be in a `Zone` to be visible.
     pub fn set_volume_zone(
-        &self,
-        _volume: VolumeHandle,
-        _zone: ZoneHandle,
+        &mut self,
+        volume: VolumeHandle,
+        zone: Option<ZoneHandle>,
     ) {
-        unimplemented!();
+        let handle = volume;
+        let volume = self.volumes.get_mut(handle).unwrap();
+
+        if let Some(zone) = volume.zone {
+            self.zones.get(zone).unwrap().volumes.write().remove(handle);
+        }
+
+        volume.zone = zone;
+
+        if zone.is_some() {
+            self.internal_update_volume_in_zone(handle);
+        }
     }
 
-    /// Returns the `Volume`'s `Zone`. An `Volume` must be in a `Zone` to be visible.
+    /// Returns the `Volume`'s `Zone`. A `Volume` must be in a `Zone` to be visible.
     pub fn get_volume_zone(
         &self,
-        _volume: VolumeHandle,
+        volume: VolumeHandle,
     ) -> Option<ZoneHandle> {
-        None
+        self.volumes.get(volume).unwrap().zone
     }
 
     /// Sets the `Volume`'s position relative to the `Zone`'s position.
     pub fn set_volume_position(
-        &self,
-        _volume: VolumeHandle,
-        _transform: Transform,
+        &mut self,
+        volume: VolumeHandle,
+        transform: Transform,
     ) {
-        unimplemented!();
+        let volume = self.volumes.get_mut(volume).unwrap();
+        volume.transform = transform;
+
+        if volume.zone.is_some() {
+            let handle = volume.handle;
+            self.internal_update_volume_in_zone(handle);
+        }
     }
 
     /// Returns the `Volume`'s position relative to the `Zone`'s position.
     pub fn get_volume_position(
         &self,
-        _volume: VolumeHandle,
+        volume: VolumeHandle,
     ) -> Transform {
-        Transform::default()
+        self.volumes.get(volume).unwrap().transform
     }
 
     /// Sets the `Volume`'s `Model`. The `Model` is tested against the `ViewFrustum` frustum for intersections.
     /// This is like a `Collider` in a collision API.
     pub fn set_volume_model(
-        &self,
-        _volume: VolumeHandle,
-        _model: ModelHandle,
+        &mut self,
+        volume: VolumeHandle,
+        model: Option<ModelHandle>,
     ) {
-        unimplemented!();
+        let volume = self.volumes.get_mut(volume).unwrap();
+
+        if let Some(model) = volume.model {
+            let count = *self.model_ref_counts.get(model).unwrap();
+            self.model_ref_counts.insert(model, count - 1);
+        }
+
+        volume.model = model;
+
+        if let Some(model) = volume.model {
+            let count = self.model_ref_counts.get(model).map_or(0, |count| *count);
+            self.model_ref_counts.insert(model, count + 1);
+        }
+
+        if volume.zone.is_some() {
+            let handle = volume.handle;
+            self.internal_update_volume_in_zone(handle);
+        }
     }
 
     /// Returns the `Model` associated with the `Volume`.
     pub fn get_volume_model(
         &self,
-        _volume: VolumeHandle,
+        volume: VolumeHandle,
     ) -> Option<ModelHandle> {
-        None
+        self.volumes.get(volume).unwrap().model
     }
 
-    /// Destroying an `Volume` will also remove it from the `Zone`.
-    /// This will **NOT** destroy the cull `Model`.
-    pub fn destroy_volume(
+    /// Makes the `Volume` a `Portal` into another `Zone`. A `ViewFrustum` that can see the portal
+    /// polygon will also see the `Objects` and `Volumes` in the `Portal`'s `target_zone`.
+    pub fn set_volume_portal(
+        &mut self,
+        volume: VolumeHandle,
+        portal: Option<Portal>,
+    ) {
+        let volume = self.volumes.get_mut(volume).unwrap();
+        volume.portal = portal;
+
+        if volume.zone.is_some() {
+            let handle = volume.handle;
+            self.internal_update_volume_in_zone(handle);
+        }
+    }
+
+    /// Returns the `Portal` associated with the `Volume`.
+    pub fn get_volume_portal(
         &self,
-        _volume: VolumeHandle,
+        volume: VolumeHandle,
+    ) -> Option<&Portal> {
+        self.volumes.get(volume).unwrap().portal.as_ref()
+    }
+
+    /// Destroying a `Volume` will also remove it from the `Zone`.
+    /// This will **NOT** destroy the `Model`.
+    pub fn destroy_volume(
+        &mut self,
+        volume: VolumeHandle,
+    ) {
+        self.set_volume_zone(volume, None);
+        self.set_volume_model(volume, None);
+        self.volumes.remove(volume).unwrap();
+    }
+
+    fn internal_update_volume_in_zone(
+        &mut self,
+        volume: VolumeHandle,
     ) {
-        unimplemented!();
+        let volume = self.volumes.get(volume).unwrap();
+        let transform = volume.transform;
+
+        let portal_polygon: Vec<Vec3> = volume.portal.as_ref().map_or(Vec::new(), |portal| {
+            portal
+                .polygon
+                .iter()
+                .map(|vertex| {
+                    transform.rotation * (*vertex * transform.scale) + transform.translation
+                })
+                .collect()
+        });
+
+        let bounding_sphere = if let Some(model) = volume.model {
+            let model = self.models.get(model).unwrap();
+            BoundingSphere::new(
+                transform.translation
+                    + transform.rotation * (model.bounding_sphere.position * transform.scale),
+                model.bounding_sphere.radius * transform.scale.max_element(),
+            )
+        } else if !portal_polygon.is_empty() {
+            let center = portal_polygon
+                .iter()
+                .fold(Vec3::ZERO, |sum, vertex| sum + *vertex)
+                / portal_polygon.len() as f32;
+            let radius = portal_polygon.iter().fold(0., |radius: f32, vertex| {
+                radius.max(center.distance(*vertex))
+            });
+            BoundingSphere::new(center, radius)
+        } else {
+            VisibilityObject::default_bounding_sphere(transform)
+        };
+
+        let zone = self.zones.get(volume.zone.unwrap()).unwrap();
+        zone.volumes.write().insert(
+            volume.handle,
+            ZoneVolume {
+                handle: volume.handle,
+                id: volume.id,
+                bounding_sphere,
+                portal_target: volume.portal.as_ref().map(|portal| portal.target_zone),
+                portal_polygon,
+            },
+        );
     }
-    */
 
     fn internal_add_object_to_zone(
         &mut self,
@@ -508,15 +719,18 @@ impl VisibilityWorld {
             }
         };
 
+        let bounding_sphere = VisibilityObject::default_bounding_sphere(object.transform);
         let in_chunk_idx = next_chunk
-            .add(
-                object.handle,
-                object.id,
-                VisibilityObject::default_bounding_sphere(object.transform),
-            )
+            .add(object.handle, object.id, bounding_sphere)
             .unwrap();
         zone.objects
             .insert(object.handle, (chunk_idx, in_chunk_idx));
+
+        if let Some(octree) = &zone.octree {
+            octree
+                .write()
+                .insert(object.handle, object.id, bounding_sphere);
+        }
     }
 
     fn internal_update_object_in_zone(
@@ -525,27 +739,61 @@ impl VisibilityWorld {
         zone: ZoneHandle,
     ) {
         let object = self.objects.get(object).unwrap();
+
+        // NOTE(dvd): Resolve the model and the occluder's triangles before the zone is borrowed.
+        let models = &self.models;
+        let model = object.cull_model.and_then(|model| models.get(model));
+        let occluder_triangles = object
+            .cull_model
+            .filter(|_| object.is_occluder)
+            .and_then(|model| self.model_polygons.get(model))
+            .map(|polygons| transformed_triangles(polygons, &object.transform));
+
         let zone = self.zones.get_mut(zone).unwrap();
         let mut chunks = zone.chunks.write();
 
         let (chunk_idx, in_chunk_idx) = *zone.objects.get(object.handle).unwrap();
         let chunk: &mut PackedBoundingSphereChunk = chunks.get_mut(chunk_idx).unwrap();
 
-        if let Some(model) = object.cull_model {
-            let model = self.models.get(model).unwrap();
-            chunk.update(
-                in_chunk_idx,
-                BoundingSphere::new(
-                    object.transform.translation
-                        + model.bounding_sphere.position * object.transform.scale,
-                    model.bounding_sphere.radius * object.transform.scale.max_element(),
-                ),
+        let bounding_sphere = if let Some(model) = model {
+            BoundingSphere::new(
+                object.transform.translation
+                    + object.transform.rotation
+                        * (model.bounding_sphere.position * object.transform.scale),
+                model.bounding_sphere.radius * object.transform.scale.max_element(),
             )
         } else {
-            chunk.update(
-                in_chunk_idx,
-                VisibilityObject::default_bounding_sphere(object.transform),
+            VisibilityObject::default_bounding_sphere(object.transform)
+        };
+
+        chunk.update(in_chunk_idx, bounding_sphere);
+
+        if let Some(octree) = &zone.octree {
+            octree.write().update(object.handle, bounding_sphere);
+        }
+
+        let oriented_box = model
+            .filter(|model| model.cull_shape == CullShape::OrientedBoundingBox)
+            .map(|model| model.obb.transformed(&object.transform));
+
+        let mut oriented_boxes = zone.oriented_boxes.write();
+        if let Some((center, half_axes)) = oriented_box {
+            oriented_boxes.insert(object.handle, ZoneOrientedBox { center, half_axes });
+        } else {
+            oriented_boxes.remove(object.handle);
+        }
+
+        let mut occluders = zone.occluders.write();
+        if let Some(triangles) = occluder_triangles {
+            occluders.insert(
+                object.handle,
+                ZoneOccluder {
+                    bounding_sphere,
+                    triangles,
+                },
             );
+        } else {
+            occluders.remove(object.handle);
         }
     }
 
@@ -566,5 +814,12 @@ impl VisibilityWorld {
             zone.objects
                 .insert(metadata.handle, (chunk_idx, in_chunk_idx));
         }
+
+        if let Some(octree) = &zone.octree {
+            octree.write().remove(object.handle);
+        }
+
+        zone.occluders.write().remove(object.handle);
+        zone.oriented_boxes.write().remove(object.handle);
     }
 }
@@ -1 +1,25 @@
-pub struct Volume {}
+use crate::geometry::Transform;
+use crate::{ModelHandle, Portal, VolumeHandle, ZoneHandle};
+
+#[derive(Default, Clone)]
+pub struct Volume {
+    pub handle: VolumeHandle,
+    pub id: u64,
+    pub zone: Option<ZoneHandle>,
+    pub model: Option<ModelHandle>,
+    pub transform: Transform,
+    pub portal: Option<Portal>,
+}
+
+impl Volume {
+    pub fn new(
+        id: u64,
+        handle: VolumeHandle,
+    ) -> Self {
+        Volume {
+            id,
+            handle,
+            ..Default::default()
+        }
+    }
+}
@@ -1,14 +1,42 @@
-use crate::frustum_culling::PackedBoundingSphereChunk;
-use crate::internal::Volume;
-use crate::{ObjectHandle, VolumeHandle};
+use crate::frustum_culling::{LooseOctree, PackedBoundingSphereChunk};
+use crate::geometry::BoundingSphere;
+use crate::{ObjectHandle, VolumeHandle, ZoneHandle};
+use glam::Vec3;
 use parking_lot::RwLock;
-use slotmap::{SecondaryMap, SlotMap};
+use slotmap::SecondaryMap;
 use std::sync::Arc;
 
+/// The part of a `Volume` that is read by visibility queries. Positions are in the `Zone`'s space.
+#[derive(Clone)]
+pub(crate) struct ZoneVolume {
+    pub handle: VolumeHandle,
+    pub id: u64,
+    pub bounding_sphere: BoundingSphere,
+    pub portal_target: Option<ZoneHandle>,
+    pub portal_polygon: Vec<Vec3>,
+}
+
+/// The `OrientedBoundingBox` of an `Object` whose cull `Model` has `CullShape::OrientedBoundingBox`.
+/// It is in the `Zone`'s space, and the `half_axes` are not orthogonal if the `Object` has a
+/// non-uniform scale.
+pub(crate) struct ZoneOrientedBox {
+    pub center: Vec3,
+    pub half_axes: [Vec3; 3],
+}
+
+/// An `Object` whose cull `Model` hides the `Objects` behind it. The triangles are in the `Zone`'s space.
+pub(crate) struct ZoneOccluder {
+    pub bounding_sphere: BoundingSphere,
+    pub triangles: Vec<[Vec3; 3]>,
+}
+
 pub struct Zone {
     pub(crate) chunks: Arc<RwLock<Vec<PackedBoundingSphereChunk>>>,
     pub(crate) objects: SecondaryMap<ObjectHandle, (usize, usize)>,
-    pub(crate) volumes: SlotMap<VolumeHandle, Volume>,
+    pub(crate) octree: Option<Arc<RwLock<LooseOctree>>>,
+    pub(crate) volumes: Arc<RwLock<SecondaryMap<VolumeHandle, ZoneVolume>>>,
+    pub(crate) occluders: Arc<RwLock<SecondaryMap<ObjectHandle, ZoneOccluder>>>,
+    pub(crate) oriented_boxes: Arc<RwLock<SecondaryMap<ObjectHandle, ZoneOrientedBox>>>,
 }
 
 impl Zone {
@@ -16,7 +44,10 @@ impl Zone {
         Zone {
             chunks: Default::default(),
             objects: Default::default(),
+            octree: None,
             volumes: Default::default(),
+            occluders: Default::default(),
+            oriented_boxes: Default::default(),
         }
     }
 }
@@ -1,5 +1,6 @@
 mod frustum_culling;
 mod internal;
+mod occlusion_culling;
 mod polygon_soup;
 mod projection;
 mod view_frustum;
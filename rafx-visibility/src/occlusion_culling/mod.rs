@@ -0,0 +1,2 @@
+mod occlusion_buffer;
+pub(crate) use occlusion_buffer::{transformed_triangles, OcclusionBuffer};
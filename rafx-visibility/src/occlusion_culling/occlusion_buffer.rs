@@ -0,0 +1,262 @@
+use crate::geometry::{BoundingSphere, Transform};
+use crate::{OcclusionCullingConfig, PolygonSoup, PolygonSoupIndex, Projection, ViewFrustum};
+use glam::{Mat4, Vec3, Vec4};
+
+/// Returns the triangles of `polygons` after they are moved by `transform`.
+pub(crate) fn transformed_triangles(
+    polygons: &PolygonSoup,
+    transform: &Transform,
+) -> Vec<[Vec3; 3]> {
+    let vertex = |index: usize| {
+        transform.rotation * (polygons.vertex_positions[index] * transform.scale)
+            + transform.translation
+    };
+
+    let indices: Vec<usize> = match &polygons.index {
+        PolygonSoupIndex::None => (0..polygons.vertex_positions.len()).collect(),
+        PolygonSoupIndex::Indexed16(indices) => {
+            indices.iter().map(|index| *index as usize).collect()
+        }
+        PolygonSoupIndex::Indexed32(indices) => {
+            indices.iter().map(|index| *index as usize).collect()
+        }
+    };
+
+    indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            [
+                vertex(triangle[0]),
+                vertex(triangle[1]),
+                vertex(triangle[2]),
+            ]
+        })
+        .collect()
+}
+
+#[derive(Copy, Clone)]
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    // NOTE(dvd): Both are linear in screen space, so the view depth at a pixel is
+    // `depth_over_w / one_over_w`.
+    one_over_w: f32,
+    depth_over_w: f32,
+}
+
+/// A low resolution software depth buffer. Occluder triangles are rasterized into it, and a
+/// `BoundingSphere` is occluded if every pixel it could cover holds an occluder that is closer
+/// to the view. Depths are distances along the view direction.
+pub(crate) struct OcclusionBuffer {
+    width: usize,
+    height: usize,
+    depths: Vec<f32>,
+    view: Mat4,
+    view_projection: Mat4,
+    near_distance: f32,
+}
+
+impl OcclusionBuffer {
+    /// Returns `None` if the `ViewFrustum` has no projection.
+    pub fn new(
+        view_frustum: &ViewFrustum,
+        config: &OcclusionCullingConfig,
+    ) -> Option<Self> {
+        let (projection, near_distance) = match view_frustum.get_projection() {
+            Projection::Perspective(parameters) => (
+                Mat4::perspective_rh(
+                    parameters.fov_y_radians(),
+                    parameters.ratio(),
+                    parameters.near_distance(),
+                    parameters.far_distance(),
+                ),
+                parameters.near_distance(),
+            ),
+            Projection::Orthographic(parameters) => (
+                Mat4::orthographic_rh(
+                    parameters.left(),
+                    parameters.right(),
+                    parameters.bottom(),
+                    parameters.top(),
+                    parameters.near_distance(),
+                    parameters.far_distance(),
+                ),
+                parameters.near_distance(),
+            ),
+            Projection::Undefined => return None,
+        };
+
+        let view = Mat4::look_at_rh(
+            view_frustum.eye_position(),
+            view_frustum.look_at(),
+            view_frustum.up(),
+        );
+
+        let width = config.buffer_width.max(1) as usize;
+        let height = config.buffer_height.max(1) as usize;
+
+        Some(OcclusionBuffer {
+            width,
+            height,
+            depths: vec![f32::INFINITY; width * height],
+            view,
+            view_projection: projection * view,
+            near_distance,
+        })
+    }
+
+    pub fn rasterize_triangle(
+        &mut self,
+        triangle: &[Vec3; 3],
+    ) {
+        // NOTE(dvd): Clip against the near plane in view space so that the perspective divide is safe.
+        let mut polygon = [Vec3::ZERO; 4];
+        let mut polygon_len = 0;
+        for (index, current) in triangle.iter().copied().enumerate() {
+            let next = triangle[(index + 1) % 3];
+            let current_distance = self.view_depth(current) - self.near_distance;
+            let next_distance = self.view_depth(next) - self.near_distance;
+
+            if current_distance >= 0. {
+                polygon[polygon_len] = current;
+                polygon_len += 1;
+            }
+
+            if (current_distance >= 0.) != (next_distance >= 0.) {
+                let t = current_distance / (current_distance - next_distance);
+                polygon[polygon_len] = current + (next - current) * t;
+                polygon_len += 1;
+            }
+        }
+
+        if polygon_len < 3 {
+            return;
+        }
+
+        let a = self.to_screen(polygon[0]);
+        for edge in polygon[1..polygon_len].windows(2) {
+            let b = self.to_screen(edge[0]);
+            let c = self.to_screen(edge[1]);
+            self.rasterize_screen_triangle(&a, &b, &c);
+        }
+    }
+
+    pub fn is_occluded(
+        &self,
+        sphere: &BoundingSphere,
+    ) -> bool {
+        let center_depth = self.view_depth(sphere.position);
+        let nearest_depth = center_depth - sphere.radius;
+        if nearest_depth <= self.near_distance {
+            return false;
+        }
+
+        // NOTE(dvd): The screen bounds of the box around the sphere contain the sphere's projection.
+        let mut min = (f32::MAX, f32::MAX);
+        let mut max = (f32::MIN, f32::MIN);
+        for corner in 0..8 {
+            let offset = Vec3::new(
+                if corner & 1 != 0 { 1. } else { -1. },
+                if corner & 2 != 0 { 1. } else { -1. },
+                if corner & 4 != 0 { 1. } else { -1. },
+            ) * sphere.radius;
+            let corner = sphere.position + offset;
+            if self.view_depth(corner) <= self.near_distance {
+                // NOTE(dvd): The box crosses the near plane, so its projection is unbounded.
+                return false;
+            }
+
+            let screen = self.to_screen(corner);
+            min = (min.0.min(screen.x), min.1.min(screen.y));
+            max = (max.0.max(screen.x), max.1.max(screen.y));
+        }
+
+        let min_x = (min.0.floor().max(0.) as usize).min(self.width);
+        let min_y = (min.1.floor().max(0.) as usize).min(self.height);
+        let max_x = (max.0.ceil().max(0.) as usize).min(self.width);
+        let max_y = (max.1.ceil().max(0.) as usize).min(self.height);
+        if min_x >= max_x || min_y >= max_y {
+            return false;
+        }
+
+        for y in min_y..max_y {
+            let row = &self.depths[y * self.width..(y + 1) * self.width];
+            if row[min_x..max_x]
+                .iter()
+                .any(|depth| *depth >= nearest_depth)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn view_depth(
+        &self,
+        position: Vec3,
+    ) -> f32 {
+        // NOTE(dvd): The view looks down -Z.
+        -self.view.transform_point3(position).z
+    }
+
+    fn to_screen(
+        &self,
+        position: Vec3,
+    ) -> ScreenVertex {
+        let clip = self.view_projection * Vec4::new(position.x, position.y, position.z, 1.);
+        let one_over_w = 1. / clip.w;
+        ScreenVertex {
+            x: (clip.x * one_over_w * 0.5 + 0.5) * self.width as f32,
+            y: (0.5 - clip.y * one_over_w * 0.5) * self.height as f32,
+            one_over_w,
+            depth_over_w: self.view_depth(position) * one_over_w,
+        }
+    }
+
+    fn rasterize_screen_triangle(
+        &mut self,
+        a: &ScreenVertex,
+        b: &ScreenVertex,
+        c: &ScreenVertex,
+    ) {
+        let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+
+        let min_x = (a.x.min(b.x).min(c.x).floor().max(0.) as usize).min(self.width);
+        let min_y = (a.y.min(b.y).min(c.y).floor().max(0.) as usize).min(self.height);
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.) as usize).min(self.width);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.) as usize).min(self.height);
+
+        let edge = |from: &ScreenVertex, to: &ScreenVertex, x: f32, y: f32| {
+            (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x)
+        };
+
+        // NOTE(dvd): Occluders are double sided, so accept either winding.
+        let inverse_area = 1. / area;
+        for y in min_y..max_y {
+            let pixel_y = y as f32 + 0.5;
+            for x in min_x..max_x {
+                let pixel_x = x as f32 + 0.5;
+                let weight_a = edge(b, c, pixel_x, pixel_y) * inverse_area;
+                let weight_b = edge(c, a, pixel_x, pixel_y) * inverse_area;
+                let weight_c = edge(a, b, pixel_x, pixel_y) * inverse_area;
+                if weight_a < 0. || weight_b < 0. || weight_c < 0. {
+                    continue;
+                }
+
+                let one_over_w =
+                    weight_a * a.one_over_w + weight_b * b.one_over_w + weight_c * c.one_over_w;
+                let depth_over_w = weight_a * a.depth_over_w
+                    + weight_b * b.depth_over_w
+                    + weight_c * c.depth_over_w;
+                let depth = depth_over_w / one_over_w;
+
+                let pixel = &mut self.depths[y * self.width + x];
+                *pixel = pixel.min(depth);
+            }
+        }
+    }
+}
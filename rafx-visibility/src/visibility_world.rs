@@ -1,11 +1,17 @@
-use crate::frustum_culling::{collect_visible_objects, PackedBoundingSphereChunk};
+use crate::frustum_culling::{
+    collect_shadow_casters, collect_visible_objects, cull_oriented_boxes, portal_frustum,
+    shadow_caster_volume, LooseOctree, PackedBoundingSphereChunk, PortalProjection,
+    ShadowCasterLight,
+};
 use crate::geometry::{BoundingSphere, Transform};
-use crate::internal::VisibilityWorld;
+use crate::internal::{VisibilityWorld, ZoneOccluder, ZoneOrientedBox, ZoneVolume};
+use crate::occlusion_culling::OcclusionBuffer;
 use crate::{Projection, ViewFrustum};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use glam::Vec3;
 use parking_lot::{Mutex, MutexGuard, RwLock};
-use slotmap::new_key_type;
+use rustc_hash::FxHashSet;
+use slotmap::{new_key_type, SecondaryMap};
 use std::hash::Hash;
 use std::sync::Arc;
 
@@ -37,9 +43,57 @@ pub enum AsyncCommand {
     DestroyZone(ZoneHandle),
     DestroyObject(ObjectHandle),
     DestroyModel(ModelHandle),
+    SetZoneAcceleration(ZoneHandle, ZoneAcceleration),
+    SetVolumePosition(VolumeHandle, Transform),
+    SetVolumeZone(VolumeHandle, Option<ZoneHandle>),
+    SetVolumeId(VolumeHandle, u64),
+    SetVolumeModel(VolumeHandle, Option<ModelHandle>),
+    SetVolumePortal(VolumeHandle, Option<Portal>),
+    DestroyVolume(VolumeHandle),
+    SetViewFrustumOcclusion(ViewFrustumHandle, Option<OcclusionCullingConfig>),
+    SetObjectOccluder(ObjectHandle, bool),
     QueuedCommands(Vec<AsyncCommand>),
 }
 
+/// Settings for the occlusion culling of a `ViewFrustum`. The depth buffer is `buffer_width` by
+/// `buffer_height` pixels and covers the whole view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OcclusionCullingConfig {
+    pub buffer_width: u32,
+    pub buffer_height: u32,
+}
+
+impl Default for OcclusionCullingConfig {
+    fn default() -> Self {
+        OcclusionCullingConfig {
+            buffer_width: 256,
+            buffer_height: 128,
+        }
+    }
+}
+
+/// A convex polygon that connects the `Zone` of a `Volume` to the `target_zone`. The `polygon` is
+/// relative to the `Volume`'s position, and the vertices are in order around its edge.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Portal {
+    pub target_zone: ZoneHandle,
+    pub polygon: Vec<Vec3>,
+}
+
+// NOTE(dvd): Limits how many portals a query may pass through in a row.
+const MAX_PORTAL_DEPTH: usize = 8;
+
+/// How a `Zone` organizes its `Objects` for visibility queries.
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub enum ZoneAcceleration {
+    /// Every `Object` in the `Zone` is tested against the `ViewFrustum`. This is the default.
+    #[default]
+    LinearScan,
+    /// `Objects` are sorted into a loose octree and only the nodes that intersect the `ViewFrustum`
+    /// are visited. `min_node_size` is the edge length of the smallest node.
+    LooseOctree { min_node_size: f32 },
+}
+
 #[derive(Copy, Clone, Default)]
 pub struct VisibilityResult<T> {
     pub handle: T,
@@ -64,10 +118,26 @@ impl<T> VisibilityResult<T> {
     }
 }
 
+/// Counts of the work done by visibility queries. Each query adds to the counts.
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct VisibilityStatistics {
+    /// `Objects` that passed the frustum test.
+    pub objects_in_frustum: usize,
+    /// `Objects` that passed the `BoundingSphere` test but whose `OrientedBoundingBox` did not.
+    pub objects_culled_by_oriented_box: usize,
+    /// `Objects` that passed the frustum test but were hidden by an occluder.
+    pub objects_occluded: usize,
+    /// Occluder `Objects` that were rasterized into the depth buffer.
+    pub occluders_rasterized: usize,
+    /// Triangles of the occluders that were rasterized.
+    pub occluder_triangles_rasterized: usize,
+}
+
 #[derive(Default)]
 pub struct VisibilityQuery {
     pub objects: VisibleObjects,
     pub volumes: VisibleVolumes,
+    pub statistics: VisibilityStatistics,
 }
 
 #[derive(Clone)]
@@ -79,6 +149,7 @@ pub struct VisibilityWorldArc {
 
 pub enum QueryError {
     NoViewFrustumZone,
+    UndefinedProjection,
 }
 
 impl VisibilityWorldArc {
@@ -172,6 +243,33 @@ impl VisibilityWorldArc {
             AsyncCommand::DestroyModel(model) => {
                 inner.destroy_model(model);
             }
+            AsyncCommand::SetZoneAcceleration(zone, acceleration) => {
+                inner.set_zone_acceleration(zone, acceleration);
+            }
+            AsyncCommand::SetVolumePosition(volume, transform) => {
+                inner.set_volume_position(volume, transform);
+            }
+            AsyncCommand::SetVolumeZone(volume, zone) => {
+                inner.set_volume_zone(volume, zone);
+            }
+            AsyncCommand::SetVolumeId(volume, id) => {
+                inner.set_volume_id(volume, id);
+            }
+            AsyncCommand::SetVolumeModel(volume, model) => {
+                inner.set_volume_model(volume, model);
+            }
+            AsyncCommand::SetVolumePortal(volume, portal) => {
+                inner.set_volume_portal(volume, portal);
+            }
+            AsyncCommand::DestroyVolume(volume) => {
+                inner.destroy_volume(volume);
+            }
+            AsyncCommand::SetViewFrustumOcclusion(view_frustum, config) => {
+                inner.set_view_frustum_occlusion(view_frustum, config);
+            }
+            AsyncCommand::SetObjectOccluder(object, is_occluder) => {
+                inner.set_object_occluder(object, is_occluder);
+            }
             AsyncCommand::QueuedCommands(commands) => {
                 for inner_command in commands {
                     VisibilityWorldArc::handle_command(inner, inner_command);
@@ -180,7 +278,10 @@ impl VisibilityWorldArc {
         }
     }
 
-    /// Queries visibility for a `ViewFrustum`. The `result` is a `VisibilityQuery`. This function is thread-safe.
+    /// Queries visibility for a `ViewFrustum`. The `result` is a `VisibilityQuery`. `Objects` and
+    /// `Volumes` in other `Zones` are visible through the `Portals` in the `ViewFrustum`'s `Zone`.
+    /// If the `ViewFrustum` has occlusion culling, `Objects` hidden by occluders are removed.
+    /// This function is thread-safe.
     #[profiling::function]
     pub fn query_visibility(
         &self,
@@ -200,11 +301,12 @@ impl VisibilityWorldArc {
             }?;
 
             let active_view_frustum = inner.view_frustums.get(view_frustum).unwrap().clone();
-            let chunks = inner.zones.get(zone).unwrap().chunks.clone();
+            let occlusion = inner.view_frustum_occlusion.get(view_frustum).copied();
 
             Ok(QueryWork {
                 active_view_frustum,
-                chunks,
+                zone,
+                occlusion,
             })
         }?;
 
@@ -212,32 +314,799 @@ impl VisibilityWorldArc {
 
         let active_view_frustum = work.active_view_frustum.write();
         let view_frustum_position = active_view_frustum.eye_position();
+        let portal_projection = match active_view_frustum.get_projection() {
+            Projection::Perspective(_) => PortalProjection::Perspective {
+                eye_position: view_frustum_position,
+            },
+            Projection::Orthographic(_) => PortalProjection::Orthographic {
+                view_direction: (active_view_frustum.look_at() - view_frustum_position).normalize(),
+            },
+            Projection::Undefined => return Err(QueryError::UndefinedProjection),
+        };
         let frustum = active_view_frustum.acquire_frustum().clone();
+        let far_plane = frustum.planes[ViewFrustum::FAR];
+        let mut occlusion_buffer = work
+            .occlusion
+            .and_then(|config| OcclusionBuffer::new(&active_view_frustum, &config));
+        let mut occluders_in_view = FxHashSet::default();
+
+        let first_object = result.objects.len();
+        let first_volume = result.volumes.len();
+        let mut visited_zones = FxHashSet::default();
+        let mut is_zone_visited_twice = false;
+
+        // NOTE(dvd): Each zone is queried with the part of the frustum that can see into it. The
+        // path is the zones that were passed through to get there.
+        let mut pending_zones = vec![(work.zone, frustum, vec![work.zone])];
+        while let Some((zone, frustum, path)) = pending_zones.pop() {
+            let zone_work = match self.zone_query_work(zone) {
+                Some(zone_work) => zone_work,
+                None => continue,
+            };
+
+            is_zone_visited_twice |= !visited_zones.insert(zone);
+            let zone_first_object = result.objects.len();
+
+            // NOTE(dvd): Descend a read-only view of the octree, or iterate through a read-only view
+            // of the chunks in the zone.
+
+            if let Some(octree) = &zone_work.octree {
+                octree.read().collect_objects(
+                    view_frustum_position,
+                    &frustum,
+                    |_| true,
+                    &mut result.objects,
+                );
+            } else {
+                let chunks = zone_work.chunks.read();
+                for chunk in chunks.iter() {
+                    collect_visible_objects(
+                        chunk,
+                        view_frustum_position,
+                        &frustum,
+                        &mut result.objects,
+                    )
+                }
+            }
+
+            // NOTE(dvd): Objects that passed the sphere test may have a box outside of the frustum.
+
+            result.statistics.objects_culled_by_oriented_box += cull_oriented_boxes(
+                &mut result.objects,
+                zone_first_object,
+                &zone_work.oriented_boxes.read(),
+                |oriented_box| {
+                    frustum.contains_box_slow(oriented_box.center, &oriented_box.half_axes)
+                },
+            );
+
+            // NOTE(dvd): Rasterize the occluders in view. Objects are tested once every zone is done.
+
+            if let Some(occlusion_buffer) = &mut occlusion_buffer {
+                let occluders = zone_work.occluders.read();
+                for (handle, occluder) in occluders.iter() {
+                    if !frustum.contains_sphere_slow(&occluder.bounding_sphere) {
+                        continue;
+                    }
+
+                    for triangle in &occluder.triangles {
+                        occlusion_buffer.rasterize_triangle(triangle);
+                    }
+
+                    occluders_in_view.insert(handle);
+                    result.statistics.occluders_rasterized += 1;
+                    result.statistics.occluder_triangles_rasterized += occluder.triangles.len();
+                }
+            }
+
+            // NOTE(dvd): Report the visible volumes, and look through the ones that are portals.
+
+            let volumes = zone_work.volumes.read();
+            for volume in volumes.values() {
+                if !frustum.contains_sphere_slow(&volume.bounding_sphere) {
+                    continue;
+                }
+
+                result.volumes.push(VisibilityResult::new(
+                    volume.handle,
+                    volume.id,
+                    view_frustum_position,
+                    volume.bounding_sphere,
+                ));
+
+                let target_zone = match volume.portal_target {
+                    Some(target_zone)
+                        if path.len() <= MAX_PORTAL_DEPTH && !path.contains(&target_zone) =>
+                    {
+                        target_zone
+                    }
+                    _ => continue,
+                };
+
+                if let Some(portal_frustum) = portal_frustum(
+                    &frustum,
+                    far_plane,
+                    &portal_projection,
+                    &volume.portal_polygon,
+                ) {
+                    let mut portal_path = path.clone();
+                    portal_path.push(target_zone);
+                    pending_zones.push((target_zone, portal_frustum, portal_path));
+                }
+            }
+        }
+
+        if is_zone_visited_twice {
+            // NOTE(dvd): A zone seen through more than one portal reports its contents more than once.
+            let mut visible_objects = FxHashSet::default();
+            let objects = result.objects.split_off(first_object);
+            result.objects.extend(
+                objects
+                    .into_iter()
+                    .filter(|object| visible_objects.insert(object.handle)),
+            );
 
-        // NOTE(dvd): Iterate through a read-only view of the chunks in the zone.
+            let mut visible_volumes = FxHashSet::default();
+            let volumes = result.volumes.split_off(first_volume);
+            result.volumes.extend(
+                volumes
+                    .into_iter()
+                    .filter(|volume| visible_volumes.insert(volume.handle)),
+            );
+        }
+
+        let objects_in_frustum = result.objects.len() - first_object;
+        result.statistics.objects_in_frustum += objects_in_frustum;
 
-        let zone = work.chunks.read();
-        for chunk in zone.iter() {
-            collect_visible_objects(chunk, view_frustum_position, &frustum, &mut result.objects)
+        if let Some(occlusion_buffer) = &occlusion_buffer {
+            // NOTE(dvd): An occluder would hide itself, so occluders are only frustum culled.
+            let objects = result.objects.split_off(first_object);
+            result.objects.extend(objects.into_iter().filter(|object| {
+                occluders_in_view.contains(&object.handle)
+                    || !occlusion_buffer.is_occluded(&object.bounding_sphere)
+            }));
+            result.statistics.objects_occluded +=
+                objects_in_frustum - (result.objects.len() - first_object);
         }
 
         Ok(())
     }
 
+    fn zone_query_work(
+        &self,
+        zone: ZoneHandle,
+    ) -> Option<ZoneQueryWork> {
+        let inner = self.inner.lock();
+        inner.zones.get(zone).map(|zone| ZoneQueryWork {
+            chunks: zone.chunks.clone(),
+            octree: zone.octree.clone(),
+            volumes: zone.volumes.clone(),
+            occluders: zone.occluders.clone(),
+            oriented_boxes: zone.oriented_boxes.clone(),
+        })
+    }
+
     /// Queries shadow casters for a `ViewFrustum` representing a light. The `result` is a `VisibilityQuery`.
     /// The objects in `result` are able to cast shadows into at least one of the `shadowed` frustums.
+    /// A light with an orthographic projection is treated as a directional light, and a light with a
+    /// perspective projection is treated as a spot light (or one face of a point light).
     /// This function is thread-safe.
+    #[profiling::function]
     pub fn query_shadow_casters(
         &self,
-        _light: ViewFrustumHandle,
-        _shadowed: &[ViewFrustumHandle],
-        _result: &mut VisibilityQuery,
-    ) {
-        unimplemented!();
+        light: ViewFrustumHandle,
+        shadowed: &[ViewFrustumHandle],
+        result: &mut VisibilityQuery,
+    ) -> Result<(), QueryError> {
+        let work = {
+            let inner = self.inner.lock();
+
+            let zone = {
+                let view_frustum_zone = inner.view_frustum_zones.get(light);
+                if let Some(zone) = view_frustum_zone {
+                    Ok(*zone)
+                } else {
+                    return Err(QueryError::NoViewFrustumZone);
+                }
+            }?;
+
+            let light_view_frustum = inner.view_frustums.get(light).unwrap().clone();
+            let shadowed_view_frustums = shadowed
+                .iter()
+                .map(|handle| inner.view_frustums.get(*handle).unwrap().clone())
+                .collect();
+            let zone = inner.zones.get(zone).unwrap();
+
+            Ok(ShadowCasterQueryWork {
+                light_view_frustum,
+                shadowed_view_frustums,
+                chunks: zone.chunks.clone(),
+                octree: zone.octree.clone(),
+                oriented_boxes: zone.oriented_boxes.clone(),
+            })
+        }?;
+
+        // NOTE(dvd): Acquire exclusive lock on the light's view frustum.
+
+        let (light_position, shadow_caster_light, light_frustum) = {
+            let light_view_frustum = work.light_view_frustum.write();
+            let light_position = light_view_frustum.eye_position();
+            let shadow_caster_light = match light_view_frustum.get_projection() {
+                Projection::Perspective(_) => ShadowCasterLight::Positional {
+                    position: light_position,
+                },
+                Projection::Orthographic(_) => ShadowCasterLight::Directional {
+                    direction_to_light: (light_position - light_view_frustum.look_at()).normalize(),
+                },
+                Projection::Undefined => return Err(QueryError::UndefinedProjection),
+            };
+
+            let light_frustum = light_view_frustum.acquire_frustum().clone();
+            (light_position, shadow_caster_light, light_frustum)
+        };
+
+        // NOTE(dvd): Extrude each shadowed frustum toward the light. The locks are taken one at a
+        // time so that a handle may appear more than once (or be the light itself).
+
+        let mut caster_volumes = Vec::with_capacity(work.shadowed_view_frustums.len());
+        for shadowed_view_frustum in &work.shadowed_view_frustums {
+            let shadowed_view_frustum = shadowed_view_frustum.write();
+            if let Projection::Undefined = shadowed_view_frustum.get_projection() {
+                return Err(QueryError::UndefinedProjection);
+            }
+
+            let frustum = shadowed_view_frustum.acquire_frustum();
+            caster_volumes.push(shadow_caster_volume(&frustum, &shadow_caster_light));
+        }
+
+        if caster_volumes.is_empty() {
+            return Ok(());
+        }
+
+        // NOTE(dvd): Descend a read-only view of the octree, or iterate through a read-only view
+        // of the chunks in the zone.
+
+        let first_object = result.objects.len();
+        if let Some(octree) = &work.octree {
+            octree.read().collect_objects(
+                light_position,
+                &light_frustum,
+                |sphere| {
+                    caster_volumes
+                        .iter()
+                        .any(|volume| volume.contains_sphere_slow(sphere))
+                },
+                &mut result.objects,
+            );
+        } else {
+            let zone = work.chunks.read();
+            for chunk in zone.iter() {
+                collect_shadow_casters(
+                    chunk,
+                    light_position,
+                    &light_frustum,
+                    &caster_volumes,
+                    &mut result.objects,
+                )
+            }
+        }
+
+        result.statistics.objects_culled_by_oriented_box += cull_oriented_boxes(
+            &mut result.objects,
+            first_object,
+            &work.oriented_boxes.read(),
+            |oriented_box| {
+                let (center, half_axes) = (oriented_box.center, &oriented_box.half_axes);
+                light_frustum.contains_box_slow(center, half_axes)
+                    && caster_volumes
+                        .iter()
+                        .any(|volume| volume.contains_box_slow(center, half_axes))
+            },
+        );
+
+        Ok(())
     }
 }
 
 struct QueryWork {
     pub active_view_frustum: Arc<RwLock<ViewFrustum>>,
+    pub zone: ZoneHandle,
+    pub occlusion: Option<OcclusionCullingConfig>,
+}
+
+struct ZoneQueryWork {
+    pub chunks: Arc<RwLock<Vec<PackedBoundingSphereChunk>>>,
+    pub octree: Option<Arc<RwLock<LooseOctree>>>,
+    pub volumes: Arc<RwLock<SecondaryMap<VolumeHandle, ZoneVolume>>>,
+    pub occluders: Arc<RwLock<SecondaryMap<ObjectHandle, ZoneOccluder>>>,
+    pub oriented_boxes: Arc<RwLock<SecondaryMap<ObjectHandle, ZoneOrientedBox>>>,
+}
+
+struct ShadowCasterQueryWork {
+    pub light_view_frustum: Arc<RwLock<ViewFrustum>>,
+    pub shadowed_view_frustums: Vec<Arc<RwLock<ViewFrustum>>>,
     pub chunks: Arc<RwLock<Vec<PackedBoundingSphereChunk>>>,
+    pub octree: Option<Arc<RwLock<LooseOctree>>>,
+    pub oriented_boxes: Arc<RwLock<SecondaryMap<ObjectHandle, ZoneOrientedBox>>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CullShape, DepthRange, PolygonSoup, PolygonSoupIndex, VisibleBounds};
+    use glam::Quat;
+
+    struct TestScene {
+        world: VisibilityWorldArc,
+        zone: ZoneHandle,
+        camera: ViewFrustumHandle,
+    }
+
+    impl TestScene {
+        // NOTE(dvd): The camera looks down -Z from (0, 0, 10) and sees a 90 degree frustum
+        // that ends at z = -10.
+        fn new() -> Self {
+            let world = VisibilityWorldArc::new();
+            let (zone, camera) = {
+                let mut inner = world.inner.lock();
+                let zone = inner.new_zone();
+                let camera = inner.new_view_frustum();
+                inner.set_view_frustum_perspective(
+                    camera,
+                    std::f32::consts::FRAC_PI_2,
+                    1.,
+                    0.1,
+                    20.,
+                    DepthRange::Normal,
+                );
+                inner.set_view_frustum_transforms(
+                    camera,
+                    Vec3::new(0., 0., 10.),
+                    Vec3::ZERO,
+                    Vec3::Y,
+                );
+                inner.set_view_frustum_zone(camera, Some(zone));
+                (zone, camera)
+            };
+
+            TestScene {
+                world,
+                zone,
+                camera,
+            }
+        }
+
+        fn add_object(
+            &self,
+            id: u64,
+            translation: Vec3,
+        ) -> ObjectHandle {
+            self.add_object_to_zone(id, translation, self.zone)
+        }
+
+        fn add_object_to_zone(
+            &self,
+            id: u64,
+            translation: Vec3,
+            zone: ZoneHandle,
+        ) -> ObjectHandle {
+            let mut inner = self.world.inner.lock();
+            let object = inner.new_object();
+            inner.set_object_id(object, id);
+            inner.set_object_position(
+                object,
+                Transform {
+                    translation,
+                    ..Default::default()
+                },
+            );
+            inner.set_object_zone(object, Some(zone));
+            object
+        }
+
+        fn add_volume_to_zone(
+            &self,
+            id: u64,
+            translation: Vec3,
+            zone: ZoneHandle,
+            portal: Option<Portal>,
+        ) -> VolumeHandle {
+            let mut inner = self.world.inner.lock();
+            let volume = inner.new_volume();
+            inner.set_volume_id(volume, id);
+            inner.set_volume_position(
+                volume,
+                Transform {
+                    translation,
+                    ..Default::default()
+                },
+            );
+            inner.set_volume_portal(volume, portal);
+            inner.set_volume_zone(volume, Some(zone));
+            volume
+        }
+
+        fn add_perspective_light(
+            &self,
+            eye_position: Vec3,
+            look_at: Vec3,
+            up: Vec3,
+        ) -> ViewFrustumHandle {
+            let mut inner = self.world.inner.lock();
+            let light = inner.new_view_frustum();
+            inner.set_view_frustum_perspective(
+                light,
+                std::f32::consts::FRAC_PI_2,
+                1.,
+                0.1,
+                100.,
+                DepthRange::Normal,
+            );
+            inner.set_view_frustum_transforms(light, eye_position, look_at, up);
+            inner.set_view_frustum_zone(light, Some(self.zone));
+            light
+        }
+
+        fn query_shadow_casters(
+            &self,
+            light: ViewFrustumHandle,
+        ) -> Vec<u64> {
+            let mut result = VisibilityQuery::default();
+            assert!(self
+                .world
+                .query_shadow_casters(light, &[self.camera], &mut result)
+                .is_ok());
+
+            let mut ids: Vec<u64> = result.objects.iter().map(|object| object.id).collect();
+            ids.sort();
+            ids
+        }
+
+        fn query_visibility(&self) -> Vec<u64> {
+            self.query_visibility_with_volumes().0
+        }
+
+        fn query_visibility_with_volumes(&self) -> (Vec<u64>, Vec<u64>) {
+            let mut result = VisibilityQuery::default();
+            assert!(self
+                .world
+                .query_visibility(self.camera, &mut result)
+                .is_ok());
+
+            let mut object_ids: Vec<u64> = result.objects.iter().map(|object| object.id).collect();
+            object_ids.sort();
+
+            let mut volume_ids: Vec<u64> = result.volumes.iter().map(|volume| volume.id).collect();
+            volume_ids.sort();
+
+            (object_ids, volume_ids)
+        }
+
+        fn set_zone_acceleration(
+            &self,
+            acceleration: ZoneAcceleration,
+        ) {
+            self.world
+                .inner
+                .lock()
+                .set_zone_acceleration(self.zone, acceleration);
+        }
+    }
+
+    #[test]
+    fn test_shadow_casters_directional_light() {
+        let scene = TestScene::new();
+        // Above the camera's view, between it and the light.
+        scene.add_object(1, Vec3::new(0., 30., 0.));
+        // Below the camera's view, so its shadow falls away from the view.
+        scene.add_object(2, Vec3::new(0., -40., 0.));
+        // Beside the camera's view.
+        scene.add_object(3, Vec3::new(60., 0., 0.));
+        // Above the camera's view, but outside of the light's frustum.
+        scene.add_object(4, Vec3::new(8., 30., 0.));
+
+        let light = {
+            let mut inner = scene.world.inner.lock();
+            let light = inner.new_view_frustum();
+            inner.set_view_frustum_orthographic(
+                light,
+                -5.,
+                5.,
+                -100.,
+                100.,
+                0.1,
+                100.,
+                DepthRange::Normal,
+            );
+            inner.set_view_frustum_transforms(light, Vec3::new(0., 50., 0.), Vec3::ZERO, -Vec3::Z);
+            inner.set_view_frustum_zone(light, Some(scene.zone));
+            light
+        };
+
+        assert_eq!(scene.query_shadow_casters(light), vec![1]);
+    }
+
+    #[test]
+    fn test_shadow_casters_spot_light() {
+        let scene = TestScene::new();
+        // Between the light and the camera's view.
+        scene.add_object(1, Vec3::new(0., 15., 0.));
+        // Below the camera's view, so its shadow falls away from the view.
+        scene.add_object(2, Vec3::new(0., -40., 0.));
+        // Behind the light.
+        scene.add_object(3, Vec3::new(0., 40., 0.));
+        // Inside of the light's frustum, but beside the camera's view.
+        scene.add_object(4, Vec3::new(25., 5., 0.));
+
+        let light = scene.add_perspective_light(Vec3::new(0., 30., 0.), Vec3::ZERO, -Vec3::Z);
+
+        assert_eq!(scene.query_shadow_casters(light), vec![1]);
+    }
+
+    #[test]
+    fn test_shadow_casters_point_light() {
+        let scene = TestScene::new();
+        // Between the light and the camera's view.
+        scene.add_object(1, Vec3::new(0., 20., 0.));
+        // Above the light.
+        scene.add_object(2, Vec3::new(0., 40., 0.));
+        // Beside the light.
+        scene.add_object(3, Vec3::new(30., 30., 0.));
+        // Inside of the camera's view, below the light.
+        scene.add_object(4, Vec3::new(0., -5., -5.));
+
+        // NOTE(dvd): A point light is six 90 degree perspective frustums, one per cube face.
+        let position = Vec3::new(0., 30., 0.);
+        let faces = [
+            (Vec3::X, Vec3::Y),
+            (-Vec3::X, Vec3::Y),
+            (Vec3::Y, -Vec3::Z),
+            (-Vec3::Y, Vec3::Z),
+            (Vec3::Z, Vec3::Y),
+            (-Vec3::Z, Vec3::Y),
+        ];
+
+        let mut ids = Vec::new();
+        for (direction, up) in faces.iter() {
+            let light = scene.add_perspective_light(position, position + *direction, *up);
+            ids.extend(scene.query_shadow_casters(light));
+        }
+
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_loose_octree_matches_linear_scan() {
+        let scene = TestScene::new();
+
+        let mut objects = Vec::new();
+        for x in -8..8 {
+            for y in -8..8 {
+                for z in -8..8 {
+                    let id = objects.len() as u64;
+                    let translation = Vec3::new(x as f32, y as f32, z as f32) * 3.7;
+                    objects.push(scene.add_object(id, translation));
+                }
+            }
+        }
+
+        let expected = scene.query_visibility();
+        assert!(!expected.is_empty() && expected.len() < objects.len());
+
+        // NOTE(dvd): The octree is built from the objects that are already in the zone.
+        scene.set_zone_acceleration(ZoneAcceleration::LooseOctree { min_node_size: 4. });
+        assert_eq!(scene.query_visibility(), expected);
+
+        // NOTE(dvd): Move objects far enough that they change nodes, and remove some of them.
+        {
+            let mut inner = scene.world.inner.lock();
+            for (index, object) in objects.iter().enumerate() {
+                if index % 5 == 0 {
+                    inner.destroy_object(*object);
+                } else if index % 3 == 0 {
+                    let mut transform = inner.get_object_position(*object);
+                    transform.translation = transform.translation * -1.5 + Vec3::new(40., 0., 0.);
+                    transform.scale = Vec3::splat(2.);
+                    inner.set_object_position(*object, transform);
+                }
+            }
+        }
+
+        let octree_result = scene.query_visibility();
+        scene.set_zone_acceleration(ZoneAcceleration::LinearScan);
+        assert_eq!(octree_result, scene.query_visibility());
+    }
+
+    #[test]
+    fn test_portal_visibility() {
+        let scene = TestScene::new();
+        let (room, unconnected_room) = {
+            let mut inner = scene.world.inner.lock();
+            (inner.new_zone(), inner.new_zone())
+        };
+
+        // NOTE(dvd): A 4x4 doorway at z = 0, directly in front of the camera.
+        let doorway = vec![
+            Vec3::new(-2., -2., 0.),
+            Vec3::new(2., -2., 0.),
+            Vec3::new(2., 2., 0.),
+            Vec3::new(-2., 2., 0.),
+        ];
+        scene.add_volume_to_zone(
+            100,
+            Vec3::ZERO,
+            scene.zone,
+            Some(Portal {
+                target_zone: room,
+                polygon: doorway,
+            }),
+        );
+
+        // Seen through the doorway.
+        scene.add_object_to_zone(1, Vec3::new(0., 0., -5.), room);
+        scene.add_volume_to_zone(101, Vec3::new(0., 0., -6.), room, None);
+        // Inside of the camera's frustum, but hidden by the wall around the doorway.
+        scene.add_object_to_zone(2, Vec3::new(6., 0., -5.), room);
+        scene.add_volume_to_zone(102, Vec3::new(8., 0., -6.), room, None);
+        // Between the camera and the doorway, so it is not in the room that is seen.
+        scene.add_object_to_zone(3, Vec3::new(0., 0., 5.), room);
+        // In a zone that is not connected to the camera's zone.
+        scene.add_object_to_zone(4, Vec3::new(0., 0., -5.), unconnected_room);
+        // In the camera's zone.
+        scene.add_object(5, Vec3::new(6., 0., -5.));
+
+        let (objects, volumes) = scene.query_visibility_with_volumes();
+        assert_eq!(objects, vec![1, 5]);
+        assert_eq!(volumes, vec![100, 101]);
+
+        // NOTE(dvd): Looking away from the doorway hides the room.
+        scene.world.inner.lock().set_view_frustum_transforms(
+            scene.camera,
+            Vec3::new(0., 0., 10.),
+            Vec3::new(0., 0., 20.),
+            Vec3::Y,
+        );
+
+        let (objects, volumes) = scene.query_visibility_with_volumes();
+        assert!(objects.is_empty());
+        assert!(volumes.is_empty());
+    }
+
+    #[test]
+    fn test_occlusion_culling() {
+        let scene = TestScene::new();
+
+        // NOTE(dvd): An 8x8 wall at z = 0, directly in front of the camera.
+        let wall = scene.add_object(1, Vec3::ZERO);
+        {
+            let mut inner = scene.world.inner.lock();
+            let quad = inner.new_quad(8., 8.);
+            inner.set_object_cull_model(wall, Some(quad));
+            inner.set_object_occluder(wall, true);
+        }
+
+        // Behind the wall.
+        scene.add_object(2, Vec3::new(0., 0., -8.));
+        // Behind the wall's plane, but off to the side of it.
+        scene.add_object(3, Vec3::new(12., 0., -8.));
+        // Between the camera and the wall.
+        scene.add_object(4, Vec3::new(0., 0., 5.));
+
+        assert_eq!(scene.query_visibility(), vec![1, 2, 3, 4]);
+
+        scene
+            .world
+            .inner
+            .lock()
+            .set_view_frustum_occlusion(scene.camera, Some(OcclusionCullingConfig::default()));
+
+        let mut result = VisibilityQuery::default();
+        assert!(scene
+            .world
+            .query_visibility(scene.camera, &mut result)
+            .is_ok());
+
+        let mut ids: Vec<u64> = result.objects.iter().map(|object| object.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 3, 4]);
+        assert_eq!(result.statistics.objects_in_frustum, 4);
+        assert_eq!(result.statistics.objects_occluded, 1);
+        assert_eq!(result.statistics.occluders_rasterized, 1);
+        assert_eq!(result.statistics.occluder_triangles_rasterized, 2);
+
+        // NOTE(dvd): A wall that is not an occluder hides nothing.
+        scene.world.inner.lock().set_object_occluder(wall, false);
+        assert_eq!(scene.query_visibility(), vec![1, 2, 3, 4]);
+    }
+
+    // NOTE(dvd): The corners of a box with `half_extents` that is rotated by `rotation`.
+    fn box_polygons(
+        half_extents: Vec3,
+        rotation: Quat,
+    ) -> PolygonSoup {
+        let vertex_positions = (0..8)
+            .map(|corner| {
+                let sign = |bit: usize| if corner & bit != 0 { 1. } else { -1. };
+                rotation * (Vec3::new(sign(1), sign(2), sign(4)) * half_extents)
+            })
+            .collect();
+
+        PolygonSoup {
+            vertex_positions,
+            index: PolygonSoupIndex::None,
+        }
+    }
+
+    #[test]
+    fn test_visible_bounds_oriented_bounding_box() {
+        let rotation = Quat::from_rotation_z(std::f32::consts::FRAC_PI_4);
+        let rail = box_polygons(Vec3::new(10., 0.1, 0.1), rotation);
+        let bounds = VisibleBounds::from(rail.clone());
+
+        let aabb_extents = bounds.aabb.max - bounds.aabb.min;
+        assert!(aabb_extents.x * aabb_extents.y * aabb_extents.z > 10.);
+        assert!(bounds.obb.volume() < 1.);
+
+        let inverse_rotation = bounds.obb.rotation.conjugate();
+        for vertex in &rail.vertex_positions {
+            let vertex = inverse_rotation * *vertex;
+            assert!(vertex.cmpge(bounds.obb.min - Vec3::splat(0.001)).all());
+            assert!(vertex.cmple(bounds.obb.max + Vec3::splat(0.001)).all());
+        }
+    }
+
+    #[test]
+    fn test_oriented_box_culling() {
+        let scene = TestScene::new();
+        let (sphere_rail, oriented_rail) = {
+            let mut inner = scene.world.inner.lock();
+            let rail = box_polygons(Vec3::new(10., 0.1, 0.1), Quat::IDENTITY);
+            (
+                inner.new_model_with_cull_shape(rail.clone(), CullShape::BoundingSphere),
+                inner.new_model_with_cull_shape(rail, CullShape::OrientedBoundingBox),
+            )
+        };
+        assert_ne!(sphere_rail, oriented_rail);
+
+        // NOTE(dvd): Each rail runs along X above the camera's view. Its bounding sphere reaches
+        // into the view, but the rail does not.
+        let add_rail = |id: u64, model: ModelHandle, rotation: Quat| {
+            let object = scene.add_object(id, Vec3::new(0., 12., 0.));
+            let mut inner = scene.world.inner.lock();
+            inner.set_object_position(
+                object,
+                Transform {
+                    translation: Vec3::new(0., 12., 0.),
+                    rotation,
+                    ..Default::default()
+                },
+            );
+            inner.set_object_cull_model(object, Some(model));
+        };
+
+        add_rail(1, sphere_rail, Quat::IDENTITY);
+        add_rail(2, oriented_rail, Quat::IDENTITY);
+        // Turned to run along Y, down into the view.
+        add_rail(
+            3,
+            oriented_rail,
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        );
+
+        let mut result = VisibilityQuery::default();
+        assert!(scene
+            .world
+            .query_visibility(scene.camera, &mut result)
+            .is_ok());
+
+        let mut ids: Vec<u64> = result.objects.iter().map(|object| object.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 3]);
+        assert_eq!(result.statistics.objects_culled_by_oriented_box, 1);
+
+        scene.set_zone_acceleration(ZoneAcceleration::LooseOctree { min_node_size: 4. });
+        assert_eq!(scene.query_visibility(), vec![1, 3]);
+    }
 }
@@ -1,27 +1,56 @@
 use crate::geometry::{AxisAlignedBoundingBox, BoundingSphere, OrientedBoundingBox};
 use crate::PolygonSoup;
-use glam::Vec3;
+use glam::{Mat3, Quat, Vec3};
 use serde::Deserialize;
 use serde::Serialize;
 
+/// The shape used to cull `Objects` with a `Model`. Every `Object` is first tested with its
+/// `BoundingSphere`.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CullShape {
+    /// Only the `BoundingSphere` is tested. This is the default.
+    #[default]
+    BoundingSphere,
+    /// `Objects` that pass the `BoundingSphere` test are tested again with the `OrientedBoundingBox`.
+    /// This is tighter for long, thin models like rails, pipes, or bridges.
+    OrientedBoundingBox,
+}
+
 #[derive(Default, Copy, Clone, Serialize, Deserialize, Debug)]
 pub struct VisibleBounds {
     pub aabb: AxisAlignedBoundingBox,
     pub obb: OrientedBoundingBox,
     pub bounding_sphere: BoundingSphere,
+    #[serde(default)]
+    pub cull_shape: CullShape,
     pub hash: u64,
 }
 
 impl VisibleBounds {
     pub fn from(mesh_data: PolygonSoup) -> Self {
         let hash = mesh_data.calculate_hash();
-        VisibleBounds::new(hash, mesh_data)
+        VisibleBounds::new(hash, &mesh_data)
+    }
+
+    pub fn with_cull_shape(
+        mut self,
+        cull_shape: CullShape,
+    ) -> Self {
+        self.cull_shape = cull_shape;
+        self
     }
 
     pub(crate) fn new(
         hash: u64,
-        mesh_data: PolygonSoup,
+        mesh_data: &PolygonSoup,
     ) -> Self {
+        if mesh_data.vertex_positions.is_empty() {
+            return VisibleBounds {
+                hash,
+                ..Default::default()
+            };
+        }
+
         let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
         let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
 
@@ -39,13 +68,10 @@ impl VisibleBounds {
             min.z + (max.z - min.z) / 2.,
         );
 
-        let mut max_distance_squared = f32::MIN;
+        let mut max_distance_squared = 0.;
         for vertex in mesh_data.vertex_positions.iter() {
             let distance_squared = sphere_center.distance_squared(*vertex);
             max_distance_squared = f32::max(max_distance_squared, distance_squared);
-            if distance_squared > max_distance_squared {
-                max_distance_squared = distance_squared;
-            }
         }
 
         let sphere_radius = f32::sqrt(max_distance_squared);
@@ -57,7 +83,113 @@ impl VisibleBounds {
             hash,
             aabb,
             bounding_sphere,
-            obb: Default::default(), // TODO(dvd): Calculate an OBB.
+            obb: oriented_bounding_box(&mesh_data.vertex_positions, &aabb),
+            cull_shape: CullShape::default(),
+        }
+    }
+}
+
+// NOTE(dvd): The axes of the OBB are the principal axes of the vertices. If the vertices have no
+// clear principal axes (e.g. a cube), the AABB may be smaller, so the smaller of the two is used.
+fn oriented_bounding_box(
+    vertices: &[Vec3],
+    aabb: &AxisAlignedBoundingBox,
+) -> OrientedBoundingBox {
+    let mean = vertices
+        .iter()
+        .fold(Vec3::ZERO, |sum, vertex| sum + *vertex)
+        / vertices.len() as f32;
+
+    let mut covariance = [[0.; 3]; 3];
+    for vertex in vertices {
+        let offset = *vertex - mean;
+        let offset = [offset.x, offset.y, offset.z];
+        for (row, offset_row) in covariance.iter_mut().zip(offset.iter()) {
+            for (element, offset_column) in row.iter_mut().zip(offset.iter()) {
+                *element += offset_row * offset_column;
+            }
+        }
+    }
+
+    let [x_axis, y_axis, _] = principal_axes(covariance);
+    let rotation = Quat::from_rotation_mat3(&Mat3::from_cols(x_axis, y_axis, x_axis.cross(y_axis)))
+        .normalize();
+
+    let inverse_rotation = rotation.conjugate();
+    let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+    for vertex in vertices {
+        let vertex = inverse_rotation * *vertex;
+        min = Vec3::min(min, vertex);
+        max = Vec3::max(max, vertex);
+    }
+
+    let obb = OrientedBoundingBox { min, max, rotation };
+    let aabb = OrientedBoundingBox {
+        min: aabb.min,
+        max: aabb.max,
+        rotation: Quat::IDENTITY,
+    };
+
+    if obb.volume() < aabb.volume() {
+        obb
+    } else {
+        aabb
+    }
+}
+
+// NOTE(dvd): Returns the eigenvectors of the symmetric `matrix` using Jacobi rotations.
+fn principal_axes(mut matrix: [[f32; 3]; 3]) -> [Vec3; 3] {
+    let mut eigenvectors = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+    for _ in 0..16 {
+        let off_diagonal = matrix[0][1].abs() + matrix[0][2].abs() + matrix[1][2].abs();
+        if off_diagonal <= f32::EPSILON {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)].iter().copied() {
+            if matrix[p][q].abs() <= f32::EPSILON {
+                continue;
+            }
+
+            // NOTE(dvd): Choose the rotation that zeroes `matrix[p][q]`.
+            let theta = (matrix[q][q] - matrix[p][p]) / (2. * matrix[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+            let c = 1. / (t * t + 1.).sqrt();
+            let s = t * c;
+
+            let rotate = |a: f32, b: f32| (c * a - s * b, s * a + c * b);
+
+            for row in matrix.iter_mut() {
+                let (a, b) = rotate(row[p], row[q]);
+                row[p] = a;
+                row[q] = b;
+            }
+
+            let (row_p, row_q) = (matrix[p], matrix[q]);
+            for (column, (a, b)) in row_p.iter().zip(row_q.iter()).enumerate() {
+                let (a, b) = rotate(*a, *b);
+                matrix[p][column] = a;
+                matrix[q][column] = b;
+            }
+
+            for row in eigenvectors.iter_mut() {
+                let (a, b) = rotate(row[p], row[q]);
+                row[p] = a;
+                row[q] = b;
+            }
         }
     }
+
+    // NOTE(dvd): The eigenvectors are the columns.
+    let column = |index: usize| {
+        Vec3::new(
+            eigenvectors[0][index],
+            eigenvectors[1][index],
+            eigenvectors[2][index],
+        )
+    };
+
+    [column(0), column(1), column(2)]
 }
@@ -432,7 +432,7 @@ fn run() -> RafxResult<()> {
                 RenderJobWriteContext::new(resource_context.clone(), &render_resources);
 
             let prepared_render_data =
-                PreparedRenderData::new(&submit_node_blocks, Vec::default(), write_context);
+                PreparedRenderData::new(&submit_node_blocks, Vec::default(), write_context, None);
 
             //
             // Execute the graph. This will write out command buffer(s). This demo doesn't use the
@@ -444,8 +444,16 @@ fn run() -> RafxResult<()> {
             //
             // Submit the command buffers to the GPU
             //
-            let refs: Vec<&RafxCommandBuffer> = command_buffers.iter().map(|x| &**x).collect();
-            presentable_frame.present(&graphics_queue, &refs)?;
+            let refs: Vec<&RafxCommandBuffer> = command_buffers.iter().map(|(x, _)| &**x).collect();
+            let wait_semaphores: Vec<&RafxSemaphore> = command_buffers
+                .iter()
+                .flat_map(|(_, x)| x.iter().map(|x| &**x))
+                .collect();
+            presentable_frame.present_with_wait_semaphores(
+                &graphics_queue,
+                &refs,
+                &wait_semaphores,
+            )?;
         }
 
         // Wait for all GPU work to complete before destroying resources it is using